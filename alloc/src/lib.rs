@@ -0,0 +1,282 @@
+//! `libtock_alloc` provides a global memory allocator for Tock processes.
+//!
+//! The allocator starts with an empty heap and grows it by moving the program
+//! break (using Memop) whenever an allocation does not fit in the memory it
+//! already has. Freed memory is kept in an address-ordered free list and
+//! merged with its neighbours, but is never returned to the kernel.
+//!
+//! The allocator keeps track of how much memory is in use, which is available
+//! through [`TockAllocator::stats`]. On parts with little RAM, this can be
+//! used to monitor memory pressure.
+//!
+//! # Example
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: TockAllocator<TockSyscalls> = TockAllocator::new();
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ptr::null_mut;
+use libtock_platform::Syscalls;
+
+/// A memory allocator that obtains memory from the kernel by moving the
+/// program break.
+pub struct TockAllocator<S: Syscalls> {
+    heap: UnsafeCell<Heap>,
+    _syscalls: PhantomData<S>,
+}
+
+// Safety: Tock processes are single-threaded, and upcalls only run during
+// Yield. The allocator never yields, so its methods cannot be re-entered and
+// there is never more than one reference to `heap` at a time.
+unsafe impl<S: Syscalls> Sync for TockAllocator<S> {}
+
+impl<S: Syscalls> TockAllocator<S> {
+    pub const fn new() -> Self {
+        Self {
+            heap: UnsafeCell::new(Heap::new()),
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Returns statistics about the heap's current state.
+    pub fn stats(&self) -> HeapStats {
+        // Safety: see the `Sync` implementation.
+        unsafe { &*self.heap.get() }.stats()
+    }
+}
+
+impl<S: Syscalls> Default for TockAllocator<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<S: Syscalls> GlobalAlloc for TockAllocator<S> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Safety: see the `Sync` implementation.
+        let heap = unsafe { &mut *self.heap.get() };
+        let ptr = heap.alloc(layout);
+        if !ptr.is_null() {
+            return ptr;
+        }
+
+        // Grow the heap by enough memory to fit the allocation even if none
+        // of the new memory merges with existing free memory and the start
+        // of the new memory is poorly aligned.
+        let Some(increment) = block_size(layout)
+            .checked_add(layout.align().max(UNIT) + UNIT)
+            .and_then(|increment| increment.checked_next_multiple_of(GROWTH_UNIT))
+        else {
+            return null_mut();
+        };
+        let Ok(increment_u32) = u32::try_from(increment) else {
+            return null_mut();
+        };
+        let Ok(previous_break) = S::memop_increment_brk(increment_u32) else {
+            return null_mut();
+        };
+        // Safety: the kernel just gave the process the `increment` bytes
+        // following `previous_break`, and nothing else uses them.
+        unsafe {
+            heap.add_memory(previous_break as *mut u8, increment);
+        }
+        heap.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Safety: see the `Sync` implementation. The caller guarantees ptr
+        // was allocated by this allocator with the same layout.
+        unsafe { (*self.heap.get()).dealloc(ptr, layout) }
+    }
+}
+
+/// Statistics about the heap, returned by [`TockAllocator::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Number of bytes currently allocated, including the padding the
+    /// allocator adds to each allocation.
+    pub used: usize,
+
+    /// The largest value `used` has had.
+    pub peak: usize,
+
+    /// The size of the largest contiguous free block. Allocations larger than
+    /// this require the heap to grow.
+    pub largest_free_block: usize,
+
+    /// The address of the end of the heap (the program break). This is 0 if
+    /// the heap has not been used yet.
+    pub brk: usize,
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// A free block's header, which is stored at the start of the free block.
+#[repr(C)]
+struct FreeBlock {
+    size: usize,
+    next: *mut FreeBlock,
+}
+
+// The granularity of allocations. Every block (allocated or free) starts at a
+// multiple of UNIT and its size is a multiple of UNIT, which guarantees that
+// every unused part of a block is large enough to hold a FreeBlock.
+const UNIT: usize = size_of::<FreeBlock>();
+
+// The heap is grown in multiples of GROWTH_UNIT, to reduce the number of Memop
+// calls.
+const GROWTH_UNIT: usize = 256;
+
+// Returns the number of bytes the allocator uses for an allocation of layout.
+fn block_size(layout: Layout) -> usize {
+    layout.size().max(1).next_multiple_of(UNIT)
+}
+
+struct Heap {
+    // Address-ordered list of free blocks. Adjacent free blocks are always
+    // merged.
+    free: *mut FreeBlock,
+    brk: usize,
+    used: usize,
+    peak: usize,
+}
+
+impl Heap {
+    const fn new() -> Heap {
+        Heap {
+            free: null_mut(),
+            brk: 0,
+            used: 0,
+            peak: 0,
+        }
+    }
+
+    // Adds the len bytes starting at start to the heap.
+    //
+    // Safety: the memory must be valid for reads and writes, not used by
+    // anything else, and above all memory previously added to the heap.
+    unsafe fn add_memory(&mut self, start: *mut u8, len: usize) {
+        let end = start as usize + len;
+        self.brk = end;
+        let offset = start.align_offset(UNIT);
+        if offset >= len {
+            return;
+        }
+        let size = (len - offset) & !(UNIT - 1);
+        if size > 0 {
+            // Safety: the caller guarantees we own this memory.
+            unsafe { self.insert_free(start.add(offset), size) }
+        }
+    }
+
+    fn alloc(&mut self, layout: Layout) -> *mut u8 {
+        let size = block_size(layout);
+        let align = layout.align().max(UNIT);
+        let mut prev: *mut FreeBlock = null_mut();
+        let mut cur = self.free;
+        // Safety: every pointer in the free list points to a valid FreeBlock
+        // header that we own.
+        unsafe {
+            while !cur.is_null() {
+                let FreeBlock {
+                    size: block_len,
+                    next,
+                } = cur.read();
+                let start = cur as *mut u8;
+                // Because start and align are both multiples of UNIT, so is
+                // front.
+                let front = start.align_offset(align);
+                if front <= block_len && size <= block_len - front {
+                    if prev.is_null() {
+                        self.free = next;
+                    } else {
+                        (*prev).next = next;
+                    }
+                    let back = block_len - front - size;
+                    if front > 0 {
+                        self.insert_free(start, front);
+                    }
+                    if back > 0 {
+                        self.insert_free(start.add(front + size), back);
+                    }
+                    self.used += size;
+                    self.peak = self.peak.max(self.used);
+                    return start.add(front);
+                }
+                prev = cur;
+                cur = next;
+            }
+        }
+        null_mut()
+    }
+
+    // Safety: ptr must have been returned by alloc with the same layout, and
+    // not freed since.
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        let size = block_size(layout);
+        self.used -= size;
+        // Safety: the caller guarantees ptr is a block we allocated.
+        unsafe { self.insert_free(ptr, size) }
+    }
+
+    // Inserts a block into the free list, merging it with adjacent free
+    // blocks.
+    //
+    // Safety: the block must be UNIT-aligned, its size a nonzero multiple of
+    // UNIT, and it must be owned by the heap and not in the free list.
+    unsafe fn insert_free(&mut self, start: *mut u8, size: usize) {
+        let block = start as *mut FreeBlock;
+        let mut prev: *mut FreeBlock = null_mut();
+        let mut next = self.free;
+        // Safety: every pointer in the free list points to a valid FreeBlock
+        // header that we own, and the caller guarantees we own block.
+        unsafe {
+            while !next.is_null() && (next as usize) < (block as usize) {
+                prev = next;
+                next = (*next).next;
+            }
+            block.write(FreeBlock { size, next });
+            if !next.is_null() && start as usize + size == next as usize {
+                (*block).size += (*next).size;
+                (*block).next = (*next).next;
+            }
+            if prev.is_null() {
+                self.free = block;
+            } else if prev as usize + (*prev).size == block as usize {
+                (*prev).size += (*block).size;
+                (*prev).next = (*block).next;
+            } else {
+                (*prev).next = block;
+            }
+        }
+    }
+
+    fn stats(&self) -> HeapStats {
+        let mut largest_free_block = 0;
+        let mut cur = self.free;
+        while !cur.is_null() {
+            // Safety: every pointer in the free list points to a valid
+            // FreeBlock header.
+            let block = unsafe { &*cur };
+            largest_free_block = largest_free_block.max(block.size);
+            cur = block.next;
+        }
+        HeapStats {
+            used: self.used,
+            peak: self.peak,
+            largest_free_block,
+            brk: self.brk,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,114 @@
+use super::*;
+use std::alloc::{alloc, dealloc};
+
+const HEAP_LEN: usize = 64 * UNIT;
+const HEAP_LAYOUT: Layout = match Layout::from_size_align(HEAP_LEN, 64) {
+    Ok(layout) => layout,
+    Err(_) => panic!("invalid heap layout"),
+};
+
+// Runs test with a Heap containing HEAP_LEN bytes of memory, starting at the
+// returned address.
+fn with_heap(test: impl FnOnce(&mut Heap, *mut u8)) {
+    let memory = unsafe { alloc(HEAP_LAYOUT) };
+    assert!(!memory.is_null());
+    let mut heap = Heap::new();
+    unsafe { heap.add_memory(memory, HEAP_LEN) };
+    test(&mut heap, memory);
+    unsafe { dealloc(memory, HEAP_LAYOUT) };
+}
+
+fn layout(size: usize, align: usize) -> Layout {
+    Layout::from_size_align(size, align).unwrap()
+}
+
+#[test]
+fn empty() {
+    let mut heap = Heap::new();
+    assert_eq!(heap.stats(), HeapStats::default());
+    assert!(heap.alloc(layout(1, 1)).is_null());
+}
+
+#[test]
+fn alloc_dealloc() {
+    with_heap(|heap, memory| {
+        assert_eq!(
+            heap.stats(),
+            HeapStats {
+                used: 0,
+                peak: 0,
+                largest_free_block: HEAP_LEN,
+                brk: memory as usize + HEAP_LEN,
+            }
+        );
+        let a = heap.alloc(layout(1, 1));
+        assert_eq!(a, memory);
+        let b = heap.alloc(layout(UNIT + 1, 1));
+        assert_eq!(b, unsafe { memory.add(UNIT) });
+        assert_eq!(heap.stats().used, 3 * UNIT);
+        assert_eq!(heap.stats().largest_free_block, HEAP_LEN - 3 * UNIT);
+
+        unsafe { heap.dealloc(a, layout(1, 1)) };
+        let stats = heap.stats();
+        assert_eq!(stats.used, 2 * UNIT);
+        assert_eq!(stats.peak, 3 * UNIT);
+        assert_eq!(stats.largest_free_block, HEAP_LEN - 3 * UNIT);
+
+        // Freeing b should merge it with the free blocks on both sides.
+        unsafe { heap.dealloc(b, layout(UNIT + 1, 1)) };
+        let stats = heap.stats();
+        assert_eq!(stats.used, 0);
+        assert_eq!(stats.peak, 3 * UNIT);
+        assert_eq!(stats.largest_free_block, HEAP_LEN);
+    });
+}
+
+#[test]
+fn reuse() {
+    with_heap(|heap, memory| {
+        let a = heap.alloc(layout(UNIT, 1));
+        let b = heap.alloc(layout(UNIT, 1));
+        unsafe { heap.dealloc(a, layout(UNIT, 1)) };
+        // The first free block that fits is used.
+        assert_eq!(heap.alloc(layout(UNIT, 1)), memory);
+        unsafe { heap.dealloc(b, layout(UNIT, 1)) };
+    });
+}
+
+#[test]
+fn alignment() {
+    with_heap(|heap, memory| {
+        let a = heap.alloc(layout(1, 1));
+        assert_eq!(a, memory);
+        let b = heap.alloc(layout(1, 4 * UNIT));
+        assert_eq!(b, unsafe { memory.add(4 * UNIT) });
+        // The padding in front of b is still available.
+        assert_eq!(heap.alloc(layout(2 * UNIT, 1)), unsafe { memory.add(UNIT) });
+        assert_eq!(heap.stats().used, 4 * UNIT);
+    });
+}
+
+#[test]
+fn out_of_memory() {
+    with_heap(|heap, memory| {
+        assert!(heap.alloc(layout(HEAP_LEN + 1, 1)).is_null());
+        assert_eq!(heap.alloc(layout(HEAP_LEN, 1)), memory);
+        assert!(heap.alloc(layout(1, 1)).is_null());
+        assert_eq!(heap.stats().largest_free_block, 0);
+    });
+}
+
+#[test]
+fn grow() {
+    let memory = unsafe { alloc(HEAP_LAYOUT) };
+    let mut heap = Heap::new();
+    // Add the memory in two parts, the way it is added when the program break
+    // moves. The two parts should be merged.
+    unsafe {
+        heap.add_memory(memory, HEAP_LEN / 2);
+        heap.add_memory(memory.add(HEAP_LEN / 2), HEAP_LEN / 2);
+    }
+    assert_eq!(heap.stats().largest_free_block, HEAP_LEN);
+    assert_eq!(heap.stats().brk, memory as usize + HEAP_LEN);
+    unsafe { dealloc(memory, HEAP_LAYOUT) };
+}
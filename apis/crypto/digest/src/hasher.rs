@@ -0,0 +1,214 @@
+//! The RustCrypto `Digest` and `Mac` traits, implemented over the drivers.
+//!
+//! [`ShaHasher`] implements `Digest`, and [`HmacHasher`] implements `Mac` and
+//! `KeyInit`, for the algorithm given by an [`Algorithm`] type. The traits
+//! cannot report errors, so the hashers panic if the driver fails, e.g. if it
+//! does not exist. Like the drivers, only one hasher (and one MAC) can be used
+//! at a time: updating a second one before the first is finalized mixes their
+//! data.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::digest::hasher::{Digest, Sha256Hasher};
+//!
+//! let digest = Sha256Hasher::new()
+//!     .chain_update(&manifest.header)
+//!     .chain_update(&manifest.body)
+//!     .finalize();
+//! ```
+
+use crate::{Config, Hmac, Sha, ShaAlgorithm};
+use core::marker::PhantomData;
+use digest::consts::{U128, U32, U48, U64};
+use digest::crypto_common::KeySizeUser;
+use digest::generic_array::ArrayLength;
+use digest::{
+    FixedOutput, FixedOutputReset, HashMarker, InvalidLength, Key, KeyInit, MacMarker, Output,
+    OutputSizeUser, Reset, Update,
+};
+use libtock_platform::{DefaultConfig, Syscalls};
+
+pub use digest::{Digest, Mac};
+
+/// A hash algorithm, as a type.
+pub trait Algorithm {
+    const ALGORITHM: ShaAlgorithm;
+    /// The length of the digests.
+    type OutputSize: ArrayLength<u8> + 'static;
+    /// The block length, which is the length of HMAC keys.
+    type BlockSize: ArrayLength<u8> + 'static;
+}
+
+pub struct Sha256;
+pub struct Sha384;
+pub struct Sha512;
+
+impl Algorithm for Sha256 {
+    const ALGORITHM: ShaAlgorithm = ShaAlgorithm::Sha256;
+    type OutputSize = U32;
+    type BlockSize = U64;
+}
+
+impl Algorithm for Sha384 {
+    const ALGORITHM: ShaAlgorithm = ShaAlgorithm::Sha384;
+    type OutputSize = U48;
+    type BlockSize = U128;
+}
+
+impl Algorithm for Sha512 {
+    const ALGORITHM: ShaAlgorithm = ShaAlgorithm::Sha512;
+    type OutputSize = U64;
+    type BlockSize = U128;
+}
+
+/// A `Digest` computed by the SHA driver.
+pub struct ShaHasher<S: Syscalls, A: Algorithm, C: Config = DefaultConfig> {
+    // Whether the algorithm is selected, which clears the driver's data.
+    started: bool,
+    _syscalls: PhantomData<(S, A, C)>,
+}
+
+pub type Sha256Hasher<S> = ShaHasher<S, Sha256>;
+pub type Sha384Hasher<S> = ShaHasher<S, Sha384>;
+pub type Sha512Hasher<S> = ShaHasher<S, Sha512>;
+
+impl<S: Syscalls, A: Algorithm, C: Config> ShaHasher<S, A, C> {
+    fn start(&mut self) {
+        if !self.started {
+            Sha::<S, C>::set_algorithm(A::ALGORITHM).expect("SHA driver failed");
+            self.started = true;
+        }
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> Default for ShaHasher<S, A, C> {
+    fn default() -> Self {
+        ShaHasher {
+            started: false,
+            _syscalls: PhantomData,
+        }
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> HashMarker for ShaHasher<S, A, C> {}
+
+impl<S: Syscalls, A: Algorithm, C: Config> OutputSizeUser for ShaHasher<S, A, C> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> Update for ShaHasher<S, A, C> {
+    fn update(&mut self, data: &[u8]) {
+        self.start();
+        Sha::<S, C>::update(data).expect("SHA driver failed");
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> FixedOutput for ShaHasher<S, A, C> {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        FixedOutputReset::finalize_into_reset(&mut self, out);
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> FixedOutputReset for ShaHasher<S, A, C> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.start();
+        Sha::<S, C>::finish(out).expect("SHA driver failed");
+        self.started = false;
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> Reset for ShaHasher<S, A, C> {
+    fn reset(&mut self) {
+        self.started = false;
+    }
+}
+
+/// A `Mac` computed by the HMAC driver.
+///
+/// Keys can be up to `MAX_KEY_LEN` bytes long, which is longer than the block
+/// size of SHA-256. `KeyInit::new_from_slice` fails on longer keys.
+pub struct HmacHasher<S: Syscalls, A: Algorithm, C: Config = DefaultConfig> {
+    key: [u8; MAX_KEY_LEN],
+    key_len: usize,
+    started: bool,
+    _syscalls: PhantomData<(S, A, C)>,
+}
+
+/// The length of the longest key of `HmacHasher`: the block size of SHA-384
+/// and SHA-512.
+pub const MAX_KEY_LEN: usize = 128;
+
+pub type HmacSha256<S> = HmacHasher<S, Sha256>;
+pub type HmacSha384<S> = HmacHasher<S, Sha384>;
+pub type HmacSha512<S> = HmacHasher<S, Sha512>;
+
+impl<S: Syscalls, A: Algorithm, C: Config> HmacHasher<S, A, C> {
+    fn start(&mut self) {
+        if !self.started {
+            Hmac::<S, C>::set_algorithm(A::ALGORITHM).expect("HMAC driver failed");
+            self.started = true;
+        }
+    }
+
+    fn key(&self) -> &[u8] {
+        &self.key[..self.key_len]
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> MacMarker for HmacHasher<S, A, C> {}
+
+impl<S: Syscalls, A: Algorithm, C: Config> KeySizeUser for HmacHasher<S, A, C> {
+    type KeySize = A::BlockSize;
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> KeyInit for HmacHasher<S, A, C> {
+    fn new(key: &Key<Self>) -> Self {
+        // Block sizes are never longer than MAX_KEY_LEN.
+        <Self as KeyInit>::new_from_slice(key).unwrap()
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        if key.len() > MAX_KEY_LEN {
+            return Err(InvalidLength);
+        }
+        let mut hasher = HmacHasher {
+            key: [0; MAX_KEY_LEN],
+            key_len: key.len(),
+            started: false,
+            _syscalls: PhantomData,
+        };
+        hasher.key[..key.len()].copy_from_slice(key);
+        Ok(hasher)
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> OutputSizeUser for HmacHasher<S, A, C> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> Update for HmacHasher<S, A, C> {
+    fn update(&mut self, data: &[u8]) {
+        self.start();
+        Hmac::<S, C>::update(self.key(), data).expect("HMAC driver failed");
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> FixedOutput for HmacHasher<S, A, C> {
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        FixedOutputReset::finalize_into_reset(&mut self, out);
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> FixedOutputReset for HmacHasher<S, A, C> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.start();
+        Hmac::<S, C>::finish(self.key(), out).expect("HMAC driver failed");
+        self.started = false;
+    }
+}
+
+impl<S: Syscalls, A: Algorithm, C: Config> Reset for HmacHasher<S, A, C> {
+    fn reset(&mut self) {
+        self.started = false;
+    }
+}
@@ -0,0 +1,61 @@
+use digest::{FixedOutputReset, InvalidLength, KeyInit};
+use libtock_unittest::fake;
+
+use crate::hasher::{Digest, Mac, MAX_KEY_LEN};
+
+type Sha256Hasher = crate::hasher::Sha256Hasher<fake::Syscalls>;
+type Sha512Hasher = crate::hasher::Sha512Hasher<fake::Syscalls>;
+type HmacSha256 = crate::hasher::HmacSha256<fake::Syscalls>;
+
+#[test]
+fn digest() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Sha::new();
+    kernel.add_driver(&driver);
+
+    let digest = Sha256Hasher::new()
+        .chain_update(b"firmware ")
+        .chain_update(b"manifest")
+        .finalize();
+    assert_eq!(digest, sha2::Sha256::digest(b"firmware manifest"));
+    assert_eq!(Sha512Hasher::digest(b""), sha2::Sha512::digest(b""));
+
+    // A reset hasher starts over.
+    let mut hasher = Sha256Hasher::new();
+    Digest::update(&mut hasher, b"dropped");
+    Digest::reset(&mut hasher);
+    Digest::update(&mut hasher, b"abc");
+    assert_eq!(hasher.finalize_fixed_reset(), sha2::Sha256::digest(b"abc"));
+    assert_eq!(hasher.finalize(), sha2::Sha256::digest(b""));
+}
+
+#[test]
+fn mac() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+
+    let key = [7; 100];
+    let expected = <hmac::Hmac<sha2::Sha256> as Mac>::new_from_slice(&key)
+        .unwrap()
+        .chain_update(b"signed data")
+        .finalize()
+        .into_bytes();
+    let mac = <HmacSha256 as KeyInit>::new_from_slice(&key)
+        .unwrap()
+        .chain_update(b"signed ")
+        .chain_update(b"data");
+    assert_eq!(mac.verify_slice(&expected), Ok(()));
+
+    assert_eq!(
+        <HmacSha256 as KeyInit>::new_from_slice(&[0; MAX_KEY_LEN + 1]).err(),
+        Some(InvalidLength)
+    );
+}
+
+#[test]
+#[should_panic(expected = "SHA driver failed")]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    Sha256Hasher::digest(b"abc");
+}
@@ -0,0 +1,276 @@
+//! The SHA and HMAC drivers, which hash and authenticate data in hardware.
+//!
+//! Both drivers share a system call interface, and hash the data added to them
+//! since the last digest with the algorithm last selected. [`Sha`] and
+//! [`Hmac`] expose it directly, and the [`hasher`] module implements the
+//! RustCrypto `Digest` and `Mac` traits over it, so that crates written
+//! against those traits use the hardware transparently.
+//!
+//! The drivers hold the state of a single computation, so a process can only
+//! compute one digest (and one MAC) at a time.
+
+#![no_std]
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+pub mod hasher;
+
+/// A hash algorithm supported by the drivers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShaAlgorithm {
+    Sha256 = 0,
+    Sha384 = 1,
+    Sha512 = 2,
+}
+
+impl ShaAlgorithm {
+    /// The length of this algorithm's digests, in bytes.
+    pub fn digest_len(self) -> usize {
+        match self {
+            ShaAlgorithm::Sha256 => 32,
+            ShaAlgorithm::Sha384 => 48,
+            ShaAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// The SHA driver.
+///
+/// # Example
+/// ```ignore
+/// use libtock::digest::{Sha, ShaAlgorithm};
+///
+/// let mut digest = [0; 32];
+/// Sha::set_algorithm(ShaAlgorithm::Sha256)?;
+/// for chunk in image.chunks(512) {
+///     Sha::update(chunk)?;
+/// }
+/// Sha::finish(&mut digest)?;
+/// ```
+pub struct Sha<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> Sha<S, C> {
+    /// Run a check against the SHA capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(sha::DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Selects the algorithm of the following digests, and drops the data
+    /// added so far.
+    pub fn set_algorithm(algorithm: ShaAlgorithm) -> Result<(), ErrorCode> {
+        S::checked_command::<C>(sha::DRIVER_NUM, command::SET_ALGORITHM, algorithm as u32, 0)
+            .to_result()
+    }
+
+    /// Adds `data` to the digest.
+    pub fn update(data: &[u8]) -> Result<(), ErrorCode> {
+        Operation::<S, C, { sha::DRIVER_NUM }>::new(command::UPDATE)
+            .data(data)
+            .run()
+            .map(|_| ())
+    }
+
+    /// Writes the digest of the data added so far to `digest`, and returns
+    /// its length. Fails with `ErrorCode::Size` if `digest` is too short.
+    pub fn finish(digest: &mut [u8]) -> Result<usize, ErrorCode> {
+        Operation::<S, C, { sha::DRIVER_NUM }>::new(command::FINISH)
+            .dest(digest)
+            .run()
+            .map(|len| len as usize)
+    }
+}
+
+/// The HMAC driver. The key is shared with the driver by each operation.
+///
+/// # Example
+/// ```ignore
+/// use libtock::digest::{Hmac, ShaAlgorithm};
+///
+/// let mut mac = [0; 32];
+/// Hmac::set_algorithm(ShaAlgorithm::Sha256)?;
+/// Hmac::update(&key, &message)?;
+/// Hmac::finish(&key, &mut mac)?;
+/// ```
+pub struct Hmac<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> Hmac<S, C> {
+    /// Run a check against the HMAC capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(hmac::DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Selects the algorithm of the following MACs, and drops the data added
+    /// so far.
+    pub fn set_algorithm(algorithm: ShaAlgorithm) -> Result<(), ErrorCode> {
+        S::checked_command::<C>(
+            hmac::DRIVER_NUM,
+            command::SET_ALGORITHM,
+            algorithm as u32,
+            0,
+        )
+        .to_result()
+    }
+
+    /// Adds `data` to the MAC keyed with `key`.
+    pub fn update(key: &[u8], data: &[u8]) -> Result<(), ErrorCode> {
+        Operation::<S, C, { hmac::DRIVER_NUM }>::new(command::UPDATE)
+            .key(key)
+            .data(data)
+            .run()
+            .map(|_| ())
+    }
+
+    /// Writes the MAC of the data added so far, keyed with `key`, to `mac`,
+    /// and returns its length. Fails with `ErrorCode::Size` if `mac` is too
+    /// short.
+    pub fn finish(key: &[u8], mac: &mut [u8]) -> Result<usize, ErrorCode> {
+        Operation::<S, C, { hmac::DRIVER_NUM }>::new(command::FINISH)
+            .key(key)
+            .dest(mac)
+            .run()
+            .map(|len| len as usize)
+    }
+}
+
+/// System call configuration trait for `Sha` and `Hmac`.
+pub trait Config:
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
+{
+}
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
+{
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// A command of either driver, with the buffers it uses.
+struct Operation<'a, S: Syscalls, C: Config, const DRIVER_NUM: u32> {
+    command_num: u32,
+    key: Option<&'a [u8]>,
+    data: Option<&'a [u8]>,
+    dest: Option<&'a mut [u8]>,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<'a, S: Syscalls, C: Config, const DRIVER_NUM: u32> Operation<'a, S, C, DRIVER_NUM> {
+    fn new(command_num: u32) -> Self {
+        Operation {
+            command_num,
+            key: None,
+            data: None,
+            dest: None,
+            _syscalls: PhantomData,
+        }
+    }
+
+    fn key(mut self, key: &'a [u8]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    fn data(mut self, data: &'a [u8]) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    fn dest(mut self, dest: &'a mut [u8]) -> Self {
+        self.dest = Some(dest);
+        self
+    }
+
+    // Shares the buffers, runs the command and waits for it to complete.
+    // Returns the value the driver reports.
+    fn run(self) -> Result<u32, ErrorCode> {
+        let done: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::KEY }>,
+                AllowRo<_, DRIVER_NUM, { allow_ro::DATA }>,
+                AllowRw<_, DRIVER_NUM, { allow_rw::DEST }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::DONE }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_key, allow_data, allow_dest, subscribe) = handle.split();
+            if let Some(key) = self.key {
+                S::allow_ro::<C, DRIVER_NUM, { allow_ro::KEY }>(allow_key, key)?;
+            }
+            if let Some(data) = self.data {
+                S::allow_ro::<C, DRIVER_NUM, { allow_ro::DATA }>(allow_data, data)?;
+            }
+            if let Some(dest) = self.dest {
+                S::allow_rw::<C, DRIVER_NUM, { allow_rw::DEST }>(allow_dest, dest)?;
+            }
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::DONE }>(subscribe, &done)?;
+            S::checked_command::<C>(DRIVER_NUM, self.command_num, 0, 0).to_result::<(), _>()?;
+            loop {
+                S::yield_wait();
+                if let Some((status, value)) = done.get() {
+                    return match status {
+                        0 => Ok(value),
+                        status => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+                    };
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod hasher_tests;
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver numbers and command IDs
+// -----------------------------------------------------------------------------
+
+mod sha {
+    pub const DRIVER_NUM: u32 = 0x40005;
+}
+
+mod hmac {
+    pub const DRIVER_NUM: u32 = 0x40003;
+}
+
+// The command IDs, shared by both drivers.
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SET_ALGORITHM: u32 = 1;
+    pub const UPDATE: u32 = 3;
+    pub const FINISH: u32 = 4;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const KEY: u32 = 0;
+    pub const DATA: u32 = 1;
+}
+
+mod allow_rw {
+    pub const DEST: u32 = 2;
+}
@@ -0,0 +1,59 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::ShaAlgorithm;
+
+type Sha = super::Sha<fake::Syscalls>;
+type Hmac = super::Hmac<fake::Syscalls>;
+
+// SHA-256("abc"), from FIPS 180-2.
+const ABC_SHA256: [u8; 32] = [
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Sha::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(Hmac::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn sha() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Sha::new();
+    kernel.add_driver(&driver);
+    assert_eq!(Sha::exists(), Ok(()));
+
+    let mut digest = [0; 32];
+    assert_eq!(Sha::update(b"abc"), Err(ErrorCode::Reserve));
+    assert_eq!(Sha::set_algorithm(ShaAlgorithm::Sha256), Ok(()));
+    assert_eq!(Sha::update(b"a"), Ok(()));
+    assert_eq!(Sha::update(b"bc"), Ok(()));
+    assert_eq!(Sha::finish(&mut digest), Ok(32));
+    assert_eq!(digest, ABC_SHA256);
+
+    assert_eq!(Sha::set_algorithm(ShaAlgorithm::Sha512), Ok(()));
+    assert_eq!(Sha::finish(&mut digest), Err(ErrorCode::Size));
+}
+
+#[test]
+fn hmac() {
+    use hmac::Mac;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Hmac::new();
+    kernel.add_driver(&driver);
+    assert_eq!(Hmac::exists(), Ok(()));
+
+    let mut mac = [0; 48];
+    assert_eq!(Hmac::set_algorithm(ShaAlgorithm::Sha384), Ok(()));
+    assert_eq!(Hmac::update(b"key", b"message"), Ok(()));
+    assert_eq!(Hmac::finish(b"key", &mut mac), Ok(48));
+    let expected = hmac::Hmac::<sha2::Sha384>::new_from_slice(b"key")
+        .unwrap()
+        .chain_update(b"message")
+        .finalize()
+        .into_bytes();
+    assert_eq!(mac[..], expected[..]);
+}
@@ -0,0 +1,251 @@
+use crate::{Config, Screen};
+use core::marker::PhantomData;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// A rectangle of pixels on the screen.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn area(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Returns the smallest rectangle containing both `self` and `other`.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    // Returns true if `self` and `other` overlap or share an edge, so their
+    // union adds no pixels outside them along that edge.
+    fn touches(&self, other: &Rect) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    // Returns the part of `self` within a `width` by `height` screen.
+    fn clip(&self, width: u32, height: u32) -> Rect {
+        let (x, y) = (self.x.min(width), self.y.min(height));
+        Rect::new(x, y, self.width.min(width - x), self.height.min(height - y))
+    }
+}
+
+/// A renderer that only pushes the parts of the screen that changed.
+///
+/// The renderer keeps a back buffer holding the whole frame, in the screen's
+/// pixel format, which is drawn on with `set_pixel` and `fill_rect`. Drawing
+/// a pixel with the color it already has is free; otherwise the pixel is
+/// added to the damage, a list of up to `MAX_RECTS` dirty rectangles. When the
+/// list is full, new damage is merged into the rectangle it grows the least,
+/// so the damage may cover some unchanged pixels, but never misses a changed
+/// one. `flush` writes each dirty rectangle to the screen, and clears the
+/// damage.
+///
+/// The frame must fit in `BUFFER_LEN` bytes, so the renderer suits the small,
+/// low-resolution screens common on MCUs (a 128x64 RGB332 screen takes 8 KiB).
+/// Rectangles spanning the whole width of the screen are written in one
+/// operation; others are written a row at a time, as their rows are not
+/// contiguous in the back buffer. Pixel formats with less than a byte per
+/// pixel are not supported.
+///
+/// # Example
+/// ```ignore
+/// use libtock::screen::{DamageRenderer, Rect};
+///
+/// let mut renderer = DamageRenderer::<{ 128 * 64 }>::new()?;
+/// loop {
+///     renderer.fill_rect(Rect::new(0, 0, 128, 64), &[0]);
+///     renderer.fill_rect(Rect::new(x, 20, 8, 8), &[0xFF]);
+///     // Only pushes the squares where the box was and is now.
+///     renderer.flush()?;
+/// }
+/// ```
+pub struct DamageRenderer<
+    S: Syscalls,
+    const BUFFER_LEN: usize,
+    const MAX_RECTS: usize = 4,
+    C: Config = DefaultConfig,
+> {
+    width: u32,
+    height: u32,
+    pixel_len: usize,
+    buffer: [u8; BUFFER_LEN],
+    damage: [Rect; MAX_RECTS],
+    damage_len: usize,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const BUFFER_LEN: usize, const MAX_RECTS: usize, C: Config>
+    DamageRenderer<S, BUFFER_LEN, MAX_RECTS, C>
+{
+    const CHECK_MAX_RECTS: () = assert!(MAX_RECTS > 0);
+
+    /// Creates a renderer for the screen's current resolution and pixel
+    /// format. Fails with `ErrorCode::NoSupport` if a pixel takes less than a
+    /// byte, and with `ErrorCode::Size` if the frame does not fit in
+    /// `BUFFER_LEN` bytes.
+    ///
+    /// The back buffer starts out zeroed, and as the screen's content is
+    /// unknown, all of it is damaged.
+    pub fn new() -> Result<Self, ErrorCode> {
+        let () = Self::CHECK_MAX_RECTS;
+        let bits = Screen::<S, C>::pixel_format()?
+            .bits_per_pixel()
+            .ok_or(ErrorCode::NoSupport)?;
+        if bits % 8 != 0 {
+            return Err(ErrorCode::NoSupport);
+        }
+        let (width, height) = Screen::<S, C>::resolution()?;
+        let pixel_len = bits as usize / 8;
+        if width as usize * height as usize * pixel_len > BUFFER_LEN {
+            return Err(ErrorCode::Size);
+        }
+        let mut renderer = DamageRenderer {
+            width,
+            height,
+            pixel_len,
+            buffer: [0; BUFFER_LEN],
+            damage: [Rect::default(); MAX_RECTS],
+            damage_len: 0,
+            _syscalls: PhantomData,
+        };
+        renderer.invalidate();
+        Ok(renderer)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of bytes a pixel takes.
+    pub fn pixel_len(&self) -> usize {
+        self.pixel_len
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`) in the back buffer.
+    ///
+    /// # Panics
+    /// Panics if the pixel is outside the screen.
+    pub fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        assert!(x < self.width && y < self.height);
+        let offset = self.offset(x, y);
+        &self.buffer[offset..offset + self.pixel_len]
+    }
+
+    /// Sets the pixel at (`x`, `y`) to `color`, given by the first
+    /// `pixel_len` bytes of `color`. Pixels outside the screen are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: &[u8]) {
+        self.fill_rect(Rect::new(x, y, 1, 1), color);
+    }
+
+    /// Fills `rect` with `color`, given by the first `pixel_len` bytes of
+    /// `color`. The part of `rect` outside the screen is ignored.
+    pub fn fill_rect(&mut self, rect: Rect, color: &[u8]) {
+        let color = &color[..self.pixel_len];
+        let rect = rect.clip(self.width, self.height);
+        // The bounding box of the pixels that changed.
+        let mut changed: Option<Rect> = None;
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                let offset = self.offset(x, y);
+                let pixel = &mut self.buffer[offset..offset + self.pixel_len];
+                if pixel != color {
+                    pixel.copy_from_slice(color);
+                    let point = Rect::new(x, y, 1, 1);
+                    changed = Some(changed.map_or(point, |changed| changed.union(&point)));
+                }
+            }
+        }
+        if let Some(changed) = changed {
+            self.add_damage(changed);
+        }
+    }
+
+    /// Returns the dirty rectangles that `flush` will write.
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage[..self.damage_len]
+    }
+
+    /// Marks the whole screen as damaged, e.g. after another process drew on
+    /// it, so the next `flush` rewrites all of it.
+    pub fn invalidate(&mut self) {
+        self.damage[0] = Rect::new(0, 0, self.width, self.height);
+        self.damage_len = 1;
+    }
+
+    /// Writes the damaged parts of the back buffer to the screen. If a write
+    /// fails, the rectangles not written yet stay damaged, so a later flush
+    /// retries them.
+    pub fn flush(&mut self) -> Result<(), ErrorCode> {
+        while self.damage_len > 0 {
+            let rect = self.damage[self.damage_len - 1];
+            if rect.x == 0 && rect.width == self.width {
+                self.write(rect)?;
+            } else {
+                for y in rect.y..rect.y + rect.height {
+                    self.write(Rect::new(rect.x, y, rect.width, 1))?;
+                }
+            }
+            self.damage_len -= 1;
+        }
+        Ok(())
+    }
+
+    fn offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * self.pixel_len
+    }
+
+    // Writes `rect`, whose pixels must be contiguous in the back buffer.
+    fn write(&self, rect: Rect) -> Result<(), ErrorCode> {
+        Screen::<S, C>::set_write_frame(rect.x, rect.y, rect.width, rect.height)?;
+        let start = self.offset(rect.x, rect.y);
+        Screen::<S, C>::write(&self.buffer[start..start + rect.area() as usize * self.pixel_len])
+    }
+
+    // Adds `rect` to the damage: merges it into a rectangle it touches, into a
+    // free slot, or into the rectangle whose area it grows the least.
+    fn add_damage(&mut self, rect: Rect) {
+        let damage = &mut self.damage[..self.damage_len];
+        if let Some(dirty) = damage.iter_mut().find(|dirty| dirty.touches(&rect)) {
+            *dirty = dirty.union(&rect);
+            return;
+        }
+        if self.damage_len < MAX_RECTS {
+            self.damage[self.damage_len] = rect;
+            self.damage_len += 1;
+            return;
+        }
+        let dirty = damage
+            .iter_mut()
+            .min_by_key(|dirty| dirty.union(&rect).area() - dirty.area())
+            .expect("MAX_RECTS is not 0");
+        *dirty = dirty.union(&rect);
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+// Holds a 4x3 RGB565 frame, with up to 2 dirty rectangles.
+type Renderer = super::DamageRenderer<fake::Syscalls, 24, 2>;
+
+#[test]
+fn new() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(4, 3);
+    kernel.add_driver(&driver);
+
+    let renderer = Renderer::new().unwrap();
+    assert_eq!((renderer.width(), renderer.height()), (4, 3));
+    assert_eq!(renderer.pixel_len(), 2);
+    assert_eq!(renderer.damage(), &[Rect::new(0, 0, 4, 3)]);
+
+    assert_eq!(
+        DamageRenderer::<fake::Syscalls, 23>::new().err(),
+        Some(ErrorCode::Size)
+    );
+    driver.set_modes(&[(4, 3)], &[0]);
+    Screen::<fake::Syscalls>::set_pixel_format(PixelFormat::Mono).unwrap();
+    assert_eq!(Renderer::new().err(), Some(ErrorCode::NoSupport));
+}
+
+#[test]
+fn damage() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(4, 3);
+    kernel.add_driver(&driver);
+    let mut renderer = Renderer::new().unwrap();
+    assert_eq!(renderer.flush(), Ok(()));
+    assert!(renderer.damage().is_empty());
+
+    // Unchanged pixels are not damaged.
+    renderer.fill_rect(Rect::new(0, 0, 4, 3), &[0, 0]);
+    renderer.set_pixel(4, 0, &[1, 1]);
+    assert!(renderer.damage().is_empty());
+
+    // Only the changed pixels of a fill are damaged.
+    renderer.set_pixel(1, 1, &[0, 7]);
+    renderer.fill_rect(Rect::new(0, 0, 3, 3), &[0, 7]);
+    assert_eq!(renderer.pixel(2, 2), &[0, 7]);
+    assert_eq!(renderer.damage(), &[Rect::new(0, 0, 3, 3)]);
+
+    // Touching damage is merged, and separate damage takes a new rectangle
+    // until they run out.
+    renderer.set_pixel(3, 2, &[0, 8]);
+    assert_eq!(renderer.damage(), &[Rect::new(0, 0, 4, 3)]);
+    renderer.invalidate();
+    renderer.flush().unwrap();
+    renderer.set_pixel(0, 0, &[0, 1]);
+    renderer.set_pixel(3, 0, &[0, 1]);
+    assert_eq!(
+        renderer.damage(),
+        &[Rect::new(0, 0, 1, 1), Rect::new(3, 0, 1, 1)]
+    );
+    renderer.set_pixel(3, 2, &[0, 1]);
+    assert_eq!(
+        renderer.damage(),
+        &[Rect::new(0, 0, 1, 1), Rect::new(3, 0, 1, 3)]
+    );
+}
+
+#[test]
+fn flush() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(4, 3);
+    kernel.add_driver(&driver);
+    let mut renderer = Renderer::new().unwrap();
+
+    // A full-width rectangle takes a single write.
+    renderer.set_pixel(1, 1, &[0x12, 0x34]);
+    assert_eq!(renderer.flush(), Ok(()));
+    assert_eq!(driver.write_count(), 1);
+    assert_eq!(driver.pixel(1, 1), 0x1234);
+
+    // Other rectangles are written a row at a time.
+    renderer.fill_rect(Rect::new(2, 0, 2, 2), &[0xAB, 0xCD]);
+    assert_eq!(renderer.flush(), Ok(()));
+    assert_eq!(driver.write_count(), 3);
+    assert_eq!(driver.pixel(3, 1), 0xABCD);
+    assert_eq!(driver.pixel(1, 1), 0x1234);
+    assert_eq!(driver.pixel(3, 2), 0);
+
+    // Failed writes stay damaged.
+    renderer.set_pixel(0, 2, &[0, 1]);
+    driver.fail_next(ErrorCode::Busy);
+    assert_eq!(renderer.flush(), Err(ErrorCode::Busy));
+    assert_eq!(renderer.damage(), &[Rect::new(0, 2, 1, 1)]);
+    assert_eq!(renderer.flush(), Ok(()));
+    assert_eq!(driver.pixel(0, 2), 1);
+}
+
+#[test]
+fn rects() {
+    let a = Rect::new(0, 0, 2, 2);
+    assert_eq!(a.union(&Rect::new(3, 1, 1, 3)), Rect::new(0, 0, 4, 4));
+    assert_eq!(a.area(), 4);
+    assert!(Rect::new(1, 1, 0, 3).is_empty());
+
+    // Rectangles are clipped to the screen.
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(4, 3);
+    kernel.add_driver(&driver);
+    let mut renderer = Renderer::new().unwrap();
+    renderer.flush().unwrap();
+    renderer.fill_rect(Rect::new(5, 5, 1, 1), &[0, 1]);
+    assert!(renderer.damage().is_empty());
+    renderer.fill_rect(Rect::new(3, 1, 4, 4), &[0, 1]);
+    assert_eq!(renderer.damage(), &[Rect::new(3, 1, 1, 2)]);
+}
@@ -0,0 +1,224 @@
+use crate::{Config, PixelFormat, Screen};
+use core::marker::PhantomData;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::{PixelColor, Rgb565, Rgb888, RgbColor};
+use embedded_graphics_core::primitives::{PointsIter, Rectangle};
+use embedded_graphics_core::Pixel;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// A color the screen can show, in the pixel format of the same name.
+pub trait ScreenColor: PixelColor + sealed::Sealed {
+    /// The screen's pixel format for this color.
+    const FORMAT: PixelFormat;
+    /// The number of bytes a pixel takes.
+    const BYTES: usize;
+
+    /// Encodes the color into the first `BYTES` bytes of `bytes`.
+    fn encode(self, bytes: &mut [u8]);
+}
+
+impl ScreenColor for Rgb565 {
+    const FORMAT: PixelFormat = PixelFormat::Rgb565;
+    const BYTES: usize = 2;
+
+    fn encode(self, bytes: &mut [u8]) {
+        let value = (self.r() as u16) << 11 | (self.g() as u16) << 5 | self.b() as u16;
+        bytes[..2].copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+impl ScreenColor for Rgb888 {
+    const FORMAT: PixelFormat = PixelFormat::Rgb888;
+    const BYTES: usize = 3;
+
+    fn encode(self, bytes: &mut [u8]) {
+        bytes[..3].copy_from_slice(&[self.r(), self.g(), self.b()]);
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::Rgb565 {}
+    impl Sealed for super::Rgb888 {}
+}
+
+/// An [embedded-graphics](https://docs.rs/embedded-graphics) `DrawTarget` that
+/// draws on the screen, so the ecosystem's text, shapes, and images can be
+/// rendered on it.
+///
+/// Pixels are collected in a buffer of `BUFFER_LEN` bytes before they are
+/// written to the screen: horizontal runs of pixels from `draw_iter`, and
+/// tiles of whole rows (or parts of rows, if a row does not fit) from
+/// `fill_contiguous`. Each write costs a few system calls, so larger buffers
+/// draw faster. `fill_solid` and `clear` fill the area in a single operation,
+/// whatever the buffer size. Pixels outside the screen are ignored.
+///
+/// # Example
+/// ```ignore
+/// use embedded_graphics::{
+///     mono_font::{ascii::FONT_10X20, MonoTextStyle},
+///     pixelcolor::Rgb565,
+///     prelude::*,
+///     text::Text,
+/// };
+/// use libtock::screen::ScreenDrawTarget;
+///
+/// let mut display = ScreenDrawTarget::<Rgb565>::new()?;
+/// display.clear(Rgb565::BLACK)?;
+/// let style = MonoTextStyle::new(&FONT_10X20, Rgb565::WHITE);
+/// Text::new("Hello", Point::new(10, 20), style).draw(&mut display)?;
+/// ```
+pub struct ScreenDrawTarget<
+    S: Syscalls,
+    Color: ScreenColor,
+    const BUFFER_LEN: usize = 1024,
+    C: Config = DefaultConfig,
+> {
+    size: Size,
+    buffer: [u8; BUFFER_LEN],
+    _syscalls: PhantomData<(S, Color, C)>,
+}
+
+impl<S: Syscalls, Color: ScreenColor, const BUFFER_LEN: usize, C: Config>
+    ScreenDrawTarget<S, Color, BUFFER_LEN, C>
+{
+    // The number of pixels the buffer holds, which must not be 0.
+    const CAPACITY: usize = {
+        assert!(BUFFER_LEN >= Color::BYTES);
+        BUFFER_LEN / Color::BYTES
+    };
+
+    /// Creates a draw target covering the whole screen. Switches the screen
+    /// to `Color`'s pixel format if it uses another one, which fails with
+    /// `ErrorCode::NoSupport` on screens that cannot switch.
+    pub fn new() -> Result<Self, ErrorCode> {
+        let _ = Self::CAPACITY;
+        if Screen::<S, C>::pixel_format()? != Color::FORMAT {
+            Screen::<S, C>::set_pixel_format(Color::FORMAT)?;
+        }
+        let (width, height) = Screen::<S, C>::resolution()?;
+        Ok(ScreenDrawTarget {
+            size: Size::new(width, height),
+            buffer: [0; BUFFER_LEN],
+            _syscalls: PhantomData,
+        })
+    }
+
+    // Writes the first `width * height` pixels of the buffer to the `width` by
+    // `height` window at `top_left`.
+    fn write(&self, top_left: Point, width: usize, height: usize) -> Result<(), ErrorCode> {
+        Screen::<S, C>::set_write_frame(
+            top_left.x as u32,
+            top_left.y as u32,
+            width as u32,
+            height as u32,
+        )?;
+        Screen::<S, C>::write(&self.buffer[..width * height * Color::BYTES])
+    }
+}
+
+impl<S: Syscalls, Color: ScreenColor, const BUFFER_LEN: usize, C: Config> OriginDimensions
+    for ScreenDrawTarget<S, Color, BUFFER_LEN, C>
+{
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<S: Syscalls, Color: ScreenColor, const BUFFER_LEN: usize, C: Config> DrawTarget
+    for ScreenDrawTarget<S, Color, BUFFER_LEN, C>
+{
+    type Color = Color;
+    type Error = ErrorCode;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), ErrorCode>
+    where
+        I: IntoIterator<Item = Pixel<Color>>,
+    {
+        let bounds = self.bounding_box();
+        // The run of horizontally adjacent pixels in the buffer: its leftmost
+        // pixel and its length.
+        let mut start = Point::zero();
+        let mut len = 0;
+        for Pixel(point, color) in pixels {
+            if !bounds.contains(point) {
+                continue;
+            }
+            let adjacent = point == start + Point::new(len as i32, 0);
+            if len > 0 && (!adjacent || len == Self::CAPACITY) {
+                self.write(start, len, 1)?;
+                len = 0;
+            }
+            if len == 0 {
+                start = point;
+            }
+            color.encode(&mut self.buffer[len * Color::BYTES..]);
+            len += 1;
+        }
+        if len > 0 {
+            self.write(start, len, 1)?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), ErrorCode>
+    where
+        I: IntoIterator<Item = Color>,
+    {
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        // Colors of pixels outside the screen must be skipped, which tiles
+        // cannot do.
+        if self.bounding_box().intersection(area) != *area {
+            return self.draw_iter(area.points().zip(colors).map(|(p, c)| Pixel(p, c)));
+        }
+        let width = area.size.width as usize;
+        // Tiles are `tile_width` by `tile_height` pixels, and are filled in
+        // the order `colors` lists the pixels.
+        let tile_width = width.min(Self::CAPACITY);
+        let tile_height = (Self::CAPACITY / width).max(1);
+        let mut colors = colors.into_iter();
+        let mut y = 0;
+        while y < area.size.height as usize {
+            let height = tile_height.min(area.size.height as usize - y);
+            let mut x = 0;
+            while x < width {
+                let tile_width = tile_width.min(width - x);
+                for index in 0..tile_width * height {
+                    let color = colors.next().ok_or(ErrorCode::Invalid)?;
+                    color.encode(&mut self.buffer[index * Color::BYTES..]);
+                }
+                self.write(
+                    area.top_left + Point::new(x as i32, y as i32),
+                    tile_width,
+                    height,
+                )?;
+                x += tile_width;
+            }
+            y += height;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Color) -> Result<(), ErrorCode> {
+        let area = self.bounding_box().intersection(area);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        Screen::<S, C>::set_write_frame(
+            area.top_left.x as u32,
+            area.top_left.y as u32,
+            area.size.width,
+            area.size.height,
+        )?;
+        let mut bytes = [0; 4];
+        color.encode(&mut bytes);
+        Screen::<S, C>::fill(&bytes[..Color::BYTES])
+    }
+
+    fn clear(&mut self, color: Color) -> Result<(), ErrorCode> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
+}
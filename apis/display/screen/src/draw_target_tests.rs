@@ -0,0 +1,120 @@
+use super::*;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::{Rgb565, Rgb888, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+use embedded_graphics_core::Pixel;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+// Holds 4 RGB565 pixels.
+type Target = super::ScreenDrawTarget<fake::Syscalls, Rgb565, 8>;
+
+#[test]
+fn new() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(8, 6);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Target::new().unwrap().size(), Size::new(8, 6));
+    assert_eq!(
+        ScreenDrawTarget::<fake::Syscalls, Rgb888>::new().err(),
+        Some(ErrorCode::NoSupport)
+    );
+    driver.set_modes(&[(8, 6)], &[2, 3]);
+    assert!(ScreenDrawTarget::<fake::Syscalls, Rgb888>::new().is_ok());
+    assert_eq!(driver.pixel_format(), 3);
+}
+
+#[test]
+fn draw_iter() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(8, 6);
+    kernel.add_driver(&driver);
+    let mut target = Target::new().unwrap();
+
+    let pixels = [
+        Pixel(Point::new(1, 1), Rgb565::RED),
+        Pixel(Point::new(2, 1), Rgb565::GREEN),
+        Pixel(Point::new(3, 1), Rgb565::BLUE),
+        // Outside the screen.
+        Pixel(Point::new(8, 1), Rgb565::RED),
+        Pixel(Point::new(-1, 2), Rgb565::RED),
+        // Starts a new run.
+        Pixel(Point::new(0, 2), Rgb565::WHITE),
+    ];
+    assert_eq!(target.draw_iter(pixels), Ok(()));
+    assert_eq!(driver.write_count(), 2);
+    assert_eq!(driver.pixel(1, 1), 0xF800);
+    assert_eq!(driver.pixel(2, 1), 0x07E0);
+    assert_eq!(driver.pixel(3, 1), 0x001F);
+    assert_eq!(driver.pixel(0, 2), 0xFFFF);
+
+    // Runs are split when the buffer is full.
+    let row = (0..6).map(|x| Pixel(Point::new(x, 4), Rgb565::WHITE));
+    assert_eq!(target.draw_iter(row), Ok(()));
+    assert_eq!(driver.write_count(), 4);
+    assert_eq!(driver.pixel(5, 4), 0xFFFF);
+    assert_eq!(driver.pixel(6, 4), 0);
+}
+
+#[test]
+fn fill_contiguous() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(8, 6);
+    kernel.add_driver(&driver);
+    let mut target = Target::new().unwrap();
+
+    // Rows of 2 pixels fit twice in the buffer.
+    let area = Rectangle::new(Point::new(1, 1), Size::new(2, 3));
+    let colors = (0..6).map(|i| Rgb565::new(i, 0, 0));
+    assert_eq!(target.fill_contiguous(&area, colors), Ok(()));
+    assert_eq!(driver.write_count(), 2);
+    assert_eq!(driver.pixel(1, 1), 0);
+    assert_eq!(driver.pixel(2, 1), 1 << 11);
+    assert_eq!(driver.pixel(1, 3), 4 << 11);
+    assert_eq!(driver.pixel(2, 3), 5 << 11);
+
+    // Rows of 6 pixels are split in two.
+    let area = Rectangle::new(Point::new(0, 4), Size::new(6, 2));
+    let colors = (0..12).map(|i| Rgb565::new(0, i, 0));
+    assert_eq!(target.fill_contiguous(&area, colors), Ok(()));
+    assert_eq!(driver.write_count(), 6);
+    assert_eq!(driver.pixel(4, 4), 4 << 5);
+    assert_eq!(driver.pixel(5, 5), 11 << 5);
+
+    // Pixels outside the screen are skipped.
+    let area = Rectangle::new(Point::new(7, 0), Size::new(2, 1));
+    let colors = [Rgb565::BLUE, Rgb565::RED];
+    assert_eq!(target.fill_contiguous(&area, colors), Ok(()));
+    assert_eq!(driver.pixel(7, 0), 0x001F);
+}
+
+#[test]
+fn fill_solid() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(8, 6);
+    kernel.add_driver(&driver);
+    let mut target = Target::new().unwrap();
+
+    assert_eq!(target.clear(Rgb565::BLUE), Ok(()));
+    assert_eq!(driver.pixel(7, 5), 0x001F);
+
+    let area = Rectangle::new(Point::new(6, -2), Size::new(4, 4));
+    assert_eq!(target.fill_solid(&area, Rgb565::RED), Ok(()));
+    assert_eq!(driver.write_count(), 2);
+    assert_eq!(driver.pixel(6, 1), 0xF800);
+    assert_eq!(driver.pixel(7, 0), 0xF800);
+    assert_eq!(driver.pixel(5, 1), 0x001F);
+    assert_eq!(driver.pixel(6, 2), 0x001F);
+}
+
+#[test]
+fn colors() {
+    let mut bytes = [0; 3];
+    // 0b10101 red, 0b110011 green, 0b01010 blue.
+    Rgb565::new(0x15, 0x33, 0x0A).encode(&mut bytes);
+    assert_eq!(bytes[..2], [0b1010_1110, 0b0110_1010]);
+    Rgb888::new(1, 2, 3).encode(&mut bytes);
+    assert_eq!(bytes, [1, 2, 3]);
+}
@@ -0,0 +1,354 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::share;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+mod damage;
+#[cfg(feature = "rust_embedded")]
+mod draw_target;
+mod font;
+mod text;
+
+pub use damage::{DamageRenderer, Rect};
+pub use text::{ScreenConsole, TextGrid};
+
+#[cfg(feature = "rust_embedded")]
+pub use draw_target::{ScreenColor, ScreenDrawTarget};
+
+/// The screen driver.
+///
+/// It draws on a display, e.g. an LCD or e-paper panel. Pixels are written to
+/// a rectangular window of the screen (the write frame), row by row, in the
+/// screen's current pixel format.
+///
+/// Except for the queries, every operation is carried out asynchronously by
+/// the screen, and these functions wait for it to complete.
+///
+/// # Example
+/// ```ignore
+/// use libtock::screen::{PixelFormat, Screen};
+///
+/// // Fills the screen with red, assuming it uses RGB565.
+/// let (width, height) = Screen::resolution()?;
+/// Screen::set_power(true)?;
+/// Screen::set_write_frame(0, 0, width, height)?;
+/// Screen::fill(&0xF800u16.to_be_bytes())?;
+/// ```
+pub struct Screen<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> Screen<S, C> {
+    /// Run a check against the screen capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Returns `true` if the screen's resolution and pixel format can be
+    /// changed.
+    pub fn has_setup() -> Result<bool, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::SCREEN_SETUP, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(|setup| setup != 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // Resolution, pixel format, and rotation
+    // -------------------------------------------------------------------------
+
+    /// Returns the screen's current resolution, as `(width, height)` in
+    /// pixels. The resolution accounts for the rotation.
+    pub fn resolution() -> Result<(u32, u32), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::GET_RESOLUTION, 0, 0).to_result()
+    }
+
+    /// Returns the number of resolutions `set_resolution` accepts.
+    pub fn resolution_modes() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::RESOLUTION_MODES, 0, 0).to_result()
+    }
+
+    /// Returns the supported resolution number `index`, as `(width, height)`.
+    pub fn resolution_mode(index: u32) -> Result<(u32, u32), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::RESOLUTION_MODE, index, 0).to_result()
+    }
+
+    /// Changes the screen's resolution. Only supported if `has_setup` returns
+    /// `true`, and only to one of the supported resolutions.
+    pub fn set_resolution(width: u32, height: u32) -> Result<(), ErrorCode> {
+        Self::run(command::SET_RESOLUTION, width, height)
+    }
+
+    /// Returns the screen's current pixel format.
+    pub fn pixel_format() -> Result<PixelFormat, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::GET_PIXEL_FORMAT, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(PixelFormat::from)
+    }
+
+    /// Returns the number of pixel formats `set_pixel_format` accepts.
+    pub fn pixel_format_modes() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::PIXEL_FORMAT_MODES, 0, 0).to_result()
+    }
+
+    /// Returns the supported pixel format number `index`.
+    pub fn pixel_format_mode(index: u32) -> Result<PixelFormat, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::PIXEL_FORMAT_MODE, index, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(PixelFormat::from)
+    }
+
+    /// Changes the screen's pixel format. Only supported if `has_setup`
+    /// returns `true`, and only to one of the supported pixel formats.
+    pub fn set_pixel_format(format: PixelFormat) -> Result<(), ErrorCode> {
+        Self::run(command::SET_PIXEL_FORMAT, format.into(), 0)
+    }
+
+    /// Returns the screen's current rotation.
+    pub fn rotation() -> Result<Rotation, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::GET_ROTATION, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .and_then(Rotation::try_from)
+    }
+
+    /// Rotates the screen. Rotating by 90 or 270 degrees swaps the width and
+    /// height reported by `resolution`.
+    pub fn set_rotation(rotation: Rotation) -> Result<(), ErrorCode> {
+        Self::run(command::SET_ROTATION, rotation as u32, 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // Display controls
+    // -------------------------------------------------------------------------
+
+    /// Turns the screen on or off. Screens may start off, and not show what is
+    /// written to them until they are turned on.
+    pub fn set_power(on: bool) -> Result<(), ErrorCode> {
+        Self::run(command::SET_POWER, on as u32, 0)
+    }
+
+    /// Sets the screen's brightness, from 0 (off) to `u16::MAX` (the
+    /// brightest). Screens that only support a few levels round it.
+    pub fn set_brightness(brightness: u16) -> Result<(), ErrorCode> {
+        Self::run(command::SET_BRIGHTNESS, brightness as u32, 0)
+    }
+
+    /// Inverts the screen's colors, or restores them.
+    pub fn set_invert(invert: bool) -> Result<(), ErrorCode> {
+        Self::run(command::SET_INVERT, invert as u32, 0)
+    }
+
+    // -------------------------------------------------------------------------
+    // Drawing
+    // -------------------------------------------------------------------------
+
+    /// Sets the window that `write` and `fill` draw to: `width` by `height`
+    /// pixels, whose top left corner is at (`x`, `y`). Each value must fit in
+    /// 16 bits, and the window must lie within the screen.
+    pub fn set_write_frame(x: u32, y: u32, width: u32, height: u32) -> Result<(), ErrorCode> {
+        if x > 0xFFFF || y > 0xFFFF || width > 0xFFFF || height > 0xFFFF {
+            return Err(ErrorCode::Invalid);
+        }
+        Self::run(command::SET_WRITE_FRAME, x << 16 | y, width << 16 | height)
+    }
+
+    /// Writes `pixels` to the write frame, in the current pixel format, row by
+    /// row from its top left corner. Each write starts again at the top left
+    /// corner, so a frame larger than the buffer is drawn by setting a write
+    /// frame for each part of it.
+    pub fn write(pixels: &[u8]) -> Result<(), ErrorCode> {
+        Self::run_with_buffer(command::WRITE, pixels)
+    }
+
+    /// Fills the write frame with the color of a single pixel, in the current
+    /// pixel format, given by the first bytes of `color`.
+    pub fn fill(color: &[u8]) -> Result<(), ErrorCode> {
+        Self::run_with_buffer(command::FILL, color)
+    }
+
+    // Runs `command_id`, and waits for the screen to complete it.
+    fn run(command_id: u32, argument0: u32, argument1: u32) -> Result<(), ErrorCode> {
+        let called: Cell<Option<Result<(), ErrorCode>>> = Cell::new(None);
+        share::scope(|subscribe| {
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::DONE }>(subscribe, &called)?;
+            S::checked_command::<C>(DRIVER_NUM, command_id, argument0, argument1)
+                .to_result::<(), ErrorCode>()?;
+            loop {
+                S::yield_wait();
+                if let Some(result) = called.get() {
+                    return result;
+                }
+            }
+        })
+    }
+
+    // Shares `buffer` with the screen, runs `command_id` on it, and waits for
+    // the screen to complete it.
+    fn run_with_buffer(command_id: u32, buffer: &[u8]) -> Result<(), ErrorCode> {
+        let called: Cell<Option<Result<(), ErrorCode>>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::BUFFER }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::DONE }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::BUFFER }>(allow_ro, buffer)?;
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::DONE }>(subscribe, &called)?;
+            S::checked_command::<C>(DRIVER_NUM, command_id, buffer.len() as u32, 0)
+                .to_result::<(), ErrorCode>()?;
+            loop {
+                S::yield_wait();
+                if let Some(result) = called.get() {
+                    return result;
+                }
+            }
+        })
+    }
+}
+
+/// A pixel format, i.e. how the color of a pixel is encoded in the buffers
+/// passed to `Screen::write` and `Screen::fill`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PixelFormat {
+    /// 1 bit per pixel, 8 horizontally adjacent pixels per byte, the leftmost
+    /// in the most significant bit.
+    Mono,
+    /// 8 bits per pixel: 3 bits of red, 3 of green, and 2 of blue.
+    Rgb332,
+    /// 16 bits per pixel, big-endian: 5 bits of red, 6 of green, and 5 of
+    /// blue.
+    Rgb565,
+    /// 24 bits per pixel: a byte each of red, green, and blue.
+    Rgb888,
+    /// 32 bits per pixel: a byte each of alpha, red, green, and blue.
+    Argb8888,
+    /// A format this API does not know about, with the kernel's raw value.
+    Other(u32),
+}
+
+impl PixelFormat {
+    /// Returns the number of bits a pixel takes, or `None` for formats this
+    /// API does not know about.
+    pub fn bits_per_pixel(self) -> Option<u32> {
+        match self {
+            PixelFormat::Mono => Some(1),
+            PixelFormat::Rgb332 => Some(8),
+            PixelFormat::Rgb565 => Some(16),
+            PixelFormat::Rgb888 => Some(24),
+            PixelFormat::Argb8888 => Some(32),
+            PixelFormat::Other(_) => None,
+        }
+    }
+}
+
+impl From<u32> for PixelFormat {
+    fn from(value: u32) -> PixelFormat {
+        match value {
+            0 => PixelFormat::Mono,
+            1 => PixelFormat::Rgb332,
+            2 => PixelFormat::Rgb565,
+            3 => PixelFormat::Rgb888,
+            4 => PixelFormat::Argb8888,
+            other => PixelFormat::Other(other),
+        }
+    }
+}
+
+impl From<PixelFormat> for u32 {
+    fn from(format: PixelFormat) -> u32 {
+        match format {
+            PixelFormat::Mono => 0,
+            PixelFormat::Rgb332 => 1,
+            PixelFormat::Rgb565 => 2,
+            PixelFormat::Rgb888 => 3,
+            PixelFormat::Argb8888 => 4,
+            PixelFormat::Other(other) => other,
+        }
+    }
+}
+
+/// The rotation of the screen's content, clockwise.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Rotation {
+    Normal = 0,
+    Rotated90 = 1,
+    Rotated180 = 2,
+    Rotated270 = 3,
+}
+
+impl TryFrom<u32> for Rotation {
+    type Error = ErrorCode;
+
+    fn try_from(value: u32) -> Result<Rotation, ErrorCode> {
+        match value {
+            0 => Ok(Rotation::Normal),
+            1 => Ok(Rotation::Rotated90),
+            2 => Ok(Rotation::Rotated180),
+            3 => Ok(Rotation::Rotated270),
+            _ => Err(ErrorCode::Fail),
+        }
+    }
+}
+
+/// System call configuration trait for `Screen`.
+pub trait Config:
+    platform::allow_ro::Config + platform::command::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_ro::Config + platform::command::Config + platform::subscribe::Config> Config
+    for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod damage_tests;
+
+#[cfg(all(test, feature = "rust_embedded"))]
+mod draw_target_tests;
+
+#[cfg(test)]
+mod text_tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90001;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SCREEN_SETUP: u32 = 1;
+    pub const SET_POWER: u32 = 2;
+    pub const SET_BRIGHTNESS: u32 = 3;
+    pub const SET_INVERT: u32 = 6;
+    pub const RESOLUTION_MODES: u32 = 11;
+    pub const RESOLUTION_MODE: u32 = 12;
+    pub const PIXEL_FORMAT_MODES: u32 = 13;
+    pub const PIXEL_FORMAT_MODE: u32 = 14;
+    pub const GET_ROTATION: u32 = 21;
+    pub const SET_ROTATION: u32 = 22;
+    pub const GET_RESOLUTION: u32 = 23;
+    pub const SET_RESOLUTION: u32 = 24;
+    pub const GET_PIXEL_FORMAT: u32 = 25;
+    pub const SET_PIXEL_FORMAT: u32 = 26;
+    pub const SET_WRITE_FRAME: u32 = 100;
+    pub const WRITE: u32 = 200;
+    pub const FILL: u32 = 300;
+}
+
+mod subscribe {
+    pub const DONE: u32 = 0;
+}
+
+mod allow_ro {
+    pub const BUFFER: u32 = 0;
+}
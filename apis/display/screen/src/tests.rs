@@ -0,0 +1,111 @@
+use super::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Screen = super::Screen<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Screen::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(Screen::set_power(true), Err(ErrorCode::NoDevice));
+    assert_eq!(Screen::write(&[0; 2]), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(240, 240);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Screen::exists(), Ok(()));
+}
+
+#[test]
+fn setup() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(240, 320);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Screen::has_setup(), Ok(false));
+    assert_eq!(Screen::resolution(), Ok((240, 320)));
+    assert_eq!(Screen::pixel_format(), Ok(PixelFormat::Rgb565));
+    assert_eq!(
+        Screen::set_pixel_format(PixelFormat::Mono),
+        Err(ErrorCode::NoSupport)
+    );
+
+    driver.set_modes(&[(240, 320), (120, 160)], &[2, 0]);
+    assert_eq!(Screen::has_setup(), Ok(true));
+    assert_eq!(Screen::resolution_modes(), Ok(2));
+    assert_eq!(Screen::resolution_mode(1), Ok((120, 160)));
+    assert_eq!(Screen::resolution_mode(2), Err(ErrorCode::Invalid));
+    assert_eq!(Screen::pixel_format_modes(), Ok(2));
+    assert_eq!(Screen::pixel_format_mode(1), Ok(PixelFormat::Mono));
+
+    assert_eq!(Screen::set_resolution(120, 160), Ok(()));
+    assert_eq!(Screen::resolution(), Ok((120, 160)));
+    assert_eq!(Screen::set_resolution(100, 100), Err(ErrorCode::Invalid));
+    assert_eq!(Screen::set_pixel_format(PixelFormat::Mono), Ok(()));
+    assert_eq!(Screen::pixel_format(), Ok(PixelFormat::Mono));
+
+    assert_eq!(Screen::rotation(), Ok(Rotation::Normal));
+    assert_eq!(Screen::set_rotation(Rotation::Rotated270), Ok(()));
+    assert_eq!(Screen::rotation(), Ok(Rotation::Rotated270));
+    assert_eq!(Screen::resolution(), Ok((160, 120)));
+}
+
+#[test]
+fn controls() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(240, 240);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Screen::set_power(true), Ok(()));
+    assert!(driver.is_on());
+    assert_eq!(Screen::set_brightness(0x8000), Ok(()));
+    assert_eq!(driver.brightness(), 0x8000);
+    assert_eq!(Screen::set_invert(true), Ok(()));
+    assert!(driver.is_inverted());
+
+    // Errors are reported through the upcall.
+    driver.fail_next(ErrorCode::Busy);
+    assert_eq!(Screen::set_power(false), Err(ErrorCode::Busy));
+    assert!(driver.is_on());
+}
+
+#[test]
+fn draw() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(4, 4);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Screen::set_write_frame(1, 1, 2, 2), Ok(()));
+    assert_eq!(driver.write_frame(), (1, 1, 2, 2));
+    assert_eq!(Screen::fill(&[0x07, 0xE0]), Ok(()));
+    assert_eq!(driver.pixel(0, 0), 0);
+    assert_eq!(driver.pixel(1, 1), 0x07E0);
+    assert_eq!(driver.pixel(2, 2), 0x07E0);
+
+    assert_eq!(Screen::set_write_frame(0, 3, 4, 1), Ok(()));
+    assert_eq!(Screen::write(&[0, 1, 0, 2, 0, 3, 0, 4]), Ok(()));
+    assert_eq!(driver.pixel(0, 3), 1);
+    assert_eq!(driver.pixel(3, 3), 4);
+
+    assert_eq!(Screen::set_write_frame(3, 3, 2, 1), Err(ErrorCode::Invalid));
+    assert_eq!(
+        Screen::set_write_frame(0x10000, 0, 1, 1),
+        Err(ErrorCode::Invalid)
+    );
+}
+
+#[test]
+fn pixel_formats() {
+    assert_eq!(PixelFormat::from(3), PixelFormat::Rgb888);
+    assert_eq!(PixelFormat::from(9), PixelFormat::Other(9));
+    assert_eq!(u32::from(PixelFormat::Argb8888), 4);
+    assert_eq!(PixelFormat::Mono.bits_per_pixel(), Some(1));
+    assert_eq!(PixelFormat::Rgb565.bits_per_pixel(), Some(16));
+    assert_eq!(PixelFormat::Other(9).bits_per_pixel(), None);
+    assert_eq!(Rotation::try_from(4), Err(ErrorCode::Fail));
+}
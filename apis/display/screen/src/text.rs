@@ -0,0 +1,238 @@
+use crate::{font, Config, PixelFormat, Screen};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// A grid of character cells, which text is written to like a terminal.
+///
+/// Text is written at the cursor. `'\n'` moves the cursor to the start of the
+/// next line and `'\r'` to the start of the current one; lines longer than
+/// the grid is wide wrap. Writing past the last row scrolls the grid up by a
+/// row. Characters other than printable ASCII are stored as `'?'`.
+///
+/// The grid records which rows changed since `take_dirty` was last called,
+/// so whatever displays it only redraws those.
+pub struct TextGrid<'b> {
+    cells: &'b mut [u8],
+    columns: usize,
+    rows: usize,
+    column: usize,
+    row: usize,
+    dirty: Range<usize>,
+}
+
+impl<'b> TextGrid<'b> {
+    /// Creates a grid of `columns` by `rows` cells, stored in `cells`, which
+    /// must hold at least `columns * rows` bytes. The grid starts out blank
+    /// and entirely dirty.
+    pub fn new(cells: &'b mut [u8], columns: usize, rows: usize) -> Result<Self, ErrorCode> {
+        if columns == 0 || rows == 0 || cells.len() < columns * rows {
+            return Err(ErrorCode::Size);
+        }
+        let mut grid = TextGrid {
+            cells,
+            columns,
+            rows,
+            column: 0,
+            row: 0,
+            dirty: 0..0,
+        };
+        grid.clear();
+        Ok(grid)
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the cursor's position, as `(column, row)`. The column equals
+    /// `columns()` once the row is full, until the next character wraps.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.column, self.row)
+    }
+
+    /// Returns the characters of row `row`.
+    pub fn row(&self, row: usize) -> &[u8] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    /// Blanks the grid and moves the cursor to its top left corner.
+    pub fn clear(&mut self) {
+        self.cells[..self.columns * self.rows].fill(b' ');
+        self.column = 0;
+        self.row = 0;
+        self.dirty = 0..self.rows;
+    }
+
+    pub fn write(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => self.new_line(),
+                '\r' => self.column = 0,
+                ' '..='~' => self.put(c as u8),
+                _ => self.put(b'?'),
+            }
+        }
+    }
+
+    /// Returns the rows that changed since the last call, and marks them
+    /// clean.
+    pub fn take_dirty(&mut self) -> Range<usize> {
+        core::mem::replace(&mut self.dirty, 0..0)
+    }
+
+    fn put(&mut self, byte: u8) {
+        if self.column == self.columns {
+            self.new_line();
+        }
+        self.cells[self.row * self.columns + self.column] = byte;
+        self.column += 1;
+        self.mark_dirty(self.row);
+    }
+
+    fn new_line(&mut self) {
+        self.column = 0;
+        if self.row + 1 < self.rows {
+            self.row += 1;
+            return;
+        }
+        let len = self.columns * self.rows;
+        self.cells[..len].rotate_left(self.columns);
+        self.cells[len - self.columns..len].fill(b' ');
+        self.dirty = 0..self.rows;
+    }
+
+    fn mark_dirty(&mut self, row: usize) {
+        self.dirty = match self.dirty.is_empty() {
+            true => row..row + 1,
+            false => self.dirty.start.min(row)..self.dirty.end.max(row + 1),
+        };
+    }
+}
+
+/// A console on the screen, for processes without a UART, e.g. to log to.
+///
+/// Text is laid out by a [`TextGrid`] of 8x8 pixel cells covering the top
+/// left of the screen, stored in a buffer provided by the caller, and drawn
+/// white on black. Each write redraws the rows it changed, a cell at a time,
+/// so writes that scroll the screen redraw all of it.
+///
+/// # Example
+/// ```ignore
+/// use core::fmt::Write;
+/// use libtock::console::Console;
+/// use libtock::screen::ScreenConsole;
+///
+/// let mut cells = [0; 40 * 30];
+/// let mut screen_console;
+/// let log: &mut dyn Write = match Console::exists() {
+///     Ok(()) => &mut Console::writer(),
+///     Err(_) => {
+///         screen_console = ScreenConsole::new(&mut cells)?;
+///         &mut screen_console
+///     }
+/// };
+/// writeln!(log, "Booted")?;
+/// ```
+pub struct ScreenConsole<'b, S: Syscalls, C: Config = DefaultConfig> {
+    grid: TextGrid<'b>,
+    pixel_format: PixelFormat,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<'b, S: Syscalls, C: Config> ScreenConsole<'b, S, C> {
+    /// Creates a console for the screen's current resolution and pixel
+    /// format, with as many rows as fit on the screen and in `cells` (a byte
+    /// per cell). Fails with `ErrorCode::NoSupport` for pixel formats this API
+    /// does not know about, and with `ErrorCode::Size` if not a single row
+    /// fits.
+    ///
+    /// The console is blank, and is drawn on the first write or `flush`.
+    pub fn new(cells: &'b mut [u8]) -> Result<Self, ErrorCode> {
+        let pixel_format = Screen::<S, C>::pixel_format()?;
+        pixel_format.bits_per_pixel().ok_or(ErrorCode::NoSupport)?;
+        let (width, height) = Screen::<S, C>::resolution()?;
+        let columns = width as usize / CELL_SIZE;
+        let rows = match columns {
+            0 => 0,
+            _ => (height as usize / CELL_SIZE).min(cells.len() / columns),
+        };
+        Ok(ScreenConsole {
+            grid: TextGrid::new(cells, columns, rows)?,
+            pixel_format,
+            _syscalls: PhantomData,
+        })
+    }
+
+    pub fn grid(&self) -> &TextGrid<'b> {
+        &self.grid
+    }
+
+    /// Blanks the console. It is redrawn on the next write or `flush`.
+    pub fn clear(&mut self) {
+        self.grid.clear();
+    }
+
+    /// Draws the rows that changed since the last flush.
+    pub fn flush(&mut self) -> Result<(), ErrorCode> {
+        for row in self.grid.take_dirty() {
+            for column in 0..self.grid.columns() {
+                let byte = self.grid.row(row)[column];
+                if let Err(error) = self.draw_cell(column, row, byte) {
+                    // Draw the remaining rows on the next flush.
+                    self.grid.dirty = row..self.grid.rows();
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_cell(&self, column: usize, row: usize, byte: u8) -> Result<(), ErrorCode> {
+        let glyph = font::glyph(byte);
+        let lit = |x: usize, y: usize| x < font::WIDTH && glyph[x] & 1 << y != 0;
+        let mut pixels = [0; CELL_SIZE * CELL_SIZE * 4];
+        let len = match self.pixel_format {
+            PixelFormat::Mono => {
+                for (y, pixel_row) in pixels[..CELL_SIZE].iter_mut().enumerate() {
+                    *pixel_row = (0..CELL_SIZE)
+                        .filter(|&x| lit(x, y))
+                        .fold(0, |bits, x| bits | 0x80 >> x);
+                }
+                CELL_SIZE
+            }
+            format => {
+                // White is all ones and black all zeros in every format.
+                let pixel_len = format.bits_per_pixel().unwrap_or(0) as usize / 8;
+                for (index, pixel) in pixels
+                    .chunks_exact_mut(pixel_len)
+                    .take(CELL_SIZE * CELL_SIZE)
+                    .enumerate()
+                {
+                    if lit(index % CELL_SIZE, index / CELL_SIZE) {
+                        pixel.fill(0xFF);
+                    }
+                }
+                CELL_SIZE * CELL_SIZE * pixel_len
+            }
+        };
+        let size = CELL_SIZE as u32;
+        Screen::<S, C>::set_write_frame(column as u32 * size, row as u32 * size, size, size)?;
+        Screen::<S, C>::write(&pixels[..len])
+    }
+}
+
+impl<S: Syscalls, C: Config> fmt::Write for ScreenConsole<'_, S, C> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.grid.write(s);
+        self.flush().map_err(|_| fmt::Error)
+    }
+}
+
+// The width and height of a cell, in pixels.
+const CELL_SIZE: usize = 8;
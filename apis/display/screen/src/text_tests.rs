@@ -0,0 +1,101 @@
+use super::*;
+use core::fmt::Write;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type ScreenConsole<'b> = super::ScreenConsole<'b, fake::Syscalls>;
+
+#[test]
+fn grid_wrap() {
+    let mut cells = [0; 12];
+    let mut grid = TextGrid::new(&mut cells, 4, 3).unwrap();
+    assert_eq!(grid.take_dirty(), 0..3);
+    assert_eq!(grid.take_dirty(), 0..0);
+
+    grid.write("abcd");
+    assert_eq!(grid.cursor(), (4, 0));
+    assert_eq!(grid.take_dirty(), 0..1);
+    grid.write("é\r\n");
+    assert_eq!(grid.row(0), b"abcd");
+    assert_eq!(grid.row(1), b"?   ");
+    assert_eq!(grid.cursor(), (0, 2));
+    grid.write("xy\rz");
+    assert_eq!(grid.row(2), b"zy  ");
+    assert_eq!(grid.take_dirty(), 1..3);
+
+    assert_eq!(TextGrid::new(&mut cells, 4, 4).err(), Some(ErrorCode::Size));
+    assert_eq!(TextGrid::new(&mut cells, 0, 1).err(), Some(ErrorCode::Size));
+}
+
+#[test]
+fn grid_scroll() {
+    let mut cells = [0; 6];
+    let mut grid = TextGrid::new(&mut cells, 3, 2).unwrap();
+    grid.take_dirty();
+    grid.write("one\ntwo");
+    assert_eq!(grid.take_dirty(), 0..2);
+    // Wrapping past the last row scrolls.
+    grid.write("3");
+    assert_eq!(grid.row(0), b"two");
+    assert_eq!(grid.row(1), b"3  ");
+    assert_eq!(grid.take_dirty(), 0..2);
+    grid.write("\n");
+    assert_eq!(grid.row(0), b"3  ");
+    assert_eq!(grid.row(1), b"   ");
+    assert_eq!(grid.cursor(), (0, 1));
+
+    grid.clear();
+    assert_eq!((grid.row(0), grid.cursor()), (&b"   "[..], (0, 0)));
+}
+
+#[test]
+fn console() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(20, 16);
+    kernel.add_driver(&driver);
+    driver.fail_next(ErrorCode::Fail);
+    let mut cells = [0; 2];
+    assert_eq!(ScreenConsole::new(&mut [0; 1]).err(), Some(ErrorCode::Size));
+
+    let mut console = ScreenConsole::new(&mut cells).unwrap();
+    assert_eq!((console.grid().columns(), console.grid().rows()), (2, 1));
+    // The first draw fails, so the next write redraws everything.
+    assert_eq!(console.flush(), Err(ErrorCode::Fail));
+    assert_eq!(driver.write_count(), 0);
+    write!(console, "|").unwrap();
+    assert_eq!(driver.write_count(), 2);
+    assert_eq!(driver.write_frame(), (8, 0, 8, 8));
+    // The '|' glyph is a column with a gap in its middle.
+    assert_eq!(driver.pixel(2, 0), 0xFFFF);
+    assert_eq!(driver.pixel(2, 3), 0);
+    assert_eq!(driver.pixel(2, 7), 0);
+    assert_eq!(driver.pixel(1, 0), 0);
+
+    // Only the changed row is redrawn.
+    console.flush().unwrap();
+    assert_eq!(driver.write_count(), 2);
+}
+
+#[test]
+fn console_mono() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Screen::new(8, 8);
+    kernel.add_driver(&driver);
+    driver.set_modes(&[(8, 8)], &[0]);
+    Screen::<fake::Syscalls>::set_pixel_format(PixelFormat::Mono).unwrap();
+
+    let mut cells = [0; 1];
+    let mut console = ScreenConsole::new(&mut cells).unwrap();
+    write!(console, "_").unwrap();
+    assert_eq!(driver.pixel(0, 6), 1);
+    assert_eq!(driver.pixel(4, 6), 1);
+    assert_eq!(driver.pixel(5, 6), 0);
+    assert_eq!(driver.pixel(0, 5), 0);
+
+    driver.set_modes(&[(8, 8)], &[7]);
+    Screen::<fake::Syscalls>::set_pixel_format(PixelFormat::Other(7)).unwrap();
+    assert_eq!(
+        ScreenConsole::new(&mut cells).err(),
+        Some(ErrorCode::NoSupport)
+    );
+}
@@ -0,0 +1,225 @@
+use crate::{ButtonState, Buttons, DRIVER_NUM};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_alarm::{Alarm, AlarmSubscribe, Convert, Milliseconds, Ticks};
+use libtock_future::TockStream;
+use libtock_platform::{
+    share::Handle, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+/// A gesture made with a button.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Gesture {
+    /// The button was clicked this many times in a row, e.g. `Clicks(2)` is a
+    /// double click.
+    Clicks(u8),
+    /// The button has been held down for `GestureConfig::long_press_ms`. It is
+    /// reported while the button is still down, and its release is ignored.
+    LongPress,
+}
+
+/// The timing of the gestures recognized by `Gestures`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GestureConfig {
+    /// How long the button must be held down to make a long press, or 0 to
+    /// never report long presses.
+    pub long_press_ms: u32,
+    /// How long after a click another click may start to be counted along
+    /// with it.
+    pub click_gap_ms: u32,
+    /// The most clicks counted in a row. Once reached, the clicks are reported
+    /// without waiting for the gap to pass, so with 1 every click is reported
+    /// as soon as the button is released.
+    pub max_clicks: u8,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            long_press_ms: 800,
+            click_gap_ms: 300,
+            max_clicks: 3,
+        }
+    }
+}
+
+/// Recognizes clicks, multiple clicks and long presses of a button, on top of
+/// its press and release interrupts, using the alarm to time them.
+///
+/// A click counts as part of a multiple click if it starts within the click
+/// gap of the previous one, and the count is reported once the gap passes
+/// without another click. Clicks still being counted when the button is held
+/// down into a long press are discarded.
+///
+/// Gestures are queued until they are taken, with `take_gesture` or as a
+/// stream, but only the latest one is kept. The button driver has a single
+/// listener, and the alarm a single alarm, so neither can be used for anything
+/// else while the gestures are registered.
+///
+/// # Example
+/// ```ignore
+/// use libtock::buttons::{Gesture, GestureConfig, Gestures};
+///
+/// let gestures = Gestures::new(0, GestureConfig::default())?;
+/// share::scope(|handle| {
+///     gestures.register(handle)?;
+///     loop {
+///         match block_on::<TockSyscalls, _>(next(&mut &gestures)) {
+///             Gesture::Clicks(1) => next_track(),
+///             Gesture::Clicks(2) => previous_track(),
+///             Gesture::Clicks(_) => {}
+///             Gesture::LongPress => power_off(),
+///         }
+///     }
+/// })
+/// ```
+pub struct Gestures<S: Syscalls> {
+    button: u32,
+    // In ticks.
+    long_press: u32,
+    click_gap: u32,
+    max_clicks: u8,
+    state: Cell<State>,
+    gesture: Cell<Option<Gesture>>,
+    // Receives the alarm's upcalls, which only wake the process up: timeouts
+    // are detected from the time.
+    fired: Cell<Option<(u32, u32)>>,
+    _syscalls: PhantomData<S>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum State {
+    Idle,
+    // The button is down, after `clicks` clicks.
+    Pressed { since: u32, clicks: u8 },
+    // The button is up after `clicks` clicks, which may go on.
+    Released { since: u32, clicks: u8 },
+    // The button is down after a long press.
+    Held,
+}
+
+impl<S: Syscalls> Gestures<S> {
+    /// Creates a recognizer for `button`, enabling its interrupts.
+    pub fn new(button: u32, config: GestureConfig) -> Result<Self, ErrorCode> {
+        let frequency = Alarm::<S>::get_frequency()?;
+        let Ticks(long_press) = Milliseconds(config.long_press_ms).to_ticks(frequency);
+        let Ticks(click_gap) = Milliseconds(config.click_gap_ms).to_ticks(frequency);
+        Buttons::<S>::enable_interrupts(button)?;
+        Ok(Gestures {
+            button,
+            long_press,
+            click_gap,
+            max_clicks: config.max_clicks.max(1),
+            state: Cell::new(State::Idle),
+            gesture: Cell::new(None),
+            fired: Cell::new(None),
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Registers the recognizer as the button listener and the alarm's
+    /// subscriber, replacing the previous ones.
+    pub fn register<'share>(
+        &'share self,
+        handle: Handle<'_, (GesturesSubscribe<'share, S>, AlarmSubscribe<'share, S>)>,
+    ) -> Result<(), ErrorCode> {
+        let (buttons, alarm) = handle.split();
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(buttons, self)?;
+        Alarm::<S>::register(&self.fired, alarm)
+    }
+
+    /// Returns the latest gesture not taken yet, if any.
+    pub fn take_gesture(&self) -> Option<Gesture> {
+        if self.fired.take().is_some() {
+            if let Ok(now) = Alarm::<S>::get_ticks() {
+                self.expire(now);
+            }
+        }
+        self.gesture.take()
+    }
+
+    // Ends the long press or the clicks whose time has passed at `now`.
+    fn expire(&self, now: u32) {
+        match self.state.get() {
+            State::Pressed { since, .. }
+                if self.long_press > 0 && now.wrapping_sub(since) >= self.long_press =>
+            {
+                self.state.set(State::Held);
+                self.gesture.set(Some(Gesture::LongPress));
+            }
+            State::Released { since, clicks } if now.wrapping_sub(since) >= self.click_gap => {
+                self.finish(Gesture::Clicks(clicks));
+            }
+            _ => {}
+        }
+    }
+
+    fn press(&self, now: u32) {
+        let clicks = match self.state.get() {
+            State::Idle => 0,
+            State::Released { clicks, .. } => clicks,
+            State::Pressed { .. } | State::Held => return,
+        };
+        self.state.set(State::Pressed { since: now, clicks });
+        if self.long_press > 0 {
+            let _ = Alarm::<S>::set_at(now, Ticks(self.long_press));
+        }
+    }
+
+    fn release(&self, now: u32) {
+        match self.state.get() {
+            State::Pressed { clicks, .. } if clicks + 1 >= self.max_clicks => {
+                self.finish(Gesture::Clicks(clicks + 1));
+            }
+            State::Pressed { clicks, .. } => {
+                self.state.set(State::Released {
+                    since: now,
+                    clicks: clicks + 1,
+                });
+                let _ = Alarm::<S>::set_at(now, Ticks(self.click_gap));
+            }
+            State::Held => {
+                self.state.set(State::Idle);
+                let _ = Alarm::<S>::stop();
+            }
+            State::Idle | State::Released { .. } => {}
+        }
+    }
+
+    fn finish(&self, gesture: Gesture) {
+        self.state.set(State::Idle);
+        self.gesture.set(Some(gesture));
+        let _ = Alarm::<S>::stop();
+    }
+}
+
+/// The button subscription of `Gestures::register`.
+pub type GesturesSubscribe<'share, S> = Subscribe<'share, S, DRIVER_NUM, 0>;
+
+impl<S: Syscalls> Upcall<OneId<DRIVER_NUM, 0>> for Gestures<S> {
+    fn upcall(&self, button: u32, state: u32, _arg2: u32) {
+        if button != self.button {
+            return;
+        }
+        let Ok(now) = Alarm::<S>::get_ticks() else {
+            return;
+        };
+        self.expire(now);
+        match ButtonState::from(state) {
+            ButtonState::Pressed => self.press(now),
+            ButtonState::Released => self.release(now),
+        }
+    }
+}
+
+impl<S: Syscalls> TockStream<S> for &Gestures<S> {
+    type Item = Gesture;
+
+    fn poll_next(&mut self) -> Poll<Gesture> {
+        match self.take_gesture() {
+            Some(gesture) => Poll::Ready(gesture),
+            None => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use libtock_future::{block_on, next};
+use libtock_platform::{share, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+use crate::{Gesture, GestureConfig};
+
+type Gestures = crate::Gestures<fake::Syscalls>;
+
+// Presses (`true`) or releases button 0 after `delay` ms, and runs the
+// resulting upcalls.
+fn step(alarm: &fake::Alarm, buttons: &fake::Buttons<2>, delay: u32, pressed: bool) {
+    alarm.advance_ticks(delay);
+    buttons.set_pressed(0, pressed).unwrap();
+    while fake::Syscalls::yield_no_wait() == YieldNoWaitReturn::Upcall {}
+}
+
+fn wait(alarm: &fake::Alarm, delay: u32) {
+    alarm.advance_ticks(delay);
+    while fake::Syscalls::yield_no_wait() == YieldNoWaitReturn::Upcall {}
+}
+
+#[test]
+fn clicks() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    let buttons = fake::Buttons::<2>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&buttons);
+
+    let gestures = Gestures::new(0, GestureConfig::default()).unwrap();
+    share::scope(|handle| {
+        gestures.register(handle).unwrap();
+
+        step(&alarm, &buttons, 0, true);
+        step(&alarm, &buttons, 50, false);
+        wait(&alarm, 299);
+        assert_eq!(gestures.take_gesture(), None);
+        wait(&alarm, 1);
+        assert_eq!(gestures.take_gesture(), Some(Gesture::Clicks(1)));
+
+        // A double click.
+        step(&alarm, &buttons, 100, true);
+        step(&alarm, &buttons, 50, false);
+        step(&alarm, &buttons, 200, true);
+        step(&alarm, &buttons, 50, false);
+        assert_eq!(gestures.take_gesture(), None);
+        wait(&alarm, 300);
+        assert_eq!(gestures.take_gesture(), Some(Gesture::Clicks(2)));
+
+        // Reaching `max_clicks` does not wait for the gap.
+        for _ in 0..3 {
+            step(&alarm, &buttons, 100, true);
+            step(&alarm, &buttons, 50, false);
+        }
+        assert_eq!(gestures.take_gesture(), Some(Gesture::Clicks(3)));
+        wait(&alarm, 1000);
+        assert_eq!(gestures.take_gesture(), None);
+    });
+}
+
+#[test]
+fn long_press() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    let buttons = fake::Buttons::<2>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&buttons);
+
+    let gestures = Gestures::new(0, GestureConfig::default()).unwrap();
+    share::scope(|handle| {
+        gestures.register(handle).unwrap();
+
+        // A click followed by a long press only reports the long press.
+        step(&alarm, &buttons, 0, true);
+        step(&alarm, &buttons, 50, false);
+        step(&alarm, &buttons, 100, true);
+        alarm.advance_ticks(800);
+        assert_eq!(block_on(next(&mut &gestures)), Gesture::LongPress);
+        step(&alarm, &buttons, 1000, false);
+        wait(&alarm, 1000);
+        assert_eq!(gestures.take_gesture(), None);
+
+        // Other buttons are ignored.
+        buttons.set_pressed(1, true).unwrap();
+        wait(&alarm, 1000);
+        assert_eq!(gestures.take_gesture(), None);
+    });
+}
+
+#[test]
+fn no_long_press() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    let buttons = fake::Buttons::<2>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&buttons);
+
+    let config = GestureConfig {
+        long_press_ms: 0,
+        max_clicks: 1,
+        ..GestureConfig::default()
+    };
+    let gestures = Gestures::new(0, config).unwrap();
+    share::scope(|handle| {
+        gestures.register(handle).unwrap();
+
+        step(&alarm, &buttons, 0, true);
+        wait(&alarm, 5000);
+        assert_eq!(gestures.take_gesture(), None);
+        step(&alarm, &buttons, 0, false);
+        assert_eq!(gestures.take_gesture(), Some(Gesture::Clicks(1)));
+    });
+}
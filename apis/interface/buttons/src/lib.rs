@@ -4,6 +4,10 @@ use libtock_platform::{
     share::Handle, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
 };
 
+mod gestures;
+
+pub use gestures::{Gesture, GestureConfig, Gestures, GesturesSubscribe};
+
 /// The Buttons driver
 ///
 /// # Example
@@ -128,6 +132,9 @@ impl<F: Fn(u32, ButtonState)> Upcall<OneId<DRIVER_NUM, 0>> for ButtonListener<F>
         self.0(button_index, state.into())
     }
 }
+#[cfg(test)]
+mod gestures_tests;
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,234 @@
+use crate::{Config, Console, WriteBuffer, WriteSubscribe, DEFAULT_DRIVER_NUM};
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::TockFuture;
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// A log for the tasks of a `libtock_future` executor, which queues their
+/// lines in a buffer instead of waiting for the console to send them.
+///
+/// The queued lines are sent by the [`Drain`] future, run as one more task of
+/// the executor, e.g. with `select`. Neither logging nor draining blocks a
+/// task: `Drain` starts writing the queued lines, and the executor polls the
+/// other tasks while the console sends them. Lines that do not fit in the
+/// buffer are dropped.
+///
+/// # Example
+/// ```ignore
+/// use libtock::console::AsyncWriter;
+///
+/// let mut buf = [0; 512];
+/// let log = AsyncWriter::new(&mut buf);
+/// // In any task, or upcall:
+/// let _ = log.log(format_args!("rx {} bytes\n", len));
+/// // Runs the app's tasks along with the log's.
+/// let written = Cell::new(None);
+/// share::scope::<(WriteBuffer<_>, WriteSubscribe<_>), _, _>(|handle| {
+///     let (allow_ro, subscribe) = handle.split();
+///     let drain = log.drain(&written, allow_ro, subscribe);
+///     block_on::<TockSyscalls, _>(select(app(&log), drain));
+/// });
+/// log.flush()?;
+/// ```
+pub struct AsyncWriter<
+    'buf,
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    // The buffer, which is not kept as a slice, because the kernel reads the
+    // bytes being sent while more are queued: `buf_len` bytes at `buf`.
+    buf: *mut u8,
+    buf_len: usize,
+    // The queued bytes are the `len` bytes starting at `start`, wrapping
+    // around the end of `buf`. They include the bytes being sent by `Drain`,
+    // which are only unqueued once sent, so that they are not overwritten.
+    start: Cell<usize>,
+    len: Cell<usize>,
+    dropped: Cell<u32>,
+    _buf: PhantomData<&'buf mut [u8]>,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<'buf, S: Syscalls, C: Config, const DRIVER_NUM: u32> AsyncWriter<'buf, S, C, DRIVER_NUM> {
+    /// Creates a log that queues lines in `buf`.
+    pub fn new(buf: &'buf mut [u8]) -> Self {
+        AsyncWriter {
+            buf: buf.as_mut_ptr(),
+            buf_len: buf.len(),
+            start: Cell::new(0),
+            len: Cell::new(0),
+            dropped: Cell::new(0),
+            _buf: PhantomData,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Queues the formatted `args`, without blocking. If they do not fit in
+    /// the buffer, nothing is queued, and this fails with `ErrorCode::NoMem`.
+    pub fn log(&self, args: fmt::Arguments) -> Result<(), ErrorCode> {
+        let len = self.len.get();
+        let mut writer = self;
+        if fmt::Write::write_fmt(&mut writer, args).is_err() {
+            // Drops the part that fit, so that the line is queued entirely or
+            // not at all.
+            self.len.set(len);
+            self.dropped.set(self.dropped.get() + 1);
+            return Err(ErrorCode::NoMem);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes waiting to be sent.
+    pub fn queued(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns the number of lines that did not fit in the buffer.
+    pub fn dropped(&self) -> u32 {
+        self.dropped.get()
+    }
+
+    /// Returns the future that sends the queued lines, one write at a time,
+    /// reporting each write into `written`. It never completes, unless the
+    /// console fails.
+    ///
+    /// A write still pending at the end of the handles' scope is not
+    /// completed, so its bytes stay queued, and `flush` sends them again.
+    pub fn drain<'handle, 'share>(
+        &'share self,
+        written: &'share Cell<Option<(u32,)>>,
+        allow_ro: share::Handle<'handle, WriteBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<'handle, WriteSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Drain<'handle, 'share, 'buf, S, C, DRIVER_NUM> {
+        Drain {
+            writer: self,
+            written,
+            allow_ro,
+            subscribe,
+            sending: 0,
+        }
+    }
+
+    /// Sends the queued lines, blocking until they are sent, e.g. once the
+    /// executor is done. Must not be called while a `Drain` is sending.
+    pub fn flush(&self) -> Result<(), ErrorCode> {
+        while self.queued() > 0 {
+            self.send_chunk()?;
+        }
+        Ok(())
+    }
+
+    // Queues `bytes`, if they fit.
+    fn push(&self, bytes: &[u8]) -> Result<(), ErrorCode> {
+        let (start, len) = (self.start.get(), self.len.get());
+        if bytes.len() > self.buf_len - len {
+            return Err(ErrorCode::NoMem);
+        }
+        for (i, &byte) in bytes.iter().enumerate() {
+            let index = (start + len + i) % self.buf_len;
+            // Safety: `index` is within the buffer, and outside of the queued
+            // bytes, which are the only ones `chunk` hands out.
+            unsafe { self.buf.add(index).write(byte) };
+        }
+        self.len.set(len + bytes.len());
+        Ok(())
+    }
+
+    // Returns the next chunk of the queued bytes. It stops at the end of the
+    // buffer, the rest wrapping around.
+    fn chunk(&self) -> &[u8] {
+        let start = self.start.get();
+        let count = self.len.get().min(CHUNK_LEN).min(self.buf_len - start);
+        // Safety: The bytes are within the buffer, and are only written again
+        // once unqueued with `unqueue`.
+        unsafe { core::slice::from_raw_parts(self.buf.add(start), count) }
+    }
+
+    // Unqueues the first `count` queued bytes.
+    fn unqueue(&self, count: usize) {
+        self.start.set((self.start.get() + count) % self.buf_len);
+        self.len.set(self.len.get() - count);
+    }
+
+    // Sends the next chunk of the queued bytes, waiting for the console.
+    fn send_chunk(&self) -> Result<(), ErrorCode> {
+        let mut chunk = [0; CHUNK_LEN];
+        let count = self.chunk().len();
+        chunk[..count].copy_from_slice(self.chunk());
+        // Unqueues the chunk before the write, which runs upcalls that may log
+        // more lines.
+        self.unqueue(count);
+        Console::<S, C, DRIVER_NUM>::write(&chunk[..count])
+    }
+}
+
+/// Queues `s`, failing if it does not fit. Lines written in several pieces,
+/// e.g. by `writeln!`, may only be partly queued; see `AsyncWriter::log`.
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> fmt::Write
+    for &AsyncWriter<'_, S, C, DRIVER_NUM>
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// The future that sends the lines queued in an `AsyncWriter`, see
+/// [`AsyncWriter::drain`]. It completes with the error of the console, if a
+/// write fails to start.
+pub struct Drain<
+    'handle,
+    'share,
+    'buf,
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    writer: &'share AsyncWriter<'buf, S, C, DRIVER_NUM>,
+    written: &'share Cell<Option<(u32,)>>,
+    allow_ro: share::Handle<'handle, WriteBuffer<'share, S, DRIVER_NUM>>,
+    subscribe: share::Handle<'handle, WriteSubscribe<'share, S, DRIVER_NUM>>,
+    // The number of bytes of the pending write, 0 if there is none.
+    sending: usize,
+}
+
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> TockFuture<S>
+    for Drain<'_, '_, '_, S, C, DRIVER_NUM>
+{
+    type Output = ErrorCode;
+
+    fn poll(&mut self) -> Poll<ErrorCode> {
+        if self.sending > 0 {
+            let Some((written,)) = self.written.take() else {
+                return Poll::Pending;
+            };
+            // The bytes the console did not take are sent with the next write.
+            self.writer.unqueue((written as usize).min(self.sending));
+            self.sending = 0;
+        }
+        // The executor may sleep once this returns, so a write is started
+        // whenever there are queued bytes; its upcall wakes the executor.
+        let chunk = self.writer.chunk();
+        if chunk.is_empty() {
+            return Poll::Pending;
+        }
+        let started = Console::<S, C, DRIVER_NUM>::write_start(
+            chunk,
+            self.written,
+            self.allow_ro,
+            self.subscribe,
+        );
+        match started {
+            Ok(()) => {
+                self.sending = chunk.len();
+                Poll::Pending
+            }
+            Err(error) => Poll::Ready(error),
+        }
+    }
+}
+
+// The most bytes sent per write.
+const CHUNK_LEN: usize = 64;
@@ -0,0 +1,178 @@
+use core::cell::Cell;
+use core::task::Poll;
+use libtock_future::{block_on, select, wait_for_upcall, Either, TockFuture};
+use libtock_platform::{share, ErrorCode, Syscalls};
+use libtock_unittest::fake;
+
+use crate::{ReadBuffer, ReadSubscribe, WriteBuffer, WriteSubscribe};
+
+type AsyncWriter<'buf> = crate::AsyncWriter<'buf, fake::Syscalls>;
+type Console = crate::Console<fake::Syscalls>;
+
+#[test]
+fn write_start() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let written = Cell::new(None);
+
+    share::scope::<(WriteBuffer<_>, WriteSubscribe<_>), _, _>(|handle| {
+        let (allow_ro, subscribe) = handle.split();
+        assert_eq!(
+            Console::write_start(b"foo", &written, allow_ro, subscribe),
+            Ok(())
+        );
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(wait_for_upcall(&written)),
+            (3,)
+        );
+    });
+    assert_eq!(driver.take_bytes(), b"foo");
+}
+
+#[test]
+fn write_fut() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let written = Cell::new(None);
+
+    share::scope::<(WriteBuffer<_>, WriteSubscribe<_>), _, _>(|handle| {
+        let (allow_ro, subscribe) = handle.split();
+        let write = Console::write_fut(b"foo", &written, allow_ro, subscribe).unwrap();
+        assert_eq!(block_on::<fake::Syscalls, _>(write), Ok(()));
+    });
+    assert_eq!(driver.take_bytes(), b"foo");
+
+    // A partial write fails.
+    let mut write = crate::WriteFuture {
+        written: wait_for_upcall(&written),
+        len: 3,
+    };
+    assert_eq!(
+        TockFuture::<fake::Syscalls>::poll(&mut write),
+        Poll::Pending
+    );
+    written.set(Some((2,)));
+    assert_eq!(
+        TockFuture::<fake::Syscalls>::poll(&mut write),
+        Poll::Ready(Err(ErrorCode::Size))
+    );
+}
+
+#[test]
+fn log() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let mut buf = [0; 16];
+    let log = AsyncWriter::new(&mut buf);
+
+    assert_eq!(log.log(format_args!("a = {}\n", 1)), Ok(()));
+    assert_eq!(log.log(format_args!("b = {}\n", 22)), Ok(()));
+    assert_eq!(log.queued(), 13);
+    // Nothing is written until the log is drained.
+    assert_eq!(driver.take_bytes(), b"");
+
+    // A line that does not fit is dropped entirely.
+    assert_eq!(
+        log.log(format_args!("{}\n", "too long")),
+        Err(ErrorCode::NoMem)
+    );
+    assert_eq!((log.queued(), log.dropped()), (13, 1));
+
+    let written = Cell::new(None);
+    share::scope::<(WriteBuffer<_>, WriteSubscribe<_>), _, _>(|handle| {
+        let (allow_ro, subscribe) = handle.split();
+        let mut drain = log.drain(&written, allow_ro, subscribe);
+        assert_eq!(
+            TockFuture::<fake::Syscalls>::poll(&mut drain),
+            Poll::Pending
+        );
+        assert_eq!(driver.take_bytes(), b"a = 1\nb = 22\n");
+        // The bytes stay queued until the write completes.
+        assert_eq!(log.queued(), 13);
+        assert_eq!(
+            TockFuture::<fake::Syscalls>::poll(&mut drain),
+            Poll::Pending
+        );
+        assert_eq!(log.queued(), 13);
+        fake::Syscalls::yield_no_wait();
+        assert_eq!(
+            TockFuture::<fake::Syscalls>::poll(&mut drain),
+            Poll::Pending
+        );
+        assert_eq!(log.queued(), 0);
+        assert_eq!(driver.take_bytes(), b"");
+    });
+
+    // The queue wraps around the end of the buffer.
+    assert_eq!(log.log(format_args!("0123456789\n")), Ok(()));
+    assert_eq!(log.flush(), Ok(()));
+    assert_eq!(driver.take_bytes(), b"0123456789\n");
+}
+
+// A task that waits for a read, logging its progress.
+struct Task<'a, 'buf> {
+    log: &'a AsyncWriter<'buf>,
+    read: &'a Cell<Option<(u32, u32)>>,
+    waiting: bool,
+}
+
+impl TockFuture<fake::Syscalls> for Task<'_, '_> {
+    type Output = u32;
+
+    fn poll(&mut self) -> Poll<u32> {
+        if let Some((_, count)) = self.read.get() {
+            let _ = self.log.log(format_args!("read {}\n", count));
+            return Poll::Ready(count);
+        }
+        if !self.waiting {
+            self.waiting = true;
+            let _ = self.log.log(format_args!("waiting\n"));
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn drain() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let mut buf = [0; 64];
+    let log = AsyncWriter::new(&mut buf);
+    let read = Cell::new(None);
+    let written = Cell::new(None);
+    let mut input = [0; 4];
+
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        Console::read_start(&mut input, &read, allow_rw, subscribe).unwrap();
+        // The input only arrives once the process sleeps, by which time the
+        // log must have been sent.
+        kernel.set_idle_handler({
+            let driver = driver.clone();
+            move || {
+                assert_eq!(driver.take_bytes(), b"waiting\n");
+                driver.queue_input(b"hi");
+            }
+        });
+        let task = Task {
+            log: &log,
+            read: &read,
+            waiting: false,
+        };
+        share::scope::<(WriteBuffer<_>, WriteSubscribe<_>), _, _>(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            let drain = log.drain(&written, allow_ro, subscribe);
+            let Either::Left(count) = block_on(select(task, drain)) else {
+                panic!("The log completed");
+            };
+            assert_eq!(count, 2);
+        });
+    });
+    assert_eq!(log.queued(), 7);
+    assert_eq!(log.flush(), Ok(()));
+    assert_eq!(driver.take_bytes(), b"read 2\n");
+}
@@ -0,0 +1,175 @@
+//! Drives AT-command modems, such as cellular or Wi-Fi modules, attached to a
+//! console-compatible UART.
+//!
+//! A command is sent as `AT<command>\r`, and the modem answers with lines of
+//! information, then a final result code: `OK`, or an error such as `ERROR`
+//! or `+CME ERROR: <code>`. Modems also send unsolicited result codes (URCs),
+//! such as `+CREG: 1` or `RING`, at any time, including while a command runs.
+//! An [`AtModem`] tells them apart by prefix: while a command runs, the lines
+//! starting with the command's response prefix are its response, and the
+//! others are URCs, which are passed to the URC handler.
+//!
+//! Modems answer some commands only after seconds, or minutes, so each
+//! command takes its own timeout, which bounds how long the modem may stay
+//! silent.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::alarm::Milliseconds;
+//! use libtock::console::AtModem;
+//!
+//! let mut modem = AtModem::<128>::new().with_urc(|urc: &str| {
+//!     if urc.starts_with("+CREG:") {
+//!         registration_changed(urc);
+//!     }
+//! });
+//! modem.command("E0", "", Milliseconds(300), |_| {})?;
+//! let mut quality = None;
+//! modem.command("+CSQ", "+CSQ:", Milliseconds(300), |line| {
+//!     quality = line.split(',').next().and_then(|rssi| rssi.parse::<u8>().ok());
+//! })?;
+//! loop {
+//!     modem.process_urcs(Milliseconds(1000))?;
+//! }
+//! ```
+
+use crate::{Config, Console, LineReader, DEFAULT_DRIVER_NUM};
+use libtock_alarm::{Convert, LivenessGuard};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// An AT-command modem on the console numbered `DRIVER_NUM`, whose lines are
+/// up to `N` bytes long, and whose URCs are passed to `U`.
+pub struct AtModem<
+    S: Syscalls,
+    const N: usize,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+    U = fn(&str),
+> {
+    reader: LineReader<S, N, C, DRIVER_NUM>,
+    urc: U,
+    last_error: Option<u16>,
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32> AtModem<S, N, C, DRIVER_NUM> {
+    /// Creates a modem whose URCs are discarded.
+    pub fn new() -> Self {
+        AtModem {
+            reader: LineReader::new(),
+            urc: |_| {},
+            last_error: None,
+        }
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32> Default
+    for AtModem<S, N, C, DRIVER_NUM>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32, U: FnMut(&str)>
+    AtModem<S, N, C, DRIVER_NUM, U>
+{
+    /// Replaces the URC handler of the modem with `urc`, which receives each
+    /// URC line.
+    pub fn with_urc<V: FnMut(&str)>(self, urc: V) -> AtModem<S, N, C, DRIVER_NUM, V> {
+        AtModem {
+            reader: self.reader,
+            urc,
+            last_error: self.last_error,
+        }
+    }
+
+    /// Sends `AT<command>\r`, and waits for its final result code. Each line
+    /// starting with `prefix` is passed to `response`, without the prefix and
+    /// the spaces following it; an empty `prefix` passes every line. The echo
+    /// of the command, if the modem echoes, is skipped.
+    ///
+    /// Fails with `ErrorCode::Fail` if the modem answers with an error, whose
+    /// code, if any, is then returned by `last_error`, and with
+    /// `ErrorCode::Busy` if the modem stays silent for `timeout`. A response
+    /// line that is too long or not UTF-8 is skipped, and its error returned
+    /// once the command completes.
+    pub fn command<T: Convert, F: FnMut(&str)>(
+        &mut self,
+        command: &str,
+        prefix: &str,
+        timeout: T,
+        mut response: F,
+    ) -> Result<(), ErrorCode> {
+        let guard = LivenessGuard::<S, C>::new(timeout)?;
+        self.last_error = None;
+        Console::<S, C, DRIVER_NUM>::write_all(b"AT")?;
+        Console::<S, C, DRIVER_NUM>::write_all(command.as_bytes())?;
+        Console::<S, C, DRIVER_NUM>::write_all(b"\r")?;
+        let mut skipped = None;
+        loop {
+            let line = match self.reader.read_line_guarded(&guard) {
+                Ok(line) => line,
+                Err(error @ (ErrorCode::Size | ErrorCode::Invalid)) => {
+                    skipped = Some(error);
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+            match final_result(line) {
+                Some(Ok(())) => return skipped.map_or(Ok(()), Err),
+                Some(Err(code)) => {
+                    self.last_error = code;
+                    return Err(ErrorCode::Fail);
+                }
+                None => {}
+            }
+            match line.strip_prefix("AT") {
+                _ if line.is_empty() => {}
+                Some(echo) if echo == command => {}
+                _ => match line.strip_prefix(prefix) {
+                    Some(rest) => response(rest.trim_start_matches(' ')),
+                    None => (self.urc)(line),
+                },
+            }
+        }
+    }
+
+    /// Passes the URCs received to the URC handler, until the modem stays
+    /// silent for `timeout`.
+    pub fn process_urcs<T: Convert>(&mut self, timeout: T) -> Result<(), ErrorCode> {
+        let guard = LivenessGuard::<S, C>::new(timeout)?;
+        loop {
+            match self.reader.read_line_guarded(&guard) {
+                Ok("") | Err(ErrorCode::Size | ErrorCode::Invalid) => {}
+                Ok(line) => (self.urc)(line),
+                Err(ErrorCode::Busy) => return Ok(()),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Returns the code of the `+CME ERROR` or `+CMS ERROR` the last command
+    /// failed with, if any.
+    pub fn last_error(&self) -> Option<u16> {
+        self.last_error
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// Returns the outcome `line` reports, if it is a final result code, with the
+// error's code, if any.
+fn final_result(line: &str) -> Option<Result<(), Option<u16>>> {
+    match line {
+        "OK" => Some(Ok(())),
+        "ERROR" | "NO CARRIER" | "BUSY" | "NO ANSWER" | "NO DIALTONE" => Some(Err(None)),
+        _ => {
+            let code = line
+                .strip_prefix("+CME ERROR:")
+                .or_else(|| line.strip_prefix("+CMS ERROR:"))?;
+            Some(Err(code.trim().parse().ok()))
+        }
+    }
+}
@@ -0,0 +1,93 @@
+extern crate std;
+
+use core::cell::RefCell;
+use libtock_alarm::Milliseconds;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+type AtModem = crate::atmodem::AtModem<fake::Syscalls, 32>;
+
+#[test]
+fn command() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[b"AT+CSQ\r\r\n+CSQ: 21,99\r\n+CREG: 1\r\n", b"\r\nOK\r\n"],
+    );
+    let urcs = RefCell::new(Vec::new());
+    let mut modem = AtModem::new().with_urc(|urc: &str| urcs.borrow_mut().push(urc.to_string()));
+
+    let mut quality = String::new();
+    assert_eq!(
+        modem.command("+CSQ", "+CSQ:", Milliseconds(100), |line| {
+            quality.push_str(line)
+        }),
+        Ok(())
+    );
+    assert_eq!(modem.last_error(), None);
+    assert_eq!(console.take_bytes(), b"AT+CSQ\r");
+    assert_eq!(quality, "21,99");
+    assert_eq!(*urcs.borrow(), ["+CREG: 1"]);
+}
+
+#[test]
+fn command_errors() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[
+            b"+CME ERROR: 10\r\n",
+            b"ERROR\r\n",
+            b"+CSQ: 0123456789012345678901234567890123456789\r\nOK\r\n",
+        ],
+    );
+    let mut modem = AtModem::new();
+
+    let ignore = |_: &str| {};
+    assert_eq!(
+        modem.command("+CPIN?", "+CPIN:", Milliseconds(100), ignore),
+        Err(ErrorCode::Fail)
+    );
+    assert_eq!(modem.last_error(), Some(10));
+    assert_eq!(
+        modem.command("+FOO", "", Milliseconds(100), ignore),
+        Err(ErrorCode::Fail)
+    );
+    assert_eq!(modem.last_error(), None);
+    assert_eq!(
+        modem.command("+CSQ", "+CSQ:", Milliseconds(100), ignore),
+        Err(ErrorCode::Size)
+    );
+    // The modem stays silent.
+    assert_eq!(
+        modem.command("", "", Milliseconds(100), ignore),
+        Err(ErrorCode::Busy)
+    );
+}
+
+#[test]
+fn process_urcs() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(&kernel, &alarm, &[b"RING\r\n\r\n", b"+CREG: 5\r\n"]);
+    let urcs = RefCell::new(Vec::new());
+    let mut modem = AtModem::new().with_urc(|urc: &str| urcs.borrow_mut().push(urc.to_string()));
+
+    assert_eq!(modem.process_urcs(Milliseconds(100)), Ok(()));
+    assert_eq!(*urcs.borrow(), ["RING", "+CREG: 5"]);
+}
@@ -3,12 +3,27 @@
 use core::cell::Cell;
 use core::fmt;
 use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::{wait_for_upcall, TockFuture, UpcallFuture};
 use libtock_platform as platform;
 use libtock_platform::allow_ro::AllowRo;
 use libtock_platform::allow_rw::AllowRw;
 use libtock_platform::share;
 use libtock_platform::subscribe::Subscribe;
-use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+use libtock_platform::{DefaultConfig, DriverInfo, ErrorCode, Syscalls, YieldNoWaitReturn};
+
+#[cfg(feature = "liveness")]
+use libtock_alarm::{Convert, LivenessGuard};
+
+mod async_writer;
+#[cfg(feature = "liveness")]
+pub mod atmodem;
+mod line_reader;
+#[cfg(feature = "liveness")]
+pub mod xmodem;
+
+pub use async_writer::{AsyncWriter, Drain};
+pub use line_reader::{LineReader, ReadLineFuture};
 
 /// The console driver.
 ///
@@ -22,9 +37,19 @@ use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 /// let mut writer = Console::writer();
 /// writeln!(writer, foo).unwrap();
 /// ```
-pub struct Console<S: Syscalls, C: Config = DefaultConfig>(S, C);
+///
+/// Boards that expose a console-compatible driver under another number select
+/// it with `DRIVER_NUM`:
+/// ```ignore
+/// type Console = libtock_console::Console<TockSyscalls, DefaultConfig, 2137>;
+/// ```
+pub struct Console<
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+>(S, C);
 
-impl<S: Syscalls, C: Config> Console<S, C> {
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> Console<S, C, DRIVER_NUM> {
     /// Run a check against the console capsule to ensure it is present.
     ///
     /// Returns `true` if the driver was present. This does not necessarily mean
@@ -32,13 +57,119 @@ impl<S: Syscalls, C: Config> Console<S, C> {
     /// memory.
     #[inline(always)]
     pub fn exists() -> bool {
-        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+    }
+
+    /// Returns what the console driver reports about itself, such as its
+    /// version and features.
+    pub fn info() -> Result<DriverInfo, ErrorCode> {
+        DriverInfo::query::<S>(DRIVER_NUM)
+    }
+
+    /// Returns `true` if the driver can abort reads. The upstream console can,
+    /// and reports no features, while console-compatible drivers that report
+    /// features can only if they report `feature::ABORT`.
+    pub fn supports_abort() -> bool {
+        Self::info().is_ok_and(|info| info.features().is_none() || info.supports(feature::ABORT))
+    }
+
+    /// Returns the longest write the driver accepts, if it reports one with
+    /// `feature::MAX_LEN`.
+    pub fn max_write_len() -> Option<usize> {
+        if !Self::info().is_ok_and(|info| info.supports(feature::MAX_LEN)) {
+            return None;
+        }
+        S::checked_command::<C>(DRIVER_NUM, command::GET_MAX_LEN, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .ok()
+            .map(|max| max as usize)
+            .filter(|&max| max > 0)
     }
 
     /// Writes bytes.
     /// This is an alternative to `fmt::Write::write`
     /// because this can actually return an error code.
+    ///
+    /// If the driver rejects `s` as longer than its maximum, and reports that
+    /// maximum (see `max_write_len`), `s` is written in several writes.
     pub fn write(s: &[u8]) -> Result<(), ErrorCode> {
+        Self::write_split(s, |chunk| {
+            Self::write_with(chunk, S::yield_until).map(|_| ())
+        })
+    }
+
+    /// Writes all of `s`. Unlike `write`, this checks how many bytes the
+    /// driver reports writing, and writes the rest again until none is left.
+    /// Fails with `ErrorCode::Fail` if the driver writes no byte at all.
+    pub fn write_all(mut s: &[u8]) -> Result<(), ErrorCode> {
+        let mut max = usize::MAX;
+        while !s.is_empty() {
+            match Self::write_with(&s[..s.len().min(max)], S::yield_until) {
+                Err(ErrorCode::Size) if max == usize::MAX => max = Self::split_len(s)?,
+                Ok(0) => return Err(ErrorCode::Fail),
+                Ok(written) => s = &s[written.min(s.len())..],
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes bytes like `write`, but returns `ErrorCode::Busy` if the write
+    /// does not complete within the guard's maximum wait.
+    #[cfg(feature = "liveness")]
+    pub fn write_guarded(s: &[u8], guard: &LivenessGuard<S, C>) -> Result<(), ErrorCode> {
+        Self::write_split(s, |chunk| {
+            Self::write_with(chunk, |done| guard.wait(done)).map(|_| ())
+        })
+    }
+
+    /// Writes bytes like `write`, but returns `ErrorCode::Busy` if the write
+    /// does not complete within `timeout`, e.g. because the UART is wedged.
+    /// The write is then aborted if the driver reports `feature::ABORT_WRITE`;
+    /// otherwise it stays pending in the driver, which can no longer read `s`
+    /// or report the write once this returns.
+    #[cfg(feature = "liveness")]
+    pub fn write_timed<T: Convert>(s: &[u8], timeout: T) -> Result<(), ErrorCode> {
+        let guard = LivenessGuard::<S, C>::new(timeout)?;
+        Self::write_split(s, |chunk| {
+            Self::write_with(chunk, |done| {
+                guard.wait(done).inspect_err(|_| {
+                    if Self::info().is_ok_and(|info| info.supports(feature::ABORT_WRITE)) {
+                        let _ = S::checked_command::<C>(DRIVER_NUM, command::ABORT, 0, 0);
+                    }
+                })
+            })
+            .map(|_| ())
+        })
+    }
+
+    // Writes `s` with `write`. If the driver rejects it as too long, writes it
+    // again in chunks of the driver's maximum length.
+    fn write_split<W: FnMut(&[u8]) -> Result<(), ErrorCode>>(
+        s: &[u8],
+        mut write: W,
+    ) -> Result<(), ErrorCode> {
+        match write(s) {
+            Err(ErrorCode::Size) => s.chunks(Self::split_len(s)?).try_for_each(write),
+            result => result,
+        }
+    }
+
+    // Returns the length of the chunks to split `s`, which the driver rejected
+    // as too long, into. Fails with `ErrorCode::Size` if the driver does not
+    // report a shorter maximum.
+    fn split_len(s: &[u8]) -> Result<usize, ErrorCode> {
+        Self::max_write_len()
+            .filter(|&max| max < s.len())
+            .ok_or(ErrorCode::Size)
+    }
+
+    // Writes bytes, waiting for the write to complete with `wait`. Returns the
+    // number of bytes the driver wrote.
+    fn write_with<W: FnOnce(&mut dyn FnMut() -> bool) -> Result<(), ErrorCode>>(
+        s: &[u8],
+        wait: W,
+    ) -> Result<usize, ErrorCode> {
         let called: Cell<Option<(u32,)>> = Cell::new(None);
         share::scope::<
             (
@@ -54,14 +185,10 @@ impl<S: Syscalls, C: Config> Console<S, C> {
 
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::WRITE }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, command::WRITE, s.len() as u32, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, command::WRITE, s.len() as u32, 0).to_result()?;
 
-            loop {
-                S::yield_wait();
-                if let Some((_,)) = called.get() {
-                    return Ok(());
-                }
-            }
+            wait(&mut || called.get().is_some())?;
+            Ok(called.get().map_or(0, |(written,)| written as usize))
         })
     }
 
@@ -70,6 +197,78 @@ impl<S: Syscalls, C: Config> Console<S, C> {
     /// No special guarantees about when the read stops.
     /// Returns count of bytes written to `buf`.
     pub fn read(buf: &mut [u8]) -> (usize, Result<(), ErrorCode>) {
+        Self::read_with(buf, S::yield_until)
+    }
+
+    /// Reads a line into `buf`, until a newline or carriage return is received
+    /// or `buf` is full. Returns the count of bytes written to `buf`, which
+    /// include the line terminator, if one was received.
+    ///
+    /// Bytes are read one at a time, so that no byte following the terminator
+    /// is consumed. On error, the bytes received so far are in `buf`.
+    pub fn read_line(buf: &mut [u8]) -> (usize, Result<(), ErrorCode>) {
+        let mut count = 0;
+        while count < buf.len() {
+            let (received, r) = Self::read(&mut buf[count..count + 1]);
+            count += received;
+            if r.is_err() {
+                return (count, r);
+            }
+            if received > 0 && matches!(buf[count - 1], b'\n' | b'\r') {
+                break;
+            }
+        }
+        (count, Ok(()))
+    }
+
+    /// Reads the bytes already received into `buf`, without blocking, and
+    /// returns their count, which is 0 if there are none.
+    ///
+    /// This issues a read and polls the driver with `yield_no_wait`; if the
+    /// read does not complete right away, it is aborted, and this waits for
+    /// the upcall of the aborted read, which the driver sends right away.
+    /// Fails with `ErrorCode::NoSupport` if the driver cannot abort reads.
+    pub fn try_read(buf: &mut [u8]) -> Result<usize, ErrorCode> {
+        if !Self::supports_abort() {
+            return Err(ErrorCode::NoSupport);
+        }
+        let (count, r) = Self::read_with(buf, |done| {
+            if poll_until::<S>(done) {
+                return Ok(());
+            }
+            S::checked_command::<C>(DRIVER_NUM, command::ABORT, 0, 0).to_result::<(), _>()?;
+            // The aborted read reports the bytes it received in its upcall,
+            // which may be delivered after other upcalls.
+            S::yield_until(done)
+        });
+        match r {
+            Ok(()) | Err(ErrorCode::Cancel) => Ok(count),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads bytes like `read`, but returns `ErrorCode::Busy` if the read does
+    /// not complete within the guard's maximum wait. The read is then aborted,
+    /// if the driver supports it, and the bytes it received are lost.
+    #[cfg(feature = "liveness")]
+    pub fn read_guarded(
+        buf: &mut [u8],
+        guard: &LivenessGuard<S, C>,
+    ) -> (usize, Result<(), ErrorCode>) {
+        Self::read_with(buf, |done| {
+            guard.wait(done).inspect_err(|_| {
+                if Self::supports_abort() {
+                    let _ = S::checked_command::<C>(DRIVER_NUM, command::ABORT, 0, 0);
+                }
+            })
+        })
+    }
+
+    // Reads bytes, waiting for the read to complete with `wait`.
+    fn read_with<W: FnOnce(&mut dyn FnMut() -> bool) -> Result<(), ErrorCode>>(
+        buf: &mut [u8],
+        wait: W,
+    ) -> (usize, Result<(), ErrorCode>) {
         let called: Cell<Option<(u32, u32)>> = Cell::new(None);
         let mut bytes_received = 0;
         let r = share::scope::<
@@ -87,57 +286,197 @@ impl<S: Syscalls, C: Config> Console<S, C> {
 
             // When this fails, `called` is guaranteed unmodified,
             // because upcalls are never processed until we call `yield`.
-            S::command(DRIVER_NUM, command::READ, len as u32, 0).to_result()?;
-
-            loop {
-                S::yield_wait();
-                if let Some((status, bytes_pushed_count)) = called.get() {
-                    bytes_received = bytes_pushed_count as usize;
-                    return match status {
-                        0 => Ok(()),
-                        e_status => Err(e_status.try_into().unwrap_or(ErrorCode::Fail)),
-                    };
-                }
+            S::checked_command::<C>(DRIVER_NUM, command::READ, len as u32, 0).to_result()?;
+
+            wait(&mut || called.get().is_some())?;
+            let (status, bytes_pushed_count) = called.get().unwrap_or_default();
+            bytes_received = bytes_pushed_count as usize;
+            match status {
+                0 => Ok(()),
+                e_status => Err(e_status.try_into().unwrap_or(ErrorCode::Fail)),
             }
         });
         (bytes_received, r)
     }
 
-    pub fn writer() -> ConsoleWriter<S> {
+    /// Starts reading into `buf` in the background, to wait for the read along
+    /// with other events. The kernel can write `buf` until the end of the
+    /// handles' scope, and reports the status and byte count of the read into
+    /// `read`, e.g. for `libtock_future::wait_for_upcall`.
+    ///
+    /// Fails with `ErrorCode::Busy` if a read started in a previous scope is
+    /// still pending. `buf` and `read` are registered nevertheless, so that the
+    /// pending read completes into them.
+    pub fn read_start<'share>(
+        buf: &'share mut [u8],
+        read: &'share Cell<Option<(u32, u32)>>,
+        allow_rw: share::Handle<ReadBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<ReadSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Result<(), ErrorCode> {
+        let len = buf.len();
+        S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, buf)?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::READ }>(subscribe, read)?;
+        S::checked_command::<C>(DRIVER_NUM, command::READ, len as u32, 0).to_result()
+    }
+
+    /// Cancels the read started by `read_start`, e.g. before handing the
+    /// console over to another mode: aborts it, then unregisters its upcall
+    /// and revokes the kernel's access to its buffer, so that neither the
+    /// cancellation nor late input reach them. Does nothing if no read is
+    /// pending. Fails with `ErrorCode::NoSupport` if the driver cannot abort
+    /// reads.
+    ///
+    /// The end of the handles' scope also unregisters the upcall and revokes
+    /// the buffer, but leaves the read pending.
+    pub fn cancel_read() -> Result<(), ErrorCode> {
+        if !Self::supports_abort() {
+            return Err(ErrorCode::NoSupport);
+        }
+        S::checked_command::<C>(DRIVER_NUM, command::ABORT, 0, 0).to_result::<(), _>()?;
+        S::unsubscribe(DRIVER_NUM, subscribe::READ);
+        S::unallow_rw(DRIVER_NUM, allow_rw::READ);
+        Ok(())
+    }
+
+    /// Starts writing `s` in the background, to wait for the write along with
+    /// other events. The kernel can read `s` until the end of the handles'
+    /// scope, and reports the number of bytes written into `written`, e.g. for
+    /// `libtock_future::wait_for_upcall`.
+    pub fn write_start<'share>(
+        s: &'share [u8],
+        written: &'share Cell<Option<(u32,)>>,
+        allow_ro: share::Handle<WriteBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<WriteSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow_ro, s)?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::WRITE }>(subscribe, written)?;
+        S::checked_command::<C>(DRIVER_NUM, command::WRITE, s.len() as u32, 0).to_result()
+    }
+
+    /// Starts writing `s` like `write_start`, returning a future that
+    /// completes once the write is over, e.g. to `select` or `join` it with
+    /// alarm or radio futures. The future fails with `ErrorCode::Size` if the
+    /// driver wrote only part of `s`.
+    pub fn write_fut<'share>(
+        s: &'share [u8],
+        written: &'share Cell<Option<(u32,)>>,
+        allow_ro: share::Handle<WriteBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<WriteSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Result<WriteFuture<'share>, ErrorCode> {
+        Self::write_start(s, written, allow_ro, subscribe)?;
+        Ok(WriteFuture {
+            written: wait_for_upcall(written),
+            len: s.len() as u32,
+        })
+    }
+
+    pub fn writer() -> ConsoleWriter<S, C, DRIVER_NUM> {
         ConsoleWriter {
             syscalls: Default::default(),
         }
     }
 }
 
-pub struct ConsoleWriter<S: Syscalls> {
-    syscalls: PhantomData<S>,
+/// The data shared by `Console::write_start`.
+pub type WriteBuffer<'share, S, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM> =
+    AllowRo<'share, S, DRIVER_NUM, { allow_ro::WRITE }>;
+
+/// The subscription of `Console::write_start`.
+pub type WriteSubscribe<'share, S, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM> =
+    Subscribe<'share, S, DRIVER_NUM, { subscribe::WRITE }>;
+
+/// The future returned by [`Console::write_fut`].
+pub struct WriteFuture<'share> {
+    written: UpcallFuture<'share, (u32,)>,
+    len: u32,
 }
 
-impl<S: Syscalls> fmt::Write for ConsoleWriter<S> {
+impl<S: Syscalls> TockFuture<S> for WriteFuture<'_> {
+    type Output = Result<(), ErrorCode>;
+
+    fn poll(&mut self) -> Poll<Result<(), ErrorCode>> {
+        match TockFuture::<S>::poll(&mut self.written) {
+            Poll::Ready((written,)) if written < self.len => Poll::Ready(Err(ErrorCode::Size)),
+            Poll::Ready(_) => Poll::Ready(Ok(())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The buffer shared by `Console::read_start`.
+pub type ReadBuffer<'share, S, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM> =
+    AllowRw<'share, S, DRIVER_NUM, { allow_rw::READ }>;
+
+/// The subscription of `Console::read_start`.
+pub type ReadSubscribe<'share, S, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM> =
+    Subscribe<'share, S, DRIVER_NUM, { subscribe::READ }>;
+
+pub struct ConsoleWriter<
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> fmt::Write for ConsoleWriter<S, C, DRIVER_NUM> {
     fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
-        Console::<S>::write(s.as_bytes()).map_err(|_e| fmt::Error)
+        Console::<S, C, DRIVER_NUM>::write(s.as_bytes()).map_err(|_e| fmt::Error)
+    }
+}
+
+// Handles the queued upcalls without blocking, and returns whether `done`
+// returns `true` then.
+fn poll_until<S: Syscalls>(done: &mut dyn FnMut() -> bool) -> bool {
+    while !done() {
+        if S::yield_no_wait() == YieldNoWaitReturn::NoUpcall {
+            return done();
+        }
     }
+    true
 }
 
+/// An estimate of the stack the deepest call of this crate uses, for
+/// `stack_size!` hints. Formatted writes take most of it.
+pub const STACK_USAGE: usize = 0x80;
+
 /// System call configuration trait for `Console`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
+#[cfg(all(test, feature = "liveness"))]
+mod atmodem_tests;
+
+#[cfg(test)]
+mod async_writer_tests;
+
+#[cfg(test)]
+mod line_reader_tests;
+
 #[cfg(test)]
 mod tests;
 
+#[cfg(all(test, feature = "liveness"))]
+mod xmodem_tests;
+
 // -----------------------------------------------------------------------------
 // Driver number and command IDs
 // -----------------------------------------------------------------------------
 
-const DRIVER_NUM: u32 = 1;
+const DEFAULT_DRIVER_NUM: u32 = 1;
 
 // Command IDs
 #[allow(unused)]
@@ -146,6 +485,20 @@ mod command {
     pub const WRITE: u32 = 1;
     pub const READ: u32 = 2;
     pub const ABORT: u32 = 3;
+    pub const GET_MAX_LEN: u32 = 4;
+}
+
+/// The feature bits a console-compatible driver may report, see
+/// `Console::info`.
+pub mod feature {
+    /// The driver can abort reads.
+    pub const ABORT: u32 = 1 << 0;
+    /// The driver's ABORT command also aborts a pending write, whose upcall
+    /// then reports the bytes written so far.
+    pub const ABORT_WRITE: u32 = 1 << 1;
+    /// The driver has a maximum write length, which it answers command 4
+    /// with, and rejects longer writes with `ErrorCode::Size`.
+    pub const MAX_LEN: u32 = 1 << 2;
 }
 
 #[allow(unused)]
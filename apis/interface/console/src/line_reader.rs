@@ -0,0 +1,272 @@
+use crate::{Config, Console, ReadBuffer, ReadSubscribe, DEFAULT_DRIVER_NUM};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::{wait_for_upcall, TockFuture, UpcallFuture};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+#[cfg(feature = "liveness")]
+use libtock_alarm::LivenessGuard;
+
+/// Reads the console a line at a time, e.g. for a shell, or for peripherals
+/// attached to the console UART that speak a line-based protocol, such as
+/// NMEA or AT commands.
+///
+/// A line ends with a carriage return, a line feed, or both, and is returned
+/// without its terminator. Backspace and delete remove the last byte of the
+/// line. With [`echo`](Self::echo), the bytes kept in the line are written
+/// back, and each line end as "\r\n", as terminals expect.
+///
+/// # Example
+/// ```ignore
+/// use libtock::console::LineReader;
+///
+/// let mut reader = LineReader::<64>::new().echo(true);
+/// loop {
+///     match reader.read_line() {
+///         Ok("reboot") => reboot(),
+///         Ok(line) => run(line),
+///         Err(ErrorCode::Size) => writeln!(Console::writer(), "line too long")?,
+///         Err(error) => return Err(error),
+///     }
+/// }
+/// ```
+///
+/// [`read_line_fut`](Self::read_line_fut) reads a line as a future instead,
+/// e.g. to `select` it with alarm or radio futures.
+pub struct LineReader<
+    S: Syscalls,
+    const N: usize,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    buf: [u8; N],
+    len: usize,
+    echo: bool,
+    // Whether bytes were dropped from the current line because it is longer
+    // than `N`.
+    overflow: bool,
+    // Whether the current line was returned, so the next read starts anew.
+    done: bool,
+    // Whether the last byte was a carriage return, so that a line feed
+    // following it does not end another line.
+    after_cr: bool,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32>
+    LineReader<S, N, C, DRIVER_NUM>
+{
+    /// Creates a reader of lines of up to `N` bytes, which does not echo.
+    pub fn new() -> Self {
+        LineReader {
+            buf: [0; N],
+            len: 0,
+            echo: false,
+            overflow: false,
+            done: false,
+            after_cr: false,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Sets whether the bytes read are written back to the console.
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// Reads the next line. Fails with `ErrorCode::Size` if the line is longer
+    /// than `N` bytes, once it ends, and with `ErrorCode::Invalid` if it is not
+    /// UTF-8.
+    ///
+    /// Bytes are read one at a time, so that no byte following the line is
+    /// consumed. If the console fails, the bytes received so far are kept, and
+    /// the next call continues the line.
+    pub fn read_line(&mut self) -> Result<&str, ErrorCode> {
+        self.read_line_with(|byte| Console::<S, C, DRIVER_NUM>::read(byte))
+    }
+
+    /// Reads the next line like `read_line`, but returns `ErrorCode::Busy` if
+    /// no byte arrives within the guard's maximum wait. Each byte restarts the
+    /// wait. The bytes received so far are kept for the next call.
+    #[cfg(feature = "liveness")]
+    pub fn read_line_guarded(&mut self, guard: &LivenessGuard<S, C>) -> Result<&str, ErrorCode> {
+        self.read_line_with(|byte| Console::<S, C, DRIVER_NUM>::read_guarded(byte, guard))
+    }
+
+    /// Returns the future that reads the next line like `read_line`, e.g. to
+    /// `select` it with other futures. Each byte is read into `byte`, and the
+    /// status of its read is reported into `read`. Echoed bytes are written
+    /// with blocking writes.
+    ///
+    /// Dropping the future before it completes cancels its pending read with
+    /// `Console::cancel_read`, if the driver supports it. The bytes received
+    /// so far are kept for the next call.
+    pub fn read_line_fut<'handle, 'share>(
+        &'share mut self,
+        byte: &'share mut [u8; 1],
+        read: &'share Cell<Option<(u32, u32)>>,
+        allow_rw: share::Handle<'handle, ReadBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<'handle, ReadSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> ReadLineFuture<'handle, 'share, S, N, C, DRIVER_NUM> {
+        self.start_line();
+        ReadLineFuture {
+            reader: Some(self),
+            byte: byte.as_mut_ptr(),
+            read,
+            allow_rw,
+            subscribe,
+            pending: None,
+        }
+    }
+
+    // Reads the next line, reading each byte with `read`.
+    fn read_line_with<R: FnMut(&mut [u8]) -> (usize, Result<(), ErrorCode>)>(
+        &mut self,
+        mut read: R,
+    ) -> Result<&str, ErrorCode> {
+        self.start_line();
+        loop {
+            let mut byte = [0];
+            let (count, result) = read(&mut byte);
+            result?;
+            if count == 1 && self.push(byte[0])? {
+                return self.end_line();
+            }
+        }
+    }
+
+    // Starts a new line, unless the current one was not returned yet.
+    fn start_line(&mut self) {
+        if self.done {
+            (self.len, self.overflow, self.done) = (0, false, false);
+        }
+    }
+
+    // Adds `byte` to the line, and returns whether it ends the line.
+    fn push(&mut self, byte: u8) -> Result<bool, ErrorCode> {
+        let after_cr = core::mem::replace(&mut self.after_cr, byte == b'\r');
+        match byte {
+            b'\n' if after_cr => {}
+            b'\r' | b'\n' => return Ok(true),
+            BACKSPACE | DELETE if self.len > 0 => {
+                self.len -= 1;
+                self.write_echo(b"\x08 \x08")?;
+            }
+            BACKSPACE | DELETE => {}
+            _ if self.len == N => self.overflow = true,
+            byte => {
+                self.buf[self.len] = byte;
+                self.len += 1;
+                self.write_echo(&[byte])?;
+            }
+        }
+        Ok(false)
+    }
+
+    // Returns the line that just ended.
+    fn end_line(&mut self) -> Result<&str, ErrorCode> {
+        self.done = true;
+        self.write_echo(b"\r\n")?;
+        if self.overflow {
+            return Err(ErrorCode::Size);
+        }
+        core::str::from_utf8(&self.buf[..self.len]).map_err(|_| ErrorCode::Invalid)
+    }
+
+    fn write_echo(&self, bytes: &[u8]) -> Result<(), ErrorCode> {
+        match self.echo {
+            true => Console::<S, C, DRIVER_NUM>::write(bytes),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32> Default
+    for LineReader<S, N, C, DRIVER_NUM>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The future returned by [`LineReader::read_line_fut`]. It completes with the
+/// line, or with the error `read_line` would return.
+pub struct ReadLineFuture<
+    'handle,
+    'share,
+    S: Syscalls,
+    const N: usize,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    // Taken once the line ends, to return the line for the rest of 'share.
+    reader: Option<&'share mut LineReader<S, N, C, DRIVER_NUM>>,
+    // The byte being read, which is not kept as a reference, because the
+    // kernel writes it while the future holds it.
+    byte: *mut u8,
+    read: &'share Cell<Option<(u32, u32)>>,
+    allow_rw: share::Handle<'handle, ReadBuffer<'share, S, DRIVER_NUM>>,
+    subscribe: share::Handle<'handle, ReadSubscribe<'share, S, DRIVER_NUM>>,
+    // The upcall of the pending read, if any.
+    pending: Option<UpcallFuture<'share, (u32, u32)>>,
+}
+
+impl<'share, S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32> TockFuture<S>
+    for ReadLineFuture<'_, 'share, S, N, C, DRIVER_NUM>
+{
+    type Output = Result<&'share str, ErrorCode>;
+
+    fn poll(&mut self) -> Poll<Result<&'share str, ErrorCode>> {
+        let Some(reader) = self.reader.as_mut() else {
+            panic!("ReadLineFuture polled after completion");
+        };
+        if let Some(pending) = &mut self.pending {
+            let Poll::Ready((status, count)) = TockFuture::<S>::poll(pending) else {
+                return Poll::Pending;
+            };
+            self.pending = None;
+            // Revokes the byte, to allow it again for the next read.
+            S::unallow_rw(DRIVER_NUM, crate::allow_rw::READ);
+            if status != 0 {
+                return Poll::Ready(Err(status.try_into().unwrap_or(ErrorCode::Fail)));
+            }
+            if count == 1 {
+                // Safety: The byte is revoked, so the kernel no longer writes
+                // it.
+                let byte = unsafe { self.byte.read() };
+                match reader.push(byte) {
+                    Ok(false) => {}
+                    Ok(true) => return Poll::Ready(self.reader.take().unwrap().end_line()),
+                    Err(error) => return Poll::Ready(Err(error)),
+                }
+            }
+        }
+        // Safety: `byte` comes from a `&'share mut [u8; 1]`, which only this
+        // future uses, and is not allowed to the kernel at this point.
+        let buf = unsafe { core::slice::from_raw_parts_mut(self.byte, 1) };
+        let started =
+            Console::<S, C, DRIVER_NUM>::read_start(buf, self.read, self.allow_rw, self.subscribe);
+        match started {
+            Ok(()) => {
+                self.pending = Some(wait_for_upcall(self.read));
+                Poll::Pending
+            }
+            Err(error) => Poll::Ready(Err(error)),
+        }
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config, const DRIVER_NUM: u32> Drop
+    for ReadLineFuture<'_, '_, S, N, C, DRIVER_NUM>
+{
+    fn drop(&mut self) {
+        if self.pending.is_some() {
+            let _ = Console::<S, C, DRIVER_NUM>::cancel_read();
+        }
+    }
+}
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7F;
@@ -0,0 +1,137 @@
+use core::cell::Cell;
+use libtock_future::{block_on, select, Either};
+use libtock_platform::{share, ErrorCode};
+use libtock_unittest::fake;
+
+use crate::{ReadBuffer, ReadSubscribe, WriteBuffer, WriteSubscribe};
+
+type Console = crate::Console<fake::Syscalls>;
+type LineReader<const N: usize> = crate::LineReader<fake::Syscalls, N>;
+
+#[test]
+fn read_line() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"ab\r\ncd\nef\r");
+    kernel.add_driver(&driver);
+
+    let mut reader = LineReader::<8>::new();
+    assert_eq!(reader.read_line(), Ok("ab"));
+    assert_eq!(reader.read_line(), Ok("cd"));
+    assert_eq!(reader.read_line(), Ok("ef"));
+    // The line feed of a CRLF that spans two reads ends no line.
+    driver.queue_input(b"\n\ngh\n");
+    assert_eq!(reader.read_line(), Ok(""));
+    assert_eq!(reader.read_line(), Ok("gh"));
+    assert_eq!(driver.take_bytes(), b"");
+}
+
+#[test]
+fn echo() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"\x7fa\x7fbc\x08\r");
+    kernel.add_driver(&driver);
+
+    let mut reader = LineReader::<8>::new().echo(true);
+    assert_eq!(reader.read_line(), Ok("b"));
+    assert_eq!(driver.take_bytes(), b"a\x08 \x08bc\x08 \x08\r\n");
+}
+
+#[test]
+fn invalid_lines() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"abcd\nab\n\xff\nok\n");
+    kernel.add_driver(&driver);
+
+    let mut reader = LineReader::<2>::new();
+    // The rest of a line too long is dropped.
+    assert_eq!(reader.read_line(), Err(ErrorCode::Size));
+    assert_eq!(reader.read_line(), Ok("ab"));
+    assert_eq!(reader.read_line(), Err(ErrorCode::Invalid));
+    assert_eq!(reader.read_line(), Ok("ok"));
+}
+
+#[cfg(feature = "liveness")]
+#[test]
+fn read_line_guarded() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"ab");
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+    kernel.add_driver(&alarm);
+    let guard = libtock_alarm::LivenessGuard::new(libtock_alarm::Milliseconds(100)).unwrap();
+    kernel.set_idle_handler({
+        let alarm = alarm.clone();
+        move || alarm.advance_ticks(100)
+    });
+
+    let mut reader = LineReader::<8>::new();
+    assert_eq!(reader.read_line_guarded(&guard), Err(ErrorCode::Busy));
+    // The bytes received before the timeout start the next line.
+    driver.queue_input(b"c\n");
+    assert_eq!(reader.read_line_guarded(&guard), Ok("abc"));
+}
+
+#[test]
+fn read_line_fut() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"ab\x7f\r");
+    kernel.add_driver(&driver);
+
+    let mut reader = LineReader::<8>::new().echo(true);
+    let (mut byte, read) = ([0], Cell::new(None));
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        let line = reader.read_line_fut(&mut byte, &read, allow_rw, subscribe);
+        assert_eq!(block_on(line), Ok("a"));
+    });
+    // The line feed of the CRLF ends no line.
+    driver.queue_input(b"\ncd\n");
+    let (mut byte, read) = ([0], Cell::new(None));
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        let line = reader.read_line_fut(&mut byte, &read, allow_rw, subscribe);
+        assert_eq!(block_on(line), Ok("cd"));
+    });
+    assert_eq!(driver.take_bytes(), b"ab\x08 \x08\r\ncd\r\n");
+    assert_eq!(driver.pending_read(), None);
+}
+
+#[test]
+fn read_line_fut_cancelled() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    // Makes the console support aborting reads.
+    driver.set_max_write_len(Some(64));
+    kernel.add_driver(&driver);
+
+    let mut reader = LineReader::<8>::new();
+    let (mut byte, read, written) = ([0], Cell::new(None), Cell::new(None));
+    share::scope::<
+        (
+            ReadBuffer<_>,
+            ReadSubscribe<_>,
+            WriteBuffer<_>,
+            WriteSubscribe<_>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, read_subscribe, allow_ro, write_subscribe) = handle.split();
+        let write = Console::write_fut(b"prompt", &written, allow_ro, write_subscribe).unwrap();
+        let line = reader.read_line_fut(&mut byte, &read, allow_rw, read_subscribe);
+        assert!(matches!(
+            block_on(select(line, write)),
+            Either::Right(Ok(()))
+        ));
+    });
+    // The dropped future cancelled its read.
+    assert_eq!(driver.pending_read(), None);
+
+    driver.queue_input(b"ok\n");
+    let (mut byte, read) = ([0], Cell::new(None));
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        let line = reader.read_line_fut(&mut byte, &read, allow_rw, subscribe);
+        assert_eq!(block_on(line), Ok("ok"));
+    });
+}
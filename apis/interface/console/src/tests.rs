@@ -21,6 +21,26 @@ fn exists() {
     assert_eq!(driver.take_bytes(), &[]);
 }
 
+#[test]
+fn supports_abort() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+
+    // The upstream console reports no features.
+    assert!(Console::supports_abort());
+    for (features, supported) in [(feature::ABORT, true), (0, false)] {
+        kernel.add_expected_syscall(ExpectedSyscall::Command {
+            driver_id: DEFAULT_DRIVER_NUM,
+            command_id: command::EXISTS,
+            argument0: 0,
+            argument1: 0,
+            override_return: Some(command_return::success_2_u32(2, features)),
+        });
+        assert_eq!(Console::supports_abort(), supported);
+    }
+}
+
 #[test]
 fn write_bytes() {
     let kernel = fake::Kernel::new();
@@ -32,6 +52,43 @@ fn write_bytes() {
     assert_eq!(driver.take_bytes(), b"foobar",);
 }
 
+#[test]
+fn write_all() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+
+    // `write` drops the bytes the driver did not write.
+    driver.set_write_chunk_size(Some(2));
+    Console::write(b"abcde").unwrap();
+    assert_eq!(driver.take_bytes(), b"ab");
+    Console::write_all(b"abcde").unwrap();
+    assert_eq!(driver.take_bytes(), b"abcde");
+    Console::write_all(b"").unwrap();
+
+    driver.set_write_chunk_size(Some(0));
+    assert_eq!(Console::write_all(b"abc"), Err(ErrorCode::Fail));
+}
+
+#[test]
+fn write_split() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    assert_eq!(Console::max_write_len(), None);
+
+    // Writes longer than the maximum are split.
+    driver.set_max_write_len(Some(4));
+    assert_eq!(Console::max_write_len(), Some(4));
+    Console::write(b"0123456789").unwrap();
+    assert_eq!(driver.take_bytes(), b"0123456789");
+    Console::write_all(b"0123456789").unwrap();
+    assert_eq!(driver.take_bytes(), b"0123456789");
+    let line = "long line";
+    writeln!(Console::writer(), "{line}").unwrap();
+    assert_eq!(driver.take_bytes(), b"long line\n");
+}
+
 #[test]
 fn write_str() {
     let kernel = fake::Kernel::new();
@@ -72,23 +129,79 @@ fn read_bytes_alot() {
     assert_eq!(&buf[..count], b" Alot");
 }
 
+#[test]
+fn read_line() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"help\nstatus\rlonger line");
+    kernel.add_driver(&driver);
+
+    let mut buf = [0; 8];
+    let (count, res) = Console::read_line(&mut buf);
+    res.unwrap();
+    assert_eq!(&buf[..count], b"help\n");
+    let (count, res) = Console::read_line(&mut buf);
+    res.unwrap();
+    assert_eq!(&buf[..count], b"status\r");
+    // A line longer than the buffer fills it.
+    let (count, res) = Console::read_line(&mut buf);
+    res.unwrap();
+    assert_eq!(&buf[..count], b"longer l");
+    assert_eq!(driver.pending_read(), None);
+}
+
+#[test]
+fn try_read() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+
+    // Without input, the read is aborted, and the console can read again.
+    let mut buf = [0; 4];
+    assert_eq!(Console::try_read(&mut buf), Ok(0));
+    assert_eq!(driver.pending_read(), None);
+    driver.queue_input(b"ab");
+    assert_eq!(Console::try_read(&mut buf), Ok(2));
+    assert_eq!(&buf[..2], b"ab");
+    assert_eq!(Console::try_read(&mut buf), Ok(0));
+}
+
+#[test]
+fn read_bytes_chunked() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_input(b"Hello");
+    driver.set_read_chunk_size(Some(2));
+    kernel.add_driver(&driver);
+
+    let mut buf = [0; 10];
+
+    let (count, res) = Console::read(&mut buf);
+    res.unwrap();
+    assert_eq!(&buf[..count], b"He");
+
+    driver.queue_input(b" there");
+    driver.set_read_chunk_size(None);
+    let (count, res) = Console::read(&mut buf);
+    res.unwrap();
+    assert_eq!(&buf[..count], b"llo there");
+}
+
 #[test]
 fn failed_print() {
     let kernel = fake::Kernel::new();
     let driver = fake::Console::new();
     kernel.add_driver(&driver);
     kernel.add_expected_syscall(ExpectedSyscall::AllowRo {
-        driver_num: DRIVER_NUM,
+        driver_num: DEFAULT_DRIVER_NUM,
         buffer_num: allow_ro::WRITE,
         return_error: None,
     });
     kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
+        driver_num: DEFAULT_DRIVER_NUM,
         subscribe_num: subscribe::WRITE,
         skip_with_error: None,
     });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
-        driver_id: DRIVER_NUM,
+        driver_id: DEFAULT_DRIVER_NUM,
         command_id: command::WRITE,
         argument0: 5,
         argument1: 0,
@@ -106,17 +219,17 @@ fn failed_read() {
     let driver = fake::Console::new_with_input(b"bugxxxx");
     kernel.add_driver(&driver);
     kernel.add_expected_syscall(ExpectedSyscall::AllowRw {
-        driver_num: DRIVER_NUM,
+        driver_num: DEFAULT_DRIVER_NUM,
         buffer_num: allow_rw::READ,
         return_error: None,
     });
     kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
-        driver_num: DRIVER_NUM,
+        driver_num: DEFAULT_DRIVER_NUM,
         subscribe_num: subscribe::READ,
         skip_with_error: None,
     });
     kernel.add_expected_syscall(ExpectedSyscall::Command {
-        driver_id: DRIVER_NUM,
+        driver_id: DEFAULT_DRIVER_NUM,
         command_id: command::READ,
         argument0: 3,
         argument1: 0,
@@ -129,3 +242,183 @@ fn failed_read() {
     assert_eq!(res, Err(ErrorCode::Fail));
     assert_eq!(count, 0);
 }
+
+#[test]
+fn driver_num_override() {
+    type Overridden = super::Console<fake::Syscalls, DefaultConfig, 2137>;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_driver_num(b"in", 2137);
+    kernel.add_driver(&driver);
+
+    assert!(!Console::exists());
+    assert!(Overridden::exists());
+    writeln!(Overridden::writer(), "out").unwrap();
+    assert_eq!(driver.take_bytes(), b"out\n");
+    let mut buf = [0; 2];
+    assert_eq!(Overridden::read(&mut buf), (2, Ok(())));
+    assert_eq!(&buf, b"in");
+}
+
+#[test]
+fn read_start() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let mut buf = [0; 4];
+    let read = Cell::new(None);
+
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(
+            Console::read_start(&mut buf, &read, allow_rw, subscribe),
+            Ok(())
+        );
+    });
+    assert_eq!(driver.pending_read(), Some(4));
+
+    // The read is still pending, and completes into the buffer shared then.
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(
+            Console::read_start(&mut buf, &read, allow_rw, subscribe),
+            Err(ErrorCode::Busy)
+        );
+        driver.queue_input(b"hi");
+        assert!(fake::Syscalls::yield_no_wait_flag());
+    });
+    assert_eq!(read.get(), Some((0, 2)));
+    assert_eq!(&buf[..2], b"hi");
+}
+
+#[test]
+fn cancel_read() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    kernel.add_driver(&driver);
+    let mut buf = [0; 4];
+    let read = Cell::new(None);
+
+    share::scope::<(ReadBuffer<_>, ReadSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(
+            Console::read_start(&mut buf, &read, allow_rw, subscribe),
+            Ok(())
+        );
+        assert_eq!(Console::cancel_read(), Ok(()));
+        assert_eq!(driver.pending_read(), None);
+        // The cancellation does not reach `read`.
+        assert!(!fake::Syscalls::yield_no_wait_flag());
+    });
+    assert_eq!(read.get(), None);
+    // Nothing is pending, and later input goes to the next read.
+    assert_eq!(Console::cancel_read(), Ok(()));
+    driver.queue_input(b"hi");
+    assert_eq!(Console::read(&mut buf), (2, Ok(())));
+
+    kernel.add_expected_syscall(ExpectedSyscall::Command {
+        driver_id: DEFAULT_DRIVER_NUM,
+        command_id: command::EXISTS,
+        argument0: 0,
+        argument1: 0,
+        override_return: Some(command_return::success_2_u32(2, 0)),
+    });
+    assert_eq!(Console::cancel_read(), Err(ErrorCode::NoSupport));
+}
+
+#[cfg(feature = "liveness")]
+#[test]
+fn write_guarded() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+    kernel.add_driver(&alarm);
+    let guard = libtock_alarm::LivenessGuard::new(libtock_alarm::Milliseconds(100)).unwrap();
+
+    assert_eq!(Console::write_guarded(b"foo", &guard), Ok(()));
+    assert_eq!(driver.take_bytes(), b"foo");
+    assert_eq!(alarm.expiration(), None);
+}
+
+#[cfg(feature = "liveness")]
+#[test]
+fn write_timed() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+    kernel.add_driver(&alarm);
+
+    assert_eq!(
+        Console::write_timed(b"foo", libtock_alarm::Milliseconds(100)),
+        Ok(())
+    );
+    assert_eq!(driver.take_bytes(), b"foo");
+    assert_eq!(alarm.expiration(), None);
+
+    // The UART is wedged, so the write never completes.
+    driver.set_write_stalled(true);
+    kernel.set_idle_handler({
+        let alarm = alarm.clone();
+        move || alarm.advance_ticks(100)
+    });
+    assert_eq!(
+        Console::write_timed(b"bar", libtock_alarm::Milliseconds(100)),
+        Err(ErrorCode::Busy)
+    );
+    assert_eq!(driver.take_bytes(), b"");
+
+    driver.set_write_stalled(false);
+    assert_eq!(Console::write(b"baz"), Ok(()));
+    assert_eq!(driver.take_bytes(), b"baz");
+}
+
+#[cfg(feature = "liveness")]
+#[test]
+fn read_guarded() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+    kernel.add_driver(&alarm);
+    let guard = libtock_alarm::LivenessGuard::new(libtock_alarm::Milliseconds(100)).unwrap();
+
+    // The input arrives before the deadline.
+    kernel.set_idle_handler({
+        let driver = driver.clone();
+        let alarm = alarm.clone();
+        move || {
+            alarm.advance_ticks(50);
+            driver.queue_input(b"in");
+        }
+    });
+    let mut buf = [0; 2];
+    assert_eq!(Console::read_guarded(&mut buf, &guard), (2, Ok(())));
+    assert_eq!(&buf, b"in");
+    assert_eq!(alarm.expiration(), None);
+}
+
+#[cfg(feature = "liveness")]
+#[test]
+fn read_guarded_timeout() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+    kernel.add_driver(&alarm);
+    let guard = libtock_alarm::LivenessGuard::new(libtock_alarm::Milliseconds(100)).unwrap();
+
+    // No input arrives.
+    kernel.set_idle_handler({
+        let alarm = alarm.clone();
+        move || alarm.advance_ticks(100)
+    });
+    let mut buf = [0; 2];
+    assert_eq!(
+        Console::read_guarded(&mut buf, &guard),
+        (0, Err(ErrorCode::Busy))
+    );
+    // The read was aborted, so the console can read again.
+    driver.queue_input(b"in");
+    assert_eq!(Console::read(&mut buf), (2, Ok(())));
+}
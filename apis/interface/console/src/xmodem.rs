@@ -0,0 +1,293 @@
+//! Transfers of binary blobs over the console with the XMODEM-CRC protocol,
+//! which most terminal programs (`sx`/`rx`, minicom, Tera Term, ...) speak,
+//! e.g. to load a configuration or a firmware image into a board without a
+//! radio.
+//!
+//! The blob is sent in 128-byte blocks, each numbered and protected by a
+//! CRC-16. The receiver acknowledges each block, or asks for it again if it is
+//! corrupted or does not arrive in time, and the transfer is abandoned after
+//! `MAX_RETRIES` attempts in a row. XMODEM does not transfer the blob's length,
+//! so the sender pads the last block with `PAD` bytes, which the receiver
+//! keeps.
+//!
+//! The timeouts are those of the `LivenessGuard` the transfer waits with: a few
+//! seconds, as the protocol expects, suit most hosts.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::alarm::{LivenessGuard, Milliseconds};
+//! use libtock::console::Xmodem;
+//!
+//! let guard = LivenessGuard::new(Milliseconds(3000))?;
+//! let mut image = [0; 16 * 1024];
+//! let len = Xmodem::new(&guard).receive(&mut image[..])?;
+//! flash(&image[..len])?;
+//! ```
+
+use crate::{Config, Console, DEFAULT_DRIVER_NUM};
+use libtock_alarm::LivenessGuard;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The length of a block.
+pub const BLOCK_LEN: usize = 128;
+
+/// The byte the last block is padded with (ASCII SUB).
+pub const PAD: u8 = 0x1a;
+
+/// The number of attempts at each step of a transfer.
+pub const MAX_RETRIES: u32 = 10;
+
+/// The destination of a received blob, e.g. a RAM buffer or a storage driver.
+pub trait Sink {
+    /// Writes a block received at `offset` in the blob. The transfer is
+    /// cancelled if this fails.
+    fn write(&mut self, offset: usize, block: &[u8]) -> Result<(), ErrorCode>;
+}
+
+/// A RAM buffer receives the blob at its start. A blob that does not fit fails
+/// with `ErrorCode::Size`.
+impl Sink for [u8] {
+    fn write(&mut self, offset: usize, block: &[u8]) -> Result<(), ErrorCode> {
+        self.get_mut(offset..offset + block.len())
+            .ok_or(ErrorCode::Size)?
+            .copy_from_slice(block);
+        Ok(())
+    }
+}
+
+/// The longest key prefix a `KeyValueSink` accepts.
+#[cfg(feature = "key_value")]
+pub const MAX_PREFIX_LEN: usize = 28;
+
+/// Stores the blob in the key-value store, one value per block, so that blobs
+/// larger than RAM can be received. Block `i` is stored under the key prefix
+/// followed by `i` as a little-endian `u32`, and replaces any previous value.
+#[cfg(feature = "key_value")]
+pub struct KeyValueSink<'p, S: Syscalls, C: libtock_key_value::Config = DefaultConfig> {
+    prefix: &'p [u8],
+    _syscalls: core::marker::PhantomData<(S, C)>,
+}
+
+#[cfg(feature = "key_value")]
+impl<'p, S: Syscalls, C: libtock_key_value::Config> KeyValueSink<'p, S, C> {
+    /// Fails with `ErrorCode::Size` if `prefix` is longer than
+    /// `MAX_PREFIX_LEN`.
+    pub fn new(prefix: &'p [u8]) -> Result<Self, ErrorCode> {
+        if prefix.len() > MAX_PREFIX_LEN {
+            return Err(ErrorCode::Size);
+        }
+        Ok(KeyValueSink {
+            prefix,
+            _syscalls: core::marker::PhantomData,
+        })
+    }
+
+    /// Returns the key block `index` is stored under, in `key`.
+    pub fn key<'k>(&self, index: u32, key: &'k mut [u8; MAX_PREFIX_LEN + 4]) -> &'k [u8] {
+        let len = self.prefix.len();
+        key[..len].copy_from_slice(self.prefix);
+        key[len..len + 4].copy_from_slice(&index.to_le_bytes());
+        &key[..len + 4]
+    }
+}
+
+#[cfg(feature = "key_value")]
+impl<S: Syscalls, C: libtock_key_value::Config> Sink for KeyValueSink<'_, S, C> {
+    fn write(&mut self, offset: usize, block: &[u8]) -> Result<(), ErrorCode> {
+        let mut key = [0; MAX_PREFIX_LEN + 4];
+        let key = self.key((offset / BLOCK_LEN) as u32, &mut key);
+        libtock_key_value::KeyValue::<S, C>::set(key, block)
+    }
+}
+
+/// An XMODEM-CRC endpoint on the console numbered `DRIVER_NUM`, which waits for
+/// the peer with `guard`.
+pub struct Xmodem<
+    'g,
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+> {
+    guard: &'g LivenessGuard<S, C>,
+}
+
+impl<'g, S: Syscalls, C: Config, const DRIVER_NUM: u32> Xmodem<'g, S, C, DRIVER_NUM> {
+    pub fn new(guard: &'g LivenessGuard<S, C>) -> Self {
+        Xmodem { guard }
+    }
+
+    /// Receives a blob into `sink`, and returns its length, including the
+    /// padding of the last block.
+    ///
+    /// Fails with `ErrorCode::Busy` if the sender stops responding, with
+    /// `ErrorCode::Fail` if it keeps sending corrupted blocks, and with
+    /// `ErrorCode::Cancel` if it cancels the transfer.
+    pub fn receive<K: Sink + ?Sized>(&self, sink: &mut K) -> Result<usize, ErrorCode> {
+        let mut len = 0;
+        let mut block = 1u8;
+        // Until the first block arrives, the sender is asked to start in CRC
+        // mode.
+        let mut reply = Some(CRC_MODE);
+        let mut failures = 0;
+        let mut error = ErrorCode::Busy;
+        while failures < MAX_RETRIES {
+            if let Some(reply) = reply.take() {
+                self.write(&[reply])?;
+            }
+            let mut packet = [0; PACKET_LEN];
+            let result = self
+                .read_exact(&mut packet[..1])
+                .and_then(|()| match packet[0] {
+                    SOH => self.read_exact(&mut packet[1..]),
+                    _ => Ok(()),
+                });
+            if let Err(read_error) = result {
+                if read_error != ErrorCode::Busy {
+                    return Err(read_error);
+                }
+                failures += 1;
+                error = ErrorCode::Busy;
+                reply = Some(if len == 0 { CRC_MODE } else { NAK });
+                continue;
+            }
+            match packet[0] {
+                SOH => {}
+                EOT => {
+                    self.write(&[ACK])?;
+                    return Ok(len);
+                }
+                CAN => return Err(ErrorCode::Cancel),
+                // Noise between blocks.
+                _ => continue,
+            }
+            let (number, complement) = (packet[1], packet[2]);
+            let data = &packet[3..3 + BLOCK_LEN];
+            let crc = u16::from_be_bytes([packet[PACKET_LEN - 2], packet[PACKET_LEN - 1]]);
+            if number != !complement || crc != crc16(data) {
+                failures += 1;
+                error = ErrorCode::Fail;
+                reply = Some(NAK);
+                continue;
+            }
+            failures = 0;
+            reply = Some(ACK);
+            if number == block.wrapping_sub(1) && len > 0 {
+                // The sender missed our ACK, and sent the block again.
+                continue;
+            }
+            if number != block {
+                // The blocks are out of sync, which cannot be recovered from.
+                self.cancel();
+                return Err(ErrorCode::Fail);
+            }
+            if let Err(error) = sink.write(len, data) {
+                self.cancel();
+                return Err(error);
+            }
+            len += BLOCK_LEN;
+            block = block.wrapping_add(1);
+        }
+        self.cancel();
+        Err(error)
+    }
+
+    /// Sends `blob`, padding its last block with `PAD` bytes.
+    ///
+    /// Fails with `ErrorCode::Busy` if the receiver does not start or stops
+    /// responding, with `ErrorCode::Fail` if it keeps rejecting a block, and
+    /// with `ErrorCode::Cancel` if it cancels the transfer.
+    pub fn send(&self, blob: &[u8]) -> Result<(), ErrorCode> {
+        self.wait_for_start()?;
+        for (index, chunk) in blob.chunks(BLOCK_LEN).enumerate() {
+            let mut packet = [PAD; PACKET_LEN];
+            let number = (index + 1) as u8;
+            packet[..3].copy_from_slice(&[SOH, number, !number]);
+            packet[3..3 + chunk.len()].copy_from_slice(chunk);
+            let crc = crc16(&packet[3..3 + BLOCK_LEN]);
+            packet[PACKET_LEN - 2..].copy_from_slice(&crc.to_be_bytes());
+            self.send_until_acked(&packet)?;
+        }
+        self.send_until_acked(&[EOT])
+    }
+
+    // Waits for the receiver to ask for a transfer in CRC mode.
+    fn wait_for_start(&self) -> Result<(), ErrorCode> {
+        for _ in 0..MAX_RETRIES {
+            match self.read_byte() {
+                Ok(CRC_MODE) => return Ok(()),
+                Ok(CAN) => return Err(ErrorCode::Cancel),
+                Ok(_) | Err(ErrorCode::Busy) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Err(ErrorCode::Busy)
+    }
+
+    // Sends `packet` until the receiver acknowledges it. Bytes other than the
+    // receiver's answers, such as its repeated requests to start, are ignored.
+    fn send_until_acked(&self, packet: &[u8]) -> Result<(), ErrorCode> {
+        let mut error = ErrorCode::Busy;
+        'attempts: for _ in 0..MAX_RETRIES {
+            self.write(packet)?;
+            loop {
+                match self.read_byte() {
+                    Ok(ACK) => return Ok(()),
+                    Ok(CAN) => return Err(ErrorCode::Cancel),
+                    Ok(NAK) => error = ErrorCode::Fail,
+                    Ok(_) => continue,
+                    Err(ErrorCode::Busy) => error = ErrorCode::Busy,
+                    Err(error) => return Err(error),
+                }
+                continue 'attempts;
+            }
+        }
+        self.cancel();
+        Err(error)
+    }
+
+    fn cancel(&self) {
+        let _ = self.write(&[CAN, CAN]);
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<(), ErrorCode> {
+        Console::<S, C, DRIVER_NUM>::write_guarded(bytes, self.guard)
+    }
+
+    fn read_byte(&self) -> Result<u8, ErrorCode> {
+        let mut byte = [0];
+        self.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    // Fills `buf`, failing with `ErrorCode::Busy` if the peer stops sending
+    // for longer than the guard's maximum wait.
+    fn read_exact(&self, mut buf: &mut [u8]) -> Result<(), ErrorCode> {
+        while !buf.is_empty() {
+            let (count, result) = Console::<S, C, DRIVER_NUM>::read_guarded(buf, self.guard);
+            result?;
+            buf = &mut buf[count..];
+        }
+        Ok(())
+    }
+}
+
+/// Computes the CRC-16 of XMODEM (polynomial 0x1021, initial value 0).
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| match crc & 0x8000 {
+            0 => crc << 1,
+            _ => crc << 1 ^ 0x1021,
+        })
+    })
+}
+
+// A block's packet: the header, the block number and its complement, the data
+// and the CRC.
+const PACKET_LEN: usize = 3 + BLOCK_LEN + 2;
+
+const SOH: u8 = 0x01;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const CRC_MODE: u8 = b'C';
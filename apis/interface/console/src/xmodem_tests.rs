@@ -0,0 +1,179 @@
+extern crate std;
+
+use crate::xmodem::{crc16, BLOCK_LEN, MAX_RETRIES, PAD};
+use libtock_alarm::{LivenessGuard, Milliseconds};
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+use std::vec::Vec;
+
+type Xmodem<'g> = crate::xmodem::Xmodem<'g, fake::Syscalls>;
+
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+
+fn packet(number: u8, data: &[u8]) -> Vec<u8> {
+    let mut block = [PAD; BLOCK_LEN];
+    block[..data.len()].copy_from_slice(data);
+    let mut packet = std::vec![0x01, number, !number];
+    packet.extend_from_slice(&block);
+    packet.extend_from_slice(&crc16(&block).to_be_bytes());
+    packet
+}
+
+#[test]
+fn crc() {
+    assert_eq!(crc16(b"123456789"), 0x31c3);
+}
+
+#[test]
+fn receive() {
+    let mut corrupted = packet(1, &[1; BLOCK_LEN]);
+    corrupted[10] ^= 0xff;
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[
+            &corrupted,
+            &packet(1, &[1; BLOCK_LEN]),
+            // The sender missed the ACK.
+            &packet(1, &[1; BLOCK_LEN]),
+            &packet(2, b"end"),
+            &[EOT],
+        ],
+    );
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    let mut buffer = [0; 4 * BLOCK_LEN];
+    assert_eq!(
+        Xmodem::new(&guard).receive(&mut buffer[..]),
+        Ok(2 * BLOCK_LEN)
+    );
+    assert_eq!(console.take_bytes(), [b'C', NAK, ACK, ACK, ACK, ACK]);
+    assert_eq!(buffer[..BLOCK_LEN], [1; BLOCK_LEN]);
+    assert_eq!(&buffer[BLOCK_LEN..BLOCK_LEN + 4], b"end\x1a");
+}
+
+#[test]
+fn receive_too_large() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[&packet(1, &[1; BLOCK_LEN]), &packet(2, b"end")],
+    );
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    let mut buffer = [0; BLOCK_LEN];
+    assert_eq!(
+        Xmodem::new(&guard).receive(&mut buffer[..]),
+        Err(ErrorCode::Size)
+    );
+    assert_eq!(console.take_bytes(), [b'C', ACK, CAN, CAN]);
+}
+
+#[test]
+fn receive_timeout() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(&kernel, &alarm, &[]);
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    let mut buffer = [0; BLOCK_LEN];
+    assert_eq!(
+        Xmodem::new(&guard).receive(&mut buffer[..]),
+        Err(ErrorCode::Busy)
+    );
+    let mut expected = std::vec![b'C'; MAX_RETRIES as usize];
+    expected.extend_from_slice(&[CAN, CAN]);
+    assert_eq!(console.take_bytes(), expected);
+}
+
+#[test]
+fn send() {
+    let blob = [7; BLOCK_LEN + 2];
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[
+            &[b'C'],
+            // A repeated request to start is ignored.
+            &[b'C', NAK],
+            &[ACK],
+            &[ACK],
+            &[ACK],
+        ],
+    );
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    assert_eq!(Xmodem::new(&guard).send(&blob), Ok(()));
+    let mut expected = packet(1, &blob[..BLOCK_LEN]);
+    expected.extend(packet(1, &blob[..BLOCK_LEN]));
+    expected.extend(packet(2, &blob[BLOCK_LEN..]));
+    expected.push(EOT);
+    assert_eq!(console.take_bytes(), expected);
+}
+
+#[test]
+fn send_cancelled() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    console.answer_when_idle(&kernel, &alarm, &[&[b'C'], &[CAN, CAN]]);
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    assert_eq!(Xmodem::new(&guard).send(b"blob"), Err(ErrorCode::Cancel));
+    assert_eq!(console.take_bytes(), packet(1, b"blob"));
+}
+
+#[cfg(feature = "key_value")]
+#[test]
+fn receive_into_key_value() {
+    use crate::xmodem::MAX_PREFIX_LEN;
+    type KeyValueSink<'p> = crate::xmodem::KeyValueSink<'p, fake::Syscalls>;
+
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    let store = fake::KeyValue::new();
+    kernel.add_driver(&console);
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&store);
+    console.answer_when_idle(
+        &kernel,
+        &alarm,
+        &[&packet(1, &[1; BLOCK_LEN]), &packet(2, b"end"), &[EOT]],
+    );
+    let guard = LivenessGuard::new(Milliseconds(1000)).unwrap();
+
+    assert!(KeyValueSink::new(&[0; MAX_PREFIX_LEN + 1]).is_err());
+    let mut sink = KeyValueSink::new(b"image").unwrap();
+    assert_eq!(Xmodem::new(&guard).receive(&mut sink), Ok(2 * BLOCK_LEN));
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get(b"image\0\0\0\0").unwrap(), [1; BLOCK_LEN]);
+    let mut end = [PAD; BLOCK_LEN];
+    end[..3].copy_from_slice(b"end");
+    assert_eq!(store.get(b"image\x01\0\0\0").unwrap(), end);
+    let mut key = [0; MAX_PREFIX_LEN + 4];
+    assert_eq!(sink.key(1, &mut key), b"image\x01\0\0\0");
+}
@@ -0,0 +1,25 @@
+/// The source of the chip's system clock, as reported by
+/// `ChipConfiguration::clock_source`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ClockSource {
+    /// An internal RC oscillator.
+    InternalRc,
+    /// An external crystal oscillator.
+    ExternalCrystal,
+    /// A PLL, fed by one of the above.
+    Pll,
+    /// A chip-specific source this API does not know about, with the kernel's
+    /// raw value.
+    Other(u32),
+}
+
+impl From<u32> for ClockSource {
+    fn from(value: u32) -> ClockSource {
+        match value {
+            0 => ClockSource::InternalRc,
+            1 => ClockSource::ExternalCrystal,
+            2 => ClockSource::Pll,
+            other => ClockSource::Other(other),
+        }
+    }
+}
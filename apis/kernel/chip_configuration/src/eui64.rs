@@ -0,0 +1,46 @@
+use core::fmt;
+
+/// An IEEE EUI-64 identifier, such as a chip's IEEE 802.15.4 MAC address.
+///
+/// The bytes are stored in transmission order: `self.0[0]` is the first byte
+/// of the OUI, and holds the universal/local and unicast/multicast bits.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Eui64(pub [u8; 8]);
+
+impl Eui64 {
+    /// Converts from the `u64` representation used by the IEEE 802.15.4
+    /// driver, whose most significant byte is the first byte of the address.
+    pub const fn from_long_address(long_addr: u64) -> Eui64 {
+        Eui64(long_addr.to_be_bytes())
+    }
+
+    /// Converts to the `u64` representation accepted by
+    /// `Ieee802154::set_address_long`.
+    pub const fn to_long_address(self) -> u64 {
+        u64::from_be_bytes(self.0)
+    }
+
+    /// Returns the modified EUI-64 IPv6 interface identifier derived from
+    /// this address (RFC 4291 appendix A), i.e. the address with its
+    /// universal/local bit inverted. It forms the lower 64 bits of the
+    /// device's link-local and SLAAC IPv6 addresses.
+    pub const fn interface_identifier(self) -> [u8; 8] {
+        let mut iid = self.0;
+        iid[0] ^= 0x02;
+        iid
+    }
+}
+
+/// Formats the address as colon-separated lowercase hex bytes, e.g.
+/// `00:11:22:33:44:55:66:77`.
+impl fmt::Display for Eui64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i != 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+#![no_std]
+
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+mod clock_source;
+mod eui64;
+mod reset_reason;
+
+pub use clock_source::ClockSource;
+pub use eui64::Eui64;
+pub use reset_reason::ResetReason;
+
+/// The chip configuration driver.
+///
+/// It reports the identity of the device the app runs on: the chip's factory
+/// IEEE 802.15.4 MAC address and serial number, the board's hardware revision
+/// and name, and the size of its memories. Firmware typically reports these in
+/// its telemetry. It also reports the chip's clock configuration, so timing
+/// code need not hard-code tick rates per board.
+///
+/// # Example
+/// ```ignore
+/// use libtock::chip_configuration::ChipConfiguration;
+///
+/// let mut name = [0; 32];
+/// let len = ChipConfiguration::board_name(&mut name)?;
+/// let serial = ChipConfiguration::serial_number()?;
+/// ```
+///
+/// The capsule is not part of upstream Tock, so boards may register it under a
+/// different driver number. `DRIVER_NUM` overrides the default number:
+/// ```ignore
+/// type ChipConfiguration =
+///     libtock_chip_configuration::ChipConfiguration<TockSyscalls, DefaultConfig, 0x90100>;
+/// ```
+pub struct ChipConfiguration<
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+>(S, C);
+
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> ChipConfiguration<S, C, DRIVER_NUM> {
+    /// Run a check against the chip configuration capsule to ensure it is
+    /// present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Returns the chip's factory IEEE 802.15.4 MAC address.
+    pub fn ieee_mac() -> Result<Eui64, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::IEEE_MAC, 0, 0)
+            .to_result()
+            .map(Eui64::from_long_address)
+    }
+
+    /// Returns the chip's serial number.
+    pub fn serial_number() -> Result<u64, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::SERIAL_NUMBER, 0, 0).to_result()
+    }
+
+    /// Returns the board's hardware revision.
+    pub fn hardware_revision() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::HARDWARE_REVISION, 0, 0).to_result()
+    }
+
+    /// Returns the size of the chip's flash, in bytes.
+    pub fn flash_size() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::FLASH_SIZE, 0, 0).to_result()
+    }
+
+    /// Returns the size of the chip's RAM, in bytes.
+    pub fn ram_size() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::RAM_SIZE, 0, 0).to_result()
+    }
+
+    /// Returns why the chip last reset.
+    ///
+    /// Apps can use this to log abnormal restarts, and e.g. skip
+    /// non-essential work after a watchdog reset.
+    pub fn reset_reason() -> Result<ResetReason, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::RESET_REASON, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(ResetReason::from)
+    }
+
+    /// Returns the frequency of the system (CPU) clock, in hertz.
+    pub fn system_clock_hz() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::SYSTEM_CLOCK_HZ, 0, 0).to_result()
+    }
+
+    /// Returns the frequency of the clock that drives the alarm driver, in
+    /// hertz. This matches `Alarm::get_frequency`, but is available without
+    /// the alarm driver.
+    pub fn alarm_clock_hz() -> Result<u32, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::ALARM_CLOCK_HZ, 0, 0).to_result()
+    }
+
+    /// Returns the source the system clock currently runs from.
+    pub fn clock_source() -> Result<ClockSource, ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::CLOCK_SOURCE, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(ClockSource::from)
+    }
+
+    /// Reads the board's name (UTF-8, not NUL-terminated) into `buf`, and
+    /// returns the name's length.
+    ///
+    /// If `buf` is too short, the name is truncated to `buf`'s length but the
+    /// full length is still returned, so callers can detect truncation by
+    /// comparing the result against `buf.len()`.
+    pub fn board_name(buf: &mut [u8]) -> Result<usize, ErrorCode> {
+        Self::read::<{ allow_rw::BOARD_NAME }>(command::BOARD_NAME, buf)
+    }
+
+    /// Reads the chip's factory-programmed unique ID into `buf`, and returns
+    /// the ID's length. The length depends on the chip (e.g. 8 bytes on the
+    /// nRF52 series, 16 bytes on the RP2040's flash).
+    ///
+    /// Unlike the MAC address, which may be reprogrammed, the ID is fixed for
+    /// the lifetime of the chip, so it is suitable for deriving stable node
+    /// identifiers. Truncation is handled as in `board_name`.
+    pub fn device_id(buf: &mut [u8]) -> Result<usize, ErrorCode> {
+        Self::read::<{ allow_rw::DEVICE_ID }>(command::DEVICE_ID, buf)
+    }
+
+    // Shares `buf` with the driver through Read-Write Allow `BUFFER_NUM`, and
+    // runs `command_id`, which fills the buffer and returns the full length of
+    // the data.
+    fn read<const BUFFER_NUM: u32>(command_id: u32, buf: &mut [u8]) -> Result<usize, ErrorCode> {
+        share::scope(|allow_rw| {
+            S::allow_rw::<C, DRIVER_NUM, BUFFER_NUM>(allow_rw, buf)?;
+            S::checked_command::<C>(DRIVER_NUM, command_id, 0, 0)
+                .to_result::<u32, ErrorCode>()
+                .map(|len| len as usize)
+        })
+    }
+}
+
+/// System call configuration trait for `ChipConfiguration`.
+pub trait Config: libtock_platform::allow_rw::Config + libtock_platform::command::Config {}
+impl<T: libtock_platform::allow_rw::Config + libtock_platform::command::Config> Config for T {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+// Upstream Tock assigns no driver number for chip configuration; this is the
+// number our boards register the capsule under, see doc/DriverNumbers.md.
+const DEFAULT_DRIVER_NUM: u32 = 0x90067;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const IEEE_MAC: u32 = 1;
+    pub const SERIAL_NUMBER: u32 = 2;
+    pub const HARDWARE_REVISION: u32 = 3;
+    pub const FLASH_SIZE: u32 = 4;
+    pub const RAM_SIZE: u32 = 5;
+    pub const BOARD_NAME: u32 = 6;
+    pub const RESET_REASON: u32 = 7;
+    pub const DEVICE_ID: u32 = 8;
+    pub const SYSTEM_CLOCK_HZ: u32 = 9;
+    pub const ALARM_CLOCK_HZ: u32 = 10;
+    pub const CLOCK_SOURCE: u32 = 11;
+}
+
+mod allow_rw {
+    pub const BOARD_NAME: u32 = 0;
+    pub const DEVICE_ID: u32 = 1;
+}
@@ -0,0 +1,38 @@
+/// Why the chip last reset, as reported by `ChipConfiguration::reset_reason`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ResetReason {
+    /// The chip was powered up.
+    PowerOn,
+    /// The reset pin was asserted (e.g. the board's reset button).
+    ExternalPin,
+    /// A watchdog expired.
+    Watchdog,
+    /// Software requested the reset, e.g. through the reboot driver.
+    Software,
+    /// The supply voltage dropped below the brownout threshold.
+    Brownout,
+    /// A chip-specific reason this API does not know about, with the kernel's
+    /// raw value.
+    Other(u32),
+}
+
+impl ResetReason {
+    /// Returns true if the reset was not requested by the user or the app:
+    /// a watchdog or brownout reset.
+    pub fn is_abnormal(self) -> bool {
+        matches!(self, ResetReason::Watchdog | ResetReason::Brownout)
+    }
+}
+
+impl From<u32> for ResetReason {
+    fn from(value: u32) -> ResetReason {
+        match value {
+            0 => ResetReason::PowerOn,
+            1 => ResetReason::ExternalPin,
+            2 => ResetReason::Watchdog,
+            3 => ResetReason::Software,
+            4 => ResetReason::Brownout,
+            other => ResetReason::Other(other),
+        }
+    }
+}
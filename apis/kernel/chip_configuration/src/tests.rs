@@ -0,0 +1,211 @@
+extern crate std;
+
+use super::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type ChipConfiguration = super::ChipConfiguration<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(ChipConfiguration::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(ChipConfiguration::serial_number(), Err(ErrorCode::NoDevice));
+    assert_eq!(
+        ChipConfiguration::board_name(&mut [0; 4]),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(ChipConfiguration::exists(), Ok(()));
+}
+
+#[test]
+fn identity() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+    driver.set_ieee_mac(Some(0x0011_2233_4455_6677));
+    driver.set_serial_number(Some(0x1234_5678_9abc));
+    driver.set_hardware_revision(Some(3));
+    driver.set_flash_size(Some(1024 * 1024));
+    driver.set_ram_size(None);
+
+    assert_eq!(
+        ChipConfiguration::ieee_mac(),
+        Ok(Eui64([0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77]))
+    );
+    assert_eq!(ChipConfiguration::serial_number(), Ok(0x1234_5678_9abc));
+    assert_eq!(ChipConfiguration::hardware_revision(), Ok(3));
+    assert_eq!(ChipConfiguration::flash_size(), Ok(1024 * 1024));
+    assert_eq!(ChipConfiguration::ram_size(), Err(ErrorCode::NoSupport));
+}
+
+#[test]
+fn board_name() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+    driver.set_board_name(Some("nrf52840dk"));
+
+    let mut buf = [0; 16];
+    assert_eq!(ChipConfiguration::board_name(&mut buf), Ok(10));
+    assert_eq!(&buf[..10], b"nrf52840dk");
+
+    // A short buffer truncates the name, but the full length is returned.
+    let mut buf = [0; 5];
+    assert_eq!(ChipConfiguration::board_name(&mut buf), Ok(10));
+    assert_eq!(&buf, b"nrf52");
+
+    driver.set_board_name(None);
+    assert_eq!(
+        ChipConfiguration::board_name(&mut buf),
+        Err(ErrorCode::NoSupport)
+    );
+}
+
+#[test]
+fn eui64() {
+    use std::string::ToString;
+    let mac = Eui64([0x00, 0x12, 0x4b, 0x00, 0x14, 0xb5, 0xd9, 0x2f]);
+    assert_eq!(mac.to_string(), "00:12:4b:00:14:b5:d9:2f");
+    assert_eq!(mac.to_long_address(), 0x0012_4b00_14b5_d92f);
+    assert_eq!(Eui64::from_long_address(0x0012_4b00_14b5_d92f), mac);
+    // The universal/local bit is inverted (RFC 4291 appendix A).
+    assert_eq!(
+        mac.interface_identifier(),
+        [0x02, 0x12, 0x4b, 0x00, 0x14, 0xb5, 0xd9, 0x2f]
+    );
+    assert_eq!(Eui64([0x02; 8]).interface_identifier()[0], 0x00);
+}
+
+#[test]
+fn reset_reason() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(ChipConfiguration::reset_reason(), Ok(ResetReason::PowerOn));
+    for (value, reason) in [
+        (0, ResetReason::PowerOn),
+        (1, ResetReason::ExternalPin),
+        (2, ResetReason::Watchdog),
+        (3, ResetReason::Software),
+        (4, ResetReason::Brownout),
+        (100, ResetReason::Other(100)),
+    ] {
+        driver.set_reset_reason(Some(value));
+        assert_eq!(ChipConfiguration::reset_reason(), Ok(reason));
+    }
+    driver.set_reset_reason(None);
+    assert_eq!(ChipConfiguration::reset_reason(), Err(ErrorCode::NoSupport));
+    assert!(ResetReason::Watchdog.is_abnormal());
+    assert!(ResetReason::Brownout.is_abnormal());
+    assert!(!ResetReason::Software.is_abnormal());
+    assert!(!ResetReason::Other(100).is_abnormal());
+}
+
+#[test]
+fn device_id() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+    driver.set_device_id(Some(&[0xd0, 0x5a, 0x11, 0x7e, 0x00, 0x42]));
+    driver.set_board_name(Some("nrf52840dk"));
+
+    let mut buf = [0; 16];
+    assert_eq!(ChipConfiguration::device_id(&mut buf), Ok(6));
+    assert_eq!(&buf[..6], [0xd0, 0x5a, 0x11, 0x7e, 0x00, 0x42]);
+    // The board name uses a separate buffer.
+    let mut name = [0; 4];
+    assert_eq!(ChipConfiguration::board_name(&mut name), Ok(10));
+    assert_eq!(&name, b"nrf5");
+    assert_eq!(&buf[..6], [0xd0, 0x5a, 0x11, 0x7e, 0x00, 0x42]);
+}
+
+#[test]
+fn clocks() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(ChipConfiguration::system_clock_hz(), Ok(64_000_000));
+    assert_eq!(ChipConfiguration::alarm_clock_hz(), Ok(32_768));
+    assert_eq!(
+        ChipConfiguration::clock_source(),
+        Ok(ClockSource::ExternalCrystal)
+    );
+    driver.set_clock_source(Some(9));
+    assert_eq!(ChipConfiguration::clock_source(), Ok(ClockSource::Other(9)));
+    driver.set_alarm_clock_hz(None);
+    assert_eq!(
+        ChipConfiguration::alarm_clock_hz(),
+        Err(ErrorCode::NoSupport)
+    );
+}
+
+// Derives a node's link-local IPv6 address and a short name from its identity,
+// falling back to the device ID when the chip has no MAC address.
+fn node_identity() -> Result<([u8; 16], u16), ErrorCode> {
+    let iid = match ChipConfiguration::ieee_mac() {
+        Ok(mac) => mac.interface_identifier(),
+        Err(ErrorCode::NoSupport) => {
+            let mut id = [0; 8];
+            ChipConfiguration::device_id(&mut id)?;
+            Eui64(id).interface_identifier()
+        }
+        Err(error) => return Err(error),
+    };
+    let mut address = [0; 16];
+    address[..2].copy_from_slice(&[0xfe, 0x80]);
+    address[8..].copy_from_slice(&iid);
+    Ok((address, u16::from_be_bytes([iid[6], iid[7]])))
+}
+
+#[test]
+fn identity_derivation() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new();
+    kernel.add_driver(&driver);
+    driver.set_ieee_mac(Some(0x0012_4b00_14b5_d92f));
+
+    let (address, name) = node_identity().unwrap();
+    assert_eq!(
+        address,
+        [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0x02, 0x12, 0x4b, 0x00, 0x14, 0xb5, 0xd9, 0x2f]
+    );
+    assert_eq!(name, 0xd92f);
+
+    driver.set_ieee_mac(None);
+    driver.set_device_id(Some(&[0xd0, 0x5a, 0x11, 0x7e, 0x00, 0x42, 0xbe, 0xef]));
+    let (address, name) = node_identity().unwrap();
+    assert_eq!(
+        address[8..],
+        [0xd2, 0x5a, 0x11, 0x7e, 0x00, 0x42, 0xbe, 0xef]
+    );
+    assert_eq!(name, 0xbeef);
+
+    driver.set_device_id(None);
+    assert_eq!(node_identity(), Err(ErrorCode::NoSupport));
+}
+
+#[test]
+fn driver_num_override() {
+    type Overridden = super::ChipConfiguration<fake::Syscalls, DefaultConfig, 0x90100>;
+    let kernel = fake::Kernel::new();
+    let driver = fake::ChipConfiguration::new_with_driver_num(0x90100);
+    kernel.add_driver(&driver);
+    driver.set_board_name(Some("custom"));
+
+    assert_eq!(ChipConfiguration::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(Overridden::exists(), Ok(()));
+    let mut buf = [0; 8];
+    assert_eq!(Overridden::board_name(&mut buf), Ok(6));
+    assert_eq!(&buf[..6], b"custom");
+}
@@ -0,0 +1,171 @@
+use crate::{allow_ro_num, command, Config, IpcReply, IpcSharedSlot, DRIVER_NUM};
+use libtock_future::block_on;
+use libtock_platform::{
+    share, subscribe::AnyId, AllowRo, AllowRw, DefaultConfig, ErrorCode, Subscribe, Syscalls,
+    Upcall,
+};
+use zerocopy::{AsBytes, FromBytes};
+
+/// The client side of IPC.
+///
+/// A client finds a service by its package name with `discover`, shares a
+/// buffer with it (typically holding a request), and notifies it with
+/// `notify_service`. When the service is done it notifies the client back,
+/// which runs the `IpcClientListener` registered for that service.
+///
+/// The allow and subscribe numbers used to talk to a service are derived from
+/// its process ID: the buffer shared with service `n` is Read-Write Allow `n`,
+/// and its notifications are delivered to Subscribe `n + 1` (see
+/// `client_upcall`). As `libtock_platform` needs these numbers at compile
+/// time, `share_buffer` and `register_listener` take them as const generics;
+/// use `discover` to check that the service runs under the expected ID.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ipc::{client_upcall, IpcClient, IpcClientListener};
+///
+/// const SERVICE: u32 = 1;
+///
+/// assert_eq!(IpcClient::discover(b"org.tockos.uppercase"), Ok(SERVICE));
+/// let done = Cell::new(false);
+/// let listener = IpcClientListener(|_service| done.set(true));
+/// let mut buffer = *b"hello";
+/// share::scope(|allow| {
+///     share::scope(|subscribe| {
+///         IpcClient::share_buffer::<SERVICE>(allow, &mut buffer)?;
+///         IpcClient::register_listener::<_, { client_upcall(SERVICE) }>(&listener, subscribe)?;
+///         IpcClient::notify_service(SERVICE)?;
+///         while !done.get() {
+///             TockSyscalls::yield_wait();
+///         }
+///         Ok(())
+///     })
+/// })?;
+/// // buffer now holds b"HELLO".
+/// ```
+pub struct IpcClient<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> IpcClient<S, C> {
+    /// Run a check against the IPC capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Returns the process ID of the service with the given package name.
+    /// Fails with `ErrorCode::Invalid` if no process has that name.
+    pub fn discover(package_name: &[u8]) -> Result<u32, ErrorCode> {
+        share::scope::<AllowRo<_, DRIVER_NUM, { allow_ro_num::SEARCH }>, _, _>(|allow_ro| {
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro_num::SEARCH }>(allow_ro, package_name)?;
+            S::checked_command::<C>(DRIVER_NUM, command::DISCOVER, 0, 0).to_result::<u32, _>()
+        })
+    }
+
+    /// Shares `buffer` with the service with process ID `SERVICE`, replacing
+    /// any buffer shared with it before. The service can read and write the
+    /// buffer until the end of the `allow_rw` handle's scope.
+    pub fn share_buffer<'share, const SERVICE: u32>(
+        allow_rw: share::Handle<AllowRw<'share, S, DRIVER_NUM, SERVICE>>,
+        buffer: &'share mut [u8],
+    ) -> Result<(), ErrorCode> {
+        S::allow_rw::<C, DRIVER_NUM, SERVICE>(allow_rw, buffer)
+    }
+
+    /// Shares the region of `slot` with the service with process ID `SERVICE`,
+    /// like `share_buffer`.
+    pub fn share_slot<'share, T: FromBytes + AsBytes, const SERVICE: u32>(
+        allow_rw: share::Handle<AllowRw<'share, S, DRIVER_NUM, SERVICE>>,
+        slot: &'share mut IpcSharedSlot<'_, T>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_rw::<C, DRIVER_NUM, SERVICE>(allow_rw, slot.region())
+    }
+
+    /// Notifies the service with process ID `service`, which runs its service
+    /// upcall. Fails with `ErrorCode::Invalid` if there is no such process.
+    pub fn notify_service(service: u32) -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::NOTIFY_SERVICE, service, 0).to_result()
+    }
+
+    /// Registers the listener called when a service notifies this client.
+    /// `SUBSCRIBE_NUM` selects the service: it is `client_upcall(service)`.
+    pub fn register_listener<'share, F: Fn(u32), const SUBSCRIBE_NUM: u32>(
+        listener: &'share IpcClientListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, SUBSCRIBE_NUM>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, SUBSCRIBE_NUM>(subscribe, listener)
+    }
+
+    /// Registers `reply` to record notifications from a service, instead of a
+    /// listener. `SUBSCRIBE_NUM` is `client_upcall(service)`.
+    pub fn register_reply<'share, const SUBSCRIBE_NUM: u32>(
+        reply: &'share IpcReply,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, SUBSCRIBE_NUM>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, SUBSCRIBE_NUM>(subscribe, reply)
+    }
+
+    /// Unregisters the listener (or `IpcReply`) for notifications from the
+    /// service with process ID `service`.
+    pub fn unregister_listener(service: u32) {
+        S::unsubscribe(DRIVER_NUM, client_upcall(service))
+    }
+
+    /// Shares `buffer` with the service with process ID `SERVICE`, notifies the
+    /// service, and waits until it notifies the client back. The buffer is no
+    /// longer shared when this returns, so it holds the service's reply.
+    ///
+    /// `SUBSCRIBE_NUM` must be `client_upcall(SERVICE)`; it is a separate
+    /// parameter as it cannot be computed from `SERVICE` in a generic context.
+    pub fn exchange<const SERVICE: u32, const SUBSCRIBE_NUM: u32>(
+        buffer: &mut [u8],
+    ) -> Result<(), ErrorCode> {
+        #[allow(clippy::let_unit_value)]
+        let () = SubscribeNum::<SERVICE, SUBSCRIBE_NUM>::VALID;
+
+        let reply = IpcReply::new();
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, SERVICE>,
+                Subscribe<_, DRIVER_NUM, SUBSCRIBE_NUM>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            S::allow_rw::<C, DRIVER_NUM, SERVICE>(allow_rw, buffer)?;
+            Self::register_reply::<SUBSCRIBE_NUM>(&reply, subscribe)?;
+            Self::notify_service(SERVICE)?;
+            block_on::<S, _>(reply.wait());
+            Ok(())
+        })
+    }
+}
+
+// Checks at compile time that SUBSCRIBE_NUM is client_upcall(SERVICE).
+struct SubscribeNum<const SERVICE: u32, const SUBSCRIBE_NUM: u32>;
+
+impl<const SERVICE: u32, const SUBSCRIBE_NUM: u32> SubscribeNum<SERVICE, SUBSCRIBE_NUM> {
+    const VALID: () = assert!(
+        SUBSCRIBE_NUM == client_upcall(SERVICE),
+        "SUBSCRIBE_NUM must be client_upcall(SERVICE)"
+    );
+}
+
+/// Returns the subscribe number of the client upcall for notifications from
+/// the service with process ID `service`.
+pub const fn client_upcall(service: u32) -> u32 {
+    service + 1
+}
+
+/// The listener for notifications from a service. It is called with the
+/// service's process ID.
+pub struct IpcClientListener<F: Fn(u32)>(pub F);
+
+impl<F: Fn(u32)> Upcall<AnyId> for IpcClientListener<F> {
+    fn upcall(&self, service: u32, _len: u32, _address: u32) {
+        self.0(service)
+    }
+}
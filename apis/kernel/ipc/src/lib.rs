@@ -0,0 +1,87 @@
+#![no_std]
+
+//! Inter-process communication (IPC).
+//!
+//! IPC lets processes exchange data through shared buffers. A *service* is a
+//! process that other processes (its *clients*) find by its package name. A
+//! client shares a buffer with the service and notifies it; the service then
+//! reads and writes the client's buffer in its upcall, and notifies the client
+//! when it is done.
+//!
+//! The client side is implemented by `IpcClient`, the service side by
+//! `IpcServer`. `IpcSharedSlot` gives both sides typed access to a shared
+//! buffer, and `IpcRingBuffer` streams values from a client to a service
+//! through a buffer shared once. Instead of listeners, notifications can be received as
+//! `libtock_future` futures and streams, through `IpcRequests` and `IpcReply`.
+//! With the `rpc` feature, the `rpc` module adds request/response
+//! calls on top. The `pubsub` module implements publish/subscribe messaging
+//! through a broker process, and the `log` module collects the log records of
+//! many processes in one.
+
+use libtock_platform::{allow_ro, allow_rw, subscribe, DefaultConfig};
+
+mod client;
+pub mod log;
+mod notifications;
+pub mod pubsub;
+mod ring;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+mod server;
+mod slot;
+
+pub use client::{client_upcall, IpcClient, IpcClientListener};
+pub use notifications::{IpcReply, IpcRequest, IpcRequests, NextRequest, WaitReply};
+pub use ring::{IpcRingBuffer, IpcRingConsumer, IpcRingProducer};
+pub use server::{IpcServer, IpcServerListener};
+pub use slot::IpcSharedSlot;
+
+/// System call configuration trait for the IPC APIs.
+///
+/// Besides passing through the configuration of the system calls, it decides
+/// how the address of a buffer a client shares (as delivered to the service's
+/// upcall) is converted into a pointer. On Tock, the address is the pointer.
+/// Unit tests, where shared buffers live in a simulated process, override
+/// `resolve_address`.
+pub trait Config:
+    allow_ro::Config + allow_rw::Config + libtock_platform::command::Config + subscribe::Config
+{
+    /// Converts the address of a `len`-byte buffer shared by another process
+    /// into a pointer, or returns `None` if the buffer cannot be accessed.
+    fn resolve_address(address: u32, _len: usize) -> Option<*mut u8> {
+        match address {
+            0 => None,
+            _ => Some(address as usize as *mut u8),
+        }
+    }
+}
+
+impl Config for DefaultConfig {}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x10000;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const DISCOVER: u32 = 1;
+    pub const NOTIFY_SERVICE: u32 = 2;
+    pub const NOTIFY_CLIENT: u32 = 3;
+}
+
+mod allow_ro_num {
+    // The package name to search for.
+    pub const SEARCH: u32 = 0;
+}
+
+mod subscribe_num {
+    // The service upcall. The client upcall for the service with process ID n
+    // is n + 1.
+    pub const SERVICE: u32 = 0;
+}
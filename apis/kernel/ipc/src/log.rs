@@ -0,0 +1,262 @@
+//! Log collection: many producer processes log records into rings shared with
+//! a single collector process, which forwards them, e.g. to flash or over the
+//! radio.
+//!
+//! Each producer shares an [`IpcRingBuffer`] of records with the collector
+//! through a `LogProducer`, and the collector drains every ring it is notified
+//! about through its `LogCollector`'s listener. A producer whose ring is full
+//! drops the record, and reports how many records it dropped in the next one
+//! it manages to push, so the collector keeps a count of the records each
+//! producer received and dropped.
+//!
+//! Records are `RECORD_LEN` bytes long:
+//!
+//! * byte 0: the level, whose meaning is up to the application.
+//! * byte 1: the length of the text.
+//! * bytes 2-3: the number of records the producer dropped just before this
+//!   one (little endian, saturating).
+//! * from byte 4: the text, of up to `MAX_TEXT_LEN` bytes.
+//!
+//! # Example
+//! ```ignore
+//! // Collector, which queues records to write them to flash from its main
+//! // loop.
+//! let collector = LogCollector::<8, 16>::new();
+//! let listener = collector.listener(|producer, record| queue.push(producer, record));
+//! share::scope(|subscribe| {
+//!     IpcServer::register_listener(&listener, subscribe)?;
+//!     loop {
+//!         TockSyscalls::yield_wait();
+//!         queue.write_to_flash();
+//!     }
+//! })
+//!
+//! // Producer, with the collector running as process 1
+//! let mut ring = LogRing::<16>::new();
+//! share::scope(|allow| {
+//!     let mut log = LogProducer::share::<1>(allow, &mut ring)?;
+//!     log.log(INFO, "sensor started");
+//! })
+//! ```
+
+use crate::{
+    Config, IpcClient, IpcRingBuffer, IpcRingConsumer, IpcRingProducer, IpcServerListener,
+};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_platform::{share, AllowRw, DefaultConfig, ErrorCode, Syscalls};
+
+/// The length of a record.
+pub const RECORD_LEN: usize = 64;
+
+/// The length of the longest text a record holds.
+pub const MAX_TEXT_LEN: usize = RECORD_LEN - TEXT_OFFSET;
+
+/// A ring of `N` records, shared by a producer with the collector.
+pub type LogRing<const N: usize> = IpcRingBuffer<[u8; RECORD_LEN], N>;
+
+/// A decoded log record.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LogRecord {
+    pub level: u8,
+    /// The number of records the producer dropped just before this one.
+    pub dropped: u16,
+    len: u8,
+    text: [u8; MAX_TEXT_LEN],
+}
+
+impl LogRecord {
+    /// Creates a record, truncating `text` to `MAX_TEXT_LEN` bytes.
+    pub fn new(level: u8, text: &[u8]) -> Self {
+        let len = text.len().min(MAX_TEXT_LEN);
+        let mut record = LogRecord {
+            level,
+            dropped: 0,
+            len: len as u8,
+            text: [0; MAX_TEXT_LEN],
+        };
+        record.text[..len].copy_from_slice(&text[..len]);
+        record
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text[..self.len as usize]
+    }
+
+    /// Decodes a record. A length longer than `MAX_TEXT_LEN` is truncated.
+    pub fn decode(bytes: &[u8; RECORD_LEN]) -> Self {
+        let mut record = LogRecord::new(bytes[0], &bytes[TEXT_OFFSET..]);
+        record.len = bytes[1].min(MAX_TEXT_LEN as u8);
+        record.dropped = u16::from_le_bytes([bytes[2], bytes[3]]);
+        record
+    }
+
+    pub fn encode(&self) -> [u8; RECORD_LEN] {
+        let mut bytes = [0; RECORD_LEN];
+        bytes[0] = self.level;
+        bytes[1] = self.len;
+        bytes[2..4].copy_from_slice(&self.dropped.to_le_bytes());
+        bytes[TEXT_OFFSET..].copy_from_slice(&self.text);
+        bytes
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Producer
+// -----------------------------------------------------------------------------
+
+/// A process's end of the [`LogRing`] it shares with the collector.
+pub struct LogProducer<'share, S: Syscalls, const N: usize, C: Config = DefaultConfig> {
+    ring: IpcRingProducer<'share, S, [u8; RECORD_LEN], N, C>,
+    dropped: u16,
+}
+
+impl<'share, S: Syscalls, const N: usize, C: Config> LogProducer<'share, S, N, C> {
+    /// Shares `ring` with the collector, running as process `COLLECTOR`. The
+    /// ring stays shared until `allow_rw`'s scope ends.
+    pub fn share<const COLLECTOR: u32>(
+        allow_rw: share::Handle<AllowRw<'share, S, { crate::DRIVER_NUM }, COLLECTOR>>,
+        ring: &'share mut LogRing<N>,
+    ) -> Result<Self, ErrorCode> {
+        Ok(LogProducer {
+            ring: IpcClient::<S, C>::share_ring::<_, N, COLLECTOR>(allow_rw, ring)?,
+            dropped: 0,
+        })
+    }
+
+    /// Logs `text` at `level`, truncated to `MAX_TEXT_LEN` bytes. If the ring
+    /// is full, the record is dropped and counted, and this fails with
+    /// `ErrorCode::NoMem`.
+    pub fn log(&mut self, level: u8, text: &str) -> Result<(), ErrorCode> {
+        let mut record = LogRecord::new(level, text.as_bytes());
+        record.dropped = self.dropped;
+        match self.ring.push(&record.encode()) {
+            Err(ErrorCode::NoMem) => {
+                self.dropped = self.dropped.saturating_add(1);
+                Err(ErrorCode::NoMem)
+            }
+            // The record is in the ring even if notifying the collector
+            // failed, and carries the drop count.
+            result => {
+                self.dropped = 0;
+                result
+            }
+        }
+    }
+
+    /// Returns the number of records dropped since the last one pushed.
+    pub fn dropped(&self) -> u16 {
+        self.dropped
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Collector
+// -----------------------------------------------------------------------------
+
+/// The statistics a `LogCollector` keeps for a producer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProducerStats {
+    /// The producer's process ID.
+    pub producer: u32,
+    /// The number of records received from the producer.
+    pub received: u32,
+    /// The number of records the producer reported dropping.
+    pub dropped: u32,
+}
+
+/// A log collector, draining the rings of `N` records of up to `P` producers.
+///
+/// Register the collector's `listener` with `IpcServer::register_listener`.
+/// Records of producers beyond the first `P` are still forwarded, but only
+/// counted in `untracked`.
+pub struct LogCollector<const P: usize = 8, const N: usize = 16, C: Config = DefaultConfig> {
+    stats: [Cell<Option<ProducerStats>>; P],
+    untracked: Cell<u32>,
+    _config: PhantomData<C>,
+}
+
+impl<const P: usize, const N: usize, C: Config> LogCollector<P, N, C> {
+    pub fn new() -> Self {
+        LogCollector {
+            stats: [(); P].map(|_| Cell::new(None)),
+            untracked: Cell::new(0),
+            _config: PhantomData,
+        }
+    }
+
+    /// Returns a listener to register with `IpcServer::register_listener`,
+    /// which drains the ring of the notifying producer, and passes each
+    /// record to `forward` with the producer's process ID.
+    ///
+    /// `forward` runs in the upcall, so if forwarding waits, e.g. for a flash
+    /// write, it should queue the record and let the main loop forward it.
+    pub fn listener<'a, F: Fn(u32, &LogRecord) + 'a>(
+        &'a self,
+        forward: F,
+    ) -> IpcServerListener<impl Fn(u32, &mut [u8]) + 'a, C> {
+        IpcServerListener::new(move |producer, buffer: &mut [u8]| {
+            // A producer without a ring cannot log.
+            let Ok(mut ring) = IpcRingConsumer::<[u8; RECORD_LEN], N>::from_shared(buffer) else {
+                return;
+            };
+            while let Some(bytes) = ring.pop() {
+                let record = LogRecord::decode(&bytes);
+                self.count(producer, &record);
+                forward(producer, &record);
+            }
+        })
+    }
+
+    /// Returns the statistics of `producer`, or `None` if it is not tracked.
+    pub fn stats(&self, producer: u32) -> Option<ProducerStats> {
+        self.producers().find(|stats| stats.producer == producer)
+    }
+
+    /// Returns the statistics of every tracked producer.
+    pub fn producers(&self) -> impl Iterator<Item = ProducerStats> + '_ {
+        self.stats.iter().filter_map(Cell::get)
+    }
+
+    /// Returns the total number of records producers reported dropping.
+    pub fn dropped(&self) -> u32 {
+        self.producers()
+            .fold(0, |total, stats| total.saturating_add(stats.dropped))
+    }
+
+    /// Returns the number of records received from untracked producers.
+    pub fn untracked(&self) -> u32 {
+        self.untracked.get()
+    }
+
+    fn count(&self, producer: u32, record: &LogRecord) {
+        let entry = self
+            .stats
+            .iter()
+            .find(|entry| matches!(entry.get(), Some(stats) if stats.producer == producer))
+            .or_else(|| self.stats.iter().find(|entry| entry.get().is_none()));
+        let Some(entry) = entry else {
+            self.untracked.set(self.untracked.get().saturating_add(1));
+            return;
+        };
+        let mut stats = entry.get().unwrap_or(ProducerStats {
+            producer,
+            ..Default::default()
+        });
+        stats.received = stats.received.saturating_add(1);
+        stats.dropped = stats.dropped.saturating_add(record.dropped as u32);
+        entry.set(Some(stats));
+    }
+}
+
+impl<const P: usize, const N: usize, C: Config> Default for LogCollector<P, N, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const TEXT_OFFSET: usize = 4;
@@ -0,0 +1,214 @@
+use crate::{Config, DRIVER_NUM};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::{TockFuture, TockStream};
+use libtock_platform::subscribe::{AnyId, OneId};
+use libtock_platform::{DefaultConfig, Syscalls, Upcall};
+
+/// Queues client notifications for a service, to be handled as a stream of
+/// `IpcRequest`s instead of in an `IpcServerListener`. Register it with
+/// `IpcServer::register_requests`.
+///
+/// Up to `N` notifications are queued; further notifications are dropped
+/// until the queue is drained (see `dropped`).
+///
+/// # Example
+/// ```ignore
+/// let requests = IpcRequests::<4>::new();
+/// share::scope(|subscribe| {
+///     IpcServer::register_requests(&requests, subscribe)?;
+///     loop {
+///         match block_on::<TockSyscalls, _>(select(requests.next(), timer)) {
+///             Either::Left(mut request) => {
+///                 request.buffer().make_ascii_uppercase();
+///                 IpcServer::notify_client(request.client())?;
+///             }
+///             Either::Right(_) => { /* ... */ }
+///         }
+///     }
+/// })
+/// ```
+pub struct IpcRequests<const N: usize = 4, C: Config = DefaultConfig> {
+    // A ring buffer of (client, len, address) upcall arguments.
+    queue: [Cell<(u32, u32, u32)>; N],
+    head: Cell<usize>,
+    len: Cell<usize>,
+    dropped: Cell<usize>,
+    _config: PhantomData<C>,
+}
+
+impl<const N: usize, C: Config> IpcRequests<N, C> {
+    pub fn new() -> Self {
+        IpcRequests {
+            queue: [(); N].map(|_| Cell::new((0, 0, 0))),
+            head: Cell::new(0),
+            len: Cell::new(0),
+            dropped: Cell::new(0),
+            _config: PhantomData,
+        }
+    }
+
+    /// Returns the next queued request, if any.
+    pub fn take(&self) -> Option<IpcRequest<C>> {
+        let len = self.len.get();
+        if len == 0 {
+            return None;
+        }
+        let head = self.head.get();
+        let (client, len_bytes, address) = self.queue[head].get();
+        self.head.set((head + 1) % N);
+        self.len.set(len - 1);
+        Some(IpcRequest {
+            client,
+            len: len_bytes as usize,
+            address,
+            _config: PhantomData,
+        })
+    }
+
+    /// Returns a future that completes with the next request.
+    pub fn next(&self) -> NextRequest<'_, N, C> {
+        NextRequest { requests: self }
+    }
+
+    /// Returns the number of notifications dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+}
+
+impl<const N: usize, C: Config> Default for IpcRequests<N, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, C: Config> Upcall<OneId<DRIVER_NUM, { crate::subscribe_num::SERVICE }>>
+    for IpcRequests<N, C>
+{
+    fn upcall(&self, client: u32, len: u32, address: u32) {
+        let queued = self.len.get();
+        if queued == N {
+            self.dropped.set(self.dropped.get() + 1);
+            return;
+        }
+        self.queue[(self.head.get() + queued) % N].set((client, len, address));
+        self.len.set(queued + 1);
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config> TockStream<S> for &IpcRequests<N, C> {
+    type Item = IpcRequest<C>;
+
+    fn poll_next(&mut self) -> Poll<IpcRequest<C>> {
+        match self.take() {
+            Some(request) => Poll::Ready(request),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A future that completes with the next request queued in an `IpcRequests`.
+pub struct NextRequest<'a, const N: usize, C: Config> {
+    requests: &'a IpcRequests<N, C>,
+}
+
+impl<S: Syscalls, const N: usize, C: Config> TockFuture<S> for NextRequest<'_, N, C> {
+    type Output = IpcRequest<C>;
+
+    fn poll(&mut self) -> Poll<IpcRequest<C>> {
+        TockStream::<S>::poll_next(&mut self.requests)
+    }
+}
+
+/// A notification from a client, with the buffer it shares with the service.
+pub struct IpcRequest<C: Config = DefaultConfig> {
+    client: u32,
+    len: usize,
+    address: u32,
+    _config: PhantomData<C>,
+}
+
+impl<C: Config> IpcRequest<C> {
+    /// Returns the process ID of the client.
+    pub fn client(&self) -> u32 {
+        self.client
+    }
+
+    /// Returns the buffer the client shared with the service when it sent
+    /// the notification (empty if it shared none).
+    ///
+    /// The client can share a different buffer once the service yields, so a
+    /// request should be handled before the service waits for the next event.
+    pub fn buffer(&mut self) -> &mut [u8] {
+        match C::resolve_address(self.address, self.len) {
+            // Safety: See IpcServerListener's upcall. The returned reference
+            // borrows the request mutably, so only one reference to the buffer
+            // exists at a time.
+            Some(pointer) if self.len > 0 => unsafe {
+                core::slice::from_raw_parts_mut(pointer, self.len)
+            },
+            _ => &mut [],
+        }
+    }
+}
+
+/// Records notifications from a service, for a client to await. Register it
+/// with `IpcClient::register_reply`.
+///
+/// Notifications that arrive while a previous one has not been taken are
+/// merged into it.
+#[derive(Default)]
+pub struct IpcReply {
+    notified: Cell<bool>,
+}
+
+impl IpcReply {
+    pub fn new() -> Self {
+        IpcReply {
+            notified: Cell::new(false),
+        }
+    }
+
+    /// Returns whether the service has notified the client since the last
+    /// call, and clears the notification.
+    pub fn take(&self) -> bool {
+        self.notified.replace(false)
+    }
+
+    /// Returns a future that completes when the service notifies the client.
+    pub fn wait(&self) -> WaitReply<'_> {
+        WaitReply { reply: self }
+    }
+}
+
+impl Upcall<AnyId> for IpcReply {
+    fn upcall(&self, _service: u32, _len: u32, _address: u32) {
+        self.notified.set(true);
+    }
+}
+
+impl<S: Syscalls> TockStream<S> for &IpcReply {
+    type Item = ();
+
+    fn poll_next(&mut self) -> Poll<()> {
+        match self.take() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+/// A future that completes when an `IpcReply` is notified.
+pub struct WaitReply<'a> {
+    reply: &'a IpcReply,
+}
+
+impl<S: Syscalls> TockFuture<S> for WaitReply<'_> {
+    type Output = ();
+
+    fn poll(&mut self) -> Poll<()> {
+        TockStream::<S>::poll_next(&mut self.reply)
+    }
+}
@@ -0,0 +1,377 @@
+//! Publish/subscribe messaging through a broker process.
+//!
+//! One process runs a `PubSubBroker` as an IPC service. Other processes use a
+//! `PubSubClient` to subscribe to topics (identified by `u32` IDs), publish
+//! messages on topics, and receive the messages published on the topics they
+//! subscribe to. The broker copies each published message into the shared
+//! buffer of every subscriber that is waiting for a message.
+//!
+//! Every exchange starts with a request from the client, in the buffer it
+//! shares with the broker, and ends with the broker writing a single reply
+//! into that buffer and notifying the client. The broker never writes into a
+//! client's buffer otherwise, so the client can safely stop sharing the
+//! buffer (e.g. to read a message) between exchanges. Messages, requests, and
+//! replies all start with an 8-byte header:
+//!
+//! * byte 0: the operation: `SUBSCRIBE`, `UNSUBSCRIBE`, `PUBLISH`, `RECEIVE`
+//!   (client to broker), or `ACK`, `DELIVER` (broker to client).
+//! * byte 1: the status of a reply: 0 on success, otherwise an `ErrorCode`.
+//! * bytes 2-3: the length of the payload following the header (little
+//!   endian).
+//! * bytes 4-7: the topic (little endian).
+//!
+//! Subscribe, unsubscribe, and publish requests are answered with an `ACK`.
+//! A receive request is answered with a `DELIVER` carrying the next message
+//! published on one of the client's topics. Messages published while a
+//! subscriber is not waiting are dropped for that subscriber.
+//!
+//! # Example
+//! ```ignore
+//! const TEMPERATURE: u32 = 1;
+//!
+//! // Broker
+//! let broker = PubSubBroker::<16>::new();
+//! let listener = broker.listener();
+//! share::scope(|subscribe| {
+//!     IpcServer::register_listener(&listener, subscribe)?;
+//!     loop {
+//!         TockSyscalls::yield_wait();
+//!     }
+//! })
+//!
+//! // Publisher, with the broker running as process 1
+//! type Broker = PubSubClient<1, { client_upcall(1) }>;
+//! Broker::publish(&mut buffer, TEMPERATURE, &2150i32.to_le_bytes())?;
+//!
+//! // Subscriber
+//! Broker::subscribe(&mut buffer, TEMPERATURE)?;
+//! loop {
+//!     let (topic, payload) = Broker::receive(&mut buffer)?;
+//! }
+//! ```
+
+use crate::{Config, IpcClient, IpcServer, IpcServerListener};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The length of the message header.
+pub const HEADER_LEN: usize = 8;
+
+// Operations.
+pub const SUBSCRIBE: u8 = 1;
+pub const UNSUBSCRIBE: u8 = 2;
+pub const PUBLISH: u8 = 3;
+pub const RECEIVE: u8 = 4;
+pub const ACK: u8 = 5;
+pub const DELIVER: u8 = 6;
+
+/// A decoded message header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Header {
+    pub op: u8,
+    pub status: u8,
+    pub len: u16,
+    pub topic: u32,
+}
+
+impl Header {
+    /// Decodes the header at the start of `buffer`. Returns `None` if `buffer`
+    /// is shorter than the header.
+    pub fn read(buffer: &[u8]) -> Option<Header> {
+        let header = buffer.get(..HEADER_LEN)?;
+        Some(Header {
+            op: header[0],
+            status: header[1],
+            len: u16::from_le_bytes([header[2], header[3]]),
+            topic: u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+        })
+    }
+
+    /// Encodes this header at the start of `buffer`. Fails with
+    /// `ErrorCode::Size` if `buffer` is shorter than the header.
+    pub fn write(&self, buffer: &mut [u8]) -> Result<(), ErrorCode> {
+        let header = buffer.get_mut(..HEADER_LEN).ok_or(ErrorCode::Size)?;
+        header[0] = self.op;
+        header[1] = self.status;
+        header[2..4].copy_from_slice(&self.len.to_le_bytes());
+        header[4..8].copy_from_slice(&self.topic.to_le_bytes());
+        Ok(())
+    }
+
+    /// Returns the status of a reply as a `Result`.
+    pub fn result(&self) -> Result<(), ErrorCode> {
+        match self.status {
+            0 => Ok(()),
+            status => Err(ErrorCode::try_from(status as u32).unwrap_or(ErrorCode::Fail)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Broker
+// -----------------------------------------------------------------------------
+
+/// A publish/subscribe broker, serving up to `N` subscriptions.
+///
+/// Register the broker's `listener` with `IpcServer::register_listener`.
+pub struct PubSubBroker<S: Syscalls, const N: usize = 16, C: Config = DefaultConfig> {
+    // (client, topic) pairs.
+    subscriptions: [Cell<Option<(u32, u32)>>; N],
+    // Clients waiting for a message. A client only waits if it has a
+    // subscription, so N entries suffice.
+    receivers: [Cell<Option<Receiver>>; N],
+    dropped: Cell<usize>,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+// A client waiting for a message, with the buffer (pointer and length) to
+// deliver it into.
+type Receiver = (u32, *mut u8, usize);
+
+impl<S: Syscalls, const N: usize, C: Config> PubSubBroker<S, N, C> {
+    pub fn new() -> Self {
+        PubSubBroker {
+            subscriptions: [(); N].map(|_| Cell::new(None)),
+            receivers: [(); N].map(|_| Cell::new(None)),
+            dropped: Cell::new(0),
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Returns the number of messages dropped because a subscriber was not
+    /// waiting for one.
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+
+    /// Returns a listener to register with `IpcServer::register_listener`.
+    pub fn listener(&self) -> IpcServerListener<impl Fn(u32, &mut [u8]) + '_, C> {
+        IpcServerListener::new(move |client, buffer: &mut [u8]| {
+            // A request too short for a header cannot be answered.
+            let Some(request) = Header::read(buffer) else {
+                return;
+            };
+            // The client reuses its buffer for every request, so a new request
+            // cancels a pending receive.
+            self.take_receiver(client);
+            if request.op == RECEIVE {
+                if let Err(error) = self.wait(client, buffer) {
+                    self.reply(client, buffer, request, Err(error));
+                }
+                return;
+            }
+            let result = match request.op {
+                SUBSCRIBE => self.subscribe(client, request.topic),
+                UNSUBSCRIBE => self.unsubscribe(client, request.topic),
+                PUBLISH => self.publish(client, request, buffer),
+                _ => Err(ErrorCode::NoSupport),
+            };
+            self.reply(client, buffer, request, result);
+        })
+    }
+
+    fn subscribe(&self, client: u32, topic: u32) -> Result<(), ErrorCode> {
+        if self.subscribed(client, topic) {
+            return Ok(());
+        }
+        let free = self
+            .subscriptions
+            .iter()
+            .find(|entry| entry.get().is_none());
+        free.ok_or(ErrorCode::NoMem)?.set(Some((client, topic)));
+        Ok(())
+    }
+
+    fn unsubscribe(&self, client: u32, topic: u32) -> Result<(), ErrorCode> {
+        let entry = self
+            .subscriptions
+            .iter()
+            .find(|entry| entry.get() == Some((client, topic)));
+        entry.ok_or(ErrorCode::Invalid)?.set(None);
+        Ok(())
+    }
+
+    fn subscribed(&self, client: u32, topic: u32) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|entry| entry.get() == Some((client, topic)))
+    }
+
+    // Records that `client` waits for a message, in `buffer`.
+    fn wait(&self, client: u32, buffer: &mut [u8]) -> Result<(), ErrorCode> {
+        let subscribed = |entry: &Cell<Option<(u32, u32)>>| matches!(entry.get(), Some((subscriber, _)) if subscriber == client);
+        if !self.subscriptions.iter().any(subscribed) {
+            return Err(ErrorCode::Invalid);
+        }
+        let free = self.receivers.iter().find(|entry| entry.get().is_none());
+        let receiver = (client, buffer.as_mut_ptr(), buffer.len());
+        free.ok_or(ErrorCode::NoMem)?.set(Some(receiver));
+        Ok(())
+    }
+
+    fn take_receiver(&self, client: u32) -> Option<(*mut u8, usize)> {
+        self.receivers.iter().find_map(|entry| match entry.get() {
+            Some((receiver, pointer, len)) if receiver == client => {
+                entry.set(None);
+                Some((pointer, len))
+            }
+            _ => None,
+        })
+    }
+
+    fn publish(&self, publisher: u32, request: Header, buffer: &[u8]) -> Result<(), ErrorCode> {
+        let payload = buffer
+            .get(HEADER_LEN..HEADER_LEN + request.len as usize)
+            .ok_or(ErrorCode::Size)?;
+        for entry in &self.subscriptions {
+            let Some((client, topic)) = entry.get() else {
+                continue;
+            };
+            if topic != request.topic || client == publisher {
+                continue;
+            }
+            let Some((pointer, len)) = self.take_receiver(client) else {
+                self.dropped.set(self.dropped.get() + 1);
+                continue;
+            };
+            // Safety: The client shared this buffer with the broker in its
+            // receive request, and the broker has not notified it since, so it
+            // is still shared and only the broker accesses it. It is not the
+            // publisher's buffer, as the publisher is skipped.
+            let buffer = unsafe { core::slice::from_raw_parts_mut(pointer, len) };
+            self.deliver(client, buffer, request.topic, payload);
+        }
+        Ok(())
+    }
+
+    fn deliver(&self, client: u32, buffer: &mut [u8], topic: u32, payload: &[u8]) {
+        let fits = HEADER_LEN + payload.len() <= buffer.len();
+        let header = Header {
+            op: DELIVER,
+            status: if fits { 0 } else { ErrorCode::Size as u8 },
+            len: if fits { payload.len() as u16 } else { 0 },
+            topic,
+        };
+        if header.write(buffer).is_err() {
+            return;
+        }
+        if fits {
+            buffer[HEADER_LEN..HEADER_LEN + payload.len()].copy_from_slice(payload);
+        }
+        let _ = IpcServer::<S, C>::notify_client(client);
+    }
+
+    fn reply(
+        &self,
+        client: u32,
+        buffer: &mut [u8],
+        request: Header,
+        result: Result<(), ErrorCode>,
+    ) {
+        let header = Header {
+            op: ACK,
+            status: match result {
+                Ok(()) => 0,
+                Err(error) => error as u8,
+            },
+            len: 0,
+            topic: request.topic,
+        };
+        // The buffer holds at least a header, as the request was decoded.
+        let _ = header.write(buffer);
+        let _ = IpcServer::<S, C>::notify_client(client);
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config> Default for PubSubBroker<S, N, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Client
+// -----------------------------------------------------------------------------
+
+/// A client of the broker with process ID `BROKER`. `SUBSCRIBE_NUM` must be
+/// `client_upcall(BROKER)`.
+///
+/// Each operation shares `buffer` with the broker for the duration of the
+/// exchange, and blocks until the broker replies.
+pub struct PubSubClient<
+    S: Syscalls,
+    const BROKER: u32,
+    const SUBSCRIBE_NUM: u32,
+    C: Config = DefaultConfig,
+>(S, C);
+
+impl<S: Syscalls, const BROKER: u32, const SUBSCRIBE_NUM: u32, C: Config>
+    PubSubClient<S, BROKER, SUBSCRIBE_NUM, C>
+{
+    /// Subscribes to `topic`. Fails with `ErrorCode::NoMem` if the broker
+    /// cannot hold more subscriptions.
+    pub fn subscribe(buffer: &mut [u8], topic: u32) -> Result<(), ErrorCode> {
+        Self::request(buffer, SUBSCRIBE, topic, &[])
+    }
+
+    /// Unsubscribes from `topic`. Fails with `ErrorCode::Invalid` if the client
+    /// is not subscribed to it.
+    pub fn unsubscribe(buffer: &mut [u8], topic: u32) -> Result<(), ErrorCode> {
+        Self::request(buffer, UNSUBSCRIBE, topic, &[])
+    }
+
+    /// Publishes `payload` on `topic`, to the subscribers that are waiting for
+    /// a message. Fails with `ErrorCode::Size` if `buffer` cannot hold the
+    /// header and the payload.
+    pub fn publish(buffer: &mut [u8], topic: u32, payload: &[u8]) -> Result<(), ErrorCode> {
+        Self::request(buffer, PUBLISH, topic, payload)
+    }
+
+    /// Waits for the next message published on one of the client's topics,
+    /// and returns its topic and payload. Fails with `ErrorCode::Invalid` if
+    /// the client has no subscriptions, and with `ErrorCode::Size` if the
+    /// message did not fit in `buffer`.
+    pub fn receive(buffer: &mut [u8]) -> Result<(u32, &[u8]), ErrorCode> {
+        let reply = Self::exchange(buffer, RECEIVE, 0, &[])?;
+        if reply.op != DELIVER {
+            return Err(ErrorCode::Fail);
+        }
+        let payload = buffer
+            .get(HEADER_LEN..HEADER_LEN + reply.len as usize)
+            .ok_or(ErrorCode::Fail)?;
+        Ok((reply.topic, payload))
+    }
+
+    fn request(buffer: &mut [u8], op: u8, topic: u32, payload: &[u8]) -> Result<(), ErrorCode> {
+        match Self::exchange(buffer, op, topic, payload)?.op {
+            ACK => Ok(()),
+            _ => Err(ErrorCode::Fail),
+        }
+    }
+
+    // Sends a request and returns the reply's header, if it reports success.
+    fn exchange(
+        buffer: &mut [u8],
+        op: u8,
+        topic: u32,
+        payload: &[u8],
+    ) -> Result<Header, ErrorCode> {
+        let len = u16::try_from(payload.len()).map_err(|_| ErrorCode::Size)?;
+        Header {
+            op,
+            status: 0,
+            len,
+            topic,
+        }
+        .write(buffer)?;
+        buffer
+            .get_mut(HEADER_LEN..HEADER_LEN + payload.len())
+            .ok_or(ErrorCode::Size)?
+            .copy_from_slice(payload);
+        IpcClient::<S, C>::exchange::<BROKER, SUBSCRIBE_NUM>(buffer)?;
+        let reply = Header::read(buffer).ok_or(ErrorCode::Fail)?;
+        reply.result()?;
+        Ok(reply)
+    }
+}
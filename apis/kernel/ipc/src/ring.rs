@@ -0,0 +1,280 @@
+use crate::{Config, IpcClient, DRIVER_NUM};
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::ptr::{addr_of, addr_of_mut, NonNull};
+use core::sync::atomic::{fence, Ordering};
+use libtock_platform::{share, AllowRw, DefaultConfig, ErrorCode, Syscalls};
+use zerocopy::{AsBytes, FromBytes};
+
+/// A single-producer, single-consumer ring buffer, shared once by a client
+/// (the producer) with a service (the consumer) and then used for any number
+/// of values without sharing it again. Suited to streaming samples at a high
+/// rate, e.g. from a sensor process to a radio process.
+///
+/// The layout is fixed, so both sides agree on it without negotiation:
+///
+/// * offset 0: `read_index` (`u32`), updated by the consumer only.
+/// * offset 4: `write_index` (`u32`), updated by the producer only.
+/// * from offset 8: `N` slots of `T`, which must be aligned to at most 8
+///   bytes, so that the ring has no padding.
+///
+/// Both indices count modulo `2 * N`, and slot `index % N` holds the value at
+/// `index`. This distinguishes a full buffer from an empty one, so unlike
+/// `RxRingBuffer`, all `N` slots are used.
+///
+/// The client shares the buffer with `IpcClient::share_ring`, which returns an
+/// `IpcRingProducer`. Pushing a value into an empty buffer notifies the
+/// service, whose `IpcServerListener` wraps the buffer it receives with
+/// `IpcRingConsumer::from_shared` and pops values until the buffer is empty.
+/// As `T` is `FromBytes + AsBytes`, neither side has to trust the other's
+/// writes; a corrupted index only produces wrong values.
+///
+/// Tock exposes the buffer through the service's MPU, which on most chips
+/// can only protect regions whose size is a power of two and whose start is
+/// aligned to that size (see `IpcSharedSlot`). Choose `T` and `N` accordingly,
+/// and align the buffer, e.g. by placing it in a `#[repr(align(..))]` wrapper.
+///
+/// # Example
+/// ```ignore
+/// // Client (sensor), with the service running as process 1
+/// let mut ring = IpcRingBuffer::<Sample, 16>::new();
+/// share::scope(|allow| {
+///     let mut producer = IpcClient::share_ring::<_, 16, 1>(allow, &mut ring)?;
+///     loop {
+///         let sample = read_sensor();
+///         if producer.push(&sample).is_err() {
+///             // The radio process is falling behind; drop the sample.
+///         }
+///     }
+/// })
+///
+/// // Service (radio), in its listener
+/// let Ok(mut consumer) = IpcRingConsumer::<Sample, 16>::from_shared(buffer) else {
+///     return;
+/// };
+/// while let Some(sample) = consumer.pop() {
+///     transmit(&sample);
+/// }
+/// ```
+#[derive(Debug)]
+#[repr(C)]
+pub struct IpcRingBuffer<T: FromBytes + AsBytes, const N: usize> {
+    /// The index of the next value the consumer will pop.
+    /// Updated by the consumer only.
+    read_index: u32,
+    /// The index of the next value the producer will push.
+    /// Updated by the producer only.
+    write_index: u32,
+    /// Slots for the values.
+    slots: [T; N],
+}
+
+impl<T: FromBytes + AsBytes, const N: usize> IpcRingBuffer<T, N> {
+    // Indices count modulo WRAP, which must fit in an index. The slots must
+    // directly follow the indices, as padding bytes could not be shared.
+    const WRAP: u32 = {
+        assert!(N > 0 && N <= (u32::MAX / 2) as usize);
+        assert!(align_of::<T>() <= 8);
+        2 * N as u32
+    };
+
+    /// Creates a new, empty [IpcRingBuffer].
+    pub fn new() -> Self {
+        IpcRingBuffer {
+            read_index: 0,
+            write_index: 0,
+            slots: [(); N].map(|_| T::new_zeroed()),
+        }
+    }
+
+    // The number of values in the ring, given its indices.
+    fn len(read_index: u32, write_index: u32) -> usize {
+        (write_index.wrapping_add(Self::WRAP - read_index % Self::WRAP) % Self::WRAP) as usize
+    }
+
+    // Returns a pointer to the slot holding the value at `index`.
+    //
+    // Safety: `ring` must point to a valid IpcRingBuffer.
+    unsafe fn slot(ring: NonNull<Self>, index: u32) -> *mut T {
+        let slots = addr_of_mut!((*ring.as_ptr()).slots) as *mut T;
+        slots.add(index as usize % N)
+    }
+}
+
+impl<T: FromBytes + AsBytes, const N: usize> Default for IpcRingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The client's end of a shared [IpcRingBuffer], returned by
+/// `IpcClient::share_ring`.
+pub struct IpcRingProducer<
+    'share,
+    S: Syscalls,
+    T: FromBytes + AsBytes,
+    const N: usize,
+    C: Config = DefaultConfig,
+> {
+    // The ring, which is shared with the service. Rust code only accesses it
+    // through this pointer while it is shared.
+    ring: NonNull<IpcRingBuffer<T, N>>,
+    service: u32,
+    _ring: PhantomData<&'share mut IpcRingBuffer<T, N>>,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<'share, S: Syscalls, T: FromBytes + AsBytes, const N: usize, C: Config>
+    IpcRingProducer<'share, S, T, N, C>
+{
+    /// Pushes `value` into the ring, and notifies the service if the ring was
+    /// empty. Fails with `ErrorCode::NoMem` if the ring is full.
+    pub fn push(&mut self, value: &T) -> Result<(), ErrorCode> {
+        let ring = self.ring.as_ptr();
+        // Safety: `ring` points to the IpcRingBuffer borrowed for 'share. The
+        // service may access it concurrently, so it is only accessed through
+        // volatile reads and writes of whole fields, and the only field the
+        // service writes, `read_index`, is only read here.
+        unsafe {
+            let write_index = addr_of!((*ring).write_index).read_volatile();
+            let read_index = addr_of!((*ring).read_index).read_volatile();
+            let len = IpcRingBuffer::<T, N>::len(read_index, write_index);
+            if len >= N {
+                return Err(ErrorCode::NoMem);
+            }
+            IpcRingBuffer::slot(self.ring, write_index).write_volatile(read_value(value));
+            // Publish the value before the index that makes it visible.
+            fence(Ordering::SeqCst);
+            let next = write_index.wrapping_add(1) % IpcRingBuffer::<T, N>::WRAP;
+            addr_of_mut!((*ring).write_index).write_volatile(next);
+            fence(Ordering::SeqCst);
+            // Re-read the consumer's index: if it has caught up with the old
+            // write index, the consumer may have stopped popping before seeing
+            // this value, and must be notified.
+            let read_index = addr_of!((*ring).read_index).read_volatile();
+            if read_index % IpcRingBuffer::<T, N>::WRAP == write_index {
+                return IpcClient::<S, C>::notify_service(self.service);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of values the service has not popped yet.
+    pub fn len(&self) -> usize {
+        let ring = self.ring.as_ptr();
+        // Safety: see `push`.
+        let (read_index, write_index) = unsafe {
+            (
+                addr_of!((*ring).read_index).read_volatile(),
+                addr_of!((*ring).write_index).read_volatile(),
+            )
+        };
+        IpcRingBuffer::<T, N>::len(read_index, write_index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The service's end of a shared [IpcRingBuffer], wrapping the buffer passed
+/// to its `IpcServerListener`.
+pub struct IpcRingConsumer<'a, T: FromBytes + AsBytes, const N: usize> {
+    ring: NonNull<IpcRingBuffer<T, N>>,
+    _ring: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a, T: FromBytes + AsBytes, const N: usize> IpcRingConsumer<'a, T, N> {
+    /// Wraps a ring shared by a client. Fails with `ErrorCode::Size` if
+    /// `buffer` is too short to hold an `IpcRingBuffer<T, N>`, and with
+    /// `ErrorCode::Invalid` if it is not aligned for one.
+    pub fn from_shared(buffer: &'a mut [u8]) -> Result<Self, ErrorCode> {
+        if buffer.len() < size_of::<IpcRingBuffer<T, N>>() {
+            return Err(ErrorCode::Size);
+        }
+        if buffer.as_ptr() as usize % align_of::<IpcRingBuffer<T, N>>() != 0 {
+            return Err(ErrorCode::Invalid);
+        }
+        Ok(IpcRingConsumer {
+            ring: NonNull::from(buffer).cast(),
+            _ring: PhantomData,
+        })
+    }
+
+    /// Pops the oldest value from the ring, or returns `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let ring = self.ring.as_ptr();
+        // Safety: `ring` points to the buffer borrowed for 'a, which is large
+        // enough and aligned for an IpcRingBuffer, and any bytes are valid for
+        // its fields. The client may access it concurrently, so it is only
+        // accessed through volatile reads and writes of whole fields, and the
+        // only field the client writes, `write_index`, is only read here.
+        unsafe {
+            let read_index = addr_of!((*ring).read_index).read_volatile();
+            let write_index = addr_of!((*ring).write_index).read_volatile();
+            if IpcRingBuffer::<T, N>::len(read_index, write_index) == 0 {
+                return None;
+            }
+            // Read the value only after the index that made it visible.
+            fence(Ordering::SeqCst);
+            let value = IpcRingBuffer::slot(self.ring, read_index).read_volatile();
+            fence(Ordering::SeqCst);
+            let next = (read_index % IpcRingBuffer::<T, N>::WRAP + 1) % IpcRingBuffer::<T, N>::WRAP;
+            addr_of_mut!((*ring).read_index).write_volatile(next);
+            Some(value)
+        }
+    }
+
+    /// Returns the number of values in the ring.
+    pub fn len(&self) -> usize {
+        let ring = self.ring.as_ptr();
+        // Safety: see `pop`.
+        let (read_index, write_index) = unsafe {
+            (
+                addr_of!((*ring).read_index).read_volatile(),
+                addr_of!((*ring).write_index).read_volatile(),
+            )
+        };
+        IpcRingBuffer::<T, N>::len(read_index, write_index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S: Syscalls, C: Config> IpcClient<S, C> {
+    /// Shares `ring` with service `SERVICE`, and returns the producer end of
+    /// it. The ring stays shared until `allow_rw`'s scope ends.
+    pub fn share_ring<'share, T: FromBytes + AsBytes, const N: usize, const SERVICE: u32>(
+        allow_rw: share::Handle<AllowRw<'share, S, DRIVER_NUM, SERVICE>>,
+        ring: &'share mut IpcRingBuffer<T, N>,
+    ) -> Result<IpcRingProducer<'share, S, T, N, C>, ErrorCode> {
+        let _ = IpcRingBuffer::<T, N>::WRAP;
+        let ring = NonNull::from(ring);
+        // Safety: `ring` is borrowed for 'share, and every byte of an
+        // IpcRingBuffer is initialized (WRAP's assertions rule out padding
+        // around the slots, and T: AsBytes has none), so it can be viewed as
+        // bytes. Once shared, the
+        // bytes are only accessed by the kernel, the service, and the producer
+        // (through `ring`).
+        let buffer = unsafe {
+            core::slice::from_raw_parts_mut(
+                ring.as_ptr() as *mut u8,
+                size_of::<IpcRingBuffer<T, N>>(),
+            )
+        };
+        S::allow_rw::<C, DRIVER_NUM, SERVICE>(allow_rw, buffer)?;
+        Ok(IpcRingProducer {
+            ring,
+            service: SERVICE,
+            _ring: PhantomData,
+            _syscalls: PhantomData,
+        })
+    }
+}
+
+// Copies `value`, which is not Copy, through its bytes.
+fn read_value<T: FromBytes + AsBytes>(value: &T) -> T {
+    T::read_from(value.as_bytes()).expect("value has the size of T")
+}
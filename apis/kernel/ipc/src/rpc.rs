@@ -0,0 +1,230 @@
+//! Request/response RPC over IPC.
+//!
+//! A service handles requests with an `RpcServer`, which dispatches each
+//! request to the `RpcMethod` registered for its method ID. A client sends a
+//! request with `RpcClient::call`, which serializes it into the buffer shared
+//! with the service, notifies the service, and waits for its reply
+//! notification.
+//!
+//! Messages are serialized with postcard, in the buffer the client shares with
+//! the service:
+//!
+//! * Request: the method ID (a `u32`) followed by the request value.
+//! * Response: a status byte (0 on success, an `RpcError` code otherwise)
+//!   followed, on success, by the response value.
+//!
+//! # Example
+//! ```ignore
+//! const ADD: u32 = 1;
+//!
+//! // Service
+//! let add = RpcMethod::new(ADD, |(a, b): (u32, u32)| a + b);
+//! let server = RpcServer::new(&[&add]);
+//! let listener = server.listener();
+//! share::scope(|subscribe| {
+//!     IpcServer::register_listener(&listener, subscribe)?;
+//!     loop {
+//!         TockSyscalls::yield_wait();
+//!     }
+//! })
+//!
+//! // Client, with the service running as process 1
+//! type Adder = RpcClient<1, { client_upcall(1) }>;
+//! let mut buffer = [0; 32];
+//! let sum: u32 = Adder::call(&mut buffer, ADD, &(2u32, 3u32))?;
+//! ```
+
+use crate::{Config, IpcClient, IpcServer, IpcServerListener};
+use core::marker::PhantomData;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The ways an RPC can fail.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RpcError {
+    /// The service has no method with the requested ID.
+    UnknownMethod,
+    /// The service could not deserialize the request.
+    BadRequest,
+    /// The response did not fit in the shared buffer.
+    ResponseTooLarge,
+    /// The request did not fit in the shared buffer.
+    RequestTooLarge,
+    /// The client could not deserialize the response, or the response had an
+    /// unknown status.
+    BadResponse,
+    /// A system call failed, e.g. because there is no such service.
+    Kernel(ErrorCode),
+}
+
+impl RpcError {
+    // Decodes a response status byte.
+    fn from_status(status: u8) -> RpcError {
+        match status {
+            STATUS_UNKNOWN_METHOD => RpcError::UnknownMethod,
+            STATUS_BAD_REQUEST => RpcError::BadRequest,
+            STATUS_RESPONSE_TOO_LARGE => RpcError::ResponseTooLarge,
+            _ => RpcError::BadResponse,
+        }
+    }
+
+    // Encodes the error as a response status byte. Errors that only occur on
+    // the client side have no status of their own.
+    fn status(self) -> u8 {
+        match self {
+            RpcError::UnknownMethod => STATUS_UNKNOWN_METHOD,
+            RpcError::BadRequest => STATUS_BAD_REQUEST,
+            RpcError::ResponseTooLarge => STATUS_RESPONSE_TOO_LARGE,
+            _ => STATUS_FAILED,
+        }
+    }
+}
+
+impl From<ErrorCode> for RpcError {
+    fn from(error: ErrorCode) -> RpcError {
+        RpcError::Kernel(error)
+    }
+}
+
+// Response status bytes.
+const STATUS_OK: u8 = 0;
+const STATUS_UNKNOWN_METHOD: u8 = 1;
+const STATUS_BAD_REQUEST: u8 = 2;
+const STATUS_RESPONSE_TOO_LARGE: u8 = 3;
+const STATUS_FAILED: u8 = 0xff;
+
+// -----------------------------------------------------------------------------
+// Service side
+// -----------------------------------------------------------------------------
+
+/// A method of an `RpcServer`, as seen by the server. Implemented by
+/// `RpcMethod`; implement it directly to handle requests without decoding
+/// them into owned values.
+pub trait RpcHandler {
+    /// The ID clients call this method by.
+    fn method(&self) -> u32;
+
+    /// Handles the request at `buffer[request_start..]`, and serializes the
+    /// response into `buffer[1..]` (the server writes the status byte). The
+    /// response overwrites the request, so the request must be decoded first.
+    fn handle(&self, buffer: &mut [u8], request_start: usize) -> Result<(), RpcError>;
+}
+
+/// A method that deserializes its request into `Req`, passes it to a handler
+/// function, and serializes the function's result.
+pub struct RpcMethod<Req, Resp, F: Fn(Req) -> Resp> {
+    method: u32,
+    handler: F,
+    _types: PhantomData<fn(Req) -> Resp>,
+}
+
+impl<Req, Resp, F: Fn(Req) -> Resp> RpcMethod<Req, Resp, F> {
+    pub fn new(method: u32, handler: F) -> Self {
+        RpcMethod {
+            method,
+            handler,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<Req: DeserializeOwned, Resp: Serialize, F: Fn(Req) -> Resp> RpcHandler
+    for RpcMethod<Req, Resp, F>
+{
+    fn method(&self) -> u32 {
+        self.method
+    }
+
+    fn handle(&self, buffer: &mut [u8], request_start: usize) -> Result<(), RpcError> {
+        let request =
+            postcard::from_bytes(&buffer[request_start..]).map_err(|_| RpcError::BadRequest)?;
+        let response = (self.handler)(request);
+        postcard::to_slice(&response, &mut buffer[1..]).map_err(|_| RpcError::ResponseTooLarge)?;
+        Ok(())
+    }
+}
+
+/// Dispatches requests to the methods of a service.
+pub struct RpcServer<'h, S: Syscalls, C: Config = DefaultConfig> {
+    handlers: &'h [&'h dyn RpcHandler],
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<'h, S: Syscalls, C: Config> RpcServer<'h, S, C> {
+    pub fn new(handlers: &'h [&'h dyn RpcHandler]) -> Self {
+        RpcServer {
+            handlers,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Handles the request in `buffer`, replacing it with the response.
+    /// Does nothing if `buffer` is empty, as there is no room for a response.
+    pub fn dispatch(&self, buffer: &mut [u8]) {
+        if buffer.is_empty() {
+            return;
+        }
+        let result = match postcard::take_from_bytes::<u32>(buffer) {
+            Err(_) => Err(RpcError::BadRequest),
+            Ok((method, request)) => {
+                let request_start = buffer.len() - request.len();
+                match self.handlers.iter().find(|h| h.method() == method) {
+                    None => Err(RpcError::UnknownMethod),
+                    Some(handler) => handler.handle(buffer, request_start),
+                }
+            }
+        };
+        buffer[0] = match result {
+            Ok(()) => STATUS_OK,
+            Err(error) => error.status(),
+        };
+    }
+
+    /// Returns a listener to register with `IpcServer::register_listener`,
+    /// which dispatches each client's request and notifies the client when
+    /// its response is ready.
+    pub fn listener(&self) -> IpcServerListener<impl Fn(u32, &mut [u8]) + '_, C> {
+        IpcServerListener::new(move |client, buffer: &mut [u8]| {
+            self.dispatch(buffer);
+            let _ = IpcServer::<S, C>::notify_client(client);
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Client side
+// -----------------------------------------------------------------------------
+
+/// Calls the methods of the service with process ID `SERVICE`.
+/// `SUBSCRIBE_NUM` must be `client_upcall(SERVICE)`; it is a separate
+/// parameter as it cannot be computed from `SERVICE` in a generic context.
+pub struct RpcClient<
+    S: Syscalls,
+    const SERVICE: u32,
+    const SUBSCRIBE_NUM: u32,
+    C: Config = DefaultConfig,
+>(S, C);
+
+impl<S: Syscalls, const SERVICE: u32, const SUBSCRIBE_NUM: u32, C: Config>
+    RpcClient<S, SERVICE, SUBSCRIBE_NUM, C>
+{
+    /// Calls method `method` of the service with `request`, using `buffer` as
+    /// the shared buffer, and waits for the response. The buffer must be large
+    /// enough for both the serialized request and response.
+    pub fn call<Req: Serialize, Resp: DeserializeOwned>(
+        buffer: &mut [u8],
+        method: u32,
+        request: &Req,
+    ) -> Result<Resp, RpcError> {
+        postcard::to_slice(&(method, request), buffer).map_err(|_| RpcError::RequestTooLarge)?;
+        IpcClient::<S, C>::exchange::<SERVICE, SUBSCRIBE_NUM>(buffer)?;
+
+        match buffer.split_first() {
+            Some((&STATUS_OK, response)) => {
+                postcard::from_bytes(response).map_err(|_| RpcError::BadResponse)
+            }
+            Some((&status, _)) => Err(RpcError::from_status(status)),
+            None => Err(RpcError::BadResponse),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{command, subscribe_num, Config, IpcRequests, DRIVER_NUM};
+use core::marker::PhantomData;
+use libtock_platform::{
+    share, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+/// The service side of IPC.
+///
+/// A process becomes a service by registering an `IpcServerListener`, which is
+/// called whenever a client notifies it, with the client's process ID and the
+/// buffer the client shares with it. The service typically handles the request
+/// in the buffer, writes its response there, and notifies the client with
+/// `notify_client`.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ipc::{IpcServer, IpcServerListener};
+///
+/// // Echoes requests back in upper case.
+/// let listener = IpcServerListener::new(|client, buffer: &mut [u8]| {
+///     buffer.make_ascii_uppercase();
+///     let _ = IpcServer::notify_client(client);
+/// });
+/// share::scope(|subscribe| {
+///     IpcServer::register_listener(&listener, subscribe)?;
+///     loop {
+///         TockSyscalls::yield_wait();
+///     }
+/// })
+/// ```
+pub struct IpcServer<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> IpcServer<S, C> {
+    /// Run a check against the IPC capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Registers the listener called when a client notifies this service.
+    pub fn register_listener<'share, F: Fn(u32, &mut [u8])>(
+        listener: &'share IpcServerListener<F, C>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe_num::SERVICE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe_num::SERVICE }>(subscribe, listener)
+    }
+
+    /// Registers `requests` to queue client notifications, instead of a
+    /// listener.
+    pub fn register_requests<'share, const N: usize>(
+        requests: &'share IpcRequests<N, C>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, { subscribe_num::SERVICE }>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe_num::SERVICE }>(subscribe, requests)
+    }
+
+    /// Unregisters the listener (or `IpcRequests`). Clients' notifications are
+    /// then dropped.
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, subscribe_num::SERVICE)
+    }
+
+    /// Notifies the client with process ID `client`, e.g. to tell it that its
+    /// request has been handled. Fails with `ErrorCode::Invalid` if there is no
+    /// such process.
+    pub fn notify_client(client: u32) -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::NOTIFY_CLIENT, client, 0).to_result()
+    }
+}
+
+/// The listener for client notifications of an `IpcServer`.
+///
+/// It is called with the client's process ID and the buffer the client shares
+/// with the service. The buffer is empty if the client shares none.
+pub struct IpcServerListener<F: Fn(u32, &mut [u8]), C: Config = DefaultConfig> {
+    handler: F,
+    _config: PhantomData<C>,
+}
+
+impl<F: Fn(u32, &mut [u8]), C: Config> IpcServerListener<F, C> {
+    pub fn new(handler: F) -> Self {
+        IpcServerListener {
+            handler,
+            _config: PhantomData,
+        }
+    }
+}
+
+impl<F: Fn(u32, &mut [u8]), C: Config> Upcall<OneId<DRIVER_NUM, { subscribe_num::SERVICE }>>
+    for IpcServerListener<F, C>
+{
+    fn upcall(&self, client: u32, len: u32, address: u32) {
+        let len = len as usize;
+        let buffer: &mut [u8] = match C::resolve_address(address, len) {
+            // Safety: The kernel passes the location of the buffer the client
+            // shares with this service, which the service may access until the
+            // client shares a different buffer. The client cannot do so while
+            // this upcall runs, as the client's own upcalls and system calls
+            // are only processed after the service yields. The reference does
+            // not outlive the call to the handler.
+            Some(pointer) if len > 0 => unsafe { core::slice::from_raw_parts_mut(pointer, len) },
+            _ => &mut [],
+        };
+        (self.handler)(client, buffer)
+    }
+}
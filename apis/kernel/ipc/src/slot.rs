@@ -0,0 +1,121 @@
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use libtock_platform::ErrorCode;
+use zerocopy::{AsBytes, FromBytes};
+
+/// A value of type `T` in a buffer shared over IPC.
+///
+/// Tock exposes a buffer a client shares with a service through the service's
+/// MPU, which on most chips can only protect regions whose size is a power of
+/// two (at least 32 bytes) and whose start is aligned to that size. The client
+/// therefore creates the slot with `new` from a larger buffer, which places the
+/// slot's region appropriately within it, and shares the region (see
+/// `IpcClient::share_slot`). The service wraps the buffer passed to its
+/// `IpcServerListener` with `from_shared`, which checks that it holds a
+/// properly aligned `T`.
+///
+/// As `T` is `FromBytes + AsBytes`, every byte pattern is a valid `T`, so
+/// neither side has to trust the other's writes.
+///
+/// # Example
+/// ```ignore
+/// #[derive(AsBytes, FromBytes)]
+/// #[repr(C)]
+/// struct Request {
+///     op: u32,
+///     value: u32,
+/// }
+///
+/// // Client
+/// let mut buffer = [0; IpcSharedSlot::<Request>::BUFFER_LEN];
+/// let mut slot = IpcSharedSlot::<Request>::new(&mut buffer)?;
+/// slot.write(&Request { op: 1, value: 2 });
+///
+/// // Service, in its listener
+/// let Ok(slot) = IpcSharedSlot::<Request>::from_shared(buffer) else { return };
+/// let op = slot.get().op;
+/// ```
+pub struct IpcSharedSlot<'a, T: FromBytes + AsBytes> {
+    region: &'a mut [u8],
+    _value: PhantomData<T>,
+}
+
+impl<'a, T: FromBytes + AsBytes> IpcSharedSlot<'a, T> {
+    /// The size of the region shared by the client: the smallest power of two
+    /// that holds a `T` and is at least the MPU's minimum region size.
+    pub const REGION_SIZE: usize = {
+        let size = size_of::<T>().next_power_of_two();
+        if size < MIN_REGION_SIZE {
+            MIN_REGION_SIZE
+        } else {
+            size
+        }
+    };
+
+    /// The length of a buffer that is guaranteed to contain a suitably aligned
+    /// region, wherever the buffer is placed in memory.
+    pub const BUFFER_LEN: usize = 2 * Self::REGION_SIZE - 1;
+
+    /// Creates a slot for a client to share, in the first suitably aligned
+    /// `REGION_SIZE` bytes of `buffer`. The slot is zeroed. Fails with
+    /// `ErrorCode::Size` if `buffer` contains no such region, which cannot
+    /// happen if it is at least `BUFFER_LEN` bytes long.
+    pub fn new(buffer: &'a mut [u8]) -> Result<Self, ErrorCode> {
+        let start = buffer.as_ptr() as usize;
+        let offset = start.next_multiple_of(Self::REGION_SIZE) - start;
+        let region = buffer
+            .get_mut(offset..offset + Self::REGION_SIZE)
+            .ok_or(ErrorCode::Size)?;
+        region.fill(0);
+        Ok(IpcSharedSlot {
+            region,
+            _value: PhantomData,
+        })
+    }
+
+    /// Wraps a buffer shared by a client, as received by the service. Fails
+    /// with `ErrorCode::Size` if `buffer` is too short to hold a `T`, and with
+    /// `ErrorCode::Invalid` if it is not aligned for `T`.
+    pub fn from_shared(buffer: &'a mut [u8]) -> Result<Self, ErrorCode> {
+        if buffer.len() < size_of::<T>() {
+            return Err(ErrorCode::Size);
+        }
+        if buffer.as_ptr() as usize % align_of::<T>() != 0 {
+            return Err(ErrorCode::Invalid);
+        }
+        Ok(IpcSharedSlot {
+            region: buffer,
+            _value: PhantomData,
+        })
+    }
+
+    /// Returns a copy of the value.
+    pub fn read(&self) -> T {
+        T::read_from_prefix(self.region).expect("IpcSharedSlot region too short")
+    }
+
+    /// Overwrites the value.
+    pub fn write(&mut self, value: &T) {
+        value
+            .write_to_prefix(self.region)
+            .expect("IpcSharedSlot region too short")
+    }
+
+    pub fn get(&self) -> &T {
+        T::ref_from_prefix(self.region).expect("IpcSharedSlot region misaligned or too short")
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        T::mut_from_prefix(self.region).expect("IpcSharedSlot region misaligned or too short")
+    }
+
+    /// Returns the whole region: the value, followed by padding up to
+    /// `REGION_SIZE` (for slots created by `new`) or the rest of the shared
+    /// buffer (for slots created by `from_shared`).
+    pub fn region(&mut self) -> &mut [u8] {
+        self.region
+    }
+}
+
+// The smallest region the MPUs of the chips Tock supports can protect.
+const MIN_REGION_SIZE: usize = 32;
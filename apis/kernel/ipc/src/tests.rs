@@ -0,0 +1,911 @@
+use crate::{
+    client_upcall, Config, IpcClient, IpcClientListener, IpcReply, IpcRequests, IpcRingBuffer,
+    IpcRingConsumer, IpcServer, IpcServerListener, IpcSharedSlot,
+};
+use core::cell::Cell;
+use libtock_future::{block_on, select, Either};
+use libtock_platform::{
+    allow_ro, allow_rw, command, share, subscribe, ErrorCode, Subscribe, Syscalls,
+    YieldNoWaitReturn,
+};
+use libtock_unittest::fake::{self, IpcNotification};
+use zerocopy::{AsBytes, FromBytes, FromZeroes};
+
+// Resolves the fake addresses of buffers shared by fake::Ipc's simulated
+// processes.
+struct TestConfig;
+
+impl allow_ro::Config for TestConfig {}
+impl allow_rw::Config for TestConfig {}
+impl command::Config for TestConfig {}
+impl subscribe::Config for TestConfig {}
+
+impl Config for TestConfig {
+    fn resolve_address(address: u32, len: usize) -> Option<*mut u8> {
+        fake::Ipc::instance()?.resolve(address, len)
+    }
+}
+
+type Client = IpcClient<fake::Syscalls, TestConfig>;
+type Server = IpcServer<fake::Syscalls, TestConfig>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Server::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(Client::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Server::exists(), Ok(()));
+    assert_eq!(Client::exists(), Ok(()));
+}
+
+#[test]
+fn notify_client() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let client = driver.add_process("org.tockos.client");
+
+    assert_eq!(Server::notify_client(client), Ok(()));
+    assert_eq!(Server::notify_client(client + 1), Err(ErrorCode::Invalid));
+    assert_eq!(Server::notify_client(0), Err(ErrorCode::Invalid));
+    assert_eq!(
+        driver.take_notifications(),
+        [IpcNotification::Client(client)]
+    );
+}
+
+#[test]
+fn client_notification() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let client = driver.add_process("org.tockos.client");
+    driver.share_from_process(client, b"hello");
+
+    let calls = Cell::new(0);
+    let listener = IpcServerListener::<_, TestConfig>::new(|from, buffer: &mut [u8]| {
+        assert_eq!(from, client);
+        assert_eq!(buffer, b"hello");
+        buffer.make_ascii_uppercase();
+        calls.set(calls.get() + 1);
+        assert_eq!(Server::notify_client(from), Ok(()));
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Server::register_listener(&listener, subscribe), Ok(()));
+
+        driver.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(driver.process_buffer(client), b"HELLO");
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Client(client)]
+        );
+
+        Server::unregister_listener();
+        driver.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        assert_eq!(calls.get(), 1);
+    });
+}
+
+#[test]
+fn client_without_buffer() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let client = driver.add_process("org.tockos.client");
+
+    let calls = Cell::new(0);
+    let listener = IpcServerListener::<_, TestConfig>::new(|from, buffer: &mut [u8]| {
+        assert_eq!(from, client);
+        assert!(buffer.is_empty());
+        calls.set(calls.get() + 1);
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Server::register_listener(&listener, subscribe), Ok(()));
+        driver.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn discover() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    driver.set_package_name("org.tockos.self");
+    let service = driver.add_process("org.tockos.service");
+
+    assert_eq!(Client::discover(b"org.tockos.service"), Ok(service));
+    assert_eq!(Client::discover(b"org.tockos.self"), Ok(0));
+    assert_eq!(Client::discover(b"org.tockos"), Err(ErrorCode::Invalid));
+    assert_eq!(Client::discover(b""), Err(ErrorCode::Invalid));
+}
+
+#[test]
+fn request() {
+    const SERVICE: u32 = 1;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    assert_eq!(driver.add_process("org.tockos.service"), SERVICE);
+
+    let notified_by = Cell::new(None);
+    let listener = IpcClientListener(|service| notified_by.set(Some(service)));
+    let mut buffer = *b"hello";
+    share::scope(|allow| {
+        share::scope(|subscribe| {
+            assert_eq!(Client::share_buffer::<SERVICE>(allow, &mut buffer), Ok(()));
+            assert_eq!(
+                Client::register_listener::<_, { client_upcall(SERVICE) }>(&listener, subscribe),
+                Ok(())
+            );
+            assert_eq!(Client::notify_service(SERVICE), Ok(()));
+            assert_eq!(
+                driver.take_notifications(),
+                [IpcNotification::Service(SERVICE)]
+            );
+
+            // The service handles the request.
+            assert_eq!(driver.shared_buffer(SERVICE), b"hello");
+            driver.write_shared_buffer(SERVICE, b"HELLO");
+            driver.notify_client(SERVICE);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(notified_by.get(), Some(SERVICE));
+
+            Client::unregister_listener(SERVICE);
+            driver.notify_client(SERVICE);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        });
+    });
+    assert_eq!(&buffer, b"HELLO");
+    assert!(driver.shared_buffer(SERVICE).is_empty());
+    assert_eq!(Client::notify_service(SERVICE + 1), Err(ErrorCode::Invalid));
+}
+
+#[derive(AsBytes, Clone, Copy, Debug, Eq, FromBytes, FromZeroes, PartialEq)]
+#[repr(C)]
+struct Request {
+    op: u32,
+    value: u16,
+    status: u16,
+}
+
+#[repr(C, align(8))]
+struct Aligned([u8; 16]);
+
+#[test]
+fn slot_layout() {
+    type Slot<'a> = IpcSharedSlot<'a, Request>;
+    assert_eq!(Slot::REGION_SIZE, 32);
+    assert_eq!(Slot::BUFFER_LEN, 63);
+    assert_eq!(IpcSharedSlot::<[u8; 33]>::REGION_SIZE, 64);
+
+    let mut buffer = [0xff; Slot::BUFFER_LEN];
+    let mut slot = Slot::new(&mut buffer).unwrap();
+    let region = slot.region();
+    assert_eq!(region.len(), 32);
+    assert_eq!(region.as_ptr() as usize % 32, 0);
+    assert!(region.iter().all(|&byte| byte == 0));
+
+    assert_eq!(Slot::new(&mut [0; 31]).err(), Some(ErrorCode::Size));
+}
+
+#[test]
+fn slot_access() {
+    let mut buffer = [0; IpcSharedSlot::<Request>::BUFFER_LEN];
+    let mut slot = IpcSharedSlot::<Request>::new(&mut buffer).unwrap();
+    let request = Request {
+        op: 0x01020304,
+        value: 5,
+        status: 0,
+    };
+    slot.write(&request);
+    assert_eq!(slot.read(), request);
+    assert_eq!(&slot.region()[..8], request.as_bytes());
+    slot.get_mut().status = 7;
+    assert_eq!(slot.get().status, 7);
+}
+
+#[test]
+fn slot_from_shared() {
+    let mut aligned = Aligned([0; 16]);
+    assert_eq!(
+        IpcSharedSlot::<Request>::from_shared(&mut aligned.0[..7]).err(),
+        Some(ErrorCode::Size)
+    );
+    assert_eq!(
+        IpcSharedSlot::<Request>::from_shared(&mut aligned.0[1..]).err(),
+        Some(ErrorCode::Invalid)
+    );
+    aligned.0[4..8].copy_from_slice(&9u32.to_ne_bytes());
+    let slot = IpcSharedSlot::<Request>::from_shared(&mut aligned.0[4..]).unwrap();
+    assert_eq!(slot.get().op, 9);
+}
+
+#[test]
+fn typed_request() {
+    const SERVICE: u32 = 1;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    assert_eq!(driver.add_process("org.tockos.service"), SERVICE);
+
+    let mut buffer = [0; IpcSharedSlot::<Request>::BUFFER_LEN];
+    let mut slot = IpcSharedSlot::<Request>::new(&mut buffer).unwrap();
+    let request = Request {
+        op: 1,
+        value: 2,
+        status: 0,
+    };
+    slot.write(&request);
+    share::scope(|allow| {
+        assert_eq!(Client::share_slot::<_, SERVICE>(allow, &mut slot), Ok(()));
+        let shared = driver.shared_buffer(SERVICE);
+        assert_eq!(shared.len(), 32);
+        assert_eq!(&shared[..8], request.as_bytes());
+        let reply = Request {
+            status: 3,
+            ..request
+        };
+        driver.write_shared_buffer(SERVICE, reply.as_bytes());
+    });
+    assert_eq!(slot.get().status, 3);
+}
+
+#[test]
+fn typed_service() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let client = driver.add_process("org.tockos.client");
+    let request = Request {
+        op: 1,
+        value: 2,
+        status: 0,
+    };
+    driver.share_from_process(client, request.as_bytes());
+
+    let listener = IpcServerListener::<_, TestConfig>::new(|_, buffer: &mut [u8]| {
+        let mut slot = IpcSharedSlot::<Request>::from_shared(buffer).unwrap();
+        assert_eq!(slot.read(), request);
+        slot.get_mut().status = slot.get().value + 1;
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Server::register_listener(&listener, subscribe), Ok(()));
+        driver.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    let reply = Request::read_from(driver.process_buffer(client).as_slice()).unwrap();
+    assert_eq!(reply.status, 3);
+}
+
+#[test]
+fn request_stream() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let first = driver.add_process("org.tockos.first");
+    let second = driver.add_process("org.tockos.second");
+    driver.share_from_process(first, b"one");
+    driver.share_from_process(second, b"two");
+
+    let requests = IpcRequests::<2, TestConfig>::new();
+    share::scope(|subscribe| {
+        assert_eq!(Server::register_requests(&requests, subscribe), Ok(()));
+        assert!(requests.take().is_none());
+        for client in [first, second, first] {
+            driver.notify_service(client);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        }
+    });
+    // The queue holds two requests, so the third notification was dropped.
+    assert_eq!(requests.dropped(), 1);
+    for (client, contents) in [(first, b"ONE"), (second, b"TWO")] {
+        let mut request = block_on::<fake::Syscalls, _>(requests.next());
+        assert_eq!(request.client(), client);
+        request.buffer().make_ascii_uppercase();
+        assert_eq!(driver.process_buffer(client), contents);
+    }
+    assert!(requests.take().is_none());
+}
+
+#[test]
+fn select_request_or_reply() {
+    const SERVICE: u32 = 1;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    assert_eq!(driver.add_process("org.tockos.service"), SERVICE);
+    let client = driver.add_process("org.tockos.client");
+    driver.set_service_handler(SERVICE, |_| {});
+
+    let requests = IpcRequests::<4, TestConfig>::new();
+    let reply = IpcReply::new();
+    share::scope::<
+        (
+            Subscribe<_, { crate::DRIVER_NUM }, 0>,
+            Subscribe<_, { crate::DRIVER_NUM }, { client_upcall(SERVICE) }>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (subscribe_requests, subscribe_reply) = handle.split();
+        assert_eq!(
+            Server::register_requests(&requests, subscribe_requests),
+            Ok(())
+        );
+        assert_eq!(
+            Client::register_reply::<{ client_upcall(SERVICE) }>(&reply, subscribe_reply),
+            Ok(())
+        );
+
+        // The service replies as soon as it is notified.
+        assert_eq!(Client::notify_service(SERVICE), Ok(()));
+        match block_on::<fake::Syscalls, _>(select(requests.next(), reply.wait())) {
+            Either::Right(()) => {}
+            Either::Left(_) => panic!("Unexpected request"),
+        }
+
+        driver.notify_service(client);
+        match block_on::<fake::Syscalls, _>(select(requests.next(), reply.wait())) {
+            Either::Left(request) => assert_eq!(request.client(), client),
+            Either::Right(()) => panic!("Unexpected reply"),
+        }
+    });
+}
+
+#[test]
+fn ring_producer() {
+    const SERVICE: u32 = 1;
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    assert_eq!(driver.add_process("org.tockos.service"), SERVICE);
+
+    // Reads the (read index, write index) of the shared ring.
+    let indices = || {
+        let ring = driver.shared_buffer(SERVICE);
+        let index = |offset| u32::read_from(&ring[offset..offset + 4]).unwrap();
+        (index(0), index(4))
+    };
+    let mut ring = IpcRingBuffer::<u32, 3>::new();
+    share::scope(|allow| {
+        let mut producer = Client::share_ring::<_, 3, SERVICE>(allow, &mut ring).unwrap();
+        assert!(producer.is_empty());
+
+        // Only pushing into an empty ring notifies the service.
+        assert_eq!(producer.push(&10), Ok(()));
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Service(SERVICE)]
+        );
+        assert_eq!(producer.push(&20), Ok(()));
+        assert_eq!(producer.push(&30), Ok(()));
+        assert_eq!(producer.push(&40), Err(ErrorCode::NoMem));
+        assert!(driver.take_notifications().is_empty());
+        assert_eq!(producer.len(), 3);
+        assert_eq!(indices(), (0, 3));
+        assert_eq!(
+            &driver.shared_buffer(SERVICE)[8..],
+            [10u32, 20, 30].as_bytes()
+        );
+
+        // The service pops two values, then the last one.
+        driver.write_shared_buffer(SERVICE, &2u32.to_ne_bytes());
+        assert_eq!(producer.push(&40), Ok(()));
+        assert!(driver.take_notifications().is_empty());
+        driver.write_shared_buffer(SERVICE, &4u32.to_ne_bytes());
+        assert!(producer.is_empty());
+        assert_eq!(producer.push(&50), Ok(()));
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Service(SERVICE)]
+        );
+        // The indices count modulo 6, and the value at index 4 is in slot 1.
+        assert_eq!(indices(), (4, 5));
+        assert_eq!(
+            &driver.shared_buffer(SERVICE)[8..],
+            [40u32, 50, 30].as_bytes()
+        );
+    });
+    assert!(driver.shared_buffer(SERVICE).is_empty());
+}
+
+#[test]
+fn ring_consumer() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ipc::new();
+    kernel.add_driver(&driver);
+    let client = driver.add_process("org.tockos.client");
+    // A full ring, whose values start at index 5 (slot 2).
+    driver.share_from_process(client, [5u32, 2, 20, 30, 10].as_bytes());
+
+    let popped = Cell::new([0; 4]);
+    let listener = IpcServerListener::<_, TestConfig>::new(|_, buffer: &mut [u8]| {
+        let mut consumer = IpcRingConsumer::<u32, 3>::from_shared(buffer).unwrap();
+        assert_eq!(consumer.len(), 3);
+        popped.set([(); 4].map(|_| consumer.pop().unwrap_or(0)));
+        assert!(consumer.is_empty());
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Server::register_listener(&listener, subscribe), Ok(()));
+        driver.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    assert_eq!(popped.get(), [10, 20, 30, 0]);
+    assert_eq!(&driver.process_buffer(client)[..8], [2u32, 2].as_bytes());
+
+    let mut short = [0; 16];
+    assert!(matches!(
+        IpcRingConsumer::<u32, 3>::from_shared(&mut short),
+        Err(ErrorCode::Size)
+    ));
+    let mut misaligned = [0u32; 6];
+    assert!(matches!(
+        IpcRingConsumer::<u32, 3>::from_shared(&mut misaligned.as_bytes_mut()[1..]),
+        Err(ErrorCode::Invalid)
+    ));
+}
+
+mod pubsub {
+    extern crate std;
+
+    use super::{TestConfig, YieldNoWaitReturn};
+    use crate::client_upcall;
+    use crate::pubsub::{
+        Header, PubSubBroker, PubSubClient, ACK, DELIVER, HEADER_LEN, PUBLISH, RECEIVE, SUBSCRIBE,
+        UNSUBSCRIBE,
+    };
+    use crate::IpcServer;
+    use libtock_platform::{share, ErrorCode, Syscalls};
+    use libtock_unittest::fake::{self, IpcNotification};
+    use std::{vec, vec::Vec};
+
+    // Encodes a request, with `spare` bytes of room after the payload.
+    fn request(op: u8, topic: u32, payload: &[u8], spare: usize) -> Vec<u8> {
+        let mut buffer = vec![0; HEADER_LEN + payload.len() + spare];
+        let len = payload.len() as u16;
+        let header = Header {
+            op,
+            status: 0,
+            len,
+            topic,
+        };
+        header.write(&mut buffer).unwrap();
+        buffer[HEADER_LEN..][..payload.len()].copy_from_slice(payload);
+        buffer
+    }
+
+    // Returns the status of the ACK in `buffer`.
+    fn ack(buffer: &[u8]) -> Result<(), ErrorCode> {
+        let reply = Header::read(buffer).unwrap();
+        assert_eq!(reply.op, ACK);
+        reply.result()
+    }
+
+    #[test]
+    fn broker() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        let waiting = driver.add_process("org.tockos.waiting");
+        let idle = driver.add_process("org.tockos.idle");
+        let publisher = driver.add_process("org.tockos.publisher");
+
+        let broker = PubSubBroker::<fake::Syscalls, 4, TestConfig>::new();
+        let listener = broker.listener();
+        let send = |client, buffer: Vec<u8>| {
+            driver.share_from_process(client, &buffer);
+            driver.notify_service(client);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        };
+        share::scope(|subscribe| {
+            assert_eq!(
+                IpcServer::<fake::Syscalls, TestConfig>::register_listener(&listener, subscribe),
+                Ok(())
+            );
+            for client in [waiting, idle, publisher] {
+                send(client, request(SUBSCRIBE, 7, &[], 0));
+                assert_eq!(ack(&driver.process_buffer(client)), Ok(()));
+            }
+            send(waiting, request(RECEIVE, 0, &[], 8));
+            assert_eq!(
+                driver.take_notifications(),
+                [
+                    IpcNotification::Client(waiting),
+                    IpcNotification::Client(idle),
+                    IpcNotification::Client(publisher)
+                ]
+            );
+
+            // Only the waiting subscriber receives the message; the publisher
+            // is skipped.
+            send(publisher, request(PUBLISH, 7, b"hi", 0));
+            assert_eq!(ack(&driver.process_buffer(publisher)), Ok(()));
+            assert_eq!(
+                driver.take_notifications(),
+                [
+                    IpcNotification::Client(waiting),
+                    IpcNotification::Client(publisher)
+                ]
+            );
+            let delivered = driver.process_buffer(waiting);
+            assert_eq!(
+                Header::read(&delivered),
+                Some(Header {
+                    op: DELIVER,
+                    status: 0,
+                    len: 2,
+                    topic: 7
+                })
+            );
+            assert_eq!(&delivered[HEADER_LEN..][..2], b"hi");
+            assert_eq!(broker.dropped(), 1);
+
+            // A message too large for the receiver's buffer.
+            send(waiting, request(RECEIVE, 0, &[], 1));
+            send(publisher, request(PUBLISH, 7, b"hi", 0));
+            let delivered = Header::read(&driver.process_buffer(waiting)).unwrap();
+            assert_eq!(delivered.op, DELIVER);
+            assert_eq!(delivered.result(), Err(ErrorCode::Size));
+            assert_eq!(broker.dropped(), 2);
+
+            // Once unsubscribed, a client cannot wait for messages.
+            send(waiting, request(UNSUBSCRIBE, 7, &[], 0));
+            assert_eq!(ack(&driver.process_buffer(waiting)), Ok(()));
+            send(waiting, request(UNSUBSCRIBE, 7, &[], 0));
+            assert_eq!(
+                ack(&driver.process_buffer(waiting)),
+                Err(ErrorCode::Invalid)
+            );
+            send(waiting, request(RECEIVE, 0, &[], 8));
+            assert_eq!(
+                ack(&driver.process_buffer(waiting)),
+                Err(ErrorCode::Invalid)
+            );
+
+            // Errors.
+            send(idle, request(SUBSCRIBE, 8, &[], 0));
+            send(idle, request(SUBSCRIBE, 9, &[], 0));
+            assert_eq!(ack(&driver.process_buffer(idle)), Ok(()));
+            send(idle, request(SUBSCRIBE, 10, &[], 0));
+            assert_eq!(ack(&driver.process_buffer(idle)), Err(ErrorCode::NoMem));
+            send(idle, request(0xaa, 7, &[], 0));
+            assert_eq!(ack(&driver.process_buffer(idle)), Err(ErrorCode::NoSupport));
+            let mut truncated = request(PUBLISH, 7, b"hi", 0);
+            truncated.pop();
+            send(publisher, truncated);
+            assert_eq!(ack(&driver.process_buffer(publisher)), Err(ErrorCode::Size));
+        });
+    }
+
+    const BROKER: u32 = 1;
+    type Client = PubSubClient<fake::Syscalls, BROKER, { client_upcall(BROKER) }>;
+
+    // Simulates a broker with a message waiting on topic 7, that cannot hold
+    // subscriptions to topic 9.
+    fn serve(buffer: &mut [u8]) {
+        let request = Header::read(buffer).unwrap();
+        let (op, status, payload): (_, _, &[u8]) = match (request.op, request.topic) {
+            (RECEIVE, _) => (DELIVER, 0, b"data"),
+            (SUBSCRIBE, 9) => (ACK, ErrorCode::NoMem as u8, &[]),
+            _ => (ACK, 0, &[]),
+        };
+        let topic = if op == DELIVER { 7 } else { request.topic };
+        let len = payload.len() as u16;
+        let reply = Header {
+            op,
+            status,
+            len,
+            topic,
+        };
+        reply.write(buffer).unwrap();
+        buffer[HEADER_LEN..][..payload.len()].copy_from_slice(payload);
+    }
+
+    #[test]
+    fn client() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        assert_eq!(driver.add_process("org.tockos.broker"), BROKER);
+        driver.set_service_handler(BROKER, serve);
+
+        let mut buffer = [0; 16];
+        assert_eq!(Client::subscribe(&mut buffer, 7), Ok(()));
+        assert_eq!(Client::subscribe(&mut buffer, 9), Err(ErrorCode::NoMem));
+        assert_eq!(Client::unsubscribe(&mut buffer, 9), Ok(()));
+        assert_eq!(Client::publish(&mut buffer, 7, b"hello"), Ok(()));
+        assert_eq!(
+            Client::publish(&mut buffer, 7, &[0; 9]),
+            Err(ErrorCode::Size)
+        );
+        assert_eq!(Client::receive(&mut buffer), Ok((7, &b"data"[..])));
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Service(BROKER); 5]
+        );
+    }
+}
+
+mod log {
+    extern crate std;
+
+    use super::{TestConfig, YieldNoWaitReturn};
+    use crate::log::{LogCollector, LogProducer, LogRecord, LogRing, ProducerStats, RECORD_LEN};
+    use crate::IpcServer;
+    use core::cell::RefCell;
+    use libtock_platform::{share, ErrorCode, Syscalls};
+    use libtock_unittest::fake::{self, IpcNotification};
+    use std::vec::Vec;
+
+    #[test]
+    fn record() {
+        let mut record = LogRecord::new(3, &[b'a'; 70]);
+        record.dropped = 258;
+        assert_eq!(record.text(), [b'a'; 60]);
+        let bytes = record.encode();
+        assert_eq!(bytes[..5], [3, 60, 2, 1, b'a']);
+        assert_eq!(LogRecord::decode(&bytes), record);
+
+        let mut bytes = LogRecord::new(1, b"hi").encode();
+        bytes[1] = 200;
+        assert_eq!(LogRecord::decode(&bytes).text().len(), 60);
+    }
+
+    #[test]
+    fn producer() {
+        const COLLECTOR: u32 = 1;
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        assert_eq!(driver.add_process("org.tockos.collector"), COLLECTOR);
+
+        let mut ring = LogRing::<2>::new();
+        share::scope(|allow| {
+            let mut log =
+                LogProducer::<fake::Syscalls, 2, TestConfig>::share::<COLLECTOR>(allow, &mut ring)
+                    .unwrap();
+            assert_eq!(log.log(1, "one"), Ok(()));
+            assert_eq!(log.log(1, "two"), Ok(()));
+            assert_eq!(log.log(1, "three"), Err(ErrorCode::NoMem));
+            assert_eq!(log.log(1, "four"), Err(ErrorCode::NoMem));
+            assert_eq!(log.dropped(), 2);
+
+            // The collector pops both records, and the next one reports the
+            // drops.
+            driver.write_shared_buffer(COLLECTOR, &2u32.to_ne_bytes());
+            assert_eq!(log.log(2, "five"), Ok(()));
+            assert_eq!(log.dropped(), 0);
+            let shared = driver.shared_buffer(COLLECTOR);
+            let record = LogRecord::decode(shared[8..][..RECORD_LEN].try_into().unwrap());
+            assert_eq!((record.level, record.text()), (2, &b"five"[..]));
+            assert_eq!(record.dropped, 2);
+        });
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Service(COLLECTOR); 2]
+        );
+    }
+
+    // Encodes a ring of 2 records holding `records`.
+    fn ring(records: &[LogRecord]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u32.to_ne_bytes());
+        bytes.extend_from_slice(&(records.len() as u32).to_ne_bytes());
+        for index in 0..2 {
+            let record = records.get(index).copied();
+            bytes.extend_from_slice(&record.unwrap_or(LogRecord::new(0, b"")).encode());
+        }
+        bytes
+    }
+
+    #[test]
+    fn collector() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        let first = driver.add_process("org.tockos.first");
+        let second = driver.add_process("org.tockos.second");
+        let untracked = driver.add_process("org.tockos.untracked");
+
+        let collector = LogCollector::<2, 2, TestConfig>::new();
+        let forwarded = RefCell::new(Vec::new());
+        let listener = collector.listener(|producer, record| {
+            forwarded
+                .borrow_mut()
+                .push((producer, record.text().to_vec()));
+        });
+        let mut dropping = LogRecord::new(1, b"b");
+        dropping.dropped = 3;
+        share::scope(|subscribe| {
+            assert_eq!(
+                IpcServer::<fake::Syscalls, TestConfig>::register_listener(&listener, subscribe),
+                Ok(())
+            );
+            for (producer, records) in [
+                (first, [LogRecord::new(1, b"a"), dropping]),
+                (second, [LogRecord::new(1, b"c"); 2]),
+                (untracked, [LogRecord::new(1, b"d"); 2]),
+            ] {
+                driver.share_from_process(producer, &ring(&records));
+                driver.notify_service(producer);
+                assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+                // The ring was drained.
+                assert_eq!(
+                    driver.process_buffer(producer)[..8],
+                    [2, 0, 0, 0, 2, 0, 0, 0]
+                );
+            }
+            // A producer without a ring is ignored.
+            driver.share_from_process(first, &[]);
+            driver.notify_service(first);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        });
+
+        let texts: Vec<_> = forwarded.take().into_iter().map(|(_, text)| text).collect();
+        assert_eq!(texts, [b"a", b"b", b"c", b"c", b"d", b"d"]);
+        assert_eq!(
+            collector.stats(first),
+            Some(ProducerStats {
+                producer: first,
+                received: 2,
+                dropped: 3
+            })
+        );
+        assert_eq!(collector.stats(second).map(|stats| stats.received), Some(2));
+        assert_eq!(collector.stats(untracked), None);
+        assert_eq!(collector.producers().count(), 2);
+        assert_eq!((collector.dropped(), collector.untracked()), (3, 2));
+    }
+}
+
+#[cfg(feature = "rpc")]
+mod rpc {
+    use super::{TestConfig, YieldNoWaitReturn};
+    use crate::rpc::{RpcClient, RpcError, RpcHandler, RpcMethod, RpcServer};
+    use crate::{client_upcall, IpcServer};
+    use libtock_platform::{share, ErrorCode, Syscalls};
+    use libtock_unittest::fake::{self, IpcNotification};
+    use serde::{Deserialize, Serialize};
+
+    const ADD: u32 = 1;
+    const GREET: u32 = 2;
+    const REPEAT: u32 = 3;
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Greeting {
+        name: [u8; 4],
+        times: u8,
+    }
+
+    // Serves the methods above, as the simulated service process 1.
+    fn serve(buffer: &mut [u8]) {
+        let add = RpcMethod::new(ADD, |(a, b): (u32, u32)| a.wrapping_add(b));
+        let greet = RpcMethod::new(GREET, |greeting: Greeting| Greeting {
+            times: greeting.times + 1,
+            ..greeting
+        });
+        let repeat = RpcMethod::new(REPEAT, |byte: u8| [byte; 8]);
+        let methods: [&dyn RpcHandler; 3] = [&add, &greet, &repeat];
+        RpcServer::<fake::Syscalls>::new(&methods).dispatch(buffer);
+    }
+
+    const SERVICE: u32 = 1;
+    type Client = RpcClient<fake::Syscalls, SERVICE, { client_upcall(SERVICE) }>;
+
+    #[test]
+    fn call() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        assert_eq!(driver.add_process("org.tockos.rpc"), SERVICE);
+        driver.set_service_handler(SERVICE, serve);
+
+        let mut buffer = [0; 32];
+        assert_eq!(Client::call(&mut buffer, ADD, &(2u32, 3u32)), Ok(5u32));
+        let greeting = Greeting {
+            name: *b"tock",
+            times: 1,
+        };
+        assert_eq!(
+            Client::call(&mut buffer, GREET, &greeting),
+            Ok(Greeting {
+                name: *b"tock",
+                times: 2
+            })
+        );
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Service(SERVICE); 2]
+        );
+    }
+
+    #[test]
+    fn call_errors() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        assert_eq!(driver.add_process("org.tockos.rpc"), SERVICE);
+        driver.set_service_handler(SERVICE, serve);
+
+        let mut buffer = [0; 32];
+        assert_eq!(
+            Client::call::<_, u32>(&mut buffer, 7, &()),
+            Err(RpcError::UnknownMethod)
+        );
+        // A response the client cannot deserialize (5 is not a valid bool).
+        assert_eq!(
+            Client::call::<_, bool>(&mut buffer, ADD, &(2u32, 3u32)),
+            Err(RpcError::BadResponse)
+        );
+        // A request the method cannot deserialize. The buffer is as long as
+        // the request, as trailing bytes would be decoded too.
+        assert_eq!(
+            Client::call::<_, u32>(&mut [0; 2], ADD, &1u8),
+            Err(RpcError::BadRequest)
+        );
+        assert_eq!(
+            Client::call::<_, u32>(&mut [0; 4], ADD, &(u32::MAX, u32::MAX)),
+            Err(RpcError::RequestTooLarge)
+        );
+        // The request fits, but the response does not.
+        assert_eq!(
+            Client::call::<_, [u8; 8]>(&mut [0; 4], REPEAT, &1u8),
+            Err(RpcError::ResponseTooLarge)
+        );
+        assert_eq!(
+            Client::call::<_, [u8; 8]>(&mut buffer, REPEAT, &1u8),
+            Ok([1; 8])
+        );
+
+        type Missing = RpcClient<fake::Syscalls, 2, { client_upcall(2) }>;
+        assert_eq!(
+            Missing::call::<_, u32>(&mut [0; 32], ADD, &(2u32, 3u32)),
+            Err(RpcError::Kernel(ErrorCode::Invalid))
+        );
+    }
+
+    #[test]
+    fn server() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ipc::new();
+        kernel.add_driver(&driver);
+        let client = driver.add_process("org.tockos.client");
+
+        let add = RpcMethod::new(ADD, |(a, b): (u32, u32)| a + b);
+        let methods: [&dyn RpcHandler; 1] = [&add];
+        let server = RpcServer::<fake::Syscalls, TestConfig>::new(&methods);
+        let listener = server.listener();
+
+        let mut request = [0; 16];
+        postcard::to_slice(&(ADD, (40u32, 2u32)), &mut request).unwrap();
+        driver.share_from_process(client, &request);
+        share::scope(|subscribe| {
+            assert_eq!(
+                IpcServer::<fake::Syscalls, TestConfig>::register_listener(&listener, subscribe),
+                Ok(())
+            );
+            driver.notify_service(client);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        });
+        assert_eq!(
+            driver.take_notifications(),
+            [IpcNotification::Client(client)]
+        );
+        let response = driver.process_buffer(client);
+        assert_eq!(response[0], 0);
+        assert_eq!(postcard::from_bytes::<u32>(&response[1..]), Ok(42));
+    }
+}
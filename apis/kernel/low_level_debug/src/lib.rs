@@ -66,6 +66,10 @@ pub enum AlertCode {
     WrongLocation = 0x02,
 }
 
+/// An estimate of the stack the deepest call of this crate uses, for
+/// `stack_size!` hints.
+pub const STACK_USAGE: usize = 0x20;
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,72 @@
+#![no_std]
+
+use libtock_alarm::{Alarm, Convert};
+use libtock_platform::{ErrorCode, Syscalls};
+
+/// The reboot driver.
+///
+/// It allows an app to request a full system reset, e.g. after it has staged
+/// an over-the-air update that is applied by the bootloader.
+///
+/// # Example
+/// ```ignore
+/// use libtock::alarm::Milliseconds;
+/// use libtock::reboot::Reboot;
+///
+/// // Give the console some time to flush, then reset the chip.
+/// Reboot::reboot_after(Milliseconds(500))?;
+/// ```
+///
+/// Boards that register the capsule under another driver number pass it as
+/// `DRIVER_NUM`:
+/// ```ignore
+/// type Reboot = libtock_reboot::Reboot<TockSyscalls, 0x90100>;
+/// ```
+pub struct Reboot<S: Syscalls, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM>(S);
+
+impl<S: Syscalls, const DRIVER_NUM: u32> Reboot<S, DRIVER_NUM> {
+    /// Run a check against the reboot capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Requests an immediate reset of the whole system.
+    ///
+    /// On real hardware the kernel resets the chip while handling the command,
+    /// so this function only returns if the request was refused (e.g. with
+    /// `ErrorCode::NoSupport` on a board that cannot reset itself).
+    pub fn reboot() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::REBOOT, 0, 0).to_result()
+    }
+
+    /// Waits for `delay` using the alarm driver, then requests a reset.
+    ///
+    /// The delay gives the rest of the system (e.g. a console transmission in
+    /// progress) a chance to finish before the chip is reset. Returns an error
+    /// if the alarm could not be set or the reset was refused.
+    pub fn reboot_after<T: Convert>(delay: T) -> Result<(), ErrorCode> {
+        Alarm::<S>::sleep_for(delay)?;
+        Self::reboot()
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+// Upstream Tock assigns no driver number for a reboot capsule; see
+// doc/DriverNumbers.md for how libtock-rs picked this one.
+const DEFAULT_DRIVER_NUM: u32 = 0x90068;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const REBOOT: u32 = 1;
+}
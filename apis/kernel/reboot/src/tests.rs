@@ -0,0 +1,67 @@
+use libtock_alarm::Milliseconds;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Reboot = super::Reboot<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Reboot::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Reboot::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Reboot::exists(), Ok(()));
+    assert_eq!(driver.reboot_count(), 0);
+}
+
+#[test]
+fn reboot() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Reboot::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Reboot::reboot(), Ok(()));
+    assert_eq!(driver.reboot_count(), 1);
+}
+
+#[test]
+fn reboot_refused() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Reboot::new();
+    kernel.add_driver(&driver);
+
+    driver.set_error(Some(ErrorCode::NoSupport));
+    assert_eq!(Reboot::reboot(), Err(ErrorCode::NoSupport));
+    assert_eq!(driver.reboot_count(), 0);
+}
+
+#[test]
+fn reboot_after() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    let driver = fake::Reboot::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Reboot::reboot_after(Milliseconds(500)), Ok(()));
+    assert_eq!(driver.reboot_count(), 1);
+}
+
+#[test]
+fn reboot_after_no_alarm() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Reboot::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(
+        Reboot::reboot_after(Milliseconds(500)),
+        Err(ErrorCode::NoDevice)
+    );
+    assert_eq!(driver.reboot_count(), 0);
+}
@@ -0,0 +1,88 @@
+#![no_std]
+
+use libtock_platform::{ErrorCode, Syscalls};
+
+/// The app watchdog driver.
+///
+/// Once started, the kernel reboots the system unless the app feeds the
+/// watchdog at least once per timeout period. This protects long-running apps
+/// whose main loop may stall.
+///
+/// # Example
+/// ```ignore
+/// use libtock::watchdog::Watchdog;
+///
+/// Watchdog::start(2000)?;
+/// loop {
+///     do_work();
+///     // Feeds the watchdog, then sleeps until the next upcall.
+///     Watchdog::yield_wait();
+/// }
+/// ```
+///
+/// Boards that register the capsule under another driver number pass it as
+/// `DRIVER_NUM`:
+/// ```ignore
+/// type Watchdog = libtock_watchdog::Watchdog<TockSyscalls, 0x90100>;
+/// ```
+pub struct Watchdog<S: Syscalls, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM>(S);
+
+impl<S: Syscalls, const DRIVER_NUM: u32> Watchdog<S, DRIVER_NUM> {
+    /// Run a check against the watchdog capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Starts the watchdog. The system is rebooted if the watchdog is not fed
+    /// within `timeout_ms` milliseconds. Calling `start` on a running watchdog
+    /// changes its timeout and feeds it.
+    pub fn start(timeout_ms: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::START, timeout_ms, 0).to_result()
+    }
+
+    /// Feeds the watchdog, restarting its timeout period.
+    ///
+    /// Returns `ErrorCode::Off` if the watchdog is not running.
+    pub fn feed() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::FEED, 0, 0).to_result()
+    }
+
+    /// Stops the watchdog.
+    pub fn stop() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    /// Feeds the watchdog, then puts the process to sleep until an upcall
+    /// arrives. Intended as a drop-in replacement for `yield_wait` in an app's
+    /// idle path, so that a main loop which keeps running keeps the watchdog
+    /// fed, while a main loop that stalls lets it fire.
+    ///
+    /// Feeding errors (e.g. a watchdog that has not been started) are ignored.
+    pub fn yield_wait() {
+        let _ = Self::feed();
+        S::yield_wait();
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+// Upstream Tock assigns no driver number for an app watchdog; see
+// doc/DriverNumbers.md for how libtock-rs picked this one.
+const DEFAULT_DRIVER_NUM: u32 = 0x90069;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const START: u32 = 1;
+    pub const FEED: u32 = 2;
+    pub const STOP: u32 = 3;
+}
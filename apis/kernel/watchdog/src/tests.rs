@@ -0,0 +1,75 @@
+use super::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::{expect, fake};
+
+type Watchdog = super::Watchdog<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Watchdog::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Watchdog::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Watchdog::exists(), Ok(()));
+    assert_eq!(driver.timeout_ms(), None);
+}
+
+#[test]
+fn start_feed_stop() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Watchdog::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Watchdog::feed(), Err(ErrorCode::Off));
+    assert_eq!(Watchdog::start(1000), Ok(()));
+    assert_eq!(driver.timeout_ms(), Some(1000));
+    assert_eq!(Watchdog::feed(), Ok(()));
+    assert_eq!(Watchdog::feed(), Ok(()));
+    assert_eq!(driver.feed_count(), 2);
+    assert_eq!(Watchdog::stop(), Ok(()));
+    assert_eq!(driver.timeout_ms(), None);
+}
+
+#[test]
+fn yield_wait_feeds() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Watchdog::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Watchdog::start(1000), Ok(()));
+
+    kernel.add_expected_syscalls(
+        expect()
+            .command(DEFAULT_DRIVER_NUM, command::FEED, 0, 0)
+            .yield_wait()
+            .skip_upcall(),
+    );
+    Watchdog::yield_wait();
+    kernel.assert_expected_syscalls_done();
+    assert_eq!(driver.feed_count(), 1);
+}
+
+#[test]
+fn yield_wait_without_watchdog() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Watchdog::new();
+    kernel.add_driver(&driver);
+
+    // The feed fails because the watchdog isn't running, but the yield still
+    // happens.
+    kernel.add_expected_syscalls(
+        expect()
+            .command(DEFAULT_DRIVER_NUM, command::FEED, 0, 0)
+            .yield_wait()
+            .skip_upcall(),
+    );
+    Watchdog::yield_wait();
+    kernel.assert_expected_syscalls_done();
+    assert_eq!(driver.feed_count(), 0);
+}
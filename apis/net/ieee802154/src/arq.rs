@@ -0,0 +1,256 @@
+//! Reliable ("at least once") delivery of data over 802.15.4 frames.
+//!
+//! [`ReliableLink`] numbers the data frames it sends, and retransmits each
+//! until the receiver acknowledges it with an ACK frame, or the retries run
+//! out. Receivers acknowledge every data frame, including duplicates (whose ACK
+//! may have been lost), but deliver each only once. Every frame starts with a
+//! 5-byte header:
+//!
+//! | Offset | Size | Content                                             |
+//! |--------|------|-----------------------------------------------------|
+//! | 0      | 1    | [`DISPATCH`]                                        |
+//! | 1      | 1    | Kind: 0 for data, 1 for ACK                         |
+//! | 2      | 2    | Sender ID of the data frame, in little-endian       |
+//! | 4      | 1    | Sequence number of the data frame                   |
+//!
+//! which data frames follow with the data.
+
+use crate::{
+    Config, Frame, Ieee802154, RxOperator, RxRingBuffer, RxSingleBufferOperator, MAX_PAYLOAD_LEN,
+};
+use libtock_alarm::{Alarm, Convert, LivenessGuard, Milliseconds, Ticks};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The first byte of ARQ frames, which tells them apart from other payloads.
+pub const DISPATCH: u8 = 0xF5;
+
+/// The maximum length of the data of a frame.
+pub const MAX_DATA_LEN: usize = MAX_PAYLOAD_LEN - HEADER_LEN;
+
+/// How the time waited for an ACK grows with each retransmission.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backoff {
+    /// Every attempt waits `ack_timeout`.
+    Constant,
+    /// The n-th retransmission waits `n + 1` times `ack_timeout`.
+    Linear,
+    /// Every retransmission waits twice as long as the previous attempt.
+    Exponential,
+}
+
+/// When a [`ReliableLink`] retransmits data frames.
+#[derive(Clone, Copy)]
+pub struct Retransmission {
+    /// The number of retransmissions before giving up.
+    pub retries: u8,
+    /// How long the first transmission waits for an ACK.
+    pub ack_timeout: Milliseconds,
+    pub backoff: Backoff,
+}
+
+impl Default for Retransmission {
+    /// 3 retries, with an exponential backoff from 50 ms.
+    fn default() -> Self {
+        Retransmission {
+            retries: 3,
+            ack_timeout: Milliseconds(50),
+            backoff: Backoff::Exponential,
+        }
+    }
+}
+
+/// Data received by a [`ReliableLink`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Delivery<'a> {
+    pub sender: u16,
+    pub data: &'a [u8],
+}
+
+/// A link delivering data at least once, by acknowledging and retransmitting
+/// frames. It receives frames from its own ring buffer of `N` frames, and
+/// suppresses the duplicates from the last `PEERS` senders it heard from.
+///
+/// Data frames received while `send` waits for an ACK are kept for the next
+/// `receive`. Only one is kept: further ones are not acknowledged, so that
+/// their senders retransmit them later.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ieee802154::{arq::Retransmission, ReliableLink, RxRingBuffer};
+///
+/// let mut buf = RxRingBuffer::<4>::new();
+/// let mut link = ReliableLink::new(0xdead, &mut buf, Retransmission::default());
+/// match link.send(b"alarm triggered") {
+///     Err(ErrorCode::NoAck) => { /* The gateway is out of range. */ }
+///     result => result?,
+/// }
+/// let command = link.receive()?;
+/// ```
+pub struct ReliableLink<
+    'buf,
+    S: Syscalls,
+    const N: usize,
+    const PEERS: usize = 4,
+    C: Config = DefaultConfig,
+> {
+    node: u16,
+    operator: RxSingleBufferOperator<'buf, N, S, C>,
+    retransmission: Retransmission,
+    sequence: u8,
+    // The last sequence number received from each recent sender, replaced in
+    // round-robin order.
+    recent: [Option<(u16, u8)>; PEERS],
+    next_recent: usize,
+    // Data received but not delivered yet.
+    inbox: Option<(u16, usize)>,
+    inbox_data: [u8; MAX_DATA_LEN],
+}
+
+impl<'buf, S: Syscalls, const N: usize, const PEERS: usize, C: Config>
+    ReliableLink<'buf, S, N, PEERS, C>
+{
+    /// Creates a link sending as `node`, which must be unique among the
+    /// devices within range, e.g. the short address.
+    pub fn new(node: u16, buf: &'buf mut RxRingBuffer<N>, retransmission: Retransmission) -> Self {
+        ReliableLink {
+            node,
+            operator: RxSingleBufferOperator::new(buf),
+            retransmission,
+            sequence: 0,
+            recent: [None; PEERS],
+            next_recent: 0,
+            inbox: None,
+            inbox_data: [0; MAX_DATA_LEN],
+        }
+    }
+
+    /// Sends `data`, and waits until it is acknowledged. Fails with
+    /// `ErrorCode::NoAck` if no ACK arrived after the last retransmission, and
+    /// with `ErrorCode::Size` if `data` is longer than [`MAX_DATA_LEN`].
+    pub fn send(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        if data.len() > MAX_DATA_LEN {
+            return Err(ErrorCode::Size);
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        let mut frame = [0; MAX_PAYLOAD_LEN];
+        let len = encode(&mut frame, KIND_DATA, self.node, self.sequence, data);
+        let frequency = Alarm::<S, C>::get_frequency()?;
+        let first_timeout = self.retransmission.ack_timeout.to_ticks(frequency).0;
+        let mut timeout = first_timeout;
+        for attempt in 0..=self.retransmission.retries as u32 {
+            Ieee802154::<S, C>::transmit_frame(&frame[..len])?;
+            if self.wait_for_ack(timeout)? {
+                return Ok(());
+            }
+            timeout = match self.retransmission.backoff {
+                Backoff::Constant => first_timeout,
+                Backoff::Linear => first_timeout.saturating_mul(attempt + 2),
+                Backoff::Exponential => timeout.saturating_mul(2),
+            };
+        }
+        Err(ErrorCode::NoAck)
+    }
+
+    /// Waits for data that was not delivered yet, acknowledging the data
+    /// frames, and returns it.
+    pub fn receive(&mut self) -> Result<Delivery<'_>, ErrorCode> {
+        loop {
+            if let Some((sender, len)) = self.inbox.take() {
+                return Ok(Delivery {
+                    sender,
+                    data: &self.inbox_data[..len],
+                });
+            }
+            let mut payload = [0; MAX_PAYLOAD_LEN];
+            let len = copy_payload(self.operator.receive_frame()?, &mut payload);
+            self.handle(&payload[..len])?;
+        }
+    }
+
+    // Handles the frames that arrive within `timeout` ticks, until the ACK of
+    // the current data frame. Returns whether it arrived.
+    fn wait_for_ack(&mut self, timeout: u32) -> Result<bool, ErrorCode> {
+        let start = Alarm::<S, C>::get_ticks()?;
+        loop {
+            let elapsed = Alarm::<S, C>::get_ticks()?.wrapping_sub(start);
+            let Some(remaining) = timeout.checked_sub(elapsed).filter(|&ticks| ticks > 0) else {
+                return Ok(false);
+            };
+            let guard = LivenessGuard::<S, C>::new(Ticks(remaining))?;
+            let mut payload = [0; MAX_PAYLOAD_LEN];
+            let len = match self.operator.receive_frame_within(&guard) {
+                Ok(frame) => copy_payload(frame, &mut payload),
+                Err(ErrorCode::Busy) => return Ok(false),
+                Err(error) => return Err(error),
+            };
+            if self.handle(&payload[..len])? {
+                return Ok(true);
+            }
+        }
+    }
+
+    // Handles a received payload: acknowledges data frames, and keeps new
+    // data in the inbox. Returns whether it is the ACK of the current data
+    // frame.
+    fn handle(&mut self, payload: &[u8]) -> Result<bool, ErrorCode> {
+        let [DISPATCH, kind, sender0, sender1, sequence, ref data @ ..] = *payload else {
+            return Ok(false);
+        };
+        let sender = u16::from_le_bytes([sender0, sender1]);
+        match kind {
+            KIND_ACK => Ok(sender == self.node && sequence == self.sequence),
+            KIND_DATA if data.len() <= MAX_DATA_LEN => {
+                let recent = self.recent.iter().position(
+                    |recent| matches!(recent, Some((recent_sender, _)) if *recent_sender == sender),
+                );
+                let duplicate =
+                    recent.is_some_and(|index| self.recent[index] == Some((sender, sequence)));
+                if !duplicate {
+                    if self.inbox.is_some() {
+                        // The sender retransmits it once the inbox is free.
+                        return Ok(false);
+                    }
+                    self.inbox_data[..data.len()].copy_from_slice(data);
+                    self.inbox = Some((sender, data.len()));
+                    let index = recent.unwrap_or_else(|| {
+                        let index = self.next_recent;
+                        self.next_recent = (index + 1) % PEERS;
+                        index
+                    });
+                    self.recent[index] = Some((sender, sequence));
+                }
+                let mut ack = [0; HEADER_LEN];
+                encode(&mut ack, KIND_ACK, sender, sequence, &[]);
+                Ieee802154::<S, C>::transmit_frame(&ack)?;
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 5;
+const KIND_DATA: u8 = 0;
+const KIND_ACK: u8 = 1;
+
+// Encodes a frame into `buf`, and returns its length.
+fn encode(buf: &mut [u8], kind: u8, sender: u16, sequence: u8, data: &[u8]) -> usize {
+    buf[0] = DISPATCH;
+    buf[1] = kind;
+    buf[2..4].copy_from_slice(&sender.to_le_bytes());
+    buf[4] = sequence;
+    buf[HEADER_LEN..HEADER_LEN + data.len()].copy_from_slice(data);
+    HEADER_LEN + data.len()
+}
+
+// Copies the payload of `frame`, which borrows the ring buffer, into `buf`.
+fn copy_payload(frame: &Frame, buf: &mut [u8; MAX_PAYLOAD_LEN]) -> usize {
+    let payload = frame.payload();
+    let len = payload.len().min(MAX_PAYLOAD_LEN);
+    buf[..len].copy_from_slice(&payload[..len]);
+    len
+}
@@ -0,0 +1,185 @@
+use crate::arq::*;
+use crate::RxRingBuffer;
+use libtock_alarm::Milliseconds;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake::{self, ieee802154::Frame as FakeFrame};
+
+type ReliableLink<'buf> = crate::ReliableLink<'buf, fake::Syscalls, 4>;
+
+const NODE: u16 = 0x0102;
+const PEER: u16 = 0x0907;
+
+// 10 ticks of the alarm set up by `setup`.
+const RETRANSMISSION: Retransmission = Retransmission {
+    retries: 2,
+    ack_timeout: Milliseconds(10),
+    backoff: Backoff::Constant,
+};
+
+// Makes the radio receive `payload`.
+fn deliver(phy: &fake::Ieee802154Phy, payload: &[u8]) {
+    phy.radio_receive_frame(FakeFrame::with_body(payload));
+    phy.driver_receive_pending_frames();
+    phy.trigger_rx_upcall();
+}
+
+// Advances the time until the alarm expires.
+fn expire(alarm: &fake::Alarm) {
+    alarm.advance_ticks(alarm.expiration().unwrap() - alarm.ticks());
+}
+
+fn data(sender: u16, sequence: u8, data: &[u8]) -> std::vec::Vec<u8> {
+    let [sender0, sender1] = sender.to_le_bytes();
+    [&[DISPATCH, 0, sender0, sender1, sequence][..], data].concat()
+}
+
+fn ack(sender: u16, sequence: u8) -> [u8; 5] {
+    let [sender0, sender1] = sender.to_le_bytes();
+    [DISPATCH, 1, sender0, sender1, sequence]
+}
+
+#[test]
+fn send() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut buf = RxRingBuffer::new();
+    let mut link = ReliableLink::new(NODE, &mut buf, RETRANSMISSION);
+
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        move || {
+            let frames = phy.take_transmitted_frames();
+            assert_eq!(frames, [data(NODE, 1, b"hello")]);
+            // ACKs for other nodes or data frames are ignored.
+            deliver(&phy, &ack(PEER, 1));
+            deliver(&phy, &ack(NODE, 0));
+            deliver(&phy, &ack(NODE, 1));
+        }
+    });
+    assert_eq!(link.send(b"hello"), Ok(()));
+
+    let too_long = [0; MAX_DATA_LEN + 1];
+    assert_eq!(link.send(&too_long), Err(ErrorCode::Size));
+}
+
+#[test]
+fn retransmit() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut buf = RxRingBuffer::new();
+    let mut link = ReliableLink::new(NODE, &mut buf, RETRANSMISSION);
+
+    // The first ACK is lost.
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        let alarm = alarm.clone();
+        move || {
+            assert_eq!(phy.take_transmitted_frames(), [data(NODE, 1, b"hello")]);
+            match alarm.ticks() {
+                0 => expire(&alarm),
+                _ => deliver(&phy, &ack(NODE, 1)),
+            }
+        }
+    });
+    assert_eq!(link.send(b"hello"), Ok(()));
+    assert_eq!(alarm.ticks(), 10);
+}
+
+#[test]
+fn no_ack() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut buf = RxRingBuffer::new();
+
+    for (backoff, elapsed) in [
+        (Backoff::Constant, 30),
+        (Backoff::Linear, 60),
+        (Backoff::Exponential, 70),
+    ] {
+        let retransmission = Retransmission {
+            backoff,
+            ..RETRANSMISSION
+        };
+        let mut link = ReliableLink::new(NODE, &mut buf, retransmission);
+        kernel.set_idle_handler({
+            let alarm = alarm.clone();
+            move || expire(&alarm)
+        });
+        let start = alarm.ticks();
+        assert_eq!(link.send(b"hello"), Err(ErrorCode::NoAck));
+        assert_eq!(alarm.ticks() - start, elapsed);
+        assert_eq!(phy.take_transmitted_frames().len(), 3);
+    }
+}
+
+#[test]
+fn receive() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut buf = RxRingBuffer::new();
+    let mut link = ReliableLink::new(NODE, &mut buf, RETRANSMISSION);
+
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        let mut sequences = [1, 1, 2].into_iter();
+        move || {
+            let sequence = sequences.next().unwrap();
+            deliver(&phy, &data(PEER, sequence, &[sequence]));
+        }
+    });
+    let delivery = link.receive().unwrap();
+    assert_eq!(
+        delivery,
+        Delivery {
+            sender: PEER,
+            data: &[1]
+        }
+    );
+    // The duplicate is acknowledged again, but not delivered.
+    assert_eq!(link.receive().unwrap().data, [2]);
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [ack(PEER, 1), ack(PEER, 1), ack(PEER, 2)]
+    );
+}
+
+#[test]
+fn receive_while_sending() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut buf = RxRingBuffer::new();
+    let mut link = ReliableLink::new(NODE, &mut buf, RETRANSMISSION);
+
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        move || {
+            deliver(&phy, &data(PEER, 1, b"first"));
+            // There is no room for the second data, so it is not
+            // acknowledged.
+            deliver(&phy, &data(PEER, 2, b"second"));
+            deliver(&phy, &ack(NODE, 1));
+        }
+    });
+    assert_eq!(link.send(b"hello"), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [data(NODE, 1, b"hello"), ack(PEER, 1).to_vec()]
+    );
+    // The first data was kept.
+    assert_eq!(link.receive().unwrap().data, b"first");
+}
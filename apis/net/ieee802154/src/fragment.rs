@@ -0,0 +1,288 @@
+//! Fragmentation of messages larger than a frame.
+//!
+//! [`Fragmenter`] splits a message into fragments, each transmitted in its own
+//! frame, and [`Reassembler`] puts the received fragments back together. This
+//! lets applications exchange messages of up to a few KB without a full
+//! 6LoWPAN stack. Each fragment starts with a 6-byte header:
+//!
+//! | Offset | Size | Content                               |
+//! |--------|------|---------------------------------------|
+//! | 0      | 1    | [`DISPATCH`]                          |
+//! | 1      | 2    | Sender ID, in little-endian           |
+//! | 3      | 1    | Message tag, incremented per message  |
+//! | 4      | 1    | Fragment index                        |
+//! | 5      | 1    | Fragment count                        |
+//!
+//! followed by the fragment's data. All fragments but the last carry
+//! [`FRAGMENT_DATA_LEN`] bytes of data.
+//!
+//! There are no acknowledgements: if a fragment is lost, the whole message is.
+
+use crate::{Config, Ieee802154, RxOperator, MAX_PAYLOAD_LEN};
+use core::marker::PhantomData;
+use libtock_alarm::{Alarm, Convert};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The first byte of fragments, which tells them apart from other payloads.
+pub const DISPATCH: u8 = 0xF4;
+
+/// The number of bytes of a message each fragment carries.
+pub const FRAGMENT_DATA_LEN: usize = MAX_PAYLOAD_LEN - HEADER_LEN;
+
+/// The length of the longest message, made of 255 fragments.
+pub const MAX_MESSAGE_LEN: usize = u8::MAX as usize * FRAGMENT_DATA_LEN;
+
+/// Transmits messages as fragments.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ieee802154::Fragmenter;
+///
+/// let mut fragmenter = Fragmenter::new(0xdead);
+/// fragmenter.send(&firmware_chunk)?;
+/// ```
+pub struct Fragmenter<S: Syscalls, C: Config = DefaultConfig> {
+    sender: u16,
+    tag: u8,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> Fragmenter<S, C> {
+    /// Creates a fragmenter sending as `sender`, which must be unique among
+    /// the devices sending to the same receivers, e.g. the short address.
+    pub fn new(sender: u16) -> Self {
+        Fragmenter {
+            sender,
+            tag: 0,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Transmits `message`, one fragment at a time. Fails with
+    /// `ErrorCode::Size` if it is longer than [`MAX_MESSAGE_LEN`].
+    ///
+    /// The receiver must keep up with the fragments, which are transmitted
+    /// back to back: its ring buffer must have room for them if it processes
+    /// other frames in the meantime.
+    pub fn send(&mut self, message: &[u8]) -> Result<(), ErrorCode> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(ErrorCode::Size);
+        }
+        let count = message.len().div_ceil(FRAGMENT_DATA_LEN).max(1);
+        let mut frame = [0; MAX_PAYLOAD_LEN];
+        frame[0] = DISPATCH;
+        frame[1..3].copy_from_slice(&self.sender.to_le_bytes());
+        frame[3] = self.tag;
+        frame[5] = count as u8;
+        self.tag = self.tag.wrapping_add(1);
+        for (index, data) in message.chunks(FRAGMENT_DATA_LEN).enumerate() {
+            frame[4] = index as u8;
+            frame[HEADER_LEN..HEADER_LEN + data.len()].copy_from_slice(data);
+            Ieee802154::<S, C>::transmit_frame(&frame[..HEADER_LEN + data.len()])?;
+        }
+        if message.is_empty() {
+            frame[4] = 0;
+            Ieee802154::<S, C>::transmit_frame(&frame[..HEADER_LEN])?;
+        }
+        Ok(())
+    }
+}
+
+/// A message put back together by a [`Reassembler`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Message<'a> {
+    pub sender: u16,
+    pub tag: u8,
+    pub data: &'a [u8],
+}
+
+/// Reassembles the messages of up to `SENDERS` senders at a time, each of up
+/// to `MAX_LEN` bytes.
+///
+/// A message that is not complete within the timeout after its first fragment
+/// arrived is dropped, which frees its buffer for other senders. While all
+/// buffers are in use, fragments of new messages are dropped. Fragments of a
+/// new message from a sender replace the sender's incomplete message.
+///
+/// # Example
+/// ```ignore
+/// use libtock::alarm::Milliseconds;
+/// use libtock::ieee802154::{Reassembler, RxRingBuffer, RxSingleBufferOperator};
+///
+/// let mut buf = RxRingBuffer::<8>::new();
+/// let mut operator = RxSingleBufferOperator::new(&mut buf);
+/// let mut reassembler = Reassembler::<2048>::new(Milliseconds(500))?;
+/// loop {
+///     let message = reassembler.receive(&mut operator)?;
+///     handle(message.sender, message.data);
+/// }
+/// ```
+pub struct Reassembler<S: Syscalls, const MAX_LEN: usize, const SENDERS: usize = 2> {
+    timeout: u32,
+    slots: [Slot<MAX_LEN>; SENDERS],
+    _syscalls: PhantomData<S>,
+}
+
+impl<S: Syscalls, const MAX_LEN: usize, const SENDERS: usize> Reassembler<S, MAX_LEN, SENDERS> {
+    /// Creates a reassembler dropping the messages that are not complete
+    /// within `timeout`.
+    pub fn new<T: Convert>(timeout: T) -> Result<Self, ErrorCode> {
+        Ok(Reassembler {
+            timeout: timeout.to_ticks(Alarm::<S>::get_frequency()?).0,
+            slots: [Slot::EMPTY; SENDERS],
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Adds a received payload. Returns the message if the payload is its
+    /// last missing fragment. Payloads that are not fragments are ignored.
+    pub fn push(&mut self, payload: &[u8]) -> Result<Option<Message<'_>>, ErrorCode> {
+        let now = Alarm::<S>::get_ticks()?;
+        Ok(self.accept(payload, now).map(|slot| self.message(slot)))
+    }
+
+    /// Receives frames from `operator` until a message is complete, and
+    /// returns it.
+    pub fn receive<O: RxOperator>(&mut self, operator: &mut O) -> Result<Message<'_>, ErrorCode> {
+        loop {
+            let frame = operator.receive_frame()?;
+            let now = Alarm::<S>::get_ticks()?;
+            if let Some(slot) = self.accept(frame.payload(), now) {
+                return Ok(self.message(slot));
+            }
+        }
+    }
+
+    // Adds a fragment, and returns the index of the slot of its message if it
+    // completed it.
+    fn accept(&mut self, payload: &[u8], now: u32) -> Option<usize> {
+        let header = Header::parse(payload)?;
+        let data = &payload[HEADER_LEN..];
+        let offset = header.index as usize * FRAGMENT_DATA_LEN;
+        let last = header.index == header.count - 1;
+        let full = data.len() == FRAGMENT_DATA_LEN;
+        if data.len() > FRAGMENT_DATA_LEN || (!last && !full) || offset + data.len() > MAX_LEN {
+            return None;
+        }
+
+        for slot in &mut self.slots {
+            if slot.active && now.wrapping_sub(slot.started) > self.timeout {
+                slot.active = false;
+            }
+        }
+        let index = match self
+            .slots
+            .iter()
+            .position(|slot| slot.active && slot.sender == header.sender)
+        {
+            Some(index) if self.slots[index].is_message(&header) => index,
+            Some(index) => {
+                self.slots[index].start(&header, now);
+                index
+            }
+            None => {
+                let index = self.slots.iter().position(|slot| !slot.active)?;
+                self.slots[index].start(&header, now);
+                index
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        let (word, bit) = (header.index as usize / 32, 1 << (header.index % 32));
+        if slot.received[word] & bit != 0 {
+            return None;
+        }
+        slot.received[word] |= bit;
+        slot.buffer[offset..offset + data.len()].copy_from_slice(data);
+        if last {
+            slot.len = offset + data.len();
+        }
+        slot.missing -= 1;
+        if slot.missing > 0 {
+            return None;
+        }
+        slot.active = false;
+        Some(index)
+    }
+
+    fn message(&self, slot: usize) -> Message<'_> {
+        let slot = &self.slots[slot];
+        Message {
+            sender: slot.sender,
+            tag: slot.tag,
+            data: &slot.buffer[..slot.len],
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 6;
+
+struct Header {
+    sender: u16,
+    tag: u8,
+    index: u8,
+    count: u8,
+}
+
+impl Header {
+    fn parse(payload: &[u8]) -> Option<Header> {
+        match *payload.get(..HEADER_LEN)? {
+            [DISPATCH, sender0, sender1, tag, index, count] if index < count => Some(Header {
+                sender: u16::from_le_bytes([sender0, sender1]),
+                tag,
+                index,
+                count,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// The buffer of a message being reassembled.
+struct Slot<const MAX_LEN: usize> {
+    active: bool,
+    sender: u16,
+    tag: u8,
+    count: u8,
+    // The tick at which the first fragment arrived.
+    started: u32,
+    // A bit per fragment index, set once the fragment has arrived.
+    received: [u32; 8],
+    missing: u8,
+    // The length of the message, known once the last fragment has arrived.
+    len: usize,
+    buffer: [u8; MAX_LEN],
+}
+
+impl<const MAX_LEN: usize> Slot<MAX_LEN> {
+    const EMPTY: Self = Slot {
+        active: false,
+        sender: 0,
+        tag: 0,
+        count: 0,
+        started: 0,
+        received: [0; 8],
+        missing: 0,
+        len: 0,
+        buffer: [0; MAX_LEN],
+    };
+
+    fn is_message(&self, header: &Header) -> bool {
+        self.tag == header.tag && self.count == header.count
+    }
+
+    fn start(&mut self, header: &Header, now: u32) {
+        self.active = true;
+        self.sender = header.sender;
+        self.tag = header.tag;
+        self.count = header.count;
+        self.started = now;
+        self.received = [0; 8];
+        self.missing = header.count;
+        self.len = 0;
+    }
+}
@@ -0,0 +1,205 @@
+use crate::fragment::*;
+use crate::{Frame, RxOperator};
+use libtock_alarm::Milliseconds;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Fragmenter = crate::Fragmenter<fake::Syscalls>;
+type Reassembler<const MAX_LEN: usize, const SENDERS: usize = 2> =
+    crate::Reassembler<fake::Syscalls, MAX_LEN, SENDERS>;
+
+// 100 ticks of the alarm set up by `setup`.
+const TIMEOUT: Milliseconds = Milliseconds(100);
+
+fn message() -> [u8; 250] {
+    core::array::from_fn(|i| i as u8)
+}
+
+#[test]
+fn send() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut fragmenter = Fragmenter::new(0xbeef);
+
+    assert_eq!(fragmenter.send(&message()), Ok(()));
+    let fragments = phy.take_transmitted_frames();
+    assert_eq!(fragments.len(), 3);
+    for (index, fragment) in fragments.iter().enumerate() {
+        assert_eq!(fragment[..6], [DISPATCH, 0xef, 0xbe, 0, index as u8, 3]);
+    }
+    assert_eq!(fragments[0].len(), 6 + FRAGMENT_DATA_LEN);
+    assert_eq!(fragments[2].len(), 6 + 250 - 2 * FRAGMENT_DATA_LEN);
+    assert_eq!(
+        fragments[1][6..],
+        message()[FRAGMENT_DATA_LEN..][..FRAGMENT_DATA_LEN]
+    );
+
+    // The next message has the next tag, and empty messages take a fragment.
+    assert_eq!(fragmenter.send(&[]), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [[DISPATCH, 0xef, 0xbe, 1, 0, 1]]
+    );
+
+    let too_long = [0; MAX_MESSAGE_LEN + 1];
+    assert_eq!(fragmenter.send(&too_long), Err(ErrorCode::Size));
+    assert!(phy.take_transmitted_frames().is_empty());
+}
+
+#[test]
+fn reassemble() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    Fragmenter::new(7).send(&message()).unwrap();
+    let fragments = phy.take_transmitted_frames();
+    let mut reassembler = Reassembler::<256>::new(TIMEOUT).unwrap();
+
+    // Fragments may arrive out of order, and duplicated.
+    assert_eq!(reassembler.push(&fragments[2]), Ok(None));
+    assert_eq!(reassembler.push(&fragments[0]), Ok(None));
+    assert_eq!(reassembler.push(&fragments[2]), Ok(None));
+    let message = message();
+    assert_eq!(
+        reassembler.push(&fragments[1]),
+        Ok(Some(Message {
+            sender: 7,
+            tag: 0,
+            data: &message,
+        }))
+    );
+    // The message is complete, so a late duplicate starts a new one.
+    assert_eq!(reassembler.push(&fragments[1]), Ok(None));
+}
+
+#[test]
+fn ignored() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    Fragmenter::new(7).send(&message()).unwrap();
+    let fragments = phy.take_transmitted_frames();
+
+    // Payloads that are not fragments.
+    let mut reassembler = Reassembler::<256>::new(TIMEOUT).unwrap();
+    assert_eq!(reassembler.push(b"hello"), Ok(None));
+    assert_eq!(reassembler.push(&[DISPATCH, 7, 0, 0, 1, 1]), Ok(None));
+    // A fragment that is not the last, but is not full.
+    assert_eq!(reassembler.push(&fragments[0][..50]), Ok(None));
+
+    // Messages longer than the buffers.
+    let mut reassembler = Reassembler::<200>::new(TIMEOUT).unwrap();
+    for fragment in &fragments {
+        assert_eq!(reassembler.push(fragment), Ok(None));
+    }
+}
+
+#[test]
+fn senders() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    for sender in 1..=3 {
+        Fragmenter::new(sender).send(&message()).unwrap();
+    }
+    let fragments = phy.take_transmitted_frames();
+    let mut reassembler = Reassembler::<256, 2>::new(TIMEOUT).unwrap();
+
+    // The messages of senders 1 and 2 interleave. Sender 3's fragments are
+    // dropped, as both buffers are in use.
+    for index in 0..2 {
+        assert_eq!(reassembler.push(&fragments[index]), Ok(None));
+        assert_eq!(reassembler.push(&fragments[3 + index]), Ok(None));
+        assert_eq!(reassembler.push(&fragments[6 + index]), Ok(None));
+    }
+    let message = message();
+    let pushed = reassembler.push(&fragments[5]).unwrap().unwrap();
+    assert_eq!((pushed.sender, pushed.data), (2, &message[..]));
+    assert_eq!(reassembler.push(&fragments[8]), Ok(None));
+    let pushed = reassembler.push(&fragments[2]).unwrap().unwrap();
+    assert_eq!((pushed.sender, pushed.data), (1, &message[..]));
+}
+
+#[test]
+fn new_message_replaces_incomplete() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut fragmenter = Fragmenter::new(7);
+    fragmenter.send(&message()).unwrap();
+    fragmenter.send(b"short").unwrap();
+    let fragments = phy.take_transmitted_frames();
+    let mut reassembler = Reassembler::<256>::new(TIMEOUT).unwrap();
+
+    assert_eq!(reassembler.push(&fragments[0]), Ok(None));
+    let pushed = reassembler.push(&fragments[3]).unwrap().unwrap();
+    assert_eq!((pushed.tag, pushed.data), (1, &b"short"[..]));
+    assert_eq!(reassembler.push(&fragments[1]), Ok(None));
+    assert_eq!(reassembler.push(&fragments[2]), Ok(None));
+}
+
+#[test]
+fn timeout() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    Fragmenter::new(7).send(&message()).unwrap();
+    let fragments = phy.take_transmitted_frames();
+    let mut reassembler = Reassembler::<256, 1>::new(TIMEOUT).unwrap();
+
+    assert_eq!(reassembler.push(&fragments[0]), Ok(None));
+    alarm.advance_ticks(101);
+    // The first fragment was dropped, so the message is incomplete.
+    assert_eq!(reassembler.push(&fragments[1]), Ok(None));
+    assert_eq!(reassembler.push(&fragments[2]), Ok(None));
+    assert_eq!(
+        reassembler.push(&fragments[0]).unwrap().unwrap().data.len(),
+        250
+    );
+}
+
+// Receives the same frame over and over.
+struct TestOperator(Frame);
+
+impl RxOperator for TestOperator {
+    fn receive_frame(&mut self) -> Result<&mut Frame, ErrorCode> {
+        Ok(&mut self.0)
+    }
+}
+
+#[test]
+fn receive() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    Fragmenter::new(7).send(b"hello").unwrap();
+    let fragment = phy.take_transmitted_frames().remove(0);
+    // A header precedes the payload.
+    let mut frame = Frame {
+        header_len: 3,
+        payload_len: fragment.len() as u8,
+        mic_len: 0,
+        body: [0; 127],
+    };
+    frame.body[3..3 + fragment.len()].copy_from_slice(&fragment);
+    let mut operator = TestOperator(frame);
+    let mut reassembler = Reassembler::<256>::new(TIMEOUT).unwrap();
+
+    let message = reassembler.receive(&mut operator).unwrap();
+    assert_eq!((message.sender, message.data), (7, &b"hello"[..]));
+}
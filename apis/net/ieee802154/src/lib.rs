@@ -1,8 +1,10 @@
 //! The raw IEEE 802.15.4 stack driver.
 
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 use core::cell::Cell;
+use core::task::Poll;
+use libtock_future::{wait_for_upcall, TockFuture, UpcallFuture};
 use libtock_platform as platform;
 use libtock_platform::allow_ro::AllowRo;
 use libtock_platform::allow_rw::AllowRw;
@@ -60,7 +62,7 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
     /// memory.
     #[inline(always)]
     pub fn exists() -> bool {
-        S::command(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).is_success()
     }
 }
 
@@ -68,17 +70,17 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
 impl<S: Syscalls, C: Config> Ieee802154<S, C> {
     #[inline(always)]
     pub fn is_on() -> bool {
-        S::command(DRIVER_NUM, command::STATUS, 0, 0).is_success()
+        S::checked_command::<C>(DRIVER_NUM, command::STATUS, 0, 0).is_success()
     }
 
     #[inline(always)]
     pub fn radio_on() -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, command::TURN_ON, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, command::TURN_ON, 0, 0).to_result()
     }
 
     #[inline(always)]
     pub fn radio_off() -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, command::TURN_OFF, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, command::TURN_OFF, 0, 0).to_result()
     }
 }
 
@@ -87,7 +89,7 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
     #[inline(always)]
     pub fn set_address_short(short_addr: u16) {
         // Setting short address can't fail, so no need to check the return value.
-        let _ = S::command(
+        let _ = S::checked_command::<C>(
             DRIVER_NUM,
             command::SET_SHORT_ADDR,
             // Driver expects 1 added to make the value positive.
@@ -101,13 +103,13 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
         // Setting long address can't fail, so no need to check the return value.
         let addr_lower: u32 = long_addr as u32;
         let addr_upper: u32 = (long_addr >> 32) as u32;
-        let _ = S::command(DRIVER_NUM, command::SET_LONG_ADDR, addr_lower, addr_upper);
+        let _ = S::checked_command::<C>(DRIVER_NUM, command::SET_LONG_ADDR, addr_lower, addr_upper);
     }
 
     #[inline(always)]
     pub fn set_pan(pan: u16) {
         // Setting PAN can't fail, so no need to check the return value.
-        let _ = S::command(
+        let _ = S::checked_command::<C>(
             DRIVER_NUM,
             command::SET_PAN,
             pan as u32 + 1, // Driver expects 1 added to make the value positive.
@@ -117,60 +119,135 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
 
     #[inline(always)]
     pub fn set_channel(chan: u8) -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, command::SET_CHAN, chan as u32, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, command::SET_CHAN, chan as u32, 0).to_result()
     }
 
     #[inline(always)]
     pub fn set_tx_power(power: i8) -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, command::SET_TX_PWR, power as i32 as u32, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, command::SET_TX_PWR, power as i32 as u32, 0).to_result()
     }
 
     #[inline(always)]
     pub fn commit_config() {
         // Committing config can't fail, so no need to check the return value.
-        let _ = S::command(DRIVER_NUM, command::COMMIT_CFG, 0, 0);
+        let _ = S::checked_command::<C>(DRIVER_NUM, command::COMMIT_CFG, 0, 0);
     }
 
     #[inline(always)]
     pub fn get_address_short() -> Result<u16, ErrorCode> {
-        S::command(DRIVER_NUM, command::GET_SHORT_ADDR, 0, 0)
+        S::checked_command::<C>(DRIVER_NUM, command::GET_SHORT_ADDR, 0, 0)
             .to_result::<u32, _>()
             // Driver adds 1 to make the value positive.
-            .map(|addr| addr as u16 - 1)
+            .map(|addr| (addr - 1) as u16)
     }
 
     #[inline(always)]
     pub fn get_address_long() -> Result<u64, ErrorCode> {
-        S::command(DRIVER_NUM, command::GET_LONG_ADDR, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, command::GET_LONG_ADDR, 0, 0).to_result()
     }
 
     #[inline(always)]
     pub fn get_pan() -> Result<u16, ErrorCode> {
-        S::command(DRIVER_NUM, command::GET_PAN, 0, 0)
+        S::checked_command::<C>(DRIVER_NUM, command::GET_PAN, 0, 0)
             .to_result::<u32, _>()
             // Driver adds 1 to make the value positive.
-            .map(|pan| pan as u16 - 1)
+            .map(|pan| (pan - 1) as u16)
     }
 
     #[inline(always)]
     pub fn get_channel() -> Result<u8, ErrorCode> {
-        S::command(DRIVER_NUM, command::GET_CHAN, 0, 0)
+        S::checked_command::<C>(DRIVER_NUM, command::GET_CHAN, 0, 0)
             .to_result::<u32, _>()
             .map(|chan| chan as u8)
     }
 
     #[inline(always)]
     pub fn get_tx_power() -> Result<i8, ErrorCode> {
-        S::command(DRIVER_NUM, command::GET_TX_PWR, 0, 0)
+        S::checked_command::<C>(DRIVER_NUM, command::GET_TX_PWR, 0, 0)
             .to_result::<u32, _>()
             .map(|power| power as i32 as i8)
     }
+
+    /// Sets the whole configuration, and commits it.
+    pub fn configure(config: &RadioConfig) -> Result<(), ErrorCode> {
+        Self::set_pan(config.pan);
+        Self::set_address_short(config.address_short);
+        Self::set_address_long(config.address_long);
+        Self::set_channel(config.channel)?;
+        Self::set_tx_power(config.tx_power)?;
+        Self::commit_config();
+        Ok(())
+    }
+
+    /// Reads back the configuration the kernel holds, which is the committed
+    /// one if `commit_config` was called after the last change.
+    pub fn read_config() -> Result<RadioConfig, ErrorCode> {
+        Ok(RadioConfig {
+            pan: Self::get_pan()?,
+            address_short: Self::get_address_short()?,
+            address_long: Self::get_address_long()?,
+            channel: Self::get_channel()?,
+            tx_power: Self::get_tx_power()?,
+        })
+    }
+
+    /// Reads back the configuration, and returns which of its fields differ
+    /// from `expected`. The radio may, for instance, round the TX power to a
+    /// level it supports.
+    pub fn verify_config(expected: &RadioConfig) -> Result<ConfigDiff, ErrorCode> {
+        Ok(expected.diff(&Self::read_config()?))
+    }
+}
+
+/// The configuration of the radio.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RadioConfig {
+    pub pan: u16,
+    pub address_short: u16,
+    pub address_long: u64,
+    pub channel: u8,
+    /// In dBm.
+    pub tx_power: i8,
+}
+
+impl RadioConfig {
+    /// Returns which fields differ between `self` and `other`.
+    pub fn diff(&self, other: &RadioConfig) -> ConfigDiff {
+        ConfigDiff {
+            pan: self.pan != other.pan,
+            address_short: self.address_short != other.address_short,
+            address_long: self.address_long != other.address_long,
+            channel: self.channel != other.channel,
+            tx_power: self.tx_power != other.tx_power,
+        }
+    }
+}
+
+/// Which fields of two `RadioConfig`s differ, see
+/// [`Ieee802154::verify_config`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ConfigDiff {
+    pub pan: bool,
+    pub address_short: bool,
+    pub address_long: bool,
+    pub channel: bool,
+    pub tx_power: bool,
+}
+
+impl ConfigDiff {
+    /// Returns `true` if no field differs.
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigDiff::default()
+    }
 }
 
 // Transmission
 impl<S: Syscalls, C: Config> Ieee802154<S, C> {
+    /// Transmits `frame`, and waits for the transmission to complete. Fails
+    /// with the error the driver reports, e.g. `ErrorCode::Busy` if the
+    /// channel stays busy.
     pub fn transmit_frame(frame: &[u8]) -> Result<(), ErrorCode> {
-        let called: Cell<Option<(u32,)>> = Cell::new(None);
+        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
         share::scope::<
             (
                 AllowRo<_, DRIVER_NUM, { allow_ro::WRITE }>,
@@ -187,34 +264,143 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
                 subscribe, &called,
             )?;
 
-            S::command(DRIVER_NUM, command::TRANSMIT, 0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, command::TRANSMIT, 0, 0).to_result()?;
 
             loop {
                 S::yield_wait();
-                if called.get().is_some() {
-                    return Ok(());
+                if let Some((status, acked)) = called.get() {
+                    return tx_info(status, acked).map(|_| ());
                 }
             }
         })
     }
+
+    /// Starts transmitting `frame` in the background, to wait for the
+    /// transmission along with other events, e.g. to `select` or `join` it
+    /// with reception or alarm futures. The kernel can read `frame` until the
+    /// end of the handles' scope, and the returned future completes with the
+    /// outcome of the transmission once it is over.
+    pub fn transmit_frame_fut<'share>(
+        frame: &'share [u8],
+        transmitted: &'share Cell<Option<(u32, u32)>>,
+        allow_ro: share::Handle<TxBuffer<'share, S>>,
+        subscribe: share::Handle<TxSubscribe<'share, S>>,
+    ) -> Result<TransmitFuture<'share>, ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::WRITE }>(allow_ro, frame)?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::FRAME_TRANSMITTED }>(
+            subscribe,
+            transmitted,
+        )?;
+        S::checked_command::<C>(DRIVER_NUM, command::TRANSMIT, 0, 0).to_result()?;
+        Ok(TransmitFuture {
+            transmitted: wait_for_upcall(transmitted),
+        })
+    }
+}
+
+/// The outcome of a successful transmission.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TxInfo {
+    /// Whether the receiver acknowledged the frame. Frames that request no
+    /// acknowledgement, such as broadcasts, are never acknowledged.
+    pub acked: bool,
+}
+
+/// The future returned by [`Ieee802154::transmit_frame_fut`]. It completes
+/// with the error the driver reports, if the transmission fails.
+pub struct TransmitFuture<'share> {
+    transmitted: UpcallFuture<'share, (u32, u32)>,
 }
 
+impl<S: Syscalls> TockFuture<S> for TransmitFuture<'_> {
+    type Output = Result<TxInfo, ErrorCode>;
+
+    fn poll(&mut self) -> Poll<Result<TxInfo, ErrorCode>> {
+        TockFuture::<S>::poll(&mut self.transmitted).map(|(status, acked)| tx_info(status, acked))
+    }
+}
+
+// Returns the outcome a FRAME_TRANSMITTED upcall reports.
+fn tx_info(status: u32, acked: u32) -> Result<TxInfo, ErrorCode> {
+    match status {
+        0 => Ok(TxInfo { acked: acked != 0 }),
+        status => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+    }
+}
+
+/// The frame shared by [`Ieee802154::transmit_frame_fut`].
+pub type TxBuffer<'share, S> = AllowRo<'share, S, DRIVER_NUM, { allow_ro::WRITE }>;
+
+/// The subscription of [`Ieee802154::transmit_frame_fut`].
+pub type TxSubscribe<'share, S> =
+    Subscribe<'share, S, DRIVER_NUM, { subscribe::FRAME_TRANSMITTED }>;
+
+pub mod arq;
+pub mod fragment;
+pub mod mesh;
+pub mod neighbor;
+pub mod role;
 mod rx;
-pub use rx::{Frame, RxOperator, RxRingBuffer, RxSingleBufferOperator};
+pub mod telemetry;
+pub mod tx_queue;
+pub use arq::ReliableLink;
+pub use fragment::{Fragmenter, Message, Reassembler};
+pub use mesh::Mesh;
+pub use neighbor::NeighborTable;
+pub use rx::{Frame, RxBuffer, RxOperator, RxRingBuffer, RxSingleBufferOperator, RxSubscribe};
+pub use tx_queue::{Priority, TxQueue};
+
+/// The largest payload that fits in a frame even with long source and
+/// destination addresses: 127 bytes minus a 21-byte MAC header and the 2-byte
+/// frame check sequence.
+pub const MAX_PAYLOAD_LEN: usize = 104;
+
+/// An estimate of the stack the deepest call of this crate uses, for
+/// `stack_size!` hints. Reception and the protocol layers keep frames on the
+/// stack.
+pub const STACK_USAGE: usize = 0x100;
 
 /// System call configuration trait for `Ieee802154`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
+#[cfg(test)]
+mod arq_tests;
+
+#[cfg(test)]
+mod fragment_tests;
+
+#[cfg(test)]
+mod mesh_tests;
+
+#[cfg(test)]
+mod neighbor_tests;
+
+#[cfg(test)]
+mod role_tests;
+
+#[cfg(test)]
+mod telemetry_tests;
+
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod tx_queue_tests;
+
 // -----------------------------------------------------------------------------
 // Driver number and command IDs
 // -----------------------------------------------------------------------------
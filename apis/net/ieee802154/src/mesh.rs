@@ -0,0 +1,278 @@
+//! Multi-hop routing, so that devices out of range of the gateway can reach it
+//! through their peers.
+//!
+//! [`Mesh`] supports two kinds of packets:
+//! - flooded packets, which every device relays and delivers;
+//! - upward packets, which travel towards the root (the gateway) only.
+//!
+//! The root periodically floods beacons, from which every device learns its
+//! rank: its distance from the root, in hops. An upward packet is only relayed
+//! by devices with a lower rank than the device it came from, so that it
+//! travels along the tree of shortest paths. Every packet starts with a 7-byte
+//! header:
+//!
+//! | Offset | Size | Content                                                 |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 1    | [`DISPATCH`]                                            |
+//! | 1      | 1    | Kind: 0 for beacons, 1 for flooded, 2 for upward        |
+//! | 2      | 2    | Originator ID, in little-endian                         |
+//! | 4      | 1    | Sequence number, per originator                         |
+//! | 5      | 1    | Hops left before the packet is dropped                  |
+//! | 6      | 1    | Rank of the device that transmitted it, 255 if unknown  |
+//!
+//! which flooded and upward packets follow with the data. Devices recognize the
+//! packets they already relayed by their originator and sequence number.
+
+use crate::{Config, Ieee802154, MAX_PAYLOAD_LEN};
+use core::marker::PhantomData;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The first byte of mesh packets, which tells them apart from other payloads.
+pub const DISPATCH: u8 = 0xF7;
+
+/// The maximum length of the data of a packet.
+pub const MAX_DATA_LEN: usize = MAX_PAYLOAD_LEN - HEADER_LEN;
+
+/// The shape of the mesh, which all its devices must agree on.
+#[derive(Clone, Copy)]
+pub struct Routing {
+    /// The ID of the device that upward packets travel to.
+    pub root: u16,
+    /// The number of times a packet may be transmitted, including by its
+    /// originator.
+    pub hop_limit: u8,
+}
+
+/// A packet delivered by a [`Mesh`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Packet<'a> {
+    pub origin: u16,
+    /// Whether the packet was flooded, rather than sent upward.
+    pub flooded: bool,
+    pub data: &'a [u8],
+}
+
+/// A device of a mesh. It recognizes the last `CACHE` packets it relayed or
+/// delivered, and ignores their copies.
+///
+/// The mesh is driven by the application, which passes the received payloads
+/// to [`push`](Self::push), and makes the root send beacons periodically.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ieee802154::{mesh::Routing, Mesh, RxOperator, RxRingBuffer, RxSingleBufferOperator};
+///
+/// let mut buf = RxRingBuffer::<4>::new();
+/// let mut operator = RxSingleBufferOperator::new(&mut buf);
+/// let mut mesh = Mesh::<8>::new(0xdead, Routing { root: 1, hop_limit: 4 });
+/// mesh.send_upward(&telemetry)?;
+/// loop {
+///     let frame = operator.receive_frame()?;
+///     if let Some(packet) = mesh.push(frame.payload())? {
+///         handle(packet.origin, packet.data);
+///     }
+/// }
+/// ```
+pub struct Mesh<S: Syscalls, const CACHE: usize = 8, C: Config = DefaultConfig> {
+    node: u16,
+    routing: Routing,
+    sequence: u8,
+    rank: Option<u8>,
+    // The sequence number of the beacon that `rank` was learned from.
+    beacon: Option<u8>,
+    // The recently relayed or delivered packets, replaced in round-robin
+    // order.
+    seen: [Option<(u16, u8)>; CACHE],
+    next_seen: usize,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const CACHE: usize, C: Config> Mesh<S, CACHE, C> {
+    // Evaluated, and therefore checked, for each `CACHE` a mesh is created
+    // with.
+    const VALID_CACHE: () = assert!(CACHE > 0, "Mesh remembers at least 1 packet");
+
+    /// Creates a device of the mesh sending as `node`, which must be unique
+    /// within the mesh, e.g. the short address.
+    pub fn new(node: u16, routing: Routing) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_CACHE;
+        Mesh {
+            node,
+            routing,
+            sequence: 0,
+            rank: (node == routing.root).then_some(0),
+            beacon: None,
+            seen: [None; CACHE],
+            next_seen: 0,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Returns the distance from the root in hops, unknown until a beacon
+    /// arrives.
+    pub fn rank(&self) -> Option<u8> {
+        self.rank
+    }
+
+    /// Floods a beacon, from which the other devices learn their rank. Fails
+    /// with `ErrorCode::Invalid` on devices other than the root.
+    pub fn beacon(&mut self) -> Result<(), ErrorCode> {
+        if self.node != self.routing.root {
+            return Err(ErrorCode::Invalid);
+        }
+        self.originate(KIND_BEACON, &[])
+    }
+
+    /// Floods `data` to every device of the mesh. Fails with `ErrorCode::Size`
+    /// if `data` is longer than [`MAX_DATA_LEN`].
+    pub fn flood(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        self.originate(KIND_FLOODED, data)
+    }
+
+    /// Sends `data` towards the root. Fails with `ErrorCode::Fail` until the
+    /// rank is known, with `ErrorCode::Invalid` on the root itself, and with
+    /// `ErrorCode::Size` if `data` is longer than [`MAX_DATA_LEN`].
+    pub fn send_upward(&mut self, data: &[u8]) -> Result<(), ErrorCode> {
+        if self.node == self.routing.root {
+            return Err(ErrorCode::Invalid);
+        }
+        if self.rank.is_none() {
+            return Err(ErrorCode::Fail);
+        }
+        self.originate(KIND_UPWARD, data)
+    }
+
+    /// Handles a received payload: relays it if needed, and returns the packet
+    /// if it is meant for this device. Payloads that are not mesh packets are
+    /// ignored.
+    pub fn push<'p>(&mut self, payload: &'p [u8]) -> Result<Option<Packet<'p>>, ErrorCode> {
+        let Some(header) = Header::parse(payload) else {
+            return Ok(None);
+        };
+        let data = &payload[HEADER_LEN..];
+        if header.origin == self.node {
+            return Ok(None);
+        }
+        let seen = self.seen.contains(&Some((header.origin, header.sequence)));
+        match header.kind {
+            KIND_BEACON if header.origin == self.routing.root => {
+                let Some(rank) = header.rank.checked_add(1) else {
+                    return Ok(None);
+                };
+                if self.beacon != Some(header.sequence) || self.rank.is_some_and(|r| rank < r) {
+                    self.beacon = Some(header.sequence);
+                    self.rank = Some(rank);
+                }
+                if !seen {
+                    self.remember(&header);
+                    self.relay(&header, data)?;
+                }
+                Ok(None)
+            }
+            KIND_FLOODED if !seen => {
+                self.remember(&header);
+                self.relay(&header, data)?;
+                Ok(Some(Packet {
+                    origin: header.origin,
+                    flooded: true,
+                    data,
+                }))
+            }
+            KIND_UPWARD if !seen && self.rank.is_some_and(|rank| rank < header.rank) => {
+                self.remember(&header);
+                if self.node == self.routing.root {
+                    return Ok(Some(Packet {
+                        origin: header.origin,
+                        flooded: false,
+                        data,
+                    }));
+                }
+                self.relay(&header, data)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn originate(&mut self, kind: u8, data: &[u8]) -> Result<(), ErrorCode> {
+        if data.len() > MAX_DATA_LEN {
+            return Err(ErrorCode::Size);
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        let header = Header {
+            kind,
+            origin: self.node,
+            sequence: self.sequence,
+            hops_left: self.routing.hop_limit,
+            rank: self.rank.unwrap_or(UNKNOWN_RANK),
+        };
+        self.transmit(&header, data)
+    }
+
+    // Transmits a received packet again, if it has hops left.
+    fn relay(&self, header: &Header, data: &[u8]) -> Result<(), ErrorCode> {
+        match header.hops_left.checked_sub(1) {
+            Some(hops_left) if hops_left > 0 => self.transmit(
+                &Header {
+                    hops_left,
+                    rank: self.rank.unwrap_or(UNKNOWN_RANK),
+                    ..*header
+                },
+                data,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn transmit(&self, header: &Header, data: &[u8]) -> Result<(), ErrorCode> {
+        let mut frame = [0; MAX_PAYLOAD_LEN];
+        frame[0] = DISPATCH;
+        frame[1] = header.kind;
+        frame[2..4].copy_from_slice(&header.origin.to_le_bytes());
+        frame[4] = header.sequence;
+        frame[5] = header.hops_left;
+        frame[6] = header.rank;
+        frame[HEADER_LEN..HEADER_LEN + data.len()].copy_from_slice(data);
+        Ieee802154::<S, C>::transmit_frame(&frame[..HEADER_LEN + data.len()])
+    }
+
+    fn remember(&mut self, header: &Header) {
+        self.seen[self.next_seen] = Some((header.origin, header.sequence));
+        self.next_seen = (self.next_seen + 1) % CACHE;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 7;
+const KIND_BEACON: u8 = 0;
+const KIND_FLOODED: u8 = 1;
+const KIND_UPWARD: u8 = 2;
+const UNKNOWN_RANK: u8 = u8::MAX;
+
+#[derive(Clone, Copy)]
+struct Header {
+    kind: u8,
+    origin: u16,
+    sequence: u8,
+    hops_left: u8,
+    rank: u8,
+}
+
+impl Header {
+    fn parse(payload: &[u8]) -> Option<Header> {
+        match *payload.get(..HEADER_LEN)? {
+            [DISPATCH, kind, origin0, origin1, sequence, hops_left, rank] => Some(Header {
+                kind,
+                origin: u16::from_le_bytes([origin0, origin1]),
+                sequence,
+                hops_left,
+                rank,
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::mesh::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Mesh = crate::Mesh<fake::Syscalls, 4>;
+
+const ROOT: u16 = 1;
+const NODE: u16 = 5;
+const ROUTING: Routing = Routing {
+    root: ROOT,
+    hop_limit: 3,
+};
+
+fn packet(kind: u8, origin: u16, sequence: u8, hops_left: u8, rank: u8, data: &[u8]) -> Vec<u8> {
+    let [origin0, origin1] = origin.to_le_bytes();
+    [
+        &[DISPATCH, kind, origin0, origin1, sequence, hops_left, rank][..],
+        data,
+    ]
+    .concat()
+}
+
+#[test]
+fn beacons() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let mut root = Mesh::new(ROOT, ROUTING);
+    let mut node = Mesh::new(NODE, ROUTING);
+    assert_eq!(root.rank(), Some(0));
+    assert_eq!(node.rank(), None);
+    assert_eq!(node.beacon(), Err(ErrorCode::Invalid));
+
+    assert_eq!(root.beacon(), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [packet(0, ROOT, 1, 3, 0, &[])]
+    );
+
+    // The first copy of a beacon sets the rank, and is relayed. Copies over
+    // shorter paths lower it.
+    assert_eq!(node.push(&packet(0, ROOT, 1, 2, 1, &[])), Ok(None));
+    assert_eq!(node.rank(), Some(2));
+    assert_eq!(node.push(&packet(0, ROOT, 1, 3, 0, &[])), Ok(None));
+    assert_eq!(node.rank(), Some(1));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [packet(0, ROOT, 1, 1, 2, &[])]
+    );
+
+    // The next beacon sets the rank again, even if it is higher.
+    assert_eq!(node.push(&packet(0, ROOT, 2, 2, 2, &[])), Ok(None));
+    assert_eq!(node.rank(), Some(3));
+
+    // Beacons from other devices are ignored.
+    assert_eq!(node.push(&packet(0, 9, 3, 3, 0, &[])), Ok(None));
+    assert_eq!(node.rank(), Some(3));
+}
+
+#[test]
+fn flood() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let mut node = Mesh::new(NODE, ROUTING);
+
+    assert_eq!(node.flood(b"hi"), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [packet(1, NODE, 1, 3, 255, b"hi")]
+    );
+    // Our own packets are not delivered back.
+    assert_eq!(node.push(&packet(1, NODE, 1, 2, 255, b"hi")), Ok(None));
+
+    let received = packet(1, 7, 1, 2, 255, b"all");
+    assert_eq!(
+        node.push(&received),
+        Ok(Some(Packet {
+            origin: 7,
+            flooded: true,
+            data: b"all",
+        }))
+    );
+    assert_eq!(node.push(&received), Ok(None));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [packet(1, 7, 1, 1, 255, b"all")]
+    );
+
+    // Packets without hops left are delivered, but not relayed.
+    assert!(node.push(&packet(1, 7, 2, 1, 255, b"")).unwrap().is_some());
+    assert!(phy.take_transmitted_frames().is_empty());
+
+    let too_long = [0; MAX_DATA_LEN + 1];
+    assert_eq!(node.flood(&too_long), Err(ErrorCode::Size));
+}
+
+#[test]
+fn upward() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let mut root = Mesh::new(ROOT, ROUTING);
+    let mut node = Mesh::new(NODE, ROUTING);
+    assert_eq!(root.send_upward(b"up"), Err(ErrorCode::Invalid));
+    assert_eq!(node.send_upward(b"up"), Err(ErrorCode::Fail));
+
+    node.push(&packet(0, ROOT, 1, 3, 0, &[])).unwrap();
+    phy.take_transmitted_frames();
+    assert_eq!(node.send_upward(b"up"), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [packet(2, NODE, 1, 3, 1, b"up")]
+    );
+
+    // Only packets from farther devices are relayed, once.
+    assert_eq!(node.push(&packet(2, 7, 1, 3, 1, b"a")), Ok(None));
+    assert_eq!(node.push(&packet(2, 7, 2, 3, 2, b"b")), Ok(None));
+    assert_eq!(node.push(&packet(2, 7, 2, 3, 2, b"b")), Ok(None));
+    assert_eq!(phy.take_transmitted_frames(), [packet(2, 7, 2, 2, 1, b"b")]);
+
+    // The root delivers them.
+    assert_eq!(
+        root.push(&packet(2, 7, 2, 2, 1, b"b")),
+        Ok(Some(Packet {
+            origin: 7,
+            flooded: false,
+            data: b"b",
+        }))
+    );
+    assert_eq!(root.push(&packet(2, 7, 2, 1, 1, b"b")), Ok(None));
+    assert!(phy.take_transmitted_frames().is_empty());
+}
+
+#[test]
+fn ignored() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let mut node = Mesh::new(NODE, ROUTING);
+
+    assert_eq!(node.push(b"hello"), Ok(None));
+    assert_eq!(node.push(&[DISPATCH, 1, 7, 0, 1, 3]), Ok(None));
+    assert_eq!(node.push(&packet(3, 7, 1, 3, 0, b"")), Ok(None));
+    assert!(phy.take_transmitted_frames().is_empty());
+}
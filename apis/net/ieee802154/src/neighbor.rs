@@ -0,0 +1,251 @@
+//! Discovery of the devices within range, for mesh routing.
+//!
+//! Every device with a [`NeighborTable`] periodically broadcasts a 3-byte hello
+//! frame:
+//!
+//! | Offset | Size | Content                     |
+//! |--------|------|-----------------------------|
+//! | 0      | 1    | [`DISPATCH`]                |
+//! | 1      | 2    | Sender ID, in little-endian |
+//!
+//! and tracks the devices whose hellos it receives, along with the quality of
+//! their links. A neighbor leaves the table once no hello arrived from it for
+//! a while.
+
+use crate::{Config, Ieee802154};
+use core::marker::PhantomData;
+use libtock_alarm::{Alarm, Convert, Milliseconds, Ticks};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The first byte of hello frames, which tells them apart from other payloads.
+pub const DISPATCH: u8 = 0xF6;
+
+/// The length of hello frames.
+pub const HELLO_LEN: usize = 3;
+
+/// When a [`NeighborTable`] sends hellos, and forgets neighbors.
+#[derive(Clone, Copy)]
+pub struct Discovery {
+    /// The time between two hellos.
+    pub hello_interval: Milliseconds,
+    /// How long after its last hello a neighbor leaves the table. It should
+    /// span a few hello intervals, so that a lost hello does not matter.
+    pub expiry: Milliseconds,
+}
+
+impl Default for Discovery {
+    /// A hello per second, with neighbors leaving after 3.5 s.
+    fn default() -> Self {
+        Discovery {
+            hello_interval: Milliseconds(1000),
+            expiry: Milliseconds(3500),
+        }
+    }
+}
+
+/// The quality of the link over which a frame was received.
+///
+/// The kernel does not report it along with received frames, so it is up to
+/// the application to measure it, e.g. through a platform-specific driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LinkQuality {
+    /// Received signal strength, in dBm.
+    pub rssi: i8,
+    /// Link quality indicator, as defined by the radio.
+    pub lqi: u8,
+}
+
+/// A device within range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Neighbor {
+    pub id: u16,
+    /// The received signal strength of its hellos, in dBm, smoothed with an
+    /// exponential moving average.
+    pub rssi: i8,
+    /// The link quality indicator of its hellos, smoothed like `rssi`.
+    pub lqi: u8,
+    /// The alarm tick at which its last hello arrived.
+    pub last_seen: u32,
+}
+
+/// A change of the neighbors in a [`NeighborTable`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NeighborEvent {
+    /// A hello arrived from a device that was not a neighbor.
+    Joined(u16),
+    /// No hello arrived from the neighbor within the expiry.
+    Left(u16),
+}
+
+/// Tracks up to `N` neighbors. Hellos from further devices are ignored while
+/// the table is full.
+///
+/// The table is driven by the application: [`poll`](Self::poll) sends the
+/// hellos when due, and [`push`](Self::push) handles received payloads. Joins
+/// and leaves are queued for [`next_event`](Self::next_event); the oldest
+/// events are dropped if they are not taken in time.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ieee802154::{neighbor::Discovery, NeighborTable, RxRingBuffer, RxSingleBufferOperator};
+/// use libtock::LivenessGuard;
+///
+/// let mut buf = RxRingBuffer::<4>::new();
+/// let mut operator = RxSingleBufferOperator::new(&mut buf);
+/// let mut table = NeighborTable::<8>::new(0xdead, Discovery::default())?;
+/// loop {
+///     table.poll()?;
+///     let guard = LivenessGuard::new(table.next_hello_in()?)?;
+///     if let Ok(frame) = operator.receive_frame_guarded(&guard) {
+///         table.push(frame.payload(), measure_link_quality())?;
+///     }
+///     while let Some(event) = table.next_event() {
+///         update_routes(event);
+///     }
+/// }
+/// ```
+pub struct NeighborTable<S: Syscalls, const N: usize = 8, C: Config = DefaultConfig> {
+    node: u16,
+    hello_interval: u32,
+    expiry: u32,
+    // The tick at which the last hello was sent.
+    last_hello: Option<u32>,
+    neighbors: [Option<Neighbor>; N],
+    events: [NeighborEvent; EVENTS],
+    first_event: usize,
+    event_count: usize,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const N: usize, C: Config> NeighborTable<S, N, C> {
+    /// Creates a table sending hellos as `node`, which must be unique among
+    /// the devices within range, e.g. the short address.
+    pub fn new(node: u16, discovery: Discovery) -> Result<Self, ErrorCode> {
+        let frequency = Alarm::<S, C>::get_frequency()?;
+        Ok(NeighborTable {
+            node,
+            hello_interval: discovery.hello_interval.to_ticks(frequency).0,
+            expiry: discovery.expiry.to_ticks(frequency).0,
+            last_hello: None,
+            neighbors: [None; N],
+            events: [NeighborEvent::Joined(0); EVENTS],
+            first_event: 0,
+            event_count: 0,
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Sends a hello if the hello interval elapsed since the last one, and
+    /// removes the neighbors that expired. Must be called at least once per
+    /// hello interval.
+    pub fn poll(&mut self) -> Result<(), ErrorCode> {
+        let now = Alarm::<S, C>::get_ticks()?;
+        self.expire(now);
+        if self
+            .last_hello
+            .is_some_and(|last| now.wrapping_sub(last) < self.hello_interval)
+        {
+            return Ok(());
+        }
+        let [id0, id1] = self.node.to_le_bytes();
+        Ieee802154::<S, C>::transmit_frame(&[DISPATCH, id0, id1])?;
+        self.last_hello = Some(now);
+        Ok(())
+    }
+
+    /// Returns the time until the next hello is due, e.g. to wait for frames
+    /// until then.
+    pub fn next_hello_in(&self) -> Result<Ticks, ErrorCode> {
+        let Some(last) = self.last_hello else {
+            return Ok(Ticks(0));
+        };
+        let elapsed = Alarm::<S, C>::get_ticks()?.wrapping_sub(last);
+        Ok(Ticks(self.hello_interval.saturating_sub(elapsed)))
+    }
+
+    /// Handles a received payload, which arrived over a link of the given
+    /// quality. Returns whether it is a hello; other payloads are ignored.
+    pub fn push(&mut self, payload: &[u8], quality: LinkQuality) -> Result<bool, ErrorCode> {
+        let [DISPATCH, id0, id1] = *payload else {
+            return Ok(false);
+        };
+        let id = u16::from_le_bytes([id0, id1]);
+        if id == self.node {
+            return Ok(true);
+        }
+        let now = Alarm::<S, C>::get_ticks()?;
+        self.expire(now);
+        if let Some(neighbor) = self.neighbors.iter_mut().flatten().find(|n| n.id == id) {
+            neighbor.rssi = smooth(neighbor.rssi.into(), quality.rssi.into()) as i8;
+            neighbor.lqi = smooth(neighbor.lqi.into(), quality.lqi.into()) as u8;
+            neighbor.last_seen = now;
+        } else if let Some(free) = self.neighbors.iter_mut().find(|n| n.is_none()) {
+            *free = Some(Neighbor {
+                id,
+                rssi: quality.rssi,
+                lqi: quality.lqi,
+                last_seen: now,
+            });
+            self.queue(NeighborEvent::Joined(id));
+        }
+        Ok(true)
+    }
+
+    /// Takes the oldest join or leave that was not taken yet.
+    pub fn next_event(&mut self) -> Option<NeighborEvent> {
+        if self.event_count == 0 {
+            return None;
+        }
+        let event = self.events[self.first_event];
+        self.first_event = (self.first_event + 1) % EVENTS;
+        self.event_count -= 1;
+        Some(event)
+    }
+
+    /// Returns the current neighbors.
+    pub fn neighbors(&self) -> impl Iterator<Item = &Neighbor> {
+        self.neighbors.iter().flatten()
+    }
+
+    /// Returns the neighbor with the given ID.
+    pub fn get(&self, id: u16) -> Option<&Neighbor> {
+        self.neighbors().find(|neighbor| neighbor.id == id)
+    }
+
+    /// Returns the neighbor with the strongest signal.
+    pub fn best(&self) -> Option<&Neighbor> {
+        self.neighbors().max_by_key(|neighbor| neighbor.rssi)
+    }
+
+    fn expire(&mut self, now: u32) {
+        for index in 0..N {
+            if let Some(neighbor) = self.neighbors[index] {
+                if now.wrapping_sub(neighbor.last_seen) > self.expiry {
+                    self.neighbors[index] = None;
+                    self.queue(NeighborEvent::Left(neighbor.id));
+                }
+            }
+        }
+    }
+
+    fn queue(&mut self, event: NeighborEvent) {
+        if self.event_count == EVENTS {
+            self.first_event = (self.first_event + 1) % EVENTS;
+            self.event_count -= 1;
+        }
+        self.events[(self.first_event + self.event_count) % EVENTS] = event;
+        self.event_count += 1;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// The number of events kept for `next_event`.
+const EVENTS: usize = 8;
+
+// Moves `average` a quarter of the way towards `sample`, rounding to nearest.
+fn smooth(average: i16, sample: i16) -> i16 {
+    (3 * average + sample + 2).div_euclid(4)
+}
@@ -0,0 +1,117 @@
+use crate::neighbor::*;
+use libtock_alarm::Milliseconds;
+use libtock_unittest::fake;
+
+type NeighborTable<const N: usize = 8> = crate::NeighborTable<fake::Syscalls, N>;
+
+const NODE: u16 = 0x0102;
+
+// 100 and 350 ticks of the alarm set up by `setup`.
+const DISCOVERY: Discovery = Discovery {
+    hello_interval: Milliseconds(100),
+    expiry: Milliseconds(350),
+};
+
+fn hello(id: u16) -> [u8; HELLO_LEN] {
+    let [id0, id1] = id.to_le_bytes();
+    [DISPATCH, id0, id1]
+}
+
+fn quality(rssi: i8, lqi: u8) -> LinkQuality {
+    LinkQuality { rssi, lqi }
+}
+
+#[test]
+fn hellos() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut table = NeighborTable::<4>::new(NODE, DISCOVERY).unwrap();
+
+    assert_eq!(table.next_hello_in().unwrap().0, 0);
+    assert_eq!(table.poll(), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [hello(NODE)]);
+    alarm.advance_ticks(60);
+    assert_eq!(table.next_hello_in().unwrap().0, 40);
+    assert_eq!(table.poll(), Ok(()));
+    assert!(phy.take_transmitted_frames().is_empty());
+    alarm.advance_ticks(40);
+    assert_eq!(table.poll(), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [hello(NODE)]);
+}
+
+#[test]
+fn join_and_leave() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut table = NeighborTable::<4>::new(NODE, DISCOVERY).unwrap();
+
+    // Other payloads and our own hellos are not neighbors.
+    assert_eq!(table.push(b"hello", quality(-40, 200)), Ok(false));
+    assert_eq!(table.push(&hello(NODE), quality(-40, 200)), Ok(true));
+    assert_eq!(table.next_event(), None);
+
+    assert_eq!(table.push(&hello(7), quality(-60, 200)), Ok(true));
+    alarm.advance_ticks(200);
+    assert_eq!(table.push(&hello(8), quality(-50, 100)), Ok(true));
+    assert_eq!(table.push(&hello(7), quality(-80, 100)), Ok(true));
+    assert_eq!(table.next_event(), Some(NeighborEvent::Joined(7)));
+    assert_eq!(table.next_event(), Some(NeighborEvent::Joined(8)));
+    assert_eq!(table.next_event(), None);
+    // The link quality is smoothed.
+    assert_eq!(
+        table.get(7),
+        Some(&Neighbor {
+            id: 7,
+            rssi: -65,
+            lqi: 175,
+            last_seen: 200,
+        })
+    );
+    assert_eq!(table.best().map(|neighbor| neighbor.id), Some(8));
+
+    alarm.advance_ticks(351);
+    assert_eq!(table.poll(), Ok(()));
+    assert_eq!(table.next_event(), Some(NeighborEvent::Left(7)));
+    assert_eq!(table.next_event(), Some(NeighborEvent::Left(8)));
+    assert_eq!(table.neighbors().count(), 0);
+}
+
+#[test]
+fn full() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut table = NeighborTable::<2>::new(NODE, DISCOVERY).unwrap();
+
+    for id in 1..=3 {
+        assert_eq!(table.push(&hello(id), quality(-50, 100)), Ok(true));
+    }
+    let ids: Vec<u16> = table.neighbors().map(|neighbor| neighbor.id).collect();
+    assert_eq!(ids, [1, 2]);
+}
+
+#[test]
+fn events_overflow() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let mut table = NeighborTable::<16>::new(NODE, DISCOVERY).unwrap();
+
+    // Only the 8 latest events are kept.
+    for id in 1..=10 {
+        table.push(&hello(id), quality(-50, 100)).unwrap();
+    }
+    let events: Vec<_> = core::iter::from_fn(|| table.next_event()).collect();
+    let expected: Vec<_> = (3..=10).map(NeighborEvent::Joined).collect();
+    assert_eq!(events, expected);
+}
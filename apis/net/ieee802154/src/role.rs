@@ -0,0 +1,178 @@
+//! Presets of the radio settings for the role a device plays in a network.
+//!
+//! A [`Role`]'s [`Preset`] picks coherent values for the settings of this
+//! crate's protocols:
+//!
+//! | Role                      | Radio                | RX after TX | Retries          | Hellos |
+//! |---------------------------|----------------------|-------------|------------------|--------|
+//! | [`Role::SleepyEndDevice`] | On to transmit only  | 100 ms      | 2, from 50 ms    | 60 s   |
+//! | [`Role::AlwaysOnRouter`]  | Always on            | -           | 4, from 50 ms    | 1 s    |
+//!
+//! Sleepy end devices rely on always-on routers: a router hears the frames a
+//! sleepy device sends at any time, and replies within the device's RX-after-TX
+//! window, e.g. with data it held for the device.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::ieee802154::role::{DutyCycledRadio, Role};
+//! use libtock::ieee802154::{ReliableLink, RxRingBuffer, RxSingleBufferOperator};
+//!
+//! let preset = Role::SleepyEndDevice.preset();
+//! let radio = DutyCycledRadio::new(preset.duty_cycle)?;
+//!
+//! // Raw frames, and the replies received within the RX-after-TX window
+//! let mut buf = RxRingBuffer::<4>::new();
+//! let mut operator = RxSingleBufferOperator::new(&mut buf);
+//! radio.transmit(b"report", &mut operator)?;
+//! while let Some(frame) = operator.try_receive_frame() {
+//!     handle(frame.payload());
+//! }
+//!
+//! // Protocols waiting for their own replies
+//! let mut buf = RxRingBuffer::<4>::new();
+//! let mut link = ReliableLink::new(0xdead, &mut buf, preset.retransmission);
+//! radio.wake(|| link.send(b"alarm triggered"))??;
+//! ```
+
+use crate::arq::{Backoff, Retransmission};
+use crate::neighbor::Discovery;
+use crate::{Config, Frame, Ieee802154, RxBuffer, RxSingleBufferOperator, RxSubscribe};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_alarm::{Alarm, Milliseconds};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// The role of a device in a network.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// A battery-powered device, which keeps its radio off except to transmit
+    /// and to receive the replies.
+    SleepyEndDevice,
+    /// A mains-powered device, which keeps its radio on, and relays frames for
+    /// its neighbors.
+    AlwaysOnRouter,
+}
+
+impl Role {
+    pub fn preset(self) -> Preset {
+        match self {
+            Role::SleepyEndDevice => Preset {
+                duty_cycle: DutyCycle::Sleepy {
+                    rx_after_tx: Milliseconds(100),
+                },
+                // Every retry keeps the radio on, so give up early.
+                retransmission: Retransmission {
+                    retries: 2,
+                    ack_timeout: Milliseconds(50),
+                    backoff: Backoff::Exponential,
+                },
+                // Hellos let routers know the device is there; the device
+                // hears few of theirs, so it forgets neighbors late.
+                discovery: Discovery {
+                    hello_interval: Milliseconds(60_000),
+                    expiry: Milliseconds(210_000),
+                },
+            },
+            Role::AlwaysOnRouter => Preset {
+                duty_cycle: DutyCycle::AlwaysOn,
+                // Frames relayed for others are worth a few more retries.
+                retransmission: Retransmission {
+                    retries: 4,
+                    ..Retransmission::default()
+                },
+                discovery: Discovery::default(),
+            },
+        }
+    }
+}
+
+/// The settings of a [`Role`].
+#[derive(Clone, Copy)]
+pub struct Preset {
+    /// For a [`DutyCycledRadio`].
+    pub duty_cycle: DutyCycle,
+    /// For a `ReliableLink`.
+    pub retransmission: Retransmission,
+    /// For a `NeighborTable`.
+    pub discovery: Discovery,
+}
+
+impl From<Role> for Preset {
+    fn from(role: Role) -> Preset {
+        role.preset()
+    }
+}
+
+/// When the radio is on.
+#[derive(Clone, Copy)]
+pub enum DutyCycle {
+    AlwaysOn,
+    /// The radio is off, except while transmitting and for `rx_after_tx`
+    /// after each transmission, so that replies can arrive.
+    Sleepy {
+        rx_after_tx: Milliseconds,
+    },
+}
+
+/// Turns the radio on and off following a [`DutyCycle`].
+///
+/// The RX-after-TX window uses the alarm, so a sleepy radio cannot transmit
+/// while the app uses the alarm otherwise.
+pub struct DutyCycledRadio<S: Syscalls, C: Config = DefaultConfig> {
+    duty_cycle: DutyCycle,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: Config> DutyCycledRadio<S, C> {
+    /// Turns the radio on or off, as `duty_cycle` wants it between
+    /// transmissions.
+    pub fn new(duty_cycle: DutyCycle) -> Result<Self, ErrorCode> {
+        match duty_cycle {
+            DutyCycle::AlwaysOn => Ieee802154::<S, C>::radio_on()?,
+            DutyCycle::Sleepy { .. } => Ieee802154::<S, C>::radio_off()?,
+        }
+        Ok(DutyCycledRadio {
+            duty_cycle,
+            _syscalls: PhantomData,
+        })
+    }
+
+    pub fn duty_cycle(&self) -> DutyCycle {
+        self.duty_cycle
+    }
+
+    /// Transmits `frame`. A sleepy radio then stays on for the RX-after-TX
+    /// window, and the frames received in the meantime are stored in
+    /// `operator`'s buffer, for its `try_receive_frame`.
+    pub fn transmit<const N: usize, F: FnMut(&Frame) -> bool>(
+        &self,
+        frame: &[u8],
+        operator: &mut RxSingleBufferOperator<'_, N, S, C, F>,
+    ) -> Result<(), ErrorCode> {
+        let DutyCycle::Sleepy { rx_after_tx } = self.duty_cycle else {
+            return Ieee802154::<S, C>::transmit_frame(frame);
+        };
+        let received = Cell::new(None);
+        self.wake(|| {
+            share::scope::<(RxBuffer<S>, RxSubscribe<S>), _, _>(|handle| {
+                let (allow_rw, subscribe) = handle.split();
+                operator.receive_start(&received, allow_rw, subscribe)?;
+                Ieee802154::<S, C>::transmit_frame(frame)?;
+                Alarm::<S, C>::sleep_for(rx_after_tx)
+            })
+        })?
+    }
+
+    /// Runs `f` with the radio on, e.g. to send with a `ReliableLink`, which
+    /// waits for its own replies. A sleepy radio is turned off again after
+    /// `f` returns, without an RX-after-TX window.
+    pub fn wake<R, F: FnOnce() -> R>(&self, f: F) -> Result<R, ErrorCode> {
+        if let DutyCycle::AlwaysOn = self.duty_cycle {
+            return Ok(f());
+        }
+        Ieee802154::<S, C>::radio_on()?;
+        let output = f();
+        Ieee802154::<S, C>::radio_off()?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::role::*;
+use crate::{RxRingBuffer, RxSingleBufferOperator};
+use libtock_alarm::Milliseconds;
+use libtock_unittest::fake::{self, ieee802154::Frame as FakeFrame};
+
+type DutyCycledRadio = crate::role::DutyCycledRadio<fake::Syscalls>;
+type Ieee802154 = crate::Ieee802154<fake::Syscalls>;
+
+#[test]
+fn presets() {
+    let sleepy = Preset::from(Role::SleepyEndDevice);
+    assert!(matches!(
+        sleepy.duty_cycle,
+        DutyCycle::Sleepy {
+            rx_after_tx: Milliseconds(100)
+        }
+    ));
+    let router = Role::AlwaysOnRouter.preset();
+    assert!(matches!(router.duty_cycle, DutyCycle::AlwaysOn));
+    // Routers hear hellos more often, and retry more.
+    assert!(router.discovery.hello_interval.0 < sleepy.discovery.hello_interval.0);
+    assert!(router.retransmission.retries > sleepy.retransmission.retries);
+}
+
+#[test]
+fn sleepy() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    Ieee802154::radio_on().unwrap();
+    let radio = DutyCycledRadio::new(Role::SleepyEndDevice.preset().duty_cycle).unwrap();
+    assert!(!Ieee802154::is_on());
+    assert_eq!(radio.wake(Ieee802154::is_on), Ok(true));
+    assert!(!Ieee802154::is_on());
+
+    // A reply arrives within the window, which then closes.
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        let mut replied = false;
+        move || match replied {
+            false => {
+                phy.radio_receive_frame(FakeFrame::with_body(b"reply"));
+                phy.driver_receive_pending_frames();
+                phy.trigger_rx_upcall();
+                replied = true;
+            }
+            true => alarm.advance_ticks(alarm.expiration().unwrap() - alarm.ticks()),
+        }
+    });
+    let mut buf = RxRingBuffer::<2>::new();
+    let mut operator = RxSingleBufferOperator::new(&mut buf);
+    assert_eq!(radio.transmit(b"report", &mut operator), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [b"report"]);
+    assert!(!Ieee802154::is_on());
+    assert_eq!(
+        operator
+            .try_receive_frame()
+            .map(|frame| frame.payload().to_vec()),
+        Some(b"reply".to_vec())
+    );
+    assert!(operator.try_receive_frame().is_none());
+}
+
+#[test]
+fn always_on() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&phy);
+    kernel.add_driver(&alarm);
+    let radio = DutyCycledRadio::new(Role::AlwaysOnRouter.preset().duty_cycle).unwrap();
+    assert!(Ieee802154::is_on());
+
+    let mut buf = RxRingBuffer::<2>::new();
+    let mut operator = RxSingleBufferOperator::new(&mut buf);
+    assert_eq!(radio.transmit(b"relay", &mut operator), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [b"relay"]);
+    // No RX-after-TX window.
+    assert_eq!(alarm.expiration(), None);
+    assert_eq!(radio.wake(|| 5), Ok(5));
+    assert!(Ieee802154::is_on());
+}
@@ -1,4 +1,5 @@
 use core::marker::PhantomData;
+use libtock_alarm::LivenessGuard;
 
 use super::*;
 
@@ -14,6 +15,17 @@ pub struct Frame {
     pub body: [u8; MAX_MTU],
 }
 
+impl Frame {
+    /// Returns the frame's payload, which follows its `header_len` bytes of
+    /// header.
+    pub fn payload(&self) -> &[u8] {
+        let start = self.header_len as usize;
+        self.body
+            .get(start..start + self.payload_len as usize)
+            .unwrap_or(&[])
+    }
+}
+
 const EMPTY_FRAME: Frame = Frame {
     header_len: 0,
     payload_len: 0,
@@ -31,6 +43,9 @@ const EMPTY_FRAME: Frame = Frame {
 /// new ones. Remember to specify `N` as `F + 1`, where `F` is the maximum expected number
 /// of frames received in short succession.
 ///
+/// The kernel stores the indices in a byte each, so `N` ranges from 2 to 256 frames;
+/// other values fail to compile when the buffer is created.
+///
 /// Given the non-deterministic nature of upcalls, the userprocess must carefully
 /// handle receiving upcalls. There exists a risk of dropping 15.4 packets while
 /// reading from the ring buffer (as the ring buffer is unallowed while reading).
@@ -39,8 +54,11 @@ const EMPTY_FRAME: Frame = Frame {
 /// failed on Miri level - we couldn't find a sound way to achieve that.
 /// Alternatively, the user can also utilize a single ring buffer if dropped frames may be permissible.
 /// This is done by [RxSingleBufferOperator].
+///
+/// The buffer is word-aligned, like `libtock_platform::AlignedBuf<_, 4>`, for radios that
+/// receive frames into it by DMA. A static buffer may be placed in the `.dma` linker section.
 #[derive(Debug)]
-#[repr(C)]
+#[repr(C, align(4))]
 pub struct RxRingBuffer<const N: usize> {
     /// From where the next frame will be read by process.
     /// Updated by process only.
@@ -53,8 +71,16 @@ pub struct RxRingBuffer<const N: usize> {
 }
 
 impl<const N: usize> RxRingBuffer<N> {
+    // Evaluated, and therefore checked, for each `N` a buffer is created with.
+    const VALID_CAPACITY: () = assert!(
+        N >= 2 && N <= u8::MAX as usize + 1,
+        "RxRingBuffer holds 2 to 256 frames"
+    );
+
     /// Creates a new [RxRingBuffer] that can be used to receive frames into.
     pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_CAPACITY;
         Self {
             read_index: 0,
             write_index: 0,
@@ -63,24 +89,27 @@ impl<const N: usize> RxRingBuffer<N> {
     }
 
     fn as_mut_byte_slice(&mut self) -> &mut [u8] {
+        // The kernel expects exactly the indices and the frames, without
+        // the padding that the alignment adds at the end.
+        let len = core::mem::offset_of!(Self, frames) + core::mem::size_of::<[Frame; N]>();
         // SAFETY: any byte value is valid for any byte of Self,
         // as well as for any byte of [u8], so casts back and forth
-        // cannot break the type system.
-        unsafe {
-            core::slice::from_raw_parts_mut(
-                self as *mut Self as *mut u8,
-                core::mem::size_of::<Self>(),
-            )
-        }
+        // cannot break the type system. `len` is at most the size of Self.
+        unsafe { core::slice::from_raw_parts_mut(self as *mut Self as *mut u8, len) }
     }
 
     fn has_frame(&self) -> bool {
         self.read_index != self.write_index
     }
 
+    fn peek_frame(&self) -> &Frame {
+        &self.frames[self.read_index as usize]
+    }
+
     fn next_frame(&mut self) -> &mut Frame {
         let frame = self.frames.get_mut(self.read_index as usize).unwrap();
-        self.read_index = (self.read_index + 1) % N as u8;
+        // Computed in usize, as both `read_index + 1` and `N` may not fit in u8.
+        self.read_index = ((self.read_index as usize + 1) % N) as u8;
         frame
     }
 }
@@ -100,8 +129,19 @@ pub trait RxOperator {
 /// the app is examining its received frames (and hence has its buffer unallowed),
 /// then the frame can be lost. Unfortunately, no alternative at the moment due to
 /// soundness issues in tried implementation.
-pub struct RxSingleBufferOperator<'buf, const N: usize, S: Syscalls, C: Config = DefaultConfig> {
+///
+/// Frames can be filtered with [`with_filter`](Self::with_filter), e.g. to
+/// skip the traffic of other applications sharing the channel.
+pub struct RxSingleBufferOperator<
+    'buf,
+    const N: usize,
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    F = fn(&Frame) -> bool,
+> {
     buf: &'buf mut RxRingBuffer<N>,
+    filter: F,
+    filtered: u32,
     s: PhantomData<S>,
     c: PhantomData<C>,
 }
@@ -111,33 +151,146 @@ impl<'buf, const N: usize, S: Syscalls, C: Config> RxSingleBufferOperator<'buf,
     pub fn new(buf: &'buf mut RxRingBuffer<N>) -> Self {
         Self {
             buf,
+            filter: |_| true,
+            filtered: 0,
             s: PhantomData,
             c: PhantomData,
         }
     }
 }
-impl<'buf, const N: usize, S: Syscalls, C: Config> RxOperator
-    for RxSingleBufferOperator<'buf, N, S, C>
+
+impl<'buf, const N: usize, S: Syscalls, C: Config, F: FnMut(&Frame) -> bool>
+    RxSingleBufferOperator<'buf, N, S, C, F>
 {
-    fn receive_frame(&mut self) -> Result<&mut Frame, ErrorCode> {
-        if self.buf.has_frame() {
-            Ok(self.buf.next_frame())
-        } else {
-            // If no frame is there, wait until one comes, then return it.
+    /// Replaces the filter of the operator with `filter`. Frames for which
+    /// `filter` returns `false` are discarded instead of being returned, and
+    /// counted by [`filtered`](Self::filtered).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut operator = RxSingleBufferOperator::new(&mut buf)
+    ///     .with_filter(|frame: &Frame| frame.payload().starts_with(MAGIC));
+    /// ```
+    pub fn with_filter<G: FnMut(&Frame) -> bool>(
+        self,
+        filter: G,
+    ) -> RxSingleBufferOperator<'buf, N, S, C, G> {
+        RxSingleBufferOperator {
+            buf: self.buf,
+            filter,
+            filtered: self.filtered,
+            s: PhantomData,
+            c: PhantomData,
+        }
+    }
+
+    /// Returns the number of frames the filter has discarded, wrapping around.
+    pub fn filtered(&self) -> u32 {
+        self.filtered
+    }
 
-            Ieee802154::<S, C>::receive_frame_single_buf(self.buf)?;
+    // Discards the received frames that the filter rejects, up to the first one
+    // it accepts. Returns whether there is such a frame.
+    fn filter_frames(&mut self) -> bool {
+        while self.buf.has_frame() {
+            if (self.filter)(self.buf.peek_frame()) {
+                return true;
+            }
+            self.buf.next_frame();
+            self.filtered = self.filtered.wrapping_add(1);
+        }
+        false
+    }
+}
 
+impl<'buf, const N: usize, S: Syscalls, C: Config, F: FnMut(&Frame) -> bool> RxOperator
+    for RxSingleBufferOperator<'buf, N, S, C, F>
+{
+    fn receive_frame(&mut self) -> Result<&mut Frame, ErrorCode> {
+        // If no frame is there, wait until one comes, then return it.
+        while !self.filter_frames() {
             // Safety: kernel schedules an upcall iff a new frame becomes available,
             // i.e. when it increments `read_index`.
-            Ok(self.buf.next_frame())
+            Ieee802154::<S, C>::receive_frame_single_buf(self.buf, S::yield_until)?;
+        }
+        Ok(self.buf.next_frame())
+    }
+}
+
+impl<'buf, const N: usize, S: Syscalls, C: Config, F: FnMut(&Frame) -> bool>
+    RxSingleBufferOperator<'buf, N, S, C, F>
+{
+    /// Receives one new frame like [RxOperator::receive_frame], but returns
+    /// `ErrorCode::Busy` if no frame arrives within the guard's maximum wait.
+    /// Each frame the filter discards restarts the wait.
+    #[cfg(feature = "liveness")]
+    pub fn receive_frame_guarded(
+        &mut self,
+        guard: &LivenessGuard<S, C>,
+    ) -> Result<&mut Frame, ErrorCode> {
+        self.receive_frame_within(guard)
+    }
+
+    // Like `receive_frame_guarded`, for the protocols of this crate.
+    pub(crate) fn receive_frame_within(
+        &mut self,
+        guard: &LivenessGuard<S, C>,
+    ) -> Result<&mut Frame, ErrorCode> {
+        while !self.filter_frames() {
+            Ieee802154::<S, C>::receive_frame_single_buf(self.buf, |done| guard.wait(done))?;
         }
+        Ok(self.buf.next_frame())
+    }
+
+    /// Returns the next received frame, or `None` if there is none, without
+    /// waiting.
+    pub fn try_receive_frame(&mut self) -> Option<&mut Frame> {
+        match self.filter_frames() {
+            true => Some(self.buf.next_frame()),
+            false => None,
+        }
+    }
+
+    /// Shares the ring buffer with the kernel in the background, to wait for
+    /// frames along with other events. The kernel stores frames in the buffer
+    /// until the end of the handles' scope, and reports them into `received`,
+    /// e.g. for `libtock_future::wait_for_upcall`. Take them with
+    /// [`try_receive_frame`](Self::try_receive_frame) after the scope.
+    pub fn receive_start<'share>(
+        &'share mut self,
+        received: &'share Cell<Option<(u32,)>>,
+        allow_rw: share::Handle<RxBuffer<'share, S>>,
+        subscribe: share::Handle<RxSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode> {
+        S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, self.buf.as_mut_byte_slice())?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::FRAME_RECEIVED }>(subscribe, received)
     }
 }
 
+/// The ring buffer shared by [`RxSingleBufferOperator::receive_start`].
+pub type RxBuffer<'share, S> = AllowRw<'share, S, DRIVER_NUM, { allow_rw::READ }>;
+
+/// The subscription of [`RxSingleBufferOperator::receive_start`].
+pub type RxSubscribe<'share, S> = Subscribe<'share, S, DRIVER_NUM, { subscribe::FRAME_RECEIVED }>;
+
 // Reception
 impl<S: Syscalls, C: Config> Ieee802154<S, C> {
-    fn receive_frame_single_buf<const N: usize>(
+    /// Stops storing received frames before the end of the scope of
+    /// [`RxSingleBufferOperator::receive_start`]'s handles, e.g. to switch
+    /// modes within it: unregisters the reception upcall, then revokes the
+    /// kernel's access to the ring buffer. The frames stored so far stay in
+    /// the buffer. The radio stays on.
+    pub fn stop_receive() {
+        S::unsubscribe(DRIVER_NUM, subscribe::FRAME_RECEIVED);
+        S::unallow_rw(DRIVER_NUM, allow_rw::READ);
+    }
+
+    fn receive_frame_single_buf<
+        const N: usize,
+        W: FnOnce(&mut dyn FnMut() -> bool) -> Result<(), ErrorCode>,
+    >(
         buf: &mut RxRingBuffer<N>,
+        wait: W,
     ) -> Result<(), ErrorCode> {
         let called: Cell<Option<(u32,)>> = Cell::new(None);
         share::scope::<
@@ -152,13 +305,8 @@ impl<S: Syscalls, C: Config> Ieee802154<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { allow_rw::READ }>(allow_rw, buf.as_mut_byte_slice())?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::FRAME_RECEIVED }>(subscribe, &called)?;
 
-            loop {
-                S::yield_wait();
-                if let Some((_lqi,)) = called.get() {
-                    // At least one frame was received.
-                    return Ok(());
-                }
-            }
+            // An upcall means at least one frame was received.
+            wait(&mut || called.get().is_some())
         })
     }
 }
@@ -0,0 +1,222 @@
+//! A compact binary format for sensor telemetry sent over the radio.
+//!
+//! A [`Record`] holds the readings of a device at one point in time, as typed
+//! channels. It is encoded as follows, with integers in little-endian:
+//!
+//! | Offset  | Size | Content                                     |
+//! |---------|------|---------------------------------------------|
+//! | 0       | 1    | Format version ([`VERSION`])                |
+//! | 1       | 2    | Device ID                                   |
+//! | 3       | 4    | Timestamp                                   |
+//! | 7       | 1    | Number of channels, n                       |
+//! | 8       | 5·n  | Channels: kind (1 byte) and value (4 bytes) |
+//! | 8 + 5·n | 2    | CRC-16/CCITT-FALSE of the preceding bytes   |
+//!
+//! An encoded record is at most [`MAX_RECORD_LEN`] bytes, which fits in the
+//! payload of one 802.15.4 frame.
+
+/// The version of the format produced by the encoder.
+pub const VERSION: u8 = 1;
+
+/// The maximum number of channels of a record.
+pub const MAX_CHANNELS: usize = 18;
+
+/// The maximum length of an encoded record, which is within
+/// [`MAX_PAYLOAD_LEN`](crate::MAX_PAYLOAD_LEN).
+pub const MAX_RECORD_LEN: usize = HEADER_LEN + MAX_CHANNELS * CHANNEL_LEN + CRC_LEN;
+
+/// The quantity a channel measures, which determines the unit of its value.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChannelKind {
+    /// In hundredths of a degree Celsius, as the temperature driver reports.
+    Temperature = 1,
+    /// Relative humidity, in hundredths of a percent.
+    Humidity = 2,
+    /// In pascals.
+    Pressure = 3,
+    /// In lux.
+    Light = 4,
+    /// In millivolts.
+    Voltage = 5,
+    /// CO2 concentration, in parts per million.
+    Co2 = 6,
+    /// A unitless count, e.g. of events or of records sent.
+    Counter = 7,
+}
+
+impl TryFrom<u8> for ChannelKind {
+    type Error = TelemetryError;
+
+    fn try_from(kind: u8) -> Result<ChannelKind, TelemetryError> {
+        Ok(match kind {
+            1 => ChannelKind::Temperature,
+            2 => ChannelKind::Humidity,
+            3 => ChannelKind::Pressure,
+            4 => ChannelKind::Light,
+            5 => ChannelKind::Voltage,
+            6 => ChannelKind::Co2,
+            7 => ChannelKind::Counter,
+            _ => return Err(TelemetryError::Channel),
+        })
+    }
+}
+
+/// A reading of a record.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Channel {
+    pub kind: ChannelKind,
+    pub value: i32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TelemetryError {
+    /// The record already has `MAX_CHANNELS` channels.
+    Full,
+    /// The buffer is too small for the encoded record.
+    BufferTooSmall,
+    /// The encoded record is shorter or longer than its channel count implies.
+    Length,
+    /// The encoded record has another format version.
+    Version,
+    /// The encoded record's CRC does not match its content.
+    Crc,
+    /// The encoded record has a channel of an unknown kind.
+    Channel,
+}
+
+/// The readings of a device at one point in time.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ieee802154::telemetry::{Channel, ChannelKind, Record, MAX_RECORD_LEN};
+///
+/// let mut record = Record::new(0xdead, Alarm::get_milliseconds()? as u32);
+/// record.push(Channel { kind: ChannelKind::Temperature, value: 2150 })?;
+/// let mut buf = [0; MAX_RECORD_LEN];
+/// let len = record.encode(&mut buf)?;
+/// Ieee802154::transmit_frame(&buf[..len])?;
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Record {
+    pub device_id: u16,
+    /// The time of the readings, in a unit of the application's choice
+    /// (typically milliseconds since boot).
+    pub timestamp: u32,
+    channels: [Channel; MAX_CHANNELS],
+    len: usize,
+}
+
+impl Record {
+    /// Creates a record without channels.
+    pub const fn new(device_id: u16, timestamp: u32) -> Record {
+        Record {
+            device_id,
+            timestamp,
+            channels: [EMPTY_CHANNEL; MAX_CHANNELS],
+            len: 0,
+        }
+    }
+
+    /// Returns the channels, in the order they were added.
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels[..self.len]
+    }
+
+    /// Returns the value of the first channel of kind `kind`, if any.
+    pub fn value(&self, kind: ChannelKind) -> Option<i32> {
+        self.channels()
+            .iter()
+            .find(|channel| channel.kind == kind)
+            .map(|channel| channel.value)
+    }
+
+    /// Adds a channel.
+    pub fn push(&mut self, channel: Channel) -> Result<(), TelemetryError> {
+        let slot = self
+            .channels
+            .get_mut(self.len)
+            .ok_or(TelemetryError::Full)?;
+        *slot = channel;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the length of the encoded record.
+    pub fn encoded_len(&self) -> usize {
+        HEADER_LEN + self.len * CHANNEL_LEN + CRC_LEN
+    }
+
+    /// Encodes the record into `buf`, and returns the length of the encoded
+    /// record.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, TelemetryError> {
+        let len = self.encoded_len();
+        let buf = buf.get_mut(..len).ok_or(TelemetryError::BufferTooSmall)?;
+        buf[0] = VERSION;
+        buf[1..3].copy_from_slice(&self.device_id.to_le_bytes());
+        buf[3..7].copy_from_slice(&self.timestamp.to_le_bytes());
+        buf[7] = self.len as u8;
+        let channels = buf[HEADER_LEN..len - CRC_LEN].chunks_exact_mut(CHANNEL_LEN);
+        for (bytes, channel) in channels.zip(self.channels()) {
+            bytes[0] = channel.kind as u8;
+            bytes[1..].copy_from_slice(&channel.value.to_le_bytes());
+        }
+        let crc = crc16(&buf[..len - CRC_LEN]);
+        buf[len - CRC_LEN..].copy_from_slice(&crc.to_le_bytes());
+        Ok(len)
+    }
+
+    /// Decodes an encoded record, such as the payload of a received frame.
+    pub fn decode(bytes: &[u8]) -> Result<Record, TelemetryError> {
+        if bytes.len() < HEADER_LEN + CRC_LEN {
+            return Err(TelemetryError::Length);
+        }
+        let (content, crc) = bytes.split_at(bytes.len() - CRC_LEN);
+        if crc16(content).to_le_bytes() != crc {
+            return Err(TelemetryError::Crc);
+        }
+        if content[0] != VERSION {
+            return Err(TelemetryError::Version);
+        }
+        let count = content[7] as usize;
+        if count > MAX_CHANNELS || content.len() != HEADER_LEN + count * CHANNEL_LEN {
+            return Err(TelemetryError::Length);
+        }
+        let mut record = Record::new(
+            u16::from_le_bytes([content[1], content[2]]),
+            u32::from_le_bytes([content[3], content[4], content[5], content[6]]),
+        );
+        for bytes in content[HEADER_LEN..].chunks_exact(CHANNEL_LEN) {
+            record.push(Channel {
+                kind: bytes[0].try_into()?,
+                value: i32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+            })?;
+        }
+        Ok(record)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 8;
+const CHANNEL_LEN: usize = 5;
+const CRC_LEN: usize = 2;
+
+const EMPTY_CHANNEL: Channel = Channel {
+    kind: ChannelKind::Counter,
+    value: 0,
+};
+
+// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF, no reflection.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
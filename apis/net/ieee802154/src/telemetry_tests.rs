@@ -0,0 +1,89 @@
+use crate::telemetry::*;
+
+fn record() -> Record {
+    let mut record = Record::new(0xdead, 10_000);
+    record
+        .push(Channel {
+            kind: ChannelKind::Temperature,
+            value: 2150,
+        })
+        .unwrap();
+    record
+        .push(Channel {
+            kind: ChannelKind::Counter,
+            value: -1,
+        })
+        .unwrap();
+    record
+}
+
+#[rustfmt::skip]
+const ENCODED: [u8; 20] = [
+    1, 0xad, 0xde, 0x10, 0x27, 0, 0, 2,
+    1, 0x66, 0x08, 0, 0,
+    7, 0xff, 0xff, 0xff, 0xff,
+    0x5e, 0xf5,
+];
+
+#[test]
+fn encode() {
+    let record = record();
+    assert_eq!(record.encoded_len(), ENCODED.len());
+    let mut buf = [0; MAX_RECORD_LEN];
+    assert_eq!(record.encode(&mut buf), Ok(ENCODED.len()));
+    assert_eq!(buf[..ENCODED.len()], ENCODED);
+    assert_eq!(
+        record.encode(&mut buf[..ENCODED.len() - 1]),
+        Err(TelemetryError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn decode() {
+    let record = Record::decode(&ENCODED).unwrap();
+    assert_eq!(record, self::record());
+    assert_eq!(record.device_id, 0xdead);
+    assert_eq!(record.timestamp, 10_000);
+    assert_eq!(record.value(ChannelKind::Temperature), Some(2150));
+    assert_eq!(record.value(ChannelKind::Counter), Some(-1));
+    assert_eq!(record.value(ChannelKind::Light), None);
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(Record::decode(&ENCODED[..9]), Err(TelemetryError::Length));
+    let mut corrupted = ENCODED;
+    corrupted[9] ^= 1;
+    assert_eq!(Record::decode(&corrupted), Err(TelemetryError::Crc));
+    // Records with a valid CRC, but invalid content.
+    assert_eq!(
+        Record::decode(&[2, 0, 0, 0, 0, 0, 0, 0, 0x98, 0xbe]),
+        Err(TelemetryError::Version)
+    );
+    assert_eq!(
+        Record::decode(&[1, 0, 0, 0, 0, 0, 0, 1, 9, 0, 0, 0, 0, 0xb3, 0x80]),
+        Err(TelemetryError::Channel)
+    );
+    // The channel count says 1, but there is no channel.
+    assert_eq!(
+        Record::decode(&[1, 0, 0, 0, 0, 0, 0, 1, 0xcc, 0x66]),
+        Err(TelemetryError::Length)
+    );
+}
+
+#[test]
+fn full() {
+    let mut record = Record::new(1, 2);
+    let channel = Channel {
+        kind: ChannelKind::Voltage,
+        value: 3300,
+    };
+    for _ in 0..MAX_CHANNELS {
+        record.push(channel).unwrap();
+    }
+    assert_eq!(record.push(channel), Err(TelemetryError::Full));
+    assert_eq!(record.encoded_len(), MAX_RECORD_LEN);
+    let mut buf = [0; MAX_RECORD_LEN];
+    let len = record.encode(&mut buf).unwrap();
+    assert_eq!(Record::decode(&buf[..len]), Ok(record));
+}
@@ -57,7 +57,7 @@ unsafe impl RawSyscalls for FakeSyscalls {
 
 use crate::{subscribe, DRIVER_NUM};
 
-use super::{RxOperator, RxRingBuffer};
+use super::{ConfigDiff, RadioConfig, RxOperator, RxRingBuffer};
 
 type Ieee802154 = super::Ieee802154<FakeSyscalls>;
 type RxSingleBufferOperator<'buf, const N: usize> =
@@ -103,6 +103,44 @@ fn configure() {
     assert_eq!(Ieee802154::get_address_long().unwrap(), addr_long);
     assert_eq!(Ieee802154::get_channel().unwrap(), channel);
     assert_eq!(Ieee802154::get_tx_power().unwrap(), tx_power);
+
+    // The largest values overflow u16 once 1 is added to them.
+    Ieee802154::set_pan(u16::MAX);
+    Ieee802154::set_address_short(u16::MAX);
+    Ieee802154::commit_config();
+    assert_eq!(Ieee802154::get_pan(), Ok(u16::MAX));
+    assert_eq!(Ieee802154::get_address_short(), Ok(u16::MAX));
+}
+
+#[test]
+fn verify_config() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ieee802154Phy::new();
+    kernel.add_driver(&driver);
+
+    let config = RadioConfig {
+        pan: 0xcafe,
+        address_short: 0xdead,
+        address_long: 0xdeaddad,
+        channel: 26,
+        tx_power: -4,
+    };
+    Ieee802154::configure(&config).unwrap();
+    assert_eq!(Ieee802154::read_config(), Ok(config));
+    assert!(Ieee802154::verify_config(&config).unwrap().is_empty());
+
+    // Changes show up once committed.
+    Ieee802154::set_channel(11).unwrap();
+    Ieee802154::set_pan(0xbeef);
+    Ieee802154::commit_config();
+    assert_eq!(
+        Ieee802154::verify_config(&config),
+        Ok(ConfigDiff {
+            pan: true,
+            channel: true,
+            ..ConfigDiff::default()
+        })
+    );
 }
 
 #[test]
@@ -119,6 +157,38 @@ fn transmit_frame() {
     );
 }
 
+#[test]
+fn transmit_frame_fut() {
+    use super::{TxBuffer, TxInfo, TxSubscribe};
+    use core::cell::Cell;
+    use libtock_future::block_on;
+    use libtock_platform::{share, ErrorCode};
+
+    let kernel = fake::Kernel::new();
+    let driver = fake::Ieee802154Phy::new();
+    kernel.add_driver(&driver);
+
+    let transmit = |frame: &[u8]| {
+        let transmitted = Cell::new(None);
+        share::scope::<(TxBuffer<_>, TxSubscribe<_>), _, _>(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            let transmission =
+                Ieee802154::transmit_frame_fut(frame, &transmitted, allow_ro, subscribe)?;
+            block_on::<FakeSyscalls, _>(transmission)
+        })
+    };
+    assert_eq!(transmit(b"foo"), Ok(TxInfo { acked: false }));
+    driver.set_transmit_result(Ok(true));
+    assert_eq!(transmit(b"bar"), Ok(TxInfo { acked: true }));
+    driver.set_transmit_result(Err(ErrorCode::NoAck));
+    assert_eq!(transmit(b"baz"), Err(ErrorCode::NoAck));
+    assert_eq!(Ieee802154::transmit_frame(b"baz"), Err(ErrorCode::NoAck));
+    assert_eq!(
+        driver.take_transmitted_frames(),
+        &[&b"foo"[..], &b"bar"[..]],
+    );
+}
+
 mod rx {
     use super::*;
     fn test_with_driver(test: impl FnOnce(&Ieee802154Phy)) {
@@ -201,6 +271,22 @@ mod rx {
         });
     }
 
+    #[test]
+    fn largest_buffer() {
+        test_with_driver(|driver| {
+            // More frames than the indices can count, so that they wrap
+            // around.
+            let mut buf = Box::new(RxRingBuffer::<256>::new());
+            let mut operator = RxSingleBufferOperator::new(&mut buf);
+            for i in 0..300u32 {
+                let body = i.to_le_bytes();
+                driver.radio_receive_frame(FakeFrame::with_body(&body));
+                let frame = operator.receive_frame().unwrap();
+                assert_eq!(&frame.body[..frame.payload_len as usize], &body);
+            }
+        });
+    }
+
     #[test]
     fn receive_many_frames() {
         test_with_driver(|driver| {
@@ -269,4 +355,119 @@ mod rx {
             );
         });
     }
+
+    #[test]
+    fn receive_start() {
+        use crate::{RxBuffer, RxSubscribe};
+        use core::cell::Cell;
+        use libtock_platform::{share, Syscalls};
+
+        test_with_driver(|driver| {
+            let mut buf = RxRingBuffer::<3>::new();
+            let mut operator = RxSingleBufferOperator::new(&mut buf);
+            assert!(operator.try_receive_frame().is_none());
+
+            driver.radio_receive_frame(FakeFrame::with_body(b"one"));
+            let received = Cell::new(None);
+            share::scope::<(RxBuffer<_>, RxSubscribe<_>), _, _>(|handle| {
+                let (allow_rw, subscribe) = handle.split();
+                operator
+                    .receive_start(&received, allow_rw, subscribe)
+                    .unwrap();
+                assert!(FakeSyscalls::yield_no_wait_flag());
+            });
+            assert_eq!(received.get(), Some((0,)));
+            assert_eq!(operator.try_receive_frame().unwrap().payload(), b"one");
+            assert!(operator.try_receive_frame().is_none());
+        });
+    }
+
+    #[test]
+    fn stop_receive() {
+        use crate::{RxBuffer, RxSubscribe};
+        use core::cell::Cell;
+        use libtock_platform::{share, Syscalls};
+
+        test_with_driver(|driver| {
+            let mut buf = RxRingBuffer::<3>::new();
+            let mut operator = RxSingleBufferOperator::new(&mut buf);
+
+            driver.radio_receive_frame(FakeFrame::with_body(b"one"));
+            let received = Cell::new(None);
+            share::scope::<(RxBuffer<_>, RxSubscribe<_>), _, _>(|handle| {
+                let (allow_rw, subscribe) = handle.split();
+                operator
+                    .receive_start(&received, allow_rw, subscribe)
+                    .unwrap();
+                Ieee802154::stop_receive();
+                // The buffer is revoked, and the upcall does not arrive.
+                assert!(!driver.has_pending_rx_frames());
+                assert!(!FakeSyscalls::yield_no_wait_flag());
+            });
+            assert_eq!(received.get(), None);
+            // The frame stored before stays in the buffer.
+            assert_eq!(operator.try_receive_frame().unwrap().payload(), b"one");
+        });
+    }
+
+    #[test]
+    fn filter() {
+        use crate::{Frame, RxBuffer, RxSubscribe};
+        use core::cell::Cell;
+        use libtock_platform::{share, Syscalls};
+
+        test_with_driver(|driver| {
+            let mut buf = RxRingBuffer::<4>::new();
+            let mut operator = RxSingleBufferOperator::new(&mut buf)
+                .with_filter(|frame: &Frame| frame.payload().starts_with(b"x"));
+
+            driver.radio_receive_frame(FakeFrame::with_body(b"xone"));
+            driver.radio_receive_frame(FakeFrame::with_body(b"two"));
+            driver.radio_receive_frame(FakeFrame::with_body(b"xthree"));
+            assert_eq!(operator.receive_frame().unwrap().payload(), b"xone");
+            assert_eq!(operator.receive_frame().unwrap().payload(), b"xthree");
+            assert_eq!(operator.filtered(), 1);
+
+            // The frames received in the background are filtered too.
+            driver.radio_receive_frame(FakeFrame::with_body(b"four"));
+            let received = Cell::new(None);
+            share::scope::<(RxBuffer<_>, RxSubscribe<_>), _, _>(|handle| {
+                let (allow_rw, subscribe) = handle.split();
+                operator
+                    .receive_start(&received, allow_rw, subscribe)
+                    .unwrap();
+                assert!(FakeSyscalls::yield_no_wait_flag());
+            });
+            assert!(operator.try_receive_frame().is_none());
+            assert_eq!(operator.filtered(), 2);
+        });
+    }
+
+    // Golden-trace test of the system calls the operator makes while receiving
+    // frames, including the (un)allowing of its buffer around each read. Set
+    // LIBTOCK_UPDATE_TRACES to update the trace after an intentional change.
+    // Miri's isolation prevents reading the trace file.
+    #[cfg(not(miri))]
+    #[test]
+    fn receive_frames_trace() {
+        let kernel = fake::Kernel::new();
+        let driver = fake::Ieee802154Phy::new();
+        kernel.add_driver(&driver);
+        kernel.start_trace();
+
+        test_with_single_buf_operator::<3>(&driver, |driver, operator| {
+            for frame in [&b"one"[..], b"two"] {
+                driver.radio_receive_frame(FakeFrame::with_body(frame));
+            }
+            for frame in [&b"one"[..], b"two"] {
+                let got_frame = operator.receive_frame().unwrap();
+                assert_eq!(&got_frame.body[..got_frame.payload_len as usize], frame);
+            }
+        });
+
+        kernel.take_trace().assert_matches_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/traces/receive_frames.trace"
+        ));
+    }
 }
@@ -0,0 +1,216 @@
+//! Prioritized transmission of 802.15.4 frames.
+//!
+//! The radio transmits one frame at a time. A [`TxQueue`] holds the frames
+//! waiting for it, and transmits them in the order of their [`Priority`], so
+//! that e.g. ACKs do not wait behind telemetry. Frames of the same priority are
+//! transmitted in the order they were pushed.
+//!
+//! Pushing only needs a shared reference, so several tasks awaited together
+//! (e.g. with `libtock_future::select`) push frames into the same queue, which
+//! [`TxQueue::run_until`] transmits while it drives them.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::future::{next, select};
+//! use libtock::ieee802154::{Priority, TxQueue};
+//!
+//! let queue = TxQueue::<4>::new();
+//! queue.run_until(select(
+//!     acks(&queue, next(&mut received)),
+//!     telemetry(&queue, next(&mut samples)),
+//! ));
+//! ```
+
+use crate::{Config, Ieee802154, TxBuffer, TxSubscribe, MAX_PAYLOAD_LEN};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::{block_on, select, Either, TockFuture};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// The priority of a queued frame. Higher priorities come first.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Priority {
+    High,
+    Normal,
+    Low,
+}
+
+/// A queue of up to `N` frames waiting for the radio.
+///
+/// When the queue is full, a frame evicts the most recently pushed frame of the
+/// lowest priority, if that priority is lower than its own, and is dropped
+/// otherwise. Frames the radio fails to transmit are dropped too. The dropped
+/// frames are counted per priority.
+pub struct TxQueue<S: Syscalls, const N: usize = 4, C: Config = DefaultConfig> {
+    // The priority and push order of the frame in each slot, if any.
+    order: [Cell<Option<(Priority, u32)>>; N],
+    frames: [Cell<QueuedFrame>; N],
+    pushed: Cell<u32>,
+    dropped: [Cell<u32>; PRIORITIES],
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const N: usize, C: Config> TxQueue<S, N, C> {
+    pub fn new() -> Self {
+        TxQueue {
+            order: core::array::from_fn(|_| Cell::new(None)),
+            frames: core::array::from_fn(|_| Cell::new(EMPTY_FRAME)),
+            pushed: Cell::new(0),
+            dropped: Default::default(),
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Queues `frame` for transmission. Fails with `ErrorCode::Size` if the
+    /// frame is longer than `MAX_PAYLOAD_LEN`, and with `ErrorCode::NoMem` if
+    /// the frame is dropped because the queue is full.
+    pub fn push(&self, priority: Priority, frame: &[u8]) -> Result<(), ErrorCode> {
+        if frame.len() > MAX_PAYLOAD_LEN {
+            return Err(ErrorCode::Size);
+        }
+        let slot = match self.order.iter().position(|slot| slot.get().is_none()) {
+            Some(slot) => slot,
+            None => {
+                let (victim, (victim_priority, _)) = self.last().unwrap_or((0, (priority, 0)));
+                if victim_priority <= priority {
+                    self.drop_frame(priority);
+                    return Err(ErrorCode::NoMem);
+                }
+                self.drop_frame(victim_priority);
+                victim
+            }
+        };
+        let mut bytes = [0; MAX_PAYLOAD_LEN];
+        bytes[..frame.len()].copy_from_slice(frame);
+        self.frames[slot].set((bytes, frame.len() as u8));
+        self.order[slot].set(Some((priority, self.pushed.get())));
+        self.pushed.set(self.pushed.get().wrapping_add(1));
+        Ok(())
+    }
+
+    /// Returns the number of queued frames.
+    pub fn len(&self) -> usize {
+        self.order
+            .iter()
+            .filter(|slot| slot.get().is_some())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of dropped frames of `priority`, including the
+    /// frames whose transmission failed.
+    pub fn dropped(&self, priority: Priority) -> u32 {
+        self.dropped[priority as usize].get()
+    }
+
+    /// Transmits the queued frames, one at a time, until `until` completes,
+    /// and returns its output. Frames pushed while `until` is polled are
+    /// transmitted too. A transmission in progress when `until` completes is
+    /// finished before returning.
+    pub fn run_until<F: TockFuture<S>>(&self, mut until: F) -> F::Output {
+        loop {
+            let Some((priority, (bytes, len))) = self.pop() else {
+                match block_on::<S, _>(select(&mut until, Pushed(self))) {
+                    Either::Left(output) => return output,
+                    Either::Right(()) => continue,
+                }
+            };
+            let transmitted = Cell::new(None);
+            let (output, sent) = share::scope::<(TxBuffer<S>, TxSubscribe<S>), _, _>(|handle| {
+                let (allow_ro, subscribe) = handle.split();
+                let mut transmission = match Ieee802154::<S, C>::transmit_frame_fut(
+                    &bytes[..len as usize],
+                    &transmitted,
+                    allow_ro,
+                    subscribe,
+                ) {
+                    Ok(transmission) => transmission,
+                    Err(_) => return (None, false),
+                };
+                match block_on::<S, _>(select(&mut until, &mut transmission)) {
+                    Either::Left(output) => {
+                        let sent = block_on::<S, _>(transmission).is_ok();
+                        (Some(output), sent)
+                    }
+                    Either::Right(result) => (None, result.is_ok()),
+                }
+            });
+            // Failed transmissions are not retried, and count as dropped
+            // frames.
+            if !sent {
+                self.drop_frame(priority);
+            }
+            if let Some(output) = output {
+                return output;
+            }
+        }
+    }
+}
+
+impl<S: Syscalls, const N: usize, C: Config> Default for TxQueue<S, N, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const PRIORITIES: usize = 3;
+
+// The bytes of a frame, and its length.
+type QueuedFrame = ([u8; MAX_PAYLOAD_LEN], u8);
+
+const EMPTY_FRAME: QueuedFrame = ([0; MAX_PAYLOAD_LEN], 0);
+
+impl<S: Syscalls, const N: usize, C: Config> TxQueue<S, N, C> {
+    // Returns the slot and order of the frame that is transmitted last.
+    fn last(&self) -> Option<(usize, (Priority, u32))> {
+        self.queued().max_by_key(|&(_, order)| self.key(order))
+    }
+
+    // Removes and returns the frame that is transmitted first, with its
+    // priority.
+    fn pop(&self) -> Option<(Priority, QueuedFrame)> {
+        let (slot, (priority, _)) = self.queued().min_by_key(|&(_, order)| self.key(order))?;
+        self.order[slot].set(None);
+        Some((priority, self.frames[slot].replace(EMPTY_FRAME)))
+    }
+
+    fn queued(&self) -> impl Iterator<Item = (usize, (Priority, u32))> + '_ {
+        self.order
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, order)| Some((slot, order.get()?)))
+    }
+
+    // The key frames are transmitted by: priority, then push order, which
+    // counts from the oldest frame so that it survives wrapping around.
+    fn key(&self, (priority, pushed): (Priority, u32)) -> (Priority, u32) {
+        (priority, pushed.wrapping_sub(self.pushed.get()))
+    }
+
+    fn drop_frame(&self, priority: Priority) {
+        let dropped = &self.dropped[priority as usize];
+        dropped.set(dropped.get().wrapping_add(1));
+    }
+}
+
+// Completes once a frame is queued.
+struct Pushed<'q, S: Syscalls, const N: usize, C: Config>(&'q TxQueue<S, N, C>);
+
+impl<S: Syscalls, const N: usize, C: Config> TockFuture<S> for Pushed<'_, S, N, C> {
+    type Output = ();
+
+    fn poll(&mut self) -> Poll<()> {
+        match self.0.is_empty() {
+            true => Poll::Pending,
+            false => Poll::Ready(()),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::tx_queue::*;
+use crate::MAX_PAYLOAD_LEN;
+use core::task::Poll;
+use libtock_future::{select, Either, TockFuture};
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type TxQueue = crate::TxQueue<fake::Syscalls, 3>;
+
+// Completes once the queue is empty.
+struct Drained<'q>(&'q TxQueue);
+
+impl TockFuture<fake::Syscalls> for Drained<'_> {
+    type Output = ();
+
+    fn poll(&mut self) -> Poll<()> {
+        match self.0.is_empty() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+// A task that pushes one frame per poll, and then completes with the number
+// of polls.
+struct Task<'q> {
+    queue: &'q TxQueue,
+    frames: Vec<(Priority, &'static [u8])>,
+    polls: usize,
+}
+
+impl TockFuture<fake::Syscalls> for Task<'_> {
+    type Output = usize;
+
+    fn poll(&mut self) -> Poll<usize> {
+        self.polls += 1;
+        if self.frames.is_empty() {
+            return Poll::Ready(self.polls);
+        }
+        let (priority, frame) = self.frames.remove(0);
+        self.queue.push(priority, frame).unwrap();
+        Poll::Pending
+    }
+}
+
+#[test]
+fn priorities() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let queue = TxQueue::new();
+
+    assert_eq!(queue.push(Priority::Low, b"t1"), Ok(()));
+    assert_eq!(queue.push(Priority::Normal, b"n"), Ok(()));
+    assert_eq!(queue.push(Priority::High, b"ack"), Ok(()));
+    assert_eq!(queue.len(), 3);
+
+    queue.run_until(Drained(&queue));
+    assert_eq!(phy.take_transmitted_frames(), [&b"ack"[..], b"n", b"t1"]);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn overflow() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let queue = TxQueue::new();
+
+    queue.push(Priority::Low, b"a").unwrap();
+    queue.push(Priority::Low, b"b").unwrap();
+    queue.push(Priority::Normal, b"c").unwrap();
+    // The most recent frame of the lowest priority is evicted.
+    assert_eq!(queue.push(Priority::High, b"d"), Ok(()));
+    assert_eq!(queue.dropped(Priority::Low), 1);
+    // Frames of the lowest priority are dropped.
+    assert_eq!(queue.push(Priority::Low, b"e"), Err(ErrorCode::NoMem));
+    assert_eq!(queue.dropped(Priority::Low), 2);
+    assert_eq!(queue.push(Priority::Normal, b"f"), Ok(()));
+    assert_eq!(queue.dropped(Priority::Low), 3);
+    assert_eq!(queue.dropped(Priority::Normal), 0);
+    assert_eq!(queue.dropped(Priority::High), 0);
+
+    queue.run_until(Drained(&queue));
+    assert_eq!(phy.take_transmitted_frames(), [&b"d"[..], b"c", b"f"]);
+
+    let too_long = [0; MAX_PAYLOAD_LEN + 1];
+    assert_eq!(queue.push(Priority::High, &too_long), Err(ErrorCode::Size));
+}
+
+#[test]
+fn failed_transmissions() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let queue = TxQueue::new();
+
+    // Frames that fail to transmit are dropped, not retried.
+    phy.set_transmit_result(Err(ErrorCode::NoAck));
+    queue.push(Priority::Normal, b"a").unwrap();
+    queue.push(Priority::Low, b"b").unwrap();
+    queue.run_until(Drained(&queue));
+    assert_eq!(queue.dropped(Priority::Normal), 1);
+    assert_eq!(queue.dropped(Priority::Low), 1);
+
+    // Including the one in progress when `until` completes.
+    queue.push(Priority::High, b"c").unwrap();
+    let task = Task {
+        queue: &queue,
+        frames: vec![],
+        polls: 0,
+    };
+    assert_eq!(queue.run_until(task), 1);
+    assert_eq!(queue.dropped(Priority::High), 1);
+    assert!(phy.take_transmitted_frames().is_empty());
+}
+
+#[test]
+fn tasks() {
+    let kernel = fake::Kernel::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&phy);
+    let queue = TxQueue::new();
+    let telemetry = Task {
+        queue: &queue,
+        frames: vec![(Priority::Low, b"t1"), (Priority::Low, b"t2")],
+        polls: 0,
+    };
+    let acks = Task {
+        queue: &queue,
+        frames: vec![(Priority::High, b"ack")],
+        polls: 0,
+    };
+
+    // The ACK pushed along with the first sample goes first. The frames left
+    // when the tasks complete stay queued.
+    assert_eq!(queue.run_until(select(telemetry, acks)), Either::Right(2));
+    assert_eq!(phy.take_transmitted_frames(), [b"ack"]);
+    assert_eq!(queue.len(), 2);
+
+    queue.run_until(Drained(&queue));
+    assert_eq!(phy.take_transmitted_frames(), [b"t1", b"t2"]);
+}
@@ -0,0 +1,72 @@
+//! Parsing of the ISO 7816-4 APDUs that Type 4 tags exchange with readers.
+//!
+//! The reader sends command APDUs, which [`Command::parse`] parses, and the
+//! tag answers each of them with a response APDU: the response data, if any,
+//! followed by a two-byte status word, such as those in [`status`]. Only short
+//! APDUs (with up to 255 bytes of data) are supported.
+
+/// The instruction byte of a SELECT command.
+pub const INS_SELECT: u8 = 0xA4;
+
+/// A command APDU.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Command<'a> {
+    pub cla: u8,
+    pub ins: u8,
+    pub p1: u8,
+    pub p2: u8,
+    pub data: &'a [u8],
+    /// The maximum length of the response data the reader expects, if it
+    /// expects any.
+    pub le: Option<u16>,
+}
+
+impl<'a> Command<'a> {
+    /// Parses a short command APDU. Returns `None` if `frame` is not one.
+    pub fn parse(frame: &'a [u8]) -> Option<Command<'a>> {
+        if frame.len() < 4 {
+            return None;
+        }
+        let (header, body) = frame.split_at(4);
+        let (data, le) = match body {
+            [] => (&body[..0], None),
+            [le] => (&body[..0], Some(*le)),
+            // Extended APDUs start the body with a 0.
+            [0, ..] => return None,
+            [lc, rest @ ..] if rest.len() == *lc as usize => (rest, None),
+            [lc, rest @ ..] if rest.len() == *lc as usize + 1 => {
+                let (data, le) = rest.split_at(*lc as usize);
+                (data, Some(le[0]))
+            }
+            _ => return None,
+        };
+        Some(Command {
+            cla: header[0],
+            ins: header[1],
+            p1: header[2],
+            p2: header[3],
+            data,
+            // An Le of 0 stands for 256.
+            le: le.map(|le| if le == 0 { 256 } else { le as u16 }),
+        })
+    }
+
+    /// Returns `true` if the command selects the application `aid` by name.
+    pub fn is_select(&self, aid: &[u8]) -> bool {
+        self.cla == 0 && self.ins == INS_SELECT && self.p1 == 0x04 && self.data == aid
+    }
+}
+
+/// Status words of response APDUs.
+pub mod status {
+    /// The command succeeded.
+    pub const OK: [u8; 2] = [0x90, 0x00];
+    /// The command's length is wrong.
+    pub const WRONG_LENGTH: [u8; 2] = [0x67, 0x00];
+    /// The command is not allowed in the tag's current state.
+    pub const CONDITIONS_NOT_SATISFIED: [u8; 2] = [0x69, 0x85];
+    /// The selected application or file does not exist.
+    pub const FILE_NOT_FOUND: [u8; 2] = [0x6A, 0x82];
+    /// The command's instruction is not supported.
+    pub const INS_NOT_SUPPORTED: [u8; 2] = [0x6D, 0x00];
+}
@@ -0,0 +1,56 @@
+use crate::apdu::*;
+
+#[test]
+fn parse() {
+    let command = |cla, ins, p1, p2, data, le| Command {
+        cla,
+        ins,
+        p1,
+        p2,
+        data,
+        le,
+    };
+    assert_eq!(
+        Command::parse(&[0x00, 0xB0, 0x00, 0x01]),
+        Some(command(0x00, 0xB0, 0x00, 0x01, &[], None))
+    );
+    assert_eq!(
+        Command::parse(&[0x00, 0xB0, 0x00, 0x01, 0x0F]),
+        Some(command(0x00, 0xB0, 0x00, 0x01, &[], Some(15)))
+    );
+    assert_eq!(
+        Command::parse(&[0x00, 0xB0, 0x00, 0x01, 0x00]),
+        Some(command(0x00, 0xB0, 0x00, 0x01, &[], Some(256)))
+    );
+    assert_eq!(
+        Command::parse(&[0x00, 0xD6, 0x00, 0x00, 0x02, 0xAB, 0xCD]),
+        Some(command(0x00, 0xD6, 0x00, 0x00, &[0xAB, 0xCD], None))
+    );
+    assert_eq!(
+        Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0xAB, 0xCD, 0x00]),
+        Some(command(0x00, 0xA4, 0x04, 0x00, &[0xAB, 0xCD], Some(256)))
+    );
+
+    assert_eq!(Command::parse(&[0x00, 0xB0, 0x00]), None);
+    assert_eq!(Command::parse(&[0x00, 0xD6, 0x00, 0x00, 0x03, 0xAB]), None);
+    assert_eq!(
+        Command::parse(&[0x00, 0xD6, 0x00, 0x00, 0x01, 0xAB, 0xCD, 0xEF]),
+        None
+    );
+    // Extended APDU
+    assert_eq!(
+        Command::parse(&[0x00, 0xD6, 0x00, 0x00, 0x00, 0x00, 0x01, 0xAB]),
+        None
+    );
+}
+
+#[test]
+fn is_select() {
+    let aid = [0xF0, 0x01, 0x02];
+    let select = Command::parse(&[0x00, 0xA4, 0x04, 0x00, 0x03, 0xF0, 0x01, 0x02, 0x00]).unwrap();
+    assert!(select.is_select(&aid));
+    assert!(!select.is_select(&aid[..2]));
+    // Selection by file identifier
+    let select = Command::parse(&[0x00, 0xA4, 0x00, 0x0C, 0x03, 0xF0, 0x01, 0x02]).unwrap();
+    assert!(!select.is_select(&aid));
+}
@@ -0,0 +1,213 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+pub mod apdu;
+
+/// The NFC tag emulation driver.
+///
+/// It makes the board act as an NFC tag, which a reader (e.g. a phone) selects
+/// when it comes in range, and then exchanges frames with: the reader sends a
+/// command frame, which the tag receives, and the tag transmits a response.
+/// With Type 4 tags, the frames are ISO 7816-4 APDUs, which the [`apdu`]
+/// module parses.
+///
+/// # Example
+/// ```ignore
+/// use libtock::nfc::{apdu, Nfc, TagType};
+///
+/// // Receives credentials written by a phone app that selects the
+/// // application F0 01 02 03 04 05.
+/// Nfc::set_tag_type(TagType::Type4)?;
+/// Nfc::enable_emulation()?;
+/// let mut buffer = [0; 256];
+/// Nfc::select_application(&[0xF0, 0x01, 0x02, 0x03, 0x04, 0x05], &mut buffer)?;
+/// let len = Nfc::receive(&mut buffer)?;
+/// let command = apdu::Command::parse(&buffer[..len]).ok_or(ErrorCode::Invalid)?;
+/// store_credentials(command.data);
+/// Nfc::transmit(&apdu::status::OK)?;
+/// ```
+pub struct Nfc<S: Syscalls, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, C: Config> Nfc<S, C> {
+    /// Run a check against the NFC capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Sets the type of tag to emulate. Must be called before emulation is
+    /// enabled.
+    pub fn set_tag_type(tag_type: TagType) -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::SET_TAG_TYPE, tag_type as u32, 0).to_result()
+    }
+
+    /// Starts emulating a tag, which readers in range can then select.
+    pub fn enable_emulation() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::ENABLE_EMULATION, 0, 0).to_result()
+    }
+
+    /// Stops emulating a tag.
+    pub fn disable_emulation() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::DISABLE_EMULATION, 0, 0).to_result()
+    }
+
+    /// Waits until a reader selects the tag.
+    pub fn wait_selected() -> Result<(), ErrorCode> {
+        let selected = Cell::new(false);
+        share::scope(|subscribe| {
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SELECTED }>(subscribe, &selected)?;
+            loop {
+                S::yield_wait();
+                if selected.get() {
+                    return Ok(());
+                }
+            }
+        })
+    }
+
+    /// Transmits `frame` to the reader, and waits until it is sent.
+    pub fn transmit(frame: &[u8]) -> Result<(), ErrorCode> {
+        let called: Cell<Option<Result<(), ErrorCode>>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, { allow_ro::TX }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::TRANSMITTED }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            S::allow_ro::<C, DRIVER_NUM, { allow_ro::TX }>(allow_ro, frame)?;
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::TRANSMITTED }>(subscribe, &called)?;
+            S::checked_command::<C>(DRIVER_NUM, command::TRANSMIT, frame.len() as u32, 0)
+                .to_result::<(), ErrorCode>()?;
+            loop {
+                S::yield_wait();
+                if let Some(result) = called.get() {
+                    return result;
+                }
+            }
+        })
+    }
+
+    /// Waits for a frame from the reader, and receives it into `buffer`.
+    /// Returns the length of the frame.
+    ///
+    /// Frames longer than `buffer` fail with `ErrorCode::Size`.
+    pub fn receive(buffer: &mut [u8]) -> Result<usize, ErrorCode> {
+        let called: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, { allow_rw::RX }>,
+                Subscribe<_, DRIVER_NUM, { subscribe::RECEIVED }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            let len = buffer.len();
+            S::allow_rw::<C, DRIVER_NUM, { allow_rw::RX }>(allow_rw, buffer)?;
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::RECEIVED }>(subscribe, &called)?;
+            S::checked_command::<C>(DRIVER_NUM, command::RECEIVE, len as u32, 0)
+                .to_result::<(), ErrorCode>()?;
+            loop {
+                S::yield_wait();
+                if let Some((status, len)) = called.get() {
+                    return match status {
+                        0 => Ok(len as usize),
+                        status => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+                    };
+                }
+            }
+        })
+    }
+
+    /// Handles the reader's commands until it selects the application `aid`
+    /// by name, which is acknowledged. Other SELECT commands are answered
+    /// with `FILE_NOT_FOUND`, and other commands with
+    /// `CONDITIONS_NOT_SATISFIED`, as the reader must select an application
+    /// first. `buffer` receives the commands, so must fit the longest command
+    /// the reader sends.
+    pub fn select_application(aid: &[u8], buffer: &mut [u8]) -> Result<(), ErrorCode> {
+        loop {
+            let len = Self::receive(buffer)?;
+            let response = match apdu::Command::parse(&buffer[..len]) {
+                Some(command) if command.is_select(aid) => {
+                    return Self::transmit(&apdu::status::OK);
+                }
+                Some(command) if command.ins == apdu::INS_SELECT => apdu::status::FILE_NOT_FOUND,
+                Some(_) => apdu::status::CONDITIONS_NOT_SATISFIED,
+                None => apdu::status::WRONG_LENGTH,
+            };
+            Self::transmit(&response)?;
+        }
+    }
+}
+
+/// The NFC Forum type of an emulated tag.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TagType {
+    Type2 = 2,
+    /// A tag exchanging ISO 7816-4 APDUs.
+    Type4 = 4,
+}
+
+/// System call configuration trait for `Nfc`.
+pub trait Config:
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
+{
+}
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod apdu_tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x30003;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const TRANSMIT: u32 = 1;
+    pub const RECEIVE: u32 = 2;
+    pub const ENABLE_EMULATION: u32 = 3;
+    pub const DISABLE_EMULATION: u32 = 4;
+    pub const SET_TAG_TYPE: u32 = 5;
+}
+
+mod subscribe {
+    pub const TRANSMITTED: u32 = 0;
+    pub const RECEIVED: u32 = 1;
+    pub const SELECTED: u32 = 2;
+}
+
+mod allow_ro {
+    pub const TX: u32 = 0;
+}
+
+mod allow_rw {
+    pub const RX: u32 = 0;
+}
@@ -0,0 +1,82 @@
+use super::*;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type Nfc = super::Nfc<fake::Syscalls>;
+
+const AID: [u8; 3] = [0xF0, 0x01, 0x02];
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Nfc::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn emulation() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Nfc::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Nfc::exists(), Ok(()));
+    assert_eq!(Nfc::transmit(&[1]), Err(ErrorCode::Off));
+    assert_eq!(Nfc::set_tag_type(TagType::Type4), Ok(()));
+    assert_eq!(driver.tag_type(), Some(4));
+    assert_eq!(Nfc::enable_emulation(), Ok(()));
+    assert!(driver.is_emulating());
+
+    // The reader comes in range while the app waits.
+    let reader = driver.clone();
+    kernel.set_idle_handler(move || reader.select());
+    assert_eq!(Nfc::wait_selected(), Ok(()));
+
+    assert_eq!(Nfc::disable_emulation(), Ok(()));
+    assert!(!driver.is_emulating());
+}
+
+#[test]
+fn frames() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Nfc::new();
+    kernel.add_driver(&driver);
+    Nfc::set_tag_type(TagType::Type4).unwrap();
+    Nfc::enable_emulation().unwrap();
+
+    let mut buffer = [0; 4];
+    driver.queue_frame(&[1, 2, 3]);
+    assert_eq!(Nfc::receive(&mut buffer), Ok(3));
+    assert_eq!(buffer, [1, 2, 3, 0]);
+    driver.queue_frame(&[1, 2, 3, 4, 5]);
+    assert_eq!(Nfc::receive(&mut buffer), Err(ErrorCode::Size));
+
+    assert_eq!(Nfc::transmit(&[4, 5]), Ok(()));
+    assert_eq!(Nfc::transmit(&apdu::status::OK), Ok(()));
+    assert_eq!(driver.take_transmitted(), [&[4, 5][..], &apdu::status::OK]);
+}
+
+#[test]
+fn select_application() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Nfc::new();
+    kernel.add_driver(&driver);
+    Nfc::set_tag_type(TagType::Type4).unwrap();
+    Nfc::enable_emulation().unwrap();
+
+    // Select another application, read before selecting, send a malformed
+    // command, and finally select the application.
+    driver.queue_frame(&[0x00, 0xA4, 0x04, 0x00, 0x02, 0xD2, 0x76, 0x00]);
+    driver.queue_frame(&[0x00, 0xB0, 0x00, 0x00, 0x0F]);
+    driver.queue_frame(&[0x00, 0xB0]);
+    driver.queue_frame(&[0x00, 0xA4, 0x04, 0x00, 0x03, 0xF0, 0x01, 0x02, 0x00]);
+    let mut buffer = [0; 16];
+    assert_eq!(Nfc::select_application(&AID, &mut buffer), Ok(()));
+    assert_eq!(
+        driver.take_transmitted(),
+        [
+            apdu::status::FILE_NOT_FOUND,
+            apdu::status::CONDITIONS_NOT_SATISFIED,
+            apdu::status::WRONG_LENGTH,
+            apdu::status::OK,
+        ]
+    );
+}
@@ -2,7 +2,8 @@
 
 use core::cell::Cell;
 use libtock_platform::{
-    share, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+    share, subscribe::OneId, Align, AlignedBuf, Alignment, AllowRw, DefaultConfig, ErrorCode,
+    Subscribe, Syscalls, Upcall,
 };
 
 pub struct Adc<S: Syscalls>(S);
@@ -62,6 +63,91 @@ impl<S: Syscalls> Adc<S> {
         })
     }
 
+    /// Samples `channel` at `frequency` Hz into `buf`, and returns the number
+    /// of samples, which are native-endian `u16`s. ADCs that sample by DMA
+    /// require `buf` to be aligned to at least 2 bytes, or this fails to build.
+    pub fn read_buffer_sync<const N: usize, const A: usize>(
+        channel: u32,
+        frequency: u32,
+        buf: &mut AlignedBuf<N, A>,
+    ) -> Result<usize, ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        let called: Cell<Option<(u32, u32, u32)>> = Cell::new(None);
+        share::scope::<(SampleBuffer<_>, SampleSubscribe<_>), _, _>(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            Self::share_buffer(buf, &called, allow_rw, subscribe)?;
+            Self::start_continuous(channel, frequency)?;
+
+            // Sampling continues into the buffer until it is stopped, so it is
+            // stopped once the buffer is full.
+            loop {
+                S::yield_wait();
+                if let Some((_, samples, _)) = called.get() {
+                    Self::stop_sampling()?;
+                    return Ok(samples as usize);
+                }
+            }
+        })
+    }
+
+    /// Shares `buf` as the buffer continuous sampling writes into, and
+    /// registers `done` to receive the upcall reporting that it is full:
+    /// `(mode, number of samples, buffer number)`. Sharing a buffer while
+    /// sampling runs makes the ADC continue into it; to sample a stream of
+    /// blocks, see `share_buffers`.
+    pub fn share_buffer<'share, const N: usize, const A: usize>(
+        buf: &'share mut AlignedBuf<N, A>,
+        done: &'share Cell<Option<(u32, u32, u32)>>,
+        allow_rw: share::Handle<SampleBuffer<'share, S>>,
+        subscribe: share::Handle<SampleSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        S::allow_rw::<DefaultConfig, DRIVER_NUM, BUFFER>(
+            allow_rw,
+            buf.aligned_to_mut::<SAMPLE_ALIGN>(),
+        )?;
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, done)
+    }
+
+    /// Shares `first` and `second` as the buffers continuous sampling writes
+    /// into in turn, and registers `done` to receive the upcall reporting that
+    /// one is full: `(mode, number of samples, buffer number)`, where the
+    /// buffer number is 0 for `first` and 1 for `second`. The ADC switches to
+    /// the other buffer as it reports one, so that no sample is lost while the
+    /// full one is processed.
+    pub fn share_buffers<'share, const N: usize, const A: usize>(
+        first: &'share mut AlignedBuf<N, A>,
+        second: &'share mut AlignedBuf<N, A>,
+        done: &'share Cell<Option<(u32, u32, u32)>>,
+        allow_first: share::Handle<SampleBuffer<'share, S>>,
+        allow_second: share::Handle<SecondSampleBuffer<'share, S>>,
+        subscribe: share::Handle<SampleSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        S::allow_rw::<DefaultConfig, DRIVER_NUM, SECOND_BUFFER>(
+            allow_second,
+            second.aligned_to_mut::<SAMPLE_ALIGN>(),
+        )?;
+        Self::share_buffer(first, done, allow_first, subscribe)
+    }
+
+    /// Starts sampling `channel` at `frequency` Hz into the buffer shared with
+    /// `share_buffer`, or the buffers shared with `share_buffers`, until
+    /// `stop_sampling`.
+    pub fn start_continuous(channel: u32, frequency: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, CONTINUOUS_BUFF_SAMPLE, channel, frequency).to_result()
+    }
+
+    pub fn stop_sampling() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, STOP_SAMPLE, 0, 0).to_result()
+    }
+
     /// Returns the number of ADC resolution bits
     pub fn get_resolution_bits() -> Result<u32, ErrorCode> {
         S::command(DRIVER_NUM, GET_RES_BITS, 0, 0).to_result()
@@ -73,6 +159,16 @@ impl<S: Syscalls> Adc<S> {
     }
 }
 
+/// The buffer continuous sampling writes into, shared by `Adc::share_buffer`.
+pub type SampleBuffer<'share, S> = AllowRw<'share, S, DRIVER_NUM, BUFFER>;
+
+/// The second buffer continuous sampling writes into, shared by
+/// `Adc::share_buffers`.
+pub type SecondSampleBuffer<'share, S> = AllowRw<'share, S, DRIVER_NUM, SECOND_BUFFER>;
+
+/// The upcall of `Adc::share_buffer`.
+pub type SampleSubscribe<'share, S> = Subscribe<'share, S, DRIVER_NUM, 0>;
+
 pub struct ADCListener<F: Fn(u16)>(pub F);
 
 impl<F: Fn(u16)> Upcall<OneId<DRIVER_NUM, 0>> for ADCListener<F> {
@@ -96,7 +192,15 @@ const EXISTS: u32 = 0;
 const SINGLE_SAMPLE: u32 = 1;
 // const REPEAT_SINGLE_SAMPLE: u32 = 2;
 // const MULTIPLE_SAMPLE: u32 = 3;
-// const CONTINUOUS_BUFF_SAMPLE: u32 = 4;
-// const STOP_SAMPLE: u32 = 5;
+const CONTINUOUS_BUFF_SAMPLE: u32 = 4;
+const STOP_SAMPLE: u32 = 5;
 const GET_RES_BITS: u32 = 101;
 const GET_VOLTAGE_REF: u32 = 102;
+
+// Allow IDs
+
+const BUFFER: u32 = 0;
+const SECOND_BUFFER: u32 = 1;
+
+// The alignment of the `u16` samples written by DMA.
+const SAMPLE_ALIGN: usize = 2;
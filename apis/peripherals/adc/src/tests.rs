@@ -1,7 +1,9 @@
 use core::cell::Cell;
-use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_platform::{share, AlignedBuf, ErrorCode, Syscalls, YieldNoWaitReturn};
 use libtock_unittest::fake;
 
+use super::{SampleBuffer, SampleSubscribe, SecondSampleBuffer};
+
 type Adc = super::Adc<fake::Syscalls>;
 
 #[test]
@@ -69,3 +71,100 @@ fn read_single_sample_sync() {
     driver.set_value_sync(1000);
     assert_eq!(Adc::read_single_sample_sync(), Ok(1000));
 }
+
+#[test]
+fn read_buffer_sync() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Adc::new();
+    kernel.add_driver(&driver);
+
+    kernel.set_idle_handler({
+        let driver = driver.clone();
+        move || {
+            driver.fill_buffer(&[1, 2, 3]);
+        }
+    });
+    let mut buf = AlignedBuf::<4, 2>::new();
+    assert_eq!(Adc::read_buffer_sync(0, 1000, &mut buf), Ok(2));
+    assert_eq!(*buf, [1u16.to_ne_bytes(), 2u16.to_ne_bytes()].concat()[..]);
+    // Sampling stopped.
+    assert_eq!(driver.active_buffer(), None);
+}
+
+#[test]
+fn stream() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Adc::new();
+    kernel.add_driver(&driver);
+
+    let done = Cell::new(None);
+    let mut first = AlignedBuf::<4, 2>::new();
+    let mut second = AlignedBuf::<4, 2>::new();
+    share::scope::<(SampleBuffer<_>, SampleSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(
+            Adc::share_buffer(&mut first, &done, allow_rw, subscribe),
+            Ok(())
+        );
+        assert_eq!(Adc::start_continuous(0, 1000), Ok(()));
+        assert_eq!(driver.fill_buffer(&[1, 2]), Some(2));
+        fake::Syscalls::yield_wait();
+        assert_eq!(done.take(), Some((4, 2, 0)));
+    });
+    // Sampling continues into the next buffer shared.
+    share::scope::<(SampleBuffer<_>, SampleSubscribe<_>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        assert_eq!(
+            Adc::share_buffer(&mut second, &done, allow_rw, subscribe),
+            Ok(())
+        );
+        assert_eq!(driver.fill_buffer(&[3]), Some(1));
+    });
+    assert_eq!(Adc::stop_sampling(), Ok(()));
+    assert_eq!(driver.active_buffer(), None);
+    assert_eq!(
+        first[..],
+        [1u16.to_ne_bytes(), 2u16.to_ne_bytes()].concat()[..]
+    );
+    assert_eq!(second[..2], 3u16.to_ne_bytes());
+}
+
+#[test]
+fn share_buffers() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Adc::new();
+    kernel.add_driver(&driver);
+
+    let done = Cell::new(None);
+    let mut first = AlignedBuf::<4, 2>::new();
+    let mut second = AlignedBuf::<4, 2>::new();
+    share::scope::<(SampleBuffer<_>, SecondSampleBuffer<_>, SampleSubscribe<_>), _, _>(|handle| {
+        let (allow_first, allow_second, subscribe) = handle.split();
+        assert_eq!(
+            Adc::share_buffers(
+                &mut first,
+                &mut second,
+                &done,
+                allow_first,
+                allow_second,
+                subscribe
+            ),
+            Ok(())
+        );
+        assert_eq!(Adc::start_continuous(0, 1000), Ok(()));
+        // The ADC alternates between the buffers, without sharing them again.
+        assert_eq!(driver.fill_buffer(&[1, 2]), Some(2));
+        fake::Syscalls::yield_wait();
+        assert_eq!(done.take(), Some((4, 2, 0)));
+        assert_eq!(driver.fill_buffer(&[3]), Some(1));
+        fake::Syscalls::yield_wait();
+        assert_eq!(done.take(), Some((4, 1, 1)));
+        assert_eq!(driver.active_buffer(), Some(0));
+        assert_eq!(Adc::stop_sampling(), Ok(()));
+    });
+    assert_eq!(
+        first[..],
+        [1u16.to_ne_bytes(), 2u16.to_ne_bytes()].concat()[..]
+    );
+    assert_eq!(second[..2], 3u16.to_ne_bytes());
+}
@@ -5,6 +5,11 @@ use libtock_platform as platform;
 use libtock_platform::share;
 use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
 
+mod liveness;
+mod retry;
+pub use liveness::LivenessGuard;
+pub use retry::RetryConfig;
+
 /// The alarm driver
 ///
 /// # Example
@@ -81,6 +86,50 @@ impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
         Ok(ticks.saturating_div(freq / 1000))
     }
 
+    /// Registers `fired` to receive the upcalls of the alarms set with
+    /// `set_at`, until the end of the handle's scope, e.g. for
+    /// `libtock_future::wait_for_upcall`.
+    pub fn register<'share>(
+        fired: &'share Cell<Option<(u32, u32)>>,
+        subscribe: share::Handle<AlarmSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, fired)
+    }
+
+    /// Sets the alarm to fire `dt` after the tick `reference`, without waiting
+    /// for it, and returns the tick it fires at. Setting each alarm relative
+    /// to the previous one's tick keeps periodic alarms from drifting.
+    pub fn set_at(reference: u32, dt: Ticks) -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_ABSOLUTE, reference, dt.0).to_result()
+    }
+
+    /// Cancels the alarm set with `set_at`, if it has not fired yet.
+    pub fn stop() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::STOP, 0, 0).to_result()
+    }
+
+    /// Returns whether `dt` has elapsed since the tick `reference`, e.g. to
+    /// poll for a deadline where subscribing to the alarm is impossible, such
+    /// as inside another upcall. The comparison is relative to `reference`, so
+    /// it survives the tick counter wrapping around.
+    pub fn has_elapsed(reference: u32, dt: Ticks) -> Result<bool, ErrorCode> {
+        let now = Self::get_ticks()?;
+        Ok(now.wrapping_sub(reference) >= dt.0)
+    }
+
+    /// Sleeps like `sleep_for`, but without subscribing to the alarm: polls the
+    /// time instead, running the pending upcalls with `yield_no_wait` in
+    /// between. The process stays busy while it waits, so this only suits
+    /// short waits.
+    pub fn sleep_for_polling<T: Convert>(time: T) -> Result<(), ErrorCode> {
+        let dt = time.to_ticks(Self::get_frequency()?);
+        let reference = Self::get_ticks()?;
+        while !Self::has_elapsed(reference, dt)? {
+            S::yield_no_wait();
+        }
+        Ok(())
+    }
+
     pub fn sleep_for<T: Convert>(time: T) -> Result<(), ErrorCode> {
         let freq = Self::get_frequency()?;
         let ticks = time.to_ticks(freq);
@@ -103,6 +152,20 @@ impl<S: Syscalls, C: platform::subscribe::Config> Alarm<S, C> {
     }
 }
 
+/// An estimate of the stack the deepest call of this crate uses, for
+/// `stack_size!` hints.
+pub const STACK_USAGE: usize = 0x40;
+
+/// The subscription of `Alarm::register`.
+pub type AlarmSubscribe<'share, S> =
+    platform::Subscribe<'share, S, DRIVER_NUM, { subscribe::CALLBACK }>;
+
+#[cfg(test)]
+mod liveness_tests;
+
+#[cfg(test)]
+mod retry_tests;
+
 #[cfg(test)]
 mod tests;
 
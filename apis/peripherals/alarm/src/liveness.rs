@@ -0,0 +1,69 @@
+use crate::{command, subscribe, Alarm, Convert, Ticks, DRIVER_NUM};
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_platform as platform;
+use libtock_platform::share;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// Bounds how long a blocking call may wait for its driver.
+///
+/// A blocking call normally yields until its driver's upcall arrives, so a
+/// wedged capsule (e.g. a UART that never completes a transfer) freezes the
+/// app. APIs that accept a guard (such as `Console::write_guarded`) arm the
+/// alarm for `max_wait` while they wait, and return `ErrorCode::Busy` if it
+/// expires before the upcall arrives.
+///
+/// The guard uses the alarm while waiting, so cannot be used while the app
+/// uses the alarm otherwise.
+///
+/// # Example
+/// ```ignore
+/// use libtock::alarm::{LivenessGuard, Milliseconds};
+/// use libtock::console::Console;
+///
+/// let guard = LivenessGuard::new(Milliseconds(500))?;
+/// match Console::write_guarded(b"ping\n", &guard) {
+///     Err(ErrorCode::Busy) => recover_uart(),
+///     result => result?,
+/// }
+/// ```
+pub struct LivenessGuard<S: Syscalls, C: platform::subscribe::Config = DefaultConfig> {
+    max_wait: Ticks,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, C: platform::subscribe::Config> LivenessGuard<S, C> {
+    /// Creates a guard that lets blocking calls wait for at most `max_wait`.
+    pub fn new<T: Convert>(max_wait: T) -> Result<Self, ErrorCode> {
+        Ok(LivenessGuard {
+            max_wait: max_wait.to_ticks(Alarm::<S, C>::get_frequency()?),
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Yields until `done` returns `true`, which is checked after every
+    /// upcall. Returns `ErrorCode::Busy` if the maximum wait passes first.
+    pub fn wait<F: FnMut() -> bool>(&self, mut done: F) -> Result<(), ErrorCode> {
+        let expired: Cell<Option<(u32, u32)>> = Cell::new(None);
+        share::scope(|subscribe| {
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &expired)?;
+
+            S::command(DRIVER_NUM, command::SET_RELATIVE, self.max_wait.0, 0)
+                .to_result()
+                .map(|_when: u32| ())?;
+
+            loop {
+                S::yield_wait();
+                if done() {
+                    // The alarm may have expired along with the last upcall,
+                    // in which case there is nothing to stop.
+                    let _ = S::command(DRIVER_NUM, command::STOP, 0, 0);
+                    return Ok(());
+                }
+                if expired.get().is_some() {
+                    return Err(ErrorCode::Busy);
+                }
+            }
+        })
+    }
+}
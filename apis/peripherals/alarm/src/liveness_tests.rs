@@ -0,0 +1,55 @@
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::{Milliseconds, Ticks};
+
+type LivenessGuard = crate::LivenessGuard<fake::Syscalls>;
+
+#[test]
+fn new() {
+    let kernel = fake::Kernel::new();
+    assert_eq!(
+        LivenessGuard::new(Ticks(10)).err(),
+        Some(ErrorCode::NoDevice)
+    );
+    let driver = fake::Alarm::new(1000);
+    kernel.add_driver(&driver);
+    assert!(LivenessGuard::new(Milliseconds(10)).is_ok());
+}
+
+#[test]
+fn done_at_deadline() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+    let guard = LivenessGuard::new(Milliseconds(500)).unwrap();
+
+    // Work that completes along with the deadline is not reported as a
+    // timeout.
+    kernel.set_idle_handler({
+        let alarm = alarm.clone();
+        move || alarm.advance_ticks(500)
+    });
+    let mut checks = 0;
+    let result = guard.wait(|| {
+        checks += 1;
+        true
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(checks, 1);
+}
+
+#[test]
+fn expired() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+    let guard = LivenessGuard::new(Milliseconds(500)).unwrap();
+
+    kernel.set_idle_handler({
+        let alarm = alarm.clone();
+        move || alarm.advance_ticks(500)
+    });
+    assert_eq!(guard.wait(|| false), Err(ErrorCode::Busy));
+    assert_eq!(alarm.ticks(), 500);
+}
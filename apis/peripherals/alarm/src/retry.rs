@@ -0,0 +1,66 @@
+use crate::{Alarm, Milliseconds};
+use core::marker::PhantomData;
+use libtock_platform::{allow_ro, allow_rw, subscribe, ErrorCode, Syscalls};
+
+/// A syscall configuration that retries Allow and Subscribe calls failing with
+/// `ErrorCode::Busy` or `ErrorCode::NoMem`, which are usually transient.
+///
+/// A failing call is retried up to `RETRIES` times, sleeping with the alarm
+/// between attempts: `BACKOFF_MS` milliseconds after the first failure, and
+/// twice as long after each following one. If the alarm cannot sleep, the
+/// failure is returned without retrying.
+///
+/// Drivers take it as their `Config` parameter.
+///
+/// # Example
+/// ```ignore
+/// use libtock::alarm::RetryConfig;
+/// use libtock::runtime::TockSyscalls;
+///
+/// type Console = libtock_console::Console<TockSyscalls, RetryConfig>;
+/// Console::write(b"retried while the UART is busy\n")?;
+/// ```
+pub struct RetryConfig<S: Syscalls, const RETRIES: u32 = 3, const BACKOFF_MS: u32 = 1> {
+    _syscalls: PhantomData<S>,
+}
+
+impl<S: Syscalls, const RETRIES: u32, const BACKOFF_MS: u32> RetryConfig<S, RETRIES, BACKOFF_MS> {
+    // Waits before retrying a call that failed with `error`, and returns
+    // whether to retry it.
+    fn backoff(error: ErrorCode, failures: u32) -> bool {
+        if !matches!(error, ErrorCode::Busy | ErrorCode::NoMem) || failures > RETRIES {
+            return false;
+        }
+        let delay = BACKOFF_MS.saturating_mul(1 << (failures - 1).min(31));
+        Alarm::<S>::sleep_for(Milliseconds(delay)).is_ok()
+    }
+}
+
+impl<S: Syscalls, const RETRIES: u32, const BACKOFF_MS: u32> allow_ro::Config
+    for RetryConfig<S, RETRIES, BACKOFF_MS>
+{
+    fn should_retry(_driver_num: u32, _buffer_num: u32, error: ErrorCode, failures: u32) -> bool {
+        Self::backoff(error, failures)
+    }
+}
+
+impl<S: Syscalls, const RETRIES: u32, const BACKOFF_MS: u32> allow_rw::Config
+    for RetryConfig<S, RETRIES, BACKOFF_MS>
+{
+    fn should_retry(_driver_num: u32, _buffer_num: u32, error: ErrorCode, failures: u32) -> bool {
+        Self::backoff(error, failures)
+    }
+}
+
+impl<S: Syscalls, const RETRIES: u32, const BACKOFF_MS: u32> subscribe::Config
+    for RetryConfig<S, RETRIES, BACKOFF_MS>
+{
+    fn should_retry(
+        _driver_num: u32,
+        _subscribe_num: u32,
+        error: ErrorCode,
+        failures: u32,
+    ) -> bool {
+        Self::backoff(error, failures)
+    }
+}
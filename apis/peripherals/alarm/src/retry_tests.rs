@@ -0,0 +1,84 @@
+extern crate std;
+
+use core::cell::RefCell;
+use libtock_platform::{share, CommandReturn, ErrorCode, Syscalls};
+use libtock_unittest::{command_return, fake, DriverInfo, RoAllowBuffer};
+use std::rc::Rc;
+use std::vec::Vec;
+
+type RetryConfig = crate::RetryConfig<fake::Syscalls, 2, 10>;
+
+const DRIVER_NUM: u32 = 0x1234;
+
+// A driver whose Read-Only Allow fails with each of `errors` in turn, and then
+// succeeds.
+struct FlakyDriver {
+    errors: RefCell<Vec<ErrorCode>>,
+}
+
+impl FlakyDriver {
+    fn new(errors: &[ErrorCode]) -> Rc<FlakyDriver> {
+        Rc::new(FlakyDriver {
+            errors: RefCell::new(errors.to_vec()),
+        })
+    }
+}
+
+impl fake::SyscallDriver for FlakyDriver {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM)
+    }
+
+    fn command(&self, _: u32, _: u32, _: u32) -> CommandReturn {
+        command_return::failure(ErrorCode::NoSupport)
+    }
+
+    fn allow_readonly(
+        &self,
+        _: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        let mut errors = self.errors.borrow_mut();
+        match errors.is_empty() {
+            true => Ok(buffer),
+            false => Err((buffer, errors.remove(0))),
+        }
+    }
+}
+
+fn allow() -> Result<(), ErrorCode> {
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<RetryConfig, DRIVER_NUM, 0>(allow_ro, b"frame")
+    })
+}
+
+#[test]
+fn retries_transient_errors() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&FlakyDriver::new(&[ErrorCode::Busy, ErrorCode::NoMem]));
+    assert_eq!(allow(), Ok(()));
+    // 10 ms after the first failure, then 20 ms.
+    assert_eq!(alarm.ticks(), 30);
+}
+
+#[test]
+fn gives_up() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&FlakyDriver::new(&[ErrorCode::Busy; 3]));
+    assert_eq!(allow(), Err(ErrorCode::Busy));
+    assert_eq!(alarm.ticks(), 30);
+}
+
+#[test]
+fn other_errors() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&FlakyDriver::new(&[ErrorCode::Invalid]));
+    assert_eq!(allow(), Err(ErrorCode::Invalid));
+    assert_eq!(alarm.ticks(), 0);
+}
@@ -1,4 +1,5 @@
-use libtock_unittest::fake;
+use libtock_platform::{Syscalls, YieldNoWaitReturn};
+use libtock_unittest::{fake, SyscallLogEntry};
 
 use crate::{Hz, Milliseconds, Ticks};
 
@@ -22,3 +23,59 @@ fn sleep() {
     assert_eq!(Alarm::sleep_for(Ticks(1000)), Ok(()));
     assert_eq!(Alarm::sleep_for(Milliseconds(1000)), Ok(()));
 }
+
+#[test]
+fn get_ticks() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+
+    assert_eq!(Alarm::get_ticks(), Ok(0));
+    driver.advance_ticks(1500);
+    assert_eq!(Alarm::get_ticks(), Ok(1500));
+    assert_eq!(Alarm::get_milliseconds(), Ok(1500));
+}
+
+#[test]
+fn set_at() {
+    use core::cell::Cell;
+    use libtock_platform::share;
+
+    let kernel = fake::Kernel::new();
+    let driver = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+
+    let fired = Cell::new(None);
+    share::scope(|subscribe| {
+        assert_eq!(Alarm::register(&fired, subscribe), Ok(()));
+        assert_eq!(Alarm::set_at(0, Ticks(50)), Ok(50));
+        assert_eq!(Alarm::stop(), Ok(()));
+        assert_eq!(Alarm::set_at(0, Ticks(10)), Ok(10));
+        driver.advance_ticks(10);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(fired.get(), Some((10, 10)));
+    });
+    assert_eq!(Alarm::stop(), Err(libtock_platform::ErrorCode::Already));
+}
+
+#[test]
+fn sleep_polling() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&driver);
+
+    driver.advance_ticks(u32::MAX - 5);
+    assert_eq!(Alarm::has_elapsed(u32::MAX - 5, Ticks(10)), Ok(false));
+    driver.advance_ticks(10);
+    assert_eq!(Alarm::has_elapsed(u32::MAX - 5, Ticks(10)), Ok(true));
+
+    driver.set_time_step(4);
+    kernel.take_syscall_log();
+    assert_eq!(Alarm::sleep_for_polling(Milliseconds(10)), Ok(()));
+    // Nothing was subscribed to.
+    let log = kernel.take_syscall_log();
+    assert!(!log
+        .iter()
+        .any(|entry| matches!(entry, SyscallLogEntry::Subscribe { .. })));
+    assert_eq!(driver.ticks(), 20);
+}
@@ -0,0 +1,307 @@
+//! CTAPHID framing: the packets that carry messages in HID reports, and the
+//! channels that separate the host's clients.
+//!
+//! A message starts with an initialization packet, which carries its channel,
+//! command and length, and continues with continuation packets numbered from 0.
+//! Clients get a channel by sending INIT on the broadcast channel, which the
+//! device answers with [`init_response`] and a channel from [`Channels`].
+
+use crate::{Report, REPORT_LEN};
+use libtock_platform::ErrorCode;
+
+/// The channel on which clients request a channel with INIT.
+pub const BROADCAST_CID: u32 = 0xffff_ffff;
+
+/// The longest message: an initialization packet and 128 continuation packets.
+pub const MAX_MESSAGE_LEN: usize = INIT_DATA_LEN + 128 * CONT_DATA_LEN;
+
+/// CTAPHID commands.
+pub mod command {
+    pub const PING: u8 = 0x01;
+    pub const MSG: u8 = 0x03;
+    pub const LOCK: u8 = 0x04;
+    pub const INIT: u8 = 0x06;
+    pub const WINK: u8 = 0x08;
+    pub const CBOR: u8 = 0x10;
+    pub const CANCEL: u8 = 0x11;
+    pub const KEEPALIVE: u8 = 0x3b;
+    pub const ERROR: u8 = 0x3f;
+}
+
+/// The error codes of ERROR messages.
+pub mod error {
+    pub const INVALID_CMD: u8 = 0x01;
+    pub const INVALID_PAR: u8 = 0x02;
+    pub const INVALID_LEN: u8 = 0x03;
+    pub const INVALID_SEQ: u8 = 0x04;
+    pub const MSG_TIMEOUT: u8 = 0x05;
+    pub const CHANNEL_BUSY: u8 = 0x06;
+    pub const INVALID_CHANNEL: u8 = 0x0b;
+    pub const OTHER: u8 = 0x7f;
+}
+
+/// The capability flags of [`init_response`].
+pub mod capability {
+    pub const WINK: u8 = 0x01;
+    pub const CBOR: u8 = 0x04;
+    pub const NMSG: u8 = 0x08;
+}
+
+/// A CTAPHID packet, parsed from a report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packet<'r> {
+    /// The first packet of a message, which carries the message's total
+    /// length.
+    Init {
+        cid: u32,
+        cmd: u8,
+        len: u16,
+        data: &'r [u8],
+    },
+    /// A following packet of a message.
+    Cont { cid: u32, seq: u8, data: &'r [u8] },
+}
+
+impl<'r> Packet<'r> {
+    pub fn parse(report: &'r Report) -> Self {
+        let cid = u32::from_be_bytes([report[0], report[1], report[2], report[3]]);
+        match report[4] {
+            // Commands have the top bit set, and sequence numbers do not.
+            cmd if cmd & INIT_FLAG != 0 => Packet::Init {
+                cid,
+                cmd: cmd & !INIT_FLAG,
+                len: u16::from_be_bytes([report[5], report[6]]),
+                data: &report[7..],
+            },
+            seq => Packet::Cont {
+                cid,
+                seq,
+                data: &report[5..],
+            },
+        }
+    }
+
+    pub fn cid(&self) -> u32 {
+        match *self {
+            Packet::Init { cid, .. } | Packet::Cont { cid, .. } => cid,
+        }
+    }
+}
+
+/// A message received by an [`Assembler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub cid: u32,
+    pub cmd: u8,
+    pub len: usize,
+}
+
+/// A packet that breaks the protocol, to be answered with an ERROR message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The channel of the packet.
+    pub cid: u32,
+    /// One of the [`error`] codes.
+    pub code: u8,
+}
+
+impl Error {
+    /// Returns the ERROR message reporting this error.
+    pub fn report(&self) -> Report {
+        let mut report = [0; REPORT_LEN];
+        report[..4].copy_from_slice(&self.cid.to_be_bytes());
+        report[4] = command::ERROR | INIT_FLAG;
+        report[6] = 1;
+        report[7] = self.code;
+        report
+    }
+}
+
+/// Reassembles messages from packets, into a buffer.
+///
+/// One message is received at a time: while a message is pending, new
+/// messages on other channels are refused with `error::CHANNEL_BUSY`.
+pub struct Assembler<'b> {
+    buffer: &'b mut [u8],
+    pending: Option<Message>,
+    received: usize,
+    seq: u8,
+}
+
+impl<'b> Assembler<'b> {
+    /// Creates an assembler for messages of up to `buffer.len()` bytes.
+    pub fn new(buffer: &'b mut [u8]) -> Self {
+        Assembler {
+            buffer,
+            pending: None,
+            received: 0,
+            seq: 0,
+        }
+    }
+
+    /// Adds the packet of `report`, and returns the message it completes, if
+    /// any. Continuation packets of no pending message are ignored.
+    pub fn push(&mut self, report: &Report) -> Result<Option<Message>, Error> {
+        match Packet::parse(report) {
+            Packet::Init {
+                cid,
+                cmd,
+                len,
+                data,
+            } => {
+                let refuse = |code| Err(Error { cid, code });
+                if cid == 0 || (cid == BROADCAST_CID && cmd != command::INIT) {
+                    return refuse(error::INVALID_CHANNEL);
+                }
+                // A new message on the pending message's channel replaces it.
+                if self.pending.is_some_and(|pending| pending.cid != cid) {
+                    return refuse(error::CHANNEL_BUSY);
+                }
+                self.pending = None;
+                let len = len as usize;
+                if len > self.buffer.len() || len > MAX_MESSAGE_LEN {
+                    return refuse(error::INVALID_LEN);
+                }
+                self.pending = Some(Message { cid, cmd, len });
+                self.received = 0;
+                self.seq = 0;
+                Ok(self.append(data))
+            }
+            Packet::Cont { cid, seq, data } => {
+                if !self.pending.is_some_and(|pending| pending.cid == cid) {
+                    return Ok(None);
+                }
+                if seq != self.seq {
+                    self.pending = None;
+                    return Err(Error {
+                        cid,
+                        code: error::INVALID_SEQ,
+                    });
+                }
+                self.seq += 1;
+                Ok(self.append(data))
+            }
+        }
+    }
+
+    /// Returns the payload of `message`, the last message `push` returned.
+    pub fn payload(&self, message: &Message) -> &[u8] {
+        &self.buffer[..message.len]
+    }
+
+    /// Drops the pending message, e.g. once it timed out.
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+
+    // Appends the data of a packet to the pending message, and returns the
+    // message if it is complete.
+    fn append(&mut self, data: &[u8]) -> Option<Message> {
+        let message = self.pending?;
+        let count = (message.len - self.received).min(data.len());
+        self.buffer[self.received..self.received + count].copy_from_slice(&data[..count]);
+        self.received += count;
+        if self.received < message.len {
+            return None;
+        }
+        self.pending.take()
+    }
+}
+
+/// The packets of a message, as reports.
+pub struct Fragments<'p> {
+    cid: u32,
+    cmd: u8,
+    payload: &'p [u8],
+    // The sequence number of the next packet, or None before the
+    // initialization packet.
+    seq: Option<u8>,
+}
+
+impl<'p> Fragments<'p> {
+    /// Splits `payload` into the packets of a message of command `cmd` on
+    /// channel `cid`. Fails with `ErrorCode::Size` if the payload is longer
+    /// than `MAX_MESSAGE_LEN`.
+    pub fn new(cid: u32, cmd: u8, payload: &'p [u8]) -> Result<Self, ErrorCode> {
+        if payload.len() > MAX_MESSAGE_LEN {
+            return Err(ErrorCode::Size);
+        }
+        Ok(Fragments {
+            cid,
+            cmd,
+            payload,
+            seq: None,
+        })
+    }
+}
+
+impl Iterator for Fragments<'_> {
+    type Item = Report;
+
+    fn next(&mut self) -> Option<Report> {
+        let mut report = [0; REPORT_LEN];
+        report[..4].copy_from_slice(&self.cid.to_be_bytes());
+        let data = match self.seq {
+            None => {
+                report[4] = self.cmd | INIT_FLAG;
+                report[5..7].copy_from_slice(&(self.payload.len() as u16).to_be_bytes());
+                self.seq = Some(0);
+                &mut report[7..]
+            }
+            Some(_) if self.payload.is_empty() => return None,
+            Some(seq) => {
+                report[4] = seq;
+                self.seq = Some(seq + 1);
+                &mut report[5..]
+            }
+        };
+        let count = data.len().min(self.payload.len());
+        data[..count].copy_from_slice(&self.payload[..count]);
+        self.payload = &self.payload[count..];
+        Some(report)
+    }
+}
+
+/// Allocates the channels requested with INIT on the broadcast channel.
+#[derive(Default)]
+pub struct Channels {
+    last: u32,
+}
+
+impl Channels {
+    pub const fn new() -> Self {
+        Channels { last: 0 }
+    }
+
+    /// Returns a new channel ID, which is never 0 nor the broadcast channel.
+    /// IDs are reused only after 2^32 - 2 allocations.
+    pub fn allocate(&mut self) -> u32 {
+        self.last = match self.last.wrapping_add(1) {
+            0 | BROADCAST_CID => 1,
+            cid => cid,
+        };
+        self.last
+    }
+}
+
+/// Returns the payload of the response to an INIT request with `nonce`, which
+/// assigns channel `cid` to the client. `version` is the device's major, minor
+/// and build version, and `capabilities` its [`capability`] flags.
+pub fn init_response(nonce: &[u8; 8], cid: u32, version: [u8; 3], capabilities: u8) -> [u8; 17] {
+    let mut response = [0; 17];
+    response[..8].copy_from_slice(nonce);
+    response[8..12].copy_from_slice(&cid.to_be_bytes());
+    response[12] = PROTOCOL_VERSION;
+    response[13..16].copy_from_slice(&version);
+    response[16] = capabilities;
+    response
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const INIT_FLAG: u8 = 0x80;
+const INIT_DATA_LEN: usize = REPORT_LEN - 7;
+const CONT_DATA_LEN: usize = REPORT_LEN - 5;
+const PROTOCOL_VERSION: u8 = 2;
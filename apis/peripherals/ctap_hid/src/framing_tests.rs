@@ -0,0 +1,145 @@
+extern crate std;
+
+use crate::framing::*;
+use crate::{Report, REPORT_LEN};
+use libtock_platform::ErrorCode;
+use std::{vec, vec::Vec};
+
+const CID: u32 = 0x1234_5678;
+
+#[test]
+fn fragments() {
+    let payload: Vec<u8> = (0..=255).cycle().take(200).collect();
+    let reports: Vec<Report> = Fragments::new(CID, command::CBOR, &payload)
+        .unwrap()
+        .collect();
+    // 57 bytes in the initialization packet, and 59 in each continuation.
+    assert_eq!(reports.len(), 4);
+    assert_eq!(
+        Packet::parse(&reports[0]),
+        Packet::Init {
+            cid: CID,
+            cmd: command::CBOR,
+            len: 200,
+            data: &payload[..57],
+        }
+    );
+    assert_eq!(
+        Packet::parse(&reports[3]),
+        Packet::Cont {
+            cid: CID,
+            seq: 2,
+            data: &[&payload[175..], &[0; 34][..]].concat(),
+        }
+    );
+
+    let empty: Vec<Report> = Fragments::new(CID, command::PING, &[]).unwrap().collect();
+    assert_eq!(empty.len(), 1);
+    assert_eq!(
+        Fragments::new(CID, command::MSG, &[0; MAX_MESSAGE_LEN + 1]).err(),
+        Some(ErrorCode::Size)
+    );
+}
+
+#[test]
+fn assemble() {
+    let payload: Vec<u8> = (0..=255).cycle().take(MAX_MESSAGE_LEN).collect();
+    let mut buffer = vec![0; MAX_MESSAGE_LEN];
+    let mut assembler = Assembler::new(&mut buffer);
+    let mut reports: Vec<Report> = Fragments::new(CID, command::MSG, &payload)
+        .unwrap()
+        .collect();
+    assert_eq!(reports.len(), 129);
+    let last = reports.pop().unwrap();
+    for report in reports {
+        assert_eq!(assembler.push(&report), Ok(None));
+    }
+    let message = assembler.push(&last).unwrap().unwrap();
+    assert_eq!(
+        message,
+        Message {
+            cid: CID,
+            cmd: command::MSG,
+            len: MAX_MESSAGE_LEN,
+        }
+    );
+    assert_eq!(assembler.payload(&message), payload);
+}
+
+#[test]
+fn protocol_errors() {
+    let mut buffer = [0; 100];
+    let mut assembler = Assembler::new(&mut buffer);
+    let mut reports = Fragments::new(CID, command::MSG, &[1; 80]).unwrap();
+    let (init, cont) = (reports.next().unwrap(), reports.next().unwrap());
+
+    // Continuation packets of no message are ignored.
+    assert_eq!(assembler.push(&cont), Ok(None));
+
+    assert_eq!(assembler.push(&init), Ok(None));
+    let other = Fragments::new(CID + 1, command::PING, &[])
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(
+        assembler.push(&other),
+        Err(Error {
+            cid: CID + 1,
+            code: error::CHANNEL_BUSY,
+        })
+    );
+    // The pending message is unaffected.
+    assert!(assembler.push(&cont).unwrap().is_some());
+
+    assert_eq!(assembler.push(&init), Ok(None));
+    let mut skipped = cont;
+    skipped[4] = 1;
+    assert_eq!(
+        assembler.push(&skipped),
+        Err(Error {
+            cid: CID,
+            code: error::INVALID_SEQ,
+        })
+    );
+    assert_eq!(assembler.push(&cont), Ok(None));
+
+    let broadcast = Fragments::new(BROADCAST_CID, command::MSG, &[])
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(
+        assembler.push(&broadcast),
+        Err(Error {
+            cid: BROADCAST_CID,
+            code: error::INVALID_CHANNEL,
+        })
+    );
+    let too_long = Fragments::new(CID, command::MSG, &[0; 101])
+        .unwrap()
+        .next()
+        .unwrap();
+    let error = assembler.push(&too_long).unwrap_err();
+    assert_eq!(error.code, error::INVALID_LEN);
+    assert_eq!(
+        Packet::parse(&error.report()),
+        Packet::Init {
+            cid: CID,
+            cmd: command::ERROR,
+            len: 1,
+            data: &[&[error::INVALID_LEN][..], &[0; REPORT_LEN - 8][..]].concat(),
+        }
+    );
+}
+
+#[test]
+fn channels() {
+    let mut channels = Channels::new();
+    assert_eq!(channels.allocate(), 1);
+    assert_eq!(channels.allocate(), 2);
+
+    let response = init_response(&[1, 2, 3, 4, 5, 6, 7, 8], CID, [1, 0, 3], capability::CBOR);
+    assert_eq!(
+        response,
+        [1, 2, 3, 4, 5, 6, 7, 8, 0x12, 0x34, 0x56, 0x78, 2, 1, 0, 3, 4]
+    );
+}
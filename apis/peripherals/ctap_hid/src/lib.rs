@@ -0,0 +1,187 @@
+//! The USB HID transport of CTAP (FIDO2 and U2F security keys).
+//!
+//! [`CtapHid`] sends and receives the 64-byte HID reports of the CTAP HID
+//! interface, through the driver of Tock's `usb_hid_driver` capsule:
+//!
+//! - Command 0 checks whether the driver exists.
+//! - Command 1 sends the report in read-write allow 1 to the host.
+//! - Command 2 receives the next report from the host into read-write allow 0.
+//! - Command 4 cancels the pending send and receive.
+//! - Subscribe 0 is called with 0 once a report is received, and with 1 once a
+//!   report is sent.
+//!
+//! CTAPHID messages are longer than a report: the [`framing`] module splits
+//! them into packets, reassembles them, and allocates the channels that
+//! separate the host's clients.
+
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform as platform;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+pub mod framing;
+
+use framing::{Assembler, Fragments, Message};
+
+/// The length of a CTAP HID report.
+pub const REPORT_LEN: usize = 64;
+
+/// A CTAP HID report, which carries one CTAPHID packet.
+pub type Report = [u8; REPORT_LEN];
+
+/// The CTAP HID interface.
+///
+/// # Example
+/// ```ignore
+/// use libtock::ctap_hid::{framing, CtapHid};
+///
+/// let mut buffer = [0; 1024];
+/// let mut assembler = framing::Assembler::new(&mut buffer);
+/// let mut channels = framing::Channels::new();
+/// loop {
+///     let message = CtapHid::receive_message(&mut assembler)?;
+///     let payload = assembler.payload(&message);
+///     match message.cmd {
+///         framing::command::INIT if message.cid == framing::BROADCAST_CID => {
+///             let Ok(nonce) = payload.try_into() else { continue };
+///             let cid = channels.allocate();
+///             let response = framing::init_response(nonce, cid, VERSION, 0);
+///             CtapHid::send_message(message.cid, message.cmd, &response)?;
+///         }
+///         // ...
+///     }
+/// }
+/// ```
+pub struct CtapHid<
+    S: Syscalls,
+    C: Config = DefaultConfig,
+    const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM,
+>(S, C);
+
+impl<S: Syscalls, C: Config, const DRIVER_NUM: u32> CtapHid<S, C, DRIVER_NUM> {
+    /// Run a check against the HID capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Receives the next report from the host into `report`.
+    pub fn receive(report: &mut Report) -> Result<(), ErrorCode> {
+        Self::transfer::<{ allow_rw::RECEIVE }>(report, command::RECEIVE, upcall::RECEIVED)
+    }
+
+    /// Sends `report` to the host.
+    pub fn send(report: &Report) -> Result<(), ErrorCode> {
+        // The driver shares the report read-write.
+        let mut report = *report;
+        Self::transfer::<{ allow_rw::SEND }>(&mut report, command::SEND, upcall::SENT)
+    }
+
+    /// Cancels the pending send and receive, e.g. those of a previous scope
+    /// that returned early.
+    pub fn cancel() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::CANCEL, 0, 0).to_result()
+    }
+
+    /// Receives reports until they complete a message, and returns it. Its
+    /// payload is in `assembler`'s buffer.
+    ///
+    /// Packets that break the protocol, e.g. by starting a message on a
+    /// channel while another channel's message is being received, are
+    /// answered with an ERROR message on their channel.
+    pub fn receive_message(assembler: &mut Assembler) -> Result<Message, ErrorCode> {
+        let mut report = [0; REPORT_LEN];
+        loop {
+            Self::receive(&mut report)?;
+            match assembler.push(&report) {
+                Ok(Some(message)) => return Ok(message),
+                Ok(None) => {}
+                Err(error) => Self::send(&error.report())?,
+            }
+        }
+    }
+
+    /// Sends `payload` to the host as a message of command `cmd` on channel
+    /// `cid`. Fails with `ErrorCode::Size` if the payload is longer than
+    /// `framing::MAX_MESSAGE_LEN`.
+    pub fn send_message(cid: u32, cmd: u8, payload: &[u8]) -> Result<(), ErrorCode> {
+        for report in Fragments::new(cid, cmd, payload)? {
+            Self::send(&report)?;
+        }
+        Ok(())
+    }
+
+    // Shares `report` as `BUFFER`, starts the transfer with `command_num`,
+    // and waits for the upcall of kind `kind`.
+    fn transfer<const BUFFER: u32>(
+        report: &mut Report,
+        command_num: u32,
+        kind: u32,
+    ) -> Result<(), ErrorCode> {
+        let done: Cell<Option<(u32,)>> = Cell::new(None);
+        share::scope::<
+            (
+                AllowRw<_, DRIVER_NUM, BUFFER>,
+                Subscribe<_, DRIVER_NUM, { subscribe::TRANSFER }>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            S::allow_rw::<C, DRIVER_NUM, BUFFER>(allow_rw, report)?;
+            S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::TRANSFER }>(subscribe, &done)?;
+            S::checked_command::<C>(DRIVER_NUM, command_num, 0, 0).to_result::<(), _>()?;
+            while done.take() != Some((kind,)) {
+                S::yield_wait();
+            }
+            Ok(())
+        })
+    }
+}
+
+/// System call configuration trait for `CtapHid`.
+pub trait Config:
+    platform::allow_rw::Config + platform::command::Config + platform::subscribe::Config
+{
+}
+impl<T: platform::allow_rw::Config + platform::command::Config + platform::subscribe::Config> Config
+    for T
+{
+}
+
+#[cfg(test)]
+mod framing_tests;
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+pub const DEFAULT_DRIVER_NUM: u32 = 0x20009;
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SEND: u32 = 1;
+    pub const RECEIVE: u32 = 2;
+    pub const CANCEL: u32 = 4;
+}
+
+mod subscribe {
+    pub const TRANSFER: u32 = 0;
+}
+
+mod upcall {
+    pub const RECEIVED: u32 = 0;
+    pub const SENT: u32 = 1;
+}
+
+mod allow_rw {
+    pub const RECEIVE: u32 = 0;
+    pub const SEND: u32 = 1;
+}
@@ -0,0 +1,76 @@
+extern crate std;
+
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+use crate::framing::{command, error, Assembler, Fragments, Packet};
+use crate::Report;
+use std::vec::Vec;
+
+type CtapHid = super::CtapHid<fake::Syscalls>;
+
+const CID: u32 = 7;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(CtapHid::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn send_receive() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::CtapHid::new();
+    kernel.add_driver(&driver);
+    assert_eq!(CtapHid::exists(), Ok(()));
+
+    driver.host_send(&[1; 64]);
+    let mut report = [0; 64];
+    assert_eq!(CtapHid::receive(&mut report), Ok(()));
+    assert_eq!(report, [1; 64]);
+    assert!(!driver.receive_pending());
+
+    assert_eq!(CtapHid::send(&[2; 64]), Ok(()));
+    assert_eq!(driver.take_host_received(), [[2; 64]]);
+}
+
+#[test]
+fn messages() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::CtapHid::new();
+    kernel.add_driver(&driver);
+
+    let request = [3; 100];
+    // A packet of another channel, interleaved with the request, is refused.
+    let mut reports = Fragments::new(CID, command::CBOR, &request).unwrap();
+    driver.host_send(&reports.next().unwrap());
+    driver.host_send(
+        &Fragments::new(CID + 1, command::PING, &[])
+            .unwrap()
+            .next()
+            .unwrap(),
+    );
+    driver.host_send(&reports.next().unwrap());
+
+    let mut buffer = [0; 128];
+    let mut assembler = Assembler::new(&mut buffer);
+    let message = CtapHid::receive_message(&mut assembler).unwrap();
+    assert_eq!((message.cid, message.cmd), (CID, command::CBOR));
+    assert_eq!(assembler.payload(&message), request);
+    let refused = driver.take_host_received();
+    assert_eq!(refused.len(), 1);
+    let Packet::Init { cid, cmd, data, .. } = Packet::parse(&refused[0]) else {
+        panic!("Expected an ERROR message");
+    };
+    assert_eq!(
+        (cid, cmd, data[0]),
+        (CID + 1, command::ERROR, error::CHANNEL_BUSY)
+    );
+
+    assert_eq!(CtapHid::send_message(CID, command::CBOR, &[4; 80]), Ok(()));
+    let sent = driver.take_host_received();
+    let expected: Vec<Report> = Fragments::new(CID, command::CBOR, &[4; 80])
+        .unwrap()
+        .collect();
+    assert_eq!(sent, expected);
+}
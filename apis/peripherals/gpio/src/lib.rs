@@ -6,6 +6,12 @@ use libtock_platform::{
     share::Handle, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
 };
 
+mod rotary_encoder;
+mod soft_pwm;
+
+pub use rotary_encoder::{RotaryEncoder, Step};
+pub use soft_pwm::SoftPwm;
+
 /// The GPIO driver.
 ///
 /// # Example
@@ -250,6 +256,12 @@ impl<'a, S: Syscalls> embedded_hal::digital::OutputPin for OutputPin<'a, S> {
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod rotary_encoder_tests;
+
+#[cfg(test)]
+mod soft_pwm_tests;
+
 // -----------------------------------------------------------------------------
 // Driver number and command IDs
 // -----------------------------------------------------------------------------
@@ -0,0 +1,176 @@
+use crate::{Gpio, InputPin, PinInterruptEdge, Pull, DRIVER_NUM};
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::task::Poll;
+use libtock_future::TockStream;
+use libtock_platform::{
+    share::Handle, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+/// A step of a rotary encoder.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Step {
+    /// A step in which pin A changes before pin B.
+    Clockwise,
+    /// A step in which pin B changes before pin A.
+    CounterClockwise,
+}
+
+/// Decodes the quadrature signal of a rotary encoder wired to two GPIO pins,
+/// A and B, into steps.
+///
+/// The encoder listens to the interrupts of both pins, and tracks the
+/// quadrature state (the levels of A and B) through a state machine, which
+/// rejects transitions skipping a state and lets contact bounce cancel itself
+/// out. The transitions are accumulated, and every `steps_per_detent` of them
+/// in the same direction make a step: mechanical encoders usually go through
+/// the 4 states between two detents, so that a step is a click of the knob.
+///
+/// Steps are queued until they are taken, either one at a time with
+/// `take_step` or as a stream, or together with `take_steps`. If the steps come
+/// out reversed, swap A and B.
+///
+/// The GPIO driver has a single interrupt subscription, so the encoder cannot
+/// be used along with another interrupt listener; interrupts of other pins are
+/// ignored.
+///
+/// # Example
+/// ```ignore
+/// use libtock::gpio::{Gpio, PullUp, RotaryEncoder, Step};
+///
+/// let (mut a, mut b) = (Gpio::get_pin(0)?, Gpio::get_pin(1)?);
+/// let (a, b) = (a.make_input::<PullUp>()?, b.make_input::<PullUp>()?);
+/// let encoder = RotaryEncoder::new(&a, &b, 4)?;
+/// share::scope(|subscribe| {
+///     encoder.register(subscribe)?;
+///     loop {
+///         match block_on::<TockSyscalls, _>(next(&mut &encoder)) {
+///             Step::Clockwise => volume += 1,
+///             Step::CounterClockwise => volume -= 1,
+///         }
+///     }
+/// })
+/// ```
+pub struct RotaryEncoder<S: Syscalls> {
+    pin_a: u32,
+    pin_b: u32,
+    steps_per_detent: i32,
+    // The levels of A (bit 1) and B (bit 0).
+    state: Cell<u8>,
+    // The transitions since the last step, positive if clockwise.
+    transitions: Cell<i32>,
+    // The steps not taken yet, positive if clockwise.
+    steps: Cell<i32>,
+    _syscalls: PhantomData<S>,
+}
+
+impl<S: Syscalls> RotaryEncoder<S> {
+    /// Creates an encoder on pins `a` and `b`, enabling their interrupts on
+    /// both edges. `steps_per_detent` is raised to at least 1.
+    pub fn new<PA: Pull, PB: Pull>(
+        a: &InputPin<S, PA>,
+        b: &InputPin<S, PB>,
+        steps_per_detent: u8,
+    ) -> Result<Self, ErrorCode> {
+        let state = (a.read()? as u8) << 1 | b.read()? as u8;
+        a.enable_interrupts(PinInterruptEdge::Either)?;
+        b.enable_interrupts(PinInterruptEdge::Either)?;
+        Ok(RotaryEncoder {
+            pin_a: a.pin.pin_number,
+            pin_b: b.pin.pin_number,
+            steps_per_detent: steps_per_detent.max(1) as i32,
+            state: Cell::new(state),
+            transitions: Cell::new(0),
+            steps: Cell::new(0),
+            _syscalls: PhantomData,
+        })
+    }
+
+    /// Registers the encoder as the GPIO interrupt listener, replacing the
+    /// previous listener.
+    pub fn register<'share>(
+        &'share self,
+        subscribe: Handle<Subscribe<'share, S, DRIVER_NUM, 0>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, self)
+    }
+
+    /// Unregisters the GPIO interrupt listener, whether or not it is the
+    /// encoder.
+    pub fn unregister() {
+        Gpio::<S>::unregister_listener()
+    }
+
+    /// Returns the oldest step not taken yet, if any.
+    ///
+    /// Steps in opposite directions cancel each other out, so after the knob
+    /// is turned back and forth, only the net steps are returned.
+    pub fn take_step(&self) -> Option<Step> {
+        let steps = self.steps.get();
+        match steps.cmp(&0) {
+            Ordering::Greater => {
+                self.steps.set(steps - 1);
+                Some(Step::Clockwise)
+            }
+            Ordering::Less => {
+                self.steps.set(steps + 1);
+                Some(Step::CounterClockwise)
+            }
+            Ordering::Equal => None,
+        }
+    }
+
+    /// Returns the net number of steps not taken yet, positive if clockwise,
+    /// and takes them.
+    pub fn take_steps(&self) -> i32 {
+        self.steps.replace(0)
+    }
+}
+
+impl<S: Syscalls> Upcall<OneId<DRIVER_NUM, 0>> for RotaryEncoder<S> {
+    fn upcall(&self, pin: u32, value: u32, _arg2: u32) {
+        let previous = self.state.get();
+        let level = (value != 0) as u8;
+        let state = if pin == self.pin_a {
+            previous & 0b01 | level << 1
+        } else if pin == self.pin_b {
+            previous & 0b10 | level
+        } else {
+            return;
+        };
+        self.state.set(state);
+        let transitions = self.transitions.get() + TRANSITIONS[(previous << 2 | state) as usize];
+        if transitions >= self.steps_per_detent {
+            self.transitions.set(0);
+            self.steps.set(self.steps.get() + 1);
+        } else if transitions <= -self.steps_per_detent {
+            self.transitions.set(0);
+            self.steps.set(self.steps.get() - 1);
+        } else {
+            self.transitions.set(transitions);
+        }
+    }
+}
+
+impl<S: Syscalls> TockStream<S> for &RotaryEncoder<S> {
+    type Item = Step;
+
+    fn poll_next(&mut self) -> Poll<Step> {
+        match self.take_step() {
+            Some(step) => Poll::Ready(step),
+            None => Poll::Pending,
+        }
+    }
+}
+
+// The direction of each transition of the quadrature state, indexed by the
+// previous state and the new state. Clockwise, A leads B through the states
+// 00, 10, 11, 01. Transitions that skip a state are ambiguous, and ignored.
+#[rustfmt::skip]
+const TRANSITIONS: [i32; 16] = [
+    0, -1, 1, 0, // From 00
+    1, 0, 0, -1, // From 01
+    -1, 0, 0, 1, // From 10
+    0, 1, -1, 0, // From 11
+];
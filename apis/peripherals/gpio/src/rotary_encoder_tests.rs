@@ -0,0 +1,90 @@
+use libtock_future::{block_on, next};
+use libtock_platform::{share, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+use crate::{PullUp, RotaryEncoder, Step};
+
+type Gpio = super::Gpio<fake::Syscalls>;
+
+// Drives pins A (0) and B (1) through `states`, given as `(a, b)` levels,
+// and runs the resulting interrupts.
+fn turn(driver: &fake::Gpio<3>, states: &[(bool, bool)]) {
+    for &(a, b) in states {
+        driver.set_value(0, a).unwrap();
+        driver.set_value(1, b).unwrap();
+        while fake::Syscalls::yield_no_wait() == YieldNoWaitReturn::Upcall {}
+    }
+}
+
+// A clockwise detent, starting and ending with both pins high.
+const CLOCKWISE: [(bool, bool); 4] = [(false, true), (false, false), (true, false), (true, true)];
+const COUNTER_CLOCKWISE: [(bool, bool); 4] =
+    [(true, false), (false, false), (false, true), (true, true)];
+
+#[test]
+fn steps() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Gpio::<3>::new();
+    kernel.add_driver(&driver);
+    driver.set_value(0, true).unwrap();
+    driver.set_value(1, true).unwrap();
+
+    let (pin_a, pin_b) = (Gpio::get_pin(0).unwrap(), Gpio::get_pin(1).unwrap());
+    let (a, b) = (
+        pin_a.make_input::<PullUp>().unwrap(),
+        pin_b.make_input::<PullUp>().unwrap(),
+    );
+    let encoder = RotaryEncoder::new(&a, &b, 4).unwrap();
+    share::scope(|subscribe| {
+        encoder.register(subscribe).unwrap();
+
+        // A partial detent is not a step.
+        turn(&driver, &CLOCKWISE[..3]);
+        assert_eq!(encoder.take_step(), None);
+        turn(&driver, &CLOCKWISE[3..]);
+        assert_eq!(encoder.take_step(), Some(Step::Clockwise));
+        assert_eq!(encoder.take_step(), None);
+
+        turn(&driver, &COUNTER_CLOCKWISE);
+        turn(&driver, &COUNTER_CLOCKWISE);
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(next(&mut &encoder)),
+            Step::CounterClockwise
+        );
+        assert_eq!(encoder.take_steps(), -1);
+
+        // Opposite steps cancel out.
+        turn(&driver, &CLOCKWISE);
+        turn(&driver, &COUNTER_CLOCKWISE);
+        turn(&driver, &CLOCKWISE);
+        assert_eq!(encoder.take_steps(), 1);
+    });
+}
+
+#[test]
+fn bounce() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Gpio::<3>::new();
+    kernel.add_driver(&driver);
+
+    let (pin_a, pin_b) = (Gpio::get_pin(0).unwrap(), Gpio::get_pin(1).unwrap());
+    let (a, b) = (
+        pin_a.make_input::<PullUp>().unwrap(),
+        pin_b.make_input::<PullUp>().unwrap(),
+    );
+    let encoder = RotaryEncoder::new(&a, &b, 1).unwrap();
+    share::scope(|subscribe| {
+        encoder.register(subscribe).unwrap();
+
+        // Pin A bouncing back and forth, then settling.
+        turn(&driver, &[(true, false), (false, false), (true, false)]);
+        assert_eq!(encoder.take_steps(), 1);
+
+        // Interrupts reporting a level the pin already had, and interrupts of
+        // other pins, are ignored.
+        driver.inject_interrupt(0, true).unwrap();
+        driver.inject_interrupt(2, false).unwrap();
+        turn(&driver, &[]);
+        assert_eq!(encoder.take_steps(), 0);
+    });
+}
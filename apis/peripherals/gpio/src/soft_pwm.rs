@@ -0,0 +1,89 @@
+use crate::OutputPin;
+use core::cell::{Cell, RefCell};
+use libtock_alarm::{Alarm, Convert, Ticks};
+use libtock_future::{block_on, select, wait_for_upcall, Either, TockFuture};
+use libtock_platform::{share, ErrorCode, Syscalls};
+
+/// Drives a low-frequency PWM signal on a GPIO output pin, for pins without a
+/// hardware PWM channel, e.g. to dim an LED or drive a buzzer.
+///
+/// Each period, the pin is set high for the duty cycle and low for the rest,
+/// with the alarm timing both edges. The alarms are set relative to the
+/// previous edge, so the period does not drift, but the edges are late by the
+/// latency of the upcall, which makes the signal jittery at high frequencies.
+/// The alarm cannot be used for anything else while the signal is driven.
+///
+/// # Example
+/// ```ignore
+/// use libtock::alarm::Milliseconds;
+/// use libtock::gpio::{Gpio, SoftPwm};
+///
+/// let mut pin = Gpio::get_pin(0)?;
+/// let pwm = SoftPwm::new(pin.make_output()?, Milliseconds(20))?;
+/// pwm.set_duty(25);
+/// pwm.run_until(fade(&pwm))?;
+/// ```
+pub struct SoftPwm<'p, S: Syscalls> {
+    pin: RefCell<OutputPin<'p, S>>,
+    // In ticks.
+    period: u32,
+    high: Cell<u32>,
+}
+
+impl<'p, S: Syscalls> SoftPwm<'p, S> {
+    /// Creates a signal with the given `period` and a duty cycle of 0%. Fails
+    /// with `ErrorCode::Invalid` if the period is shorter than 2 ticks.
+    pub fn new<T: Convert>(pin: OutputPin<'p, S>, period: T) -> Result<Self, ErrorCode> {
+        let Ticks(period) = period.to_ticks(Alarm::<S>::get_frequency()?);
+        if period < 2 {
+            return Err(ErrorCode::Invalid);
+        }
+        Ok(SoftPwm {
+            pin: RefCell::new(pin),
+            period,
+            high: Cell::new(0),
+        })
+    }
+
+    /// Sets the duty cycle, in percent, clamped to 100. It takes effect at the
+    /// start of the next period.
+    pub fn set_duty(&self, percent: u8) {
+        let percent = u64::from(percent.min(100));
+        self.high
+            .set((u64::from(self.period) * percent / 100) as u32);
+    }
+
+    /// Drives the signal until `until` completes, and returns its output. The
+    /// pin is left low.
+    ///
+    /// Fails if the alarm or the pin fails, in which case `until` is dropped
+    /// before it completes.
+    pub fn run_until<F: TockFuture<S>>(&self, mut until: F) -> Result<F::Output, ErrorCode> {
+        let fired = Cell::new(None);
+        let result = share::scope(|subscribe| {
+            Alarm::<S>::register(&fired, subscribe)?;
+            let mut pin = self.pin.borrow_mut();
+            let mut edge = Alarm::<S>::get_ticks()?;
+            loop {
+                let high = self.high.get();
+                let phases = [(high, true), (self.period - high, false)];
+                for &(ticks, level) in phases.iter().filter(|&&(ticks, _)| ticks > 0) {
+                    match level {
+                        true => pin.set()?,
+                        false => pin.clear()?,
+                    }
+                    edge = Alarm::<S>::set_at(edge, Ticks(ticks))?;
+                    let upcall = wait_for_upcall(&fired);
+                    if let Either::Left(output) = block_on::<S, _>(select(&mut until, upcall)) {
+                        return Ok(output);
+                    }
+                }
+            }
+        });
+        // The alarm may have fired already.
+        let _ = Alarm::<S>::stop();
+        let cleared = self.pin.borrow_mut().clear();
+        let output = result?;
+        cleared.map(|()| output)
+    }
+}
@@ -0,0 +1,107 @@
+extern crate std;
+
+use core::task::Poll;
+use libtock_future::TockFuture;
+use libtock_platform::ErrorCode;
+use libtock_unittest::{fake, SyscallLogEntry};
+use std::rc::Rc;
+use std::vec::Vec;
+
+use crate::SoftPwm;
+use libtock_alarm::Ticks;
+
+type Gpio = crate::Gpio<fake::Syscalls>;
+
+// Completes once the alarm's tick counter reaches `.1`.
+struct Until(Rc<fake::Alarm>, u32);
+
+impl TockFuture<fake::Syscalls> for Until {
+    type Output = u32;
+
+    fn poll(&mut self) -> Poll<u32> {
+        match self.0.ticks() >= self.1 {
+            true => Poll::Ready(self.0.ticks()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+// Returns the pin writes, as levels, and the alarms, as (reference, dt) pairs.
+fn edges(kernel: &fake::Kernel) -> (Vec<bool>, Vec<(u32, u32)>) {
+    let (mut levels, mut alarms) = (Vec::new(), Vec::new());
+    for entry in kernel.take_syscall_log() {
+        match entry {
+            SyscallLogEntry::Command {
+                driver_id: 4,
+                command_id,
+                argument0: 0,
+                ..
+            } if command_id == 2 || command_id == 3 => levels.push(command_id == 2),
+            SyscallLogEntry::Command {
+                driver_id: 0,
+                command_id: 6,
+                argument0,
+                argument1,
+            } => alarms.push((argument0, argument1)),
+            _ => {}
+        }
+    }
+    (levels, alarms)
+}
+
+#[test]
+fn duty_cycle() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    let gpio = fake::Gpio::<1>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&gpio);
+    let mut pin = Gpio::get_pin(0).unwrap();
+    let pwm = SoftPwm::new(pin.make_output().unwrap(), Ticks(10)).unwrap();
+    pwm.set_duty(30);
+    kernel.take_syscall_log();
+
+    assert_eq!(pwm.run_until(Until(alarm.clone(), 25)), Ok(30));
+    let (levels, alarms) = edges(&kernel);
+    assert_eq!(levels, [true, false, true, false, true, false, false]);
+    assert_eq!(alarms, [(0, 3), (3, 7), (10, 3), (13, 7), (20, 3), (23, 7)]);
+    assert!(!gpio.get_gpio_state(0).unwrap().value);
+}
+
+#[test]
+fn full_and_zero_duty() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    let gpio = fake::Gpio::<1>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&gpio);
+    let mut pin = Gpio::get_pin(0).unwrap();
+    let pwm = SoftPwm::new(pin.make_output().unwrap(), Ticks(10)).unwrap();
+    pwm.set_duty(150);
+    kernel.take_syscall_log();
+
+    assert_eq!(pwm.run_until(Until(alarm.clone(), 15)), Ok(20));
+    let (levels, alarms) = edges(&kernel);
+    assert_eq!(levels, [true, true, false]);
+    assert_eq!(alarms, [(0, 10), (10, 10)]);
+
+    pwm.set_duty(0);
+    assert_eq!(pwm.run_until(Until(alarm.clone(), 35)), Ok(40));
+    let (levels, alarms) = edges(&kernel);
+    assert_eq!(levels, [false, false, false]);
+    assert_eq!(alarms, [(20, 10), (30, 10)]);
+}
+
+#[test]
+fn short_period() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::new(1000);
+    let gpio = fake::Gpio::<1>::new();
+    kernel.add_driver(&alarm);
+    kernel.add_driver(&gpio);
+    let mut pin = Gpio::get_pin(0).unwrap();
+    assert!(matches!(
+        SoftPwm::new(pin.make_output().unwrap(), Ticks(1)),
+        Err(ErrorCode::Invalid)
+    ));
+}
@@ -11,7 +11,7 @@ pub struct I2CMaster<S: Syscalls, C: Config = DefaultConfig>(S, C);
 
 impl<S: Syscalls, C: Config> I2CMaster<S, C> {
     pub fn exists() -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, i2c_master_cmd::EXISTS, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, i2c_master_cmd::EXISTS, 0, 0).to_result()
     }
 
     /// # Summary
@@ -55,7 +55,7 @@ impl<S: Syscalls, C: Config> I2CMaster<S, C> {
                 subscribe, &called,
             )?;
 
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 i2c_master_cmd::MASTER_WRITE,
                 cmd_arg0,
@@ -106,7 +106,7 @@ impl<S: Syscalls, C: Config> I2CMaster<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { rw_allow::MASTER }>(allow_rw, buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::MASTER_WRITE }>(subscribe, &called)?;
 
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 i2c_master_cmd::MASTER_WRITE,
                 addr.into(),
@@ -157,7 +157,7 @@ impl<S: Syscalls, C: Config> I2CMaster<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { rw_allow::MASTER }>(allow_rw, buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::MASTER_READ }>(subscribe, &called)?;
 
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 i2c_master_cmd::MASTER_READ,
                 addr.into(),
@@ -181,11 +181,18 @@ impl<S: Syscalls, C: Config> I2CMaster<S, C> {
 
 /// System call configuration trait for `I2CMaster`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
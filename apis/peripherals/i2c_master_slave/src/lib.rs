@@ -12,7 +12,7 @@ pub struct I2CMasterSlave<S: Syscalls, C: Config = DefaultConfig>(S, C);
 
 impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
     pub fn exists() -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, i2c_master_slave_cmd::EXISTS, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, i2c_master_slave_cmd::EXISTS, 0, 0).to_result()
     }
 
     /// # Summary
@@ -55,7 +55,8 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
 
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::MASTER_WRITE }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, i2c_master_slave_cmd::MASTER_WRITE, cmd_arg0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, i2c_master_slave_cmd::MASTER_WRITE, cmd_arg0, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -113,7 +114,8 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::MASTER_READ }>(subscribe, &called)?;
             // When this fails, `called` is guaranteed unmodified,
             // because upcalls are never processed until we call `yield`.
-            S::command(DRIVER_NUM, i2c_master_slave_cmd::MASTER_READ, cmd_arg0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, i2c_master_slave_cmd::MASTER_READ, cmd_arg0, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -202,7 +204,7 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
             )?;
             // When this fails, `called` is guaranteed unmodified,
             // because upcalls are never processed until we call `yield`.
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 i2c_master_slave_cmd::MASTER_WRITE_READ,
                 cmd_arg0,
@@ -252,7 +254,7 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
         if addr > 0x7f {
             return Err(ErrorCode::Invalid);
         }
-        S::command(
+        S::checked_command::<C>(
             DRIVER_NUM,
             i2c_master_slave_cmd::SLAVE_SET_ADDR,
             addr as u32,
@@ -293,7 +295,8 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { rw_allow::SLAVE_RX }>(allow_rw, buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SLAVE_READ }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, i2c_master_slave_cmd::SLAVE_START_LISTEN, 0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, i2c_master_slave_cmd::SLAVE_START_LISTEN, 0, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -350,7 +353,7 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
             S::allow_ro::<C, DRIVER_NUM, { ro_allow::SLAVE_TX }>(allow_ro, buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::SLAVE_READ }>(subscribe, &called)?;
 
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 i2c_master_slave_cmd::SLAVE_READ_SEND,
                 len as u32,
@@ -380,11 +383,18 @@ impl<S: Syscalls, C: Config> I2CMasterSlave<S, C> {
 
 /// System call configuration trait for `I2CMaster`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
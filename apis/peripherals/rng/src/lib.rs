@@ -81,6 +81,9 @@ impl<F: Fn(u32)> Upcall<OneId<DRIVER_NUM, 0>> for RngListener<F> {
     }
 }
 
+#[cfg(test)]
+mod tests;
+
 // -------------
 // DRIVER NUMBER
 // -------------
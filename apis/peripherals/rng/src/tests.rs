@@ -0,0 +1,50 @@
+use core::cell::Cell;
+use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+type Rng = super::Rng<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Rng::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&fake::Rng::new());
+    assert_eq!(Rng::exists(), Ok(()));
+}
+
+#[test]
+fn get_bytes_sync() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&fake::Rng::with_seed(1234));
+    let mut expected = [0; 8];
+    fake::Rng::with_seed(1234).fill(&mut expected[..5]);
+
+    let mut buffer = [0; 8];
+    assert_eq!(Rng::get_bytes_sync(&mut buffer, 5), Ok(()));
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn get_bytes_async() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&fake::Rng::new());
+
+    let filled = Cell::new(None);
+    let listener = crate::RngListener(|count| filled.set(Some(count)));
+    let mut buffer = [0; 4];
+    share::scope(|allow_rw| {
+        Rng::allow_buffer(&mut buffer, allow_rw).unwrap();
+        share::scope(|subscribe| {
+            Rng::register_listener(&listener, subscribe).unwrap();
+            assert_eq!(Rng::get_bytes_async(16), Ok(()));
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        });
+    });
+    assert_eq!(filled.get(), Some(4));
+    assert_ne!(buffer, [0; 4]);
+}
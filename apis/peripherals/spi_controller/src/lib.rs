@@ -6,13 +6,13 @@ use libtock_platform::allow_rw::AllowRw;
 use libtock_platform::share;
 use libtock_platform::subscribe::Subscribe;
 use libtock_platform::AllowRo;
-use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+use libtock_platform::{Align, AlignedBuf, Alignment, DefaultConfig, ErrorCode, Syscalls};
 
 pub struct SpiController<S: Syscalls, C: Config = DefaultConfig>(S, C);
 
 impl<S: Syscalls, C: Config> SpiController<S, C> {
     pub fn exists() -> Result<(), ErrorCode> {
-        S::command(DRIVER_NUM, spi_controller_cmd::EXISTS, 0, 0).to_result()
+        S::checked_command::<C>(DRIVER_NUM, spi_controller_cmd::EXISTS, 0, 0).to_result()
     }
 
     /// # Summary
@@ -55,7 +55,8 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
             S::allow_ro::<C, DRIVER_NUM, { ro_allow::WRITE }>(allow_ro, w_buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::COMPLETE }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, spi_controller_cmd::READ_WRITE_BYTES, len, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, spi_controller_cmd::READ_WRITE_BYTES, len, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -70,6 +71,24 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
         })
     }
 
+    /// Performs `spi_controller_write_read_sync` with word-aligned buffers,
+    /// which controllers that transfer by DMA require. Fails to build if the
+    /// buffers are aligned to less than 4 bytes.
+    pub fn spi_controller_write_read_dma_sync<const N: usize, const A: usize>(
+        w_buf: &AlignedBuf<N, A>,
+        r_buf: &mut AlignedBuf<N, A>,
+        len: u32,
+    ) -> Result<(), ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        Self::spi_controller_write_read_sync(
+            w_buf.aligned_to::<DMA_ALIGN>(),
+            r_buf.aligned_to_mut::<DMA_ALIGN>(),
+            len,
+        )
+    }
+
     pub fn spi_controller_write_sync(w_buf: &[u8], len: u32) -> Result<(), ErrorCode> {
         if len as usize > w_buf.len() {
             return Err(ErrorCode::NoMem);
@@ -88,7 +107,8 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
             S::allow_ro::<C, DRIVER_NUM, { ro_allow::WRITE }>(allow_ro, w_buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::COMPLETE }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, spi_controller_cmd::READ_WRITE_BYTES, len, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, spi_controller_cmd::READ_WRITE_BYTES, len, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -121,7 +141,8 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { rw_allow::READ }>(allow_rw, r_buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::COMPLETE }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, spi_controller_cmd::READ_BYTES, len, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, spi_controller_cmd::READ_BYTES, len, 0)
+                .to_result()?;
 
             loop {
                 S::yield_wait();
@@ -157,7 +178,7 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
             S::allow_rw::<C, DRIVER_NUM, { rw_allow::READ }>(allow_rw, r_buf)?;
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::COMPLETE }>(subscribe, &called)?;
 
-            S::command(
+            S::checked_command::<C>(
                 DRIVER_NUM,
                 spi_controller_cmd::INPLACE_READ_WRITE_BYTES,
                 len,
@@ -181,11 +202,18 @@ impl<S: Syscalls, C: Config> SpiController<S, C> {
 
 /// System call configuration trait for `SpiController`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
@@ -194,6 +222,9 @@ impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subs
 // -----------------------------------------------------------------------------
 const DRIVER_NUM: u32 = 0x20001;
 
+// The alignment of buffers transferred by DMA.
+const DMA_ALIGN: usize = 4;
+
 #[allow(unused)]
 mod subscribe {
     pub const COMPLETE: u32 = 0;
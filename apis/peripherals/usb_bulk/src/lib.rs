@@ -0,0 +1,209 @@
+//! Raw access to a pair of USB bulk endpoints, for custom host tools that move
+//! large amounts of data, such as flash logs or firmware images, faster than a
+//! CDC-ACM console can.
+//!
+//! Upstream Tock has no bulk endpoint capsule, so boards that provide one
+//! number it themselves, and [`UsbBulk`] takes the number as `DRIVER_NUM`. The
+//! capsule is expected to implement this API:
+//!
+//! - Command 0 checks whether the driver exists.
+//! - Command 1 queues a buffer for the OUT endpoint: the next transfer from the
+//!   host, of up to `argument0` bytes, is received into read-write allow 0.
+//!   Subscribe 0 is called with the status (0 or an `ErrorCode`) and the
+//!   length of the transfer once it is received.
+//! - Command 2 submits the first `argument0` bytes of read-only allow 0 to the
+//!   IN endpoint, as one transfer to the host. Subscribe 1 is called with the
+//!   status and the number of bytes transferred once the host has read them.
+//! - Command 3 aborts the queued OUT and submitted IN transfers, which complete
+//!   with `ErrorCode::Cancel`.
+//!
+//! A transfer can be longer than the endpoints' maximum packet size: the
+//! capsule splits it into packets, and an OUT transfer ends with a short
+//! packet, as usual for bulk endpoints.
+
+#![no_std]
+
+use core::cell::Cell;
+use core::task::Poll;
+use libtock_future::{block_on, wait_for_upcall, TockFuture, UpcallFuture};
+use libtock_platform as platform;
+use libtock_platform::allow_ro::AllowRo;
+use libtock_platform::allow_rw::AllowRw;
+use libtock_platform::share;
+use libtock_platform::subscribe::Subscribe;
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+/// The USB bulk endpoints of the driver numbered `DRIVER_NUM`.
+///
+/// # Example
+/// ```ignore
+/// use libtock::usb_bulk::UsbBulk;
+///
+/// type Usb = UsbBulk<0x90100>;
+///
+/// let mut command = [0; 64];
+/// let len = Usb::read(&mut command)?;
+/// for chunk in log.chunks(4096) {
+///     Usb::write(chunk)?;
+/// }
+/// ```
+pub struct UsbBulk<S: Syscalls, const DRIVER_NUM: u32, C: Config = DefaultConfig>(S, C);
+
+impl<S: Syscalls, const DRIVER_NUM: u32, C: Config> UsbBulk<S, DRIVER_NUM, C> {
+    /// Run a check against the USB bulk capsule to ensure it is present.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Receives a transfer from the host into `buffer`, and returns its length.
+    pub fn read(buffer: &mut [u8]) -> Result<usize, ErrorCode> {
+        let done = Cell::new(None);
+        share::scope::<(OutBuffer<S, DRIVER_NUM>, OutSubscribe<S, DRIVER_NUM>), _, _>(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            let transfer = Self::queue_out(buffer, &done, allow_rw, subscribe)?;
+            block_on::<S, _>(transfer)
+        })
+    }
+
+    /// Sends `data` to the host as one transfer, and returns the number of
+    /// bytes the host read.
+    pub fn write(data: &[u8]) -> Result<usize, ErrorCode> {
+        let done = Cell::new(None);
+        share::scope::<(InBuffer<S, DRIVER_NUM>, InSubscribe<S, DRIVER_NUM>), _, _>(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            let transfer = Self::submit_in(data, &done, allow_ro, subscribe)?;
+            block_on::<S, _>(transfer)
+        })
+    }
+
+    /// Queues `buffer` to receive the next transfer from the host in the
+    /// background, to wait for it along with other events. The kernel can
+    /// write `buffer` until the end of the handles' scope, and the returned
+    /// future completes with the transfer's length once it is received.
+    pub fn queue_out<'share>(
+        buffer: &'share mut [u8],
+        done: &'share Cell<Option<(u32, u32)>>,
+        allow_rw: share::Handle<OutBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<OutSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Result<Transfer<'share>, ErrorCode> {
+        let len = buffer.len();
+        S::allow_rw::<C, DRIVER_NUM, { allow_rw::OUT }>(allow_rw, buffer)?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::OUT }>(subscribe, done)?;
+        S::checked_command::<C>(DRIVER_NUM, command::RECEIVE, len as u32, 0)
+            .to_result::<(), _>()?;
+        Ok(Transfer::new(done))
+    }
+
+    /// Submits `data` to be sent to the host as one transfer in the
+    /// background, to wait for it along with other events. The kernel can
+    /// read `data` until the end of the handles' scope, and the returned
+    /// future completes with the number of bytes transferred once the host has
+    /// read them.
+    pub fn submit_in<'share>(
+        data: &'share [u8],
+        done: &'share Cell<Option<(u32, u32)>>,
+        allow_ro: share::Handle<InBuffer<'share, S, DRIVER_NUM>>,
+        subscribe: share::Handle<InSubscribe<'share, S, DRIVER_NUM>>,
+    ) -> Result<Transfer<'share>, ErrorCode> {
+        S::allow_ro::<C, DRIVER_NUM, { allow_ro::IN }>(allow_ro, data)?;
+        S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::IN }>(subscribe, done)?;
+        S::checked_command::<C>(DRIVER_NUM, command::TRANSMIT, data.len() as u32, 0)
+            .to_result::<(), _>()?;
+        Ok(Transfer::new(done))
+    }
+
+    /// Aborts the queued OUT transfer and the submitted IN transfer, whose
+    /// futures complete with `ErrorCode::Cancel`.
+    pub fn abort() -> Result<(), ErrorCode> {
+        S::checked_command::<C>(DRIVER_NUM, command::ABORT, 0, 0).to_result()
+    }
+}
+
+/// A future that completes with the length of a transfer, once the transfer
+/// is over. See [`UsbBulk::queue_out`] and [`UsbBulk::submit_in`].
+pub struct Transfer<'share> {
+    done: UpcallFuture<'share, (u32, u32)>,
+}
+
+impl<'share> Transfer<'share> {
+    fn new(done: &'share Cell<Option<(u32, u32)>>) -> Self {
+        Transfer {
+            done: wait_for_upcall(done),
+        }
+    }
+}
+
+impl<S: Syscalls> TockFuture<S> for Transfer<'_> {
+    type Output = Result<usize, ErrorCode>;
+
+    fn poll(&mut self) -> Poll<Result<usize, ErrorCode>> {
+        let Poll::Ready((status, len)) = TockFuture::<S>::poll(&mut self.done) else {
+            return Poll::Pending;
+        };
+        Poll::Ready(match status {
+            0 => Ok(len as usize),
+            status => Err(status.try_into().unwrap_or(ErrorCode::Fail)),
+        })
+    }
+}
+
+/// The buffer shared by [`UsbBulk::queue_out`].
+pub type OutBuffer<'share, S, const DRIVER_NUM: u32> =
+    AllowRw<'share, S, DRIVER_NUM, { allow_rw::OUT }>;
+
+/// The subscription of [`UsbBulk::queue_out`].
+pub type OutSubscribe<'share, S, const DRIVER_NUM: u32> =
+    Subscribe<'share, S, DRIVER_NUM, { subscribe::OUT }>;
+
+/// The data shared by [`UsbBulk::submit_in`].
+pub type InBuffer<'share, S, const DRIVER_NUM: u32> =
+    AllowRo<'share, S, DRIVER_NUM, { allow_ro::IN }>;
+
+/// The subscription of [`UsbBulk::submit_in`].
+pub type InSubscribe<'share, S, const DRIVER_NUM: u32> =
+    Subscribe<'share, S, DRIVER_NUM, { subscribe::IN }>;
+
+/// System call configuration trait for `UsbBulk`.
+pub trait Config:
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
+{
+}
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
+{
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Command IDs
+// -----------------------------------------------------------------------------
+
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const RECEIVE: u32 = 1;
+    pub const TRANSMIT: u32 = 2;
+    pub const ABORT: u32 = 3;
+}
+
+mod subscribe {
+    pub const OUT: u32 = 0;
+    pub const IN: u32 = 1;
+}
+
+mod allow_ro {
+    pub const IN: u32 = 0;
+}
+
+mod allow_rw {
+    pub const OUT: u32 = 0;
+}
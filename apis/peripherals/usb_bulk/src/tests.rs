@@ -0,0 +1,83 @@
+use core::cell::Cell;
+use libtock_future::{block_on, select, Either};
+use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+use crate::{InBuffer, InSubscribe, OutBuffer, OutSubscribe};
+
+type UsbBulk = super::UsbBulk<fake::Syscalls, DRIVER_NUM>;
+
+const DRIVER_NUM: u32 = 0x90100;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(UsbBulk::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn read_write() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::UsbBulk::new(DRIVER_NUM);
+    kernel.add_driver(&driver);
+    assert_eq!(UsbBulk::exists(), Ok(()));
+
+    driver.host_send(b"log?");
+    let mut buffer = [0; 64];
+    assert_eq!(UsbBulk::read(&mut buffer), Ok(4));
+    assert_eq!(&buffer[..4], b"log?");
+
+    assert_eq!(UsbBulk::write(&[7; 512]), Ok(512));
+    assert_eq!(driver.take_host_received(), [[7; 512].to_vec()]);
+}
+
+#[test]
+fn futures() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::UsbBulk::new(DRIVER_NUM);
+    kernel.add_driver(&driver);
+
+    let mut buffer = [0; 8];
+    let (received, sent) = (Cell::new(None), Cell::new(None));
+    share::scope::<
+        (
+            OutBuffer<_, DRIVER_NUM>,
+            OutSubscribe<_, DRIVER_NUM>,
+            InBuffer<_, DRIVER_NUM>,
+            InSubscribe<_, DRIVER_NUM>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, subscribe_out, allow_ro, subscribe_in) = handle.split();
+        let mut out = UsbBulk::queue_out(&mut buffer, &received, allow_rw, subscribe_out).unwrap();
+        let mut in_ = UsbBulk::submit_in(b"chunk", &sent, allow_ro, subscribe_in).unwrap();
+        // The host reads IN first, and then sends OUT.
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(select(&mut out, &mut in_)),
+            Either::Right(Ok(5))
+        );
+        driver.host_send(b"ack");
+        assert_eq!(block_on::<fake::Syscalls, _>(&mut out), Ok(3));
+    });
+    assert_eq!(&buffer[..3], b"ack");
+    assert_eq!(driver.take_host_received(), [b"chunk".to_vec()]);
+}
+
+#[test]
+fn abort() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::UsbBulk::new(DRIVER_NUM);
+    kernel.add_driver(&driver);
+
+    let mut buffer = [0; 8];
+    let received = Cell::new(None);
+    share::scope::<(OutBuffer<_, DRIVER_NUM>, OutSubscribe<_, DRIVER_NUM>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        let out = UsbBulk::queue_out(&mut buffer, &received, allow_rw, subscribe).unwrap();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        assert_eq!(UsbBulk::abort(), Ok(()));
+        assert_eq!(block_on::<fake::Syscalls, _>(out), Err(ErrorCode::Cancel));
+    });
+    assert!(!driver.out_queued());
+}
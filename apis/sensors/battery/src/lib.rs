@@ -0,0 +1,135 @@
+#![no_std]
+
+use libtock_platform::{
+    share, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+/// The battery driver.
+///
+/// It reports the state of the device's battery, as measured by a fuel gauge
+/// or charger chip, and notifies the app when the charge drops below a
+/// threshold, so it can e.g. throttle radio activity as the battery depletes.
+///
+/// # Example
+/// ```ignore
+/// use libtock::battery::{Battery, LowBatteryListener};
+///
+/// let low = Cell::new(false);
+/// let listener = LowBatteryListener(|_percentage| low.set(true));
+/// share::scope(|subscribe| {
+///     Battery::register_low_battery_listener(&listener, subscribe)?;
+///     Battery::set_low_threshold(20)?;
+///     // ...
+/// });
+/// ```
+///
+/// Boards that register the capsule under another driver number pass it as
+/// `DRIVER_NUM`:
+/// ```ignore
+/// type Battery = libtock_battery::Battery<TockSyscalls, 0x90100>;
+/// ```
+pub struct Battery<S: Syscalls, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM>(S);
+
+impl<S: Syscalls, const DRIVER_NUM: u32> Battery<S, DRIVER_NUM> {
+    /// Run a check against the battery capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Returns the battery voltage, in millivolts.
+    pub fn voltage_mv() -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, command::VOLTAGE_MV, 0, 0).to_result()
+    }
+
+    /// Returns the remaining charge, as a percentage (0 to 100).
+    pub fn percentage() -> Result<u32, ErrorCode> {
+        S::command(DRIVER_NUM, command::PERCENTAGE, 0, 0).to_result()
+    }
+
+    /// Returns whether the battery is charging.
+    pub fn charging_state() -> Result<ChargingState, ErrorCode> {
+        S::command(DRIVER_NUM, command::CHARGING_STATE, 0, 0)
+            .to_result::<u32, ErrorCode>()
+            .map(ChargingState::from)
+    }
+
+    /// Sets the threshold for the low-battery event: the registered listener
+    /// is called when the charge drops below `percentage`. A threshold of 0
+    /// disables the event.
+    pub fn set_low_threshold(percentage: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SET_LOW_THRESHOLD, percentage, 0).to_result()
+    }
+
+    /// Register a low-battery events listener
+    pub fn register_low_battery_listener<'share, F: Fn(u32)>(
+        listener: &'share LowBatteryListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, 0>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, listener)
+    }
+
+    /// Unregister the low-battery events listener
+    pub fn unregister_low_battery_listener() {
+        S::unsubscribe(DRIVER_NUM, 0)
+    }
+}
+
+/// Whether the battery is charging, as reported by `Battery::charging_state`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChargingState {
+    /// The device runs from the battery.
+    Discharging,
+    /// External power is present, and the battery is charging.
+    Charging,
+    /// External power is present, and the battery is full.
+    Full,
+    /// No battery is connected.
+    NotPresent,
+    /// A state this API does not know about, with the kernel's raw value.
+    Other(u32),
+}
+
+impl From<u32> for ChargingState {
+    fn from(value: u32) -> ChargingState {
+        match value {
+            0 => ChargingState::Discharging,
+            1 => ChargingState::Charging,
+            2 => ChargingState::Full,
+            3 => ChargingState::NotPresent,
+            other => ChargingState::Other(other),
+        }
+    }
+}
+
+/// A listener for the low-battery event. It is called with the remaining
+/// charge, as a percentage.
+pub struct LowBatteryListener<F: Fn(u32)>(pub F);
+impl<F: Fn(u32), const DRIVER_NUM: u32> Upcall<OneId<DRIVER_NUM, 0>> for LowBatteryListener<F> {
+    fn upcall(&self, percentage: u32, _arg1: u32, _arg2: u32) {
+        self.0(percentage)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+// Upstream Tock assigns no driver number for a battery or fuel gauge; see
+// doc/DriverNumbers.md for how libtock-rs picked this one.
+const DEFAULT_DRIVER_NUM: u32 = 0x9006C;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const VOLTAGE_MV: u32 = 1;
+    pub const PERCENTAGE: u32 = 2;
+    pub const CHARGING_STATE: u32 = 3;
+    pub const SET_LOW_THRESHOLD: u32 = 4;
+}
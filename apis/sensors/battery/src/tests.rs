@@ -0,0 +1,72 @@
+use super::*;
+use core::cell::Cell;
+use libtock_platform::{share, ErrorCode, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+type Battery = super::Battery<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Battery::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Battery::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Battery::exists(), Ok(()));
+}
+
+#[test]
+fn state() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Battery::new();
+    kernel.add_driver(&driver);
+
+    driver.set_voltage_mv(3850);
+    driver.set_percentage(72);
+    assert_eq!(Battery::voltage_mv(), Ok(3850));
+    assert_eq!(Battery::percentage(), Ok(72));
+    for (value, state) in [
+        (0, ChargingState::Discharging),
+        (1, ChargingState::Charging),
+        (2, ChargingState::Full),
+        (3, ChargingState::NotPresent),
+        (7, ChargingState::Other(7)),
+    ] {
+        driver.set_charging_state(value);
+        assert_eq!(Battery::charging_state(), Ok(state));
+    }
+}
+
+#[test]
+fn low_battery_listener() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Battery::new();
+    kernel.add_driver(&driver);
+
+    let low: Cell<Option<u32>> = Cell::new(None);
+    let listener = LowBatteryListener(|percentage| low.set(Some(percentage)));
+    share::scope(|subscribe| {
+        assert_eq!(
+            Battery::register_low_battery_listener(&listener, subscribe),
+            Ok(())
+        );
+        assert_eq!(Battery::set_low_threshold(101), Err(ErrorCode::Invalid));
+        assert_eq!(Battery::set_low_threshold(20), Ok(()));
+        assert_eq!(driver.low_threshold(), 20);
+        driver.set_percentage(30);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        driver.set_percentage(15);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(low.get(), Some(15));
+
+        Battery::unregister_low_battery_listener();
+        driver.set_percentage(50);
+        driver.set_percentage(10);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+}
@@ -0,0 +1,189 @@
+//! Support for GNSS (GPS, Galileo, ...) receivers connected to a UART.
+//!
+//! Receivers output their fixes as NMEA 0183 sentences, which [`Gnss`] reads
+//! through a console-compatible UART driver, typically a board's secondary
+//! UART, and parses into [`Fix`]es. The [`nmea`] module parses sentences from
+//! other sources.
+
+#![cfg_attr(not(test), no_std)]
+
+use core::marker::PhantomData;
+use libtock_console::{Config, Console};
+use libtock_platform::{DefaultConfig, ErrorCode, Syscalls};
+
+pub mod nmea;
+
+use nmea::{Date, Decoder, NmeaError, Position, Sentence, Time};
+
+/// A position fix, merged from the RMC and GGA sentences of the receiver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fix {
+    pub time: Time,
+    /// The date, once an RMC sentence has reported it.
+    pub date: Option<Date>,
+    pub position: Position,
+    /// The horizontal dilution of precision in hundredths, once a GGA sentence
+    /// has reported it for the fix's time.
+    pub hdop: Option<u16>,
+    /// The number of satellites used, once a GGA sentence has reported it for
+    /// the fix's time.
+    pub satellites: Option<u8>,
+}
+
+/// A GNSS receiver that outputs NMEA sentences to the UART driver numbered
+/// `DRIVER_NUM`, which must implement the console API.
+///
+/// Each RMC or GGA sentence that reports a position updates the fix, so most
+/// receivers update it twice per epoch; the second update completes the fix
+/// with the fields only the other sentence has. Sentences reporting that the
+/// receiver has no fix clear it.
+///
+/// The UART is read in chunks of 16 bytes, and a console read only completes
+/// once its buffer is full, so a fix is only parsed once the next 16 bytes of
+/// output arrived. Receivers output several sentences per epoch, so this
+/// usually happens within the same epoch.
+///
+/// # Example
+/// ```ignore
+/// use libtock::gnss::Gnss;
+///
+/// let mut gnss = Gnss::<3>::new();
+/// for fix in gnss.fixes() {
+///     let fix = fix?;
+///     writeln!(writer, "{} {}", fix.position.latitude, fix.position.longitude)?;
+/// }
+/// ```
+pub struct Gnss<S: Syscalls, const DRIVER_NUM: u32, C: Config = DefaultConfig> {
+    decoder: Decoder,
+    // Bytes read from the UART, of which those from `read_pos` on have not been
+    // decoded yet.
+    read_buffer: [u8; READ_LEN],
+    read_pos: usize,
+    read_len: usize,
+    fix: Option<Fix>,
+    errors: u32,
+    _syscalls: PhantomData<(S, C)>,
+}
+
+impl<S: Syscalls, const DRIVER_NUM: u32, C: Config> Gnss<S, DRIVER_NUM, C> {
+    pub fn new() -> Self {
+        Gnss {
+            decoder: Decoder::new(),
+            read_buffer: [0; READ_LEN],
+            read_pos: 0,
+            read_len: 0,
+            fix: None,
+            errors: 0,
+            _syscalls: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the UART driver is present.
+    pub fn exists() -> bool {
+        Console::<S, C, DRIVER_NUM>::exists()
+    }
+
+    /// Returns the latest fix, or `None` if the receiver has not reported one
+    /// or has lost it.
+    pub fn fix(&self) -> Option<&Fix> {
+        self.fix.as_ref()
+    }
+
+    /// Returns the number of sentences that were garbled or malformed.
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Reads from the UART until the fix is updated, and returns the new fix.
+    pub fn next_fix(&mut self) -> Result<Fix, ErrorCode> {
+        loop {
+            while self.read_pos < self.read_len {
+                let byte = self.read_buffer[self.read_pos];
+                self.read_pos += 1;
+                match self.decoder.push(byte) {
+                    Some(Ok(sentence)) => {
+                        if let Some(fix) = self.update(sentence) {
+                            return Ok(fix);
+                        }
+                    }
+                    Some(Err(NmeaError::Unsupported)) | None => {}
+                    Some(Err(_)) => self.errors += 1,
+                }
+            }
+            // Bytes received by a failed read are still decoded by the next
+            // call.
+            let (count, result) = Console::<S, C, DRIVER_NUM>::read(&mut self.read_buffer);
+            self.read_pos = 0;
+            self.read_len = count;
+            result?;
+        }
+    }
+
+    /// Returns an endless iterator over the updates of the fix, which reads
+    /// from the UART like `next_fix`.
+    pub fn fixes(&mut self) -> Fixes<'_, S, DRIVER_NUM, C> {
+        Fixes { gnss: self }
+    }
+
+    // Merges `sentence` into the fix, and returns the fix if it was updated.
+    fn update(&mut self, sentence: Sentence) -> Option<Fix> {
+        let previous = self.fix.take();
+        self.fix = match sentence {
+            Sentence::Rmc(rmc) if rmc.valid => {
+                // GGA sentences of the same epoch are merged.
+                let same_epoch = previous.filter(|fix| fix.time == rmc.time);
+                rmc.position.map(|position| Fix {
+                    time: rmc.time,
+                    date: Some(rmc.date),
+                    position,
+                    hdop: same_epoch.and_then(|fix| fix.hdop),
+                    satellites: same_epoch.and_then(|fix| fix.satellites),
+                })
+            }
+            Sentence::Gga(gga) if gga.quality > 0 => gga.position.map(|position| Fix {
+                time: gga.time,
+                // The date has not changed unless midnight has passed.
+                date: previous
+                    .filter(|fix| fix.time <= gga.time)
+                    .and_then(|fix| fix.date),
+                position,
+                hdop: gga.hdop,
+                satellites: Some(gga.satellites),
+            }),
+            _ => None,
+        };
+        self.fix
+    }
+}
+
+impl<S: Syscalls, const DRIVER_NUM: u32, C: Config> Default for Gnss<S, DRIVER_NUM, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over the updates of a receiver's fix. See [`Gnss::fixes`].
+pub struct Fixes<'a, S: Syscalls, const DRIVER_NUM: u32, C: Config> {
+    gnss: &'a mut Gnss<S, DRIVER_NUM, C>,
+}
+
+impl<S: Syscalls, const DRIVER_NUM: u32, C: Config> Iterator for Fixes<'_, S, DRIVER_NUM, C> {
+    type Item = Result<Fix, ErrorCode>;
+
+    fn next(&mut self) -> Option<Result<Fix, ErrorCode>> {
+        Some(self.gnss.next_fix())
+    }
+}
+
+#[cfg(test)]
+mod nmea_tests;
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// The number of bytes read from the UART at a time.
+const READ_LEN: usize = 16;
@@ -0,0 +1,321 @@
+//! Parsing of the NMEA 0183 sentences GNSS receivers output.
+//!
+//! Only the sentences that carry a position fix are parsed: RMC (recommended
+//! minimum data) and GGA (fix data), from any talker (`$GPRMC`, `$GNGGA`,
+//! ...). Sentences must end with a checksum, so that lines garbled on the UART
+//! are rejected instead of producing wrong fixes.
+
+/// A time of day, in UTC.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub millisecond: u16,
+}
+
+/// A calendar date, in UTC.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// A position, in ten-millionths of a degree (the resolution of the
+/// receivers' output). Latitudes are positive north of the equator, and
+/// longitudes east of the prime meridian.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Position {
+    pub latitude: i32,
+    pub longitude: i32,
+}
+
+/// An RMC sentence: the recommended minimum fix data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rmc {
+    pub time: Time,
+    /// Whether the receiver has a fix (the `A` status).
+    pub valid: bool,
+    pub position: Option<Position>,
+    pub date: Date,
+}
+
+/// A GGA sentence: the fix data, along with its quality.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Gga {
+    pub time: Time,
+    pub position: Option<Position>,
+    /// The fix quality indicator: 0 if there is no fix, 1 for a GPS fix, 2 for
+    /// a differential fix, and so on.
+    pub quality: u8,
+    /// The number of satellites used for the fix.
+    pub satellites: u8,
+    /// The horizontal dilution of precision, in hundredths.
+    pub hdop: Option<u16>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sentence {
+    Rmc(Rmc),
+    Gga(Gga),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NmeaError {
+    /// The line is not a sentence: it does not start with `$`, or has no
+    /// checksum.
+    Framing,
+    /// The sentence's checksum does not match its content.
+    Checksum,
+    /// The sentence is valid, but is not one of the parsed types.
+    Unsupported,
+    /// A field of the sentence is missing or malformed.
+    Field,
+}
+
+/// Parses a sentence, with or without its line terminator.
+pub fn parse(line: &[u8]) -> Result<Sentence, NmeaError> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = line.strip_prefix(b"$").ok_or(NmeaError::Framing)?;
+    let star = line.len().checked_sub(3).ok_or(NmeaError::Framing)?;
+    if line[star] != b'*' {
+        return Err(NmeaError::Framing);
+    }
+    let (body, checksum) = (&line[..star], &line[star + 1..]);
+    let checksum = hex(checksum[0]).zip(hex(checksum[1]));
+    let checksum = checksum.ok_or(NmeaError::Framing)?;
+    if body.iter().fold(0, |sum, byte| sum ^ byte) != checksum.0 << 4 | checksum.1 {
+        return Err(NmeaError::Checksum);
+    }
+
+    let mut fields = Fields(body.split(|&byte| byte == b','));
+    let address = fields.next()?;
+    // The address is a two-letter talker ID followed by the sentence type.
+    // Proprietary sentences (`$P...`) have longer addresses.
+    if address.len() != 5 || address[0] == b'P' {
+        return Err(NmeaError::Unsupported);
+    }
+    match &address[2..] {
+        b"RMC" => {
+            let time = time(fields.next()?)?;
+            let valid = match fields.next()? {
+                b"A" => true,
+                b"V" => false,
+                _ => return Err(NmeaError::Field),
+            };
+            let position = position(&mut fields)?;
+            fields.next()?; // Speed over ground
+            fields.next()?; // Course over ground
+            let date = date(fields.next()?)?;
+            Ok(Sentence::Rmc(Rmc {
+                time,
+                valid,
+                position,
+                date,
+            }))
+        }
+        b"GGA" => {
+            let time = time(fields.next()?)?;
+            let position = position(&mut fields)?;
+            let quality = integer(fields.next()?)?;
+            let satellites = integer(fields.next()?)?;
+            let hdop = match fields.next()? {
+                b"" => None,
+                hdop => Some(decimal(hdop, 2)?.try_into().map_err(|_| NmeaError::Field)?),
+            };
+            Ok(Sentence::Gga(Gga {
+                time,
+                position,
+                quality: quality.try_into().map_err(|_| NmeaError::Field)?,
+                satellites: satellites.try_into().map_err(|_| NmeaError::Field)?,
+                hdop,
+            }))
+        }
+        _ => Err(NmeaError::Unsupported),
+    }
+}
+
+/// Assembles sentences out of the bytes a receiver outputs, and parses them.
+///
+/// Bytes before the first `$` are ignored, so decoding can start in the middle
+/// of a sentence. Lines longer than the 82 bytes NMEA allows are dropped.
+pub struct Decoder {
+    line: [u8; MAX_LINE_LEN],
+    // The length of the line received so far, or `None` while waiting for the
+    // start of a line.
+    len: Option<usize>,
+}
+
+impl Decoder {
+    pub const fn new() -> Decoder {
+        Decoder {
+            line: [0; MAX_LINE_LEN],
+            len: None,
+        }
+    }
+
+    /// Adds a byte to the current line. Returns the result of parsing the
+    /// line if the byte ends it.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Sentence, NmeaError>> {
+        if byte == b'$' {
+            self.len = Some(0);
+        }
+        let len = self.len?;
+        if byte == b'\n' {
+            self.len = None;
+            return Some(parse(&self.line[..len]));
+        }
+        if len == MAX_LINE_LEN {
+            self.len = None;
+            return None;
+        }
+        self.line[len] = byte;
+        self.len = Some(len + 1);
+        None
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// The maximum length of a sentence, including its `$` and line terminator.
+const MAX_LINE_LEN: usize = 82;
+
+// The fields of a sentence, which fail to parse if one is missing.
+struct Fields<'a, I: Iterator<Item = &'a [u8]>>(I);
+
+impl<'a, I: Iterator<Item = &'a [u8]>> Fields<'a, I> {
+    fn next(&mut self) -> Result<&'a [u8], NmeaError> {
+        self.0.next().ok_or(NmeaError::Field)
+    }
+}
+
+fn hex(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|digit| digit as u8)
+}
+
+fn integer(field: &[u8]) -> Result<u32, NmeaError> {
+    if field.is_empty() || field.len() > 9 {
+        return Err(NmeaError::Field);
+    }
+    field.iter().try_fold(0, |value, &byte| match byte {
+        b'0'..=b'9' => Ok(value * 10 + (byte - b'0') as u32),
+        _ => Err(NmeaError::Field),
+    })
+}
+
+// Parses a decimal number as a multiple of 10^-`decimals`, ignoring further
+// digits.
+fn decimal(field: &[u8], decimals: usize) -> Result<u32, NmeaError> {
+    let (whole, fraction) = match field.iter().position(|&byte| byte == b'.') {
+        Some(dot) => (&field[..dot], &field[dot + 1..]),
+        None => (field, &b""[..]),
+    };
+    let mut value = integer(whole)?;
+    for place in 0..decimals {
+        let digit = match fraction.get(place) {
+            Some(_) => integer(&fraction[place..place + 1])?,
+            None => 0,
+        };
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(digit))
+            .ok_or(NmeaError::Field)?;
+    }
+    Ok(value)
+}
+
+// Parses a `hhmmss.sss` time, whose fraction is optional.
+fn time(field: &[u8]) -> Result<Time, NmeaError> {
+    if field.len() < 6 {
+        return Err(NmeaError::Field);
+    }
+    let milliseconds = decimal(&field[4..], 3)?;
+    let time = Time {
+        hour: integer(&field[0..2])? as u8,
+        minute: integer(&field[2..4])? as u8,
+        second: (milliseconds / 1000) as u8,
+        millisecond: (milliseconds % 1000) as u16,
+    };
+    // Leap seconds are numbered 60.
+    if time.hour > 23 || time.minute > 59 || milliseconds >= 61_000 {
+        return Err(NmeaError::Field);
+    }
+    Ok(time)
+}
+
+// Parses a `ddmmyy` date.
+fn date(field: &[u8]) -> Result<Date, NmeaError> {
+    if field.len() != 6 {
+        return Err(NmeaError::Field);
+    }
+    let date = Date {
+        day: integer(&field[0..2])? as u8,
+        month: integer(&field[2..4])? as u8,
+        year: 2000 + integer(&field[4..6])? as u16,
+    };
+    if !(1..=31).contains(&date.day) || !(1..=12).contains(&date.month) {
+        return Err(NmeaError::Field);
+    }
+    Ok(date)
+}
+
+// Parses the latitude, N/S, longitude, and E/W fields, which are all empty if
+// there is no fix.
+fn position<'a, I: Iterator<Item = &'a [u8]>>(
+    fields: &mut Fields<'a, I>,
+) -> Result<Option<Position>, NmeaError> {
+    let latitude = coordinate(fields.next()?, fields.next()?, 2, b'N', b'S')?;
+    let longitude = coordinate(fields.next()?, fields.next()?, 3, b'E', b'W')?;
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => Ok(Some(Position {
+            latitude,
+            longitude,
+        })),
+        (None, None) => Ok(None),
+        _ => Err(NmeaError::Field),
+    }
+}
+
+// Parses a coordinate in the `dddmm.mmmm` format, with `degree_digits` digits
+// of degrees, into ten-millionths of a degree.
+fn coordinate(
+    field: &[u8],
+    hemisphere: &[u8],
+    degree_digits: usize,
+    positive: u8,
+    negative: u8,
+) -> Result<Option<i32>, NmeaError> {
+    if field.is_empty() && hemisphere.is_empty() {
+        return Ok(None);
+    }
+    if field.len() < degree_digits + 2 {
+        return Err(NmeaError::Field);
+    }
+    let degrees = integer(&field[..degree_digits])?;
+    // Minutes, in hundred-thousandths.
+    let minutes = decimal(&field[degree_digits..], 5)?;
+    if minutes >= 60 * 100_000 {
+        return Err(NmeaError::Field);
+    }
+    let value = degrees * 10_000_000 + minutes * 100 / 60;
+    let max_degrees = if degree_digits == 2 { 90 } else { 180 };
+    if value > max_degrees * 10_000_000 {
+        return Err(NmeaError::Field);
+    }
+    let value = value as i32;
+    match hemisphere {
+        [byte] if *byte == positive => Ok(Some(value)),
+        [byte] if *byte == negative => Ok(Some(-value)),
+        _ => Err(NmeaError::Field),
+    }
+}
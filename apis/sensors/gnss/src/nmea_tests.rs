@@ -0,0 +1,140 @@
+use crate::nmea::*;
+
+const RMC: &[u8] = b"$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230324,003.1,W*61\r\n";
+const GGA: &[u8] = b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n";
+
+const TIME: Time = Time {
+    hour: 12,
+    minute: 35,
+    second: 19,
+    millisecond: 0,
+};
+const POSITION: Position = Position {
+    latitude: 481_173_000,
+    longitude: 115_166_666,
+};
+
+#[test]
+fn rmc() {
+    assert_eq!(
+        parse(RMC),
+        Ok(Sentence::Rmc(Rmc {
+            time: TIME,
+            valid: true,
+            position: Some(POSITION),
+            date: Date {
+                year: 2024,
+                month: 3,
+                day: 23
+            },
+        }))
+    );
+    assert_eq!(
+        parse(b"$GNRMC,123520.50,A,3351.1234,S,15112.5678,W,0.0,,230324,,,A*6D"),
+        Ok(Sentence::Rmc(Rmc {
+            time: Time {
+                hour: 12,
+                minute: 35,
+                second: 20,
+                millisecond: 500,
+            },
+            valid: true,
+            position: Some(Position {
+                latitude: -338_520_566,
+                longitude: -1_512_094_633,
+            }),
+            date: Date {
+                year: 2024,
+                month: 3,
+                day: 23
+            },
+        }))
+    );
+    assert_eq!(
+        parse(b"$GPRMC,000000,V,,,,,,,240324,,*32"),
+        Ok(Sentence::Rmc(Rmc {
+            time: Time::default(),
+            valid: false,
+            position: None,
+            date: Date {
+                year: 2024,
+                month: 3,
+                day: 24
+            },
+        }))
+    );
+}
+
+#[test]
+fn gga() {
+    assert_eq!(
+        parse(GGA),
+        Ok(Sentence::Gga(Gga {
+            time: TIME,
+            position: Some(POSITION),
+            quality: 1,
+            satellites: 8,
+            hdop: Some(90),
+        }))
+    );
+    assert_eq!(
+        parse(b"$GPGGA,000000,,,,,0,00,,,M,,M,,*66"),
+        Ok(Sentence::Gga(Gga {
+            time: Time::default(),
+            position: None,
+            quality: 0,
+            satellites: 0,
+            hdop: None,
+        }))
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(parse(b"GPGSV,1,1,00*79"), Err(NmeaError::Framing));
+    assert_eq!(parse(b"$GPGSV,1,1,00"), Err(NmeaError::Framing));
+    assert_eq!(parse(b"$GPGSV,1,1,00*7G"), Err(NmeaError::Framing));
+    assert_eq!(parse(b"$GPGSV,1,1,00*78"), Err(NmeaError::Checksum));
+    assert_eq!(parse(b"$GPGSV,1,1,00*79"), Err(NmeaError::Unsupported));
+    // Invalid status.
+    assert_eq!(
+        parse(b"$GPRMC,123519,X,4807.038,N,01131.000,E,,,230324,,*0F"),
+        Err(NmeaError::Field)
+    );
+    // Missing longitude.
+    assert_eq!(
+        parse(b"$GPGGA,123519,4807.038,N,,E,1,08,0.9,545.4,M,46.9,M,,*6B"),
+        Err(NmeaError::Field)
+    );
+    // Latitude out of range.
+    assert_eq!(
+        parse(b"$GPRMC,123519,A,9107.038,N,01131.000,E,,,230324,,*12"),
+        Err(NmeaError::Field)
+    );
+}
+
+#[test]
+fn decoder() {
+    let mut decoder = Decoder::new();
+    let mut push = |bytes: &[u8]| -> Vec<_> {
+        bytes
+            .iter()
+            .filter_map(|&byte| decoder.push(byte))
+            .collect()
+    };
+
+    // Bytes before the start of a sentence are ignored.
+    assert_eq!(push(b"0,M,,*47\r\n"), []);
+    assert_eq!(push(&RMC[..20]), []);
+    assert_eq!(push(&RMC[20..]), [parse(RMC)]);
+    assert_eq!(push(&[GGA, RMC].concat()), [parse(GGA), parse(RMC)]);
+
+    // A new sentence restarts the line.
+    assert_eq!(push(&[&GGA[..10], RMC].concat()), [parse(RMC)]);
+
+    // Overlong lines are dropped.
+    assert_eq!(
+        push(&[b"$GPTXT,".as_slice(), &[b'x'; 100], b"\r\n", GGA].concat()),
+        [parse(GGA)]
+    );
+}
@@ -0,0 +1,163 @@
+use super::*;
+use libtock_unittest::fake;
+
+const DRIVER_NUM: u32 = 3;
+
+type Gnss = super::Gnss<fake::Syscalls, DRIVER_NUM>;
+
+const RMC: &[u8] = b"$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230324,003.1,W*61\r\n";
+const GGA: &[u8] = b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n";
+const GSV: &[u8] = b"$GPGSV,1,1,00*79\r\n";
+
+const FIX: Fix = Fix {
+    time: Time {
+        hour: 12,
+        minute: 35,
+        second: 19,
+        millisecond: 0,
+    },
+    date: Some(Date {
+        year: 2024,
+        month: 3,
+        day: 23,
+    }),
+    position: Position {
+        latitude: 481_173_000,
+        longitude: 115_166_666,
+    },
+    hdop: Some(90),
+    satellites: Some(8),
+};
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    assert!(!Gnss::exists());
+    let driver = fake::Console::new_with_driver_num(b"", DRIVER_NUM);
+    kernel.add_driver(&driver);
+    assert!(Gnss::exists());
+}
+
+#[test]
+fn next_fix() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_driver_num(&[RMC, GSV, GGA, GSV].concat(), DRIVER_NUM);
+    kernel.add_driver(&driver);
+    let mut gnss = Gnss::new();
+    assert_eq!(gnss.fix(), None);
+
+    // The RMC sentence has no HDOP, which the GGA sentence completes.
+    let fix = Fix {
+        hdop: None,
+        satellites: None,
+        ..FIX
+    };
+    assert_eq!(gnss.next_fix(), Ok(fix));
+    assert_eq!(gnss.fix(), Some(&fix));
+    assert_eq!(gnss.fixes().next(), Some(Ok(FIX)));
+    assert_eq!(gnss.errors(), 0);
+}
+
+#[test]
+fn merge() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_driver_num(
+        &[
+            GGA,
+            RMC,
+            // A fix from the next epoch replaces the previous one.
+            b"$GNRMC,123520.50,A,3351.1234,S,15112.5678,W,0.0,,230324,,,A*6D\r\n",
+            // A GGA sentence after midnight does not keep the previous date.
+            b"$GPRMC,235959,A,4807.038,N,01131.000,E,,,230324,,*1A\r\n",
+            b"$GPGGA,000001,4807.038,N,01131.000,E,1,05,2.0,0,M,,M,,*46\r\n",
+            GSV,
+        ]
+        .concat(),
+        DRIVER_NUM,
+    );
+    kernel.add_driver(&driver);
+    let mut gnss = Gnss::new();
+
+    assert_eq!(gnss.next_fix(), Ok(Fix { date: None, ..FIX }));
+    assert_eq!(gnss.next_fix(), Ok(FIX));
+    assert_eq!(
+        gnss.next_fix(),
+        Ok(Fix {
+            time: Time {
+                hour: 12,
+                minute: 35,
+                second: 20,
+                millisecond: 500,
+            },
+            position: Position {
+                latitude: -338_520_566,
+                longitude: -1_512_094_633,
+            },
+            hdop: None,
+            satellites: None,
+            ..FIX
+        })
+    );
+    assert_eq!(gnss.next_fix().map(|fix| fix.date), Ok(FIX.date));
+    assert_eq!(
+        gnss.next_fix(),
+        Ok(Fix {
+            time: Time {
+                hour: 0,
+                minute: 0,
+                second: 1,
+                millisecond: 0,
+            },
+            date: None,
+            hdop: Some(200),
+            satellites: Some(5),
+            ..FIX
+        })
+    );
+}
+
+#[test]
+fn lost_fix() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Console::new_with_driver_num(
+        &[
+            RMC,
+            b"$GPGGA,000000,,,,,0,00,,,M,,M,,*66\r\n",
+            b"$GPRMC,000000,V,,,,,,,240324,,*32\r\n",
+            GGA,
+            GSV,
+        ]
+        .concat(),
+        DRIVER_NUM,
+    );
+    kernel.add_driver(&driver);
+    let mut gnss = Gnss::new();
+
+    gnss.next_fix().unwrap();
+    assert!(gnss.fix().is_some());
+    // Sentences without a fix clear the fix, but do not update it.
+    assert_eq!(gnss.next_fix(), Ok(Fix { date: None, ..FIX }));
+}
+
+#[test]
+fn errors() {
+    let kernel = fake::Kernel::new();
+    // The first sentence is garbled, the second truncated.
+    let driver = fake::Console::new_with_driver_num(
+        &[
+            b"$GPGGA,123519,4807.038,N,01131.000,E,1,09,0.9,545.4,M,46.9,M,,*47\r\n",
+            &GGA[..40],
+            b"\r\n",
+            b"noise",
+            RMC,
+            GSV,
+        ]
+        .concat(),
+        DRIVER_NUM,
+    );
+    kernel.add_driver(&driver);
+    let mut gnss = Gnss::new();
+
+    assert_eq!(gnss.next_fix().map(|fix| fix.position), Ok(FIX.position));
+    assert_eq!(gnss.errors(), 2);
+}
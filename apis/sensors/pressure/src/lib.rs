@@ -0,0 +1,95 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform::{
+    share, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+pub struct Pressure<S: Syscalls>(S);
+
+impl<S: Syscalls> Pressure<S> {
+    /// Returns Ok() if the driver was present.This does not necessarily mean
+    /// that the driver is working.
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, EXISTS, 0, 0).to_result()
+    }
+
+    /// Initiate a pressure measurement.
+    ///
+    /// This function is used both for synchronous and asynchronous readings
+    pub fn read_pressure() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, READ_PRESSURE, 0, 0).to_result()
+    }
+
+    /// Register an events listener
+    pub fn register_listener<'share, F: Fn(u32)>(
+        listener: &'share PressureListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, 0>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, listener)
+    }
+
+    /// Unregister the events listener
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, 0)
+    }
+
+    /// Initiates a pressure measurement, to wait for it along with other
+    /// events. The kernel reports the measured value into `read` until the end
+    /// of the handle's scope, e.g. for `libtock_future::wait_for_upcall`.
+    pub fn read_start<'share>(
+        read: &'share Cell<Option<(u32,)>>,
+        subscribe: share::Handle<ReadSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, read)?;
+        Self::read_pressure()
+    }
+
+    /// Initiate a synchronous pressure measurement.
+    /// Returns Ok(pressure_value) if the operation was successful
+    /// pressure_value is returned in hectopascals
+    pub fn read_pressure_sync() -> Result<u32, ErrorCode> {
+        let pressure_cell: Cell<Option<u32>> = Cell::new(None);
+        let listener = PressureListener(|pressure_val| {
+            pressure_cell.set(Some(pressure_val));
+        });
+        share::scope(|subscribe| {
+            if let Ok(()) = Self::register_listener(&listener, subscribe) {
+                if let Ok(()) = Self::read_pressure() {
+                    while pressure_cell.get().is_none() {
+                        S::yield_wait();
+                    }
+                }
+            }
+        });
+
+        match pressure_cell.get() {
+            None => Err(ErrorCode::Busy),
+            Some(pressure_val) => Ok(pressure_val),
+        }
+    }
+}
+
+/// The subscription of `Pressure::read_start`.
+pub type ReadSubscribe<'share, S> = Subscribe<'share, S, DRIVER_NUM, 0>;
+
+pub struct PressureListener<F: Fn(u32)>(pub F);
+impl<F: Fn(u32)> Upcall<OneId<DRIVER_NUM, 0>> for PressureListener<F> {
+    fn upcall(&self, pressure_val: u32, _arg1: u32, _arg2: u32) {
+        self.0(pressure_val)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x60008;
+
+// Command IDs
+
+const EXISTS: u32 = 0;
+const READ_PRESSURE: u32 = 1;
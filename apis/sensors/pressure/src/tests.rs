@@ -0,0 +1,87 @@
+use core::cell::Cell;
+use libtock_platform::{share, ErrorCode, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+type Pressure = super::Pressure<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(Pressure::exists(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pressure::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Pressure::exists(), Ok(()));
+}
+
+#[test]
+fn read_pressure() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pressure::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(Pressure::read_pressure(), Ok(()));
+    assert!(driver.is_busy());
+
+    assert_eq!(Pressure::read_pressure(), Err(ErrorCode::Busy));
+    assert_eq!(Pressure::read_pressure_sync(), Err(ErrorCode::Busy));
+}
+
+#[test]
+fn register_unregister_listener() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pressure::new();
+    kernel.add_driver(&driver);
+
+    let pressure_cell: Cell<Option<u32>> = Cell::new(None);
+    let listener = crate::PressureListener(|pressure_val| {
+        pressure_cell.set(Some(pressure_val));
+    });
+    share::scope(|subscribe| {
+        assert_eq!(Pressure::read_pressure(), Ok(()));
+        driver.set_value(100);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        assert_eq!(Pressure::register_listener(&listener, subscribe), Ok(()));
+        assert_eq!(Pressure::read_pressure(), Ok(()));
+        driver.set_value(100);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(pressure_cell.get(), Some(100));
+
+        Pressure::unregister_listener();
+        assert_eq!(Pressure::read_pressure(), Ok(()));
+        driver.set_value(100);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+}
+
+#[test]
+fn read_pressure_sync() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pressure::new();
+    kernel.add_driver(&driver);
+
+    driver.set_value_sync(1000);
+    assert_eq!(Pressure::read_pressure_sync(), Ok(1000));
+}
+
+#[test]
+fn read_start() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Pressure::new();
+    kernel.add_driver(&driver);
+
+    let read: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        assert_eq!(Pressure::read_start(&read, subscribe), Ok(()));
+        assert!(driver.is_busy());
+        driver.set_value(1000);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(read.get(), Some((1000,)));
+    });
+}
@@ -0,0 +1,210 @@
+#![no_std]
+
+use core::cell::Cell;
+use libtock_platform::{
+    share, subscribe::OneId, DefaultConfig, ErrorCode, Subscribe, Syscalls, Upcall,
+};
+
+/// The supply monitor driver.
+///
+/// It measures the chip's internal supply voltage (VCC) and die temperature
+/// channels, so battery-powered devices can report their supply health. The
+/// driver reports raw samples, along with a per-chip calibration that converts
+/// them to physical units; `vcc_mv` and `die_temperature` apply it.
+///
+/// It also warns of imminent power loss: once enabled, the power-fail listener
+/// is called when VCC drops below a threshold (e.g. the nRF52's POFWARN), which
+/// leaves the app a short time to flush its flash log and park the radio before
+/// the supply collapses.
+///
+/// # Example
+/// ```ignore
+/// use libtock::supply_monitor::SupplyMonitor;
+///
+/// let vcc_mv = SupplyMonitor::vcc_mv()?;
+/// let temperature = SupplyMonitor::die_temperature()?;
+/// ```
+///
+/// Boards that register the capsule under another driver number pass it as
+/// `DRIVER_NUM`:
+/// ```ignore
+/// type SupplyMonitor = libtock_supply_monitor::SupplyMonitor<TockSyscalls, 0x90100>;
+/// ```
+pub struct SupplyMonitor<S: Syscalls, const DRIVER_NUM: u32 = DEFAULT_DRIVER_NUM>(S);
+
+impl<S: Syscalls, const DRIVER_NUM: u32> SupplyMonitor<S, DRIVER_NUM> {
+    /// Run a check against the supply monitor capsule to ensure it is present.
+    ///
+    /// Returns `Ok(())` if the driver was present. This does not necessarily
+    /// mean that the driver is working.
+    #[inline(always)]
+    pub fn exists() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::EXISTS, 0, 0).to_result()
+    }
+
+    /// Initiates a measurement of `channel`. The raw sample is delivered to
+    /// the registered listener.
+    pub fn sample(channel: Channel) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::SAMPLE, channel as u32, 0).to_result()
+    }
+
+    /// Returns the calibration that converts `channel`'s raw samples to
+    /// physical units.
+    pub fn calibration(channel: Channel) -> Result<Calibration, ErrorCode> {
+        let (gain, shift, offset) = S::command(DRIVER_NUM, command::CALIBRATION, channel as u32, 0)
+            .to_result::<(u32, u32, u32), ErrorCode>()?;
+        Ok(Calibration {
+            gain,
+            shift,
+            offset: offset as i32,
+        })
+    }
+
+    /// Register an events listener
+    pub fn register_listener<'share, F: Fn(Channel, u32)>(
+        listener: &'share SupplyMonitorListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, 0>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, listener)
+    }
+
+    /// Unregister the events listener
+    pub fn unregister_listener() {
+        S::unsubscribe(DRIVER_NUM, 0)
+    }
+
+    /// Enables the power-fail warning: the power-fail listener is called when
+    /// VCC drops below `threshold_mv`. The supported thresholds depend on the
+    /// chip; others fail with `ErrorCode::Invalid`.
+    pub fn enable_power_fail_warning(threshold_mv: u32) -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::ENABLE_POWER_FAIL, threshold_mv, 0).to_result()
+    }
+
+    /// Disables the power-fail warning.
+    pub fn disable_power_fail_warning() -> Result<(), ErrorCode> {
+        S::command(DRIVER_NUM, command::DISABLE_POWER_FAIL, 0, 0).to_result()
+    }
+
+    /// Register a power-fail warning listener
+    pub fn register_power_fail_listener<'share, F: Fn(u32)>(
+        listener: &'share PowerFailListener<F>,
+        subscribe: share::Handle<Subscribe<'share, S, DRIVER_NUM, 1>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 1>(subscribe, listener)
+    }
+
+    /// Unregister the power-fail warning listener
+    pub fn unregister_power_fail_listener() {
+        S::unsubscribe(DRIVER_NUM, 1)
+    }
+
+    /// Measures `channel` synchronously, and returns the raw sample.
+    pub fn read_raw_sync(channel: Channel) -> Result<u32, ErrorCode> {
+        let sample: Cell<Option<u32>> = Cell::new(None);
+        let listener = SupplyMonitorListener(|sampled, raw| {
+            if sampled == channel {
+                sample.set(Some(raw));
+            }
+        });
+        share::scope(|subscribe| {
+            Self::register_listener(&listener, subscribe)?;
+            Self::sample(channel)?;
+            loop {
+                S::yield_wait();
+                if let Some(raw) = sample.get() {
+                    return Ok(raw);
+                }
+            }
+        })
+    }
+
+    /// Measures the supply voltage synchronously, and returns it in
+    /// millivolts.
+    pub fn vcc_mv() -> Result<u32, ErrorCode> {
+        let calibration = Self::calibration(Channel::Vcc)?;
+        let raw = Self::read_raw_sync(Channel::Vcc)?;
+        Ok(calibration.apply(raw).max(0) as u32)
+    }
+
+    /// Measures the die temperature synchronously, and returns it in
+    /// hundredths of a degree Celsius (like `Temperature::read_temperature_sync`).
+    pub fn die_temperature() -> Result<i32, ErrorCode> {
+        let calibration = Self::calibration(Channel::DieTemperature)?;
+        let raw = Self::read_raw_sync(Channel::DieTemperature)?;
+        Ok(calibration.apply(raw))
+    }
+}
+
+/// A measurement channel of the supply monitor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Channel {
+    /// The supply voltage. Calibrated samples are in millivolts.
+    Vcc = 0,
+    /// The die temperature. Calibrated samples are in hundredths of a degree
+    /// Celsius.
+    DieTemperature = 1,
+}
+
+/// Converts a channel's raw samples to physical units, as
+/// `((raw * gain) >> shift) + offset`.
+///
+/// E.g. an nRF52 measuring VDD with its 12-bit SAADC (0.6 V reference, 1/6
+/// gain) reports `gain = 3600, shift = 12, offset = 0`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Calibration {
+    pub gain: u32,
+    pub shift: u32,
+    pub offset: i32,
+}
+
+impl Calibration {
+    /// Converts the raw sample `raw`.
+    pub fn apply(&self, raw: u32) -> i32 {
+        let scaled = (raw as u64 * self.gain as u64) >> self.shift.min(63);
+        (scaled as i64 + self.offset as i64).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+}
+
+pub struct SupplyMonitorListener<F: Fn(Channel, u32)>(pub F);
+impl<F: Fn(Channel, u32), const DRIVER_NUM: u32> Upcall<OneId<DRIVER_NUM, 0>>
+    for SupplyMonitorListener<F>
+{
+    fn upcall(&self, channel: u32, raw: u32, _arg2: u32) {
+        let channel = match channel {
+            0 => Channel::Vcc,
+            1 => Channel::DieTemperature,
+            _ => return,
+        };
+        self.0(channel, raw)
+    }
+}
+
+/// A listener for the power-fail warning. It is called with the threshold (in
+/// millivolts) that VCC dropped below. It should do as little as possible: the
+/// supply may collapse soon after.
+pub struct PowerFailListener<F: Fn(u32)>(pub F);
+impl<F: Fn(u32), const DRIVER_NUM: u32> Upcall<OneId<DRIVER_NUM, 1>> for PowerFailListener<F> {
+    fn upcall(&self, threshold_mv: u32, _arg1: u32, _arg2: u32) {
+        self.0(threshold_mv)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+// Upstream Tock assigns no driver number for a supply monitor; see
+// doc/DriverNumbers.md for how libtock-rs picked this one.
+const DEFAULT_DRIVER_NUM: u32 = 0x9006B;
+
+// Command IDs
+mod command {
+    pub const EXISTS: u32 = 0;
+    pub const SAMPLE: u32 = 1;
+    pub const CALIBRATION: u32 = 2;
+    pub const ENABLE_POWER_FAIL: u32 = 3;
+    pub const DISABLE_POWER_FAIL: u32 = 4;
+}
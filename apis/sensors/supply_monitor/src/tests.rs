@@ -0,0 +1,123 @@
+use super::*;
+use libtock_platform::{share, ErrorCode, YieldNoWaitReturn};
+use libtock_unittest::fake;
+
+type SupplyMonitor = super::SupplyMonitor<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(SupplyMonitor::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(SupplyMonitor::vcc_mv(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn exists() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::SupplyMonitor::new();
+    kernel.add_driver(&driver);
+
+    assert_eq!(SupplyMonitor::exists(), Ok(()));
+}
+
+#[test]
+fn calibration() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::SupplyMonitor::new();
+    kernel.add_driver(&driver);
+
+    let vcc = SupplyMonitor::calibration(Channel::Vcc).unwrap();
+    assert_eq!(
+        vcc,
+        Calibration {
+            gain: 3600,
+            shift: 12,
+            offset: 0
+        }
+    );
+    assert_eq!(vcc.apply(4095), 3599);
+    assert_eq!(vcc.apply(3413), 2999);
+
+    driver.set_calibration(1, 25, 0, -150);
+    let temperature = SupplyMonitor::calibration(Channel::DieTemperature).unwrap();
+    assert_eq!(temperature.offset, -150);
+    assert_eq!(temperature.apply(0), -150);
+    assert_eq!(temperature.apply(100), 2350);
+}
+
+#[test]
+fn listener() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::SupplyMonitor::new();
+    kernel.add_driver(&driver);
+
+    let sample: Cell<Option<(Channel, u32)>> = Cell::new(None);
+    let listener = SupplyMonitorListener(|channel, raw| sample.set(Some((channel, raw))));
+    share::scope(|subscribe| {
+        assert_eq!(
+            SupplyMonitor::register_listener(&listener, subscribe),
+            Ok(())
+        );
+        assert_eq!(SupplyMonitor::sample(Channel::DieTemperature), Ok(()));
+        assert_eq!(SupplyMonitor::sample(Channel::Vcc), Err(ErrorCode::Busy));
+        driver.set_raw(1, 90);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(sample.get(), Some((Channel::DieTemperature, 90)));
+
+        SupplyMonitor::unregister_listener();
+        assert_eq!(SupplyMonitor::sample(Channel::Vcc), Ok(()));
+        driver.set_raw(0, 90);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+    });
+}
+
+#[test]
+fn read_sync() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::SupplyMonitor::new();
+    kernel.add_driver(&driver);
+
+    driver.set_raw_sync(0, 3413);
+    assert_eq!(SupplyMonitor::vcc_mv(), Ok(2999));
+    driver.set_raw_sync(1, 100);
+    assert_eq!(SupplyMonitor::die_temperature(), Ok(2500));
+    driver.set_raw_sync(1, 7);
+    assert_eq!(SupplyMonitor::read_raw_sync(Channel::DieTemperature), Ok(7));
+}
+
+#[test]
+fn power_fail_warning() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::SupplyMonitor::new();
+    kernel.add_driver(&driver);
+
+    let warned: Cell<Option<u32>> = Cell::new(None);
+    let listener = PowerFailListener(|threshold_mv| warned.set(Some(threshold_mv)));
+    share::scope(|subscribe| {
+        assert_eq!(
+            SupplyMonitor::register_power_fail_listener(&listener, subscribe),
+            Ok(())
+        );
+        assert_eq!(
+            SupplyMonitor::enable_power_fail_warning(1000),
+            Err(ErrorCode::Invalid)
+        );
+        assert_eq!(SupplyMonitor::enable_power_fail_warning(2500), Ok(()));
+        assert_eq!(driver.power_fail_threshold(), Some(2500));
+        driver.trigger_power_fail();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(warned.get(), Some(2500));
+
+        assert_eq!(SupplyMonitor::disable_power_fail_warning(), Ok(()));
+        warned.set(None);
+        driver.trigger_power_fail();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        // Samples and power-fail warnings use separate upcalls.
+        assert_eq!(SupplyMonitor::enable_power_fail_warning(2500), Ok(()));
+        SupplyMonitor::unregister_power_fail_listener();
+        driver.trigger_power_fail();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        assert_eq!(warned.get(), None);
+    });
+}
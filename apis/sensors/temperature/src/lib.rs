@@ -34,6 +34,18 @@ impl<S: Syscalls> Temperature<S> {
         S::unsubscribe(DRIVER_NUM, 0)
     }
 
+    /// Initiates a temperature measurement, to wait for it along with other
+    /// events. The kernel reports the measured value into `read` until the end
+    /// of the handle's scope, e.g. for `libtock_future::wait_for_upcall`. The
+    /// reported `u32` is the temperature's bits, to be cast to `i32`.
+    pub fn read_start<'share>(
+        read: &'share Cell<Option<(u32,)>>,
+        subscribe: share::Handle<ReadSubscribe<'share, S>>,
+    ) -> Result<(), ErrorCode> {
+        S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, read)?;
+        Self::read_temperature()
+    }
+
     /// Initiate a synchronous temperature measurement.
     /// Returns Ok(temperature_value) if the operation was successful
     /// temperature_value is returned in hundreds of centigrades
@@ -59,6 +71,9 @@ impl<S: Syscalls> Temperature<S> {
     }
 }
 
+/// The subscription of `Temperature::read_start`.
+pub type ReadSubscribe<'share, S> = Subscribe<'share, S, DRIVER_NUM, 0>;
+
 pub struct TemperatureListener<F: Fn(i32)>(pub F);
 impl<F: Fn(i32)> Upcall<OneId<DRIVER_NUM, 0>> for TemperatureListener<F> {
     fn upcall(&self, temp_val: u32, _arg1: u32, _arg2: u32) {
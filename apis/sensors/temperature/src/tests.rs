@@ -79,3 +79,19 @@ fn negative_value() {
     driver.set_value_sync(-1000);
     assert_eq!(Temperature::read_temperature_sync(), Ok(-1000));
 }
+
+#[test]
+fn read_start() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::Temperature::new();
+    kernel.add_driver(&driver);
+
+    let read: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        assert_eq!(Temperature::read_start(&read, subscribe), Ok(()));
+        assert!(driver.is_busy());
+        driver.set_value(1000);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(read.get(), Some((1000,)));
+    });
+}
@@ -0,0 +1,85 @@
+//! Samples the temperature, humidity and pressure sensors together.
+//!
+//! [`WeatherStation::read`] starts the three measurements at once and waits
+//! for all of them, so the common "environmental triple" costs one wait
+//! instead of three blocking reads.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::weather::WeatherStation;
+//!
+//! let record = WeatherStation::read()?;
+//! writeln!(Console::writer(), "{} hPa at tick {}", record.pressure, record.ticks)?;
+//! ```
+
+#![no_std]
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use libtock_alarm::Alarm;
+use libtock_future::{block_on, join, wait_for_upcall};
+use libtock_humidity::Humidity;
+use libtock_platform::{share, ErrorCode, Syscalls};
+use libtock_pressure::Pressure;
+use libtock_temperature::Temperature;
+
+pub struct WeatherStation<S: Syscalls>(PhantomData<S>);
+
+/// A sample of the three sensors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WeatherRecord {
+    /// The alarm's ticks when the last measurement arrived.
+    pub ticks: u32,
+    /// In hundredths of degrees Celsius.
+    pub temperature: i32,
+    /// In hundredths of a percent of relative humidity.
+    pub humidity: u32,
+    /// In hectopascals.
+    pub pressure: u32,
+}
+
+impl<S: Syscalls> WeatherStation<S> {
+    /// Returns Ok() if all three drivers are present.
+    pub fn exists() -> Result<(), ErrorCode> {
+        Temperature::<S>::exists()?;
+        Humidity::<S>::exists()?;
+        Pressure::<S>::exists()
+    }
+
+    /// Measures the temperature, humidity and pressure, and returns them along
+    /// with the time the measurements completed. Fails with the error of the
+    /// first measurement that cannot start.
+    pub fn read() -> Result<WeatherRecord, ErrorCode> {
+        let temperature = Cell::new(None);
+        let humidity = Cell::new(None);
+        let pressure = Cell::new(None);
+        share::scope::<
+            (
+                libtock_temperature::ReadSubscribe<S>,
+                libtock_humidity::ReadSubscribe<S>,
+                libtock_pressure::ReadSubscribe<S>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (temperature_subscribe, humidity_subscribe, pressure_subscribe) = handle.split();
+            Temperature::<S>::read_start(&temperature, temperature_subscribe)?;
+            Humidity::<S>::read_start(&humidity, humidity_subscribe)?;
+            Pressure::<S>::read_start(&pressure, pressure_subscribe)?;
+
+            let (((temperature,), (humidity,)), (pressure,)) = block_on::<S, _>(join(
+                join(wait_for_upcall(&temperature), wait_for_upcall(&humidity)),
+                wait_for_upcall(&pressure),
+            ));
+            Ok(WeatherRecord {
+                ticks: Alarm::<S>::get_ticks()?,
+                temperature: temperature as i32,
+                humidity,
+                pressure,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
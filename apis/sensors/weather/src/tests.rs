@@ -0,0 +1,42 @@
+use super::WeatherRecord;
+use libtock_platform::ErrorCode;
+use libtock_unittest::fake;
+
+type WeatherStation = super::WeatherStation<fake::Syscalls>;
+
+#[test]
+fn no_driver() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&fake::Temperature::new());
+    assert_eq!(WeatherStation::exists(), Err(ErrorCode::NoDevice));
+    assert_eq!(WeatherStation::read(), Err(ErrorCode::NoDevice));
+}
+
+#[test]
+fn read() {
+    let kernel = fake::Kernel::new();
+    let temperature = fake::Temperature::new();
+    let humidity = fake::Humidity::new();
+    let pressure = fake::Pressure::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&temperature);
+    kernel.add_driver(&humidity);
+    kernel.add_driver(&pressure);
+    kernel.add_driver(&alarm);
+    assert_eq!(WeatherStation::exists(), Ok(()));
+
+    alarm.advance_ticks(42);
+    temperature.set_value_sync(-150);
+    humidity.set_value_sync(4550);
+    pressure.set_value_sync(1013);
+    assert_eq!(
+        WeatherStation::read(),
+        Ok(WeatherRecord {
+            ticks: 42,
+            temperature: -150,
+            humidity: 4550,
+            pressure: 1013,
+        })
+    );
+    assert!(!temperature.is_busy() && !humidity.is_busy() && !pressure.is_busy());
+}
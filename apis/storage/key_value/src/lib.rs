@@ -17,7 +17,7 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
     /// Run a check against the key-value capsule to ensure it is present.
     #[inline(always)]
     pub fn exists() -> bool {
-        S::command(DRIVER_NUM, command::DRIVER_CHECK, 0, 0).is_success()
+        S::checked_command::<C>(DRIVER_NUM, command::DRIVER_CHECK, 0, 0).is_success()
     }
 
     /// Get a key-value object from the `key`.
@@ -39,7 +39,7 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, command::GET, 0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, command::GET, 0, 0).to_result()?;
 
             loop {
                 S::yield_wait();
@@ -69,7 +69,7 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, command_num, 0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, command_num, 0, 0).to_result()?;
 
             loop {
                 S::yield_wait();
@@ -112,7 +112,7 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
             S::subscribe::<_, _, C, DRIVER_NUM, { subscribe::CALLBACK }>(subscribe, &called)?;
 
-            S::command(DRIVER_NUM, command::DELETE, 0, 0).to_result()?;
+            S::checked_command::<C>(DRIVER_NUM, command::DELETE, 0, 0).to_result()?;
 
             loop {
                 S::yield_wait();
@@ -126,11 +126,18 @@ impl<S: Syscalls, C: Config> KeyValue<S, C> {
 
 /// System call configuration trait for `KeyValue`.
 pub trait Config:
-    platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config
+    platform::allow_ro::Config
+    + platform::allow_rw::Config
+    + platform::command::Config
+    + platform::subscribe::Config
 {
 }
-impl<T: platform::allow_ro::Config + platform::allow_rw::Config + platform::subscribe::Config>
-    Config for T
+impl<
+        T: platform::allow_ro::Config
+            + platform::allow_rw::Config
+            + platform::command::Config
+            + platform::subscribe::Config,
+    > Config for T
 {
 }
 
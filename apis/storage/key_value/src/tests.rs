@@ -225,3 +225,32 @@ fn delete_fail() {
 
     assert_eq!(_delete(&kernel), Err(ErrorCode::NoSupport));
 }
+
+#[test]
+fn set_over_quota() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::KeyValue::new();
+    kernel.add_driver(&driver);
+    driver.set_quota(Some(10));
+
+    assert_eq!(_set(&kernel), Err(ErrorCode::NoMem));
+    assert_eq!(driver.get(b"mykey"), None);
+    driver.set_quota(Some(11));
+    assert_eq!(_set(&kernel), Ok(()));
+    assert_eq!(driver.used_bytes(), 11);
+}
+
+#[test]
+fn injected_errors() {
+    let kernel = fake::Kernel::new();
+    let driver = fake::KeyValue::new();
+    kernel.add_driver(&driver);
+    driver.insert(b"mykey", b"abc");
+
+    driver.fail_next(ErrorCode::NoSupport);
+    assert_eq!(_get(&kernel), Err(ErrorCode::NoSupport));
+    driver.fail_next(ErrorCode::NoMem);
+    assert_eq!(_update(&kernel), Err(ErrorCode::NoMem));
+    assert_eq!(driver.get(b"mykey"), Some(b"abc".to_vec()));
+    assert_eq!(_get(&kernel), Ok(3));
+}
@@ -0,0 +1,222 @@
+//! `libtock_bench` measures the latency of system calls and driver operations
+//! on a Tock board, timing them with the alarm driver.
+//!
+//! Each benchmark is run `samples` times in batches of `batch` back-to-back
+//! calls, as a single system call is usually shorter than an alarm tick. The
+//! latency of a sample is the duration of its batch divided by `batch`, and
+//! includes the two commands that read the alarm's counter, amortized over the
+//! batch. Results are written to the console as CSV, so they can be parsed by
+//! a script on the host:
+//! ```text
+//! # libtock_bench samples=16 batch=64 frequency_hz=32768
+//! name,min_ns,mean_ns,max_ns,error
+//! command,1430,1502,1525,
+//! temperature,,,,BUSY
+//! ```
+//! A benchmark that fails is reported with the error it failed with, and its
+//! remaining samples are skipped.
+//!
+//! [`Bench::syscalls`] returns benchmarks of the raw system calls and of the
+//! `share::scope` abstractions built on them, to quantify the overhead of the
+//! abstractions. Per-driver operations are benchmarked by passing their own
+//! [`Benchmark`]s.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::bench::{Bench, Benchmark};
+//!
+//! fn main() {
+//!     Bench::run(&Bench::syscalls());
+//!     Bench::run(&[Benchmark::new("temperature", || {
+//!         Temperature::read_temperature_sync().map(|_| ())
+//!     })]);
+//! }
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+use core::cell::Cell;
+use core::fmt::Write;
+use libtock_alarm::Alarm;
+use libtock_console::Console;
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// A benchmarked operation, along with the name it is reported under.
+#[derive(Clone, Copy)]
+pub struct Benchmark {
+    pub name: &'static str,
+    pub run: fn() -> Result<(), ErrorCode>,
+}
+
+impl Benchmark {
+    pub const fn new(name: &'static str, run: fn() -> Result<(), ErrorCode>) -> Benchmark {
+        Benchmark { name, run }
+    }
+}
+
+/// Latency statistics of a benchmark, in nanoseconds per call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub mean_ns: u64,
+    pub max_ns: u64,
+}
+
+pub struct Bench<S: Syscalls>(S);
+
+impl<S: Syscalls> Bench<S> {
+    /// The number of samples `run` takes of each benchmark.
+    pub const SAMPLES: u32 = 16;
+    /// The number of calls `run` times in each sample.
+    pub const BATCH: u32 = 64;
+
+    /// Runs `benchmarks` in order with the default number of samples and batch
+    /// size, and reports the results. Returns the number of benchmarks that
+    /// failed.
+    pub fn run(benchmarks: &[Benchmark]) -> u32 {
+        Self::run_benchmarks(benchmarks, Self::SAMPLES, Self::BATCH)
+    }
+
+    /// Runs `benchmarks` in order, and reports the results. Returns the number
+    /// of benchmarks that failed. If the alarm is unavailable, no benchmark is
+    /// run and all of them count as failed.
+    pub fn run_benchmarks(benchmarks: &[Benchmark], samples: u32, batch: u32) -> u32 {
+        let mut writer = Console::<S>::writer();
+        // Failing to report a result is not a reason to stop benchmarking, so
+        // write errors are ignored.
+        let frequency = match Alarm::<S>::get_frequency() {
+            Ok(frequency) => frequency.0,
+            Err(error) => {
+                let _ = writeln!(writer, "# libtock_bench alarm unavailable: {:?}", error);
+                return benchmarks.len() as u32;
+            }
+        };
+        let _ = writeln!(
+            writer,
+            "# libtock_bench samples={} batch={} frequency_hz={}",
+            samples, batch, frequency
+        );
+        let _ = writeln!(writer, "name,min_ns,mean_ns,max_ns,error");
+        let mut failures = 0;
+        for benchmark in benchmarks {
+            let _ = match Self::measure(benchmark, samples, batch) {
+                Ok(stats) => writeln!(
+                    writer,
+                    "{},{},{},{},",
+                    benchmark.name, stats.min_ns, stats.mean_ns, stats.max_ns
+                ),
+                Err(error) => {
+                    failures += 1;
+                    writeln!(writer, "{},,,,{:?}", benchmark.name, error)
+                }
+            };
+        }
+        failures
+    }
+
+    /// Runs `benchmark` and returns its latency statistics, without reporting
+    /// them. `samples` and `batch` are raised to at least 1.
+    pub fn measure(benchmark: &Benchmark, samples: u32, batch: u32) -> Result<Stats, ErrorCode> {
+        let (samples, batch) = (samples.max(1), batch.max(1));
+        let frequency = Alarm::<S>::get_frequency()?.0 as u64;
+        if frequency == 0 {
+            return Err(ErrorCode::Fail);
+        }
+        let mut stats = Stats {
+            min_ns: u64::MAX,
+            mean_ns: 0,
+            max_ns: 0,
+        };
+        let mut total_ns = 0;
+        for _ in 0..samples {
+            let start = Alarm::<S>::get_ticks()?;
+            for _ in 0..batch {
+                (benchmark.run)()?;
+            }
+            let ticks = Alarm::<S>::get_ticks()?.wrapping_sub(start) as u64;
+            let ns = ticks * 1_000_000_000 / frequency / batch as u64;
+            stats.min_ns = stats.min_ns.min(ns);
+            stats.max_ns = stats.max_ns.max(ns);
+            total_ns += ns;
+        }
+        stats.mean_ns = total_ns / samples as u64;
+        Ok(stats)
+    }
+
+    /// Returns benchmarks of individual system calls, using the alarm and
+    /// console drivers as targets:
+    ///
+    /// * `command`: a Command (the alarm's existence check).
+    /// * `yield_no_wait`: a Yield that does not wait.
+    /// * `subscribe_raw`, `allow_ro_raw`, `allow_rw_raw`: a Subscribe or Allow
+    ///   that clears the upcall or buffer, which is a single system call.
+    /// * `subscribe_scope`, `allow_ro_scope`, `allow_rw_scope`: a
+    ///   `share::scope` that registers an upcall or shares a buffer, including
+    ///   the system call that clears it when the scope ends. These make two
+    ///   system calls, so their overhead is their latency minus twice that of
+    ///   the corresponding raw benchmark.
+    ///
+    /// The benchmarks leave the alarm's upcall and the console's buffers
+    /// cleared, so must not run while those are in use.
+    pub fn syscalls() -> [Benchmark; 8] {
+        [
+            Benchmark::new("command", Alarm::<S>::exists),
+            Benchmark::new("yield_no_wait", || {
+                S::yield_no_wait();
+                Ok(())
+            }),
+            Benchmark::new("subscribe_raw", || {
+                S::unsubscribe(ALARM_DRIVER_NUM, ALARM_SUBSCRIBE_NUM);
+                Ok(())
+            }),
+            Benchmark::new("subscribe_scope", || {
+                let upcall = Cell::new(None::<(u32, u32)>);
+                share::scope(|subscribe| {
+                    S::subscribe::<_, _, DefaultConfig, ALARM_DRIVER_NUM, ALARM_SUBSCRIBE_NUM>(
+                        subscribe, &upcall,
+                    )
+                })
+            }),
+            Benchmark::new("allow_ro_raw", || {
+                S::unallow_ro(CONSOLE_DRIVER_NUM, CONSOLE_ALLOW_NUM);
+                Ok(())
+            }),
+            Benchmark::new("allow_ro_scope", || {
+                let buffer = [0; 4];
+                share::scope(|allow_ro| {
+                    S::allow_ro::<DefaultConfig, CONSOLE_DRIVER_NUM, CONSOLE_ALLOW_NUM>(
+                        allow_ro, &buffer,
+                    )
+                })
+            }),
+            Benchmark::new("allow_rw_raw", || {
+                S::unallow_rw(CONSOLE_DRIVER_NUM, CONSOLE_ALLOW_NUM);
+                Ok(())
+            }),
+            Benchmark::new("allow_rw_scope", || {
+                let mut buffer = [0; 4];
+                share::scope(|allow_rw| {
+                    S::allow_rw::<DefaultConfig, CONSOLE_DRIVER_NUM, CONSOLE_ALLOW_NUM>(
+                        allow_rw,
+                        &mut buffer,
+                    )
+                })
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// System call targets of the syscall benchmarks
+// -----------------------------------------------------------------------------
+
+const ALARM_DRIVER_NUM: u32 = 0;
+const ALARM_SUBSCRIBE_NUM: u32 = 0;
+
+// The console's write (Read-Only Allow) and read (Read-Write Allow) buffers
+// share a number.
+const CONSOLE_DRIVER_NUM: u32 = 1;
+const CONSOLE_ALLOW_NUM: u32 = 1;
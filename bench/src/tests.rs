@@ -0,0 +1,113 @@
+use super::*;
+use libtock_unittest::fake;
+use std::rc::Rc;
+
+type Bench = super::Bench<fake::Syscalls>;
+
+std::thread_local! {
+    // The alarm advanced by `ten_ticks`.
+    static ALARM: Cell<Option<Rc<fake::Alarm>>> = const { Cell::new(None) };
+}
+
+// Takes 10 ticks of ALARM.
+fn ten_ticks() -> Result<(), ErrorCode> {
+    ALARM.with(|cell| {
+        let alarm = cell.take().expect("no alarm");
+        alarm.advance_ticks(10);
+        cell.set(Some(alarm));
+    });
+    Ok(())
+}
+
+#[test]
+fn no_alarm() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+
+    let benchmarks = [Benchmark::new("nop", || Ok(()))];
+    assert_eq!(Bench::run(&benchmarks), 1);
+    assert_eq!(
+        console.take_bytes(),
+        b"# libtock_bench alarm unavailable: NODEVICE\n"
+    );
+}
+
+#[test]
+fn measure() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+    ALARM.with(|cell| cell.set(Some(alarm.clone())));
+
+    // 4 calls of 10 ticks at 1 kHz take 10 ms each.
+    let benchmark = Benchmark::new("ten_ticks", ten_ticks);
+    assert_eq!(
+        Bench::measure(&benchmark, 3, 4),
+        Ok(Stats {
+            min_ns: 10_000_000,
+            mean_ns: 10_000_000,
+            max_ns: 10_000_000,
+        })
+    );
+    assert_eq!(alarm.ticks(), 120);
+
+    let failing = Benchmark::new("fails", || Err(ErrorCode::Busy));
+    assert_eq!(Bench::measure(&failing, 3, 4), Err(ErrorCode::Busy));
+}
+
+#[test]
+fn run_benchmarks() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+    ALARM.with(|cell| cell.set(Some(alarm)));
+
+    let benchmarks = [
+        Benchmark::new("ten_ticks", ten_ticks),
+        Benchmark::new("fails", || Err(ErrorCode::Busy)),
+        Benchmark::new("nop", || Ok(())),
+    ];
+    assert_eq!(Bench::run_benchmarks(&benchmarks, 2, 5), 1);
+    assert_eq!(
+        console.take_bytes(),
+        b"# libtock_bench samples=2 batch=5 frequency_hz=1000\n\
+          name,min_ns,mean_ns,max_ns,error\n\
+          ten_ticks,10000000,10000000,10000000,\n\
+          fails,,,,BUSY\n\
+          nop,0,0,0,\n"
+    );
+}
+
+#[test]
+fn syscalls() {
+    let kernel = fake::Kernel::new();
+    let alarm = fake::Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+
+    let benchmarks = Bench::syscalls();
+    assert_eq!(Bench::run_benchmarks(&benchmarks, 1, 2), 0);
+    let output = String::from_utf8(console.take_bytes()).unwrap();
+    let names: Vec<_> = output
+        .lines()
+        .skip(2)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "command",
+            "yield_no_wait",
+            "subscribe_raw",
+            "subscribe_scope",
+            "allow_ro_raw",
+            "allow_ro_scope",
+            "allow_rw_raw",
+            "allow_rw_scope",
+        ]
+    );
+}
@@ -0,0 +1,204 @@
+//! `libtock_bridge` tunnels a console over 802.15.4, for debugging motes that
+//! are out of reach of a serial cable.
+//!
+//! A [`Bridge`] forwards the bytes read from the console in data frames, and
+//! writes the bytes of the data frames it receives to the console. Running a
+//! bridge on a mote attached to the host and on the remote mote makes a
+//! transparent serial tunnel between the host and the remote mote's console.
+//! Every frame starts with a 3-byte header:
+//!
+//! | Offset | Size | Content                                                    |
+//! |--------|------|------------------------------------------------------------|
+//! | 0      | 1    | [`DISPATCH`]                                               |
+//! | 1      | 1    | Sequence number of the last data frame sent                |
+//! | 2      | 1    | Sequence number of the last data frame written to console  |
+//!
+//! followed by the bytes, if any. Frames without bytes only carry the header,
+//! which the receiver of a data frame sends back once it wrote the bytes.
+//!
+//! # Flow control
+//!
+//! A bridge has at most `N - 1` data frames in flight, i.e. not written to the
+//! peer's console yet, which is what the peer's ring buffer holds if both
+//! bridges use the same `N`. While its window is full, a bridge keeps reading
+//! from the console, and sends the bytes read meanwhile in one data frame once
+//! the window opens. Once it holds [`MAX_DATA_LEN`] bytes, it stops reading,
+//! and the console drops its input.
+//!
+//! The console is read one byte at a time, so that each byte is forwarded as
+//! soon as it is typed, rather than once a whole frame's worth is read.
+//!
+//! Frames are not retransmitted: a lost frame loses its bytes, and keeps its
+//! place in the window until a later frame from the peer acknowledges a later
+//! data frame.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::bridge::Bridge;
+//! use libtock::ieee802154::{Ieee802154, RxRingBuffer};
+//!
+//! fn main() {
+//!     Ieee802154::radio_on().unwrap();
+//!     let mut buf = RxRingBuffer::<4>::new();
+//!     let error = Bridge::new(&mut buf).run();
+//! }
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+use core::cell::Cell;
+use libtock_console::{Console, ReadBuffer, ReadSubscribe};
+use libtock_future::{block_on, select, wait_for_upcall, Either};
+use libtock_ieee802154::{
+    Config, Ieee802154, RxBuffer, RxRingBuffer, RxSingleBufferOperator, RxSubscribe,
+    MAX_PAYLOAD_LEN,
+};
+use libtock_platform::{share, DefaultConfig, ErrorCode, Syscalls};
+
+/// The first byte of bridge frames, which tells them apart from other
+/// payloads.
+pub const DISPATCH: u8 = 0xF8;
+
+/// The maximum number of bytes of a data frame.
+pub const MAX_DATA_LEN: usize = MAX_PAYLOAD_LEN - HEADER_LEN;
+
+/// Forwards bytes between the console and 802.15.4 frames, in both directions.
+/// It receives frames in its own ring buffer of `N` frames.
+///
+/// The console's input and the received frames are awaited together, so that
+/// neither direction waits for the other.
+pub struct Bridge<'buf, S: Syscalls, const N: usize, C: Config = DefaultConfig> {
+    operator: RxSingleBufferOperator<'buf, N, S, C>,
+    window: u8,
+    // The sequence number of the last data frame sent.
+    sent: u8,
+    // The sequence number of the last data frame the peer wrote to its console.
+    acked: u8,
+    // The sequence number of the last data frame written to the console.
+    written: u8,
+    // The bytes read from the console, and not sent yet.
+    input: [u8; MAX_DATA_LEN],
+    pending: usize,
+}
+
+impl<'buf, S: Syscalls, const N: usize, C: Config> Bridge<'buf, S, N, C> {
+    pub fn new(buf: &'buf mut RxRingBuffer<N>) -> Self {
+        Bridge {
+            operator: RxSingleBufferOperator::new(buf),
+            window: N.saturating_sub(1).clamp(1, u8::MAX as usize) as u8,
+            sent: 0,
+            acked: 0,
+            written: 0,
+            input: [0; MAX_DATA_LEN],
+            pending: 0,
+        }
+    }
+
+    /// Forwards bytes until an error occurs, and returns it.
+    pub fn run(&mut self) -> ErrorCode {
+        loop {
+            if let Err(error) = self.step() {
+                return error;
+            }
+        }
+    }
+
+    /// Waits for console input or frames, and forwards them.
+    pub fn step(&mut self) -> Result<(), ErrorCode> {
+        if !self.forward_frames()? {
+            self.wait()?;
+            self.forward_frames()?;
+        }
+        if self.sent.wrapping_sub(self.acked) < self.window {
+            self.send()?;
+        }
+        Ok(())
+    }
+
+    // Waits for a byte from the console or a frame, and appends the byte, if
+    // any, to the input.
+    fn wait(&mut self) -> Result<(), ErrorCode> {
+        let read: Cell<Option<(u32, u32)>> = Cell::new(None);
+        let received: Cell<Option<(u32,)>> = Cell::new(None);
+        let pending = self.pending;
+        let event = share::scope::<Handles<S>, _, _>(|handle| {
+            let (read_buffer, read_subscribe, rx_buffer, rx_subscribe) = handle.split();
+            if pending < MAX_DATA_LEN {
+                // A read left pending by a previous step completes into
+                // the byte shared now, which is the same one, as the input
+                // only grows once a read completes.
+                match Console::<S, C>::read_start(
+                    &mut self.input[pending..pending + 1],
+                    &read,
+                    read_buffer,
+                    read_subscribe,
+                ) {
+                    Ok(()) | Err(ErrorCode::Busy) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            self.operator
+                .receive_start(&received, rx_buffer, rx_subscribe)?;
+            Ok(block_on::<S, _>(select(
+                wait_for_upcall(&read),
+                wait_for_upcall(&received),
+            )))
+        })?;
+        if let Either::Left((0, len)) = event {
+            self.pending += len as usize;
+        }
+        Ok(())
+    }
+
+    // Transmits the input in a data frame, if there is any.
+    fn send(&mut self) -> Result<(), ErrorCode> {
+        let len = core::mem::take(&mut self.pending);
+        if len == 0 {
+            return Ok(());
+        }
+        self.sent = self.sent.wrapping_add(1);
+        let mut frame = [0; MAX_PAYLOAD_LEN];
+        frame[..HEADER_LEN].copy_from_slice(&[DISPATCH, self.sent, self.written]);
+        frame[HEADER_LEN..HEADER_LEN + len].copy_from_slice(&self.input[..len]);
+        Ieee802154::<S, C>::transmit_frame(&frame[..HEADER_LEN + len])
+    }
+
+    // Writes the bytes of the received data frames to the console, and
+    // acknowledges them. Returns whether any frame was received.
+    fn forward_frames(&mut self) -> Result<bool, ErrorCode> {
+        let mut any = false;
+        while let Some(frame) = self.operator.try_receive_frame() {
+            any = true;
+            let [DISPATCH, sequence, written, ref data @ ..] = *frame.payload() else {
+                continue;
+            };
+            // Acknowledgements of frames that were not sent are ignored.
+            if written.wrapping_sub(self.acked) <= self.sent.wrapping_sub(self.acked) {
+                self.acked = written;
+            }
+            if !data.is_empty() {
+                Console::<S, C>::write(data)?;
+                self.written = sequence;
+                Ieee802154::<S, C>::transmit_frame(&[DISPATCH, self.sent, self.written])?;
+            }
+        }
+        Ok(any)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const HEADER_LEN: usize = 3;
+
+// The buffers and subscriptions of a step.
+type Handles<'share, S> = (
+    ReadBuffer<'share, S>,
+    ReadSubscribe<'share, S>,
+    RxBuffer<'share, S>,
+    RxSubscribe<'share, S>,
+);
@@ -0,0 +1,90 @@
+use super::*;
+use libtock_unittest::fake::{self, ieee802154::Frame as FakeFrame};
+
+type Bridge<'buf> = super::Bridge<'buf, fake::Syscalls, 3>;
+
+// Makes the radio receive `payload`.
+fn deliver(phy: &fake::Ieee802154Phy, payload: &[u8]) {
+    phy.radio_receive_frame(FakeFrame::with_body(payload));
+    phy.driver_receive_pending_frames();
+    phy.trigger_rx_upcall();
+}
+
+#[test]
+fn console_to_radio() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&console);
+    kernel.add_driver(&phy);
+    let mut buf = RxRingBuffer::new();
+    let mut bridge = Bridge::new(&mut buf);
+
+    // Each byte is sent as soon as it is read.
+    console.queue_input(b"ls");
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [[DISPATCH, 1, 0, b'l']]);
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(phy.take_transmitted_frames(), [[DISPATCH, 2, 0, b's']]);
+}
+
+#[test]
+fn radio_to_console() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&console);
+    kernel.add_driver(&phy);
+    let mut buf = RxRingBuffer::new();
+    let mut bridge = Bridge::new(&mut buf);
+
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        move || {
+            deliver(&phy, b"noise");
+            deliver(&phy, &[DISPATCH, 5, 0, b'h', b'i']);
+        }
+    });
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(console.take_bytes(), b"hi");
+    // The data frame is acknowledged.
+    assert_eq!(phy.take_transmitted_frames(), [[DISPATCH, 0, 5]]);
+    // The console read is still pending, for the next step.
+    assert!(console.pending_read().is_some());
+}
+
+#[test]
+fn flow_control() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&console);
+    kernel.add_driver(&phy);
+    let mut buf = RxRingBuffer::new();
+    let mut bridge = Bridge::new(&mut buf);
+
+    console.queue_input(b"abcd");
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [[DISPATCH, 1, 0, b'a'], [DISPATCH, 2, 0, b'b']]
+    );
+
+    // The window of 2 frames is full, so the bytes read are held until the
+    // peer acknowledges the first frame, then sent together.
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(bridge.step(), Ok(()));
+    assert!(phy.take_transmitted_frames().is_empty());
+    kernel.set_idle_handler({
+        let phy = phy.clone();
+        move || deliver(&phy, &[DISPATCH, 0, 1])
+    });
+    assert_eq!(bridge.step(), Ok(()));
+    assert_eq!(
+        phy.take_transmitted_frames(),
+        [[DISPATCH, 3, 0, b'c', b'd']]
+    );
+    // The console is still read.
+    assert!(console.pending_read().is_some());
+}
@@ -1,3 +1,7 @@
 fn main() {
-    libtock_build_scripts::auto_layout();
+    // Host builds are ordinary host programs, which do not use Tock's linker
+    // scripts.
+    if std::env::var_os("CARGO_FEATURE_HOST").is_none() {
+        libtock_build_scripts::auto_layout();
+    }
 }
@@ -0,0 +1,38 @@
+//! `libtock_critical_section` provides an implementation of the
+//! [`critical-section`](https://docs.rs/critical-section) crate for Tock
+//! processes.
+//!
+//! A Tock process is single-threaded, and upcalls only run when the process
+//! calls Yield. As long as the process does not yield, no other code from the
+//! process can run, so entering a critical section does not need to do
+//! anything. The kernel may preempt the process to run itself or other
+//! processes, but those cannot access the process' memory.
+//!
+//! Process binaries that use crates which require a `critical-section`
+//! implementation (e.g. `embedded-alloc` or `portable-atomic`) should depend
+//! on this crate and link it in with:
+//! ```ignore
+//! extern crate libtock_critical_section;
+//! ```
+//!
+//! Calling Yield inside a critical section runs upcalls, which may enter
+//! critical sections themselves. This nests the same way a recursive call
+//! would: data protected by a `critical_section::Mutex` is only accessible
+//! through shared references, so a `RefCell` inside it will panic rather than
+//! hand out aliasing mutable borrows.
+
+#![no_std]
+
+use critical_section::RawRestoreState;
+
+struct TockCriticalSection;
+critical_section::set_impl!(TockCriticalSection);
+
+// Safety: upcalls only run during Yield, and the process has a single thread,
+// so no other code from this process can run while a critical section is held
+// unless the critical section itself yields (see the crate documentation).
+unsafe impl critical_section::Impl for TockCriticalSection {
+    unsafe fn acquire() -> RawRestoreState {}
+
+    unsafe fn release(_restore_state: RawRestoreState) {}
+}
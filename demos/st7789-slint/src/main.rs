@@ -5,6 +5,7 @@
 #![no_std]
 
 extern crate alloc;
+extern crate libtock_critical_section;
 
 use core::fmt::Write;
 use libtock::alarm::{Alarm, Milliseconds};
@@ -14,7 +15,6 @@ use libtock::platform::ErrorCode;
 use libtock::runtime::{set_main, stack_size};
 use libtock::spi_controller::EmbeddedHalSpi;
 
-use critical_section::RawRestoreState;
 use embedded_alloc::Heap;
 
 use display_interface_spi::SPIInterface;
@@ -29,22 +29,6 @@ stack_size! {0x1400}
 #[global_allocator]
 static HEAP: Heap = Heap::empty();
 
-struct MyCriticalSection;
-critical_section::set_impl!(MyCriticalSection);
-
-unsafe impl critical_section::Impl for MyCriticalSection {
-    unsafe fn acquire() -> RawRestoreState {
-        // Tock is single threaded, so this can only be preempted by interrupts
-        // The kernel won't schedule anything from our app unless we yield
-        // so as long as we don't yield we won't concurrently run with
-        // other critical sections from our app.
-        // The kernel might schedule itself or other applications, but there
-        // is nothing we can do about that.
-    }
-
-    unsafe fn release(_token: RawRestoreState) {}
-}
-
 // Setup the heap and the global allocator.
 unsafe fn setup_heap() {
     use core::mem::MaybeUninit;
@@ -0,0 +1,52 @@
+//! A simple libtock-rs example. Reports the battery state every 10 seconds,
+//! and announces when the battery runs low.
+
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+use core::fmt::Write;
+use libtock::alarm::{Alarm, Milliseconds};
+use libtock::battery::{Battery, LowBatteryListener};
+use libtock::console::Console;
+use libtock::runtime::{set_main, stack_size};
+use libtock_platform::share;
+
+set_main! {main}
+stack_size! {0x300}
+
+fn main() {
+    if Battery::exists().is_err() {
+        writeln!(Console::writer(), "battery driver unavailable").unwrap();
+        return;
+    }
+
+    let low = Cell::new(false);
+    let listener = LowBatteryListener(|_| low.set(true));
+    share::scope(|subscribe| {
+        Battery::register_low_battery_listener(&listener, subscribe).unwrap();
+        Battery::set_low_threshold(20).unwrap();
+
+        loop {
+            if let (Ok(mv), Ok(percentage), Ok(state)) = (
+                Battery::voltage_mv(),
+                Battery::percentage(),
+                Battery::charging_state(),
+            ) {
+                writeln!(
+                    Console::writer(),
+                    "battery: {} mV, {}%, {:?}",
+                    mv,
+                    percentage,
+                    state
+                )
+                .unwrap();
+            }
+            if low.take() {
+                writeln!(Console::writer(), "battery low!").unwrap();
+            }
+
+            Alarm::sleep_for(Milliseconds(10000)).unwrap();
+        }
+    });
+}
@@ -0,0 +1,18 @@
+//! Measures the latency of system calls, and of the `share::scope`
+//! abstractions built on them, and reports the results over the console.
+
+#![no_main]
+#![no_std]
+use libtock::bench::{Bench, Benchmark};
+use libtock::runtime::{set_main, stack_size};
+use libtock::temperature::Temperature;
+
+set_main! {main}
+stack_size! {0x400}
+
+fn main() {
+    Bench::run(&Bench::syscalls());
+    Bench::run(&[Benchmark::new("temperature", || {
+        Temperature::read_temperature_sync().map(|_| ())
+    })]);
+}
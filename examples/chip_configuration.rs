@@ -0,0 +1,60 @@
+//! A simple libtock-rs example. Prints the identity of the device it runs on.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::chip_configuration::ChipConfiguration;
+use libtock::console::Console;
+use libtock::runtime::{set_main, stack_size};
+
+set_main! {main}
+stack_size! {0x300}
+
+fn main() {
+    if ChipConfiguration::exists().is_err() {
+        writeln!(Console::writer(), "chip configuration driver unavailable").unwrap();
+        return;
+    }
+
+    let mut name = [0; 32];
+    match ChipConfiguration::board_name(&mut name) {
+        Ok(len) => {
+            let name = core::str::from_utf8(&name[..len.min(name.len())]).unwrap_or("?");
+            writeln!(Console::writer(), "board: {}", name).unwrap();
+        }
+        Err(e) => writeln!(Console::writer(), "board: {:?}", e).unwrap(),
+    }
+    let mut id = [0; 16];
+    if let Ok(len) = ChipConfiguration::device_id(&mut id) {
+        write!(Console::writer(), "device ID: ").unwrap();
+        for byte in &id[..len.min(id.len())] {
+            write!(Console::writer(), "{:02x}", byte).unwrap();
+        }
+        writeln!(Console::writer()).unwrap();
+    }
+    if let Ok(reason) = ChipConfiguration::reset_reason() {
+        writeln!(Console::writer(), "last reset: {:?}", reason).unwrap();
+    }
+    if let Ok(revision) = ChipConfiguration::hardware_revision() {
+        writeln!(Console::writer(), "revision: {}", revision).unwrap();
+    }
+    if let Ok(serial) = ChipConfiguration::serial_number() {
+        writeln!(Console::writer(), "serial: {:016x}", serial).unwrap();
+    }
+    if let Ok(mac) = ChipConfiguration::ieee_mac() {
+        writeln!(Console::writer(), "MAC: {}", mac).unwrap();
+    }
+    if let (Ok(hz), Ok(source)) = (
+        ChipConfiguration::system_clock_hz(),
+        ChipConfiguration::clock_source(),
+    ) {
+        writeln!(Console::writer(), "clock: {} Hz ({:?})", hz, source).unwrap();
+    }
+    if let (Ok(flash), Ok(ram)) = (
+        ChipConfiguration::flash_size(),
+        ChipConfiguration::ram_size(),
+    ) {
+        writeln!(Console::writer(), "flash: {} B, RAM: {} B", flash, ram).unwrap();
+    }
+}
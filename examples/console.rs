@@ -4,11 +4,15 @@
 #![no_main]
 #![no_std]
 use core::fmt::Write;
-use libtock::console::Console;
-use libtock::runtime::{set_main, stack_size};
+use libtock::console::{self, Console};
+use libtock::runtime::{self, set_main, stack_size};
 
 set_main! {main}
-stack_size! {0x100}
+stack_size! {
+    0x100,
+    covers: [runtime::STACK_USAGE, console::STACK_USAGE],
+    margin: 25,
+}
 
 fn main() {
     writeln!(Console::writer(), "Hello world!").unwrap();
@@ -0,0 +1,20 @@
+//! Prints the drivers that are present on the board.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::console::Console;
+use libtock::drivers::Drivers;
+use libtock::runtime::{set_main, stack_size};
+
+set_main! {main}
+stack_size! {0x300}
+
+fn main() {
+    let drivers = Drivers::probe();
+    writeln!(Console::writer(), "Drivers ({:#018x}):", drivers.bits()).unwrap();
+    for driver in drivers.iter() {
+        writeln!(Console::writer(), "  {:?}", driver).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! Prints the fixes of a GNSS receiver connected to a secondary UART.
+
+#![no_main]
+#![no_std]
+use core::fmt::Write;
+use libtock::console::Console;
+use libtock::gnss::Gnss;
+use libtock::runtime::{set_main, stack_size};
+
+set_main! {main}
+stack_size! {0x400}
+
+// The driver number of the UART the receiver is connected to, which depends on
+// the board.
+const UART_DRIVER_NUM: u32 = 0x90002;
+
+fn main() {
+    if !Gnss::<UART_DRIVER_NUM>::exists() {
+        writeln!(Console::writer(), "GNSS UART not found").unwrap();
+        return;
+    }
+    let mut gnss = Gnss::<UART_DRIVER_NUM>::new();
+    for fix in gnss.fixes() {
+        let Ok(fix) = fix else {
+            continue;
+        };
+        writeln!(
+            Console::writer(),
+            "{:02}:{:02}:{:02} lat={} lon={} hdop={:?}",
+            fix.time.hour,
+            fix.time.minute,
+            fix.time.second,
+            fix.position.latitude,
+            fix.position.longitude,
+            fix.hdop,
+        )
+        .unwrap();
+    }
+}
@@ -1,12 +1,13 @@
 //! An example showing use of IEEE 802.15.4 networking.
-//! It infinitely sends a frame with a constantly incremented counter,
-//! and after each send receives a frame and prints it to Console.
+//! It infinitely sends a telemetry record with a constantly incremented counter,
+//! and after each send receives a record and prints it to Console.
 
 #![no_main]
 #![no_std]
 use core::fmt::Write as _;
 use libtock::alarm::{Alarm, Milliseconds};
 use libtock::console::Console;
+use libtock::ieee802154::telemetry::{Channel, ChannelKind, Record, MAX_RECORD_LEN};
 use libtock::ieee802154::{Ieee802154, RxOperator as _, RxRingBuffer, RxSingleBufferOperator};
 use libtock::runtime::{set_main, stack_size};
 
@@ -37,44 +38,46 @@ fn main() {
     let mut buf = RxRingBuffer::<2>::new();
     let mut operator = RxSingleBufferOperator::new(&mut buf);
 
-    let mut counter = 0_usize;
-    let mut buf = [
-        b'f', b'r', b'a', b'm', b'e', b' ', b'n', b'.', b'o', b'.', b' ', b'\0', b'\0', b'\0',
-        b'\0',
-    ];
-    fn set_buf_cnt(buf: &mut [u8], counter: &mut usize) {
-        let buf_len = buf.len();
-        let buf_cnt = &mut buf[buf_len - core::mem::size_of_val(&counter)..];
-        buf_cnt.copy_from_slice(&counter.to_be_bytes());
-    }
+    let mut counter = 0_i32;
+    let mut buf = [0; MAX_RECORD_LEN];
 
     loop {
         Alarm::sleep_for(Milliseconds(1000)).unwrap();
 
-        set_buf_cnt(&mut buf, &mut counter);
+        let timestamp = Alarm::get_milliseconds().unwrap() as u32;
+        let mut record = Record::new(addr_short, timestamp);
+        record
+            .push(Channel {
+                kind: ChannelKind::Counter,
+                value: counter,
+            })
+            .unwrap();
+        let len = record.encode(&mut buf).unwrap();
 
         // Transmit a frame
-        Ieee802154::transmit_frame(&buf).unwrap();
+        Ieee802154::transmit_frame(&buf[..len]).unwrap();
 
         writeln!(Console::writer(), "Transmitted frame {}!\n", counter).unwrap();
 
         let frame = operator.receive_frame().unwrap();
 
-        let body_len = frame.payload_len;
-        writeln!(
-            Console::writer(),
-            "Received frame with body of len {}: {}-{} {:?}!\n",
-            body_len,
-            core::str::from_utf8(&frame.body[..frame.body.len() - core::mem::size_of::<usize>()])
-                .unwrap_or("<error decoding>"),
-            usize::from_le_bytes(
-                frame.body[frame.body.len() - core::mem::size_of::<usize>()..]
-                    .try_into()
-                    .unwrap()
-            ),
-            frame.body
-        )
-        .unwrap();
+        match Record::decode(frame.payload()) {
+            Ok(record) => {
+                writeln!(
+                    Console::writer(),
+                    "Received record from {:#06x} at {} ms:",
+                    record.device_id,
+                    record.timestamp
+                )
+                .unwrap();
+                for channel in record.channels() {
+                    writeln!(Console::writer(), "  {:?}: {}", channel.kind, channel.value).unwrap();
+                }
+            }
+            Err(error) => {
+                writeln!(Console::writer(), "Received invalid record: {:?}", error).unwrap()
+            }
+        }
 
         counter += 1;
     }
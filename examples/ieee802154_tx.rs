@@ -1,5 +1,5 @@
 //! An example showing use of IEEE 802.15.4 networking.
-//! It infinitely sends a frame with a constantly incremented counter.
+//! It infinitely sends a telemetry record with a constantly incremented counter.
 
 #![no_main]
 #![no_std]
@@ -7,6 +7,7 @@ use core::fmt::Write as _;
 
 use libtock::alarm::{Alarm, Milliseconds};
 use libtock::console::Console;
+use libtock::ieee802154::telemetry::{Channel, ChannelKind, Record, MAX_RECORD_LEN};
 use libtock::ieee802154::Ieee802154;
 use libtock::runtime::{set_main, stack_size};
 
@@ -34,24 +35,24 @@ fn main() {
     Ieee802154::radio_on().unwrap();
     assert!(Ieee802154::is_on());
 
-    let mut counter = 0_usize;
-    let mut buf = [
-        b'f', b'r', b'a', b'm', b'e', b' ', b'n', b'.', b'o', b'.', b' ', b'\0', b'\0', b'\0',
-        b'\0',
-    ];
-    fn set_buf_cnt(buf: &mut [u8], counter: &mut usize) {
-        let buf_len = buf.len();
-        let buf_cnt = &mut buf[buf_len - core::mem::size_of_val(&counter)..];
-        buf_cnt.copy_from_slice(&counter.to_be_bytes());
-    }
+    let mut counter = 0_i32;
+    let mut buf = [0; MAX_RECORD_LEN];
 
     loop {
         Alarm::sleep_for(Milliseconds(1000)).unwrap();
 
-        set_buf_cnt(&mut buf, &mut counter);
+        let timestamp = Alarm::get_milliseconds().unwrap() as u32;
+        let mut record = Record::new(addr_short, timestamp);
+        record
+            .push(Channel {
+                kind: ChannelKind::Counter,
+                value: counter,
+            })
+            .unwrap();
+        let len = record.encode(&mut buf).unwrap();
 
         // Transmit a frame
-        Ieee802154::transmit_frame(&buf).unwrap();
+        Ieee802154::transmit_frame(&buf[..len]).unwrap();
 
         writeln!(Console::writer(), "Transmitted frame {}!\n", counter).unwrap();
 
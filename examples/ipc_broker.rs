@@ -0,0 +1,32 @@
+//! A libtock-rs IPC publish/subscribe broker. Clients subscribe to topics and
+//! publish messages through it with `libtock::ipc::pubsub::PubSubClient`.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::console::Console;
+use libtock::ipc::pubsub::PubSubBroker;
+use libtock::ipc::IpcServer;
+use libtock::runtime::{set_main, stack_size};
+use libtock_platform::{share, Syscalls};
+use libtock_runtime::TockSyscalls;
+
+set_main! {main}
+stack_size! {0x400}
+
+fn main() {
+    if IpcServer::exists().is_err() {
+        writeln!(Console::writer(), "IPC driver unavailable").unwrap();
+        return;
+    }
+
+    let broker = PubSubBroker::<8>::new();
+    let listener = broker.listener();
+    share::scope(|subscribe| {
+        IpcServer::register_listener(&listener, subscribe).unwrap();
+        loop {
+            TockSyscalls::yield_wait();
+        }
+    });
+}
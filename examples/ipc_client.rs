@@ -0,0 +1,56 @@
+//! A simple libtock-rs IPC client for the `ipc_server` example. Sends a
+//! message to the service every second and prints its upper-case reply.
+
+#![no_main]
+#![no_std]
+
+use core::cell::Cell;
+use core::fmt::Write;
+use libtock::alarm::{Alarm, Milliseconds};
+use libtock::console::Console;
+use libtock::ipc::{client_upcall, IpcClient, IpcClientListener};
+use libtock::runtime::{set_main, stack_size};
+use libtock_platform::{share, Syscalls};
+use libtock_runtime::TockSyscalls;
+
+set_main! {main}
+stack_size! {0x400}
+
+// The process ID of the service, which is loaded right after this process.
+const SERVICE: u32 = 1;
+
+fn main() {
+    match IpcClient::discover(b"ipc_server") {
+        Ok(SERVICE) => {}
+        Ok(service) => {
+            writeln!(Console::writer(), "ipc_server has process ID {}", service).unwrap();
+            return;
+        }
+        Err(_) => {
+            writeln!(Console::writer(), "ipc_server not found").unwrap();
+            return;
+        }
+    }
+
+    let done = Cell::new(false);
+    let listener = IpcClientListener(|_| done.set(true));
+    loop {
+        let mut buffer = *b"hello, service";
+        done.set(false);
+        share::scope(|allow| {
+            share::scope(|subscribe| {
+                IpcClient::share_buffer::<SERVICE>(allow, &mut buffer).unwrap();
+                IpcClient::register_listener::<_, { client_upcall(SERVICE) }>(&listener, subscribe)
+                    .unwrap();
+                IpcClient::notify_service(SERVICE).unwrap();
+                while !done.get() {
+                    TockSyscalls::yield_wait();
+                }
+            });
+        });
+        let reply = core::str::from_utf8(&buffer).unwrap_or("<invalid UTF-8>");
+        writeln!(Console::writer(), "reply: {}", reply).unwrap();
+
+        Alarm::sleep_for(Milliseconds(1000)).unwrap();
+    }
+}
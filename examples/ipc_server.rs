@@ -0,0 +1,33 @@
+//! A simple libtock-rs IPC service. Converts the text in each client's shared
+//! buffer to upper case, and notifies the client when done.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::console::Console;
+use libtock::ipc::{IpcServer, IpcServerListener};
+use libtock::runtime::{set_main, stack_size};
+use libtock_platform::{share, Syscalls};
+use libtock_runtime::TockSyscalls;
+
+set_main! {main}
+stack_size! {0x400}
+
+fn main() {
+    if IpcServer::exists().is_err() {
+        writeln!(Console::writer(), "IPC driver unavailable").unwrap();
+        return;
+    }
+
+    let listener = IpcServerListener::new(|client, buffer: &mut [u8]| {
+        buffer.make_ascii_uppercase();
+        let _ = IpcServer::notify_client(client);
+    });
+    share::scope(|subscribe| {
+        IpcServer::register_listener(&listener, subscribe).unwrap();
+        loop {
+            TockSyscalls::yield_wait();
+        }
+    });
+}
@@ -0,0 +1,26 @@
+//! A simple libtock-rs example. Prints a message, waits a few seconds, then
+//! asks the kernel to reboot the board.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::alarm::Milliseconds;
+use libtock::console::Console;
+use libtock::reboot::Reboot;
+use libtock::runtime::{set_main, stack_size};
+
+set_main! {main}
+stack_size! {0x200}
+
+fn main() {
+    if Reboot::exists().is_err() {
+        writeln!(Console::writer(), "reboot driver unavailable").unwrap();
+        return;
+    }
+
+    writeln!(Console::writer(), "Rebooting in 3 seconds...").unwrap();
+    if let Err(e) = Reboot::reboot_after(Milliseconds(3000)) {
+        writeln!(Console::writer(), "reboot failed: {:?}", e).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+//! Draws colored stripes on the screen, one row of pixels at a time.
+
+#![no_main]
+#![no_std]
+use core::fmt::Write;
+use libtock::console::Console;
+use libtock::runtime::{set_main, stack_size};
+use libtock::screen::{PixelFormat, Screen};
+
+set_main! {main}
+stack_size! {0x400}
+
+// RGB565 colors, big-endian.
+const STRIPES: [u16; 3] = [0xF800, 0x07E0, 0x001F];
+
+fn main() {
+    if Screen::exists().is_err() {
+        writeln!(Console::writer(), "screen not found").unwrap();
+        return;
+    }
+    if Screen::pixel_format() != Ok(PixelFormat::Rgb565) {
+        writeln!(Console::writer(), "this example needs an RGB565 screen").unwrap();
+        return;
+    }
+    let (width, height) = Screen::resolution().unwrap();
+    Screen::set_power(true).unwrap();
+    Screen::set_brightness(u16::MAX).unwrap();
+
+    let mut row = [0; 2 * 320];
+    let row_len = core::cmp::min(2 * width as usize, row.len());
+    for y in 0..height {
+        let color = STRIPES[(y * 3 / height) as usize].to_be_bytes();
+        for pixel in row[..row_len].chunks_mut(2) {
+            pixel.copy_from_slice(&color);
+        }
+        Screen::set_write_frame(0, y, row_len as u32 / 2, 1).unwrap();
+        Screen::write(&row[..row_len]).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+//! A simple libtock-rs example. Checks for the supply monitor driver and
+//! reports the supply voltage and die temperature every 10 seconds.
+
+#![no_main]
+#![no_std]
+
+use core::fmt::Write;
+use libtock::console::Console;
+
+use libtock::alarm::{Alarm, Milliseconds};
+use libtock::fmt::Centi;
+use libtock::runtime::{set_main, stack_size};
+use libtock::supply_monitor::SupplyMonitor;
+
+set_main! {main}
+stack_size! {0x200}
+
+fn main() {
+    if SupplyMonitor::exists().is_err() {
+        writeln!(Console::writer(), "supply monitor driver unavailable").unwrap();
+        return;
+    }
+
+    loop {
+        match SupplyMonitor::vcc_mv() {
+            Ok(mv) => writeln!(Console::writer(), "VCC: {} mV", mv).unwrap(),
+            Err(e) => writeln!(Console::writer(), "VCC: {:?}", e).unwrap(),
+        }
+        match SupplyMonitor::die_temperature() {
+            Ok(temp_val) => {
+                writeln!(Console::writer(), "Die temperature: {}*C", Centi(temp_val)).unwrap()
+            }
+            Err(e) => writeln!(Console::writer(), "Die temperature: {:?}", e).unwrap(),
+        }
+
+        Alarm::sleep_for(Milliseconds(10000)).unwrap();
+    }
+}
@@ -1,13 +1,16 @@
 //! A simple libtock-rs example. Checks for temperature driver
 //! and samples the sensor every 2 seconds.
+//!
+//! The temperature is formatted with `libtock::fmt` rather than `core::fmt`,
+//! which keeps the binary small.
 
 #![no_main]
 #![no_std]
 
-use core::fmt::Write;
 use libtock::console::Console;
 
 use libtock::alarm::{Alarm, Milliseconds};
+use libtock::fmt::{Buffer, Centi};
 use libtock::runtime::{set_main, stack_size};
 use libtock::temperature::Temperature;
 
@@ -16,24 +19,22 @@ stack_size! {0x200}
 
 fn main() {
     match Temperature::exists() {
-        Ok(()) => writeln!(Console::writer(), "temperature driver available").unwrap(),
+        Ok(()) => Console::write(b"temperature driver available\n").unwrap(),
         Err(_) => {
-            writeln!(Console::writer(), "temperature driver unavailable").unwrap();
+            Console::write(b"temperature driver unavailable\n").unwrap();
             return;
         }
     }
 
+    let mut buffer = Buffer::new();
     loop {
         match Temperature::read_temperature_sync() {
-            Ok(temp_val) => writeln!(
-                Console::writer(),
-                "Temperature: {}{}.{}*C\n",
-                if temp_val > 0 { "" } else { "-" },
-                i32::abs(temp_val) / 100,
-                i32::abs(temp_val) % 100
-            )
-            .unwrap(),
-            Err(_) => writeln!(Console::writer(), "error while reading temperature",).unwrap(),
+            Ok(temp_val) => {
+                Console::write(b"Temperature: ").unwrap();
+                Console::write(buffer.format(Centi(temp_val)).as_bytes()).unwrap();
+                Console::write(b"*C\n").unwrap();
+            }
+            Err(_) => Console::write(b"error while reading temperature\n").unwrap(),
         }
 
         Alarm::sleep_for(Milliseconds(2000)).unwrap();
@@ -0,0 +1,185 @@
+//! `libtock_fmt` formats numbers into text without `core::fmt`.
+//!
+//! Formatting an integer with `core::fmt` pulls in its generic formatting
+//! machinery (`Formatter`, padding, `Arguments`), which costs several
+//! kilobytes of flash. Processes that only print numbers and static strings
+//! can instead format numbers into a [`Buffer`], and write the resulting
+//! string directly:
+//!
+//! ```ignore
+//! let mut buffer = Buffer::new();
+//! Console::write(b"Temperature: ")?;
+//! Console::write(buffer.format(Centi(temperature)).as_bytes())?;
+//! Console::write(b" C\n")?;
+//! ```
+//!
+//! Besides the primitive integers, [`Buffer`] formats the fixed-point values
+//! [`Deci`], [`Centi`], and [`Milli`], which sensor drivers commonly report
+//! (e.g. temperatures in hundredths of a degree). These also implement
+//! `Display`, for use with `write!` where `core::fmt` is linked anyway.
+
+#![cfg_attr(not(test), no_std)]
+
+use core::fmt;
+
+/// The length of the longest formatted value (`i64::MIN`).
+pub const MAX_LEN: usize = 20;
+
+/// A buffer to format numbers into.
+pub struct Buffer {
+    bytes: [u8; MAX_LEN],
+}
+
+impl Buffer {
+    pub const fn new() -> Self {
+        Buffer {
+            bytes: [0; MAX_LEN],
+        }
+    }
+
+    /// Formats `value`, and returns the text, which is borrowed from the
+    /// buffer.
+    pub fn format<V: Format>(&mut self, value: V) -> &str {
+        let start = value.write(&mut self.bytes);
+        // Safety: `write` only writes ASCII digits, '-', and '.' to
+        // `bytes[start..]`, so it is valid UTF-8. This skips the UTF-8
+        // validation code.
+        unsafe { core::str::from_utf8_unchecked(&self.bytes[start..]) }
+    }
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A value a [`Buffer`] can format. Implemented for the primitive integers
+/// and the fixed-point types of this crate.
+pub trait Format: private::Sealed {
+    /// Writes the text of `self` to the end of `bytes`, and returns the index
+    /// where it starts.
+    #[doc(hidden)]
+    fn write(self, bytes: &mut [u8; MAX_LEN]) -> usize;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+// Writes the digits of `value` before `end`, and returns the index of the
+// first one. Separate from `write_u64` as 64-bit division is much larger (and
+// slower) on the 32-bit targets Tock runs on.
+fn write_u32(mut value: u32, bytes: &mut [u8; MAX_LEN], mut end: usize) -> usize {
+    loop {
+        end -= 1;
+        bytes[end] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            return end;
+        }
+    }
+}
+
+fn write_u64(mut value: u64, bytes: &mut [u8; MAX_LEN], mut end: usize) -> usize {
+    // Format the low digits with 32-bit arithmetic once the value fits.
+    while value > u32::MAX as u64 {
+        end -= 1;
+        bytes[end] = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+    write_u32(value as u32, bytes, end)
+}
+
+// Prepends a '-' to the text starting at `start` if `negative`.
+fn write_sign(negative: bool, bytes: &mut [u8; MAX_LEN], start: usize) -> usize {
+    if !negative {
+        return start;
+    }
+    bytes[start - 1] = b'-';
+    start - 1
+}
+
+macro_rules! format_unsigned {
+    ($write:ident: $($t:ty),*) => {$(
+        impl private::Sealed for $t {}
+        impl Format for $t {
+            fn write(self, bytes: &mut [u8; MAX_LEN]) -> usize {
+                $write(self as _, bytes, MAX_LEN)
+            }
+        }
+    )*};
+}
+
+macro_rules! format_signed {
+    ($write:ident: $($t:ty),*) => {$(
+        impl private::Sealed for $t {}
+        impl Format for $t {
+            fn write(self, bytes: &mut [u8; MAX_LEN]) -> usize {
+                let start = $write(self.unsigned_abs() as _, bytes, MAX_LEN);
+                write_sign(self < 0, bytes, start)
+            }
+        }
+    )*};
+}
+
+format_unsigned!(write_u32: u8, u16, u32);
+format_unsigned!(write_u64: u64);
+format_signed!(write_u32: i8, i16, i32);
+format_signed!(write_u64: i64);
+#[cfg(not(target_pointer_width = "64"))]
+format_unsigned!(write_u32: usize);
+#[cfg(not(target_pointer_width = "64"))]
+format_signed!(write_u32: isize);
+#[cfg(target_pointer_width = "64")]
+format_unsigned!(write_u64: usize);
+#[cfg(target_pointer_width = "64")]
+format_signed!(write_u64: isize);
+
+// Writes `value` / 10^`decimals` with exactly `decimals` digits after the
+// decimal point.
+fn write_fixed(value: i32, decimals: u32, bytes: &mut [u8; MAX_LEN]) -> usize {
+    let scale = 10u32.pow(decimals);
+    let abs = value.unsigned_abs();
+    // Pad the fraction with leading zeros by formatting it offset by `scale`,
+    // then overwriting the leading 1 with the decimal point.
+    let start = write_u32(scale + abs % scale, bytes, MAX_LEN);
+    bytes[start] = b'.';
+    let start = write_u32(abs / scale, bytes, start);
+    write_sign(value < 0, bytes, start)
+}
+
+macro_rules! fixed {
+    ($($(#[$doc:meta])* $name:ident: $decimals:literal),*) => {$(
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub struct $name(pub i32);
+
+        impl private::Sealed for $name {}
+        impl Format for $name {
+            fn write(self, bytes: &mut [u8; MAX_LEN]) -> usize {
+                write_fixed(self.0, $decimals, bytes)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(Buffer::new().format(*self))
+            }
+        }
+    )*};
+}
+
+fixed!(
+    /// A value in tenths, formatted with one decimal, e.g. `Deci(-5)` as `-0.5`.
+    Deci: 1,
+    /// A value in hundredths, formatted with two decimals, e.g. `Centi(2105)` as
+    /// `21.05`.
+    Centi: 2,
+    /// A value in thousandths, formatted with three decimals, e.g. `Milli(3300)`
+    /// as `3.300`.
+    Milli: 3
+);
+
+#[cfg(test)]
+mod tests;
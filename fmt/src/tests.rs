@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn integers() {
+    let mut buffer = Buffer::new();
+    assert_eq!(buffer.format(0u8), "0");
+    assert_eq!(buffer.format(u8::MAX), "255");
+    assert_eq!(buffer.format(i8::MIN), "-128");
+    assert_eq!(buffer.format(-7i16), "-7");
+    assert_eq!(buffer.format(1_000_000u32), "1000000");
+    assert_eq!(buffer.format(u32::MAX), "4294967295");
+    assert_eq!(buffer.format(i32::MIN), "-2147483648");
+    assert_eq!(buffer.format(u32::MAX as u64 + 1), "4294967296");
+    assert_eq!(buffer.format(u64::MAX), "18446744073709551615");
+    assert_eq!(buffer.format(i64::MIN), "-9223372036854775808");
+    assert_eq!(buffer.format(usize::MAX), format!("{}", usize::MAX));
+    assert_eq!(buffer.format(isize::MIN), format!("{}", isize::MIN));
+}
+
+#[test]
+fn fixed_point() {
+    let mut buffer = Buffer::new();
+    assert_eq!(buffer.format(Centi(2105)), "21.05");
+    assert_eq!(buffer.format(Centi(2150)), "21.50");
+    assert_eq!(buffer.format(Centi(-5)), "-0.05");
+    assert_eq!(buffer.format(Centi(0)), "0.00");
+    assert_eq!(buffer.format(Centi(i32::MIN)), "-21474836.48");
+    assert_eq!(buffer.format(Deci(-5)), "-0.5");
+    assert_eq!(buffer.format(Milli(3300)), "3.300");
+    assert_eq!(buffer.format(Milli(i32::MAX)), "2147483.647");
+}
+
+#[test]
+fn display() {
+    assert_eq!(format!("{}*C", Centi(-1234)), "-12.34*C");
+    assert_eq!(format!("{}", Milli(7)), "0.007");
+}
@@ -0,0 +1,249 @@
+//! `libtock_future` provides a lightweight future abstraction for Tock
+//! processes, along with a minimal executor that drives futures by yielding to
+//! the kernel.
+//!
+//! In a Tock process, the only events that can make an asynchronous operation
+//! progress are upcalls, and upcalls only run during Yield. Therefore, unlike
+//! `core::future::Future`, a [`TockFuture`] does not need a waker: the
+//! executor simply polls it again after every upcall.
+//!
+//! Events that recur, such as notifications from other processes, are
+//! [`TockStream`]s, whose next item is awaited with [`next`]. [`select`] waits
+//! for whichever of two futures completes first, which lets a process handle
+//! several event sources in one `block_on` loop, and [`join`] waits for both.
+//!
+//! Futures that depend on kernel state (allowed buffers and subscriptions)
+//! are created inside a `share::scope`, which guarantees the kernel's access
+//! is revoked before the borrowed data goes away, even if the future is
+//! leaked.
+
+#![cfg_attr(not(test), no_std)]
+
+use core::cell::Cell;
+use core::task::Poll;
+use libtock_platform::Syscalls;
+
+/// An asynchronous operation that can be polled for completion.
+pub trait TockFuture<S: Syscalls> {
+    /// The value the operation produces when it completes.
+    type Output;
+
+    /// Checks whether the operation has completed, without blocking. Once
+    /// `poll` has returned `Poll::Ready`, it should not be called again.
+    fn poll(&mut self) -> Poll<Self::Output>;
+}
+
+/// A future can be polled through a mutable reference, e.g. to keep polling it
+/// after a [`select`] it lost.
+impl<S: Syscalls, F: TockFuture<S>> TockFuture<S> for &mut F {
+    type Output = F::Output;
+
+    fn poll(&mut self) -> Poll<F::Output> {
+        (**self).poll()
+    }
+}
+
+/// Drives `future` to completion, putting the process to sleep while no
+/// upcalls are pending.
+///
+/// Between polls, pending upcalls are run with `yield_no_wait`. The process
+/// only blocks in `yield_wait` once nothing is pending and the future still
+/// cannot make progress.
+pub fn block_on<S: Syscalls, F: TockFuture<S>>(mut future: F) -> F::Output {
+    loop {
+        if let Poll::Ready(output) = future.poll() {
+            return output;
+        }
+        if !S::yield_no_wait_flag() {
+            S::yield_wait();
+        }
+    }
+}
+
+/// A future that completes once an upcall stores a value into a cell.
+///
+/// `Cell<Option<T>>` implements `Upcall` for tuples of `u32`, so the cell can
+/// be passed directly to `Syscalls::subscribe`. See [`wait_for_upcall`].
+pub struct UpcallFuture<'a, T> {
+    cell: &'a Cell<Option<T>>,
+}
+
+/// Returns a future that completes with the value an upcall stores into
+/// `cell`.
+///
+/// # Example
+/// ```ignore
+/// let called: Cell<Option<(u32,)>> = Cell::new(None);
+/// share::scope(|subscribe| {
+///     S::subscribe::<_, _, C, DRIVER_NUM, 0>(subscribe, &called)?;
+///     S::command(DRIVER_NUM, START, 0, 0).to_result()?;
+///     let (status,) = block_on::<S, _>(wait_for_upcall(&called));
+///     Ok(status)
+/// })
+/// ```
+pub fn wait_for_upcall<T>(cell: &Cell<Option<T>>) -> UpcallFuture<'_, T> {
+    UpcallFuture { cell }
+}
+
+impl<S: Syscalls, T> TockFuture<S> for UpcallFuture<'_, T> {
+    type Output = T;
+
+    fn poll(&mut self) -> Poll<T> {
+        match self.cell.take() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A source of items that become available over time, typically one per
+/// upcall. Unlike futures, streams do not complete: they can be polled for
+/// another item after each item.
+pub trait TockStream<S: Syscalls> {
+    /// The type of the items the stream produces.
+    type Item;
+
+    /// Returns the next item if one is available, without blocking.
+    fn poll_next(&mut self) -> Poll<Self::Item>;
+}
+
+/// A future that completes with the next item of a stream. See [`next`].
+pub struct Next<'a, St> {
+    stream: &'a mut St,
+}
+
+/// Returns a future that completes with the next item of `stream`.
+pub fn next<St>(stream: &mut St) -> Next<'_, St> {
+    Next { stream }
+}
+
+impl<S: Syscalls, St: TockStream<S>> TockFuture<S> for Next<'_, St> {
+    type Output = St::Item;
+
+    fn poll(&mut self) -> Poll<St::Item> {
+        self.stream.poll_next()
+    }
+}
+
+/// The output of [`select`]: the output of the first or second future.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+/// A future that completes with the output of whichever of two futures
+/// completes first. See [`select`].
+pub struct Select<A, B> {
+    a: A,
+    b: B,
+}
+
+/// Returns a future that completes with the output of whichever of `a` and
+/// `b` completes first. The other future is dropped without being completed.
+///
+/// `a` is polled first, so if both are ready, `a` wins. To handle several
+/// event sources fairly, select on their `next` futures in a loop: an event
+/// that loses is still returned by the following iteration.
+///
+/// # Example
+/// ```ignore
+/// loop {
+///     match block_on::<S, _>(select(next(&mut requests), wait_for_upcall(&alarm))) {
+///         Either::Left(request) => handle(request),
+///         Either::Right(_) => tick(),
+///     }
+/// }
+/// ```
+pub fn select<A, B>(a: A, b: B) -> Select<A, B> {
+    Select { a, b }
+}
+
+impl<S: Syscalls, A: TockFuture<S>, B: TockFuture<S>> TockFuture<S> for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.a.poll() {
+            return Poll::Ready(Either::Left(output));
+        }
+        self.b.poll().map(Either::Right)
+    }
+}
+
+/// A future that completes with the outputs of two futures, once both have
+/// completed. `TA` and `TB` are the futures' outputs. See [`join`].
+pub struct Join<A, B, TA, TB> {
+    a: Joined<TA, A>,
+    b: Joined<TB, B>,
+}
+
+/// Returns a future that completes with the outputs of `a` and `b`, once both
+/// have completed. This starts several operations together, e.g. sampling
+/// several sensors at once, and waits for all of them.
+///
+/// # Example
+/// ```ignore
+/// let (temperature, humidity) = block_on::<S, _>(join(
+///     wait_for_upcall(&temperature),
+///     wait_for_upcall(&humidity),
+/// ));
+/// ```
+pub fn join<A, B, TA, TB>(a: A, b: B) -> Join<A, B, TA, TB> {
+    Join {
+        a: Joined::Pending(a),
+        b: Joined::Pending(b),
+    }
+}
+
+impl<S, A, B, TA, TB> TockFuture<S> for Join<A, B, TA, TB>
+where
+    S: Syscalls,
+    A: TockFuture<S, Output = TA>,
+    B: TockFuture<S, Output = TB>,
+{
+    type Output = (TA, TB);
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        let a_ready = self.a.poll();
+        if !self.b.poll() || !a_ready {
+            return Poll::Pending;
+        }
+        match (self.a.take(), self.b.take()) {
+            (Some(a), Some(b)) => Poll::Ready((a, b)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+// A future of a join, and then its output until the join completes.
+enum Joined<T, F> {
+    Pending(F),
+    Ready(T),
+    Taken,
+}
+
+impl<T, F> Joined<T, F> {
+    // Polls the future if it has not completed yet. Returns whether it has.
+    fn poll<S: Syscalls>(&mut self) -> bool
+    where
+        F: TockFuture<S, Output = T>,
+    {
+        if let Joined::Pending(future) = self {
+            match future.poll() {
+                Poll::Ready(output) => *self = Joined::Ready(output),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    fn take(&mut self) -> Option<T> {
+        match core::mem::replace(self, Joined::Taken) {
+            Joined::Ready(output) => Some(output),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
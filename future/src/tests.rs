@@ -0,0 +1,180 @@
+use super::*;
+use libtock_platform::{share, DefaultConfig, YieldNoWaitReturn};
+use libtock_unittest::{fake, DriverInfo, DriverShareRef, ExpectedSyscall, SyscallLogEntry};
+use std::rc::Rc;
+
+const DRIVER_NUM: u32 = 0x1234;
+
+// A driver that schedules an upcall whenever it receives a command.
+#[derive(Default)]
+struct MockDriver {
+    share_ref: DriverShareRef,
+}
+
+impl fake::SyscallDriver for MockDriver {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, _: u32, argument0: u32, _: u32) -> libtock_platform::CommandReturn {
+        self.share_ref
+            .schedule_upcall(0, (argument0, 0, 0))
+            .expect("Unable to schedule upcall");
+        libtock_unittest::command_return::success()
+    }
+}
+
+#[test]
+fn ready_immediately() {
+    let kernel = fake::Kernel::new();
+    let called = Cell::new(Some((7,)));
+    assert_eq!(
+        block_on::<fake::Syscalls, _>(wait_for_upcall(&called)),
+        (7,)
+    );
+    assert_eq!(called.get(), None);
+    assert_eq!(kernel.take_syscall_log(), []);
+}
+
+#[test]
+fn upcall_pending() {
+    let kernel = fake::Kernel::new();
+    let driver = Rc::new(MockDriver::default());
+    kernel.add_driver(&driver);
+
+    let called: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &called)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, 0, 42, 0).is_success());
+        kernel.take_syscall_log();
+
+        // The upcall is already queued, so the executor should not block.
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(wait_for_upcall(&called)),
+            (42,)
+        );
+        assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldNoWait]);
+    });
+}
+
+#[test]
+fn blocks_when_idle() {
+    let kernel = fake::Kernel::new();
+    let driver = Rc::new(MockDriver::default());
+    kernel.add_driver(&driver);
+
+    let called: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &called)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, 0, 42, 0).is_success());
+        kernel.take_syscall_log();
+
+        // Pretend nothing was pending on the first yield-no-wait, so that the
+        // executor goes to sleep with yield-wait.
+        kernel.add_expected_syscall(ExpectedSyscall::YieldNoWait {
+            override_return: Some(YieldNoWaitReturn::NoUpcall),
+        });
+        kernel.add_expected_syscall(ExpectedSyscall::YieldWait { skip_upcall: true });
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(wait_for_upcall(&called)),
+            (42,)
+        );
+        assert_eq!(
+            kernel.take_syscall_log(),
+            [SyscallLogEntry::YieldNoWait, SyscallLogEntry::YieldWait]
+        );
+    });
+}
+
+// A stream of the values stored into a cell, as an upcall would.
+struct CellStream<'a>(&'a Cell<Option<u32>>);
+
+impl TockStream<fake::Syscalls> for CellStream<'_> {
+    type Item = u32;
+
+    fn poll_next(&mut self) -> Poll<u32> {
+        match self.0.take() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[test]
+fn stream_next() {
+    let _kernel = fake::Kernel::new();
+    let cell = Cell::new(None);
+    let mut stream = CellStream(&cell);
+    assert_eq!(TockFuture::poll(&mut next(&mut stream)), Poll::Pending);
+    for value in [1, 2] {
+        cell.set(Some(value));
+        assert_eq!(block_on::<fake::Syscalls, _>(next(&mut stream)), value);
+    }
+}
+
+#[test]
+fn select_first_ready() {
+    let kernel = fake::Kernel::new();
+    let driver = Rc::new(MockDriver::default());
+    kernel.add_driver(&driver);
+
+    let values = Cell::new(None);
+    let mut stream = CellStream(&values);
+    let called: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &called)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, 0, 42, 0).is_success());
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(select(next(&mut stream), wait_for_upcall(&called))),
+            Either::Right((42,))
+        );
+
+        // When both are ready, the first future wins, and the second's event
+        // is left for the next select.
+        values.set(Some(1));
+        called.set(Some((2,)));
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(select(next(&mut stream), wait_for_upcall(&called))),
+            Either::Left(1)
+        );
+        assert_eq!(
+            block_on::<fake::Syscalls, _>(select(next(&mut stream), wait_for_upcall(&called))),
+            Either::Right((2,))
+        );
+    });
+}
+
+#[test]
+fn join_both() {
+    let kernel = fake::Kernel::new();
+    let driver = Rc::new(MockDriver::default());
+    kernel.add_driver(&driver);
+
+    let values = Cell::new(None);
+    let mut stream = CellStream(&values);
+    let called: Cell<Option<(u32,)>> = Cell::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &called)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, 0, 42, 0).is_success());
+        let mut joined = join(next(&mut stream), wait_for_upcall(&called));
+
+        // The upcall completes the second future, which keeps its output until
+        // the first completes too.
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(
+            TockFuture::<fake::Syscalls>::poll(&mut joined),
+            Poll::Pending
+        );
+        assert_eq!(called.get(), None);
+        values.set(Some(1));
+        assert_eq!(block_on::<fake::Syscalls, _>(joined), (1, (42,)));
+    });
+}
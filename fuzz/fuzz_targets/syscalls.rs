@@ -0,0 +1,300 @@
+//! Drives the Console and IEEE 802.15.4 APIs against the fake kernel with
+//! fuzzer-chosen operations, injected system call failures, and skipped
+//! upcalls.
+//!
+//! The input is consumed one operation at a time: an opcode byte followed by
+//! the operation's arguments. Running out of input ends the run. Operations
+//! are only issued when the fake kernel is guaranteed to eventually deliver an
+//! upcall, because `yield-wait` with nothing queued panics in the fake kernel
+//! and would be reported as a (false) crash.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libtock_platform::{ErrorCode, RawSyscalls, Register, Syscalls, YieldNoWaitReturn};
+use libtock_unittest::fake::{self, ieee802154::Frame as FakeFrame};
+use libtock_unittest::{expect, SyscallSequence};
+use std::cell::Cell;
+
+type Console = libtock_console::Console<FuzzSyscalls>;
+type Ieee802154 = libtock_ieee802154::Ieee802154<FuzzSyscalls>;
+type RxRingBuffer = libtock_ieee802154::RxRingBuffer<RX_FRAMES>;
+type RxSingleBufferOperator<'b> =
+    libtock_ieee802154::RxSingleBufferOperator<'b, RX_FRAMES, FuzzSyscalls>;
+use libtock_ieee802154::RxOperator;
+
+fuzz_target!(|data: &[u8]| run(data));
+
+const CONSOLE: u32 = 1;
+const RADIO: u32 = 0x30001;
+const RX_FRAMES: usize = 3;
+
+// Largest frame body fake::ieee802154::Frame::with_body accepts.
+const MAX_FRAME_BODY: usize = 125;
+
+const ERRORS: [ErrorCode; 4] = [
+    ErrorCode::Fail,
+    ErrorCode::Busy,
+    ErrorCode::NoMem,
+    ErrorCode::Invalid,
+];
+
+fn run(data: &[u8]) {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    let radio = fake::Ieee802154Phy::new();
+    kernel.add_driver(&console);
+    kernel.add_driver(&radio);
+
+    let mut input = Input(data);
+    let mut rx_buf = RxRingBuffer::new();
+    // Frames given to the fake radio that have not been copied into the ring
+    // buffer yet.
+    let mut frames_in_flight = 0;
+
+    while let Some(opcode) = input.byte() {
+        match opcode % 7 {
+            // Console write.
+            0 => {
+                let bytes = input.bytes(64);
+                let fault = Fault::decode(&mut input);
+                kernel.add_expected_syscalls(fault.sequence(
+                    expect().allow_ro(CONSOLE, 1),
+                    (CONSOLE, 1),
+                    (CONSOLE, 1, bytes.len() as u32),
+                ));
+                let result = Console::write(bytes);
+                kernel.take_syscall_log();
+                check(&kernel, fault, result);
+                let written = console.take_bytes();
+                match fault {
+                    Fault::None | Fault::SkipUpcall => assert_eq!(written, bytes),
+                    Fault::Allow(_) | Fault::Subscribe(_) => assert!(written.is_empty()),
+                    // Command overrides replace the driver's return value
+                    // after the driver has run.
+                    Fault::Command(_) => {}
+                }
+            }
+
+            // Console read. Input is queued first so the read completes.
+            1 => {
+                let queued = input.bytes(32);
+                if queued.is_empty() {
+                    continue;
+                }
+                console.queue_input(queued);
+                let chunk = input.byte().unwrap_or(0) as usize % 8;
+                console.set_read_chunk_size((chunk != 0).then_some(chunk));
+                let mut buf = vec![0; 1 + input.byte().unwrap_or(0) as usize % 32];
+                let fault = Fault::decode(&mut input);
+                kernel.add_expected_syscalls(fault.sequence(
+                    expect().allow_rw(CONSOLE, 1),
+                    (CONSOLE, 2),
+                    (CONSOLE, 2, buf.len() as u32),
+                ));
+                let (count, result) = Console::read(&mut buf);
+                kernel.take_syscall_log();
+                check(&kernel, fault, result);
+                assert!(count <= buf.len());
+                if result.is_err() {
+                    assert_eq!(count, 0);
+                }
+            }
+
+            // Radio transmit.
+            2 => {
+                let frame = input.bytes(MAX_FRAME_BODY);
+                let fault = Fault::decode(&mut input);
+                kernel.add_expected_syscalls(fault.sequence(
+                    expect().allow_ro(RADIO, 0),
+                    (RADIO, 1),
+                    (RADIO, 27, 0),
+                ));
+                let result = Ieee802154::transmit_frame(frame);
+                kernel.take_syscall_log();
+                check(&kernel, fault, result);
+                let transmitted = radio.take_transmitted_frames();
+                match fault {
+                    Fault::None | Fault::SkipUpcall => assert_eq!(transmitted, [frame]),
+                    Fault::Allow(_) | Fault::Subscribe(_) => assert!(transmitted.is_empty()),
+                    Fault::Command(_) => {}
+                }
+            }
+
+            // A frame arrives at the radio.
+            3 => {
+                radio.radio_receive_frame(FakeFrame::with_body(input.bytes(MAX_FRAME_BODY)));
+                frames_in_flight += 1;
+            }
+
+            // Receive a frame.
+            4 => {
+                if frames_in_flight == 0 {
+                    continue;
+                }
+                FRAMES_DELIVERED.with(|delivered| delivered.set(false));
+                let mut operator = RxSingleBufferOperator::new(&mut rx_buf);
+                let frame = operator.receive_frame().expect("receive_frame failed");
+                assert!(frame.payload_len as usize <= MAX_FRAME_BODY);
+                if FRAMES_DELIVERED.with(Cell::get) {
+                    frames_in_flight = 0;
+                }
+                kernel.take_syscall_log();
+            }
+
+            // Radio configuration round trip.
+            5 => {
+                let [a, b, c, d] = input.array();
+                Ieee802154::set_pan(u16::from_le_bytes([a, b]));
+                Ieee802154::set_address_short(u16::from_le_bytes([c, d]));
+                Ieee802154::set_channel(a).unwrap();
+                Ieee802154::set_tx_power(b as i8).unwrap();
+                Ieee802154::commit_config();
+                assert_eq!(Ieee802154::get_pan(), Ok(u16::from_le_bytes([a, b])));
+                assert_eq!(
+                    Ieee802154::get_address_short(),
+                    Ok(u16::from_le_bytes([c, d]))
+                );
+                assert_eq!(Ieee802154::get_channel(), Ok(a));
+                assert_eq!(Ieee802154::get_tx_power(), Ok(b as i8));
+                kernel.take_syscall_log();
+            }
+
+            // Run any stray upcalls. Every operation above waits for its own
+            // upcall and unsubscribes afterwards, so there should be none.
+            _ => {
+                assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+                kernel.take_syscall_log();
+            }
+        }
+    }
+}
+
+// A failure injected into the system calls made by one operation.
+#[derive(Clone, Copy, Debug)]
+enum Fault {
+    None,
+    Allow(ErrorCode),
+    Subscribe(ErrorCode),
+    Command(ErrorCode),
+    // The first yield-wait returns without running the operation's upcall.
+    SkipUpcall,
+}
+
+impl Fault {
+    fn decode(input: &mut Input) -> Fault {
+        let byte = input.byte().unwrap_or(0);
+        let error = ERRORS[(byte >> 4) as usize % ERRORS.len()];
+        match byte % 5 {
+            0 => Fault::None,
+            1 => Fault::Allow(error),
+            2 => Fault::Subscribe(error),
+            3 => Fault::Command(error),
+            _ => Fault::SkipUpcall,
+        }
+    }
+
+    // Builds the expected system calls for an operation that allows a buffer
+    // (`allow`, already in the sequence), subscribes, issues a command and
+    // then waits for the upcall. The sequence stops at the injected fault, so
+    // the calls made after it (including cleanup) are not checked.
+    fn sequence(
+        self,
+        allow: SyscallSequence,
+        (subscribe_driver, subscribe_num): (u32, u32),
+        (command_driver, command_id, argument0): (u32, u32, u32),
+    ) -> SyscallSequence {
+        let subscribe = |seq: SyscallSequence| seq.subscribe(subscribe_driver, subscribe_num);
+        let command = |seq: SyscallSequence| seq.command(command_driver, command_id, argument0, 0);
+        match self {
+            Fault::None => expect(),
+            Fault::Allow(error) => allow.returning(error),
+            Fault::Subscribe(error) => subscribe(allow).returning(error),
+            Fault::Command(error) => command(subscribe(allow)).returning(error),
+            Fault::SkipUpcall => command(subscribe(allow)).yield_wait().skip_upcall(),
+        }
+    }
+}
+
+// Checks that an operation reported the injected fault and consumed all of
+// its expected system calls.
+fn check(kernel: &fake::Kernel, fault: Fault, result: Result<(), ErrorCode>) {
+    kernel.assert_expected_syscalls_done();
+    match fault {
+        Fault::Allow(error) | Fault::Subscribe(error) | Fault::Command(error) => {
+            assert_eq!(result, Err(error))
+        }
+        Fault::None | Fault::SkipUpcall => assert_eq!(result, Ok(())),
+    }
+}
+
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(first)
+    }
+
+    // Reads a length byte, then up to that many bytes (capped at `max`).
+    fn bytes(&mut self, max: usize) -> &'a [u8] {
+        let len = (self.byte().unwrap_or(0) as usize % (max + 1)).min(self.0.len());
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        bytes
+    }
+
+    // Reads N bytes, padding with zeros if the input runs out.
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut array = [0; N];
+        for byte in &mut array {
+            *byte = self.byte().unwrap_or(0);
+        }
+        array
+    }
+}
+
+thread_local!(static FRAMES_DELIVERED: Cell<bool> = const { Cell::new(false) });
+
+/// The fake 15.4 driver only copies received frames into the ring buffer when
+/// told to. Like the wrapper in the ieee802154 crate's unit tests, this hooks
+/// Subscribe to FRAME_RECEIVED to deliver the frames the fake radio has
+/// received, so the operator's `yield-wait` has an upcall to run.
+struct FuzzSyscalls;
+
+unsafe impl RawSyscalls for FuzzSyscalls {
+    unsafe fn yield1([r0]: [Register; 1]) {
+        unsafe { fake::Syscalls::yield1([r0]) }
+    }
+
+    unsafe fn yield2([r0, r1]: [Register; 2]) {
+        unsafe { fake::Syscalls::yield2([r0, r1]) }
+    }
+
+    unsafe fn syscall1<const CLASS: usize>([r0]: [Register; 1]) -> [Register; 2] {
+        unsafe { fake::Syscalls::syscall1::<CLASS>([r0]) }
+    }
+
+    unsafe fn syscall2<const CLASS: usize>([r0, r1]: [Register; 2]) -> [Register; 2] {
+        unsafe { fake::Syscalls::syscall2::<CLASS>([r0, r1]) }
+    }
+
+    unsafe fn syscall4<const CLASS: usize>([r0, r1, r2, r3]: [Register; 4]) -> [Register; 4] {
+        let frame_subscribe = CLASS == libtock_platform::syscall_class::SUBSCRIBE
+            && u32::try_from(r0).ok() == Some(RADIO)
+            && u32::try_from(r1).ok() == Some(0)
+            && usize::from(r3) != 0;
+        let ret = unsafe { fake::Syscalls::syscall4::<CLASS>([r0, r1, r2, r3]) };
+        if frame_subscribe {
+            if let Some(radio) = fake::Ieee802154Phy::instance() {
+                radio.driver_receive_pending_frames();
+                if radio.has_pending_rx_frames() {
+                    radio.trigger_rx_upcall();
+                }
+                FRAMES_DELIVERED.with(|delivered| delivered.set(true));
+            }
+        }
+        ret
+    }
+}
@@ -0,0 +1,259 @@
+//! `libtock_host_runtime` runs Tock process binaries on the development
+//! machine, using `libtock_unittest`'s fake kernel in place of a board. It is
+//! a drop-in replacement for `libtock_runtime`: building `libtock` with the
+//! `host` feature makes `libtock::runtime` refer to this crate, so a process
+//! binary written with `set_main!` and `stack_size!` compiles for the host
+//! unchanged:
+//!
+//! ```shell
+//! cargo run --features host --example console
+//! ```
+//!
+//! The process runs against a simulated board with the following drivers:
+//!
+//! * Console, connected to the host's stdin and stdout. Output is written to
+//!   stdout whenever the process yields. Reads are satisfied from stdin when
+//!   the process has nothing else to wait for.
+//! * Alarm, running on a virtual clock. Time only passes while the process is
+//!   blocked in `yield-wait`, at which point the clock jumps straight to the
+//!   armed alarm's expiration, so `sleep` returns immediately in real time.
+//! * LowLevelDebug, which prints its messages to stdout.
+//!
+//! When the process exits, the host program exits with the process' completion
+//! code. If the process blocks in `yield-wait` while no simulated event can
+//! wake it (e.g. it reads from the console after stdin has ended, without an
+//! alarm armed), the fake kernel panics.
+//!
+//! Tests can build the same simulation with `Host::with_io`, supplying the
+//! console's input and capturing its output, and add further fake drivers to
+//! `Host::kernel`.
+
+use libtock_low_level_debug::LowLevelDebug;
+use libtock_platform::{RawSyscalls, Register, Termination};
+use libtock_unittest::{fake, ExitCall};
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+/// The frequency of the simulated alarm's virtual clock.
+pub const ALARM_FREQUENCY_HZ: u32 = 32768;
+
+/// The system call implementation used by process binaries running on the
+/// host. Forwards all system calls to `fake::Syscalls`, and writes console
+/// output to the host after every Yield.
+pub struct TockSyscalls;
+
+unsafe impl RawSyscalls for TockSyscalls {
+    unsafe fn yield1([r0]: [Register; 1]) {
+        unsafe { fake::Syscalls::yield1([r0]) };
+        flush_output();
+    }
+
+    unsafe fn yield2([r0, r1]: [Register; 2]) {
+        unsafe { fake::Syscalls::yield2([r0, r1]) };
+        flush_output();
+    }
+
+    unsafe fn syscall1<const CLASS: usize>([r0]: [Register; 1]) -> [Register; 2] {
+        unsafe { fake::Syscalls::syscall1::<CLASS>([r0]) }
+    }
+
+    unsafe fn syscall2<const CLASS: usize>([r0, r1]: [Register; 2]) -> [Register; 2] {
+        unsafe { fake::Syscalls::syscall2::<CLASS>([r0, r1]) }
+    }
+
+    unsafe fn syscall4<const CLASS: usize>([r0, r1, r2, r3]: [Register; 4]) -> [Register; 4] {
+        unsafe { fake::Syscalls::syscall4::<CLASS>([r0, r1, r2, r3]) }
+    }
+}
+
+/// `set_main!` is used to tell the host runtime which function is the
+/// process' main function. It has the same syntax as
+/// `libtock_runtime::set_main!`:
+/// ```ignore
+/// set_main! {main}
+/// ```
+/// It defines the host program's C `main`, which runs the named function in a
+/// new `Host`.
+#[macro_export]
+macro_rules! set_main {
+    {$name:ident} => {
+        #[export_name = "main"]
+        extern "C" fn libtock_host_main(_argc: i32, _argv: *const *const u8) -> i32 {
+            #[allow(unreachable_code)] // so that fn main() -> ! does not produce a warning.
+            $crate::run($name)
+        }
+    }
+}
+
+/// Accepts the same syntax as `libtock_runtime::stack_size!`. Host programs
+/// run on the host's stack, so the size is only type-checked.
+#[macro_export]
+macro_rules! stack_size {
+    {hints: [$($hint:expr),* $(,)?], margin: $margin:expr $(,)?} => {
+        $crate::stack_size! {$crate::hinted_stack_size(&[$($hint),*], $margin)}
+    };
+    {$size:expr, covers: [$($hint:expr),* $(,)?], margin: $margin:expr $(,)?} => {
+        const _: () = assert!(
+            $size >= $crate::hinted_stack_size(&[$($hint),*], $margin),
+            "the stack size does not cover the stack usage hints and margin",
+        );
+    };
+    {$size:expr} => {
+        const _: usize = $size;
+    };
+}
+
+/// This is public for the sake of making `stack_size!` usable in other crates.
+/// It doesn't have another function.
+#[doc(hidden)]
+pub use libtock_platform::hinted_stack_size;
+
+/// Mirrors `libtock_runtime::STACK_USAGE`, for `stack_size!`.
+pub const STACK_USAGE: usize = 0x40;
+
+/// Runs `main` in a new `Host` connected to stdin and stdout. Used by
+/// `set_main!`.
+pub fn run<T: Termination>(main: fn() -> T) -> ! {
+    Host::new().run(main)
+}
+
+/// A simulated board: a `fake::Kernel` with the host runtime's drivers
+/// attached. Only one `Host` can exist per thread, as it owns the thread's
+/// `fake::Kernel`.
+pub struct Host {
+    kernel: fake::Kernel,
+    board: Rc<Board>,
+}
+
+impl Host {
+    /// Creates a `Host` whose console is connected to stdin and stdout.
+    // Host is not a plain value type (only one can exist per thread), so it
+    // does not implement Default. See fake::Kernel::new.
+    #[allow(clippy::new_without_default)]
+    #[track_caller]
+    pub fn new() -> Host {
+        Self::with_io(std::io::stdin(), std::io::stdout())
+    }
+
+    /// Creates a `Host` whose console reads from `input` and writes to
+    /// `output`.
+    #[track_caller]
+    pub fn with_io<R: Read + 'static, W: Write + 'static>(input: R, output: W) -> Host {
+        let kernel = fake::Kernel::new();
+        let board = Rc::new(Board {
+            console: fake::Console::new(),
+            alarm: fake::Alarm::with_manual_time(ALARM_FREQUENCY_HZ),
+            input: RefCell::new(Box::new(input)),
+            output: RefCell::new(Box::new(output)),
+        });
+        kernel.add_driver(&board.console);
+        kernel.add_driver(&board.alarm);
+        kernel.add_driver(&fake::LowLevelDebug::new());
+        let idle_board = board.clone();
+        kernel.set_idle_handler(move || idle_board.idle());
+        BOARD.with(|current| current.replace(Some(board.clone())));
+        Host { kernel, board }
+    }
+
+    /// Returns the simulation's kernel, to which further fake drivers may be
+    /// added.
+    pub fn kernel(&self) -> &fake::Kernel {
+        &self.kernel
+    }
+
+    /// Returns the simulated alarm, whose tick counter is the virtual clock.
+    pub fn alarm(&self) -> &Rc<fake::Alarm> {
+        &self.board.alarm
+    }
+
+    /// Runs `main` as the process' main function. When the process exits, the
+    /// host program exits with the process' completion code.
+    pub fn run<T: Termination>(self, main: fn() -> T) -> ! {
+        let board = self.board.clone();
+        self.kernel.set_exit_handler(move |exit_call| {
+            board.flush_output();
+            let code = match exit_call {
+                ExitCall::Terminate(code) => code,
+                ExitCall::Restart(code) => {
+                    eprintln!(
+                        "Process requested a restart, which the host runtime does not support"
+                    );
+                    code
+                }
+            };
+            std::process::exit(code as i32)
+        });
+        let result = main();
+        if let Some(exit_code) = result.error_code() {
+            LowLevelDebug::<TockSyscalls>::print_1(exit_code);
+        }
+        Termination::complete::<TockSyscalls>(result)
+    }
+}
+
+impl Drop for Host {
+    fn drop(&mut self) {
+        BOARD.with(|current| current.replace(None));
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+// The drivers and host I/O of the current thread's Host. TockSyscalls uses this
+// to find the console output to flush.
+thread_local!(static BOARD: RefCell<Option<Rc<Board>>> = const { RefCell::new(None) });
+
+fn flush_output() {
+    if let Some(board) = BOARD.with(|current| current.borrow().clone()) {
+        board.flush_output();
+    }
+}
+
+struct Board {
+    console: Rc<fake::Console>,
+    alarm: Rc<fake::Alarm>,
+    input: RefCell<Box<dyn Read>>,
+    output: RefCell<Box<dyn Write>>,
+}
+
+impl Board {
+    // Writes the bytes the process has sent to the console to the host.
+    fn flush_output(&self) {
+        let bytes = self.console.take_bytes();
+        if bytes.is_empty() {
+            return;
+        }
+        let mut output = self.output.borrow_mut();
+        output
+            .write_all(&bytes)
+            .and_then(|()| output.flush())
+            .expect("Unable to write console output");
+    }
+
+    // The idle handler: called when the process blocks in yield-wait with no
+    // upcall queued. Makes the next simulated event happen, preferring console
+    // input over the passage of time.
+    fn idle(&self) {
+        self.flush_output();
+        if let Some(wanted) = self.console.pending_read() {
+            let mut buffer = vec![0; wanted];
+            // A read error is treated like the end of the input, so the
+            // process can still be woken by the alarm.
+            let count = self.input.borrow_mut().read(&mut buffer).unwrap_or(0);
+            if count > 0 {
+                self.console.queue_input(&buffer[..count]);
+                return;
+            }
+        }
+        if let Some(expiration) = self.alarm.expiration() {
+            self.alarm
+                .advance_ticks(expiration.wrapping_sub(self.alarm.ticks()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,64 @@
+use super::*;
+use libtock_alarm::Milliseconds;
+use std::io::Cursor;
+
+type Alarm = libtock_alarm::Alarm<TockSyscalls>;
+type Console = libtock_console::Console<TockSyscalls>;
+
+// A Write implementation whose contents can be inspected after it is moved into
+// a Host.
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    fn take(&self) -> Vec<u8> {
+        self.0.take()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn console_output() {
+    let output = SharedOutput::default();
+    let _host = Host::with_io(std::io::empty(), output.clone());
+    assert_eq!(Console::write(b"Hello"), Ok(()));
+    // The output is written to the host as soon as the write completes.
+    assert_eq!(output.take(), b"Hello");
+    assert_eq!(Console::write(b", world\n"), Ok(()));
+    assert_eq!(output.take(), b", world\n");
+}
+
+#[test]
+fn console_input() {
+    let _host = Host::with_io(Cursor::new(b"abcdef".to_vec()), std::io::sink());
+    let mut buffer = [0; 4];
+    assert_eq!(Console::read(&mut buffer), (4, Ok(())));
+    assert_eq!(&buffer, b"abcd");
+    assert_eq!(Console::read(&mut buffer), (2, Ok(())));
+    assert_eq!(&buffer[..2], b"ef");
+}
+
+#[test]
+fn virtual_clock() {
+    let host = Host::with_io(std::io::empty(), std::io::sink());
+    assert_eq!(Alarm::sleep_for(Milliseconds(1000)), Ok(()));
+    assert!(host.alarm().ticks() >= ALARM_FREQUENCY_HZ);
+    assert!(host.alarm().ticks() < 2 * ALARM_FREQUENCY_HZ);
+}
+
+#[test]
+#[should_panic = "yield-wait called with no queued upcall"]
+fn input_exhausted() {
+    let _host = Host::with_io(std::io::empty(), std::io::sink());
+    let _ = Console::read(&mut [0; 4]);
+}
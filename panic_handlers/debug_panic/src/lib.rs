@@ -9,12 +9,20 @@ use libtock_runtime::TockSyscalls;
 
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+    #[cfg(target_arch = "arm")]
+    let registers = libtock_runtime::panic_registers::PanicRegisters::capture();
+
     // Signal a panic using the LowLevelDebug capsule (if available).
     LowLevelDebug::<TockSyscalls>::print_alert_code(AlertCode::Panic);
 
     let mut writer = Console::<TockSyscalls>::writer();
     // If this printing fails, we can't panic harder, and we can't print it either.
     let _ = writeln!(writer, "{}", info);
+    #[cfg(target_arch = "arm")]
+    let _ = writeln!(writer, "{}", registers);
+
+    libtock_runtime::run_panic_hook(info);
+
     // Exit with a non-zero exit code to indicate failure.
     TockSyscalls::exit_terminate(ErrorCode::Fail as u32);
 }
@@ -5,10 +5,23 @@ use libtock_platform::{ErrorCode, Syscalls};
 use libtock_runtime::TockSyscalls;
 
 #[panic_handler]
-fn panic_handler(_info: &core::panic::PanicInfo) -> ! {
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
     // Signal a panic using the LowLevelDebug capsule (if available).
     LowLevelDebug::<TockSyscalls>::print_alert_code(AlertCode::Panic);
 
+    // Print the registers and top of the stack for post-mortem debugging, as
+    // pairs of (index, value): pc, lr, sp, then the stack words.
+    #[cfg(target_arch = "arm")]
+    {
+        let registers = libtock_runtime::panic_registers::PanicRegisters::capture();
+        let values = [registers.pc, registers.lr, registers.sp];
+        for (i, &value) in values.iter().chain(registers.stack()).enumerate() {
+            LowLevelDebug::<TockSyscalls>::print_2(i as u32, value);
+        }
+    }
+
+    libtock_runtime::run_panic_hook(info);
+
     // Exit with a non-zero exit code to indicate failure.
     // TODO(kupiakos@google.com): Make this logic consistent with tock/tock#2914
     // when it is merged.
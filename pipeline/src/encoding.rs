@@ -0,0 +1,183 @@
+//! The frames of a `SamplePipeline`, and how samples are encoded in them.
+
+use libtock_ieee802154::MAX_PAYLOAD_LEN;
+use libtock_platform::ErrorCode;
+
+/// The first byte of pipeline frames, which tells them apart from other
+/// payloads.
+pub const DISPATCH: u8 = 0xF9;
+
+/// The length of the header of pipeline frames.
+pub const HEADER_LEN: usize = 7;
+
+/// How the samples of a frame are encoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    /// 2 bytes per sample, little endian.
+    Raw = 0,
+    /// The first sample in 2 bytes, then each sample as its difference from
+    /// the previous one, in 1 byte if it lies within ±127, and otherwise as
+    /// 0x80 followed by the sample in 2 bytes. Suits slowly changing signals.
+    Delta = 1,
+    /// 12-bit samples, 2 in 3 bytes: the first sample's low byte, its high
+    /// nibble with the second sample's low nibble above it, and the second
+    /// sample's high byte. A trailing odd sample takes 2 bytes. Higher bits
+    /// are dropped.
+    Packed12 = 2,
+}
+
+impl TryFrom<u8> for Encoding {
+    type Error = ErrorCode;
+
+    fn try_from(value: u8) -> Result<Encoding, ErrorCode> {
+        match value {
+            0 => Ok(Encoding::Raw),
+            1 => Ok(Encoding::Delta),
+            2 => Ok(Encoding::Packed12),
+            _ => Err(ErrorCode::Invalid),
+        }
+    }
+}
+
+/// The header of a pipeline frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameHeader {
+    pub encoding: Encoding,
+    /// The index of the frame's first sample in the stream, which counts
+    /// decimated samples, including the dropped ones.
+    pub first_index: u32,
+    /// The number of samples in the frame.
+    pub count: u8,
+}
+
+/// Decodes the samples of a pipeline frame into `samples`, and returns the
+/// frame's header. Fails with `ErrorCode::Invalid` if `frame` is not a valid
+/// pipeline frame, and with `ErrorCode::Size` if `samples` is too short.
+pub fn decode(frame: &[u8], samples: &mut [u16]) -> Result<FrameHeader, ErrorCode> {
+    if frame.len() < HEADER_LEN || frame[0] != DISPATCH {
+        return Err(ErrorCode::Invalid);
+    }
+    let header = FrameHeader {
+        encoding: frame[1].try_into()?,
+        first_index: u32::from_le_bytes([frame[2], frame[3], frame[4], frame[5]]),
+        count: frame[6],
+    };
+    let samples = samples
+        .get_mut(..header.count as usize)
+        .ok_or(ErrorCode::Size)?;
+    let data = &frame[HEADER_LEN..];
+    let byte = |offset: usize| data.get(offset).copied().ok_or(ErrorCode::Invalid);
+    let word = |offset: usize| Ok(u16::from_le_bytes([byte(offset)?, byte(offset + 1)?]));
+    let mut offset = 0;
+    let mut last: u16 = 0;
+    for (index, sample) in samples.iter_mut().enumerate() {
+        *sample = match header.encoding {
+            Encoding::Raw => word(2 * index)?,
+            Encoding::Packed12 => {
+                let pair = index / 2 * 3;
+                match index % 2 {
+                    0 => byte(pair)? as u16 | (byte(pair + 1)? as u16 & 0x0F) << 8,
+                    _ => (byte(pair + 1)? >> 4) as u16 | (byte(pair + 2)? as u16) << 4,
+                }
+            }
+            Encoding::Delta if index == 0 => {
+                offset = 2;
+                word(0)?
+            }
+            Encoding::Delta => match byte(offset)? {
+                ESCAPE => {
+                    offset += 3;
+                    word(offset - 2)?
+                }
+                delta => {
+                    offset += 1;
+                    last.wrapping_add_signed(delta as i8 as i16)
+                }
+            },
+        };
+        last = *sample;
+    }
+    Ok(header)
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+// Marks a sample the Delta encoding stores in full.
+const ESCAPE: u8 = 0x80;
+
+// Builds a frame, a sample at a time.
+pub(crate) struct FrameBuilder {
+    encoding: Encoding,
+    bytes: [u8; MAX_PAYLOAD_LEN],
+    len: usize,
+    count: u8,
+    last: u16,
+}
+
+impl FrameBuilder {
+    pub(crate) fn new(encoding: Encoding) -> Self {
+        let mut bytes = [0; MAX_PAYLOAD_LEN];
+        bytes[0] = DISPATCH;
+        bytes[1] = encoding as u8;
+        FrameBuilder {
+            encoding,
+            bytes,
+            len: HEADER_LEN,
+            count: 0,
+            last: 0,
+        }
+    }
+
+    pub(crate) fn count(&self) -> u8 {
+        self.count
+    }
+
+    // Appends `sample`, whose index in the stream is `index`, and returns
+    // whether it fits.
+    pub(crate) fn push(&mut self, index: u32, sample: u16) -> bool {
+        let delta = sample as i32 - self.last as i32;
+        let needed = match (self.encoding, self.count) {
+            (Encoding::Raw, _) | (Encoding::Delta, 0) => 2,
+            (Encoding::Packed12, count) => 2 - count as usize % 2,
+            (Encoding::Delta, _) if (-127..=127).contains(&delta) => 1,
+            (Encoding::Delta, _) => 3,
+        };
+        if self.len + needed > MAX_PAYLOAD_LEN || self.count == u8::MAX {
+            return false;
+        }
+        if self.count == 0 {
+            self.bytes[2..6].copy_from_slice(&index.to_le_bytes());
+        }
+        let bytes = &mut self.bytes[self.len..self.len + needed];
+        match (self.encoding, needed) {
+            (Encoding::Packed12, 2) => {
+                bytes[0] = sample as u8;
+                bytes[1] = (sample >> 8) as u8 & 0x0F;
+            }
+            (Encoding::Packed12, _) => {
+                self.bytes[self.len - 1] |= (sample as u8 & 0x0F) << 4;
+                self.bytes[self.len] = (sample >> 4) as u8;
+            }
+            (_, 1) => bytes[0] = delta as i8 as u8,
+            (_, 2) => bytes.copy_from_slice(&sample.to_le_bytes()),
+            (_, _) => {
+                bytes[0] = ESCAPE;
+                bytes[1..].copy_from_slice(&sample.to_le_bytes());
+            }
+        }
+        self.len += needed;
+        self.count += 1;
+        self.last = sample;
+        true
+    }
+
+    // Returns the frame, and starts a new one.
+    pub(crate) fn take(&mut self) -> ([u8; MAX_PAYLOAD_LEN], usize) {
+        self.bytes[6] = self.count;
+        let frame = (self.bytes, self.len);
+        *self = FrameBuilder::new(self.encoding);
+        frame
+    }
+}
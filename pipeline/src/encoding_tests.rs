@@ -0,0 +1,89 @@
+use super::encoding::FrameBuilder;
+use super::*;
+use libtock_ieee802154::MAX_PAYLOAD_LEN;
+use libtock_platform::ErrorCode;
+
+// Encodes `samples` into a single frame, starting at `first_index`.
+fn encode(encoding: Encoding, first_index: u32, samples: &[u16]) -> Vec<u8> {
+    let mut builder = FrameBuilder::new(encoding);
+    for (index, &sample) in (first_index..).zip(samples) {
+        assert!(builder.push(index, sample));
+    }
+    let (bytes, len) = builder.take();
+    bytes[..len].to_vec()
+}
+
+fn round_trip(encoding: Encoding, samples: &[u16]) -> Vec<u8> {
+    let frame = encode(encoding, 7, samples);
+    let mut decoded = [0; 64];
+    let header = decode(&frame, &mut decoded).unwrap();
+    assert_eq!(
+        header,
+        FrameHeader {
+            encoding,
+            first_index: 7,
+            count: samples.len() as u8,
+        }
+    );
+    assert_eq!(&decoded[..samples.len()], samples);
+    frame
+}
+
+#[test]
+fn raw() {
+    let frame = round_trip(Encoding::Raw, &[0x1234, 0xFFFF]);
+    assert_eq!(frame, [DISPATCH, 0, 7, 0, 0, 0, 2, 0x34, 0x12, 0xFF, 0xFF]);
+}
+
+#[test]
+fn delta() {
+    // Differences beyond ±127 escape to the full sample.
+    let frame = round_trip(Encoding::Delta, &[1000, 1127, 1000, 873, 872, 0, 65535]);
+    assert_eq!(
+        frame[HEADER_LEN..],
+        [0xE8, 0x03, 127, 0x81, 0x81, 0xFF, 0x80, 0, 0, 0x80, 0xFF, 0xFF]
+    );
+}
+
+#[test]
+fn packed12() {
+    let frame = round_trip(Encoding::Packed12, &[0x123, 0xABC, 0xFFF]);
+    assert_eq!(frame[HEADER_LEN..], [0x23, 0xC1, 0xAB, 0xFF, 0x0F]);
+    // Higher bits are dropped.
+    let frame = encode(Encoding::Packed12, 0, &[0xF123]);
+    let mut decoded = [0; 1];
+    decode(&frame, &mut decoded).unwrap();
+    assert_eq!(decoded, [0x123]);
+}
+
+#[test]
+fn full_frame() {
+    let mut builder = FrameBuilder::new(Encoding::Raw);
+    let capacity = (MAX_PAYLOAD_LEN - HEADER_LEN) / 2;
+    for index in 0..capacity as u32 {
+        assert!(builder.push(index, 0));
+    }
+    assert!(!builder.push(capacity as u32, 0));
+    assert_eq!(builder.count() as usize, capacity);
+    builder.take();
+    assert_eq!(builder.count(), 0);
+    assert!(builder.push(capacity as u32, 0));
+}
+
+#[test]
+fn invalid() {
+    let frame = encode(Encoding::Raw, 0, &[1, 2]);
+    let mut samples = [0; 2];
+    assert_eq!(decode(&frame[..6], &mut samples), Err(ErrorCode::Invalid));
+    assert_eq!(
+        decode(&frame[..frame.len() - 1], &mut samples),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(decode(&frame, &mut samples[..1]), Err(ErrorCode::Size));
+    let mut other = frame.clone();
+    other[0] = 0;
+    assert_eq!(decode(&other, &mut samples), Err(ErrorCode::Invalid));
+    other = frame;
+    other[1] = 3;
+    assert_eq!(decode(&other, &mut samples), Err(ErrorCode::Invalid));
+}
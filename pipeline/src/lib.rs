@@ -0,0 +1,239 @@
+//! `libtock_pipeline` streams ADC samples over 802.15.4, for workloads such as
+//! vibration monitoring, which sample faster than a design copying every
+//! sample through intermediate buffers keeps up with.
+//!
+//! A [`SamplePipeline`] samples an ADC channel continuously into two buffers
+//! in turn. While the ADC fills one, the pipeline decimates the samples of the
+//! other and encodes them straight into frames, which it pushes into a
+//! `TxQueue`, and transmits the queued frames.
+//!
+//! # Back-pressure
+//!
+//! The ADC does not wait for the radio: when the queue is full, the frames
+//! that do not fit are dropped, and their samples counted in
+//! [`SamplePipeline::dropped`]. Every frame carries the index of its first
+//! sample, so receivers see the gaps. Lower the frequency, raise the
+//! decimation or pick a denser [`Encoding`] until nothing is dropped.
+//!
+//! # Frames
+//!
+//! Every frame starts with a 7-byte header:
+//!
+//! | Offset | Size | Content                                               |
+//! |--------|------|-------------------------------------------------------|
+//! | 0      | 1    | [`DISPATCH`]                                          |
+//! | 1      | 1    | [`Encoding`]                                          |
+//! | 2      | 4    | Index of the first sample in the stream (little endian) |
+//! | 6      | 1    | Number of samples                                     |
+//!
+//! followed by the encoded samples, which [`decode`] decodes.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::ieee802154::{Ieee802154, TxQueue};
+//! use libtock::pipeline::{Encoding, SamplePipeline};
+//! use libtock::platform::AlignedBuf;
+//!
+//! fn main() {
+//!     Ieee802154::radio_on().unwrap();
+//!     let queue = TxQueue::<8>::new();
+//!     let mut buffers = [AlignedBuf::<512, 2>::new(), AlignedBuf::new()];
+//!     let mut pipeline = SamplePipeline::new(&queue, Encoding::Delta).decimation(4);
+//!     pipeline.run(0, 8000, &mut buffers, u32::MAX).unwrap();
+//! }
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+mod encoding;
+
+pub use encoding::{decode, Encoding, FrameHeader, DISPATCH, HEADER_LEN};
+
+use core::cell::Cell;
+use core::task::Poll;
+use encoding::FrameBuilder;
+use libtock_adc::{Adc, SampleBuffer, SampleSubscribe, SecondSampleBuffer};
+use libtock_future::{wait_for_upcall, TockFuture};
+use libtock_ieee802154::{Config, Priority, TxQueue};
+use libtock_platform::{share, Align, AlignedBuf, Alignment, DefaultConfig, ErrorCode, Syscalls};
+
+/// Streams the samples of an ADC channel into the frames of a `TxQueue`.
+///
+/// Frames are pushed with `Priority::Low`, so that other frames sharing the
+/// queue, e.g. ACKs, go first.
+pub struct SamplePipeline<'q, S: Syscalls, const N: usize, C: Config = DefaultConfig> {
+    queue: &'q TxQueue<S, N, C>,
+    decimation: u16,
+    // The sum and number of the samples averaged into the next decimated one.
+    sum: u32,
+    summed: u16,
+    // The index of the next decimated sample.
+    index: u32,
+    frame: FrameBuilder,
+    sent: u32,
+    dropped: u32,
+}
+
+impl<'q, S: Syscalls, const N: usize, C: Config> SamplePipeline<'q, S, N, C> {
+    /// Creates a pipeline pushing frames of samples encoded with `encoding`
+    /// into `queue`.
+    pub fn new(queue: &'q TxQueue<S, N, C>, encoding: Encoding) -> Self {
+        SamplePipeline {
+            queue,
+            decimation: 1,
+            sum: 0,
+            summed: 0,
+            index: 0,
+            frame: FrameBuilder::new(encoding),
+            sent: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Averages every `factor` samples into one, which also filters out
+    /// frequencies the decimated stream cannot represent. A factor of 0 is
+    /// taken as 1, which keeps every sample.
+    pub fn decimation(mut self, factor: u16) -> Self {
+        self.decimation = factor.max(1);
+        self
+    }
+
+    /// Samples `channel` at `frequency` Hz for `blocks` buffers' worth of
+    /// samples, and streams them. Returns once the last frame is transmitted.
+    ///
+    /// The ADC samples into each of `buffers` in turn. Each buffer is encoded
+    /// while the ADC fills the other, so a buffer must take longer to fill
+    /// than one to encode. The samples of a partly filled frame are carried
+    /// over to the next call.
+    pub fn run<const B: usize, const A: usize>(
+        &mut self,
+        channel: u32,
+        frequency: u32,
+        buffers: &mut [AlignedBuf<B, A>; 2],
+        blocks: u32,
+    ) -> Result<(), ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        let result = self.stream(channel, frequency, buffers, blocks);
+        let stopped = match blocks {
+            0 => Ok(()),
+            _ => Adc::<S>::stop_sampling(),
+        };
+        if let Some((buffer, count)) = result? {
+            self.push_samples(&buffers[buffer][..2 * count]);
+        }
+        stopped?;
+        if self.frame.count() > 0 {
+            self.push_frame();
+        }
+        self.queue.run_until(Drained(self.queue));
+        Ok(())
+    }
+
+    /// Returns the number of decimated samples queued for transmission.
+    pub fn sent(&self) -> u32 {
+        self.sent
+    }
+
+    /// Returns the number of decimated samples dropped because the queue was
+    /// full.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    // Samples `blocks` buffers, and encodes all but the last. Returns the
+    // last buffer and its number of samples, if any.
+    fn stream<const B: usize, const A: usize>(
+        &mut self,
+        channel: u32,
+        frequency: u32,
+        buffers: &mut [AlignedBuf<B, A>; 2],
+        blocks: u32,
+    ) -> Result<Option<(usize, usize)>, ErrorCode>
+    where
+        Align<A>: Alignment,
+    {
+        if blocks == 0 {
+            return Ok(None);
+        }
+        let done = Cell::new(None);
+        let [first, second] = buffers;
+        // Both buffers stay shared for the whole run, so a full one is read
+        // through these while the ADC fills the other.
+        let samples: [*const u8; 2] = [first.as_mut_ptr(), second.as_mut_ptr()];
+        share::scope::<Handles<S>, _, _>(|handle| {
+            let (allow_first, allow_second, subscribe) = handle.split();
+            Adc::<S>::share_buffers(first, second, &done, allow_first, allow_second, subscribe)?;
+            Adc::<S>::start_continuous(channel, frequency)?;
+            let mut filled: Option<(usize, usize)> = None;
+            for _ in 0..blocks {
+                if let Some((buffer, count)) = filled {
+                    // Safety: The ADC reported the buffer full, and writes the
+                    // other one until it reports that one full, which is only
+                    // awaited once these samples are encoded.
+                    let bytes = unsafe { core::slice::from_raw_parts(samples[buffer], 2 * count) };
+                    self.push_samples(bytes);
+                }
+                let (_, count, buffer) = self.queue.run_until(wait_for_upcall(&done));
+                filled = Some(((buffer as usize).min(1), (count as usize).min(B / 2)));
+            }
+            Ok(filled)
+        })
+    }
+
+    // Decimates and encodes `bytes`, native-endian samples, into frames.
+    fn push_samples(&mut self, bytes: &[u8]) {
+        for sample in bytes.chunks_exact(2) {
+            self.sum += u16::from_ne_bytes([sample[0], sample[1]]) as u32;
+            self.summed += 1;
+            if self.summed < self.decimation {
+                continue;
+            }
+            let sample = (self.sum / self.decimation as u32) as u16;
+            (self.sum, self.summed) = (0, 0);
+            if !self.frame.push(self.index, sample) {
+                self.push_frame();
+                self.frame.push(self.index, sample);
+            }
+            self.index = self.index.wrapping_add(1);
+        }
+    }
+
+    // Queues the frame being built, or drops it if the queue is full.
+    fn push_frame(&mut self) {
+        let count = self.frame.count() as u32;
+        let (bytes, len) = self.frame.take();
+        match self.queue.push(Priority::Low, &bytes[..len]) {
+            Ok(()) => self.sent += count,
+            Err(_) => self.dropped += count,
+        }
+    }
+}
+
+// The buffers and subscription of a run.
+type Handles<'share, S> = (
+    SampleBuffer<'share, S>,
+    SecondSampleBuffer<'share, S>,
+    SampleSubscribe<'share, S>,
+);
+
+// Completes once the queue is empty.
+struct Drained<'q, S: Syscalls, const N: usize, C: Config>(&'q TxQueue<S, N, C>);
+
+impl<S: Syscalls, const N: usize, C: Config> TockFuture<S> for Drained<'_, S, N, C> {
+    type Output = ();
+
+    fn poll(&mut self) -> Poll<()> {
+        match self.0.is_empty() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod encoding_tests;
+
+#[cfg(test)]
+mod tests;
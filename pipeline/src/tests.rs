@@ -0,0 +1,97 @@
+use super::*;
+use libtock_unittest::fake;
+use std::rc::Rc;
+
+type SamplePipeline<'q, const N: usize> = super::SamplePipeline<'q, fake::Syscalls, N>;
+type TxQueue<const N: usize> = libtock_ieee802154::TxQueue<fake::Syscalls, N>;
+
+// Makes the ADC fill each buffer with the next `block` samples of the ramp
+// 0, 1, 2, ...
+fn ramp_when_idle(kernel: &fake::Kernel, adc: &Rc<fake::Adc>, block: u16) {
+    kernel.set_idle_handler({
+        let adc = adc.clone();
+        let mut next = 0;
+        move || {
+            let samples: Vec<u16> = (next..next + block).collect();
+            adc.fill_buffer(&samples);
+            next += block;
+        }
+    });
+}
+
+// Decodes the transmitted frames, as (first index, samples).
+fn decode_frames(phy: &fake::Ieee802154Phy) -> Vec<(u32, Vec<u16>)> {
+    phy.take_transmitted_frames()
+        .iter()
+        .map(|frame| {
+            let mut samples = [0; 64];
+            let header = decode(frame, &mut samples).unwrap();
+            (
+                header.first_index,
+                samples[..header.count as usize].to_vec(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn stream() {
+    let kernel = fake::Kernel::new();
+    let adc = fake::Adc::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&adc);
+    kernel.add_driver(&phy);
+    ramp_when_idle(&kernel, &adc, 4);
+    let queue = TxQueue::<2>::new();
+    let mut pipeline = SamplePipeline::new(&queue, Encoding::Delta).decimation(2);
+    let mut buffers = [AlignedBuf::<8, 2>::new(), AlignedBuf::new()];
+
+    assert_eq!(pipeline.run(0, 1000, &mut buffers, 3), Ok(()));
+    assert_eq!(adc.active_buffer(), None);
+    // Pairs of samples are averaged.
+    assert_eq!(decode_frames(&phy), [(0, vec![0, 2, 4, 6, 8, 10])]);
+    assert_eq!((pipeline.sent(), pipeline.dropped()), (6, 0));
+
+    // Indices carry on across runs.
+    assert_eq!(pipeline.run(0, 1000, &mut buffers, 1), Ok(()));
+    assert_eq!(decode_frames(&phy), [(6, vec![12, 14])]);
+}
+
+#[test]
+fn back_pressure() {
+    let kernel = fake::Kernel::new();
+    let adc = fake::Adc::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&adc);
+    kernel.add_driver(&phy);
+    ramp_when_idle(&kernel, &adc, 100);
+    let queue = TxQueue::<2>::new();
+    let mut pipeline = SamplePipeline::new(&queue, Encoding::Raw);
+    let mut buffers = [AlignedBuf::<200, 2>::new(), AlignedBuf::new()];
+
+    assert_eq!(pipeline.run(0, 1000, &mut buffers, 2), Ok(()));
+    // Each block makes two full frames, which fill the queue, so the samples
+    // left over after the second block are dropped.
+    let frames = decode_frames(&phy);
+    let indices: Vec<u32> = frames.iter().map(|&(index, _)| index).collect();
+    assert_eq!(indices, [0, 48, 96, 144]);
+    assert_eq!(frames[3].1, (144..192).collect::<Vec<u16>>());
+    assert_eq!((pipeline.sent(), pipeline.dropped()), (192, 8));
+}
+
+#[test]
+fn no_blocks() {
+    let kernel = fake::Kernel::new();
+    let adc = fake::Adc::new();
+    let phy = fake::Ieee802154Phy::new();
+    kernel.add_driver(&adc);
+    kernel.add_driver(&phy);
+    ramp_when_idle(&kernel, &adc, 4);
+    let queue = TxQueue::<2>::new();
+    let mut pipeline = SamplePipeline::new(&queue, Encoding::Raw);
+    let mut buffers = [AlignedBuf::<8, 2>::new(), AlignedBuf::new()];
+
+    assert_eq!(pipeline.run(0, 1000, &mut buffers, 0), Ok(()));
+    assert_eq!(adc.active_buffer(), None);
+    assert!(phy.take_transmitted_frames().is_empty());
+}
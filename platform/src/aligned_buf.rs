@@ -0,0 +1,152 @@
+//! Buffers with the alignment and placement that DMA-capable capsules require.
+//!
+//! Some capsules hand allowed buffers directly to a peripheral's DMA engine,
+//! which may require them to be word-aligned, or to be in a RAM bank the DMA
+//! engine can reach. A `[u8; N]` is only byte-aligned, so such buffers are
+//! declared as an [`AlignedBuf`] instead, which is aligned to `A` bytes:
+//!
+//! ```
+//! use libtock_platform::AlignedBuf;
+//!
+//! let mut buf = AlignedBuf::<64, 4>::new();
+//! buf[0] = 0x2A;
+//! assert_eq!(buf.as_ptr() as usize % 4, 0);
+//! ```
+//!
+//! `A` must be a power of two from 1 to 4096; other alignments fail to compile.
+//! APIs that need a minimum alignment take an `AlignedBuf` and call
+//! [`AlignedBuf::aligned_to`] or [`AlignedBuf::aligned_to_mut`], which fail to build if `A` is too small.
+//!
+//! # Placement
+//!
+//! Static buffers are placed in the `.dma` linker section with
+//! `#[link_section]`. `libtock_layout.ld` zeroes `.dma` at startup along with
+//! `.bss`, and places it at the start of `.bss`, word-aligned:
+//!
+//! ```ignore
+//! #[link_section = ".dma"]
+//! static mut SPI_BUF: AlignedBuf<256, 4> = AlignedBuf::new();
+//! ```
+//!
+//! Boards whose DMA engines only reach some RAM banks place `.dma` in one of
+//! them by defining a `.dma` output section in their linker script before
+//! including `libtock_layout.ld`.
+
+use core::ops::{Deref, DerefMut};
+
+/// `N` bytes, aligned to `A` bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct AlignedBuf<const N: usize, const A: usize>
+where
+    Align<A>: Alignment,
+{
+    _align: [<Align<A> as Alignment>::Marker; 0],
+    bytes: [u8; N],
+}
+
+impl<const N: usize, const A: usize> AlignedBuf<N, A>
+where
+    Align<A>: Alignment,
+{
+    /// Returns a zeroed buffer.
+    pub const fn new() -> Self {
+        Self {
+            _align: [],
+            bytes: [0; N],
+        }
+    }
+
+    /// Returns the bytes, and fails to build unless the buffer is aligned to at
+    /// least `MIN` bytes.
+    pub fn aligned_to<const MIN: usize>(&self) -> &[u8; N] {
+        #[allow(clippy::let_unit_value)]
+        let () = MinAlign::<A, MIN>::CHECK;
+        &self.bytes
+    }
+
+    /// Like [`aligned_to`](Self::aligned_to), but returns the bytes mutably.
+    pub fn aligned_to_mut<const MIN: usize>(&mut self) -> &mut [u8; N] {
+        #[allow(clippy::let_unit_value)]
+        let () = MinAlign::<A, MIN>::CHECK;
+        &mut self.bytes
+    }
+}
+
+impl<const N: usize, const A: usize> Default for AlignedBuf<N, A>
+where
+    Align<A>: Alignment,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const A: usize> Deref for AlignedBuf<N, A>
+where
+    Align<A>: Alignment,
+{
+    type Target = [u8; N];
+
+    fn deref(&self) -> &[u8; N] {
+        &self.bytes
+    }
+}
+
+impl<const N: usize, const A: usize> DerefMut for AlignedBuf<N, A>
+where
+    Align<A>: Alignment,
+{
+    fn deref_mut(&mut self) -> &mut [u8; N] {
+        &mut self.bytes
+    }
+}
+
+/// An alignment of `A` bytes, which implements [`Alignment`] if `A` is
+/// supported.
+pub struct Align<const A: usize>;
+
+/// Implemented by the supported [`Align`]s.
+pub trait Alignment {
+    /// A zero-sized type aligned to the alignment.
+    type Marker: Copy + core::fmt::Debug + Eq;
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+macro_rules! alignments {
+    ($($marker:ident = $align:literal),*) => {$(
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(align($align))]
+        pub struct $marker;
+
+        impl Alignment for Align<$align> {
+            type Marker = $marker;
+        }
+    )*};
+}
+
+alignments!(
+    Align1 = 1,
+    Align2 = 2,
+    Align4 = 4,
+    Align8 = 8,
+    Align16 = 16,
+    Align32 = 32,
+    Align64 = 64,
+    Align128 = 128,
+    Align256 = 256,
+    Align512 = 512,
+    Align1024 = 1024,
+    Align2048 = 2048,
+    Align4096 = 4096
+);
+
+// Fails to evaluate, and therefore to build, if `A` is less than `MIN`.
+struct MinAlign<const A: usize, const MIN: usize>;
+
+impl<const A: usize, const MIN: usize> MinAlign<A, MIN> {
+    const CHECK: () = assert!(A >= MIN, "the buffer is not aligned enough");
+}
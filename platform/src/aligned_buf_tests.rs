@@ -0,0 +1,18 @@
+use crate::AlignedBuf;
+use core::mem::{align_of, size_of};
+
+#[test]
+fn layout() {
+    assert_eq!(align_of::<AlignedBuf<3, 1>>(), 1);
+    assert_eq!(align_of::<AlignedBuf<3, 4>>(), 4);
+    assert_eq!(size_of::<AlignedBuf<3, 4>>(), 4);
+    assert_eq!(align_of::<AlignedBuf<64, 4096>>(), 4096);
+}
+
+#[test]
+fn aligned_to() {
+    let mut buf = AlignedBuf::<4, 8>::new();
+    buf.aligned_to_mut::<4>()[1] = 7;
+    assert_eq!(buf.aligned_to::<8>(), &[0, 7, 0, 0]);
+    assert_eq!(buf.as_ptr() as usize % 8, 0);
+}
@@ -1,4 +1,5 @@
 use crate::share::List;
+use crate::ErrorCode;
 use crate::Syscalls;
 use core::marker::PhantomData;
 
@@ -70,4 +71,11 @@ pub trait Config {
     /// buffer. In some applications, this may indicate unexpected reentrance.
     /// By default, the non-zero buffer is ignored.
     fn returned_nonzero_buffer(_driver_num: u32, _buffer_num: u32) {}
+
+    /// Called if a Read-Write Allow call fails, with the number of times it
+    /// has failed in a row. Returns whether to make the call again, e.g. after
+    /// a delay if the error is transient. By default, the call is not retried.
+    fn should_retry(_driver_num: u32, _buffer_num: u32, _error: ErrorCode, _failures: u32) -> bool {
+        false
+    }
 }
@@ -0,0 +1,10 @@
+use crate::ErrorCode;
+
+/// `Config` configures the behavior of the Command system call, when it is
+/// made with `Syscalls::checked_command`. It should generally be passed
+/// through by drivers, to allow application code to configure error handling.
+pub trait Config {
+    /// Called if a Command call fails, before its error is returned. By
+    /// default, nothing is done.
+    fn command_failed(_driver_num: u32, _command_num: u32, _error: ErrorCode) {}
+}
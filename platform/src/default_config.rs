@@ -4,4 +4,5 @@ pub struct DefaultConfig;
 
 impl crate::allow_ro::Config for DefaultConfig {}
 impl crate::allow_rw::Config for DefaultConfig {}
+impl crate::command::Config for DefaultConfig {}
 impl crate::subscribe::Config for DefaultConfig {}
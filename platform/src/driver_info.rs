@@ -0,0 +1,70 @@
+use crate::{return_variant, ErrorCode, Syscalls};
+
+/// What a driver reports about itself in response to command 0.
+///
+/// Every driver answers command 0 with a success variant if it exists, and the
+/// kernel answers it with `ErrorCode::NoDevice` if it does not. Most drivers
+/// answer with plain success, and some with a value, such as the number of
+/// LEDs. By convention, drivers whose API grows over time answer with 2 values:
+/// their version, and a bit per optional feature they support, such as a
+/// command added in a later version. API crates can then gate those features on
+/// the driver's capability instead of probing them.
+///
+/// # Example
+/// ```ignore
+/// use libtock_platform::DriverInfo;
+///
+/// let info = DriverInfo::query::<S>(DRIVER_NUM)?;
+/// if info.supports(feature::ABORT) {
+///     S::command(DRIVER_NUM, command::ABORT, 0, 0).to_result::<(), _>()?;
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DriverInfo {
+    value: Option<u32>,
+    features: Option<u32>,
+}
+
+impl DriverInfo {
+    /// Queries the driver numbered `driver_num` with command 0.
+    pub fn query<S: Syscalls>(driver_num: u32) -> Result<Self, ErrorCode> {
+        let command_return = S::command(driver_num, 0, 0, 0);
+        let (variant, r1, r2, _) = command_return.raw_values();
+        match variant {
+            return_variant::SUCCESS | return_variant::SUCCESS_U64 => Ok(DriverInfo::default()),
+            return_variant::SUCCESS_U32 | return_variant::SUCCESS_U32_U64 => Ok(DriverInfo {
+                value: Some(r1),
+                features: None,
+            }),
+            return_variant::SUCCESS_2_U32 | return_variant::SUCCESS_3_U32 => Ok(DriverInfo {
+                value: Some(r1),
+                features: Some(r2),
+            }),
+            // Every failure variant carries its error code in r1.
+            _ => Err(r1.try_into().unwrap_or(ErrorCode::BadRVal)),
+        }
+    }
+
+    /// Returns the first value the driver answered with, if any: its version
+    /// if it reports features, and a driver-specific value otherwise.
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+
+    /// Returns the driver's version, if it reports one.
+    pub fn version(&self) -> Option<u32> {
+        self.features.and(self.value)
+    }
+
+    /// Returns the driver's feature bits, if it reports them.
+    pub fn features(&self) -> Option<u32> {
+        self.features
+    }
+
+    /// Returns `true` if the driver reports all the feature bits of
+    /// `features`. Drivers that do not report features support none.
+    pub fn supports(&self, features: u32) -> bool {
+        self.features
+            .is_some_and(|supported| supported & features == features)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{ErrorCode, Syscalls};
+use core::fmt;
+
+/// A version of the Tock kernel's system call ABI.
+///
+/// Tock has no system call that reports the kernel's version. Instead, the
+/// process's TBF header states the version it was built for (`elf2tab
+/// --kernel-major --kernel-minor`), and the kernel refuses to load a process
+/// unless it has the same major version and at least the same minor version.
+/// [`KernelVersion::query`] reads that version back from the header, so a
+/// process that runs knows the kernel is compatible with it, and at least that
+/// recent.
+///
+/// # Example
+/// ```ignore
+/// use libtock_platform::KernelVersion;
+///
+/// // Safety: This process was loaded by a Tock kernel from a TBF image.
+/// match unsafe { KernelVersion::query::<S>() }? {
+///     Some(version) if version >= KernelVersion::new(2, 1) => {}
+///     // Built without a kernel version, so the kernel may be older.
+///     _ => S::exit_terminate(1),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct KernelVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl KernelVersion {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        KernelVersion { major, minor }
+    }
+
+    /// Returns the kernel version this process's TBF header requires, which
+    /// the running kernel is compatible with, or `None` if the header does not
+    /// state one.
+    ///
+    /// # Safety
+    /// The flash region reported by `S::memop_flash_start` must start with a
+    /// readable TBF header, as it does in a process loaded by the Tock kernel.
+    /// Test doubles, such as `libtock_unittest::fake::Syscalls`, report a flash
+    /// address with nothing mapped at it.
+    pub unsafe fn query<S: Syscalls>() -> Result<Option<Self>, ErrorCode> {
+        let flash_start = S::memop_flash_start()?;
+        // Safety: By the caller's guarantee, the process's flash region starts
+        // with its TBF header, which starts with the base header.
+        let base = unsafe { core::slice::from_raw_parts(flash_start, BASE_HEADER_LEN) };
+        let header_size = u16::from_le_bytes([base[2], base[3]]) as usize;
+        if header_size < BASE_HEADER_LEN {
+            return Ok(None);
+        }
+        // Safety: The base header states the size of the whole header. When
+        // it loaded the process, the kernel checked that the header lies
+        // within the process's flash region, which is readable.
+        let header = unsafe { core::slice::from_raw_parts(flash_start, header_size) };
+        Ok(Self::from_tbf_header(header))
+    }
+
+    /// Returns the kernel version stated by the TBF header `header`, if any.
+    pub fn from_tbf_header(header: &[u8]) -> Option<Self> {
+        // The TLV entries follow the base header. Each one is a type and a
+        // length, followed by a value padded to a multiple of 4 bytes.
+        let mut entries = header.get(BASE_HEADER_LEN..)?;
+        while entries.len() >= 4 {
+            let tlv_type = u16::from_le_bytes([entries[0], entries[1]]);
+            let length = u16::from_le_bytes([entries[2], entries[3]]) as usize;
+            let value = entries.get(4..4 + length)?;
+            if tlv_type == KERNEL_VERSION_TLV && length >= 4 {
+                return Some(KernelVersion {
+                    major: u16::from_le_bytes([value[0], value[1]]),
+                    minor: u16::from_le_bytes([value[2], value[3]]),
+                });
+            }
+            entries = entries.get(4 + (length + 3) / 4 * 4..)?;
+        }
+        None
+    }
+
+    /// Returns `true` if a process built for `self` can run on a kernel of
+    /// version `kernel`, the same check the kernel does when loading it.
+    pub fn is_compatible_with(&self, kernel: KernelVersion) -> bool {
+        kernel.major == self.major && kernel.minor >= self.minor
+    }
+}
+
+impl fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+// The length of the base TBF header: version, header size, total size, flags
+// and checksum.
+const BASE_HEADER_LEN: usize = 16;
+
+const KERNEL_VERSION_TLV: u16 = 8;
@@ -0,0 +1,36 @@
+use crate::KernelVersion;
+
+// A base header, followed by a Main TLV and a KernelVersion TLV for 2.1.
+const HEADER: [u8; 36] = [
+    2, 0, 36, 0, 0, 4, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, // Base header
+    1, 0, 12, 0, 0x41, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // Main
+    8, 0, 4, 0, // KernelVersion
+];
+
+#[test]
+fn from_tbf_header() {
+    let mut header = [0; 40];
+    header[..36].copy_from_slice(&HEADER);
+    header[36..].copy_from_slice(&[2, 0, 1, 0]);
+    assert_eq!(
+        KernelVersion::from_tbf_header(&header),
+        Some(KernelVersion::new(2, 1))
+    );
+    // Without the KernelVersion TLV.
+    assert_eq!(KernelVersion::from_tbf_header(&header[..32]), None);
+    // Truncated within the KernelVersion TLV.
+    assert_eq!(KernelVersion::from_tbf_header(&header[..38]), None);
+    // Truncated within the base header.
+    assert_eq!(KernelVersion::from_tbf_header(&header[..8]), None);
+}
+
+#[test]
+fn is_compatible_with() {
+    let version = KernelVersion::new(2, 1);
+    assert!(version.is_compatible_with(KernelVersion::new(2, 1)));
+    assert!(version.is_compatible_with(KernelVersion::new(2, 2)));
+    assert!(!version.is_compatible_with(KernelVersion::new(2, 0)));
+    assert!(!version.is_compatible_with(KernelVersion::new(3, 1)));
+    assert!(version < KernelVersion::new(3, 0));
+    assert_eq!(format!("{}", version), "2.1");
+}
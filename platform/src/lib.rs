@@ -1,39 +1,68 @@
 #![cfg_attr(not(test), no_std)]
 #![warn(unsafe_op_in_unsafe_fn)]
 
+mod aligned_buf;
 pub mod allow_ro;
 pub mod allow_rw;
+pub mod command;
 pub mod command_return;
 mod constants;
 mod default_config;
+mod driver_info;
 mod error_code;
 pub mod exit_on_drop;
+mod kernel_version;
 mod raw_syscalls;
 mod register;
 pub mod return_variant;
 pub mod share;
+mod stack_usage;
+mod strict_config;
 pub mod subscribe;
 mod syscalls;
 mod syscalls_impl;
 mod termination;
+#[cfg(feature = "heapless")]
+mod upcall_buffers;
+mod upcall_ctx;
 mod yield_types;
 
+pub use aligned_buf::{Align, AlignedBuf, Alignment};
 pub use allow_ro::AllowRo;
 pub use allow_rw::AllowRw;
 pub use command_return::CommandReturn;
 pub use constants::{exit_id, syscall_class, yield_id};
 pub use default_config::DefaultConfig;
+pub use driver_info::DriverInfo;
 pub use error_code::ErrorCode;
+pub use kernel_version::KernelVersion;
 pub use raw_syscalls::RawSyscalls;
 pub use register::Register;
 pub use return_variant::ReturnVariant;
+pub use stack_usage::hinted_stack_size;
+pub use strict_config::StrictConfig;
 pub use subscribe::{Subscribe, Upcall};
 pub use syscalls::Syscalls;
-pub use termination::Termination;
+pub use termination::{ExitError, Termination};
+#[cfg(feature = "heapless")]
+pub use upcall_buffers::{FromUpcallArgs, UpcallQueue, UpcallVec};
+pub use upcall_ctx::{CtxUpcall, UpcallCtx};
 pub use yield_types::YieldNoWaitReturn;
 
+#[cfg(test)]
+mod aligned_buf_tests;
+
 #[cfg(test)]
 mod command_return_tests;
 
 #[cfg(test)]
 mod error_code_tests;
+
+#[cfg(test)]
+mod kernel_version_tests;
+
+#[cfg(test)]
+mod stack_usage_tests;
+
+#[cfg(all(test, feature = "heapless"))]
+mod upcall_buffers_tests;
@@ -0,0 +1,25 @@
+/// Returns a stack size covering the stack usage `hints`, in bytes, plus a
+/// safety margin of `margin_percent` percent of their sum, rounded up to the
+/// 8 bytes the stack pointer is aligned to. Used by `stack_size!`, e.g.:
+///
+/// ```ignore
+/// stack_size! {hints: [runtime::STACK_USAGE, console::STACK_USAGE, 0x80], margin: 25}
+/// ```
+///
+/// API crates that know their worst-case stack usage publish it as a
+/// `STACK_USAGE` constant: the deepest stack one of their calls uses, not
+/// counting the buffers the caller passes in. An app sums the hints of the
+/// APIs it uses with the runtime's and its own usage: calls can nest, as the
+/// upcalls of one API run inside the yields of another API's calls, so the sum
+/// bounds the stack from above even where the largest hint would not. The
+/// margin is applied to that sum.
+pub const fn hinted_stack_size(hints: &[usize], margin_percent: usize) -> usize {
+    let mut sum = 0;
+    let mut i = 0;
+    while i < hints.len() {
+        sum += hints[i];
+        i += 1;
+    }
+    let size = sum + (sum * margin_percent).div_ceil(100);
+    size.next_multiple_of(8)
+}
@@ -0,0 +1,14 @@
+use crate::hinted_stack_size;
+
+#[test]
+fn hinted_stack_size_sums() {
+    assert_eq!(hinted_stack_size(&[], 25), 0);
+    assert_eq!(hinted_stack_size(&[0x40, 0x80], 0), 0xC0);
+    assert_eq!(hinted_stack_size(&[0x40, 0x80], 25), 0xF0);
+    // The margin is rounded up, then the size to 8 bytes.
+    assert_eq!(hinted_stack_size(&[0x41], 10), 0x48);
+    assert_eq!(hinted_stack_size(&[0x40], 1), 0x48);
+}
+
+// The size is usable in constant expressions, such as array lengths.
+const _: [u8; hinted_stack_size(&[4], 0)] = [0; 8];
@@ -0,0 +1,56 @@
+use crate::{allow_ro, allow_rw, command, subscribe, ErrorCode};
+
+/// A syscall configuration that, in debug builds, panics when an Allow,
+/// Subscribe or checked Command call fails with `ErrorCode::NoSupport`,
+/// `ErrorCode::Invalid` or `ErrorCode::NoDevice`.
+///
+/// These errors usually come from a programming or board configuration error,
+/// such as a wrong driver, buffer or command number, or a driver missing from
+/// the board, rather than from a condition the app can recover from. Panicking
+/// with the driver and allow/subscribe/command number points at the
+/// misconfiguration early. Failures of command 0, which checks whether a
+/// driver exists, are answers rather than errors, and are returned. Release
+/// builds return the error like `DefaultConfig`.
+pub struct StrictConfig;
+
+impl StrictConfig {
+    fn check(call: &str, driver_num: u32, num: u32, error: ErrorCode) {
+        if cfg!(debug_assertions)
+            && matches!(
+                error,
+                ErrorCode::NoSupport | ErrorCode::Invalid | ErrorCode::NoDevice
+            )
+        {
+            panic!("{call} {num} of driver {driver_num:#x} failed with {error:?}");
+        }
+    }
+}
+
+impl allow_ro::Config for StrictConfig {
+    fn should_retry(driver_num: u32, buffer_num: u32, error: ErrorCode, _failures: u32) -> bool {
+        Self::check("Read-Only Allow", driver_num, buffer_num, error);
+        false
+    }
+}
+
+impl allow_rw::Config for StrictConfig {
+    fn should_retry(driver_num: u32, buffer_num: u32, error: ErrorCode, _failures: u32) -> bool {
+        Self::check("Read-Write Allow", driver_num, buffer_num, error);
+        false
+    }
+}
+
+impl command::Config for StrictConfig {
+    fn command_failed(driver_num: u32, command_num: u32, error: ErrorCode) {
+        if command_num != 0 {
+            Self::check("Command", driver_num, command_num, error);
+        }
+    }
+}
+
+impl subscribe::Config for StrictConfig {
+    fn should_retry(driver_num: u32, subscribe_num: u32, error: ErrorCode, _failures: u32) -> bool {
+        Self::check("Subscribe", driver_num, subscribe_num, error);
+        false
+    }
+}
@@ -220,4 +220,16 @@ pub trait Config {
     /// some applications, this may indicate unexpected reentrance. By default,
     /// the non-null upcall is ignored.
     fn returned_nonnull_upcall(_driver_num: u32, _subscribe_num: u32) {}
+
+    /// Called if a Subscribe call fails, with the number of times it has failed
+    /// in a row. Returns whether to make the call again, e.g. after a delay if
+    /// the error is transient. By default, the call is not retried.
+    fn should_retry(
+        _driver_num: u32,
+        _subscribe_num: u32,
+        _error: ErrorCode,
+        _failures: u32,
+    ) -> bool {
+        false
+    }
 }
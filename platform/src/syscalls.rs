@@ -1,6 +1,6 @@
 use crate::{
-    allow_ro, allow_rw, share, subscribe, AllowRo, AllowRw, CommandReturn, ErrorCode, RawSyscalls,
-    Subscribe, Upcall, YieldNoWaitReturn,
+    allow_ro, allow_rw, command, share, subscribe, AllowRo, AllowRw, CommandReturn, ErrorCode,
+    RawSyscalls, Subscribe, Upcall, YieldNoWaitReturn,
 };
 
 /// `Syscalls` provides safe abstractions over Tock's system calls. It is
@@ -16,10 +16,21 @@ pub trait Syscalls: RawSyscalls + Sized {
     /// pending.
     fn yield_no_wait() -> YieldNoWaitReturn;
 
+    /// Runs the next pending callback, if a callback is pending, and returns
+    /// `true` if a callback ran. This is the same system call as
+    /// `yield_no_wait`, but its return value lets cooperative schedulers
+    /// distinguish "did work" from "nothing pending" with a plain `bool`.
+    fn yield_no_wait_flag() -> bool;
+
     /// Puts the process to sleep until a callback becomes pending, invokes the
     /// callback, then returns.
     fn yield_wait();
 
+    /// Calls `yield_wait` until `done` returns `true`. It never fails, but
+    /// returns a `Result` so that it can be passed where a wait that may time
+    /// out is expected.
+    fn yield_until(done: &mut dyn FnMut() -> bool) -> Result<(), ErrorCode>;
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------
@@ -47,6 +58,15 @@ pub trait Syscalls: RawSyscalls + Sized {
 
     fn command(driver_id: u32, command_id: u32, argument0: u32, argument1: u32) -> CommandReturn;
 
+    /// Calls Command like `command`, and passes its error, if it fails, to
+    /// `CONFIG::command_failed`.
+    fn checked_command<CONFIG: command::Config>(
+        driver_id: u32,
+        command_id: u32,
+        argument0: u32,
+        argument1: u32,
+    ) -> CommandReturn;
+
     // -------------------------------------------------------------------------
     // Read-Write Allow
     // -------------------------------------------------------------------------
@@ -112,7 +132,31 @@ pub trait Syscalls: RawSyscalls + Sized {
     /// Tells the kernel the initial program break, to support debugging.
     fn memop_debug_heap_start(initial_break: *const u8) -> Result<(), ErrorCode>;
 
-    // TODO: Add remaining memop() methods (3-9).
+    /// Gets the address just past the end of this application's RAM
+    /// allocation.
+    fn memop_app_ram_end() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address of the start of this application's flash region,
+    /// where its TBF header is.
+    fn memop_flash_start() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address just past the end of this application's flash region.
+    fn memop_flash_end() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address of the start of the grant region, the part of this
+    /// application's RAM allocation that the kernel uses.
+    fn memop_grant_start() -> Result<*const u8, ErrorCode>;
+
+    /// Gets the number of writeable flash regions in this application's TBF
+    /// header.
+    fn memop_flash_region_count() -> Result<u32, ErrorCode>;
+
+    /// Gets the address of the start of writeable flash region `region`.
+    fn memop_flash_region_start(region: u32) -> Result<*const u8, ErrorCode>;
+
+    /// Gets the address just past the end of writeable flash region
+    /// `region`.
+    fn memop_flash_region_end(region: u32) -> Result<*const u8, ErrorCode>;
 
     // -------------------------------------------------------------------------
     // Exit
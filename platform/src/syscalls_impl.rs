@@ -1,9 +1,9 @@
 //! Implements `Syscalls` for all types that implement `RawSyscalls`.
 
 use crate::{
-    allow_ro, allow_rw, exit_id, exit_on_drop, return_variant, share, subscribe, syscall_class,
-    yield_id, AllowRo, AllowRw, CommandReturn, ErrorCode, RawSyscalls, Register, ReturnVariant,
-    Subscribe, Syscalls, Upcall, YieldNoWaitReturn,
+    allow_ro, allow_rw, command, exit_id, exit_on_drop, return_variant, share, subscribe,
+    syscall_class, yield_id, AllowRo, AllowRw, CommandReturn, ErrorCode, RawSyscalls, Register,
+    ReturnVariant, Subscribe, Syscalls, Upcall, YieldNoWaitReturn,
 };
 
 impl<S: RawSyscalls> Syscalls for S {
@@ -26,6 +26,10 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 
+    fn yield_no_wait_flag() -> bool {
+        Self::yield_no_wait() == YieldNoWaitReturn::Upcall
+    }
+
     fn yield_wait() {
         // Safety: yield-wait does not return a value, which satisfies yield1's
         // requirement. The yield-wait system call cannot trigger undefined
@@ -35,6 +39,15 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 
+    fn yield_until(done: &mut dyn FnMut() -> bool) -> Result<(), ErrorCode> {
+        loop {
+            Self::yield_wait();
+            if done() {
+                return Ok(());
+            }
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Subscribe
     // -------------------------------------------------------------------------
@@ -136,12 +149,14 @@ impl<S: RawSyscalls> Syscalls for S {
 
         let upcall_fcn = (kernel_upcall::<S, IDS, U> as *const ()).into();
         let upcall_data = (upcall as *const U).into();
-        // Safety: upcall's type guarantees it is a reference to a U that will
-        // remain valid for at least the 'scope lifetime. _subscribe is a
-        // reference to a Subscribe<'scope, Self, DRIVER_NUM, SUBSCRIBE_NUM>,
-        // proving one exists. upcall_fcn and upcall_data are derived in ways
-        // that satisfy inner's requirements.
-        unsafe { inner::<Self, CONFIG>(DRIVER_NUM, SUBSCRIBE_NUM, upcall_fcn, upcall_data) }
+        retry(DRIVER_NUM, SUBSCRIBE_NUM, CONFIG::should_retry, || {
+            // Safety: upcall's type guarantees it is a reference to a U that
+            // will remain valid for at least the 'scope lifetime. _subscribe is
+            // a reference to a Subscribe<'scope, Self, DRIVER_NUM,
+            // SUBSCRIBE_NUM>, proving one exists. upcall_fcn and upcall_data
+            // are derived in ways that satisfy inner's requirements.
+            unsafe { inner::<Self, CONFIG>(DRIVER_NUM, SUBSCRIBE_NUM, upcall_fcn, upcall_data) }
+        })
     }
 
     fn unsubscribe(driver_num: u32, subscribe_num: u32) {
@@ -181,6 +196,24 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 
+    fn checked_command<CONFIG: command::Config>(
+        driver_id: u32,
+        command_id: u32,
+        argument0: u32,
+        argument1: u32,
+    ) -> CommandReturn {
+        let command_return = Self::command(driver_id, command_id, argument0, argument1);
+        let error = command_return
+            .get_failure()
+            .or(command_return.get_failure_u32().map(|(error, _)| error))
+            .or(command_return.get_failure_2_u32().map(|(error, ..)| error))
+            .or(command_return.get_failure_u64().map(|(error, _)| error));
+        if let Some(error) = error {
+            CONFIG::command_failed(driver_id, command_id, error);
+        }
+        command_return
+    }
+
     // -------------------------------------------------------------------------
     // Read-Write Allow
     // -------------------------------------------------------------------------
@@ -245,10 +278,12 @@ impl<S: RawSyscalls> Syscalls for S {
             Ok(())
         }
 
-        // Safety: The presence of the share::Handle<AllowRw<'share, ...>>
-        // guarantees that an AllowRw exists and will clean up this Allow ID
-        // before the 'share lifetime ends.
-        unsafe { inner::<Self, CONFIG>(DRIVER_NUM, BUFFER_NUM, buffer) }
+        retry(DRIVER_NUM, BUFFER_NUM, CONFIG::should_retry, || {
+            // Safety: The presence of the share::Handle<AllowRw<'share, ...>>
+            // guarantees that an AllowRw exists and will clean up this Allow
+            // ID before the 'share lifetime ends.
+            unsafe { inner::<Self, CONFIG>(DRIVER_NUM, BUFFER_NUM, buffer) }
+        })
     }
 
     fn unallow_rw(driver_num: u32, buffer_num: u32) {
@@ -332,10 +367,12 @@ impl<S: RawSyscalls> Syscalls for S {
             Ok(())
         }
 
-        // Security: The presence of the share::Handle<AllowRo<'share, ...>>
-        // guarantees that an AllowRo exists and will clean up this Allow ID
-        // before the 'share lifetime ends.
-        inner::<Self, CONFIG>(DRIVER_NUM, BUFFER_NUM, buffer)
+        retry(DRIVER_NUM, BUFFER_NUM, CONFIG::should_retry, || {
+            // Security: The presence of the share::Handle<AllowRo<'share, ...>>
+            // guarantees that an AllowRo exists and will clean up this Allow
+            // ID before the 'share lifetime ends.
+            inner::<Self, CONFIG>(DRIVER_NUM, BUFFER_NUM, buffer)
+        })
     }
 
     fn unallow_ro(driver_num: u32, buffer_num: u32) {
@@ -499,6 +536,34 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 
+    fn memop_app_ram_end() -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(3, None).map(Into::into)
+    }
+
+    fn memop_flash_start() -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(4, None).map(Into::into)
+    }
+
+    fn memop_flash_end() -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(5, None).map(Into::into)
+    }
+
+    fn memop_grant_start() -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(6, None).map(Into::into)
+    }
+
+    fn memop_flash_region_count() -> Result<u32, ErrorCode> {
+        memop_query::<Self>(7, None).map(Register::as_u32)
+    }
+
+    fn memop_flash_region_start(region: u32) -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(8, Some(region)).map(Into::into)
+    }
+
+    fn memop_flash_region_end(region: u32) -> Result<*const u8, ErrorCode> {
+        memop_query::<Self>(9, Some(region)).map(Into::into)
+    }
+
     // -------------------------------------------------------------------------
     // Exit
     // -------------------------------------------------------------------------
@@ -530,3 +595,55 @@ impl<S: RawSyscalls> Syscalls for S {
         }
     }
 }
+
+// Makes a system call until it succeeds, or `should_retry` (a Config hook)
+// tells to stop retrying.
+fn retry<F: FnMut() -> Result<(), ErrorCode>>(
+    driver_num: u32,
+    num: u32,
+    should_retry: fn(u32, u32, ErrorCode, u32) -> bool,
+    mut call: F,
+) -> Result<(), ErrorCode> {
+    let mut failures = 0;
+    loop {
+        match call() {
+            Err(error) => {
+                failures += 1;
+                if !should_retry(driver_num, num, error, failures) {
+                    return Err(error);
+                }
+            }
+            Ok(()) => return Ok(()),
+        }
+    }
+}
+
+// Calls memop `memop_num`, which reports a value about the process (memops 2
+// to 9), passing `argument0` if it takes one.
+fn memop_query<S: RawSyscalls>(
+    memop_num: u32,
+    argument0: Option<u32>,
+) -> Result<Register, ErrorCode> {
+    let [r0, r1] = match argument0 {
+        // Safety: syscall1's documentation indicates it can be used to call
+        // Memop operations that only accept a memop operation number.
+        None => unsafe { S::syscall1::<{ syscall_class::MEMOP }>([memop_num.into()]) },
+        // Safety: syscall2's documentation indicates it can be used to call
+        // Memop.
+        Some(argument0) => unsafe {
+            S::syscall2::<{ syscall_class::MEMOP }>([memop_num.into(), argument0.into()])
+        },
+    };
+    let return_variant: ReturnVariant = r0.as_u32().into();
+    // TRD 104 guarantees that these memops return either Success with u32 or
+    // Failure. We compare against Failure for the same reasons as
+    // memop_app_ram_start.
+    if return_variant == return_variant::FAILURE {
+        // Safety: TRD 104 guarantees that if r0 is Failure, then r1 will
+        // contain a valid error code. ErrorCode is designed to be safely
+        // transmuted directly from a kernel error code.
+        Err(unsafe { core::mem::transmute::<u32, ErrorCode>(r1.as_u32()) })
+    } else {
+        Ok(r1)
+    }
+}
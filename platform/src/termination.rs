@@ -5,6 +5,31 @@ use crate::{ErrorCode, Syscalls};
 
 pub trait Termination {
     fn complete<S: Syscalls>(self) -> !;
+
+    /// Returns the exit code of a failed `main`, so the runtime can report it
+    /// before calling `complete`. Returns `None` if `main` succeeded.
+    fn error_code(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// An error that `main` can return as `Result<(), E>`. Implement this for an
+/// application-specific error type to let `main` return it directly.
+pub trait ExitError {
+    /// The exit code to pass to the kernel.
+    fn exit_code(&self) -> u32;
+
+    /// Whether the process should be restarted, rather than terminated, after
+    /// `main` returns this error.
+    fn restart(&self) -> bool {
+        false
+    }
+}
+
+impl ExitError for ErrorCode {
+    fn exit_code(&self) -> u32 {
+        *self as u32
+    }
 }
 
 impl Termination for () {
@@ -13,12 +38,16 @@ impl Termination for () {
     }
 }
 
-impl Termination for Result<(), ErrorCode> {
+impl<E: ExitError> Termination for Result<(), E> {
     fn complete<S: Syscalls>(self) -> ! {
-        let exit_code = match self {
-            Ok(()) => 0,
-            Err(ec) => ec as u32,
-        };
-        S::exit_terminate(exit_code);
+        match self {
+            Ok(()) => S::exit_terminate(0),
+            Err(error) if error.restart() => S::exit_restart(error.exit_code()),
+            Err(error) => S::exit_terminate(error.exit_code()),
+        }
+    }
+
+    fn error_code(&self) -> Option<u32> {
+        self.as_ref().err().map(ExitError::exit_code)
     }
 }
@@ -0,0 +1,227 @@
+//! `Upcall` implementations that buffer several events, backed by `heapless`
+//! collections.
+//!
+//! The `Cell<Option<...>>` upcalls only hold the most recent event, which is
+//! enough to wait for a single operation but loses events that arrive faster
+//! than the process handles them (button presses, received packets, sensor
+//! samples). [`UpcallQueue`] and [`UpcallVec`] store every event, up to a
+//! fixed capacity, and count the events they had to drop.
+//!
+//! # Aliasing
+//!
+//! Upcalls only run during Yield system calls, and a process is single
+//! threaded, so an upcall never runs while the process is in the middle of
+//! another access to the same buffer, as long as that access does not yield.
+//! The buffers rely on this: they hand out owned values only (never
+//! references into the collection), and none of their methods yield or call
+//! back into user code while accessing the collection. Code that needs to
+//! inspect several events at once should `take` them from an `UpcallVec`, or
+//! `pop` them from an `UpcallQueue`, between yields.
+//!
+//! # Example
+//! ```ignore
+//! // Buffers up to 7 button events.
+//! let events: UpcallQueue<(u32, u32), 8> = UpcallQueue::new();
+//! share::scope(|subscribe| {
+//!     Buttons::register_listener(&events, subscribe)?;
+//!     loop {
+//!         TockSyscalls::yield_wait();
+//!         while let Some((button, state)) = events.pop() {
+//!             // ...
+//!         }
+//!     }
+//! })
+//! ```
+
+use crate::subscribe::AnyId;
+use crate::{ErrorCode, Upcall};
+use core::cell::{Cell, UnsafeCell};
+
+/// Converts the arguments of an upcall into an event stored by
+/// [`UpcallQueue`] or [`UpcallVec`]. Implemented for the tuples and results the
+/// `Cell<Option<...>>` upcalls store; implement it for driver-specific event
+/// types.
+pub trait FromUpcallArgs {
+    fn from_upcall_args(arg0: u32, arg1: u32, arg2: u32) -> Self;
+}
+
+impl FromUpcallArgs for () {
+    fn from_upcall_args(_: u32, _: u32, _: u32) -> Self {}
+}
+
+impl FromUpcallArgs for (u32,) {
+    fn from_upcall_args(arg0: u32, _: u32, _: u32) -> Self {
+        (arg0,)
+    }
+}
+
+impl FromUpcallArgs for (u32, u32) {
+    fn from_upcall_args(arg0: u32, arg1: u32, _: u32) -> Self {
+        (arg0, arg1)
+    }
+}
+
+impl FromUpcallArgs for (u32, u32, u32) {
+    fn from_upcall_args(arg0: u32, arg1: u32, arg2: u32) -> Self {
+        (arg0, arg1, arg2)
+    }
+}
+
+impl FromUpcallArgs for Result<(), ErrorCode> {
+    fn from_upcall_args(arg0: u32, _: u32, _: u32) -> Self {
+        match arg0 {
+            0 => Ok(()),
+            _ => Err(arg0.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+}
+
+impl FromUpcallArgs for Result<(u32,), ErrorCode> {
+    fn from_upcall_args(arg0: u32, arg1: u32, _: u32) -> Self {
+        match arg0 {
+            0 => Ok((arg1,)),
+            _ => Err(arg0.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+}
+
+impl FromUpcallArgs for Result<(u32, u32), ErrorCode> {
+    fn from_upcall_args(arg0: u32, arg1: u32, arg2: u32) -> Self {
+        match arg0 {
+            0 => Ok((arg1, arg2)),
+            _ => Err(arg0.try_into().unwrap_or(ErrorCode::Fail)),
+        }
+    }
+}
+
+/// An `Upcall` that appends each event to a FIFO queue. The queue holds up to
+/// `N - 1` events (a limitation of `heapless::spsc::Queue`); events that
+/// arrive while it is full are dropped.
+pub struct UpcallQueue<T, const N: usize> {
+    // Only accessed by methods that do not yield; see the module
+    // documentation.
+    queue: UnsafeCell<heapless::spsc::Queue<T, N>>,
+    dropped: Cell<usize>,
+}
+
+impl<T, const N: usize> UpcallQueue<T, N> {
+    pub const fn new() -> Self {
+        UpcallQueue {
+            queue: UnsafeCell::new(heapless::spsc::Queue::new()),
+            dropped: Cell::new(0),
+        }
+    }
+
+    /// Removes and returns the oldest event, if any.
+    pub fn pop(&self) -> Option<T> {
+        // Safety: see `with_queue`.
+        unsafe { self.with_queue(|queue| queue.dequeue()) }
+    }
+
+    /// Returns the number of buffered events.
+    pub fn len(&self) -> usize {
+        // Safety: see `with_queue`.
+        unsafe { self.with_queue(|queue| queue.len()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of events dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+
+    // Runs `f` with exclusive access to the queue.
+    //
+    // Safety: `f` must not yield, call user code, or access the queue through
+    // `self`. As upcalls only run during Yield, nothing else accesses the
+    // queue while `f` runs.
+    unsafe fn with_queue<R>(&self, f: impl FnOnce(&mut heapless::spsc::Queue<T, N>) -> R) -> R {
+        // Safety: by the caller's guarantee, this is the only reference to
+        // the queue while it exists.
+        f(unsafe { &mut *self.queue.get() })
+    }
+}
+
+impl<T, const N: usize> Default for UpcallQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FromUpcallArgs, const N: usize> Upcall<AnyId> for UpcallQueue<T, N> {
+    fn upcall(&self, arg0: u32, arg1: u32, arg2: u32) {
+        let event = T::from_upcall_args(arg0, arg1, arg2);
+        // Safety: enqueue does not yield or call user code.
+        if unsafe { self.with_queue(|queue| queue.enqueue(event)) }.is_err() {
+            self.dropped.set(self.dropped.get() + 1);
+        }
+    }
+}
+
+/// An `Upcall` that appends each event to a vector of up to `N` events, which
+/// the process takes as a whole. Events that arrive while the vector is full
+/// are dropped.
+pub struct UpcallVec<T, const N: usize> {
+    // Only accessed by methods that do not yield; see the module
+    // documentation.
+    events: UnsafeCell<heapless::Vec<T, N>>,
+    dropped: Cell<usize>,
+}
+
+impl<T, const N: usize> UpcallVec<T, N> {
+    pub const fn new() -> Self {
+        UpcallVec {
+            events: UnsafeCell::new(heapless::Vec::new()),
+            dropped: Cell::new(0),
+        }
+    }
+
+    /// Returns the buffered events, oldest first, and empties the buffer.
+    pub fn take(&self) -> heapless::Vec<T, N> {
+        // Safety: see `with_events`.
+        unsafe { self.with_events(core::mem::take) }
+    }
+
+    /// Returns the number of buffered events.
+    pub fn len(&self) -> usize {
+        // Safety: see `with_events`.
+        unsafe { self.with_events(|events| events.len()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of events dropped because the buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
+
+    // Runs `f` with exclusive access to the events.
+    //
+    // Safety: as for `UpcallQueue::with_queue`.
+    unsafe fn with_events<R>(&self, f: impl FnOnce(&mut heapless::Vec<T, N>) -> R) -> R {
+        // Safety: by the caller's guarantee, this is the only reference to
+        // the events while it exists.
+        f(unsafe { &mut *self.events.get() })
+    }
+}
+
+impl<T, const N: usize> Default for UpcallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FromUpcallArgs, const N: usize> Upcall<AnyId> for UpcallVec<T, N> {
+    fn upcall(&self, arg0: u32, arg1: u32, arg2: u32) {
+        let event = T::from_upcall_args(arg0, arg1, arg2);
+        // Safety: push does not yield or call user code.
+        if unsafe { self.with_events(|events| events.push(event)) }.is_err() {
+            self.dropped.set(self.dropped.get() + 1);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{ErrorCode, Upcall, UpcallQueue, UpcallVec};
+
+#[test]
+fn queue() {
+    let queue: UpcallQueue<(u32, u32), 4> = UpcallQueue::new();
+    assert!(queue.is_empty());
+    assert_eq!(queue.pop(), None);
+
+    queue.upcall(1, 2, 3);
+    queue.upcall(4, 5, 6);
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.pop(), Some((1, 2)));
+
+    // The queue holds N - 1 events, and drops the rest.
+    queue.upcall(7, 8, 9);
+    queue.upcall(10, 11, 12);
+    queue.upcall(13, 14, 15);
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.dropped(), 1);
+    assert_eq!(queue.pop(), Some((4, 5)));
+    assert_eq!(queue.pop(), Some((7, 8)));
+    assert_eq!(queue.pop(), Some((10, 11)));
+    assert_eq!(queue.pop(), None);
+    assert_eq!(queue.dropped(), 1);
+}
+
+#[test]
+fn vec() {
+    let events: UpcallVec<Result<(u32,), ErrorCode>, 2> = UpcallVec::default();
+    assert!(events.is_empty());
+
+    events.upcall(0, 1, 2);
+    events.upcall(ErrorCode::Busy as u32, 3, 4);
+    events.upcall(0, 5, 6);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events.dropped(), 1);
+    assert_eq!(&events.take()[..], &[Ok((1,)), Err(ErrorCode::Busy)]);
+    assert!(events.is_empty());
+
+    // Taking the events makes room for new ones.
+    events.upcall(0, 7, 8);
+    assert_eq!(&events.take()[..], &[Ok((7,))]);
+}
+
+#[test]
+fn from_upcall_args() {
+    let unit: UpcallVec<(), 1> = UpcallVec::new();
+    unit.upcall(1, 2, 3);
+    assert_eq!(unit.len(), 1);
+
+    let three: UpcallVec<(u32, u32, u32), 1> = UpcallVec::new();
+    three.upcall(1, 2, 3);
+    assert_eq!(&three.take()[..], &[(1, 2, 3)]);
+
+    let two: UpcallVec<Result<(u32, u32), ErrorCode>, 2> = UpcallVec::new();
+    two.upcall(0, 2, 3);
+    // Not an error code.
+    two.upcall(0x10000, 2, 3);
+    assert_eq!(&two.take()[..], &[Ok((2, 3)), Err(ErrorCode::Fail)]);
+}
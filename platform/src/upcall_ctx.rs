@@ -0,0 +1,77 @@
+use crate::subscribe::AnyId;
+use crate::{CommandReturn, Syscalls, Upcall};
+use core::marker::PhantomData;
+
+/// A token given to upcall callbacks, which only exposes the system calls
+/// that are safe to make in upcall context.
+///
+/// Upcalls run inside Yield, so a callback that yields again (e.g. by calling
+/// a blocking API, which waits with `yield_wait`) runs other upcalls, and
+/// possibly itself, re-entrantly, or never returns if the event it waits for
+/// is only delivered once it returns. Callbacks written against `UpcallCtx`
+/// instead of `Syscalls` cannot yield, allow or subscribe: they can only issue
+/// commands, e.g. to start the next operation, and record what happened for
+/// the code outside the upcall. The token cannot be created outside of an
+/// upcall, nor kept after it.
+pub struct UpcallCtx<'u, S: Syscalls> {
+    _syscalls: PhantomData<(&'u (), S)>,
+}
+
+impl<S: Syscalls> UpcallCtx<'_, S> {
+    /// Makes a Command system call, which never blocks.
+    pub fn command(
+        &self,
+        driver_id: u32,
+        command_id: u32,
+        argument0: u32,
+        argument1: u32,
+    ) -> CommandReturn {
+        S::command(driver_id, command_id, argument0, argument1)
+    }
+
+    /// Terminates the process, e.g. on an unrecoverable error.
+    pub fn exit_terminate(&self, exit_code: u32) -> ! {
+        S::exit_terminate(exit_code)
+    }
+}
+
+/// An `Upcall` that calls `F` with an [`UpcallCtx`] and the upcall's
+/// arguments.
+///
+/// # Example
+/// ```ignore
+/// use libtock_platform::{CtxUpcall, UpcallCtx};
+///
+/// let count = Cell::new(0);
+/// // Restarts the sampling after each sample, without yielding.
+/// let upcall = CtxUpcall::new(|ctx: &UpcallCtx<S>, _, _, _| {
+///     count.set(count.get() + 1);
+///     let _ = ctx.command(DRIVER_NUM, command::SAMPLE, 0, 0);
+/// });
+/// share::scope(|subscribe| {
+///     S::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &upcall)?;
+///     // ...
+/// });
+/// ```
+pub struct CtxUpcall<S: Syscalls, F: Fn(&UpcallCtx<S>, u32, u32, u32)> {
+    callback: F,
+    _syscalls: PhantomData<S>,
+}
+
+impl<S: Syscalls, F: Fn(&UpcallCtx<S>, u32, u32, u32)> CtxUpcall<S, F> {
+    pub fn new(callback: F) -> Self {
+        CtxUpcall {
+            callback,
+            _syscalls: PhantomData,
+        }
+    }
+}
+
+impl<S: Syscalls, F: Fn(&UpcallCtx<S>, u32, u32, u32)> Upcall<AnyId> for CtxUpcall<S, F> {
+    fn upcall(&self, arg0: u32, arg1: u32, arg2: u32) {
+        let ctx = UpcallCtx {
+            _syscalls: PhantomData,
+        };
+        (self.callback)(&ctx, arg0, arg1, arg2);
+    }
+}
@@ -0,0 +1,158 @@
+//! Registry of the buffers and upcalls currently shared with the kernel.
+//!
+//! A buffer stays shared until it is unallowed, which the `share::scope`
+//! handles do when they are dropped. If such a handle is leaked (e.g. by
+//! `core::mem::forget`), the kernel keeps writing into the buffer after it is
+//! freed, which corrupts whatever reuses its memory, typically a later stack
+//! frame. To find such bugs, the `allow_registry` feature records every
+//! successful Read-Write Allow, Read-Only Allow and Subscribe made through
+//! `TockSyscalls`, and [`snapshot`] returns the ones that are still in effect.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::allow_registry;
+//!
+//! fn main() {
+//!     // ...
+//!     // Nothing should be shared here.
+//!     allow_registry::dump_to_console();
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+use libtock_platform::{return_variant, syscall_class, Register, ReturnVariant};
+
+/// The maximum number of shares the registry tracks. Shares made while it is
+/// full are counted by [`Snapshot::dropped`].
+pub const CAPACITY: usize = 16;
+
+/// The system call that shared a buffer or upcall.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    AllowRw,
+    AllowRo,
+    Subscribe,
+}
+
+/// A buffer or upcall shared with the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub kind: Kind,
+    pub driver_num: u32,
+    /// The buffer number, or the subscribe number of upcalls.
+    pub num: u32,
+    /// The address of the buffer, or of the upcall's data.
+    pub address: usize,
+    /// The length of the buffer, or 0 for upcalls.
+    pub len: usize,
+}
+
+/// The shares in effect at the time of a [`snapshot`].
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    shares: [Option<Share>; CAPACITY],
+    dropped: u32,
+}
+
+impl Snapshot {
+    pub fn iter(&self) -> impl Iterator<Item = &Share> {
+        self.shares.iter().flatten()
+    }
+
+    /// Returns the number of shares that were made while the registry was
+    /// full, and are therefore missing from the snapshot.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+/// Lists one share per line.
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for share in self.iter() {
+            writeln!(
+                f,
+                "{:?} driver {:#x} num {}: {:#010x} len {}",
+                share.kind, share.driver_num, share.num, share.address, share.len
+            )?;
+        }
+        if self.dropped > 0 {
+            writeln!(f, "{} untracked", self.dropped)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the shares currently in effect. The snapshot is a copy, so sharing
+/// buffers while printing it (e.g. to the console) does not change it.
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        shares: REGISTRY.shares.get(),
+        dropped: REGISTRY.dropped.get(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+struct Registry {
+    shares: Cell<[Option<Share>; CAPACITY]>,
+    dropped: Cell<u32>,
+}
+
+// Safety: Tock processes are single-threaded, and the registry is only
+// accessed by snapshot and record, neither of which yields. Therefore there is
+// never concurrent access to the Cells.
+unsafe impl Sync for Registry {}
+
+static REGISTRY: Registry = Registry {
+    shares: Cell::new([None; CAPACITY]),
+    dropped: Cell::new(0),
+};
+
+// Records the effect of a syscall4 of class `CLASS`, called with `args`, that
+// returned `r0`.
+pub(crate) fn record<const CLASS: usize>(args: [Register; 4], r0: Register) {
+    let kind = match CLASS {
+        syscall_class::ALLOW_RW => Kind::AllowRw,
+        syscall_class::ALLOW_RO => Kind::AllowRo,
+        syscall_class::SUBSCRIBE => Kind::Subscribe,
+        _ => return,
+    };
+    if ReturnVariant::from(r0.as_u32()) != return_variant::SUCCESS_2_U32 {
+        return;
+    }
+    let [driver_num, num, arg2, arg3] = args;
+    let (driver_num, num) = (driver_num.as_u32(), num.as_u32());
+    // Unallowing shares an empty buffer, and unsubscribing the null upcall.
+    let share = match kind {
+        Kind::Subscribe if usize::from(arg2) == 0 => None,
+        Kind::Subscribe => Some((usize::from(arg3), 0)),
+        _ if usize::from(arg3) == 0 => None,
+        _ => Some((usize::from(arg2), usize::from(arg3))),
+    }
+    .map(|(address, len)| Share {
+        kind,
+        driver_num,
+        num,
+        address,
+        len,
+    });
+
+    let mut shares = REGISTRY.shares.get();
+    // A share replaces the previous one with the same ID.
+    let slot = shares
+        .iter()
+        .position(
+            |s| matches!(s, Some(s) if (s.kind, s.driver_num, s.num) == (kind, driver_num, num)),
+        )
+        .or_else(|| shares.iter().position(Option::is_none));
+    match (slot, share) {
+        (Some(slot), _) => shares[slot] = share,
+        (None, Some(_)) => REGISTRY.dropped.set(REGISTRY.dropped.get() + 1),
+        (None, None) => {}
+    }
+    REGISTRY.shares.set(shares);
+}
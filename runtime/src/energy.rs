@@ -0,0 +1,214 @@
+//! Estimates of the time each subsystem spends active, to estimate the
+//! battery lifetime of a device from its firmware.
+//!
+//! With the `energy` feature, `TockSyscalls` accounts for:
+//! - the time the CPU is awake, between the return of a yield-wait and the
+//!   next one, starting at the first yield-wait;
+//! - the time the IEEE 802.15.4 radio is on, between its TURN_ON and TURN_OFF
+//!   commands;
+//! - the bytes written to the console, from which [`Snapshot::console_ms`]
+//!   estimates the time the UART is busy sending them.
+//!
+//! The time is read from the alarm driver, with a command per yield-wait and
+//! per radio command, and nothing is accounted for if there is no alarm. A
+//! period longer than the alarm's wrap-around period (e.g. a CPU asleep for
+//! that long) is undercounted.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::energy;
+//!
+//! let usage = energy::snapshot();
+//! // Estimated charge, in µAh, from the board's datasheet currents in µA.
+//! let charge = (usage.cpu_ms() * CPU_UA
+//!     + usage.radio_ms() * RADIO_UA
+//!     + usage.console_ms(115200) * UART_UA)
+//!     / 3_600_000;
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+use libtock_platform::{syscall_class, yield_id, Register, Syscalls};
+
+/// The active time of each subsystem, in ticks of the alarm, at the time of a
+/// [`snapshot`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The frequency of the alarm, in Hz.
+    pub frequency: u32,
+    pub cpu_ticks: u64,
+    pub radio_ticks: u64,
+    pub console_bytes: u64,
+}
+
+impl Snapshot {
+    /// Returns the time the CPU was awake, in milliseconds.
+    pub fn cpu_ms(&self) -> u64 {
+        self.ticks_to_ms(self.cpu_ticks)
+    }
+
+    /// Returns the time the radio was on, in milliseconds.
+    pub fn radio_ms(&self) -> u64 {
+        self.ticks_to_ms(self.radio_ticks)
+    }
+
+    /// Returns the time the UART took to send the console's bytes at
+    /// `baud_rate`, in milliseconds, assuming 10 bits per byte (8N1).
+    pub fn console_ms(&self, baud_rate: u32) -> u64 {
+        match baud_rate {
+            0 => 0,
+            _ => self.console_bytes * 10 * 1000 / baud_rate as u64,
+        }
+    }
+
+    fn ticks_to_ms(&self, ticks: u64) -> u64 {
+        match self.frequency {
+            0 => 0,
+            frequency => ticks * 1000 / frequency as u64,
+        }
+    }
+}
+
+/// Lists one subsystem per line.
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "cpu {} ms", self.cpu_ms())?;
+        writeln!(f, "radio {} ms", self.radio_ms())?;
+        writeln!(f, "console {} bytes", self.console_bytes)
+    }
+}
+
+/// Returns the active time so far, including the ongoing awake and radio-on
+/// periods.
+pub fn snapshot() -> Snapshot {
+    let mut snapshot = Snapshot {
+        frequency: crate::TockSyscalls::command(alarm::DRIVER_NUM, alarm::FREQUENCY, 0, 0)
+            .get_success_u32()
+            .unwrap_or(0),
+        cpu_ticks: ACCOUNTS.cpu.get(),
+        radio_ticks: ACCOUNTS.radio.get(),
+        console_bytes: ACCOUNTS.console_bytes.get(),
+    };
+    if let Some(now) = now() {
+        snapshot.cpu_ticks += elapsed(ACCOUNTS.awake_since.get(), now);
+        snapshot.radio_ticks += elapsed(ACCOUNTS.radio_on_since.get(), now);
+    }
+    snapshot
+}
+
+/// Resets the active times to 0, e.g. to measure a single operation. The
+/// ongoing periods are only counted from now on.
+pub fn reset() {
+    let now = now();
+    ACCOUNTS.cpu.set(0);
+    ACCOUNTS.radio.set(0);
+    ACCOUNTS.console_bytes.set(0);
+    if ACCOUNTS.awake_since.get().is_some() {
+        ACCOUNTS.awake_since.set(now);
+    }
+    if ACCOUNTS.radio_on_since.get().is_some() {
+        ACCOUNTS.radio_on_since.set(now);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+struct Accounts {
+    cpu: Cell<u64>,
+    radio: Cell<u64>,
+    console_bytes: Cell<u64>,
+    // The time the CPU woke up, once it has yielded.
+    awake_since: Cell<Option<u32>>,
+    // The time the radio was turned on, if it is on.
+    radio_on_since: Cell<Option<u32>>,
+}
+
+// Safety: Tock processes are single-threaded, and the accounts are only
+// accessed by the functions of this module, none of which yields. Therefore
+// there is never concurrent access to the Cells.
+unsafe impl Sync for Accounts {}
+
+static ACCOUNTS: Accounts = Accounts {
+    cpu: Cell::new(0),
+    radio: Cell::new(0),
+    console_bytes: Cell::new(0),
+    awake_since: Cell::new(None),
+    radio_on_since: Cell::new(None),
+};
+
+// Returns the ticks since `since`, if set.
+fn elapsed(since: Option<u32>, now: u32) -> u64 {
+    since.map_or(0, |since| now.wrapping_sub(since) as u64)
+}
+
+// Reads the alarm. The command is ignored by `record`.
+fn now() -> Option<u32> {
+    crate::TockSyscalls::command(alarm::DRIVER_NUM, alarm::TIME, 0, 0).get_success_u32()
+}
+
+// Accounts for the awake period ending with a yield of type `id`.
+pub(crate) fn before_yield(id: Register) {
+    if id.as_u32() != yield_id::WAIT {
+        return;
+    }
+    if let Some(now) = now() {
+        let awake = elapsed(ACCOUNTS.awake_since.take(), now);
+        ACCOUNTS.cpu.set(ACCOUNTS.cpu.get() + awake);
+    }
+}
+
+// Starts the awake period following a yield of type `id`.
+pub(crate) fn after_yield(id: Register) {
+    if id.as_u32() == yield_id::WAIT {
+        ACCOUNTS.awake_since.set(now());
+    }
+}
+
+// Records the effect of a syscall4 of class `CLASS`, called with `args`, that
+// returned `r0`.
+pub(crate) fn record<const CLASS: usize>(args: [Register; 4], r0: Register) {
+    // Success variants start at 128.
+    if CLASS != syscall_class::COMMAND || r0.as_u32() < 128 {
+        return;
+    }
+    let [driver_num, command_num, argument0, _] = args;
+    match (driver_num.as_u32(), command_num.as_u32()) {
+        (radio::DRIVER_NUM, radio::TURN_ON) => {
+            if ACCOUNTS.radio_on_since.get().is_none() {
+                ACCOUNTS.radio_on_since.set(now());
+            }
+        }
+        (radio::DRIVER_NUM, radio::TURN_OFF) => {
+            if let Some(now) = now() {
+                let on = elapsed(ACCOUNTS.radio_on_since.take(), now);
+                ACCOUNTS.radio.set(ACCOUNTS.radio.get() + on);
+            }
+        }
+        (console::DRIVER_NUM, console::WRITE) => {
+            let bytes = argument0.as_u32() as u64;
+            ACCOUNTS
+                .console_bytes
+                .set(ACCOUNTS.console_bytes.get() + bytes);
+        }
+        _ => {}
+    }
+}
+
+mod alarm {
+    pub const DRIVER_NUM: u32 = 0;
+    pub const FREQUENCY: u32 = 1;
+    pub const TIME: u32 = 2;
+}
+
+mod console {
+    pub const DRIVER_NUM: u32 = 1;
+    pub const WRITE: u32 = 1;
+}
+
+mod radio {
+    pub const DRIVER_NUM: u32 = 0x30001;
+    pub const TURN_ON: u32 = 30;
+    pub const TURN_OFF: u32 = 31;
+}
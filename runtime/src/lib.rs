@@ -21,8 +21,19 @@
 #![no_std]
 #![warn(unsafe_op_in_unsafe_fn)]
 
+#[cfg(feature = "allow_registry")]
+pub mod allow_registry;
+#[cfg(feature = "energy")]
+pub mod energy;
+mod panic_hook;
+#[cfg(target_arch = "arm")]
+pub mod panic_registers;
+pub mod persistent;
 pub mod startup;
 
+pub use panic_hook::{run_panic_hook, set_panic_hook};
+pub use startup::STACK_USAGE;
+
 /// TockSyscalls implements `libtock_platform::Syscalls`.
 pub struct TockSyscalls;
 
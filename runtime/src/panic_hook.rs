@@ -0,0 +1,33 @@
+//! Support for application-provided panic hooks.
+
+use core::cell::Cell;
+use core::panic::PanicInfo;
+
+// Holds the hook registered by set_panic_hook.
+struct HookCell(Cell<Option<fn(&PanicInfo)>>);
+
+// Safety: Tock processes are single-threaded, and the hook is only accessed
+// by set_panic_hook and run_panic_hook, neither of which yields. Therefore
+// there is never concurrent access to the Cell.
+unsafe impl Sync for HookCell {}
+
+static HOOK: HookCell = HookCell(Cell::new(None));
+
+/// Registers a function that the panic handler calls before the process exits.
+/// This can be used to put the system into a safe state (e.g. stop the radio
+/// or flush a log to flash) when the app panics. Replaces the previously
+/// registered hook, if any.
+///
+/// The hook runs at most once: if the hook itself panics, the panic handler
+/// proceeds directly to exiting.
+pub fn set_panic_hook(hook: fn(&PanicInfo)) {
+    HOOK.0.set(Some(hook));
+}
+
+/// Runs the hook registered with `set_panic_hook`, if there is one. This is
+/// called by the panic handlers; apps do not need to call it.
+pub fn run_panic_hook(info: &PanicInfo) {
+    if let Some(hook) = HOOK.0.take() {
+        hook(info);
+    }
+}
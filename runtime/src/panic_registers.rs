@@ -0,0 +1,86 @@
+//! Capture of processor state in the panic path, for post-mortem debugging.
+//!
+//! The panic handlers print the captured values so that panics on deployed
+//! devices can be symbolicated later (e.g. with `addr2line`) using the ELF
+//! file of the process binary.
+
+use crate::startup::MemoryLayout;
+
+/// The number of words at the top of the stack that are captured.
+pub const STACK_WORDS: usize = 8;
+
+/// Processor registers and stack contents captured by `capture`.
+#[derive(Clone, Copy, Debug)]
+pub struct PanicRegisters {
+    pub pc: u32,
+    pub lr: u32,
+    pub sp: u32,
+    /// The words at the top of the stack, starting at `sp`. These typically
+    /// contain saved registers and return addresses of the panicking code.
+    /// Only the first `stack_len` words are valid.
+    pub stack: [u32; STACK_WORDS],
+    pub stack_len: usize,
+}
+
+impl PanicRegisters {
+    /// Captures the registers at the point this is called. Because this is
+    /// inlined, `lr` is the return address of the calling function (usually
+    /// the panic handler's caller).
+    #[inline(always)]
+    pub fn capture() -> PanicRegisters {
+        let pc: u32;
+        let lr: u32;
+        let sp: u32;
+        // Safety: Copying pc, lr, and sp into other registers has no side
+        // effects.
+        unsafe {
+            core::arch::asm!(
+                "mov {pc}, pc",
+                "mov {lr}, lr",
+                "mov {sp}, sp",
+                pc = out(reg) pc,
+                lr = out(reg) lr,
+                sp = out(reg) sp,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+
+        let stack_top = MemoryLayout::get().stack_top as u32;
+        let stack_len = (stack_top.saturating_sub(sp) / 4).min(STACK_WORDS as u32) as usize;
+        let mut stack = [0; STACK_WORDS];
+        for (i, word) in stack.iter_mut().take(stack_len).enumerate() {
+            // Safety: The words between sp and the top of the stack are part
+            // of the stack, which is valid, aligned memory. Volatile reads
+            // keep the compiler from making assumptions about the values of
+            // other functions' stack frames.
+            *word = unsafe { core::ptr::read_volatile((sp as *const u32).add(i)) };
+        }
+
+        PanicRegisters {
+            pc,
+            lr,
+            sp,
+            stack,
+            stack_len,
+        }
+    }
+
+    /// The valid words at the top of the stack.
+    pub fn stack(&self) -> &[u32] {
+        &self.stack[..self.stack_len]
+    }
+}
+
+impl core::fmt::Display for PanicRegisters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "pc: {:#010x} lr: {:#010x} sp: {:#010x}\nstack:",
+            self.pc, self.lr, self.sp
+        )?;
+        for word in self.stack() {
+            write!(f, " {:#010x}", word)?;
+        }
+        Ok(())
+    }
+}
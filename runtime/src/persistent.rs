@@ -0,0 +1,108 @@
+//! Storage for data that survives a process restart.
+//!
+//! Statics placed in the `.noinit` linker section are neither copied from
+//! flash nor zeroed when the process starts, so they keep their value when the
+//! process restarts (e.g. after `exit_restart`) or the board is soft-reset.
+//! This is useful for crash counters and for reporting the reason for the last
+//! restart.
+//!
+//! After the process is loaded for the first time (or the board loses power),
+//! `.noinit` contains garbage. [`PersistentCell`] stores a magic number and a
+//! checksum along with its value, so it can tell whether its value is valid.
+//!
+//! # Example
+//! ```ignore
+//! use libtock_runtime::persistent::PersistentCell;
+//!
+//! #[link_section = ".noinit"]
+//! static CRASH_COUNT: PersistentCell<1> = PersistentCell::new();
+//!
+//! fn main() {
+//!     let [crashes] = CRASH_COUNT.get().unwrap_or([0]);
+//!     CRASH_COUNT.set([crashes + 1]);
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::ptr::{read_volatile, write_volatile};
+
+/// `N` words of data that keep their value across process restarts. Must be
+/// placed in the `.noinit` section (see the module documentation).
+pub struct PersistentCell<const N: usize> {
+    magic: UnsafeCell<MaybeUninit<u32>>,
+    checksum: UnsafeCell<MaybeUninit<u32>>,
+    data: UnsafeCell<MaybeUninit<[u32; N]>>,
+}
+
+// Safety: Tock processes are single-threaded, and PersistentCell's methods do
+// not yield, so there is never concurrent access to its contents.
+unsafe impl<const N: usize> Sync for PersistentCell<N> {}
+
+impl<const N: usize> PersistentCell<N> {
+    pub const fn new() -> Self {
+        Self {
+            magic: UnsafeCell::new(MaybeUninit::uninit()),
+            checksum: UnsafeCell::new(MaybeUninit::uninit()),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns the stored value, or `None` if no valid value is stored.
+    pub fn get(&self) -> Option<[u32; N]> {
+        // Safety: The pointers are valid and aligned. The memory may not have
+        // been written since the process was loaded, which is why we use
+        // volatile reads: the compiler cannot assume anything about the
+        // values they return. Every bit pattern is a valid u32.
+        let (magic, checksum, data) = unsafe {
+            (
+                read_volatile(self.magic.get() as *const u32),
+                read_volatile(self.checksum.get() as *const u32),
+                read_volatile(self.data.get() as *const [u32; N]),
+            )
+        };
+        if magic != MAGIC || checksum != compute_checksum(&data) {
+            return None;
+        }
+        Some(data)
+    }
+
+    /// Stores `data`, which will be returned by `get` after a restart.
+    pub fn set(&self, data: [u32; N]) {
+        // Safety: The pointers are valid and aligned, and see the Sync
+        // implementation for why there is no concurrent access.
+        unsafe {
+            write_volatile(self.magic.get() as *mut u32, 0);
+            write_volatile(self.data.get() as *mut [u32; N], data);
+            write_volatile(self.checksum.get() as *mut u32, compute_checksum(&data));
+            write_volatile(self.magic.get() as *mut u32, MAGIC);
+        }
+    }
+
+    /// Invalidates the stored value, so that `get` returns `None`.
+    pub fn clear(&self) {
+        // Safety: The pointer is valid and aligned, and see the Sync
+        // implementation for why there is no concurrent access.
+        unsafe { write_volatile(self.magic.get() as *mut u32, 0) }
+    }
+}
+
+impl<const N: usize> Default for PersistentCell<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const MAGIC: u32 = 0x5045_5253; // "PERS"
+
+// FNV-1a over the data words, which is enough to reject the random contents
+// RAM has at power-on.
+fn compute_checksum(data: &[u32]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, &word| {
+        (hash ^ word).wrapping_mul(0x0100_0193)
+    })
+}
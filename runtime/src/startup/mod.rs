@@ -1,6 +1,7 @@
 //! Runtime components related to process startup.
 
 use crate::TockSyscalls;
+use libtock_low_level_debug::LowLevelDebug;
 use libtock_platform::{Syscalls, Termination};
 
 // Include the correct `start` symbol (the program entry point) for the
@@ -15,12 +16,23 @@ core::arch::global_asm!(include_str!("asm_riscv32.s"));
 /// signature `FnOnce() -> T`, where T is some concrete type that implements
 /// `libtock_platform::Termination`.
 ///
+/// `main` may return `Result<(), E>` for any `E` that implements
+/// `libtock_platform::ExitError`, such as `ErrorCode`. If it returns an error,
+/// the runtime prints the error's exit code using the low-level debug driver,
+/// then terminates or restarts the process as the error requests.
+///
 /// # Example
 /// ```
 /// libtock_runtime::set_main!{main};
 ///
 /// fn main() -> () { /* Omitted */ }
 /// ```
+///
+/// ```
+/// libtock_runtime::set_main!{main};
+///
+/// fn main() -> Result<(), ErrorCode> { /* Omitted */ }
+/// ```
 // set_main! generates a function called `libtock_unsafe_main`, which is called
 // by `rust_start`. The function has `unsafe` in its name because implementing
 // it is `unsafe` (it *must* have the signature `libtock_unsafe_main() -> !`),
@@ -38,27 +50,89 @@ macro_rules! set_main {
     }
 }
 
+/// `init_hook!` registers a function that the runtime calls before `main`.
+/// This lets library crates initialize themselves (e.g. install a logging
+/// backend) without requiring the process binary to call them. The function
+/// must have the signature `fn()`.
+///
+/// Hooks run in an unspecified order. A hook is only run if the crate that
+/// registers it is linked into the process binary, so a crate that registers
+/// a hook but is otherwise unused must be linked in using `extern crate`.
+///
+/// # Example
+/// ```
+/// libtock_runtime::init_hook!{init}
+///
+/// fn init() { /* Omitted */ }
+/// ```
+// init_hook! places a pointer to the function in the .libtock_init_hooks
+// section. The linker script collects those pointers into an array, which
+// rust_start iterates through.
+#[macro_export]
+macro_rules! init_hook {
+    {$name:path} => {
+        const _: () = {
+            #[used]
+            #[link_section = ".libtock_init_hooks"]
+            static INIT_HOOK: fn() = $name;
+        };
+    }
+}
+
 /// Executables must specify their stack size by using the `stack_size!` macro.
 /// It takes a single argument, the desired stack size in bytes. Example:
 /// ```
 /// stack_size!{0x400}
 /// ```
+///
+/// Alternatively, the size can be derived from the `STACK_USAGE` hints of the
+/// runtime and of the APIs the executable uses, plus its own usage and a
+/// safety margin in percent (see `libtock_platform::hinted_stack_size`):
+/// ```
+/// stack_size!{hints: [runtime::STACK_USAGE, console::STACK_USAGE, 0x80], margin: 25}
+/// ```
+/// or an explicit size can be checked against them at compile time:
+/// ```
+/// stack_size!{0x400, covers: [runtime::STACK_USAGE, console::STACK_USAGE], margin: 25}
+/// ```
 // stack_size works by putting a symbol equal to the size of the stack in the
 // .stack_buffer section. The linker script uses the .stack_buffer section to
 // size the stack. flash.sh looks for the symbol by name (hence #[no_mangle]) to
 // determine the size of the stack to pass to elf2tab.
 #[macro_export]
 macro_rules! stack_size {
+    {hints: [$($hint:expr),* $(,)?], margin: $margin:expr $(,)?} => {
+        $crate::stack_size! {$crate::startup::hinted_stack_size(&[$($hint),*], $margin)}
+    };
+    {$size:expr, covers: [$($hint:expr),* $(,)?], margin: $margin:expr $(,)?} => {
+        const _: () = assert!(
+            $size >= $crate::startup::hinted_stack_size(&[$($hint),*], $margin),
+            "the stack size does not cover the stack usage hints and margin",
+        );
+        $crate::stack_size! {$size}
+    };
     {$size:expr} => {
         #[no_mangle]
         #[link_section = ".stack_buffer"]
         pub static mut STACK_MEMORY: [u8; $size] = [0; $size];
-    }
+    };
 }
 
+/// This is public for the sake of making `stack_size!` usable in other crates.
+/// It doesn't have another function.
+#[doc(hidden)]
+pub use libtock_platform::hinted_stack_size;
+
+/// The stack usage of the runtime, which starts the process, runs the init
+/// hooks and `main`, and handles panics, for `stack_size!`.
+pub const STACK_USAGE: usize = 0x40;
+
 /// This is public for the sake of making `set_main!` usable in other crates.
 /// It doesn't have another function.
 pub fn handle_main_return<T: Termination>(result: T) -> ! {
+    if let Some(exit_code) = result.error_code() {
+        LowLevelDebug::<TockSyscalls>::print_1(exit_code);
+    }
     Termination::complete::<TockSyscalls>(result)
 }
 
@@ -93,6 +167,8 @@ extern "C" fn rust_start() -> ! {
         let _ = TockSyscalls::memop_debug_heap_start(rt_header.initial_break as *const u8);
     }
 
+    run_init_hooks();
+
     // Safety: libtock_unsafe_main is defined by the set_main! macro, and its
     // signature matches the signature in the `extern` block in this function.
     unsafe {
@@ -100,6 +176,30 @@ extern "C" fn rust_start() -> ! {
     }
 }
 
+// Calls the functions registered with init_hook!.
+fn run_init_hooks() {
+    extern "Rust" {
+        static libtock_init_hooks_start: fn();
+        static libtock_init_hooks_end: fn();
+    }
+    // Safety: The linker script defines these symbols at the start and end of
+    // an array of function pointers, each placed there by init_hook!. We only
+    // take the addresses of the symbols.
+    let (mut hook, end) = unsafe {
+        (
+            core::ptr::addr_of!(libtock_init_hooks_start),
+            core::ptr::addr_of!(libtock_init_hooks_end),
+        )
+    };
+    while hook < end {
+        // Safety: hook is within the array of function pointers.
+        unsafe {
+            (*hook)();
+            hook = hook.add(1);
+        }
+    }
+}
+
 /// Function which an allocator can call to learn the initial
 /// start of the heap region
 pub fn get_heap_start() -> *mut () {
@@ -110,3 +210,54 @@ pub fn get_heap_start() -> *mut () {
     // and not modified anywhere
     unsafe { rt_header.initial_break }
 }
+
+/// The memory layout of the process, as set up by the linker script and the
+/// runtime. The stack is placed at the start of the process' RAM, followed by
+/// `.noinit`, `.data`, `.bss`, and then the heap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryLayout {
+    /// The lowest address of the stack. The stack overflows when it grows
+    /// below this address.
+    pub stack_bottom: *const u8,
+    /// The address the stack pointer is initialized to.
+    pub stack_top: *const u8,
+    pub data_start: *const u8,
+    pub data_size: usize,
+    pub bss_start: *const u8,
+    pub bss_size: usize,
+    /// The initial program break, which is where the heap starts.
+    pub heap_start: *const u8,
+}
+
+impl MemoryLayout {
+    /// Returns the layout of this process.
+    pub fn get() -> MemoryLayout {
+        extern "Rust" {
+            static rt_header: RtHeader;
+        }
+        extern "C" {
+            // Defined by the linker script at the start of the stack.
+            static _sram_origin: u8;
+        }
+        // Safety: rt_header is defined in the linker script, valid for its
+        // type, and not modified anywhere. We only take the address of
+        // _sram_origin, without reading it.
+        unsafe {
+            MemoryLayout {
+                stack_bottom: core::ptr::addr_of!(_sram_origin),
+                stack_top: rt_header.stack_top as *const u8,
+                data_start: rt_header.data_ram_start,
+                data_size: rt_header.data_size,
+                bss_start: rt_header.bss_start,
+                bss_size: rt_header.bss_size,
+                heap_start: rt_header.initial_break as *const u8,
+            }
+        }
+    }
+
+    /// The size of the stack, as configured by `stack_size!` (possibly
+    /// rounded up for alignment).
+    pub fn stack_size(&self) -> usize {
+        self.stack_top as usize - self.stack_bottom as usize
+    }
+}
@@ -3,6 +3,8 @@ use libtock_platform::{syscall_class, RawSyscalls, Register};
 
 unsafe impl RawSyscalls for crate::TockSyscalls {
     unsafe fn yield1([Register(r0)]: [Register; 1]) {
+        #[cfg(feature = "energy")]
+        crate::energy::before_yield(Register(r0));
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield1
         // the use of `clobber_abi` allows us this to run on both Thumb-1 and Thumb-2
@@ -20,6 +22,8 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
                  clobber_abi("C"), // a2, a3, a4, ip (r12), lr (r14)
             );
         }
+        #[cfg(feature = "energy")]
+        crate::energy::after_yield(Register(r0));
     }
 
     unsafe fn yield2([Register(r0), Register(r1)]: [Register; 2]) {
@@ -90,6 +94,8 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     unsafe fn syscall4<const CLASS: usize>(
         [Register(mut r0), Register(mut r1), Register(mut r2), Register(mut r3)]: [Register; 4],
     ) -> [Register; 4] {
+        #[cfg(any(feature = "allow_registry", feature = "energy"))]
+        let args = [Register(r0), Register(r1), Register(r2), Register(r3)];
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::syscall4
         unsafe {
@@ -130,6 +136,10 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
                 _ => unreachable!(),
             }
         }
+        #[cfg(feature = "allow_registry")]
+        crate::allow_registry::record::<CLASS>(args, Register(r0));
+        #[cfg(feature = "energy")]
+        crate::energy::record::<CLASS>(args, Register(r0));
         [Register(r0), Register(r1), Register(r2), Register(r3)]
     }
 }
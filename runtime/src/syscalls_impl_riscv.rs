@@ -6,6 +6,8 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     // floating-point registers, as it does not mark them clobbered.
     #[cfg(not(any(target_feature = "d", target_feature = "f")))]
     unsafe fn yield1([Register(r0)]: [Register; 1]) {
+        #[cfg(feature = "energy")]
+        crate::energy::before_yield(Register(r0));
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::yield1
         unsafe {
@@ -33,6 +35,8 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
                  lateout("x31") _, // t6
             );
         }
+        #[cfg(feature = "energy")]
+        crate::energy::after_yield(Register(r0));
     }
 
     // This yield implementation is currently limited to RISC-V versions without
@@ -102,6 +106,8 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
     unsafe fn syscall4<const CLASS: usize>(
         [Register(mut r0), Register(mut r1), Register(mut r2), Register(mut r3)]: [Register; 4],
     ) -> [Register; 4] {
+        #[cfg(any(feature = "allow_registry", feature = "energy"))]
+        let args = [Register(r0), Register(r1), Register(r2), Register(r3)];
         // Safety: This matches the invariants required by the documentation on
         // RawSyscalls::syscall4
         unsafe {
@@ -114,6 +120,10 @@ unsafe impl RawSyscalls for crate::TockSyscalls {
                  options(preserves_flags, nostack),
             );
         }
+        #[cfg(feature = "allow_registry")]
+        crate::allow_registry::record::<CLASS>(args, Register(r0));
+        #[cfg(feature = "energy")]
+        crate::energy::record::<CLASS>(args, Register(r0));
         [Register(r0), Register(r1), Register(r2), Register(r3)]
     }
 }
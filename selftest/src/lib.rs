@@ -0,0 +1,78 @@
+//! `libtock_selftest` runs tests on a Tock board, for hardware-in-the-loop
+//! testing of the driver APIs.
+//!
+//! Results are written to the console in the
+//! [Test Anything Protocol](https://testanything.org/) format, so they can be
+//! parsed by a test runner on the host:
+//! ```text
+//! 1..2
+//! ok 1 - temperature
+//! not ok 2 - radio # BUSY
+//! ```
+//! After the last test, the process exits with the number of failed tests as
+//! its completion code.
+//!
+//! # Example
+//! ```ignore
+//! use libtock::selftest::{SelfTest, Test};
+//!
+//! fn main() -> ! {
+//!     SelfTest::run(&[
+//!         Test::new("temperature", || Temperature::read_temperature_sync().map(|_| ())),
+//!         Test::new("radio", test_radio),
+//!     ])
+//! }
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+use core::fmt::Write;
+use libtock_console::Console;
+use libtock_platform::{ErrorCode, Syscalls};
+
+/// A test function, along with the name it is reported under.
+#[derive(Clone, Copy)]
+pub struct Test {
+    pub name: &'static str,
+    pub run: fn() -> Result<(), ErrorCode>,
+}
+
+impl Test {
+    pub const fn new(name: &'static str, run: fn() -> Result<(), ErrorCode>) -> Test {
+        Test { name, run }
+    }
+}
+
+pub struct SelfTest<S: Syscalls>(S);
+
+impl<S: Syscalls> SelfTest<S> {
+    /// Runs `tests` in order, reports the results, and exits. The completion
+    /// code is the number of tests that failed.
+    pub fn run(tests: &[Test]) -> ! {
+        let failures = Self::run_tests(tests);
+        S::exit_terminate(failures)
+    }
+
+    /// Runs `tests` in order and reports the results, without exiting. Returns
+    /// the number of tests that failed.
+    pub fn run_tests(tests: &[Test]) -> u32 {
+        let mut writer = Console::<S>::writer();
+        // Failing to report a result is not a reason to stop testing, so write
+        // errors are ignored.
+        let _ = writeln!(writer, "1..{}", tests.len());
+        let mut failures = 0;
+        for (number, test) in (1..).zip(tests) {
+            let _ = match (test.run)() {
+                Ok(()) => writeln!(writer, "ok {} - {}", number, test.name),
+                Err(error) => {
+                    failures += 1;
+                    writeln!(writer, "not ok {} - {} # {:?}", number, test.name, error)
+                }
+            };
+        }
+        failures
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,47 @@
+use super::*;
+use libtock_unittest::fake;
+
+type SelfTest = super::SelfTest<fake::Syscalls>;
+
+#[test]
+fn no_tests() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+
+    assert_eq!(SelfTest::run_tests(&[]), 0);
+    assert_eq!(console.take_bytes(), b"1..0\n");
+}
+
+#[test]
+fn run_tests() {
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+
+    let tests = [
+        Test::new("passes", || Ok(())),
+        Test::new("fails", || Err(ErrorCode::Busy)),
+        Test::new("also passes", || Ok(())),
+    ];
+    assert_eq!(SelfTest::run_tests(&tests), 1);
+    assert_eq!(
+        console.take_bytes(),
+        b"1..3\nok 1 - passes\nnot ok 2 - fails # BUSY\nok 3 - also passes\n"
+    );
+}
+
+#[cfg(not(miri))]
+#[test]
+fn run_exits() {
+    let exit = libtock_unittest::exit_test("tests::run_exits", || {
+        let kernel = fake::Kernel::new();
+        let console = fake::Console::new();
+        kernel.add_driver(&console);
+        SelfTest::run(&[
+            Test::new("a", || Err(ErrorCode::Fail)),
+            Test::new("b", || Err(ErrorCode::NoDevice)),
+        ]);
+    });
+    assert_eq!(exit, libtock_unittest::ExitCall::Terminate(2));
+}
@@ -0,0 +1,177 @@
+//! Detection of the drivers available on the running board.
+//!
+//! Boards differ in which capsules they include. [`Drivers::probe`] checks
+//! which of the drivers libtock has APIs for are present, so a single process
+//! binary can adapt to the board it runs on.
+
+use crate::{
+    adc::Adc, air_quality::AirQuality, alarm::Alarm, ambient_light::AmbientLight, battery::Battery,
+    buttons::Buttons, buzzer::Buzzer, chip_configuration::ChipConfiguration, console::Console,
+    ctap_hid::CtapHid, digest::Hmac, digest::Sha, gpio::Gpio, humidity::Humidity,
+    i2c_master::I2CMaster, i2c_master_slave::I2CMasterSlave, ieee802154::Ieee802154,
+    ipc::IpcClient, key_value::KeyValue, leds::Leds, low_level_debug::LowLevelDebug, nfc::Nfc,
+    ninedof::NineDof, pressure::Pressure, proximity::Proximity, reboot::Reboot, rng::Rng,
+    screen::Screen, sound_pressure::SoundPressure, spi_controller::SpiController,
+    supply_monitor::SupplyMonitor, temperature::Temperature, watchdog::Watchdog,
+};
+
+/// A driver that libtock has an API for.
+///
+/// The APIs that drive a peripheral through a driver the app chooses have no
+/// driver of their own, and are not listed: `Gnss`, which reads a receiver on
+/// a console-compatible UART, and `UsbBulk`, whose driver number the app sets
+/// for its board. IPC is listed once, as clients and services use the same
+/// driver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Driver {
+    Adc,
+    AirQuality,
+    Alarm,
+    AmbientLight,
+    Battery,
+    Buttons,
+    Buzzer,
+    ChipConfiguration,
+    Console,
+    CtapHid,
+    Gpio,
+    Hmac,
+    Humidity,
+    I2CMaster,
+    I2CMasterSlave,
+    Ieee802154,
+    Ipc,
+    KeyValue,
+    Leds,
+    LowLevelDebug,
+    Nfc,
+    NineDof,
+    Pressure,
+    Proximity,
+    Reboot,
+    Rng,
+    Screen,
+    Sha,
+    SoundPressure,
+    SpiController,
+    SupplyMonitor,
+    Temperature,
+    Watchdog,
+}
+
+impl Driver {
+    /// Every driver, in the order `Drivers::iter` returns them.
+    pub const ALL: [Driver; 33] = [
+        Driver::Adc,
+        Driver::AirQuality,
+        Driver::Alarm,
+        Driver::AmbientLight,
+        Driver::Battery,
+        Driver::Buttons,
+        Driver::Buzzer,
+        Driver::ChipConfiguration,
+        Driver::Console,
+        Driver::CtapHid,
+        Driver::Gpio,
+        Driver::Hmac,
+        Driver::Humidity,
+        Driver::I2CMaster,
+        Driver::I2CMasterSlave,
+        Driver::Ieee802154,
+        Driver::Ipc,
+        Driver::KeyValue,
+        Driver::Leds,
+        Driver::LowLevelDebug,
+        Driver::Nfc,
+        Driver::NineDof,
+        Driver::Pressure,
+        Driver::Proximity,
+        Driver::Reboot,
+        Driver::Rng,
+        Driver::Screen,
+        Driver::Sha,
+        Driver::SoundPressure,
+        Driver::SpiController,
+        Driver::SupplyMonitor,
+        Driver::Temperature,
+        Driver::Watchdog,
+    ];
+
+    /// Asks the kernel whether this driver is present.
+    pub fn exists(self) -> bool {
+        match self {
+            Driver::Adc => Adc::exists().is_ok(),
+            Driver::AirQuality => AirQuality::exists().is_ok(),
+            Driver::Alarm => Alarm::exists().is_ok(),
+            Driver::AmbientLight => AmbientLight::exists().is_ok(),
+            Driver::Battery => Battery::exists().is_ok(),
+            Driver::Buttons => Buttons::count().is_ok(),
+            Driver::Buzzer => Buzzer::exists().is_ok(),
+            Driver::ChipConfiguration => ChipConfiguration::exists().is_ok(),
+            Driver::Console => Console::exists(),
+            Driver::CtapHid => CtapHid::exists().is_ok(),
+            Driver::Gpio => Gpio::exists().is_ok(),
+            Driver::Hmac => Hmac::exists().is_ok(),
+            Driver::Humidity => Humidity::exists().is_ok(),
+            Driver::I2CMaster => I2CMaster::exists().is_ok(),
+            Driver::I2CMasterSlave => I2CMasterSlave::exists().is_ok(),
+            Driver::Ieee802154 => Ieee802154::exists(),
+            Driver::Ipc => IpcClient::exists().is_ok(),
+            Driver::KeyValue => KeyValue::exists(),
+            Driver::Leds => Leds::count().is_ok(),
+            Driver::LowLevelDebug => LowLevelDebug::exists(),
+            Driver::Nfc => Nfc::exists().is_ok(),
+            Driver::NineDof => NineDof::exists().is_ok(),
+            Driver::Pressure => Pressure::exists().is_ok(),
+            Driver::Proximity => Proximity::exists().is_ok(),
+            Driver::Reboot => Reboot::exists().is_ok(),
+            Driver::Rng => Rng::exists().is_ok(),
+            Driver::Screen => Screen::exists().is_ok(),
+            Driver::Sha => Sha::exists().is_ok(),
+            Driver::SoundPressure => SoundPressure::exists().is_ok(),
+            Driver::SpiController => SpiController::exists().is_ok(),
+            Driver::SupplyMonitor => SupplyMonitor::exists().is_ok(),
+            Driver::Temperature => Temperature::exists().is_ok(),
+            Driver::Watchdog => Watchdog::exists().is_ok(),
+        }
+    }
+}
+
+/// A set of drivers, stored as a bitset indexed by `Driver`'s discriminant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Drivers(u64);
+
+impl Drivers {
+    /// Returns the set of drivers that are present on this board. This makes
+    /// one Command system call per driver.
+    pub fn probe() -> Drivers {
+        let mut drivers = Drivers::default();
+        for driver in Driver::ALL {
+            if driver.exists() {
+                drivers.insert(driver);
+            }
+        }
+        drivers
+    }
+
+    pub fn contains(self, driver: Driver) -> bool {
+        self.0 & (1 << driver as u32) != 0
+    }
+
+    pub fn insert(&mut self, driver: Driver) {
+        self.0 |= 1 << driver as u32;
+    }
+
+    /// Returns the set as a bitmask, with bit `n` set if `Driver::ALL[n]` is
+    /// in the set. Useful for reporting the board's capabilities compactly.
+    pub fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Iterates through the drivers in this set.
+    pub fn iter(self) -> impl Iterator<Item = Driver> {
+        Driver::ALL
+            .into_iter()
+            .filter(move |&driver| self.contains(driver))
+    }
+}
@@ -1,113 +1,370 @@
 #![forbid(unsafe_code)]
 #![no_std]
 
-#[cfg(debug_assertions)]
+#[cfg(all(debug_assertions, not(feature = "host")))]
 extern crate libtock_debug_panic;
-#[cfg(not(debug_assertions))]
+#[cfg(all(not(debug_assertions), not(feature = "host")))]
 extern crate libtock_small_panic;
 
+pub use libtock_fmt as fmt;
+pub use libtock_future as future;
 pub use libtock_platform as platform;
+
+#[cfg(feature = "host")]
+pub use libtock_host_runtime as runtime;
+#[cfg(not(feature = "host"))]
 pub use libtock_runtime as runtime;
 
+#[cfg(feature = "adc")]
 pub mod adc {
     use libtock_adc as adc;
     pub type Adc = adc::Adc<super::runtime::TockSyscalls>;
     pub use adc::ADCListener;
 }
 
+#[cfg(feature = "air_quality")]
 pub mod air_quality {
     use libtock_air_quality as air_quality;
     pub type AirQuality = air_quality::AirQuality<super::runtime::TockSyscalls>;
     pub use air_quality::AirQualityListener;
 }
 
+#[cfg(feature = "alarm")]
 pub mod alarm {
     use libtock_alarm as alarm;
     pub type Alarm = alarm::Alarm<super::runtime::TockSyscalls>;
-    pub use alarm::{Convert, Hz, Milliseconds, Ticks};
+    pub type LivenessGuard = alarm::LivenessGuard<super::runtime::TockSyscalls>;
+    pub type RetryConfig<const RETRIES: u32 = 3, const BACKOFF_MS: u32 = 1> =
+        alarm::RetryConfig<super::runtime::TockSyscalls, RETRIES, BACKOFF_MS>;
+    pub use alarm::{Convert, Hz, Milliseconds, Ticks, STACK_USAGE};
+}
+#[cfg(feature = "alloc")]
+pub mod alloc {
+    use libtock_alloc as alloc;
+    pub type TockAllocator = alloc::TockAllocator<super::runtime::TockSyscalls>;
+    pub use alloc::HeapStats;
+
+    #[global_allocator]
+    static ALLOCATOR: TockAllocator = TockAllocator::new();
+
+    /// Returns statistics about the process' heap.
+    pub fn heap_stats() -> HeapStats {
+        ALLOCATOR.stats()
+    }
 }
+#[cfg(all(feature = "allow_registry", not(feature = "host")))]
+pub mod allow_registry {
+    use core::fmt::Write;
+    pub use libtock_runtime::allow_registry::*;
+
+    /// Writes the buffers and upcalls currently shared with the kernel to the
+    /// console, one per line.
+    pub fn dump_to_console() {
+        let _ = write!(super::console::Console::writer(), "{}", snapshot());
+    }
+}
+#[cfg(feature = "ambient_light")]
 pub mod ambient_light {
     use libtock_ambient_light as ambient_light;
     pub type AmbientLight = ambient_light::AmbientLight<super::runtime::TockSyscalls>;
     pub use ambient_light::IntensityListener;
 }
+#[cfg(feature = "battery")]
+pub mod battery {
+    use libtock_battery as battery;
+    pub type Battery = battery::Battery<super::runtime::TockSyscalls>;
+    pub use battery::{ChargingState, LowBatteryListener};
+}
+#[cfg(feature = "bench")]
+pub mod bench {
+    use libtock_bench as bench;
+    pub type Bench = bench::Bench<super::runtime::TockSyscalls>;
+    pub use bench::{Benchmark, Stats};
+}
+#[cfg(feature = "bridge")]
+pub mod bridge {
+    use libtock_bridge as bridge;
+    pub type Bridge<'buf, const N: usize> = bridge::Bridge<'buf, super::runtime::TockSyscalls, N>;
+}
+#[cfg(feature = "buttons")]
 pub mod buttons {
     use libtock_buttons as buttons;
     pub type Buttons = buttons::Buttons<super::runtime::TockSyscalls>;
-    pub use buttons::{ButtonListener, ButtonState};
+    pub type Gestures = buttons::Gestures<super::runtime::TockSyscalls>;
+    pub use buttons::{ButtonListener, ButtonState, Gesture, GestureConfig};
 }
+#[cfg(feature = "buzzer")]
 pub mod buzzer {
     use libtock_buzzer as buzzer;
     pub type Buzzer = buzzer::Buzzer<super::runtime::TockSyscalls>;
     pub use buzzer::Note;
 }
+#[cfg(feature = "chip_configuration")]
+pub mod chip_configuration {
+    use libtock_chip_configuration as chip_configuration;
+    pub type ChipConfiguration =
+        chip_configuration::ChipConfiguration<super::runtime::TockSyscalls>;
+}
+#[cfg(feature = "console")]
 pub mod console {
     use libtock_console as console;
+    pub type AsyncWriter<'buf> = console::AsyncWriter<'buf, super::runtime::TockSyscalls>;
     pub type Console = console::Console<super::runtime::TockSyscalls>;
-    pub use console::ConsoleWriter;
+    pub use console::{ConsoleWriter, WriteFuture, STACK_USAGE};
+    pub type Drain<'handle, 'share, 'buf> =
+        console::Drain<'handle, 'share, 'buf, super::runtime::TockSyscalls>;
+    pub type LineReader<const N: usize> = console::LineReader<super::runtime::TockSyscalls, N>;
+    pub type ReadLineFuture<'handle, 'share, const N: usize> =
+        console::ReadLineFuture<'handle, 'share, super::runtime::TockSyscalls, N>;
+    #[cfg(feature = "liveness")]
+    pub type Xmodem<'g> = console::xmodem::Xmodem<'g, super::runtime::TockSyscalls>;
+    #[cfg(feature = "liveness")]
+    pub use console::xmodem::Sink;
+    #[cfg(all(feature = "liveness", feature = "key_value"))]
+    pub type KeyValueSink<'p> = console::xmodem::KeyValueSink<'p, super::runtime::TockSyscalls>;
+    #[cfg(feature = "liveness")]
+    pub type AtModem<const N: usize> = console::atmodem::AtModem<super::runtime::TockSyscalls, N>;
+}
+#[cfg(feature = "ctap_hid")]
+pub mod ctap_hid {
+    use libtock_ctap_hid as ctap_hid;
+    pub type CtapHid = ctap_hid::CtapHid<super::runtime::TockSyscalls>;
+    pub use ctap_hid::{framing, Report, REPORT_LEN};
+}
+#[cfg(feature = "digest")]
+pub mod digest {
+    use libtock_digest as digest;
+    pub type Hmac = digest::Hmac<super::runtime::TockSyscalls>;
+    pub type Sha = digest::Sha<super::runtime::TockSyscalls>;
+    pub use digest::ShaAlgorithm;
+    pub mod hasher {
+        pub use hasher::{Algorithm, Digest, Mac, Sha256, Sha384, Sha512, MAX_KEY_LEN};
+        use libtock_digest::hasher;
+        pub type HmacSha256 = hasher::HmacSha256<crate::runtime::TockSyscalls>;
+        pub type HmacSha384 = hasher::HmacSha384<crate::runtime::TockSyscalls>;
+        pub type HmacSha512 = hasher::HmacSha512<crate::runtime::TockSyscalls>;
+        pub type Sha256Hasher = hasher::Sha256Hasher<crate::runtime::TockSyscalls>;
+        pub type Sha384Hasher = hasher::Sha384Hasher<crate::runtime::TockSyscalls>;
+        pub type Sha512Hasher = hasher::Sha512Hasher<crate::runtime::TockSyscalls>;
+    }
 }
+#[cfg(feature = "all_drivers")]
+pub mod drivers;
+#[cfg(all(feature = "energy", not(feature = "host")))]
+pub mod energy {
+    pub use libtock_runtime::energy::*;
+}
+#[cfg(feature = "gnss")]
+pub mod gnss {
+    use libtock_gnss as gnss;
+    pub type Gnss<const DRIVER_NUM: u32> = gnss::Gnss<super::runtime::TockSyscalls, DRIVER_NUM>;
+    pub use gnss::{nmea, Fix, Fixes};
+}
+#[cfg(feature = "gpio")]
 pub mod gpio {
     use libtock_gpio as gpio;
     pub type Gpio = gpio::Gpio<super::runtime::TockSyscalls>;
+    pub type RotaryEncoder = gpio::RotaryEncoder<super::runtime::TockSyscalls>;
+    pub type SoftPwm<'p> = gpio::SoftPwm<'p, super::runtime::TockSyscalls>;
     pub use gpio::{
         Error, GpioInterruptListener, GpioState, InputPin, OutputPin, PinInterruptEdge, Pull,
-        PullDown, PullNone, PullUp,
+        PullDown, PullNone, PullUp, Step,
     };
 }
+#[cfg(feature = "humidity")]
+pub mod humidity {
+    use libtock_humidity as humidity;
+    pub type Humidity = humidity::Humidity<super::runtime::TockSyscalls>;
+    pub use humidity::HumidityListener;
+}
+#[cfg(feature = "i2c_master")]
 pub mod i2c_master {
     use libtock_i2c_master as i2c_master;
     pub type I2CMaster = i2c_master::I2CMaster<super::runtime::TockSyscalls>;
 }
+#[cfg(feature = "i2c_master_slave")]
 pub mod i2c_master_slave {
     use libtock_i2c_master_slave as i2c_master_slave;
     pub type I2CMasterSlave = i2c_master_slave::I2CMasterSlave<super::runtime::TockSyscalls>;
 }
+#[cfg(feature = "ieee802154")]
 pub mod ieee802154 {
     use libtock_ieee802154 as ieee802154;
     pub type Ieee802154 = ieee802154::Ieee802154<super::runtime::TockSyscalls>;
-    pub use ieee802154::{Frame, RxOperator, RxRingBuffer};
-    pub type RxSingleBufferOperator<'buf, const N: usize> =
-        ieee802154::RxSingleBufferOperator<'buf, N, super::runtime::TockSyscalls>;
+    pub use ieee802154::{
+        arq, fragment, mesh, neighbor, role, telemetry, tx_queue, ConfigDiff, Frame, Message,
+        Priority, RadioConfig, RxOperator, RxRingBuffer, TransmitFuture, TxInfo, STACK_USAGE,
+    };
+    pub type Fragmenter = ieee802154::Fragmenter<super::runtime::TockSyscalls>;
+    pub type Mesh<const CACHE: usize = 8> = ieee802154::Mesh<super::runtime::TockSyscalls, CACHE>;
+    pub type NeighborTable<const N: usize = 8> =
+        ieee802154::NeighborTable<super::runtime::TockSyscalls, N>;
+    pub type Reassembler<const MAX_LEN: usize, const SENDERS: usize = 2> =
+        ieee802154::Reassembler<super::runtime::TockSyscalls, MAX_LEN, SENDERS>;
+    pub type ReliableLink<'buf, const N: usize, const PEERS: usize = 4> =
+        ieee802154::ReliableLink<'buf, super::runtime::TockSyscalls, N, PEERS>;
+    pub type RxSingleBufferOperator<'buf, const N: usize, F = fn(&ieee802154::Frame) -> bool> =
+        ieee802154::RxSingleBufferOperator<
+            'buf,
+            N,
+            super::runtime::TockSyscalls,
+            libtock_platform::DefaultConfig,
+            F,
+        >;
+    pub type TxQueue<const N: usize = 4> = ieee802154::TxQueue<super::runtime::TockSyscalls, N>;
 }
+#[cfg(feature = "ipc")]
+pub mod ipc {
+    use libtock_ipc as ipc;
+    pub type IpcClient = ipc::IpcClient<super::runtime::TockSyscalls>;
+    pub type IpcServer = ipc::IpcServer<super::runtime::TockSyscalls>;
+    pub type IpcRingProducer<'share, T, const N: usize> =
+        ipc::IpcRingProducer<'share, super::runtime::TockSyscalls, T, N>;
+    pub use ipc::{
+        client_upcall, IpcClientListener, IpcReply, IpcRequest, IpcRequests, IpcRingBuffer,
+        IpcRingConsumer, IpcServerListener, IpcSharedSlot,
+    };
+
+    pub mod pubsub {
+        use libtock_ipc::pubsub;
+        pub type PubSubBroker<const N: usize = 16> =
+            pubsub::PubSubBroker<crate::runtime::TockSyscalls, N>;
+        pub type PubSubClient<const BROKER: u32, const SUBSCRIBE_NUM: u32> =
+            pubsub::PubSubClient<crate::runtime::TockSyscalls, BROKER, SUBSCRIBE_NUM>;
+        pub use pubsub::{Header, HEADER_LEN};
+    }
+
+    pub mod log {
+        use libtock_ipc::log;
+        pub type LogProducer<'share, const N: usize> =
+            log::LogProducer<'share, crate::runtime::TockSyscalls, N>;
+        pub use log::{LogCollector, LogRecord, LogRing, ProducerStats, MAX_TEXT_LEN, RECORD_LEN};
+    }
+
+    #[cfg(feature = "ipc_rpc")]
+    pub mod rpc {
+        use libtock_ipc::rpc;
+        pub type RpcClient<const SERVICE: u32, const SUBSCRIBE_NUM: u32> =
+            rpc::RpcClient<crate::runtime::TockSyscalls, SERVICE, SUBSCRIBE_NUM>;
+        pub type RpcServer<'h> = rpc::RpcServer<'h, crate::runtime::TockSyscalls>;
+        pub use rpc::{RpcError, RpcHandler, RpcMethod};
+    }
+}
+#[cfg(feature = "leds")]
 pub mod leds {
     use libtock_leds as leds;
     pub type Leds = leds::Leds<super::runtime::TockSyscalls>;
 }
+#[cfg(feature = "low_level_debug")]
 pub mod low_level_debug {
     use libtock_low_level_debug as lldb;
     pub type LowLevelDebug = lldb::LowLevelDebug<super::runtime::TockSyscalls>;
-    pub use lldb::AlertCode;
+    pub use lldb::{AlertCode, STACK_USAGE};
+}
+#[cfg(feature = "nfc")]
+pub mod nfc {
+    use libtock_nfc as nfc;
+    pub type Nfc = nfc::Nfc<super::runtime::TockSyscalls>;
+    pub use nfc::{apdu, TagType};
 }
+#[cfg(feature = "ninedof")]
 pub mod ninedof {
     use libtock_ninedof as ninedof;
     pub type NineDof = ninedof::NineDof<super::runtime::TockSyscalls>;
     pub use ninedof::NineDofListener;
 }
+#[cfg(feature = "pipeline")]
+pub mod pipeline {
+    use libtock_pipeline as pipeline;
+    pub type SamplePipeline<'q, const N: usize> =
+        pipeline::SamplePipeline<'q, super::runtime::TockSyscalls, N>;
+    pub use pipeline::{decode, Encoding, FrameHeader, DISPATCH, HEADER_LEN};
+}
+#[cfg(feature = "pressure")]
+pub mod pressure {
+    use libtock_pressure as pressure;
+    pub type Pressure = pressure::Pressure<super::runtime::TockSyscalls>;
+    pub use pressure::PressureListener;
+}
+#[cfg(feature = "proximity")]
 pub mod proximity {
     use libtock_proximity as proximity;
     pub type Proximity = proximity::Proximity<super::runtime::TockSyscalls>;
 }
+#[cfg(feature = "reboot")]
+pub mod reboot {
+    use libtock_reboot as reboot;
+    pub type Reboot = reboot::Reboot<super::runtime::TockSyscalls>;
+}
+#[cfg(feature = "rng")]
 pub mod rng {
     use libtock_rng as rng;
     pub type Rng = rng::Rng<super::runtime::TockSyscalls>;
     pub use rng::RngListener;
 }
+#[cfg(feature = "screen")]
+pub mod screen {
+    use libtock_screen as screen;
+    pub type Screen = screen::Screen<super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_embedded")]
+    pub use screen::ScreenColor;
+    pub type DamageRenderer<const BUFFER_LEN: usize, const MAX_RECTS: usize = 4> =
+        screen::DamageRenderer<super::runtime::TockSyscalls, BUFFER_LEN, MAX_RECTS>;
+    pub use screen::{PixelFormat, Rect, Rotation, TextGrid};
+    pub type ScreenConsole<'b> = screen::ScreenConsole<'b, super::runtime::TockSyscalls>;
+    #[cfg(feature = "rust_embedded")]
+    pub type ScreenDrawTarget<Color, const BUFFER_LEN: usize = 1024> =
+        screen::ScreenDrawTarget<super::runtime::TockSyscalls, Color, BUFFER_LEN>;
+}
+#[cfg(feature = "selftest")]
+pub mod selftest {
+    use libtock_selftest as selftest;
+    pub type SelfTest = selftest::SelfTest<super::runtime::TockSyscalls>;
+    pub use selftest::Test;
+}
+#[cfg(feature = "sound_pressure")]
 pub mod sound_pressure {
     use libtock_sound_pressure as sound_pressure;
     pub type SoundPressure = sound_pressure::SoundPressure<super::runtime::TockSyscalls>;
 }
-#[cfg(feature = "rust_embedded")]
+#[cfg(all(feature = "spi_controller", feature = "rust_embedded"))]
 pub mod spi_controller;
-#[cfg(not(feature = "rust_embedded"))]
+#[cfg(all(feature = "spi_controller", not(feature = "rust_embedded")))]
 pub mod spi_controller {
     use libtock_spi_controller as spi_controller;
     pub type SpiController = spi_controller::SpiController<super::runtime::TockSyscalls>;
 }
+#[cfg(feature = "supply_monitor")]
+pub mod supply_monitor {
+    use libtock_supply_monitor as supply_monitor;
+    pub type SupplyMonitor = supply_monitor::SupplyMonitor<super::runtime::TockSyscalls>;
+    pub use supply_monitor::{Calibration, Channel, PowerFailListener, SupplyMonitorListener};
+}
+#[cfg(feature = "temperature")]
 pub mod temperature {
     use libtock_temperature as temperature;
     pub type Temperature = temperature::Temperature<super::runtime::TockSyscalls>;
     pub use temperature::TemperatureListener;
 }
+#[cfg(feature = "usb_bulk")]
+pub mod usb_bulk {
+    use libtock_usb_bulk as usb_bulk;
+    pub type UsbBulk<const DRIVER_NUM: u32> =
+        usb_bulk::UsbBulk<super::runtime::TockSyscalls, DRIVER_NUM>;
+    pub use usb_bulk::Transfer;
+}
+#[cfg(feature = "watchdog")]
+pub mod watchdog {
+    use libtock_watchdog as watchdog;
+    pub type Watchdog = watchdog::Watchdog<super::runtime::TockSyscalls>;
+}
+#[cfg(feature = "weather")]
+pub mod weather {
+    use libtock_weather as weather;
+    pub type WeatherStation = weather::WeatherStation<super::runtime::TockSyscalls>;
+    pub use weather::WeatherRecord;
+}
+#[cfg(feature = "key_value")]
 pub mod key_value {
     use libtock_key_value as key_value;
     pub type KeyValue = key_value::KeyValue<super::runtime::TockSyscalls>;
@@ -0,0 +1,64 @@
+use libtock_platform::{DriverInfo, ErrorCode};
+use libtock_unittest::{command_return, fake, ExpectedSyscall};
+
+// Queries driver 1, which answers command 0 with `command_return`.
+fn query(
+    kernel: &fake::Kernel,
+    command_return: libtock_platform::CommandReturn,
+) -> Result<DriverInfo, ErrorCode> {
+    kernel.add_expected_syscall(ExpectedSyscall::Command {
+        driver_id: 1,
+        command_id: 0,
+        argument0: 0,
+        argument1: 0,
+        override_return: Some(command_return),
+    });
+    DriverInfo::query::<fake::Syscalls>(1)
+}
+
+#[test]
+fn no_driver() {
+    let _kernel = fake::Kernel::new();
+    assert_eq!(
+        DriverInfo::query::<fake::Syscalls>(1),
+        Err(ErrorCode::NoDevice)
+    );
+}
+
+#[test]
+fn query_variants() {
+    let kernel = fake::Kernel::new();
+
+    let info = query(&kernel, command_return::success()).unwrap();
+    assert_eq!(
+        (info.value(), info.version(), info.features()),
+        (None, None, None)
+    );
+    assert!(!info.supports(0b1));
+
+    // E.g. the number of LEDs.
+    let info = query(&kernel, command_return::success_u32(4)).unwrap();
+    assert_eq!(
+        (info.value(), info.version(), info.features()),
+        (Some(4), None, None)
+    );
+    assert!(!info.supports(0b1));
+
+    let info = query(&kernel, command_return::success_2_u32(3, 0b101)).unwrap();
+    assert_eq!(
+        (info.value(), info.version(), info.features()),
+        (Some(3), Some(3), Some(0b101))
+    );
+    assert!(info.supports(0b1));
+    assert!(info.supports(0b101));
+    assert!(!info.supports(0b11));
+
+    assert_eq!(
+        query(&kernel, command_return::failure(ErrorCode::Busy)),
+        Err(ErrorCode::Busy)
+    );
+    assert_eq!(
+        query(&kernel, command_return::failure_u32(ErrorCode::Fail, 7)),
+        Err(ErrorCode::Fail)
+    );
+}
@@ -19,6 +19,9 @@ mod allow_rw;
 #[cfg(test)]
 mod command_tests;
 
+#[cfg(test)]
+mod driver_info;
+
 #[cfg(test)]
 mod exit_on_drop;
 
@@ -27,8 +30,17 @@ mod exit_on_drop;
 #[cfg(test)]
 mod memop_tests;
 
+#[cfg(test)]
+mod strict_config;
+
 #[cfg(test)]
 mod subscribe_tests;
 
+#[cfg(test)]
+mod termination;
+
+#[cfg(test)]
+mod upcall_ctx;
+
 #[cfg(test)]
 mod yield_tests;
@@ -150,3 +150,57 @@ fn debug_heap_start_test() {
         }]
     );
 }
+
+#[test]
+fn layout_test() {
+    let kernel = fake::Kernel::new();
+    let ram_end = fake::Syscalls::memop_app_ram_end().unwrap();
+    let grant_start = fake::Syscalls::memop_grant_start().unwrap();
+    assert!(fake::Syscalls::memop_app_ram_start().unwrap() < grant_start);
+    assert!(grant_start < ram_end);
+    let flash_start = fake::Syscalls::memop_flash_start().unwrap();
+    assert!(flash_start < fake::Syscalls::memop_flash_end().unwrap());
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [3, 6, 2, 4, 5].map(|memop_num| SyscallLogEntry::Memop {
+            memop_num,
+            argument0: 0.into(),
+        })
+    );
+}
+
+#[test]
+fn flash_region_test() {
+    let kernel = fake::Kernel::new();
+    assert_eq!(fake::Syscalls::memop_flash_region_count(), Ok(0));
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_start(0),
+        Err(ErrorCode::Invalid)
+    );
+    kernel.add_expected_syscall(ExpectedSyscall::Memop {
+        memop_num: 9,
+        argument0: 1.into(),
+        return_error: Some(ErrorCode::NoSupport),
+    });
+    assert_eq!(
+        fake::Syscalls::memop_flash_region_end(1),
+        Err(ErrorCode::NoSupport)
+    );
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [
+            SyscallLogEntry::Memop {
+                memop_num: 7,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 8,
+                argument0: 0.into(),
+            },
+            SyscallLogEntry::Memop {
+                memop_num: 9,
+                argument0: 1.into(),
+            },
+        ]
+    );
+}
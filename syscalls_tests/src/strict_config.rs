@@ -0,0 +1,46 @@
+use libtock_platform::{share, ErrorCode, StrictConfig, Syscalls};
+use libtock_unittest::{fake, ExpectedSyscall};
+use std::cell::Cell;
+
+#[should_panic(expected = "Subscribe 3 of driver 0x60000 failed with NOSUPPORT")]
+#[test]
+fn panics_on_misconfiguration() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::Subscribe {
+        driver_num: 0x60000,
+        subscribe_num: 3,
+        skip_with_error: Some(ErrorCode::NoSupport),
+    });
+    let called = Cell::new(false);
+    share::scope(|subscribe| {
+        let _ = fake::Syscalls::subscribe::<_, _, StrictConfig, 0x60000, 3>(subscribe, &called);
+    });
+}
+
+#[test]
+fn returns_other_errors() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::AllowRo {
+        driver_num: 42,
+        buffer_num: 0,
+        return_error: Some(ErrorCode::Busy),
+    });
+    let result =
+        share::scope(|allow_ro| fake::Syscalls::allow_ro::<StrictConfig, 42, 0>(allow_ro, b"data"));
+    assert_eq!(result, Err(ErrorCode::Busy));
+}
+
+#[should_panic(expected = "Command 1 of driver 0x60000 failed with NODEVICE")]
+#[test]
+fn panics_on_missing_driver() {
+    let _kernel = fake::Kernel::new();
+    let _ = fake::Syscalls::checked_command::<StrictConfig>(0x60000, 1, 0, 0);
+}
+
+#[test]
+fn returns_existence_check() {
+    let _kernel = fake::Kernel::new();
+    // Command 0 checks whether the driver exists, so its failure is returned.
+    let result = fake::Syscalls::checked_command::<StrictConfig>(0x60000, 0, 0, 0);
+    assert_eq!(result.get_failure(), Some(ErrorCode::NoDevice));
+}
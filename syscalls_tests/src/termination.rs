@@ -0,0 +1,53 @@
+use libtock_platform::{ErrorCode, ExitError, Termination};
+use libtock_unittest::{exit_test, fake, ExitCall};
+
+// An application-specific error type that asks for the process to restart.
+struct Transient;
+
+impl ExitError for Transient {
+    fn exit_code(&self) -> u32 {
+        7
+    }
+
+    fn restart(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn error_code() {
+    assert_eq!(().error_code(), None);
+    assert_eq!(Ok::<(), ErrorCode>(()).error_code(), None);
+    assert_eq!(
+        Err::<(), _>(ErrorCode::Busy).error_code(),
+        Some(ErrorCode::Busy as u32)
+    );
+    assert_eq!(Err::<(), _>(Transient).error_code(), Some(7));
+}
+
+#[cfg(not(miri))]
+#[test]
+fn ok() {
+    let exit = exit_test("termination::ok", || {
+        Ok::<(), ErrorCode>(()).complete::<fake::Syscalls>()
+    });
+    assert_eq!(exit, ExitCall::Terminate(0));
+}
+
+#[cfg(not(miri))]
+#[test]
+fn error_terminates() {
+    let exit = exit_test("termination::error_terminates", || {
+        Err::<(), _>(ErrorCode::Fail).complete::<fake::Syscalls>()
+    });
+    assert_eq!(exit, ExitCall::Terminate(ErrorCode::Fail as u32));
+}
+
+#[cfg(not(miri))]
+#[test]
+fn error_restarts() {
+    let exit = exit_test("termination::error_restarts", || {
+        Err::<(), _>(Transient).complete::<fake::Syscalls>()
+    });
+    assert_eq!(exit, ExitCall::Restart(7));
+}
@@ -0,0 +1,56 @@
+use libtock_platform::{share, CommandReturn, CtxUpcall, DefaultConfig, Syscalls, UpcallCtx};
+use libtock_unittest::{command_return, fake, DriverInfo, DriverShareRef, SyscallLogEntry};
+use std::{cell::Cell, rc::Rc};
+
+// Fake driver that accepts an upcall, and counts its commands.
+#[derive(Default)]
+struct MockDriver {
+    share_ref: DriverShareRef,
+    commands: Cell<u32>,
+}
+
+impl fake::SyscallDriver for MockDriver {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(1).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, _: u32, _: u32, _: u32) -> CommandReturn {
+        self.commands.set(self.commands.get() + 1);
+        command_return::success_u32(self.commands.get())
+    }
+}
+
+#[test]
+fn command_from_upcall() {
+    let kernel = fake::Kernel::new();
+    let driver = Rc::new(MockDriver::default());
+    kernel.add_driver(&driver);
+    let args = Cell::new(None);
+    let upcall = CtxUpcall::new(|ctx: &UpcallCtx<fake::Syscalls>, arg0, arg1, arg2| {
+        args.set(Some((arg0, arg1, arg2)));
+        assert_eq!(ctx.command(1, 2, 3, 4).get_success_u32(), Some(1));
+    });
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 1, 0>(subscribe, &upcall).unwrap();
+        driver.share_ref.schedule_upcall(0, (5, 6, 7)).unwrap();
+        kernel.take_syscall_log();
+        assert!(fake::Syscalls::yield_no_wait_flag());
+    });
+    assert_eq!(args.get(), Some((5, 6, 7)));
+    assert_eq!(
+        kernel.take_syscall_log()[..2],
+        [
+            SyscallLogEntry::YieldNoWait,
+            SyscallLogEntry::Command {
+                driver_id: 1,
+                command_id: 2,
+                argument0: 3,
+                argument1: 4,
+            },
+        ]
+    );
+}
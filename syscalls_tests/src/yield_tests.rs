@@ -28,6 +28,24 @@ fn no_wait_no_upcall() {
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldNoWait]);
 }
 
+// Tests yield_no_wait_flag with and without an upcall executed.
+#[test]
+fn no_wait_flag() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::YieldNoWait {
+        override_return: Some(YieldNoWaitReturn::Upcall),
+    });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldNoWait {
+        override_return: Some(YieldNoWaitReturn::NoUpcall),
+    });
+    assert!(fake::Syscalls::yield_no_wait_flag());
+    assert!(!fake::Syscalls::yield_no_wait_flag());
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldNoWait, SyscallLogEntry::YieldNoWait]
+    );
+}
+
 // Tests yield_wait.
 #[test]
 fn wait() {
@@ -36,3 +54,23 @@ fn wait() {
     fake::Syscalls::yield_wait();
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
 }
+
+// Tests yield_until.
+#[test]
+fn until() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWait { skip_upcall: true });
+    kernel.add_expected_syscall(ExpectedSyscall::YieldWait { skip_upcall: true });
+    let mut waits = 0;
+    assert_eq!(
+        fake::Syscalls::yield_until(&mut || {
+            waits += 1;
+            waits == 2
+        }),
+        Ok(())
+    );
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWait, SyscallLogEntry::YieldWait]
+    );
+}
@@ -3,17 +3,30 @@
 //! Like the real API, `Adc` controls a fake Adc sensor. It provides
 //! a function `set_value` used to immediately call an upcall with a Adc value read by the sensor
 //! and a function 'set_value_sync' used to call the upcall when the read command is received.
+//!
+//! Samples for single reads can also be scripted in advance with
+//! `queue_samples`. For continuous buffered sampling, the app allows up to two
+//! read-write buffers; each call to `fill_buffer` writes samples into the
+//! active buffer, reports it with an upcall, and switches to the other buffer
+//! (if one is allowed), like the double buffering in the real capsule.
 
-use crate::{DriverInfo, DriverShareRef};
+use crate::{DriverInfo, DriverShareRef, RwAllowBuffer};
 use libtock_platform::{CommandReturn, ErrorCode};
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 
 // The `upcall_on_command` field is set to Some(value) if an upcall(with value as its argument) should be called when read command is received,
 // or None otherwise. It was needed for testing `read_sync` library function which simulates a synchronous Adc read,
 // because it was impossible to schedule an upcall during the `synchronous` read in other ways.
+//
+// `active_buffer` is Some(buffer number) while continuous buffered sampling is
+// running.
 pub struct Adc {
     busy: Cell<bool>,
     upcall_on_command: Cell<Option<i32>>,
+    queued_samples: RefCell<VecDeque<u16>>,
+    buffers: [Cell<RwAllowBuffer>; 2],
+    active_buffer: Cell<Option<u32>>,
     share_ref: DriverShareRef,
 }
 
@@ -22,6 +35,9 @@ impl Adc {
         std::rc::Rc::new(Adc {
             busy: Cell::new(false),
             upcall_on_command: Cell::new(None),
+            queued_samples: Default::default(),
+            buffers: Default::default(),
+            active_buffer: Cell::new(None),
             share_ref: Default::default(),
         })
     }
@@ -40,6 +56,48 @@ impl Adc {
     pub fn set_value_sync(&self, value: i32) {
         self.upcall_on_command.set(Some(value));
     }
+
+    /// Queues samples to be returned by future single-sample reads. Each read
+    /// takes the next queued sample and completes immediately, as with
+    /// `set_value_sync`. Once the queue is empty, reads wait for `set_value`
+    /// again.
+    pub fn queue_samples(&self, samples: &[u16]) {
+        self.queued_samples.borrow_mut().extend(samples);
+    }
+
+    /// Returns the buffer number continuous buffered sampling is currently
+    /// writing to, or `None` if it is not running.
+    pub fn active_buffer(&self) -> Option<u32> {
+        self.active_buffer.get()
+    }
+
+    /// Writes `samples` (as native-endian `u16`s) into the active buffer,
+    /// schedules an upcall reporting `(CONTINUOUS_BUFF_SAMPLE, samples
+    /// written, buffer number)` and switches to the other buffer if it is
+    /// allowed. Samples that do not fit are dropped. Returns the number of
+    /// samples written, or `None` if continuous buffered sampling is not
+    /// running.
+    pub fn fill_buffer(&self, samples: &[u16]) -> Option<usize> {
+        let buffer_num = self.active_buffer.get()?;
+        let mut buffer = self.buffers[buffer_num as usize].take();
+        let written = samples.len().min(buffer.len() / 2);
+        for (chunk, sample) in buffer.chunks_exact_mut(2).zip(&samples[..written]) {
+            chunk.copy_from_slice(&sample.to_ne_bytes());
+        }
+        self.buffers[buffer_num as usize].set(buffer);
+
+        let other = 1 - buffer_num;
+        let other_buffer = self.buffers[other as usize].take();
+        if !other_buffer.is_empty() {
+            self.active_buffer.set(Some(other));
+        }
+        self.buffers[other as usize].set(other_buffer);
+
+        self.share_ref
+            .schedule_upcall(0, (CONTINUOUS_BUFF_SAMPLE, written as u32, buffer_num))
+            .expect("Unable to schedule upcall");
+        Some(written)
+    }
 }
 
 impl crate::fake::SyscallDriver for Adc {
@@ -51,20 +109,54 @@ impl crate::fake::SyscallDriver for Adc {
         self.share_ref.replace(share_ref);
     }
 
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match self.buffers.get(buffer_num as usize) {
+            Some(slot) => Ok(slot.replace(buffer)),
+            None => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
     fn command(&self, command_id: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
         match command_id {
             EXISTS => crate::command_return::success_u32(1),
 
             SINGLE_SAMPLE => {
-                if self.busy.get() {
+                if self.busy.get() || self.active_buffer.get().is_some() {
                     return crate::command_return::failure(ErrorCode::Busy);
                 }
                 self.busy.set(true);
+                let queued = self.queued_samples.borrow_mut().pop_front();
                 if let Some(val) = self.upcall_on_command.take() {
                     self.set_value(val);
+                } else if let Some(sample) = queued {
+                    self.set_value(sample.into());
                 }
                 crate::command_return::success()
             }
+
+            CONTINUOUS_BUFF_SAMPLE => {
+                if self.busy.get() || self.active_buffer.get().is_some() {
+                    return crate::command_return::failure(ErrorCode::Busy);
+                }
+                let buffer = self.buffers[0].take();
+                let allowed = !buffer.is_empty();
+                self.buffers[0].set(buffer);
+                if !allowed {
+                    return crate::command_return::failure(ErrorCode::NoMem);
+                }
+                self.active_buffer.set(Some(0));
+                crate::command_return::success()
+            }
+
+            STOP_SAMPLE => {
+                self.busy.set(false);
+                self.active_buffer.set(None);
+                crate::command_return::success()
+            }
             _ => crate::command_return::failure(ErrorCode::NoSupport),
         }
     }
@@ -82,3 +174,5 @@ const DRIVER_NUM: u32 = 0x5;
 
 const EXISTS: u32 = 0;
 const SINGLE_SAMPLE: u32 = 1;
+const CONTINUOUS_BUFF_SAMPLE: u32 = 4;
+const STOP_SAMPLE: u32 = 5;
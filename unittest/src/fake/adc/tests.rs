@@ -65,3 +65,88 @@ fn kernel_integration() {
         assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
     });
 }
+
+#[test]
+fn queued_samples() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let adc = Adc::new();
+    kernel.add_driver(&adc);
+    adc.queue_samples(&[10, 20]);
+
+    let listener = Cell::<Option<(u32,)>>::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &listener)
+            .unwrap();
+        for expected in [10, 20] {
+            assert!(fake::Syscalls::command(DRIVER_NUM, SINGLE_SAMPLE, 0, 0).is_success());
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(listener.take(), Some((expected,)));
+        }
+        // The queue is empty, so the next read waits for set_value.
+        assert!(fake::Syscalls::command(DRIVER_NUM, SINGLE_SAMPLE, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        assert!(adc.is_busy());
+    });
+}
+
+#[test]
+fn continuous_buffered() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let adc = Adc::new();
+    kernel.add_driver(&adc);
+    assert_eq!(adc.fill_buffer(&[1]), None);
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, CONTINUOUS_BUFF_SAMPLE, 0, 100).get_failure(),
+        Some(ErrorCode::NoMem)
+    );
+
+    let mut buffer0 = [0u8; 4];
+    let mut buffer1 = [0u8; 6];
+    let listener = Cell::<Option<(u32, u32, u32)>>::new(None);
+    share::scope::<
+        (
+            libtock_platform::AllowRw<_, DRIVER_NUM, 0>,
+            libtock_platform::AllowRw<_, DRIVER_NUM, 1>,
+            libtock_platform::Subscribe<_, DRIVER_NUM, 0>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow0, allow1, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 0>(allow0, &mut buffer0).unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 1>(allow1, &mut buffer1).unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &listener)
+            .unwrap();
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, CONTINUOUS_BUFF_SAMPLE, 0, 100).is_success());
+        assert_eq!(adc.active_buffer(), Some(0));
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, SINGLE_SAMPLE, 0, 0).get_failure(),
+            Some(ErrorCode::Busy)
+        );
+
+        // Extra samples are dropped, and sampling moves on to buffer 1.
+        assert_eq!(adc.fill_buffer(&[1, 2, 3]), Some(2));
+        assert_eq!(adc.active_buffer(), Some(1));
+        fake::Syscalls::yield_wait();
+        assert_eq!(listener.take(), Some((CONTINUOUS_BUFF_SAMPLE, 2, 0)));
+
+        assert_eq!(adc.fill_buffer(&[4, 5, 6]), Some(3));
+        assert_eq!(adc.active_buffer(), Some(0));
+        fake::Syscalls::yield_wait();
+        assert_eq!(listener.take(), Some((CONTINUOUS_BUFF_SAMPLE, 3, 1)));
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, STOP_SAMPLE, 0, 0).is_success());
+        assert_eq!(adc.active_buffer(), None);
+    });
+    let samples = |buffer: &[u8]| -> Vec<u16> {
+        buffer
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect()
+    };
+    assert_eq!(samples(&buffer0), [1, 2]);
+    assert_eq!(samples(&buffer1), [4, 5, 6]);
+}
@@ -0,0 +1,226 @@
+//! Fake implementation of an AES-128 driver, modeled on Tock's AES capsule.
+//! Encryption is done in software, as soon as the process requests it.
+//!
+//! The system call interface is:
+//!
+//! * Read-Only Allow 0: the 16-byte key.
+//! * Read-Only Allow 1: the 16-byte IV (CBC) or initial counter block (CTR).
+//! * Read-Only Allow 2: the source data.
+//! * Read-Write Allow 0: the destination buffer.
+//! * Command 1: set the mode to `argument0` (0: ECB, 1: CBC, 2: CTR), and
+//!   whether to encrypt (`argument1` = 1) or decrypt (`argument1` = 0).
+//! * Command 2: encrypts or decrypts the source into the destination buffer.
+//!   Each call starts from the allowed IV; chaining state is not kept between
+//!   calls.
+//! * Subscribe 0: run when command 2 completes, with arguments
+//!   `(0, length, 0)`.
+//!
+//! Command 2 fails with `Reserve` if no mode is set, `Invalid` if the key or IV
+//! has the wrong length or the source is not a whole number of blocks (ECB and
+//! CBC), and `Size` if the destination is shorter than the source.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+use ::aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use ::aes::Aes128;
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+/// A block cipher mode of operation supported by `fake::Aes`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AesMode {
+    Ecb,
+    Cbc,
+    Ctr,
+}
+
+pub struct Aes {
+    // The mode and whether to encrypt, as set by the process.
+    mode: Cell<Option<(AesMode, bool)>>,
+    key: Cell<RoAllowBuffer>,
+    iv: Cell<RoAllowBuffer>,
+    source: Cell<RoAllowBuffer>,
+    dest: RefCell<RwAllowBuffer>,
+    share_ref: DriverShareRef,
+}
+
+impl Aes {
+    pub fn new() -> std::rc::Rc<Aes> {
+        std::rc::Rc::new(Aes {
+            mode: Default::default(),
+            key: Default::default(),
+            iv: Default::default(),
+            source: Default::default(),
+            dest: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the mode selected by the process, if any.
+    pub fn mode(&self) -> Option<AesMode> {
+        self.mode.get().map(|(mode, _)| mode)
+    }
+
+    /// Returns true if the process selected encryption, false if it selected
+    /// decryption or has not selected a mode.
+    pub fn is_encrypting(&self) -> bool {
+        self.mode.get().is_some_and(|(_, encrypting)| encrypting)
+    }
+
+    // Encrypts or decrypts the source into the destination buffer, returning
+    // the number of bytes written.
+    fn crypt(&self) -> Result<u32, ErrorCode> {
+        let (mode, encrypting) = self.mode.get().ok_or(ErrorCode::Reserve)?;
+        let (key, iv, source) = (self.key.take(), self.iv.take(), self.source.take());
+        let result = crypt(
+            mode,
+            encrypting,
+            &key,
+            &iv,
+            &source,
+            &mut self.dest.borrow_mut(),
+        );
+        self.key.set(key);
+        self.iv.set(iv);
+        self.source.set(source);
+        result
+    }
+}
+
+impl crate::fake::SyscallDriver for Aes {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_KEY => Ok(self.key.replace(buffer)),
+            ALLOW_IV => Ok(self.iv.replace(buffer)),
+            ALLOW_SOURCE => Ok(self.source.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_DEST => Ok(self.dest.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => command_return::success(),
+            SET_MODE => {
+                let mode = match argument0 {
+                    0 => AesMode::Ecb,
+                    1 => AesMode::Cbc,
+                    2 => AesMode::Ctr,
+                    _ => return command_return::failure(ErrorCode::Invalid),
+                };
+                self.mode.set(Some((mode, argument1 != 0)));
+                command_return::success()
+            }
+            CRYPT => match self.crypt() {
+                Err(error) => command_return::failure(error),
+                Ok(len) => {
+                    self.share_ref
+                        .schedule_upcall(SUBSCRIBE_DONE, (0, len, 0))
+                        .expect("Unable to schedule upcall");
+                    command_return::success()
+                }
+            },
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+const BLOCK_LEN: usize = 16;
+
+fn crypt(
+    mode: AesMode,
+    encrypting: bool,
+    key: &[u8],
+    iv: &[u8],
+    source: &[u8],
+    dest: &mut [u8],
+) -> Result<u32, ErrorCode> {
+    let cipher = Aes128::new_from_slice(key).map_err(|_| ErrorCode::Invalid)?;
+    if (mode != AesMode::Ecb && iv.len() != BLOCK_LEN)
+        || (mode != AesMode::Ctr && source.len() % BLOCK_LEN != 0)
+    {
+        return Err(ErrorCode::Invalid);
+    }
+    let dest = dest.get_mut(..source.len()).ok_or(ErrorCode::Size)?;
+    dest.copy_from_slice(source);
+    // The previous ciphertext block (CBC) or the counter block (CTR).
+    let mut chain = [0; BLOCK_LEN];
+    if mode != AesMode::Ecb {
+        chain.copy_from_slice(iv);
+    }
+    for block in dest.chunks_mut(BLOCK_LEN) {
+        match (mode, encrypting) {
+            (AesMode::Ecb, true) => cipher.encrypt_block(GenericArray::from_mut_slice(block)),
+            (AesMode::Ecb, false) => cipher.decrypt_block(GenericArray::from_mut_slice(block)),
+            (AesMode::Cbc, true) => {
+                xor(block, &chain);
+                cipher.encrypt_block(GenericArray::from_mut_slice(block));
+                chain.copy_from_slice(block);
+            }
+            (AesMode::Cbc, false) => {
+                let ciphertext: [u8; BLOCK_LEN] = block.try_into().unwrap();
+                cipher.decrypt_block(GenericArray::from_mut_slice(block));
+                xor(block, &chain);
+                chain = ciphertext;
+            }
+            (AesMode::Ctr, _) => {
+                let mut keystream = chain;
+                cipher.encrypt_block(GenericArray::from_mut_slice(&mut keystream));
+                xor(block, &keystream);
+                chain = u128::from_be_bytes(chain).wrapping_add(1).to_be_bytes();
+            }
+        }
+    }
+    Ok(source.len() as u32)
+}
+
+fn xor(block: &mut [u8], other: &[u8]) {
+    block.iter_mut().zip(other).for_each(|(b, o)| *b ^= o);
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40006;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const SET_MODE: u32 = 1;
+const CRYPT: u32 = 2;
+
+// Allow IDs
+const ALLOW_KEY: u32 = 0;
+const ALLOW_IV: u32 = 1;
+const ALLOW_SOURCE: u32 = 2;
+const ALLOW_DEST: u32 = 0;
+
+const SUBSCRIBE_DONE: u32 = 0;
@@ -0,0 +1,145 @@
+use crate::fake::{self, aes::*};
+use libtock_platform::{
+    share, AllowRo, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn,
+};
+
+// The AES-128 example vector from FIPS 197, appendix C.1.
+const KEY: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+const PLAINTEXT: [u8; 16] = [
+    0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+];
+const CIPHERTEXT: [u8; 16] = [
+    0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a,
+];
+
+#[test]
+fn modes() {
+    let crypt = |mode, encrypting, iv: &[u8], source: &[u8]| {
+        let mut dest = vec![0; source.len()];
+        crypt(mode, encrypting, &KEY, iv, source, &mut dest).map(|_| dest)
+    };
+    assert_eq!(
+        crypt(AesMode::Ecb, true, &[], &PLAINTEXT),
+        Ok(CIPHERTEXT.to_vec())
+    );
+    assert_eq!(
+        crypt(AesMode::Ecb, false, &[], &CIPHERTEXT),
+        Ok(PLAINTEXT.to_vec())
+    );
+
+    // With a zero IV, the first CBC block is the ECB block.
+    let source: Vec<u8> = (0..32).collect();
+    let mut first_block = PLAINTEXT;
+    first_block
+        .iter_mut()
+        .zip(&source)
+        .for_each(|(p, s)| *p ^= s);
+    let cbc = crypt(AesMode::Cbc, true, &source[..16], &first_block).unwrap();
+    assert_eq!(cbc, CIPHERTEXT);
+    let cbc = crypt(AesMode::Cbc, true, &[7; 16], &source).unwrap();
+    assert_eq!(
+        crypt(AesMode::Cbc, false, &[7; 16], &cbc),
+        Ok(source.clone())
+    );
+
+    // CTR handles partial blocks, and decryption is encryption.
+    let ctr = crypt(AesMode::Ctr, true, &[0xff; 16], &source[..20]).unwrap();
+    assert_eq!(
+        &ctr[..16],
+        &crypt(AesMode::Ecb, true, &[], &[0xff; 16])
+            .unwrap()
+            .iter()
+            .zip(&source)
+            .map(|(k, s)| k ^ s)
+            .collect::<Vec<_>>()[..]
+    );
+    assert_eq!(
+        crypt(AesMode::Ctr, true, &[0xff; 16], &ctr),
+        Ok(source[..20].to_vec())
+    );
+
+    assert_eq!(
+        crypt(AesMode::Cbc, true, &[0; 15], &source),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        crypt(AesMode::Ecb, true, &[], &source[..20]),
+        Err(ErrorCode::Invalid)
+    );
+    let mut short = [0; 8];
+    assert_eq!(
+        super::crypt(AesMode::Ctr, true, &KEY, &[0; 16], &source, &mut short),
+        Err(ErrorCode::Size)
+    );
+}
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let aes = Aes::new();
+    assert!(aes.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        aes.command(CRYPT, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        aes.command(SET_MODE, 3, 1).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(aes.command(SET_MODE, 2, 1).is_success());
+    assert_eq!(aes.mode(), Some(AesMode::Ctr));
+    assert!(aes.is_encrypting());
+    // No key.
+    assert_eq!(
+        aes.command(CRYPT, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        aes.command(3, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+}
+
+// Integration test that verifies Aes works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let aes = Aes::new();
+    kernel.add_driver(&aes);
+    assert!(fake::Syscalls::command(DRIVER_NUM, SET_MODE, 0, 1).is_success());
+
+    let mut dest = [0; 16];
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, ALLOW_KEY>,
+            AllowRo<_, DRIVER_NUM, ALLOW_SOURCE>,
+            AllowRw<_, DRIVER_NUM, ALLOW_DEST>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_DONE>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_key, allow_source, allow_dest, subscribe) = handle.split();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_DONE>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_KEY>(allow_key, &KEY).unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_SOURCE>(
+            allow_source,
+            &PLAINTEXT,
+        )
+        .unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_DEST>(allow_dest, &mut dest)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, CRYPT, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((0, 16)));
+    });
+    assert_eq!(dest, CIPHERTEXT);
+}
@@ -1,7 +1,15 @@
 //! Fake implementation of the Alarm API.
 //!
-//! Supports frequency and set_relative.
-//! Will schedule the upcall immediately.
+//! Supports frequency, time, stop, set_relative, and set_absolute.
+//!
+//! By default (`Alarm::new`), time only passes when an alarm is set: setting
+//! an alarm advances the tick counter to the alarm's expiration and schedules
+//! the upcall immediately.
+//!
+//! An alarm created with `Alarm::with_manual_time` instead keeps time still
+//! until the test calls `advance_ticks`, which fires the alarm if it becomes
+//! due. This allows deterministic tests of code that depends on timing.
+//! `set_time_step` also makes time pass whenever the process reads it.
 
 use core::cell::Cell;
 use core::num::Wrapping;
@@ -11,18 +19,88 @@ use crate::{DriverInfo, DriverShareRef};
 
 pub struct Alarm {
     frequency_hz: u32,
+    manual_time: bool,
     now: Cell<Wrapping<u32>>,
+    // The ticks that pass each time the process reads the time.
+    time_step: Cell<u32>,
+    // The (reference, dt) pair of the armed alarm, which expires at
+    // reference + dt.
+    armed: Cell<Option<(Wrapping<u32>, Wrapping<u32>)>>,
     share_ref: DriverShareRef,
 }
 
 impl Alarm {
     pub fn new(frequency_hz: u32) -> std::rc::Rc<Alarm> {
+        Self::new_inner(frequency_hz, false)
+    }
+
+    /// Creates an alarm whose time only advances when `advance_ticks` is
+    /// called.
+    pub fn with_manual_time(frequency_hz: u32) -> std::rc::Rc<Alarm> {
+        Self::new_inner(frequency_hz, true)
+    }
+
+    fn new_inner(frequency_hz: u32, manual_time: bool) -> std::rc::Rc<Alarm> {
         std::rc::Rc::new(Alarm {
             frequency_hz,
+            manual_time,
             now: Cell::new(Wrapping(0)),
+            time_step: Cell::new(0),
+            armed: Cell::new(None),
             share_ref: Default::default(),
         })
     }
+
+    /// Returns the current value of the tick counter.
+    pub fn ticks(&self) -> u32 {
+        self.now.get().0
+    }
+
+    /// Makes `ticks` pass each time the process reads the time, as if it were
+    /// busy between reads, e.g. to test code that polls the time.
+    pub fn set_time_step(&self, ticks: u32) {
+        self.time_step.set(ticks);
+    }
+
+    /// Returns the tick at which the armed alarm expires, or `None` if no
+    /// alarm is armed.
+    pub fn expiration(&self) -> Option<u32> {
+        self.armed.get().map(|(reference, dt)| (reference + dt).0)
+    }
+
+    /// Advances the tick counter by `ticks`, firing the armed alarm if it
+    /// becomes due.
+    pub fn advance_ticks(&self, ticks: u32) {
+        self.now.set(self.now.get() + Wrapping(ticks));
+        self.fire_if_due();
+    }
+
+    fn arm(&self, reference: Wrapping<u32>, dt: Wrapping<u32>) -> u32 {
+        let expiration = reference + dt;
+        if !self.manual_time {
+            // We're not actually sleeping, just ticking the timer.
+            self.now.set(expiration);
+        }
+        self.armed.set(Some((reference, dt)));
+        self.fire_if_due();
+        expiration.0
+    }
+
+    fn fire_if_due(&self) {
+        let Some((reference, dt)) = self.armed.get() else {
+            return;
+        };
+        if self.now.get() - reference < dt {
+            return;
+        }
+        self.armed.set(None);
+        let expiration = reference + dt;
+        // The capsule passes the current tick and the expiration.
+        let args = (self.now.get().0, expiration.0, 0);
+        self.share_ref
+            .schedule_upcall(subscribe::CALLBACK, args)
+            .expect("schedule_upcall failed");
+    }
 }
 
 impl crate::fake::SyscallDriver for Alarm {
@@ -34,21 +112,26 @@ impl crate::fake::SyscallDriver for Alarm {
         self.share_ref.replace(share_ref);
     }
 
-    fn command(&self, command_number: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+    fn command(&self, command_number: u32, argument0: u32, argument1: u32) -> CommandReturn {
         match command_number {
+            command::EXISTS => crate::command_return::success(),
             command::FREQUENCY => crate::command_return::success_u32(self.frequency_hz),
+            command::TIME => {
+                let now = self.now.get();
+                self.advance_ticks(self.time_step.get());
+                crate::command_return::success_u32(now.0)
+            }
+            command::STOP => match self.armed.take() {
+                Some(_) => crate::command_return::success(),
+                None => crate::command_return::failure(ErrorCode::Already),
+            },
             command::SET_RELATIVE => {
-                // We're not actually sleeping, just ticking the timer.
-                // The semantics of sleeping aren't clear,
-                // so we're assuming that all future times are equal,
-                // and waking immediately.
-                let relative = argument0;
-                let wake = self.now.get() + Wrapping(relative);
-                self.share_ref
-                    .schedule_upcall(subscribe::CALLBACK, (wake.0, 0, 0))
-                    .expect("schedule_upcall failed");
-                self.now.set(wake);
-                crate::command_return::success_u32(wake.0)
+                let expiration = self.arm(self.now.get(), Wrapping(argument0));
+                crate::command_return::success_u32(expiration)
+            }
+            command::SET_ABSOLUTE => {
+                let expiration = self.arm(Wrapping(argument0), Wrapping(argument1));
+                crate::command_return::success_u32(expiration)
             }
             _ => crate::command_return::failure(ErrorCode::NoSupport),
         }
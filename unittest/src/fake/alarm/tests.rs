@@ -1,5 +1,6 @@
 use crate::fake;
 use fake::alarm::*;
+use libtock_platform::ErrorCode;
 
 // Tests the command implementation.
 #[test]
@@ -7,8 +8,129 @@ fn command() {
     use fake::SyscallDriver;
     let alarm = Alarm::new(10);
 
+    assert!(alarm.command(command::EXISTS, 0, 0).is_success());
     assert_eq!(
         alarm.command(command::FREQUENCY, 1, 2).get_success_u32(),
         Some(10)
     );
+    assert_eq!(
+        alarm.command(command::SET_RELATIVE, 5, 0).get_success_u32(),
+        Some(5)
+    );
+    // Without manual time, setting an alarm moves time to its expiration.
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(5)
+    );
+    assert_eq!(alarm.expiration(), None);
+    assert_eq!(
+        alarm.command(command::STOP, 0, 0).get_failure(),
+        Some(ErrorCode::Already)
+    );
+}
+
+#[test]
+fn manual_time() {
+    use fake::SyscallDriver;
+    let alarm = Alarm::with_manual_time(1000);
+
+    assert_eq!(
+        alarm
+            .command(command::SET_RELATIVE, 10, 0)
+            .get_success_u32(),
+        Some(10)
+    );
+    assert_eq!(alarm.ticks(), 0);
+    assert_eq!(alarm.expiration(), Some(10));
+    alarm.advance_ticks(9);
+    assert_eq!(alarm.expiration(), Some(10));
+    alarm.advance_ticks(1);
+    assert_eq!(alarm.expiration(), None);
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(10)
+    );
+
+    // An absolute alarm relative to a reference in the past.
+    assert_eq!(
+        alarm
+            .command(command::SET_ABSOLUTE, 5, 20)
+            .get_success_u32(),
+        Some(25)
+    );
+    assert!(alarm.command(command::STOP, 0, 0).is_success());
+    alarm.advance_ticks(100);
+    assert_eq!(alarm.expiration(), None);
+
+    // The tick counter wraps around.
+    alarm.advance_ticks(u32::MAX - 114);
+    assert_eq!(alarm.ticks(), u32::MAX - 4);
+    assert!(alarm.command(command::SET_RELATIVE, 10, 0).is_success_u32());
+    assert_eq!(alarm.expiration(), Some(5));
+    alarm.advance_ticks(9);
+    assert_eq!(alarm.expiration(), Some(5));
+    alarm.advance_ticks(1);
+    assert_eq!(alarm.expiration(), None);
+}
+
+// Integration test that verifies Alarm works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::{share, DefaultConfig, Syscalls, YieldNoWaitReturn};
+    let kernel = fake::Kernel::new();
+    let alarm = Alarm::with_manual_time(1000);
+    kernel.add_driver(&alarm);
+
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 0, { subscribe::CALLBACK }>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(0, command::SET_RELATIVE, 100, 0).is_success_u32());
+        alarm.advance_ticks(50);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        alarm.advance_ticks(60);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((110, 100)));
+    });
+}
+
+// Without manual time, the upcall has the same layout, with the current tick
+// being the expiration.
+#[test]
+fn kernel_integration_default_time() {
+    use libtock_platform::{share, DefaultConfig, Syscalls, YieldNoWaitReturn};
+    let kernel = fake::Kernel::new();
+    let alarm = Alarm::new(1000);
+    kernel.add_driver(&alarm);
+
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 0, { subscribe::CALLBACK }>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(0, command::SET_RELATIVE, 100, 0).is_success_u32());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((100, 100)));
+    });
+}
+
+#[test]
+fn time_step() {
+    use fake::SyscallDriver;
+    let alarm = Alarm::with_manual_time(10);
+    alarm.set_time_step(3);
+
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(0)
+    );
+    assert_eq!(
+        alarm.command(command::TIME, 0, 0).get_success_u32(),
+        Some(3)
+    );
+    assert_eq!(alarm.ticks(), 6);
 }
@@ -0,0 +1,104 @@
+//! Fake implementation of the battery driver.
+//!
+//! `Battery` reports the voltage, charge percentage, and charging state set by
+//! the test. When `set_percentage` lowers the charge below the threshold set by
+//! the process, it schedules the low-battery upcall, as the real driver does
+//! when its fuel gauge reports the drop. The upcall is scheduled once per
+//! crossing: the charge has to rise to the threshold again before it is
+//! repeated.
+
+use crate::{command_return, DriverInfo, DriverShareRef};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::cell::Cell;
+
+pub struct Battery {
+    voltage_mv: Cell<u32>,
+    percentage: Cell<u32>,
+    charging_state: Cell<u32>,
+    low_threshold: Cell<u32>,
+    share_ref: DriverShareRef,
+}
+
+impl Battery {
+    pub fn new() -> std::rc::Rc<Battery> {
+        std::rc::Rc::new(Battery {
+            voltage_mv: Cell::new(3700),
+            percentage: Cell::new(100),
+            charging_state: Cell::new(0),
+            low_threshold: Cell::new(0),
+            share_ref: Default::default(),
+        })
+    }
+
+    pub fn set_voltage_mv(&self, voltage_mv: u32) {
+        self.voltage_mv.set(voltage_mv);
+    }
+
+    /// Sets the charge, scheduling the low-battery upcall if it drops below
+    /// the threshold.
+    pub fn set_percentage(&self, percentage: u32) {
+        let threshold = self.low_threshold.get();
+        if percentage < threshold && self.percentage.get() >= threshold {
+            self.share_ref
+                .schedule_upcall(SUBSCRIBE_LOW_BATTERY, (percentage, 0, 0))
+                .expect("Unable to schedule upcall");
+        }
+        self.percentage.set(percentage);
+    }
+
+    /// Sets the raw charging state value (0: discharging, 1: charging, 2: full,
+    /// 3: not present).
+    pub fn set_charging_state(&self, charging_state: u32) {
+        self.charging_state.set(charging_state);
+    }
+
+    /// Returns the low-battery threshold set by the process (0 if disabled).
+    pub fn low_threshold(&self) -> u32 {
+        self.low_threshold.get()
+    }
+}
+
+impl crate::fake::SyscallDriver for Battery {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => command_return::success(),
+            VOLTAGE_MV => command_return::success_u32(self.voltage_mv.get()),
+            PERCENTAGE => command_return::success_u32(self.percentage.get()),
+            CHARGING_STATE => command_return::success_u32(self.charging_state.get()),
+            SET_LOW_THRESHOLD => {
+                if argument0 > 100 {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.low_threshold.set(argument0);
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x9006C;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const VOLTAGE_MV: u32 = 1;
+const PERCENTAGE: u32 = 2;
+const CHARGING_STATE: u32 = 3;
+const SET_LOW_THRESHOLD: u32 = 4;
+
+const SUBSCRIBE_LOW_BATTERY: u32 = 0;
@@ -0,0 +1,68 @@
+use crate::fake::{self, battery::*};
+use libtock_platform::{share, DefaultConfig, Syscalls, YieldNoWaitReturn};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let battery = Battery::new();
+    assert!(battery.command(EXISTS, 0, 0).is_success());
+    battery.set_voltage_mv(3300);
+    battery.set_percentage(40);
+    battery.set_charging_state(1);
+    assert_eq!(
+        battery.command(VOLTAGE_MV, 0, 0).get_success_u32(),
+        Some(3300)
+    );
+    assert_eq!(
+        battery.command(PERCENTAGE, 0, 0).get_success_u32(),
+        Some(40)
+    );
+    assert_eq!(
+        battery.command(CHARGING_STATE, 0, 0).get_success_u32(),
+        Some(1)
+    );
+    assert!(battery.command(SET_LOW_THRESHOLD, 15, 0).is_success());
+    assert_eq!(battery.low_threshold(), 15);
+    assert_eq!(
+        battery.command(SET_LOW_THRESHOLD, 101, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(battery.low_threshold(), 15);
+}
+
+// Integration test that verifies Battery works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let battery = Battery::new();
+    kernel.add_driver(&battery);
+
+    let listener = Cell::<Option<(u32,)>>::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_LOW_BATTERY>(
+            subscribe, &listener,
+        )
+        .unwrap();
+        // No threshold is set, so the event is disabled.
+        battery.set_percentage(5);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        battery.set_percentage(50);
+        assert!(fake::Syscalls::command(DRIVER_NUM, SET_LOW_THRESHOLD, 20, 0).is_success());
+        battery.set_percentage(20);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        battery.set_percentage(19);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((19,)));
+
+        // The event is not repeated until the charge recovers.
+        battery.set_percentage(18);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        battery.set_percentage(25);
+        battery.set_percentage(10);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((10,)));
+    });
+}
@@ -6,9 +6,14 @@
 //! status of a button.
 //!
 //! It also provides the function `set_pressed` that set the button's state.
+//!
+//! Tests can script the state a button will have on successive reads with
+//! `queue_presses`, and deliver an interrupt without changing the button's
+//! state with `inject_interrupt`.
 
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
 use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::{HashMap, VecDeque};
 
 use crate::{DriverInfo, DriverShareRef};
 
@@ -20,6 +25,7 @@ pub struct ButtonState {
 
 pub struct Buttons<const NUM_BUTTONS: usize> {
     buttons: [Cell<ButtonState>; NUM_BUTTONS],
+    queued_presses: RefCell<HashMap<u32, VecDeque<bool>>>,
     share_ref: DriverShareRef,
 }
 
@@ -32,6 +38,7 @@ impl<const NUM_BUTTONS: usize> Buttons<NUM_BUTTONS> {
         });
         std::rc::Rc::new(Buttons {
             buttons: [OFF; NUM_BUTTONS],
+            queued_presses: Default::default(),
             share_ref: Default::default(),
         })
     }
@@ -56,6 +63,30 @@ impl<const NUM_BUTTONS: usize> Buttons<NUM_BUTTONS> {
             .ok_or(ErrorCode::Invalid)
     }
 
+    /// Queues states for `button`. Each read of the button first applies the
+    /// next queued state, as if by `set_pressed` (so it triggers an interrupt
+    /// if enabled), and then returns it. Once the queue runs out, the button
+    /// keeps its last state.
+    pub fn queue_presses(&self, button: u32, presses: &[bool]) {
+        self.queued_presses
+            .borrow_mut()
+            .entry(button)
+            .or_default()
+            .extend(presses);
+    }
+
+    /// Schedules an interrupt upcall for `button` reporting `pressed`,
+    /// regardless of the button's state and whether its interrupt is enabled.
+    pub fn inject_interrupt(&self, button: u32, pressed: bool) -> Result<(), ErrorCode> {
+        if button as usize >= NUM_BUTTONS {
+            return Err(ErrorCode::Invalid);
+        }
+        self.share_ref
+            .schedule_upcall(0, (button, pressed as u32, 0))
+            .expect("Unable to schedule upcall");
+        Ok(())
+    }
+
     pub fn get_button_state(&self, button: u32) -> Option<ButtonState> {
         self.buttons.get(button as usize).map(|button| button.get())
     }
@@ -99,6 +130,14 @@ impl<const NUM_BUTTONS: usize> crate::fake::SyscallDriver for Buttons<NUM_BUTTON
             }
             BUTTONS_READ => {
                 if argument0 < NUM_BUTTONS as u32 {
+                    let pressed = self
+                        .queued_presses
+                        .borrow_mut()
+                        .get_mut(&argument0)
+                        .and_then(VecDeque::pop_front);
+                    if let Some(pressed) = pressed {
+                        let _ = self.set_pressed(argument0, pressed);
+                    }
                     crate::command_return::success_u32(
                         self.buttons[argument0 as usize].get().pressed as u32,
                     )
@@ -110,3 +110,42 @@ fn kernel_integration() {
     );
     assert!(fake::Syscalls::command(DRIVER_NUM, BUTTONS_DISABLE_INTERRUPTS, 0, 0).is_success());
 }
+
+// Tests queue_presses and inject_interrupt.
+#[test]
+fn scripted_presses() {
+    use libtock_platform::{share, DefaultConfig, Syscalls};
+    let kernel = fake::Kernel::new();
+    let buttons = Buttons::<2>::new();
+    kernel.add_driver(&buttons);
+    assert_eq!(buttons.inject_interrupt(2, true), Err(ErrorCode::Invalid));
+
+    buttons.queue_presses(1, &[true, true, false]);
+    let interrupt: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &interrupt)
+            .unwrap();
+        let read = || fake::Syscalls::command(DRIVER_NUM, BUTTONS_READ, 1, 0);
+        assert_eq!(read().get_success_u32(), Some(1));
+        assert!(fake::Syscalls::command(DRIVER_NUM, BUTTONS_ENABLE_INTERRUPTS, 1, 0).is_success());
+        assert_eq!(read().get_success_u32(), Some(1));
+        assert!(!fake::Kernel::is_upcall_pending());
+        assert_eq!(read().get_success_u32(), Some(0));
+        fake::Syscalls::yield_wait();
+        assert_eq!(interrupt.take(), Some((1, 0)));
+        assert_eq!(read().get_success_u32(), Some(0));
+
+        // Injected interrupts are delivered even while disabled.
+        assert!(fake::Syscalls::command(DRIVER_NUM, BUTTONS_DISABLE_INTERRUPTS, 1, 0).is_success());
+        assert_eq!(buttons.inject_interrupt(1, true), Ok(()));
+        fake::Syscalls::yield_wait();
+        assert_eq!(interrupt.take(), Some((1, 1)));
+        assert_eq!(
+            buttons.get_button_state(1),
+            Some(ButtonState {
+                pressed: false,
+                interrupt_enabled: false
+            })
+        );
+    });
+}
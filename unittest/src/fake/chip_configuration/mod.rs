@@ -0,0 +1,198 @@
+//! Fake implementation of the chip configuration driver.
+//!
+//! `ChipConfiguration` reports the device identity and clock configuration set
+//! by the test. Every value can be set to `None`, which makes the corresponding
+//! command fail with `NoSupport`, as on a chip that lacks that information.
+//! The defaults describe an nRF52840 DK.
+
+use crate::{command_return, DriverInfo, RwAllowBuffer};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct ChipConfiguration {
+    ieee_mac: Cell<Option<u64>>,
+    serial_number: Cell<Option<u64>>,
+    hardware_revision: Cell<Option<u32>>,
+    flash_size: Cell<Option<u32>>,
+    ram_size: Cell<Option<u32>>,
+    reset_reason: Cell<Option<u32>>,
+    system_clock_hz: Cell<Option<u32>>,
+    alarm_clock_hz: Cell<Option<u32>>,
+    clock_source: Cell<Option<u32>>,
+    board_name: RefCell<Option<Vec<u8>>>,
+    device_id: RefCell<Option<Vec<u8>>>,
+    board_name_buffer: RefCell<RwAllowBuffer>,
+    device_id_buffer: RefCell<RwAllowBuffer>,
+    driver_num: u32,
+}
+
+impl ChipConfiguration {
+    pub fn new() -> std::rc::Rc<ChipConfiguration> {
+        Self::new_with_driver_num(DRIVER_NUM)
+    }
+
+    /// Creates a driver registered under `driver_num` instead of the default
+    /// number, as on boards that number the capsule differently.
+    pub fn new_with_driver_num(driver_num: u32) -> std::rc::Rc<ChipConfiguration> {
+        std::rc::Rc::new(ChipConfiguration {
+            ieee_mac: Cell::new(Some(0xf4ce_3600_0000_0001)),
+            serial_number: Cell::new(Some(0x0000_1234_5678_9abc)),
+            hardware_revision: Cell::new(Some(3)),
+            flash_size: Cell::new(Some(1024 * 1024)),
+            ram_size: Cell::new(Some(256 * 1024)),
+            reset_reason: Cell::new(Some(RESET_POWER_ON)),
+            system_clock_hz: Cell::new(Some(64_000_000)),
+            alarm_clock_hz: Cell::new(Some(32_768)),
+            clock_source: Cell::new(Some(CLOCK_EXTERNAL_CRYSTAL)),
+            board_name: RefCell::new(Some(b"nrf52840dk".to_vec())),
+            device_id: RefCell::new(Some(vec![0xd0, 0x5a, 0x11, 0x7e, 0x00, 0x42, 0xbe, 0xef])),
+            board_name_buffer: Default::default(),
+            device_id_buffer: Default::default(),
+            driver_num,
+        })
+    }
+
+    /// Sets the IEEE 802.15.4 MAC address, with its first byte most
+    /// significant.
+    pub fn set_ieee_mac(&self, ieee_mac: Option<u64>) {
+        self.ieee_mac.set(ieee_mac);
+    }
+
+    pub fn set_serial_number(&self, serial_number: Option<u64>) {
+        self.serial_number.set(serial_number);
+    }
+
+    pub fn set_hardware_revision(&self, hardware_revision: Option<u32>) {
+        self.hardware_revision.set(hardware_revision);
+    }
+
+    pub fn set_flash_size(&self, flash_size: Option<u32>) {
+        self.flash_size.set(flash_size);
+    }
+
+    pub fn set_ram_size(&self, ram_size: Option<u32>) {
+        self.ram_size.set(ram_size);
+    }
+
+    /// Sets the raw reset reason (0: power-on, 1: reset pin, 2: watchdog,
+    /// 3: software, 4: brownout).
+    pub fn set_reset_reason(&self, reset_reason: Option<u32>) {
+        self.reset_reason.set(reset_reason);
+    }
+
+    pub fn set_system_clock_hz(&self, system_clock_hz: Option<u32>) {
+        self.system_clock_hz.set(system_clock_hz);
+    }
+
+    pub fn set_alarm_clock_hz(&self, alarm_clock_hz: Option<u32>) {
+        self.alarm_clock_hz.set(alarm_clock_hz);
+    }
+
+    /// Sets the raw clock source (0: internal RC, 1: external crystal, 2: PLL).
+    pub fn set_clock_source(&self, clock_source: Option<u32>) {
+        self.clock_source.set(clock_source);
+    }
+
+    pub fn set_board_name(&self, board_name: Option<&str>) {
+        self.board_name
+            .replace(board_name.map(|name| name.as_bytes().into()));
+    }
+
+    pub fn set_device_id(&self, device_id: Option<&[u8]>) {
+        self.device_id.replace(device_id.map(Into::into));
+    }
+}
+
+impl crate::fake::SyscallDriver for ChipConfiguration {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(self.driver_num)
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_BOARD_NAME => Ok(self.board_name_buffer.replace(buffer)),
+            ALLOW_DEVICE_ID => Ok(self.device_id_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_id: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
+        let value = match command_id {
+            EXISTS => return command_return::success(),
+            IEEE_MAC => return u64_return(self.ieee_mac.get()),
+            SERIAL_NUMBER => return u64_return(self.serial_number.get()),
+            BOARD_NAME => return read(&self.board_name, &self.board_name_buffer),
+            DEVICE_ID => return read(&self.device_id, &self.device_id_buffer),
+            HARDWARE_REVISION => self.hardware_revision.get(),
+            FLASH_SIZE => self.flash_size.get(),
+            RAM_SIZE => self.ram_size.get(),
+            RESET_REASON => self.reset_reason.get(),
+            SYSTEM_CLOCK_HZ => self.system_clock_hz.get(),
+            ALARM_CLOCK_HZ => self.alarm_clock_hz.get(),
+            CLOCK_SOURCE => self.clock_source.get(),
+            _ => None,
+        };
+        match value {
+            Some(value) => command_return::success_u32(value),
+            None => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+fn u64_return(value: Option<u64>) -> CommandReturn {
+    match value {
+        Some(value) => command_return::success_u64(value),
+        None => command_return::failure(ErrorCode::NoSupport),
+    }
+}
+
+// Copies as much of `data` as fits into `buffer`, and returns the full length.
+fn read(data: &RefCell<Option<Vec<u8>>>, buffer: &RefCell<RwAllowBuffer>) -> CommandReturn {
+    let data = data.borrow();
+    let Some(data) = data.as_ref() else {
+        return command_return::failure(ErrorCode::NoSupport);
+    };
+    let mut buffer = buffer.borrow_mut();
+    let len = buffer.len().min(data.len());
+    buffer[..len].copy_from_slice(&data[..len]);
+    command_return::success_u32(data.len() as u32)
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90067;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const IEEE_MAC: u32 = 1;
+const SERIAL_NUMBER: u32 = 2;
+const HARDWARE_REVISION: u32 = 3;
+const FLASH_SIZE: u32 = 4;
+const RAM_SIZE: u32 = 5;
+const BOARD_NAME: u32 = 6;
+const RESET_REASON: u32 = 7;
+const DEVICE_ID: u32 = 8;
+const SYSTEM_CLOCK_HZ: u32 = 9;
+const ALARM_CLOCK_HZ: u32 = 10;
+const CLOCK_SOURCE: u32 = 11;
+
+// Allow IDs
+const ALLOW_BOARD_NAME: u32 = 0;
+const ALLOW_DEVICE_ID: u32 = 1;
+
+// Raw values of the defaults.
+const RESET_POWER_ON: u32 = 0;
+const CLOCK_EXTERNAL_CRYSTAL: u32 = 1;
@@ -0,0 +1,77 @@
+use crate::fake::{self, chip_configuration::*};
+use libtock_platform::{share, DefaultConfig, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let chip = ChipConfiguration::new();
+    assert!(chip.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        chip.command(IEEE_MAC, 0, 0).get_success_u64(),
+        Some(0xf4ce_3600_0000_0001)
+    );
+    assert_eq!(
+        chip.command(CLOCK_SOURCE, 0, 0).get_success_u32(),
+        Some(CLOCK_EXTERNAL_CRYSTAL)
+    );
+    assert_eq!(
+        chip.command(12, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+
+    chip.set_serial_number(Some(7));
+    chip.set_ram_size(Some(64 * 1024));
+    assert_eq!(chip.command(SERIAL_NUMBER, 0, 0).get_success_u64(), Some(7));
+    assert_eq!(
+        chip.command(RAM_SIZE, 0, 0).get_success_u32(),
+        Some(64 * 1024)
+    );
+
+    // Unset values are unsupported.
+    chip.set_serial_number(None);
+    chip.set_reset_reason(None);
+    chip.set_device_id(None);
+    for command_id in [SERIAL_NUMBER, RESET_REASON, DEVICE_ID] {
+        assert_eq!(
+            chip.command(command_id, 0, 0).get_failure(),
+            Some(ErrorCode::NoSupport)
+        );
+    }
+    // Without a buffer, the length is still reported.
+    assert_eq!(chip.command(BOARD_NAME, 0, 0).get_success_u32(), Some(10));
+}
+
+// Integration test that verifies ChipConfiguration works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let chip = ChipConfiguration::new();
+    kernel.add_driver(&chip);
+    chip.set_board_name(Some("mote"));
+    chip.set_device_id(Some(&[1, 2, 3]));
+
+    let mut name = [0; 3];
+    let mut id = [0; 4];
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_BOARD_NAME>(
+            allow_rw, &mut name,
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, BOARD_NAME, 0, 0).get_success_u32(),
+            Some(4)
+        );
+    });
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_DEVICE_ID>(allow_rw, &mut id)
+            .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, DEVICE_ID, 0, 0).get_success_u32(),
+            Some(3)
+        );
+    });
+    assert_eq!(&name, b"mot");
+    assert_eq!(id, [1, 2, 3, 0]);
+}
@@ -4,9 +4,20 @@
 //! Like the real API, `Console` stores each message written to it.
 //! The resulting byte stream can be retrieved via `take_bytes`
 //! for use in unit tests.
+//!
+//! Input is queued with `new_with_input` or `queue_input`. A READ command
+//! completes immediately if input is available. Otherwise, the read stays
+//! pending until input is queued, and the bytes are then copied into whichever
+//! buffer is allowed at that point. `set_read_chunk_size` limits how many bytes
+//! each READ upcall delivers, to simulate input trickling in, and
+//! `set_write_chunk_size` how many bytes each WRITE prints, to simulate a
+//! driver with a small transmit buffer. `set_write_stalled` makes WRITEs never
+//! complete, to simulate a wedged UART. `set_max_write_len` makes the console
+//! reject longer WRITEs, and report the maximum, like a lite console.
+//! `answer_when_idle` queues input whenever the process waits, like a peer
+//! answering it.
 
 use core::cell::{Cell, RefCell};
-use core::cmp;
 use libtock_platform::{CommandReturn, ErrorCode};
 
 use crate::{DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
@@ -18,6 +29,13 @@ pub struct Console {
     read_buffer: RefCell<RwAllowBuffer>,
     /// To be returned on read
     input: Cell<Vec<u8>>,
+    /// Number of bytes requested by a READ command that has not completed.
+    pending_read: Cell<Option<usize>>,
+    read_chunk_size: Cell<Option<usize>>,
+    write_chunk_size: Cell<Option<usize>>,
+    write_stalled: Cell<bool>,
+    max_write_len: Cell<Option<usize>>,
+    driver_num: u32,
 
     share_ref: DriverShareRef,
 }
@@ -28,11 +46,23 @@ impl Console {
     }
 
     pub fn new_with_input(inputs: &[u8]) -> std::rc::Rc<Console> {
+        Self::new_with_driver_num(inputs, DRIVER_NUM)
+    }
+
+    /// Creates a console registered under `driver_num` instead of the default
+    /// number, as a board's console-compatible driver may be.
+    pub fn new_with_driver_num(inputs: &[u8], driver_num: u32) -> std::rc::Rc<Console> {
         std::rc::Rc::new(Console {
             messages: Default::default(),
             buffer: Default::default(),
             read_buffer: Default::default(),
             input: Cell::new(Vec::from(inputs)),
+            pending_read: Cell::new(None),
+            read_chunk_size: Cell::new(None),
+            write_chunk_size: Cell::new(None),
+            write_stalled: Cell::new(false),
+            max_write_len: Cell::new(None),
+            driver_num,
             share_ref: Default::default(),
         })
     }
@@ -42,11 +72,99 @@ impl Console {
     pub fn take_bytes(&self) -> Vec<u8> {
         self.messages.take()
     }
+
+    /// Adds bytes to the input. If a read is pending, it is completed using
+    /// the new input.
+    pub fn queue_input(&self, bytes: &[u8]) {
+        let mut input = self.input.take();
+        input.extend_from_slice(bytes);
+        self.input.set(input);
+        self.deliver_input();
+    }
+
+    /// Makes the console's peer answer with each of `answers` in turn whenever
+    /// the process waits with no upcall queued, by setting `kernel`'s idle
+    /// handler. Once the answers run out, waiting advances `alarm` by 1000
+    /// ticks instead, so that timeouts expire.
+    pub fn answer_when_idle(
+        self: &std::rc::Rc<Self>,
+        kernel: &crate::fake::Kernel,
+        alarm: &std::rc::Rc<crate::fake::Alarm>,
+        answers: &[&[u8]],
+    ) {
+        let answers: std::collections::VecDeque<_> =
+            answers.iter().map(|answer| answer.to_vec()).collect();
+        let answers = RefCell::new(answers);
+        kernel.set_idle_handler({
+            let (console, alarm) = (self.clone(), alarm.clone());
+            move || match answers.borrow_mut().pop_front() {
+                Some(answer) => console.queue_input(&answer),
+                None => alarm.advance_ticks(1000),
+            }
+        });
+    }
+
+    /// Limits the number of bytes each READ upcall delivers. `None` (the
+    /// default) delivers as many bytes as are available and fit.
+    pub fn set_read_chunk_size(&self, chunk_size: Option<usize>) {
+        self.read_chunk_size.set(chunk_size);
+    }
+
+    /// Limits the number of bytes each WRITE prints, and reports in its
+    /// upcall. `None` (the default) prints all the bytes requested.
+    pub fn set_write_chunk_size(&self, chunk_size: Option<usize>) {
+        self.write_chunk_size.set(chunk_size);
+    }
+
+    /// While `stalled`, WRITEs succeed but print nothing and never complete.
+    pub fn set_write_stalled(&self, stalled: bool) {
+        self.write_stalled.set(stalled);
+    }
+
+    /// Makes WRITEs longer than `max_len` fail with `ErrorCode::Size`. The
+    /// console then reports the `MAX_LEN` and `ABORT` features, and answers
+    /// GET_MAX_LEN with `max_len`.
+    pub fn set_max_write_len(&self, max_len: Option<usize>) {
+        self.max_write_len.set(max_len);
+    }
+
+    /// Returns the number of bytes requested by the pending read, or `None` if
+    /// no read is pending.
+    pub fn pending_read(&self) -> Option<usize> {
+        self.pending_read.get()
+    }
+
+    // Completes the pending read, if there is one and input is available.
+    fn deliver_input(&self) {
+        let Some(count_wanted) = self.pending_read.get() else {
+            return;
+        };
+        let mut input = self.input.take();
+        if !input.is_empty() {
+            let mut buffer = self.read_buffer.borrow_mut();
+            let count = [
+                count_wanted,
+                input.len(),
+                buffer.len(),
+                self.read_chunk_size.get().unwrap_or(usize::MAX),
+            ]
+            .into_iter()
+            .min()
+            .unwrap();
+            buffer[..count].copy_from_slice(&input[..count]);
+            input.drain(..count);
+            self.pending_read.set(None);
+            self.share_ref
+                .schedule_upcall(SUBSCRIBE_READ, (0, count as u32, 0))
+                .expect("Unable to schedule upcall {}");
+        }
+        self.input.set(input);
+    }
 }
 
 impl crate::fake::SyscallDriver for Console {
     fn info(&self) -> DriverInfo {
-        DriverInfo::new(DRIVER_NUM).upcall_count(3)
+        DriverInfo::new(self.driver_num).upcall_count(3)
     }
 
     fn register(&self, share_ref: DriverShareRef) {
@@ -79,11 +197,30 @@ impl crate::fake::SyscallDriver for Console {
 
     fn command(&self, command_num: u32, argument0: u32, _argument1: u32) -> CommandReturn {
         match command_num {
+            EXISTS if self.max_write_len.get().is_some() => {
+                return crate::command_return::success_2_u32(1, FEATURE_ABORT | FEATURE_MAX_LEN);
+            }
             EXISTS => {}
+            WRITE
+                if self
+                    .max_write_len
+                    .get()
+                    .is_some_and(|max| argument0 as usize > max) =>
+            {
+                return crate::command_return::failure(ErrorCode::Size);
+            }
+            WRITE if self.write_stalled.get() => {}
             WRITE => {
                 let mut bytes = self.messages.take();
                 let buffer = self.buffer.take();
-                let size = cmp::min(buffer.len(), argument0 as usize);
+                let size = [
+                    buffer.len(),
+                    argument0 as usize,
+                    self.write_chunk_size.get().unwrap_or(usize::MAX),
+                ]
+                .into_iter()
+                .min()
+                .unwrap();
                 bytes.extend_from_slice(&(*buffer)[..size]);
                 self.buffer.set(buffer);
                 self.messages.set(bytes);
@@ -92,19 +229,23 @@ impl crate::fake::SyscallDriver for Console {
                     .expect("Unable to schedule upcall {}");
             }
             READ => {
-                let count_wanted = argument0 as usize;
-                let bytes = self.input.take();
-                let count_wanted = cmp::min(count_wanted, bytes.len());
-                let to_send = &bytes[..count_wanted];
-                let to_keep = &bytes[count_wanted..];
-                self.input.set(Vec::from(to_keep));
-
-                let count_available = to_send.len();
-                self.read_buffer.borrow_mut()[..count_wanted].copy_from_slice(to_send);
-                self.share_ref
-                    .schedule_upcall(SUBSCRIBE_READ, (0, count_available as u32, 0))
-                    .expect("Unable to schedule upcall {}");
+                if self.pending_read.get().is_some() {
+                    return crate::command_return::failure(ErrorCode::Busy);
+                }
+                self.pending_read.set(Some(argument0 as usize));
+                self.deliver_input();
             }
+            ABORT => {
+                if self.pending_read.take().is_some() {
+                    self.share_ref
+                        .schedule_upcall(SUBSCRIBE_READ, (ErrorCode::Cancel as u32, 0, 0))
+                        .expect("Unable to schedule upcall {}");
+                }
+            }
+            GET_MAX_LEN => match self.max_write_len.get() {
+                Some(max) => return crate::command_return::success_u32(max as u32),
+                None => return crate::command_return::failure(ErrorCode::NoSupport),
+            },
             _ => return crate::command_return::failure(ErrorCode::NoSupport),
         }
         crate::command_return::success()
@@ -124,7 +265,13 @@ const DRIVER_NUM: u32 = 1;
 const EXISTS: u32 = 0;
 const WRITE: u32 = 1;
 const READ: u32 = 2;
-//const ABORT: u32 = 3;
+const ABORT: u32 = 3;
+const GET_MAX_LEN: u32 = 4;
+
+// Feature bits
+const FEATURE_ABORT: u32 = 1 << 0;
+const FEATURE_MAX_LEN: u32 = 1 << 2;
+
 const SUBSCRIBE_WRITE: u32 = 1;
 const SUBSCRIBE_READ: u32 = 2;
 const ALLOW_WRITE: u32 = 1;
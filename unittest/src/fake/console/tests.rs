@@ -1,7 +1,7 @@
 use crate::fake;
 use crate::{RoAllowBuffer, RwAllowBuffer};
 use libtock_platform::share;
-use libtock_platform::DefaultConfig;
+use libtock_platform::{DefaultConfig, ErrorCode, YieldNoWaitReturn};
 
 // Tests the command implementation.
 #[test]
@@ -16,6 +16,18 @@ fn command() {
     assert!(console
         .allow_readwrite(2, RwAllowBuffer::default())
         .is_err());
+
+    console.set_max_write_len(Some(4));
+    assert_eq!(
+        console
+            .command(fake::console::GET_MAX_LEN, 0, 0)
+            .get_success_u32(),
+        Some(4)
+    );
+    assert_eq!(
+        console.command(fake::console::WRITE, 5, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
 }
 
 // Integration test that verifies Console works with fake::Kernel and
@@ -45,6 +57,39 @@ fn kernel_integration() {
     assert_eq!(console.take_bytes(), b"abc");
     assert_eq!(console.take_bytes(), b"");
 
+    // Each WRITE prints at most one chunk.
+    console.set_write_chunk_size(Some(2));
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<
+            DefaultConfig,
+            { fake::console::DRIVER_NUM },
+            { fake::console::ALLOW_WRITE },
+        >(allow_ro, b"abcd")
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(fake::console::DRIVER_NUM, fake::console::WRITE, 4, 0)
+                .is_success()
+        );
+    });
+    assert_eq!(console.take_bytes(), b"ab");
+
+    // A stalled WRITE prints nothing.
+    console.set_write_stalled(true);
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<
+            DefaultConfig,
+            { fake::console::DRIVER_NUM },
+            { fake::console::ALLOW_WRITE },
+        >(allow_ro, b"abcd")
+        .unwrap();
+        assert!(
+            fake::Syscalls::command(fake::console::DRIVER_NUM, fake::console::WRITE, 4, 0)
+                .is_success()
+        );
+    });
+    assert_eq!(console.take_bytes(), b"");
+    console.set_write_stalled(false);
+
     let mut buf = [0; 4];
 
     share::scope(|allow_rw| {
@@ -60,3 +105,75 @@ fn kernel_integration() {
         );
     });
 }
+
+// Tests input injection: pending reads, chunking, and re-allowing the buffer
+// while a read is pending.
+#[test]
+fn queue_input() {
+    use libtock_platform::{ErrorCode, Syscalls};
+    let kernel = fake::Kernel::new();
+    let console = fake::Console::new();
+    kernel.add_driver(&console);
+    let read =
+        |len| fake::Syscalls::command(fake::console::DRIVER_NUM, fake::console::READ, len, 0);
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+
+    let mut buf1 = [0; 4];
+    let mut buf2 = [0; 4];
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<
+            _,
+            _,
+            DefaultConfig,
+            { fake::console::DRIVER_NUM },
+            { fake::console::SUBSCRIBE_READ },
+        >(subscribe, &upcall)
+        .unwrap();
+
+        // Without input, the read stays pending.
+        share::scope(|allow_rw| {
+            fake::Syscalls::allow_rw::<
+                DefaultConfig,
+                { fake::console::DRIVER_NUM },
+                { fake::console::ALLOW_READ },
+            >(allow_rw, &mut buf1)
+            .unwrap();
+            assert!(read(4).is_success());
+            assert_eq!(read(4).get_failure(), Some(ErrorCode::Busy));
+        });
+        assert_eq!(console.pending_read(), Some(4));
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        // The input goes into the buffer allowed when it arrives, and at most
+        // one chunk is delivered per upcall.
+        console.set_read_chunk_size(Some(3));
+        share::scope(|allow_rw| {
+            fake::Syscalls::allow_rw::<
+                DefaultConfig,
+                { fake::console::DRIVER_NUM },
+                { fake::console::ALLOW_READ },
+            >(allow_rw, &mut buf2)
+            .unwrap();
+            console.queue_input(b"hello");
+            assert_eq!(console.pending_read(), None);
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(upcall.take(), Some((0, 3)));
+
+            // The rest of the input is available to the next read.
+            assert!(read(4).is_success());
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(upcall.take(), Some((0, 2)));
+
+            // Aborting a pending read completes it with CANCEL.
+            assert!(read(4).is_success());
+            assert!(
+                fake::Syscalls::command(fake::console::DRIVER_NUM, fake::console::ABORT, 0, 0)
+                    .is_success()
+            );
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(upcall.take(), Some((ErrorCode::Cancel as u32, 0)));
+        });
+    });
+    assert_eq!(buf1, [0; 4]);
+    assert_eq!(&buf2, b"lol\0");
+}
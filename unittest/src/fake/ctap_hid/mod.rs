@@ -0,0 +1,143 @@
+//! Fake implementation of the CTAP HID driver, as expected by
+//! `libtock_ctap_hid`.
+//!
+//! The fake plays the host too. Reports from the host are queued with
+//! `host_send`, and each completes the pending receive, immediately if one is
+//! pending or as soon as one is. Reports to the host are read by the host as
+//! soon as they are sent, and retrieved with `take_host_received`.
+
+use core::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{DriverInfo, DriverShareRef, RwAllowBuffer};
+
+pub struct CtapHid {
+    receive_buffer: RefCell<RwAllowBuffer>,
+    send_buffer: RefCell<RwAllowBuffer>,
+    receive_pending: Cell<bool>,
+    host_sent: RefCell<VecDeque<[u8; REPORT_LEN]>>,
+    host_received: RefCell<Vec<[u8; REPORT_LEN]>>,
+    share_ref: DriverShareRef,
+}
+
+impl CtapHid {
+    pub fn new() -> std::rc::Rc<CtapHid> {
+        std::rc::Rc::new(CtapHid {
+            receive_buffer: Default::default(),
+            send_buffer: Default::default(),
+            receive_pending: Cell::new(false),
+            host_sent: Default::default(),
+            host_received: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Queues a report from the host.
+    pub fn host_send(&self, report: &[u8; REPORT_LEN]) {
+        self.host_sent.borrow_mut().push_back(*report);
+        self.deliver();
+    }
+
+    /// Returns the reports the host has received so far, and clears them.
+    pub fn take_host_received(&self) -> Vec<[u8; REPORT_LEN]> {
+        self.host_received.take()
+    }
+
+    /// Returns `true` if a receive is pending.
+    pub fn receive_pending(&self) -> bool {
+        self.receive_pending.get()
+    }
+
+    // Completes the pending receive with the next report from the host, if
+    // both exist.
+    fn deliver(&self) {
+        if !self.receive_pending.get() {
+            return;
+        }
+        let Some(report) = self.host_sent.borrow_mut().pop_front() else {
+            return;
+        };
+        let mut buffer = self.receive_buffer.borrow_mut();
+        let len = buffer.len().min(REPORT_LEN);
+        buffer[..len].copy_from_slice(&report[..len]);
+        self.receive_pending.set(false);
+        self.share_ref
+            .schedule_upcall(SUBSCRIBE_TRANSFER, (UPCALL_RECEIVED, 0, 0))
+            .expect("Unable to schedule upcall");
+    }
+}
+
+impl crate::fake::SyscallDriver for CtapHid {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_RECEIVE => Ok(self.receive_buffer.replace(buffer)),
+            ALLOW_SEND => Ok(self.send_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_num: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_num {
+            EXISTS => {}
+            SEND => {
+                let buffer = self.send_buffer.borrow();
+                if buffer.len() < REPORT_LEN {
+                    return crate::command_return::failure(ErrorCode::Size);
+                }
+                let mut report = [0; REPORT_LEN];
+                report.copy_from_slice(&buffer[..REPORT_LEN]);
+                self.host_received.borrow_mut().push(report);
+                self.share_ref
+                    .schedule_upcall(SUBSCRIBE_TRANSFER, (UPCALL_SENT, 0, 0))
+                    .expect("Unable to schedule upcall");
+            }
+            RECEIVE => {
+                if self.receive_pending.get() {
+                    return crate::command_return::failure(ErrorCode::Busy);
+                }
+                self.receive_pending.set(true);
+                self.deliver();
+            }
+            CANCEL => self.receive_pending.set(false),
+            _ => return crate::command_return::failure(ErrorCode::NoSupport),
+        }
+        crate::command_return::success()
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x20009;
+
+const REPORT_LEN: usize = 64;
+
+const EXISTS: u32 = 0;
+const SEND: u32 = 1;
+const RECEIVE: u32 = 2;
+const CANCEL: u32 = 4;
+
+const SUBSCRIBE_TRANSFER: u32 = 0;
+const UPCALL_RECEIVED: u32 = 0;
+const UPCALL_SENT: u32 = 1;
+
+const ALLOW_RECEIVE: u32 = 0;
+const ALLOW_SEND: u32 = 1;
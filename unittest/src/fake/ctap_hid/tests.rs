@@ -0,0 +1,56 @@
+use crate::fake::{self, SyscallDriver};
+use fake::ctap_hid::*;
+use libtock_platform::{share, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn};
+
+#[test]
+fn command() {
+    let ctap = CtapHid::new();
+    assert!(ctap.command(EXISTS, 0, 0).is_success());
+    assert!(ctap.command(RECEIVE, 0, 0).is_success());
+    assert!(ctap.receive_pending());
+    assert_eq!(
+        ctap.command(RECEIVE, 0, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    assert!(ctap.command(CANCEL, 0, 0).is_success());
+    assert!(!ctap.receive_pending());
+    // Nothing to send.
+    assert_eq!(
+        ctap.command(SEND, 0, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
+}
+
+// Integration test that verifies CtapHid works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let ctap = CtapHid::new();
+    kernel.add_driver(&ctap);
+
+    let mut buffer = [0; REPORT_LEN];
+    let done = core::cell::Cell::<Option<(u32,)>>::new(None);
+    share::scope::<
+        (
+            AllowRw<_, DRIVER_NUM, ALLOW_RECEIVE>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_TRANSFER>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_RECEIVE>(allow_rw, &mut buffer)
+            .unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_TRANSFER>(
+            subscribe, &done,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, RECEIVE, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        ctap.host_send(&[5; REPORT_LEN]);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(done.get(), Some((UPCALL_RECEIVED,)));
+    });
+    assert_eq!(buffer, [5; REPORT_LEN]);
+}
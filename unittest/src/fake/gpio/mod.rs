@@ -6,9 +6,14 @@
 //! status of a button.
 //!
 //! It also provides the function `set_pressed` that set the button's state.
+//!
+//! Tests can script the level a pin will have on successive reads with
+//! `queue_levels`, and deliver an interrupt without changing the pin's level
+//! (e.g. to simulate contact bounce) with `inject_interrupt`.
 
-use core::cell::Cell;
+use core::cell::{Cell, RefCell};
 use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::{HashMap, VecDeque};
 use std::convert::TryFrom;
 
 use crate::{DriverInfo, DriverShareRef};
@@ -69,6 +74,7 @@ pub struct GpioState {
 
 pub struct Gpio<const NUM_GPIOS: usize> {
     gpios: [Cell<Option<GpioState>>; NUM_GPIOS],
+    queued_levels: RefCell<HashMap<u32, VecDeque<bool>>>,
     share_ref: DriverShareRef,
 }
 
@@ -82,6 +88,7 @@ impl<const NUM_GPIOS: usize> Gpio<NUM_GPIOS> {
         }));
         std::rc::Rc::new(Gpio {
             gpios: [OFF; NUM_GPIOS],
+            queued_levels: Default::default(),
             share_ref: Default::default(),
         })
     }
@@ -128,6 +135,33 @@ impl<const NUM_GPIOS: usize> Gpio<NUM_GPIOS> {
             .and_then(|value| value)
     }
 
+    /// Queues levels for `pin`. Each read of the pin first applies the next
+    /// queued level, as if by `set_value` (so it triggers interrupts as
+    /// configured), and then returns it. Once the queue runs out, the pin keeps
+    /// its last level.
+    pub fn queue_levels(&self, pin: u32, levels: &[bool]) {
+        self.queued_levels
+            .borrow_mut()
+            .entry(pin)
+            .or_default()
+            .extend(levels);
+    }
+
+    /// Schedules an interrupt upcall for `pin` reporting `value`, regardless of
+    /// the pin's level and interrupt configuration.
+    pub fn inject_interrupt(&self, pin: u32, value: bool) -> Result<(), ErrorCode> {
+        match self.get_gpio_state(pin) {
+            None if pin as usize >= NUM_GPIOS => Err(ErrorCode::Invalid),
+            None => Err(ErrorCode::NoDevice),
+            Some(_) => {
+                self.share_ref
+                    .schedule_upcall(0, (pin, value as u32, 0))
+                    .expect("Unable to schedule upcall");
+                Ok(())
+            }
+        }
+    }
+
     pub fn get_gpio_state(&self, button: u32) -> Option<GpioState> {
         self.gpios
             .get(button as usize)
@@ -151,6 +185,16 @@ impl<const NUM_GPIOS: usize> crate::fake::SyscallDriver for Gpio<NUM_GPIOS> {
         } else if command_number == GPIO_COUNT {
             crate::command_return::success_u32(NUM_GPIOS as u32)
         } else if argument0 < NUM_GPIOS as u32 {
+            if command_number == GPIO_READ_INPUT {
+                let level = self
+                    .queued_levels
+                    .borrow_mut()
+                    .get_mut(&argument0)
+                    .and_then(VecDeque::pop_front);
+                if let Some(level) = level {
+                    let _ = self.set_value(argument0, level);
+                }
+            }
             if self.gpios[argument0 as usize].get().is_some() {
                 let gpio = self.gpios[argument0 as usize].get().unwrap();
                 match command_number {
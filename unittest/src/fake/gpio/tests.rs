@@ -401,3 +401,41 @@ fn kernel_integration() {
     assert!(fake::Syscalls::command(DRIVER_NUM, GPIO_DISABLE, 0, 0).is_success());
     assert_eq!(gpio.get_gpio_state(0).unwrap().mode, GpioMode::Disable);
 }
+
+// Tests queue_levels and inject_interrupt.
+#[test]
+fn scripted_levels() {
+    use libtock_platform::{share, DefaultConfig, Syscalls};
+    let kernel = fake::Kernel::new();
+    let gpio = Gpio::<2>::new();
+    gpio.set_missing_gpio(1);
+    kernel.add_driver(&gpio);
+    assert_eq!(gpio.inject_interrupt(1, true), Err(ErrorCode::NoDevice));
+    assert_eq!(gpio.inject_interrupt(2, true), Err(ErrorCode::Invalid));
+
+    gpio.queue_levels(0, &[true, false]);
+    assert!(fake::Syscalls::command(DRIVER_NUM, GPIO_ENABLE_INTERRUPTS, 0, 1).is_success());
+    let interrupt: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &interrupt)
+            .unwrap();
+        let read = || fake::Syscalls::command(DRIVER_NUM, GPIO_READ_INPUT, 0, 0);
+        assert_eq!(read().get_success_u32(), Some(1));
+        // The rising edge caused by the first scripted level triggers the
+        // interrupt; the falling edge does not.
+        assert_eq!(
+            fake::Syscalls::yield_no_wait(),
+            libtock_platform::YieldNoWaitReturn::Upcall
+        );
+        assert_eq!(interrupt.take(), Some((0, 1)));
+        assert_eq!(read().get_success_u32(), Some(0));
+        assert!(!fake::Kernel::is_upcall_pending());
+        // The queue is exhausted, so the pin keeps its level.
+        assert_eq!(read().get_success_u32(), Some(0));
+
+        assert_eq!(gpio.inject_interrupt(0, true), Ok(()));
+        fake::Syscalls::yield_wait();
+        assert_eq!(interrupt.take(), Some((0, 1)));
+        assert!(!gpio.get_gpio_state(0).unwrap().value);
+    });
+}
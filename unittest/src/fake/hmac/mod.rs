@@ -0,0 +1,67 @@
+//! Fake implementation of the HMAC driver, following the system call interface
+//! of Tock's HMAC capsule. MACs are computed in software, as soon as the
+//! process requests them.
+//!
+//! The system call interface is the same as `fake::Sha`'s (see there), with
+//! the addition of Read-Only Allow 0, which holds the key. The key is read
+//! whenever a MAC is computed.
+
+use super::sha::{DigestEngine, ShaAlgorithm};
+use crate::{DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct Hmac {
+    engine: DigestEngine,
+}
+
+impl Hmac {
+    pub fn new() -> std::rc::Rc<Hmac> {
+        std::rc::Rc::new(Hmac {
+            engine: DigestEngine::new(true),
+        })
+    }
+
+    /// Returns the algorithm selected by the process, if any.
+    pub fn algorithm(&self) -> Option<ShaAlgorithm> {
+        self.engine.algorithm()
+    }
+}
+
+impl crate::fake::SyscallDriver for Hmac {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.engine.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        self.engine.allow_readonly(buffer_num, buffer)
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        self.engine.allow_readwrite(buffer_num, buffer)
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        self.engine.command(command_id, argument0)
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40003;
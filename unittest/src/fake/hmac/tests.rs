@@ -0,0 +1,72 @@
+use crate::fake::{self, hmac::*, sha::*};
+use libtock_platform::{
+    share, AllowRo, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn,
+};
+
+// Test case 2 of RFC 4231: HMAC-SHA-256 with key "Jefe".
+const DATA: &[u8] = b"what do ya want for nothing?";
+const MAC: [u8; 32] = [
+    0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75, 0xc7,
+    0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+];
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let hmac = Hmac::new();
+    assert!(hmac.command(EXISTS, 0, 0).is_success());
+    assert!(hmac.command(SET_ALGORITHM, 2, 0).is_success());
+    assert_eq!(hmac.algorithm(), Some(ShaAlgorithm::Sha512));
+    assert!(hmac
+        .allow_readonly(ALLOW_KEY, RoAllowBuffer::default())
+        .is_ok());
+    assert!(hmac.allow_readonly(3, RoAllowBuffer::default()).is_err());
+}
+
+// Integration test that verifies Hmac works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let hmac = Hmac::new();
+    kernel.add_driver(&hmac);
+    assert!(fake::Syscalls::command(DRIVER_NUM, SET_ALGORITHM, 0, 0).is_success());
+
+    let mut mac = [0; 32];
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, ALLOW_KEY>,
+            AllowRo<_, DRIVER_NUM, ALLOW_DATA>,
+            AllowRo<_, DRIVER_NUM, ALLOW_COMPARE>,
+            AllowRw<_, DRIVER_NUM, ALLOW_DEST>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_DONE>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_key, allow_data, allow_compare, allow_dest, subscribe) = handle.split();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_DONE>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_KEY>(allow_key, b"Jefe")
+            .unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_DATA>(allow_data, DATA)
+            .unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_COMPARE>(allow_compare, &MAC)
+            .unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_DEST>(allow_dest, &mut mac)
+            .unwrap();
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, RUN, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((0, 32)));
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, VERIFY, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((0, 1)));
+    });
+    assert_eq!(mac, MAC);
+}
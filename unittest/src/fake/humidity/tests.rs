@@ -0,0 +1,67 @@
+use crate::fake::{self, SyscallDriver};
+use fake::humidity::*;
+use libtock_platform::{share, DefaultConfig, YieldNoWaitReturn};
+
+//Test the command implementation
+#[test]
+fn command() {
+    let humidity = Humidity::new();
+
+    assert!(humidity.command(EXISTS, 1, 2).is_success());
+
+    assert!(humidity.command(READ_HUMIDITY, 0, 0).is_success());
+
+    assert_eq!(
+        humidity.command(READ_HUMIDITY, 0, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+
+    humidity.set_value(100);
+    assert!(humidity.command(READ_HUMIDITY, 0, 1).is_success());
+    humidity.set_value(100);
+
+    humidity.set_value_sync(100);
+    assert!(humidity.command(READ_HUMIDITY, 0, 1).is_success());
+    assert!(humidity.command(READ_HUMIDITY, 0, 1).is_success());
+}
+
+// Integration test that verifies Humidity works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let humidity = Humidity::new();
+    kernel.add_driver(&humidity);
+    assert!(fake::Syscalls::command(DRIVER_NUM, EXISTS, 1, 2).is_success());
+    assert!(fake::Syscalls::command(DRIVER_NUM, READ_HUMIDITY, 0, 0).is_success());
+    assert_eq!(
+        fake::Syscalls::command(DRIVER_NUM, READ_HUMIDITY, 0, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    humidity.set_value(100);
+    assert!(fake::Syscalls::command(DRIVER_NUM, READ_HUMIDITY, 0, 1).is_success());
+
+    let listener = Cell::<Option<(u32,)>>::new(None);
+    share::scope(|subscribe| {
+        assert_eq!(
+            fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &listener),
+            Ok(())
+        );
+
+        humidity.set_value(100);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((100,)));
+
+        humidity.set_value(200);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, READ_HUMIDITY, 0, 1).is_success());
+        humidity.set_value(200);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+
+        humidity.set_value_sync(200);
+        assert!(fake::Syscalls::command(DRIVER_NUM, READ_HUMIDITY, 0, 1).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+}
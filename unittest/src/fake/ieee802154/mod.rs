@@ -52,6 +52,7 @@ pub struct Ieee802154Phy {
     rx_buf: RefCell<RwAllowBuffer>,
 
     transmitted_frames: Cell<Vec<Vec<u8>>>,
+    transmit_result: Cell<Result<bool, ErrorCode>>,
 
     frames_to_be_received: RefCell<VecDeque<Frame>>,
 
@@ -87,6 +88,7 @@ impl Ieee802154Phy {
             tx_buf: Default::default(),
             rx_buf: Default::default(),
             transmitted_frames: Default::default(),
+            transmit_result: Cell::new(Ok(false)),
             frames_to_be_received: RefCell::new(frames_to_be_received.into_iter().collect()),
             share_ref: Default::default(),
         })
@@ -96,6 +98,13 @@ impl Ieee802154Phy {
         self.transmitted_frames.take()
     }
 
+    /// Sets the outcome the FRAME_TRANSMITTED upcalls report: whether the
+    /// frame was acknowledged (`Ok(false)` by default), or the error it
+    /// failed with, in which case the frame is not transmitted.
+    pub fn set_transmit_result(&self, result: Result<bool, ErrorCode>) {
+        self.transmit_result.set(result);
+    }
+
     pub fn has_pending_rx_frames(&self) -> bool {
         let rx_buf = self.rx_buf.borrow();
 
@@ -244,12 +253,24 @@ impl crate::fake::SyscallDriver for Ieee802154Phy {
                 }
             }
             command::SET_SHORT_ADDR => {
-                self.addr_short.set(u16::try_from(argument0).unwrap());
-                command_return::success()
+                // The app adds 1 to make the value positive.
+                match argument0.checked_sub(1).map(u16::try_from) {
+                    Some(Ok(value)) => {
+                        self.addr_short.set(value);
+                        command_return::success()
+                    }
+                    _ => command_return::failure(ErrorCode::Invalid),
+                }
             }
             command::SET_PAN => {
-                self.pan.set(u16::try_from(argument0).unwrap());
-                command_return::success()
+                // The app adds 1 to make the value positive.
+                match argument0.checked_sub(1).map(u16::try_from) {
+                    Some(Ok(value)) => {
+                        self.pan.set(value);
+                        command_return::success()
+                    }
+                    _ => command_return::failure(ErrorCode::Invalid),
+                }
             }
             command::SET_CHAN => {
                 self.chan.set(u8::try_from(argument0).unwrap());
@@ -260,8 +281,10 @@ impl crate::fake::SyscallDriver for Ieee802154Phy {
                 command_return::success()
             }
             command::COMMIT_CFG => command_return::success(),
-            command::GET_SHORT_ADDR => command_return::success_u32(self.addr_short.get() as u32),
-            command::GET_PAN => command_return::success_u32(self.pan.get() as u32),
+            command::GET_SHORT_ADDR => {
+                command_return::success_u32(self.addr_short.get() as u32 + 1)
+            }
+            command::GET_PAN => command_return::success_u32(self.pan.get() as u32 + 1),
             command::GET_CHAN => command_return::success_u32(self.chan.get() as u32),
             command::GET_TX_PWR => command_return::success_u32(self.tx_power.get() as i32 as u32),
             command::SET_LONG_ADDR => {
@@ -279,14 +302,20 @@ impl crate::fake::SyscallDriver for Ieee802154Phy {
                 command_return::success()
             }
             command::TRANSMIT => {
-                let mut transmitted_frames = self.transmitted_frames.take();
-                let tx_buf = self.tx_buf.take();
-                transmitted_frames.push(Vec::from(tx_buf.as_ref()));
-
-                self.tx_buf.set(tx_buf);
-                self.transmitted_frames.set(transmitted_frames);
+                let upcall = match self.transmit_result.get() {
+                    Ok(acked) => {
+                        let mut transmitted_frames = self.transmitted_frames.take();
+                        let tx_buf = self.tx_buf.take();
+                        transmitted_frames.push(Vec::from(tx_buf.as_ref()));
+
+                        self.tx_buf.set(tx_buf);
+                        self.transmitted_frames.set(transmitted_frames);
+                        (0, acked as u32, 0)
+                    }
+                    Err(error) => (error as u32, 0, 0),
+                };
                 self.share_ref
-                    .schedule_upcall(subscribe::FRAME_TRANSMITTED, (2137, 0, 0))
+                    .schedule_upcall(subscribe::FRAME_TRANSMITTED, upcall)
                     .expect("Unable to schedule upcall {}");
 
                 command_return::success()
@@ -0,0 +1,356 @@
+//! Fake implementation of the IPC driver.
+//!
+//! IPC lets a process find another process (a *service*) by its package name,
+//! share buffers with it, and notify it. The fake simulates the other
+//! processes: tests add them with `add_process`, act on their behalf
+//! (`share_from_process`, `notify_service`, `notify_client`), and observe the
+//! notifications sent by the process under test with `take_notifications`. A
+//! simulated service can also answer notifications on its own (see
+//! `set_service_handler`), for code under test that blocks until the service
+//! replies.
+//!
+//! The system call interface is:
+//!
+//! * Read-Only Allow 0: the package name to search for.
+//! * Command 1: discover. Returns the process ID of the process with the
+//!   allowed package name, or `Invalid` if there is none.
+//! * Command 2: notify the client whose process ID is `argument0`.
+//! * Command 3: notify the service whose process ID is `argument0`.
+//! * Read-Write Allow `n`: shares a buffer with process `n`.
+//! * Subscribe 0: the service upcall, run when a client notifies this process.
+//! * Subscribe `n + 1`: the client upcall, run when service `n` notifies this
+//!   process.
+//!
+//! Upcalls receive `(notifier's process ID, length, address)` describing the
+//! buffer the notifier shared with the receiving process, or a length and
+//! address of 0 if it did not share one.
+//!
+//! The process under test has process ID 0. Addresses of buffers shared by
+//! simulated processes are fake 32-bit addresses, as host pointers may not fit
+//! in an upcall argument; `resolve` turns them back into pointers.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::rc::{self, Rc};
+
+/// A notification sent by the process under test.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IpcNotification {
+    /// The process notified the client with this process ID (command 2).
+    Client(u32),
+    /// The process notified the service with this process ID (command 3).
+    Service(u32),
+}
+
+pub struct Ipc {
+    package_name: RefCell<Option<String>>,
+    // Simulated processes. The process at index i has process ID i + 1.
+    processes: RefCell<Vec<Process>>,
+    search_buffer: Cell<RoAllowBuffer>,
+    // Buffers shared by the process under test, indexed by process ID.
+    shared_buffers: RefCell<[RwAllowBuffer; MAX_PROCESSES as usize]>,
+    notifications: Cell<Vec<IpcNotification>>,
+    share_ref: DriverShareRef,
+}
+
+thread_local!(static DRIVER: RefCell<rc::Weak<Ipc>> = const { RefCell::new(rc::Weak::new()) });
+
+impl Ipc {
+    /// Returns the `Ipc` most recently created on this thread, if it still
+    /// exists. Lets code that only sees upcall arguments `resolve` addresses.
+    pub fn instance() -> Option<Rc<Ipc>> {
+        DRIVER.with_borrow(|driver| driver.upgrade())
+    }
+
+    pub fn new() -> Rc<Ipc> {
+        let new = Rc::new(Ipc {
+            package_name: Default::default(),
+            processes: Default::default(),
+            search_buffer: Default::default(),
+            shared_buffers: Default::default(),
+            notifications: Default::default(),
+            share_ref: Default::default(),
+        });
+        DRIVER.with_borrow_mut(|driver| *driver = Rc::downgrade(&new));
+        new
+    }
+
+    /// Sets the package name of the process under test, under which it can be
+    /// discovered (by itself). By default, it has no package name.
+    pub fn set_package_name(&self, name: &str) {
+        self.package_name.replace(Some(name.into()));
+    }
+
+    /// Adds a simulated process with the given package name, and returns its
+    /// process ID. Panics if there are already `MAX_PROCESSES` processes.
+    pub fn add_process(&self, name: &str) -> u32 {
+        let mut processes = self.processes.borrow_mut();
+        assert!(
+            processes.len() + 1 < MAX_PROCESSES as usize,
+            "fake::Ipc supports at most {} processes",
+            MAX_PROCESSES
+        );
+        processes.push(Process {
+            name: name.into(),
+            shared: None,
+            handler: None,
+        });
+        processes.len() as u32
+    }
+
+    /// Makes simulated process `process` share a buffer, initialized with
+    /// `contents`, with the process under test. Replaces any buffer it shared
+    /// previously.
+    pub fn share_from_process(&self, process: u32, contents: &[u8]) {
+        assert!(
+            contents.len() <= PROCESS_MEMORY_SIZE as usize,
+            "Buffer too large"
+        );
+        let address = PROCESS_MEMORY_BASE + process * PROCESS_MEMORY_SIZE;
+        self.with_process(process, |simulated| {
+            simulated.shared = Some(SharedMemory {
+                address,
+                memory: contents.iter().copied().map(Cell::new).collect(),
+            })
+        });
+    }
+
+    /// Returns the current contents of the buffer simulated process `process`
+    /// shares with the process under test (empty if it shares none).
+    pub fn process_buffer(&self, process: u32) -> Vec<u8> {
+        self.with_process(process, |simulated| match &simulated.shared {
+            None => Vec::new(),
+            Some(shared) => shared.memory.iter().map(Cell::get).collect(),
+        })
+    }
+
+    /// Converts a fake address received in an IPC upcall into a pointer to the
+    /// simulated process' memory. Returns `None` unless the `len` bytes at
+    /// `address` lie within a buffer shared by a simulated process. The
+    /// pointer is valid until that buffer is replaced or the `Ipc` is dropped.
+    pub fn resolve(&self, address: u32, len: usize) -> Option<*mut u8> {
+        self.processes
+            .borrow()
+            .iter()
+            .filter_map(|simulated| simulated.shared.as_ref())
+            .find_map(|shared| {
+                let offset = address.checked_sub(shared.address)? as usize;
+                if offset.checked_add(len)? > shared.memory.len() {
+                    return None;
+                }
+                Some(shared.memory[offset..].as_ptr() as *mut u8)
+            })
+    }
+
+    /// Returns the contents of the buffer the process under test shares with
+    /// `process` (empty if it shares none).
+    pub fn shared_buffer(&self, process: u32) -> Vec<u8> {
+        self.shared_buffers.borrow()[Self::index(process)].to_vec()
+    }
+
+    /// Writes `bytes` to the start of the buffer the process under test shares
+    /// with `process`, as that process would. Panics if the buffer is too
+    /// short.
+    pub fn write_shared_buffer(&self, process: u32, bytes: &[u8]) {
+        let mut shared_buffers = self.shared_buffers.borrow_mut();
+        let buffer = &mut shared_buffers[Self::index(process)];
+        assert!(
+            bytes.len() <= buffer.len(),
+            "{} bytes do not fit in the {}-byte buffer shared with process {}",
+            bytes.len(),
+            buffer.len(),
+            process
+        );
+        buffer[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Makes simulated process `service` handle the notifications the process
+    /// under test sends it: `handler` is called with the buffer the process
+    /// under test shares with `service` (empty if it shares none), after which
+    /// `service` notifies the process under test back. Notifications are still
+    /// recorded for `take_notifications`.
+    pub fn set_service_handler<F: Fn(&mut [u8]) + 'static>(&self, service: u32, handler: F) {
+        self.with_process(service, |simulated| {
+            simulated.handler = Some(Rc::new(handler));
+        });
+    }
+
+    /// Makes simulated process `client` notify the process under test, which
+    /// runs its service upcall.
+    pub fn notify_service(&self, client: u32) {
+        let args = self.upcall_args(client);
+        self.share_ref
+            .schedule_upcall(SERVICE_UPCALL, args)
+            .expect("Unable to schedule upcall");
+    }
+
+    /// Makes simulated process `service` notify the process under test, which
+    /// runs its client upcall for `service`.
+    pub fn notify_client(&self, service: u32) {
+        let args = self.upcall_args(service);
+        self.share_ref
+            .schedule_upcall(service + 1, args)
+            .expect("Unable to schedule upcall");
+    }
+
+    /// Returns the notifications sent by the process under test, and clears
+    /// them.
+    pub fn take_notifications(&self) -> Vec<IpcNotification> {
+        self.notifications.take()
+    }
+
+    fn with_process<R, F: FnOnce(&mut Process) -> R>(&self, process: u32, f: F) -> R {
+        let mut processes = self.processes.borrow_mut();
+        let simulated = (process as usize)
+            .checked_sub(1)
+            .and_then(|index| processes.get_mut(index))
+            .unwrap_or_else(|| panic!("No simulated process with ID {}", process));
+        f(simulated)
+    }
+
+    fn upcall_args(&self, process: u32) -> (u32, u32, u32) {
+        self.with_process(process, |simulated| match &simulated.shared {
+            None => (process, 0, 0),
+            Some(shared) => (process, shared.memory.len() as u32, shared.address),
+        })
+    }
+
+    #[track_caller]
+    fn index(process: u32) -> usize {
+        assert!(process < MAX_PROCESSES, "Invalid process ID {}", process);
+        process as usize
+    }
+
+    // Returns the process ID of the process with the given package name.
+    fn discover(&self, name: &[u8]) -> Option<u32> {
+        if self.package_name.borrow().as_deref().map(str::as_bytes) == Some(name) {
+            return Some(0);
+        }
+        let processes = self.processes.borrow();
+        let index = processes.iter().position(|p| p.name.as_bytes() == name)?;
+        Some(index as u32 + 1)
+    }
+
+    fn notify(&self, process: u32, notification: IpcNotification) -> CommandReturn {
+        if process == 0 || process as usize > self.processes.borrow().len() {
+            return command_return::failure(ErrorCode::Invalid);
+        }
+        let mut notifications = self.notifications.take();
+        notifications.push(notification);
+        self.notifications.set(notifications);
+        if let IpcNotification::Service(service) = notification {
+            let handler = self.with_process(service, |simulated| simulated.handler.clone());
+            if let Some(handler) = handler {
+                handler(&mut self.shared_buffers.borrow_mut()[service as usize]);
+                self.notify_client(service);
+            }
+        }
+        command_return::success()
+    }
+}
+
+impl crate::fake::SyscallDriver for Ipc {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(MAX_PROCESSES + 1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        if buffer_num == ALLOW_SEARCH {
+            Ok(self.search_buffer.replace(buffer))
+        } else {
+            Err((buffer, ErrorCode::Invalid))
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        if buffer_num >= MAX_PROCESSES {
+            return Err((buffer, ErrorCode::Invalid));
+        }
+        Ok(core::mem::replace(
+            &mut self.shared_buffers.borrow_mut()[buffer_num as usize],
+            buffer,
+        ))
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => command_return::success(),
+            DISCOVER => {
+                let search = self.search_buffer.take();
+                let result = self.discover(&search);
+                self.search_buffer.set(search);
+                match result {
+                    Some(process) => command_return::success_u32(process),
+                    None => command_return::failure(ErrorCode::Invalid),
+                }
+            }
+            NOTIFY_CLIENT => self.notify(argument0, IpcNotification::Client(argument0)),
+            NOTIFY_SERVICE => self.notify(argument0, IpcNotification::Service(argument0)),
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+struct Process {
+    name: String,
+    shared: Option<SharedMemory>,
+    handler: Option<ServiceHandler>,
+}
+
+// Answers notifications from the process under test (see set_service_handler).
+type ServiceHandler = Rc<dyn Fn(&mut [u8])>;
+
+// A buffer in a simulated process' memory, at a fake address. Cells let the
+// process under test write to it through a resolved pointer while the fake
+// holds a shared reference.
+struct SharedMemory {
+    address: u32,
+    memory: Box<[Cell<u8>]>,
+}
+
+// Fake addresses of simulated process memory: process n's buffer is at
+// PROCESS_MEMORY_BASE + n * PROCESS_MEMORY_SIZE.
+const PROCESS_MEMORY_BASE: u32 = 0x8000_0000;
+const PROCESS_MEMORY_SIZE: u32 = 0x0100_0000;
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+pub const DRIVER_NUM: u32 = 0x10000;
+
+/// The number of processes, including the process under test, the fake
+/// supports.
+pub const MAX_PROCESSES: u32 = 8;
+
+// Command IDs
+pub const EXISTS: u32 = 0;
+pub const DISCOVER: u32 = 1;
+pub const NOTIFY_SERVICE: u32 = 2;
+pub const NOTIFY_CLIENT: u32 = 3;
+
+// Allow IDs
+pub const ALLOW_SEARCH: u32 = 0;
+
+// Upcall IDs. The client upcall for service n is n + 1.
+pub const SERVICE_UPCALL: u32 = 0;
@@ -0,0 +1,181 @@
+use crate::fake::{self, ipc::*};
+use crate::{RoAllowBuffer, RwAllowBuffer};
+use libtock_platform::{
+    share, AllowRw, DefaultConfig, ErrorCode, Subscribe, Syscalls, YieldNoWaitReturn,
+};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let ipc = Ipc::new();
+    let service = ipc.add_process("org.tock.service");
+    assert_eq!(service, 1);
+    assert!(ipc.command(EXISTS, 0, 0).is_success());
+    // No package name has been allowed.
+    assert_eq!(
+        ipc.command(DISCOVER, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(ipc.command(NOTIFY_SERVICE, service, 0).is_success());
+    assert!(ipc.command(NOTIFY_CLIENT, service, 0).is_success());
+    assert_eq!(
+        ipc.take_notifications(),
+        [
+            IpcNotification::Service(service),
+            IpcNotification::Client(service)
+        ]
+    );
+    // Process 0 is the process under test, and process 2 does not exist.
+    assert_eq!(
+        ipc.command(NOTIFY_SERVICE, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        ipc.command(NOTIFY_CLIENT, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(ipc.take_notifications(), []);
+
+    assert!(ipc
+        .allow_readonly(ALLOW_SEARCH, RoAllowBuffer::default())
+        .is_ok());
+    assert!(ipc.allow_readonly(1, RoAllowBuffer::default()).is_err());
+    assert!(ipc
+        .allow_readwrite(service, RwAllowBuffer::default())
+        .is_ok());
+    assert!(ipc
+        .allow_readwrite(MAX_PROCESSES, RwAllowBuffer::default())
+        .is_err());
+}
+
+// Integration test that verifies Ipc works with fake::Kernel and
+// libtock_platform::Syscalls, with the process under test acting as a client.
+#[test]
+fn kernel_integration_client() {
+    let kernel = fake::Kernel::new();
+    let ipc = Ipc::new();
+    kernel.add_driver(&ipc);
+    let service = ipc.add_process("org.tock.service");
+    ipc.add_process("org.tock.other");
+
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_SEARCH>(
+            allow_ro,
+            b"org.tock.service",
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, DISCOVER, 0, 0).get_success_u32(),
+            Some(service)
+        );
+    });
+
+    let mut request = *b"ping";
+    let upcall: core::cell::Cell<Option<(u32, u32, u32)>> = Default::default();
+    share::scope::<(AllowRw<_, DRIVER_NUM, 1>, Subscribe<_, DRIVER_NUM, 2>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 1>(allow_rw, &mut request).unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 2>(subscribe, &upcall)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, NOTIFY_SERVICE, service, 0).is_success());
+        assert_eq!(
+            ipc.take_notifications(),
+            [IpcNotification::Service(service)]
+        );
+
+        // The simulated service answers in place, and in a buffer of its own.
+        assert_eq!(ipc.shared_buffer(service), b"ping");
+        ipc.write_shared_buffer(service, b"po");
+        ipc.share_from_process(service, b"reply");
+        ipc.notify_client(service);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    assert_eq!(request, *b"pong");
+
+    let (from, len, address) = upcall.get().unwrap();
+    assert_eq!((from, len), (service, 5));
+    let reply = ipc.resolve(address, len as usize).unwrap();
+    // Safety: resolve returned a pointer to 5 bytes of the simulated service's
+    // buffer, which is not replaced before this read.
+    assert_eq!(unsafe { core::slice::from_raw_parts(reply, 5) }, b"reply");
+    assert_eq!(ipc.resolve(address + 1, 5), None);
+}
+
+// Integration test with the process under test acting as a service.
+#[test]
+fn kernel_integration_service() {
+    let kernel = fake::Kernel::new();
+    let ipc = Ipc::new();
+    kernel.add_driver(&ipc);
+    ipc.set_package_name("org.tock.service");
+    let client = ipc.add_process("org.tock.client");
+
+    // A process can discover itself.
+    share::scope(|allow_ro| {
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_SEARCH>(
+            allow_ro,
+            b"org.tock.service",
+        )
+        .unwrap();
+        assert_eq!(
+            fake::Syscalls::command(DRIVER_NUM, DISCOVER, 0, 0).get_success_u32(),
+            Some(0)
+        );
+    });
+
+    let upcall: core::cell::Cell<Option<(u32, u32, u32)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SERVICE_UPCALL>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        // A client without a shared buffer.
+        ipc.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.take(), Some((client, 0, 0)));
+
+        ipc.share_from_process(client, b"ping");
+        ipc.notify_service(client);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+
+    let (from, len, address) = upcall.get().unwrap();
+    assert_eq!((from, len), (client, 4));
+    let request = ipc.resolve(address, len as usize).unwrap();
+    // Safety: resolve returned a pointer to 4 bytes of the simulated client's
+    // buffer, and no other reference to it exists.
+    let request = unsafe { core::slice::from_raw_parts_mut(request, 4) };
+    assert_eq!(request, b"ping");
+    request.copy_from_slice(b"pong");
+    assert!(fake::Syscalls::command(DRIVER_NUM, NOTIFY_CLIENT, client, 0).is_success());
+    assert_eq!(ipc.take_notifications(), [IpcNotification::Client(client)]);
+    assert_eq!(ipc.process_buffer(client), b"pong");
+}
+
+// Tests a simulated service that answers notifications on its own.
+#[test]
+fn service_handler() {
+    let kernel = fake::Kernel::new();
+    let ipc = Ipc::new();
+    kernel.add_driver(&ipc);
+    let service = ipc.add_process("org.tock.service");
+    ipc.set_service_handler(service, |buffer| buffer.make_ascii_uppercase());
+
+    let mut buffer = *b"ping";
+    let upcall: core::cell::Cell<Option<(u32,)>> = Default::default();
+    share::scope::<(AllowRw<_, DRIVER_NUM, 1>, Subscribe<_, DRIVER_NUM, 2>), _, _>(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 1>(allow_rw, &mut buffer).unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 2>(subscribe, &upcall)
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, NOTIFY_SERVICE, service, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((service,)));
+    });
+    assert_eq!(&buffer, b"PING");
+    assert_eq!(
+        ipc.take_notifications(),
+        [IpcNotification::Service(service)]
+    );
+}
@@ -1,5 +1,7 @@
-use crate::kernel_data::{with_kernel_data, DriverData, KernelData, KERNEL_DATA};
-use crate::{DriverShareRef, ExpectedSyscall, SyscallLogEntry};
+use crate::kernel_data::{with_kernel_data, AllowType, DriverData, KernelData, KERNEL_DATA};
+use crate::trace::Recorder;
+use crate::upcall::{PendingUpcall, UpcallId};
+use crate::{DriverShareRef, ExpectedSyscall, SyscallLogEntry, Trace};
 use std::cell::Cell;
 
 /// A fake implementation of the Tock kernel. Used with `fake::Syscalls`, which
@@ -32,10 +34,16 @@ impl Kernel {
         let old_option = KERNEL_DATA.with(|kernel_data| {
             kernel_data.replace(Some(KernelData {
                 allow_db: Default::default(),
+                allowed_buffers: Default::default(),
+                check_leaks_on_drop: false,
                 create_location: std::panic::Location::caller(),
                 drivers: Default::default(),
                 expected_syscalls: Default::default(),
+                idle_handler: None,
+                #[cfg(not(miri))]
+                exit_handler: None,
                 syscall_log: Vec::new(),
+                trace: None,
                 upcall_queue: Default::default(),
                 memory_break: core::ptr::null(),
             }))
@@ -105,11 +113,116 @@ impl Kernel {
         });
     }
 
+    /// Adds several ExpectedSyscalls to the expected syscall queue, in order.
+    /// Usually used with a `SyscallSequence` built by `libtock_unittest::expect`.
+    pub fn add_expected_syscalls<I: IntoIterator<Item = ExpectedSyscall>>(&self, syscalls: I) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().expected_syscalls.extend(syscalls));
+    }
+
+    /// Panics if the expected syscall queue is not empty. Tests should call
+    /// this after exercising the code under test, as otherwise an expected
+    /// system call that was never made goes unnoticed.
+    #[track_caller]
+    pub fn assert_expected_syscalls_done(&self) {
+        with_kernel_data(|kernel_data| {
+            let expected_syscalls = &kernel_data.unwrap().expected_syscalls;
+            assert!(
+                expected_syscalls.is_empty(),
+                "Expected system calls were not made: {:?}",
+                expected_syscalls
+            );
+        });
+    }
+
+    /// Sets a function to call when the process calls yield-wait while no
+    /// upcall is queued. A real process would sleep until an interrupt queues
+    /// an upcall; the idle handler plays the role of the outside world by
+    /// giving a fake driver something to report (e.g. by advancing a fake
+    /// alarm's clock). If no upcall is queued after the handler returns,
+    /// yield-wait panics as it does without an idle handler.
+    pub fn set_idle_handler<F: FnMut() + 'static>(&self, handler: F) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().idle_handler = Some(Box::new(handler)));
+    }
+
+    /// Sets a function to call when the process calls Exit, before the fake
+    /// kernel's default handling (which reports the exit to `exit_test` or
+    /// ends the test process). The handler may end the process itself.
+    #[cfg(not(miri))]
+    pub fn set_exit_handler<F: FnOnce(crate::ExitCall) + 'static>(&self, handler: F) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().exit_handler = Some(Box::new(handler)));
+    }
+
+    /// Makes dropping this `Kernel` panic if any buffer is still Allowed or any
+    /// upcall is still subscribed (see `assert_no_leaks`). This catches code
+    /// that leaks kernel access to its memory, e.g. by forgetting a guard that
+    /// was supposed to revoke it. The check is skipped if the thread is
+    /// already panicking.
+    pub fn check_leaks_on_drop(&self) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().check_leaks_on_drop = true);
+    }
+
+    /// Panics if any non-empty buffer is still Allowed to a driver, or any
+    /// upcall is still subscribed, listing each one.
+    #[track_caller]
+    pub fn assert_no_leaks(&self) {
+        let leaks = with_kernel_data(|kernel_data| leaks(kernel_data.unwrap()));
+        assert!(leaks.is_empty(), "{}", leak_message(&leaks));
+    }
+
     /// Returns the system call log and empties it.
     pub fn take_syscall_log(&self) -> Vec<SyscallLogEntry> {
         with_kernel_data(|kernel_data| std::mem::take(&mut kernel_data.unwrap().syscall_log))
     }
 
+    /// Starts recording a `Trace` of the system calls made from now on, their
+    /// results, and the upcalls they run. Discards any trace being recorded.
+    pub fn start_trace(&self) {
+        with_kernel_data(|kernel_data| kernel_data.unwrap().trace = Some(Default::default()));
+    }
+
+    /// Returns the trace recorded since `start_trace` (or the previous
+    /// `take_trace`) and empties it; recording continues. If called while a
+    /// system call is in progress (e.g. from an upcall), the trace stops before
+    /// that call. Panics if no trace is being recorded.
+    #[track_caller]
+    pub fn take_trace(&self) -> Trace {
+        with_kernel_data(|kernel_data| {
+            kernel_data
+                .unwrap()
+                .trace
+                .as_mut()
+                .expect("take_trace called without start_trace")
+                .take()
+        })
+    }
+
+    /// Replays `trace`: from now on, each system call is compared against the
+    /// next event in `trace` when it returns, and the system call panics if
+    /// they differ. Call `assert_trace_replayed` at the end of the test to
+    /// check the whole trace was replayed. Replaying also records a trace,
+    /// which can be retrieved with `take_trace`.
+    pub fn replay_trace(&self, trace: Trace) {
+        with_kernel_data(|kernel_data| {
+            kernel_data.unwrap().trace = Some(Recorder::replaying(trace));
+        });
+    }
+
+    /// Panics if the trace passed to `replay_trace` has events that have not
+    /// been replayed yet.
+    #[track_caller]
+    pub fn assert_trace_replayed(&self) {
+        let unreplayed = with_kernel_data(|kernel_data| {
+            kernel_data
+                .unwrap()
+                .trace
+                .as_ref()
+                .and_then(Recorder::unreplayed)
+        });
+        if let Some(unreplayed) = unreplayed {
+            panic!("Trace not fully replayed: {}", unreplayed);
+        }
+    }
+
     /// Returns true if the specified driver installed.
     pub fn is_driver_present(driver_num: u32) -> bool {
         with_kernel_data(|kernel_data| {
@@ -123,10 +236,157 @@ impl Kernel {
             kernel_data.map_or(false, |kernel| !kernel.upcall_queue.is_empty())
         })
     }
+
+    // -------------------------------------------------------------------------
+    // Upcall ordering control
+    //
+    // By default, Yield delivers queued upcalls in FIFO order. The following
+    // functions let a test inspect the upcall queue and rearrange it between
+    // Yield calls, so that interleavings that are hard to trigger on real
+    // hardware (such as an upcall arriving from one driver while the app is
+    // between two steps of an operation on another) can be tested
+    // deterministically.
+    // -------------------------------------------------------------------------
+
+    /// Queues an upcall as if driver `driver_num` had scheduled it. Like
+    /// `DriverShareRef::schedule_upcall`, this does nothing if no upcall is
+    /// subscribed under `subscribe_num`. Panics if the driver is not present
+    /// or `subscribe_num` is out of range for it.
+    pub fn schedule_upcall(&self, driver_num: u32, subscribe_num: u32, args: (u32, u32, u32)) {
+        assert!(
+            Self::is_driver_present(driver_num),
+            "schedule_upcall: no driver with number {}",
+            driver_num
+        );
+        let share_ref = DriverShareRef {
+            driver_num: Cell::new(driver_num),
+        };
+        if let Err(error) = share_ref.schedule_upcall(subscribe_num, args) {
+            panic!("schedule_upcall: {}", error);
+        }
+    }
+
+    /// Returns the queued upcalls, in the order they will be delivered.
+    pub fn pending_upcalls(&self) -> Vec<PendingUpcall> {
+        with_kernel_data(|kernel_data| {
+            kernel_data
+                .unwrap()
+                .upcall_queue
+                .iter()
+                .map(PendingUpcall::from)
+                .collect()
+        })
+    }
+
+    /// Rearranges the upcall queue. `order` must be a permutation of the
+    /// indices into `pending_upcalls()`; after the call, the upcall that was
+    /// at index `order[0]` is delivered first, `order[1]` second, and so on.
+    /// Panics if `order` is not a permutation.
+    pub fn reorder_upcalls(&self, order: &[usize]) {
+        with_kernel_data(|kernel_data| {
+            let queue = &mut kernel_data.unwrap().upcall_queue;
+            let mut seen = vec![false; queue.len()];
+            assert_eq!(
+                order.len(),
+                queue.len(),
+                "reorder_upcalls: {} upcalls queued, order has {} entries",
+                queue.len(),
+                order.len()
+            );
+            for &index in order {
+                assert!(
+                    index < queue.len() && !seen[index],
+                    "reorder_upcalls: {:?} is not a permutation",
+                    order
+                );
+                seen[index] = true;
+            }
+            let mut old: Vec<_> = queue.drain(..).map(Some).collect();
+            queue.extend(order.iter().map(|&index| old[index].take().unwrap()));
+        });
+    }
+
+    /// Moves the oldest queued upcall for the given driver and subscribe
+    /// number to the front of the queue, so that it is delivered by the next
+    /// Yield. Returns false (and leaves the queue unchanged) if no such upcall
+    /// is queued.
+    pub fn prioritize_upcall(&self, driver_num: u32, subscribe_num: u32) -> bool {
+        with_kernel_data(|kernel_data| {
+            let queue = &mut kernel_data.unwrap().upcall_queue;
+            let id = UpcallId {
+                driver_num,
+                subscribe_num,
+            };
+            match queue.iter().position(|entry| entry.id == id) {
+                None => false,
+                Some(index) => {
+                    let entry = queue.remove(index).unwrap();
+                    queue.push_front(entry);
+                    true
+                }
+            }
+        })
+    }
 }
 
 impl Drop for Kernel {
     fn drop(&mut self) {
-        KERNEL_DATA.with(|kernel_data| kernel_data.replace(None));
+        // The kernel data is removed before checking for leaks, so a leak
+        // panic does not prevent the thread from creating a new Kernel.
+        let kernel_data = KERNEL_DATA.with(|kernel_data| kernel_data.replace(None));
+        if let Some(kernel_data) = kernel_data {
+            if kernel_data.check_leaks_on_drop && !std::thread::panicking() {
+                let leaks = leaks(&kernel_data);
+                assert!(leaks.is_empty(), "{}", leak_message(&leaks));
+            }
+        }
     }
 }
+
+// Returns a description of each Allowed buffer and subscribed upcall.
+fn leaks(kernel_data: &KernelData) -> Vec<String> {
+    let mut leaks: Vec<_> = kernel_data
+        .allowed_buffers
+        .iter()
+        .map(|(&(allow_type, driver_num, buffer_num), len)| {
+            let allow_type = match allow_type {
+                AllowType::ReadOnly => "Read-Only",
+                AllowType::ReadWrite => "Read-Write",
+            };
+            format!(
+                "{} Allow: driver {:#x}, buffer {} ({} bytes)",
+                allow_type, driver_num, buffer_num, len
+            )
+        })
+        .collect();
+    let mut subscriptions: Vec<_> = kernel_data
+        .drivers
+        .iter()
+        .flat_map(|(&driver_num, driver_data)| {
+            driver_data
+                .upcalls
+                .iter()
+                .filter(|(_, upcall)| !upcall.is_null())
+                .map(move |(&subscribe_num, _)| (driver_num, subscribe_num))
+        })
+        .collect();
+    subscriptions.sort_unstable();
+    leaks.extend(
+        subscriptions
+            .into_iter()
+            .map(|(driver_num, subscribe_num)| {
+                format!(
+                    "Subscribe: driver {:#x}, subscribe {}",
+                    driver_num, subscribe_num
+                )
+            }),
+    );
+    leaks
+}
+
+fn leak_message(leaks: &[String]) -> String {
+    format!(
+        "Buffers still Allowed or upcalls still subscribed:\n  {}",
+        leaks.join("\n  ")
+    )
+}
@@ -45,3 +45,268 @@ fn syscall_log() {
     assert_eq!(kernel.take_syscall_log(), [YieldNoWait, YieldWait]);
     assert_eq!(kernel.take_syscall_log(), []);
 }
+
+// A driver with two upcalls that does nothing on its own; upcalls are injected
+// with fake::Kernel::schedule_upcall.
+struct UpcallDriver(u32);
+
+impl fake::SyscallDriver for UpcallDriver {
+    fn info(&self) -> crate::DriverInfo {
+        crate::DriverInfo::new(self.0).upcall_count(2)
+    }
+
+    fn command(&self, _: u32, _: u32, _: u32) -> libtock_platform::CommandReturn {
+        crate::command_return::failure(libtock_platform::ErrorCode::NoSupport)
+    }
+}
+
+// An upcall that records its ID and first argument in a shared log, so tests
+// can observe delivery order across drivers.
+struct Recorder<'l> {
+    id: u32,
+    log: &'l std::cell::RefCell<Vec<(u32, u32)>>,
+}
+
+impl libtock_platform::Upcall<libtock_platform::subscribe::AnyId> for Recorder<'_> {
+    fn upcall(&self, arg0: u32, _: u32, _: u32) {
+        self.log.borrow_mut().push((self.id, arg0));
+    }
+}
+
+#[test]
+fn upcall_ordering() {
+    use crate::PendingUpcall;
+    use libtock_platform::{share, DefaultConfig, Syscalls};
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(1)));
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(2)));
+
+    let log = Default::default();
+    let a = Recorder { id: 10, log: &log };
+    let b = Recorder { id: 11, log: &log };
+    let c = Recorder { id: 20, log: &log };
+    share::scope::<
+        (
+            libtock_platform::Subscribe<_, 1, 0>,
+            libtock_platform::Subscribe<_, 1, 1>,
+            libtock_platform::Subscribe<_, 2, 0>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (ha, hb, hc) = handle.split();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 1, 0>(ha, &a).unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 1, 1>(hb, &b).unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 2, 0>(hc, &c).unwrap();
+
+        kernel.schedule_upcall(1, 0, (1, 0, 0));
+        kernel.schedule_upcall(1, 1, (2, 0, 0));
+        kernel.schedule_upcall(2, 0, (3, 0, 0));
+        assert_eq!(
+            kernel.pending_upcalls(),
+            [
+                PendingUpcall {
+                    driver_num: 1,
+                    subscribe_num: 0,
+                    args: (1, 0, 0)
+                },
+                PendingUpcall {
+                    driver_num: 1,
+                    subscribe_num: 1,
+                    args: (2, 0, 0)
+                },
+                PendingUpcall {
+                    driver_num: 2,
+                    subscribe_num: 0,
+                    args: (3, 0, 0)
+                },
+            ]
+        );
+
+        // Deliver the driver 2 upcall first, then the remaining two in
+        // reverse order.
+        assert!(kernel.prioritize_upcall(2, 0));
+        assert!(!kernel.prioritize_upcall(2, 1));
+        fake::Syscalls::yield_wait();
+        assert_eq!(*log.borrow(), [(20, 3)]);
+        kernel.reorder_upcalls(&[1, 0]);
+        fake::Syscalls::yield_wait();
+        fake::Syscalls::yield_wait();
+        assert_eq!(*log.borrow(), [(20, 3), (11, 2), (10, 1)]);
+        assert_eq!(kernel.pending_upcalls(), []);
+    });
+}
+
+#[test]
+#[should_panic = "not a permutation"]
+fn reorder_upcalls_invalid() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(1)));
+    let called: core::cell::Cell<Option<(u32,)>> = Default::default();
+    libtock_platform::share::scope(|subscribe| {
+        use libtock_platform::Syscalls;
+        fake::Syscalls::subscribe::<_, _, libtock_platform::DefaultConfig, 1, 0>(
+            subscribe, &called,
+        )
+        .unwrap();
+        kernel.schedule_upcall(1, 0, (1, 0, 0));
+        kernel.schedule_upcall(1, 0, (2, 0, 0));
+        kernel.reorder_upcalls(&[0, 0]);
+    });
+}
+
+#[test]
+fn add_expected_syscalls() {
+    use libtock_platform::{Syscalls, YieldNoWaitReturn};
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscalls(
+        crate::expect()
+            .yield_no_wait()
+            .returning(YieldNoWaitReturn::Upcall)
+            .yield_wait()
+            .skip_upcall(),
+    );
+    assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    fake::Syscalls::yield_wait();
+    kernel.assert_expected_syscalls_done();
+}
+
+#[test]
+#[should_panic = "Expected system calls were not made"]
+fn expected_syscalls_not_done() {
+    let kernel = fake::Kernel::new();
+    kernel.add_expected_syscalls(crate::expect().yield_wait());
+    kernel.assert_expected_syscalls_done();
+}
+
+#[test]
+fn assert_no_leaks() {
+    use libtock_platform::{share, AllowRo, DefaultConfig, Subscribe, Syscalls};
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(2)));
+    kernel.add_driver(&fake::Console::new());
+    kernel.check_leaks_on_drop();
+    let called: core::cell::Cell<Option<(u32,)>> = Default::default();
+    share::scope::<(AllowRo<_, 2, 0>, Subscribe<_, 2, 1>), _, _>(|handle| {
+        let (allow_ro, subscribe) = handle.split();
+        // UpcallDriver does not support Allow.
+        assert!(fake::Syscalls::allow_ro::<DefaultConfig, 2, 0>(allow_ro, b"abc").is_err());
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 2, 1>(subscribe, &called).unwrap();
+        let message =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| kernel.assert_no_leaks()))
+                .expect_err("assert_no_leaks did not detect the subscription")
+                .downcast::<String>()
+                .unwrap();
+        assert!(message.contains("Subscribe: driver 0x2, subscribe 1"));
+    });
+    kernel.assert_no_leaks();
+
+    share::scope(|allow_ro| {
+        // Console's write buffer.
+        fake::Syscalls::allow_ro::<DefaultConfig, 1, 1>(allow_ro, b"abc").unwrap();
+        let message =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| kernel.assert_no_leaks()))
+                .expect_err("assert_no_leaks did not detect the buffer")
+                .downcast::<String>()
+                .unwrap();
+        assert!(message.contains("Read-Only Allow: driver 0x1, buffer 1 (3 bytes)"));
+    });
+    kernel.assert_no_leaks();
+}
+
+#[test]
+#[should_panic = "Read-Write Allow: driver 0x1, buffer 1 (4 bytes)"]
+fn leaked_allow() {
+    use libtock_platform::{syscall_class, RawSyscalls};
+    static mut BUFFER: [u8; 4] = [0; 4];
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&fake::Console::new());
+    kernel.check_leaks_on_drop();
+    // Allow Console's read buffer and never un-Allow it, as a persistent Allow
+    // guard would if it were forgotten instead of dropped.
+    // Safety: BUFFER is only accessed by this test, and is 'static, so it
+    // remains valid after the kernel is dropped.
+    let [r0, ..] = unsafe {
+        fake::Syscalls::syscall4::<{ syscall_class::ALLOW_RW }>([
+            1u32.into(),
+            1u32.into(),
+            core::ptr::addr_of_mut!(BUFFER).cast::<u8>().into(),
+            4u32.into(),
+        ])
+    };
+    assert_eq!(
+        r0.try_into(),
+        Ok(Into::<u32>::into(
+            libtock_platform::return_variant::SUCCESS_2_U32
+        ))
+    );
+}
+
+// Makes a few system calls through UpcallDriver, for the trace tests. If
+// `command_id` is 1, the driver's upcall is scheduled before yielding.
+fn traced_calls(kernel: &fake::Kernel, command_id: u32) {
+    use libtock_platform::{share, DefaultConfig, Syscalls};
+    let called: core::cell::Cell<Option<(u32,)>> = Default::default();
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, 1, 0>(subscribe, &called).unwrap();
+        let _ = fake::Syscalls::command(1, command_id, 2, 3);
+        if command_id == 1 {
+            kernel.schedule_upcall(1, 0, (7, 0, 0));
+        }
+        fake::Syscalls::yield_no_wait();
+    });
+}
+
+#[test]
+fn trace() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(1)));
+    // Calls made before start_trace are not recorded.
+    traced_calls(&kernel, 0);
+    kernel.start_trace();
+    traced_calls(&kernel, 1);
+    let trace = kernel.take_trace();
+    assert_eq!(
+        trace.to_string(),
+        "\
+subscribe 0x1 0 -> ok
+command 0x1 1 2 3 -> 0 10 0 0
+yield-no-wait -> upcall
+upcall 0x1 0 7 0 0
+unsubscribe 0x1 0 -> ok
+"
+    );
+    assert_eq!(kernel.take_trace(), crate::Trace::default());
+    drop(kernel);
+
+    // Replaying the same calls succeeds.
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(1)));
+    kernel.replay_trace(trace.clone());
+    traced_calls(&kernel, 1);
+    kernel.assert_trace_replayed();
+    kernel.take_trace().assert_matches(&trace);
+}
+
+#[test]
+#[should_panic = "event 1: expected `command 0x1 1 2 3 -> 0 10 0 0`, got `command 0x1 0 2 3 -> 0 10 0 0`"]
+fn trace_replay_divergence() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(UpcallDriver(1)));
+    kernel.replay_trace(
+        "subscribe 0x1 0 -> ok\ncommand 0x1 1 2 3 -> 0 10 0 0"
+            .parse()
+            .unwrap(),
+    );
+    traced_calls(&kernel, 0);
+}
+
+#[test]
+#[should_panic = "1 of 2 events replayed; next expected event: yield-wait"]
+fn trace_not_replayed() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    kernel.replay_trace("yield-no-wait -> no-upcall\nyield-wait".parse().unwrap());
+    fake::Syscalls::yield_no_wait();
+    kernel.assert_trace_replayed();
+}
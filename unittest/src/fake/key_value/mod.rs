@@ -1,8 +1,15 @@
+//! Fake implementation of the Key-Value API.
+//!
+//! `KeyValue` stores keys and values (arbitrary bytes) in an in-memory map.
+//! Operations complete immediately, scheduling their upcall from the command.
+//! Tests can preload and inspect the store (`insert`, `get`), limit its size
+//! to simulate a full store (`set_quota`), and make the next operation fail
+//! (`fail_next`).
+
 use libtock_platform::{CommandReturn, ErrorCode};
 
 use core::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::str;
 
 use crate::{DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
 
@@ -14,7 +21,9 @@ pub struct KeyValue {
 
     share_ref: DriverShareRef,
 
-    database: RefCell<HashMap<String, String>>,
+    database: RefCell<HashMap<Vec<u8>, Vec<u8>>>,
+    quota: Cell<Option<usize>>,
+    next_error: Cell<Option<ErrorCode>>,
 }
 
 impl KeyValue {
@@ -27,8 +36,98 @@ impl KeyValue {
             share_ref: Default::default(),
 
             database: Default::default(),
+            quota: Default::default(),
+            next_error: Default::default(),
         })
     }
+
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.database.borrow().get(key).cloned()
+    }
+
+    /// Stores `value` under `key`, as if the process had set it. Ignores the
+    /// quota.
+    pub fn insert(&self, key: &[u8], value: &[u8]) {
+        self.database.borrow_mut().insert(key.into(), value.into());
+    }
+
+    /// Returns the number of stored keys.
+    pub fn len(&self) -> usize {
+        self.database.borrow().len()
+    }
+
+    /// Returns true if the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.database.borrow().is_empty()
+    }
+
+    /// Returns the number of bytes used by the store: the sum of the lengths
+    /// of its keys and values.
+    pub fn used_bytes(&self) -> usize {
+        self.database
+            .borrow()
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+
+    /// Limits the store to `quota` bytes (as counted by `used_bytes`), or
+    /// removes the limit if `quota` is `None`. Writes that would exceed the
+    /// quota fail with `NoMem`.
+    pub fn set_quota(&self, quota: Option<usize>) {
+        self.quota.set(quota);
+    }
+
+    /// Makes the next get, set, add, update, or delete operation fail with
+    /// `error` (e.g. `NoMem` or `NoSupport`) without touching the store. The
+    /// error is delivered through the upcall, as the real driver's errors are.
+    pub fn fail_next(&self, error: ErrorCode) {
+        self.next_error.set(Some(error));
+    }
+
+    // Performs the operation `command_id` on the allowed key (and value),
+    // returning the upcall's second argument (the value length for get).
+    fn operation(&self, command_id: u32) -> Result<u32, ErrorCode> {
+        if let Some(error) = self.next_error.take() {
+            return Err(error);
+        }
+        let key = self.buffer_in_key.take();
+        let value = self.buffer_in_val.take();
+        let result = self.operation_on(command_id, &key, &value);
+        self.buffer_in_key.set(key);
+        self.buffer_in_val.set(value);
+        result
+    }
+
+    fn operation_on(&self, command_id: u32, key: &[u8], value: &[u8]) -> Result<u32, ErrorCode> {
+        let mut db = self.database.borrow_mut();
+        match command_id {
+            CMD_GET => {
+                let val = db.get(key).ok_or(ErrorCode::NoSupport)?;
+                let mut out = self.buffer_out_val.borrow_mut();
+                let cp_len = core::cmp::min(out.len(), val.len());
+                out[..cp_len].copy_from_slice(&val[..cp_len]);
+                return Ok(val.len() as u32);
+            }
+            CMD_DELETE => {
+                db.remove(key).ok_or(ErrorCode::NoSupport)?;
+                return Ok(0);
+            }
+            CMD_ADD if db.contains_key(key) => return Err(ErrorCode::NoSupport),
+            CMD_UPDATE if !db.contains_key(key) => return Err(ErrorCode::NoSupport),
+            _ => {}
+        }
+        if let Some(quota) = self.quota.get() {
+            let replaced = db.get(key).map_or(0, |old| key.len() + old.len());
+            let used: usize = db.iter().map(|(k, v)| k.len() + v.len()).sum();
+            if used - replaced + key.len() + value.len() > quota {
+                return Err(ErrorCode::NoMem);
+            }
+        }
+        db.insert(key.into(), value.into());
+        Ok(0)
+    }
 }
 
 impl crate::fake::SyscallDriver for KeyValue {
@@ -66,137 +165,14 @@ impl crate::fake::SyscallDriver for KeyValue {
     fn command(&self, command_id: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
         match command_id {
             CMD_DRIVER_CHECK => crate::command_return::success(),
-            CMD_GET => {
-                let k = self.buffer_in_key.take();
-                let k_str = str::from_utf8(&k).unwrap();
-
-                let db = self.database.take();
-                match db.get(k_str) {
-                    Some(val) => {
-                        let cp_len = core::cmp::min(self.buffer_out_val.borrow().len(), val.len());
-                        self.buffer_out_val.borrow_mut()[..cp_len]
-                            .copy_from_slice(&val.as_bytes()[..cp_len]);
-
-                        self.share_ref
-                            .schedule_upcall(SUB_CALLBACK, (0, val.len() as u32, 0))
-                            .expect("Unable to schedule upcall {}");
-                    }
-                    _ => {
-                        self.share_ref
-                            .schedule_upcall(SUB_CALLBACK, (ErrorCode::NoSupport as u32, 0, 0))
-                            .expect("Unable to schedule upcall {}");
-                    }
-                }
-                self.buffer_in_key.set(k);
-                self.database.replace(db);
-
-                crate::command_return::success()
-            }
-            CMD_SET => {
-                let k = self.buffer_in_key.take();
-                let k_str = str::from_utf8(&k).unwrap();
-                let v = self.buffer_in_val.take();
-                let v_str = str::from_utf8(&v).unwrap();
-
-                let mut db = self.database.take();
-                db.insert(k_str.to_string(), v_str.to_string());
-
-                self.buffer_in_key.set(k);
-                self.buffer_in_val.set(v);
-                self.database.replace(db);
-
+            CMD_GET | CMD_SET | CMD_DELETE | CMD_ADD | CMD_UPDATE => {
+                let args = match self.operation(command_id) {
+                    Ok(len) => (0, len, 0),
+                    Err(error) => (error as u32, 0, 0),
+                };
                 self.share_ref
-                    .schedule_upcall(SUB_CALLBACK, (0, 0, 0))
-                    .expect("Unable to schedule upcall {}");
-
-                crate::command_return::success()
-            }
-            CMD_ADD => {
-                let k = self.buffer_in_key.take();
-                let k_str = str::from_utf8(&k).unwrap();
-                let v = self.buffer_in_val.take();
-                let v_str = str::from_utf8(&v).unwrap();
-
-                let mut db = self.database.take();
-
-                let mut found = false;
-                if let Some(_val) = db.get(k_str) {
-                    self.share_ref
-                        .schedule_upcall(SUB_CALLBACK, (ErrorCode::NoSupport as u32, 0, 0))
-                        .expect("Unable to schedule upcall {}");
-                    found = true;
-                }
-
-                if !found {
-                    db.insert(k_str.to_string(), v_str.to_string());
-
-                    self.share_ref
-                        .schedule_upcall(SUB_CALLBACK, (0, 0, 0))
-                        .expect("Unable to schedule upcall {}");
-                }
-
-                self.buffer_in_key.set(k);
-                self.buffer_in_val.set(v);
-                self.database.replace(db);
-
-                crate::command_return::success()
-            }
-            CMD_UPDATE => {
-                let k = self.buffer_in_key.take();
-                let k_str = str::from_utf8(&k).unwrap();
-                let v = self.buffer_in_val.take();
-                let v_str = str::from_utf8(&v).unwrap();
-
-                let mut db = self.database.take();
-
-                let mut found = false;
-                match db.get(k_str) {
-                    Some(_val) => {
-                        found = true;
-                    }
-                    _ => {
-                        self.share_ref
-                            .schedule_upcall(SUB_CALLBACK, (ErrorCode::NoSupport as u32, 0, 0))
-                            .expect("Unable to schedule upcall {}");
-                    }
-                }
-
-                if found {
-                    db.insert(k_str.to_string(), v_str.to_string());
-
-                    self.share_ref
-                        .schedule_upcall(SUB_CALLBACK, (0, 0, 0))
-                        .expect("Unable to schedule upcall {}");
-                }
-
-                self.buffer_in_key.set(k);
-                self.buffer_in_val.set(v);
-                self.database.replace(db);
-
-                crate::command_return::success()
-            }
-            CMD_DELETE => {
-                let k = self.buffer_in_key.take();
-                let k_str = str::from_utf8(&k).unwrap();
-
-                let mut db = self.database.take();
-
-                match db.remove(k_str) {
-                    Some(_val) => {
-                        self.share_ref
-                            .schedule_upcall(SUB_CALLBACK, (0, 0, 0))
-                            .expect("Unable to schedule upcall {}");
-                    }
-                    _ => {
-                        self.share_ref
-                            .schedule_upcall(SUB_CALLBACK, (ErrorCode::NoSupport as u32, 0, 0))
-                            .expect("Unable to schedule upcall {}");
-                    }
-                }
-
-                self.buffer_in_key.set(k);
-                self.database.replace(db);
-
+                    .schedule_upcall(SUB_CALLBACK, args)
+                    .expect("Unable to schedule upcall");
                 crate::command_return::success()
             }
             _ => crate::command_return::failure(ErrorCode::NoSupport),
@@ -204,6 +180,9 @@ impl crate::fake::SyscallDriver for KeyValue {
     }
 }
 
+#[cfg(test)]
+mod tests;
+
 // -----------------------------------------------------------------------------
 // Driver number and command IDs
 // -----------------------------------------------------------------------------
@@ -0,0 +1,100 @@
+use crate::fake::{self, key_value::*};
+use libtock_platform::{share, AllowRo, AllowRw, DefaultConfig, Subscribe, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let kv = KeyValue::new();
+    assert!(kv.command(CMD_DRIVER_CHECK, 0, 0).is_success());
+    assert_eq!(
+        kv.command(6, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+
+    // Values are arbitrary bytes.
+    assert!(kv.is_empty());
+    kv.insert(b"key", &[0xff, 0, 0xfe]);
+    assert_eq!(kv.get(b"key"), Some(vec![0xff, 0, 0xfe]));
+    assert_eq!(kv.get(b"other"), None);
+    assert_eq!(kv.len(), 1);
+    assert_eq!(kv.used_bytes(), 6);
+
+    // The quota counts keys and values, and replaced values are not counted.
+    kv.set_quota(Some(8));
+    assert_eq!(kv.operation_on(CMD_SET, b"key", b"12345"), Ok(0));
+    assert_eq!(
+        kv.operation_on(CMD_SET, b"key", b"123456"),
+        Err(ErrorCode::NoMem)
+    );
+    assert_eq!(kv.operation_on(CMD_ADD, b"k", b""), Err(ErrorCode::NoMem));
+    assert_eq!(kv.operation_on(CMD_DELETE, b"key", b""), Ok(0));
+    assert_eq!(kv.operation_on(CMD_ADD, b"k", b""), Ok(0));
+    kv.set_quota(None);
+    assert_eq!(kv.operation_on(CMD_UPDATE, b"k", &[7; 100]), Ok(0));
+
+    // Injected errors apply to one operation, and leave the store untouched.
+    kv.fail_next(ErrorCode::NoSupport);
+    assert_eq!(kv.operation(CMD_DELETE), Err(ErrorCode::NoSupport));
+    assert_eq!(kv.operation(CMD_SET), Ok(0));
+    assert_eq!(kv.get(b""), Some(vec![]));
+    assert_eq!(kv.len(), 2);
+}
+
+// Integration test that verifies KeyValue works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let kv = KeyValue::new();
+    kernel.add_driver(&kv);
+
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    let run = |command_id, key: &[u8], value: &[u8], out: &mut [u8]| {
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, RO_ALLOW_KEY>,
+                AllowRo<_, DRIVER_NUM, RO_ALLOW_VAL>,
+                AllowRw<_, DRIVER_NUM, RW_ALLOW_VAL>,
+                Subscribe<_, DRIVER_NUM, SUB_CALLBACK>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_key, allow_val, allow_out, subscribe) = handle.split();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, RO_ALLOW_KEY>(allow_key, key)
+                .unwrap();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, RO_ALLOW_VAL>(allow_val, value)
+                .unwrap();
+            fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, RW_ALLOW_VAL>(allow_out, out)
+                .unwrap();
+            fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUB_CALLBACK>(
+                subscribe, &upcall,
+            )
+            .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, command_id, 0, 0).is_success());
+            fake::Syscalls::yield_wait();
+        });
+        upcall.get()
+    };
+
+    let mut out = [0; 4];
+    assert_eq!(run(CMD_SET, b"key", b"value", &mut out), Some((0, 0)));
+    assert_eq!(kv.get(b"key"), Some(b"value".to_vec()));
+    // Reads are truncated to the buffer, but report the full length.
+    assert_eq!(run(CMD_GET, b"key", b"", &mut out), Some((0, 5)));
+    assert_eq!(&out, b"valu");
+
+    kv.set_quota(Some(8));
+    assert_eq!(
+        run(CMD_UPDATE, b"key", b"values", &mut out),
+        Some((ErrorCode::NoMem as u32, 0))
+    );
+    kv.fail_next(ErrorCode::NoSupport);
+    assert_eq!(
+        run(CMD_GET, b"key", b"", &mut out),
+        Some((ErrorCode::NoSupport as u32, 0))
+    );
+    assert_eq!(run(CMD_DELETE, b"key", b"", &mut out), Some((0, 0)));
+    assert!(kv.is_empty());
+}
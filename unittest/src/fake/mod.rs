@@ -10,44 +10,76 @@
 //! (e.g. `fake::Console`).
 
 mod adc;
+mod aes;
 mod air_quality;
 mod alarm;
 mod ambient_light;
+mod battery;
 mod buttons;
 mod buzzer;
+mod chip_configuration;
 mod console;
+mod ctap_hid;
 mod gpio;
+mod hmac;
+mod humidity;
 pub mod ieee802154;
+mod ipc;
 mod kernel;
 mod key_value;
 mod leds;
 mod low_level_debug;
+mod nfc;
 mod ninedof;
+mod pressure;
 mod proximity;
+mod reboot;
+mod rng;
+mod screen;
+mod sha;
 mod sound_pressure;
+mod supply_monitor;
 mod syscall_driver;
 mod syscalls;
 mod temperature;
+mod usb_bulk;
+mod watchdog;
 
 pub use adc::Adc;
+pub use aes::{Aes, AesMode};
 pub use air_quality::AirQuality;
 pub use alarm::Alarm;
 pub use ambient_light::AmbientLight;
+pub use battery::Battery;
 pub use buttons::Buttons;
 pub use buzzer::Buzzer;
+pub use chip_configuration::ChipConfiguration;
 pub use console::Console;
+pub use ctap_hid::CtapHid;
 pub use gpio::{Gpio, GpioMode, InterruptEdge, PullMode};
+pub use hmac::Hmac;
+pub use humidity::Humidity;
 pub use ieee802154::Ieee802154Phy;
+pub use ipc::{Ipc, IpcNotification};
 pub use kernel::Kernel;
 pub use key_value::KeyValue;
 pub use leds::Leds;
 pub use low_level_debug::{LowLevelDebug, Message};
+pub use nfc::Nfc;
 pub use ninedof::{NineDof, NineDofData};
+pub use pressure::Pressure;
 pub use proximity::Proximity;
+pub use reboot::Reboot;
+pub use rng::Rng;
+pub use screen::Screen;
+pub use sha::{Sha, ShaAlgorithm};
 pub use sound_pressure::SoundPressure;
+pub use supply_monitor::SupplyMonitor;
 pub use syscall_driver::SyscallDriver;
 pub use syscalls::Syscalls;
 pub use temperature::Temperature;
+pub use usb_bulk::UsbBulk;
+pub use watchdog::Watchdog;
 
 #[cfg(test)]
 mod kernel_tests;
@@ -0,0 +1,188 @@
+//! Fake implementation of the NFC tag emulation driver.
+//!
+//! `Nfc` plays both the tag's controller and the reader. Tests select the tag
+//! with `select`, and queue the reader's frames with `queue_frame`. A receive
+//! completes immediately if a frame is queued, and otherwise stays pending
+//! until one is. Transmitted frames are recorded, and retrieved with
+//! `take_transmitted`. Frames are only exchanged while emulation is enabled.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::collections::VecDeque;
+
+pub struct Nfc {
+    emulating: Cell<bool>,
+    tag_type: Cell<Option<u32>>,
+    frames: RefCell<VecDeque<Vec<u8>>>,
+    transmitted: RefCell<Vec<Vec<u8>>>,
+    // The length requested by the pending receive, if any.
+    pending_receive: Cell<Option<usize>>,
+    tx_buffer: Cell<RoAllowBuffer>,
+    rx_buffer: RefCell<RwAllowBuffer>,
+    share_ref: DriverShareRef,
+}
+
+impl Nfc {
+    pub fn new() -> std::rc::Rc<Nfc> {
+        std::rc::Rc::new(Nfc {
+            emulating: Cell::new(false),
+            tag_type: Cell::new(None),
+            frames: Default::default(),
+            transmitted: Default::default(),
+            pending_receive: Cell::new(None),
+            tx_buffer: Default::default(),
+            rx_buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns whether emulation is enabled.
+    pub fn is_emulating(&self) -> bool {
+        self.emulating.get()
+    }
+
+    /// Returns the tag type set by the app, if any.
+    pub fn tag_type(&self) -> Option<u32> {
+        self.tag_type.get()
+    }
+
+    /// Selects the tag, as a reader coming in range does.
+    pub fn select(&self) {
+        self.share_ref
+            .schedule_upcall(SUBSCRIBE_SELECTED, (0, 0, 0))
+            .expect("Unable to schedule upcall");
+    }
+
+    /// Queues a frame from the reader. If a receive is pending, it is
+    /// completed with the frame.
+    pub fn queue_frame(&self, frame: &[u8]) {
+        self.frames.borrow_mut().push_back(frame.to_vec());
+        self.deliver_frame();
+    }
+
+    /// Returns the frames transmitted so far, and clears them.
+    pub fn take_transmitted(&self) -> Vec<Vec<u8>> {
+        self.transmitted.take()
+    }
+
+    // Completes the pending receive, if there is one and a frame is queued.
+    fn deliver_frame(&self) {
+        let Some(len) = self.pending_receive.get() else {
+            return;
+        };
+        let Some(frame) = self.frames.borrow_mut().pop_front() else {
+            return;
+        };
+        self.pending_receive.set(None);
+        let mut buffer = self.rx_buffer.borrow_mut();
+        let status = if frame.len() > len.min(buffer.len()) {
+            ErrorCode::Size as u32
+        } else {
+            buffer[..frame.len()].copy_from_slice(&frame);
+            0
+        };
+        self.share_ref
+            .schedule_upcall(SUBSCRIBE_RECEIVED, (status, frame.len() as u32, 0))
+            .expect("Unable to schedule upcall");
+    }
+}
+
+impl crate::fake::SyscallDriver for Nfc {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(3)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_TX => Ok(self.tx_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_RX => Ok(self.rx_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => {}
+            TRANSMIT | RECEIVE if !self.emulating.get() => {
+                return command_return::failure(ErrorCode::Off)
+            }
+            TRANSMIT => {
+                let buffer = self.tx_buffer.take();
+                if argument0 as usize > buffer.len() {
+                    self.tx_buffer.set(buffer);
+                    return command_return::failure(ErrorCode::Size);
+                }
+                self.transmitted
+                    .borrow_mut()
+                    .push(buffer[..argument0 as usize].to_vec());
+                self.tx_buffer.set(buffer);
+                self.share_ref
+                    .schedule_upcall(SUBSCRIBE_TRANSMITTED, (0, 0, 0))
+                    .expect("Unable to schedule upcall");
+            }
+            RECEIVE => {
+                if self.pending_receive.get().is_some() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                self.pending_receive.set(Some(argument0 as usize));
+                self.deliver_frame();
+            }
+            ENABLE_EMULATION => {
+                if self.tag_type.get().is_none() {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.emulating.set(true);
+            }
+            DISABLE_EMULATION => self.emulating.set(false),
+            SET_TAG_TYPE => match argument0 {
+                2 | 4 => self.tag_type.set(Some(argument0)),
+                _ => return command_return::failure(ErrorCode::NoSupport),
+            },
+            _ => return command_return::failure(ErrorCode::NoSupport),
+        }
+        command_return::success()
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x30003;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const TRANSMIT: u32 = 1;
+const RECEIVE: u32 = 2;
+const ENABLE_EMULATION: u32 = 3;
+const DISABLE_EMULATION: u32 = 4;
+const SET_TAG_TYPE: u32 = 5;
+
+const SUBSCRIBE_TRANSMITTED: u32 = 0;
+const SUBSCRIBE_RECEIVED: u32 = 1;
+const SUBSCRIBE_SELECTED: u32 = 2;
+
+const ALLOW_TX: u32 = 0;
+const ALLOW_RX: u32 = 0;
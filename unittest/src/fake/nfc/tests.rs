@@ -0,0 +1,84 @@
+use crate::fake::{self, nfc::*};
+use libtock_platform::{share, AllowRw, DefaultConfig, Subscribe, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let nfc = Nfc::new();
+    assert!(nfc.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        nfc.command(TRANSMIT, 0, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    assert_eq!(
+        nfc.command(ENABLE_EMULATION, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        nfc.command(SET_TAG_TYPE, 3, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+    assert!(nfc.command(SET_TAG_TYPE, 4, 0).is_success());
+    assert_eq!(nfc.tag_type(), Some(4));
+    assert!(nfc.command(ENABLE_EMULATION, 0, 0).is_success());
+    assert!(nfc.is_emulating());
+    assert_eq!(
+        nfc.command(TRANSMIT, 1, 0).get_failure(),
+        Some(ErrorCode::Size)
+    );
+    assert!(nfc.command(RECEIVE, 4, 0).is_success());
+    assert_eq!(
+        nfc.command(RECEIVE, 4, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    assert!(nfc.command(DISABLE_EMULATION, 0, 0).is_success());
+    assert!(!nfc.is_emulating());
+}
+
+// Integration test that verifies Nfc works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let nfc = Nfc::new();
+    kernel.add_driver(&nfc);
+    assert!(fake::Syscalls::command(DRIVER_NUM, SET_TAG_TYPE, 4, 0).is_success());
+    assert!(fake::Syscalls::command(DRIVER_NUM, ENABLE_EMULATION, 0, 0).is_success());
+
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    let mut buffer = [0; 4];
+    share::scope::<
+        (
+            AllowRw<_, DRIVER_NUM, ALLOW_RX>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_RECEIVED>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_RX>(allow_rw, &mut buffer)
+            .unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_RECEIVED>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+
+        // The receive stays pending until a frame is queued.
+        assert!(fake::Syscalls::command(DRIVER_NUM, RECEIVE, 4, 0).is_success());
+        assert_eq!(
+            fake::Syscalls::yield_no_wait(),
+            libtock_platform::YieldNoWaitReturn::NoUpcall
+        );
+        nfc.queue_frame(&[1, 2, 3]);
+        fake::Syscalls::yield_wait();
+        assert_eq!(upcall.get(), Some((0, 3)));
+
+        // Frames that do not fit fail.
+        nfc.queue_frame(&[1, 2, 3, 4, 5]);
+        assert!(fake::Syscalls::command(DRIVER_NUM, RECEIVE, 4, 0).is_success());
+        fake::Syscalls::yield_wait();
+        assert_eq!(upcall.get(), Some((ErrorCode::Size as u32, 5)));
+    });
+    assert_eq!(buffer, [1, 2, 3, 0]);
+}
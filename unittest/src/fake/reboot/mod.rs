@@ -0,0 +1,66 @@
+//! Fake implementation of the Reboot API.
+//!
+//! Instead of resetting anything, `Reboot` counts the reboot requests it
+//! receives, which tests can inspect via `reboot_count`. An error can be
+//! injected with `set_error` to simulate a board that cannot reset itself.
+
+use crate::DriverInfo;
+use core::cell::Cell;
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct Reboot {
+    reboot_count: Cell<u32>,
+    error: Cell<Option<ErrorCode>>,
+}
+
+impl Reboot {
+    pub fn new() -> std::rc::Rc<Reboot> {
+        std::rc::Rc::new(Reboot {
+            reboot_count: Cell::new(0),
+            error: Cell::new(None),
+        })
+    }
+
+    /// Returns the number of successful reboot requests so far.
+    pub fn reboot_count(&self) -> u32 {
+        self.reboot_count.get()
+    }
+
+    /// If set to `Some`, reboot requests fail with the given error.
+    pub fn set_error(&self, error: Option<ErrorCode>) {
+        self.error.set(error);
+    }
+}
+
+impl crate::fake::SyscallDriver for Reboot {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM)
+    }
+
+    fn command(&self, command_num: u32, _argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_num {
+            EXISTS => crate::command_return::success(),
+            REBOOT => match self.error.get() {
+                Some(error) => crate::command_return::failure(error),
+                None => {
+                    self.reboot_count.set(self.reboot_count.get() + 1);
+                    crate::command_return::success()
+                }
+            },
+            _ => crate::command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90068;
+
+// Command numbers
+const EXISTS: u32 = 0;
+const REBOOT: u32 = 1;
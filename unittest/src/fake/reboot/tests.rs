@@ -0,0 +1,32 @@
+use crate::fake;
+use fake::reboot::*;
+use libtock_platform::ErrorCode;
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let reboot = Reboot::new();
+    assert!(reboot.command(EXISTS, 0, 0).is_success());
+    assert!(reboot.command(REBOOT, 0, 0).is_success());
+    assert_eq!(reboot.reboot_count(), 1);
+    reboot.set_error(Some(ErrorCode::NoSupport));
+    assert_eq!(
+        reboot.command(REBOOT, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+    assert_eq!(reboot.reboot_count(), 1);
+}
+
+// Integration test that verifies Reboot works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let reboot = Reboot::new();
+    kernel.add_driver(&reboot);
+    assert!(fake::Syscalls::command(DRIVER_NUM, EXISTS, 0, 0).is_success());
+    assert!(fake::Syscalls::command(DRIVER_NUM, REBOOT, 0, 0).is_success());
+    assert_eq!(reboot.reboot_count(), 1);
+}
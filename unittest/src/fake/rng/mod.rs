@@ -0,0 +1,106 @@
+//! Fake implementation of the RNG API, documented here:
+//! https://github.com/tock/tock/blob/master/doc/syscalls/40001_rng.md
+//!
+//! `Rng` produces bytes from a seeded pseudorandom generator, so a test sees
+//! the same "random" bytes on every run. Requests complete immediately: the
+//! get-bytes command fills the allowed buffer and schedules the upcall.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RwAllowBuffer};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct Rng {
+    state: Cell<u64>,
+    buffer: RefCell<RwAllowBuffer>,
+    share_ref: DriverShareRef,
+}
+
+impl Rng {
+    /// Creates an `Rng` with the default seed, 0.
+    pub fn new() -> std::rc::Rc<Rng> {
+        Self::with_seed(0)
+    }
+
+    /// Creates an `Rng` whose output is determined by `seed`.
+    pub fn with_seed(seed: u64) -> std::rc::Rc<Rng> {
+        std::rc::Rc::new(Rng {
+            state: Cell::new(seed),
+            buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Fills `bytes` with the next bytes of this `Rng`'s output, as if they
+    /// were requested by the process.
+    pub fn fill(&self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let word = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+
+    // SplitMix64: small, fast, and fully determined by the seed, which is all
+    // tests need. It is not cryptographically secure.
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl crate::fake::SyscallDriver for Rng {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        if buffer_num == ALLOW_BUFFER {
+            Ok(self.buffer.replace(buffer))
+        } else {
+            Err((buffer, ErrorCode::Invalid))
+        }
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => command_return::success(),
+            GET_BYTES => {
+                let mut buffer = self.buffer.borrow_mut();
+                let count = core::cmp::min(argument0 as usize, buffer.len());
+                self.fill(&mut buffer[..count]);
+                self.share_ref
+                    .schedule_upcall(SUBSCRIBE_BYTES, (0, count as u32, 0))
+                    .expect("Unable to schedule upcall");
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40001;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const GET_BYTES: u32 = 1;
+
+const ALLOW_BUFFER: u32 = 0;
+const SUBSCRIBE_BYTES: u32 = 0;
@@ -0,0 +1,59 @@
+use crate::fake::{self, rng::*};
+use libtock_platform::{share, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let rng = Rng::new();
+    assert!(rng.command(EXISTS, 0, 0).is_success());
+    // Without a buffer, the request completes with no bytes.
+    assert!(rng.command(GET_BYTES, 4, 0).is_success());
+    assert_eq!(
+        rng.command(2, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+
+    // The output is determined by the seed.
+    let (mut a, mut b, mut c) = ([0; 12], [0; 12], [0; 12]);
+    Rng::with_seed(7).fill(&mut a);
+    Rng::with_seed(7).fill(&mut b);
+    Rng::with_seed(8).fill(&mut c);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+// Integration test that verifies Rng works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let rng = Rng::with_seed(42);
+    kernel.add_driver(&rng);
+    let reference = Rng::with_seed(42);
+
+    let mut buffer = [0; 6];
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    let mut get_bytes = |count| {
+        share::scope::<(AllowRw<_, DRIVER_NUM, 0>, Subscribe<_, DRIVER_NUM, 0>), _, _>(|handle| {
+            let (allow_rw, subscribe) = handle.split();
+            fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 0>(allow_rw, &mut buffer)
+                .unwrap();
+            fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe, &upcall)
+                .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, GET_BYTES, count, 0).is_success());
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        });
+        buffer
+    };
+
+    let mut expected = [0; 6];
+    reference.fill(&mut expected[..4]);
+    assert_eq!(get_bytes(4), expected);
+    assert_eq!(upcall.get(), Some((0, 4)));
+
+    // Requests are limited to the buffer's length.
+    reference.fill(&mut expected);
+    assert_eq!(get_bytes(10), expected);
+    assert_eq!(upcall.get(), Some((0, 6)));
+}
@@ -0,0 +1,338 @@
+//! Fake implementation of the screen driver.
+//!
+//! `Screen` keeps a framebuffer holding the value of each pixel, in the
+//! screen's rotated coordinates, which tests inspect with `pixel`. Writes and
+//! fills decode the shared buffer in the current pixel format (pixels wider
+//! than a byte are big-endian) and update the write frame. Operations complete
+//! immediately, scheduling their upcall from the command.
+//!
+//! By default the screen has a single resolution and pixel format (RGB565),
+//! and does not support changing them; `set_modes` enables that. Rotating the
+//! screen by 90 or 270 degrees swaps its width and height, and like changing
+//! the resolution, clears the framebuffer.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer};
+use core::cell::{Cell, RefCell};
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct Screen {
+    // Unrotated resolution.
+    resolution: Cell<(u32, u32)>,
+    resolution_modes: RefCell<Vec<(u32, u32)>>,
+    pixel_format: Cell<u32>,
+    pixel_formats: RefCell<Vec<u32>>,
+    has_setup: Cell<bool>,
+    rotation: Cell<u32>,
+    power: Cell<bool>,
+    brightness: Cell<u16>,
+    invert: Cell<bool>,
+    // (x, y, width, height)
+    write_frame: Cell<(u32, u32, u32, u32)>,
+    framebuffer: RefCell<Vec<u32>>,
+    write_count: Cell<usize>,
+    next_error: Cell<Option<ErrorCode>>,
+    buffer: Cell<RoAllowBuffer>,
+    share_ref: DriverShareRef,
+}
+
+impl Screen {
+    /// Creates a `width` by `height` RGB565 screen, which is off and whose
+    /// write frame covers the whole screen.
+    pub fn new(width: u32, height: u32) -> std::rc::Rc<Screen> {
+        std::rc::Rc::new(Screen {
+            resolution: Cell::new((width, height)),
+            resolution_modes: RefCell::new(vec![(width, height)]),
+            pixel_format: Cell::new(PIXEL_FORMAT_RGB565),
+            pixel_formats: RefCell::new(vec![PIXEL_FORMAT_RGB565]),
+            has_setup: Cell::new(false),
+            rotation: Cell::new(0),
+            power: Cell::new(false),
+            brightness: Cell::new(0),
+            invert: Cell::new(false),
+            write_frame: Cell::new((0, 0, width, height)),
+            framebuffer: RefCell::new(vec![0; (width * height) as usize]),
+            write_count: Cell::new(0),
+            next_error: Cell::new(None),
+            buffer: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Makes the resolutions in `resolutions` and the pixel formats (as the
+    /// kernel numbers them) in `pixel_formats` available to `set_resolution`
+    /// and `set_pixel_format`, and reports that the screen supports setup.
+    pub fn set_modes(&self, resolutions: &[(u32, u32)], pixel_formats: &[u32]) {
+        self.resolution_modes.replace(resolutions.to_vec());
+        self.pixel_formats.replace(pixel_formats.to_vec());
+        self.has_setup.set(true);
+    }
+
+    /// Returns the current resolution, accounting for the rotation.
+    pub fn resolution(&self) -> (u32, u32) {
+        let (width, height) = self.resolution.get();
+        match self.rotation.get() % 2 {
+            0 => (width, height),
+            _ => (height, width),
+        }
+    }
+
+    /// Returns the current pixel format, as the kernel numbers it.
+    pub fn pixel_format(&self) -> u32 {
+        self.pixel_format.get()
+    }
+
+    /// Returns the current rotation, as the kernel numbers it.
+    pub fn rotation(&self) -> u32 {
+        self.rotation.get()
+    }
+
+    /// Returns whether the screen is on.
+    pub fn is_on(&self) -> bool {
+        self.power.get()
+    }
+
+    pub fn brightness(&self) -> u16 {
+        self.brightness.get()
+    }
+
+    pub fn is_inverted(&self) -> bool {
+        self.invert.get()
+    }
+
+    /// Returns the current write frame, as `(x, y, width, height)`.
+    pub fn write_frame(&self) -> (u32, u32, u32, u32) {
+        self.write_frame.get()
+    }
+
+    /// Returns the value of the pixel at (`x`, `y`).
+    pub fn pixel(&self, x: u32, y: u32) -> u32 {
+        let (width, _) = self.resolution();
+        self.framebuffer.borrow()[(y * width + x) as usize]
+    }
+
+    /// Returns the number of writes and fills the screen has completed.
+    pub fn write_count(&self) -> usize {
+        self.write_count.get()
+    }
+
+    /// Makes the next operation that completes through the upcall fail with
+    /// `error`, without changing the screen.
+    pub fn fail_next(&self, error: ErrorCode) {
+        self.next_error.set(Some(error));
+    }
+
+    // Clears the framebuffer, e.g. after its dimensions changed, and resets
+    // the write frame to the whole screen.
+    fn reset_framebuffer(&self) {
+        let (width, height) = self.resolution();
+        self.framebuffer.replace(vec![0; (width * height) as usize]);
+        self.write_frame.set((0, 0, width, height));
+    }
+
+    // Stores `pixels` in the write frame, row by row, stopping at the end of
+    // the frame.
+    fn draw(&self, pixels: impl Iterator<Item = u32>) {
+        let (x, y, width, height) = self.write_frame.get();
+        let (screen_width, _) = self.resolution();
+        let mut framebuffer = self.framebuffer.borrow_mut();
+        for (index, pixel) in pixels.take((width * height) as usize).enumerate() {
+            let index = index as u32;
+            let (px, py) = (x + index % width, y + index / width);
+            framebuffer[(py * screen_width + px) as usize] = pixel;
+        }
+        self.write_count.set(self.write_count.get() + 1);
+    }
+
+    // Performs `command_id`, which completes through the upcall.
+    fn operation(&self, command_id: u32, argument0: u32, argument1: u32) -> Result<(), ErrorCode> {
+        if let Some(error) = self.next_error.take() {
+            return Err(error);
+        }
+        match command_id {
+            SET_POWER => self.power.set(argument0 != 0),
+            SET_BRIGHTNESS => self.brightness.set(argument0 as u16),
+            SET_INVERT => self.invert.set(argument0 != 0),
+            SET_ROTATION => {
+                if argument0 > 3 {
+                    return Err(ErrorCode::Invalid);
+                }
+                self.rotation.set(argument0);
+                self.reset_framebuffer();
+            }
+            SET_RESOLUTION => {
+                if !self.has_setup.get() {
+                    return Err(ErrorCode::NoSupport);
+                }
+                if !self
+                    .resolution_modes
+                    .borrow()
+                    .contains(&(argument0, argument1))
+                {
+                    return Err(ErrorCode::Invalid);
+                }
+                self.resolution.set((argument0, argument1));
+                self.reset_framebuffer();
+            }
+            SET_PIXEL_FORMAT => {
+                if !self.has_setup.get() {
+                    return Err(ErrorCode::NoSupport);
+                }
+                if !self.pixel_formats.borrow().contains(&argument0) {
+                    return Err(ErrorCode::Invalid);
+                }
+                self.pixel_format.set(argument0);
+            }
+            SET_WRITE_FRAME => {
+                let (x, y) = (argument0 >> 16, argument0 & 0xFFFF);
+                let (width, height) = (argument1 >> 16, argument1 & 0xFFFF);
+                let (screen_width, screen_height) = self.resolution();
+                if x + width > screen_width || y + height > screen_height {
+                    return Err(ErrorCode::Invalid);
+                }
+                self.write_frame.set((x, y, width, height));
+            }
+            WRITE | FILL => {
+                let bits = bits_per_pixel(self.pixel_format.get());
+                let buffer = self.buffer.take();
+                let len = argument0 as usize;
+                let result = if len > buffer.len() {
+                    Err(ErrorCode::Size)
+                } else if command_id == WRITE {
+                    let count = len * 8 / bits;
+                    self.draw((0..count).map(|index| decode(&buffer, index, bits)));
+                    Ok(())
+                } else if len * 8 < bits {
+                    Err(ErrorCode::Size)
+                } else {
+                    let color = decode(&buffer, 0, bits);
+                    self.draw(core::iter::repeat(color));
+                    Ok(())
+                };
+                self.buffer.set(buffer);
+                return result;
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+impl crate::fake::SyscallDriver for Screen {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_BUFFER => Ok(self.buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, argument1: u32) -> CommandReturn {
+        let (width, height) = self.resolution();
+        match command_id {
+            EXISTS => command_return::success(),
+            SCREEN_SETUP => command_return::success_u32(self.has_setup.get() as u32),
+            RESOLUTION_MODES => {
+                command_return::success_u32(self.resolution_modes.borrow().len() as u32)
+            }
+            RESOLUTION_MODE => match self.resolution_modes.borrow().get(argument0 as usize) {
+                Some(&(width, height)) => command_return::success_2_u32(width, height),
+                None => command_return::failure(ErrorCode::Invalid),
+            },
+            PIXEL_FORMAT_MODES => {
+                command_return::success_u32(self.pixel_formats.borrow().len() as u32)
+            }
+            PIXEL_FORMAT_MODE => match self.pixel_formats.borrow().get(argument0 as usize) {
+                Some(&format) => command_return::success_u32(format),
+                None => command_return::failure(ErrorCode::Invalid),
+            },
+            GET_ROTATION => command_return::success_u32(self.rotation.get()),
+            GET_RESOLUTION => command_return::success_2_u32(width, height),
+            GET_PIXEL_FORMAT => command_return::success_u32(self.pixel_format.get()),
+            SET_POWER | SET_BRIGHTNESS | SET_INVERT | SET_ROTATION | SET_RESOLUTION
+            | SET_PIXEL_FORMAT | SET_WRITE_FRAME | WRITE | FILL => {
+                let status = match self.operation(command_id, argument0, argument1) {
+                    Ok(()) => 0,
+                    Err(error) => error as u32,
+                };
+                self.share_ref
+                    .schedule_upcall(SUBSCRIBE_DONE, (status, 0, 0))
+                    .expect("Unable to schedule upcall");
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+// Returns the number of bits a pixel takes in the kernel's pixel format
+// `format`.
+fn bits_per_pixel(format: u32) -> usize {
+    match format {
+        PIXEL_FORMAT_MONO => 1,
+        PIXEL_FORMAT_RGB332 => 8,
+        PIXEL_FORMAT_RGB565 => 16,
+        PIXEL_FORMAT_RGB888 => 24,
+        _ => 32,
+    }
+}
+
+// Returns the value of pixel number `index` in `buffer`, whose pixels take
+// `bits` bits each.
+fn decode(buffer: &[u8], index: usize, bits: usize) -> u32 {
+    if bits == 1 {
+        return (buffer[index / 8] >> (7 - index % 8)) as u32 & 1;
+    }
+    let bytes = bits / 8;
+    buffer[index * bytes..(index + 1) * bytes]
+        .iter()
+        .fold(0, |value, &byte| value << 8 | byte as u32)
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90001;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const SCREEN_SETUP: u32 = 1;
+const SET_POWER: u32 = 2;
+const SET_BRIGHTNESS: u32 = 3;
+const SET_INVERT: u32 = 6;
+const RESOLUTION_MODES: u32 = 11;
+const RESOLUTION_MODE: u32 = 12;
+const PIXEL_FORMAT_MODES: u32 = 13;
+const PIXEL_FORMAT_MODE: u32 = 14;
+const GET_ROTATION: u32 = 21;
+const SET_ROTATION: u32 = 22;
+const GET_RESOLUTION: u32 = 23;
+const SET_RESOLUTION: u32 = 24;
+const GET_PIXEL_FORMAT: u32 = 25;
+const SET_PIXEL_FORMAT: u32 = 26;
+const SET_WRITE_FRAME: u32 = 100;
+const WRITE: u32 = 200;
+const FILL: u32 = 300;
+
+const SUBSCRIBE_DONE: u32 = 0;
+const ALLOW_BUFFER: u32 = 0;
+
+// Pixel formats
+const PIXEL_FORMAT_MONO: u32 = 0;
+const PIXEL_FORMAT_RGB332: u32 = 1;
+const PIXEL_FORMAT_RGB565: u32 = 2;
+const PIXEL_FORMAT_RGB888: u32 = 3;
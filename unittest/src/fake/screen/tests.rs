@@ -0,0 +1,133 @@
+use crate::fake::{self, screen::*};
+use libtock_platform::{share, AllowRo, DefaultConfig, Subscribe, Syscalls};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let screen = Screen::new(4, 2);
+    assert!(screen.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        screen.command(SCREEN_SETUP, 0, 0).get_success_u32(),
+        Some(0)
+    );
+    assert_eq!(
+        screen.command(GET_RESOLUTION, 0, 0).get_success_2_u32(),
+        Some((4, 2))
+    );
+    assert_eq!(
+        screen.command(GET_PIXEL_FORMAT, 0, 0).get_success_u32(),
+        Some(PIXEL_FORMAT_RGB565)
+    );
+    assert_eq!(
+        screen.command(7, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+
+    // Setup is only supported once modes are set.
+    assert_eq!(
+        screen.operation(SET_PIXEL_FORMAT, PIXEL_FORMAT_MONO, 0),
+        Err(ErrorCode::NoSupport)
+    );
+    screen.set_modes(&[(4, 2), (8, 8)], &[PIXEL_FORMAT_RGB565, PIXEL_FORMAT_MONO]);
+    assert_eq!(
+        screen.command(RESOLUTION_MODE, 1, 0).get_success_2_u32(),
+        Some((8, 8))
+    );
+    assert_eq!(
+        screen.command(PIXEL_FORMAT_MODE, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert_eq!(
+        screen.operation(SET_RESOLUTION, 8, 4),
+        Err(ErrorCode::Invalid)
+    );
+    assert_eq!(screen.operation(SET_RESOLUTION, 8, 8), Ok(()));
+    assert_eq!(screen.write_frame(), (0, 0, 8, 8));
+
+    // Rotation swaps the width and height.
+    assert_eq!(screen.operation(SET_RESOLUTION, 4, 2), Ok(()));
+    assert_eq!(screen.operation(SET_ROTATION, 1, 0), Ok(()));
+    assert_eq!(screen.resolution(), (2, 4));
+    assert_eq!(screen.write_frame(), (0, 0, 2, 4));
+    assert_eq!(
+        screen.operation(SET_ROTATION, 4, 0),
+        Err(ErrorCode::Invalid)
+    );
+
+    assert_eq!(
+        screen.operation(SET_WRITE_FRAME, 1 << 16 | 3, 1 << 16 | 1),
+        Ok(())
+    );
+    assert_eq!(screen.write_frame(), (1, 3, 1, 1));
+    assert_eq!(
+        screen.operation(SET_WRITE_FRAME, 1 << 16 | 3, 2 << 16 | 1),
+        Err(ErrorCode::Invalid)
+    );
+
+    screen.fail_next(ErrorCode::Busy);
+    assert_eq!(screen.operation(SET_POWER, 1, 0), Err(ErrorCode::Busy));
+    assert!(!screen.is_on());
+    assert_eq!(screen.operation(SET_POWER, 1, 0), Ok(()));
+    assert!(screen.is_on());
+}
+
+#[test]
+fn decode_pixels() {
+    assert_eq!(decode(&[0b1010_0000], 0, 1), 1);
+    assert_eq!(decode(&[0b1010_0000], 1, 1), 0);
+    assert_eq!(decode(&[0b1010_0000], 2, 1), 1);
+    assert_eq!(decode(&[0x12, 0x34, 0x56, 0x78], 1, 16), 0x5678);
+    assert_eq!(decode(&[0x12, 0x34, 0x56], 0, 24), 0x123456);
+}
+
+// Integration test that verifies Screen works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let screen = Screen::new(3, 2);
+    kernel.add_driver(&screen);
+
+    let upcall: core::cell::Cell<Option<(u32,)>> = Default::default();
+    let run = |command_id, argument0, argument1, buffer: &[u8]| {
+        share::scope::<
+            (
+                AllowRo<_, DRIVER_NUM, ALLOW_BUFFER>,
+                Subscribe<_, DRIVER_NUM, SUBSCRIBE_DONE>,
+            ),
+            _,
+            _,
+        >(|handle| {
+            let (allow_ro, subscribe) = handle.split();
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_BUFFER>(allow_ro, buffer)
+                .unwrap();
+            fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_DONE>(
+                subscribe, &upcall,
+            )
+            .unwrap();
+            assert!(
+                fake::Syscalls::command(DRIVER_NUM, command_id, argument0, argument1).is_success()
+            );
+            fake::Syscalls::yield_wait();
+        });
+        upcall.get()
+    };
+
+    // Writes wrap at the end of each row of the write frame.
+    assert_eq!(run(SET_WRITE_FRAME, 1, 2 << 16 | 1, &[]), Some((0,)));
+    let pixels = [0x00, 0x01, 0x00, 0x02, 0x00, 0x03];
+    assert_eq!(run(WRITE, 4, 0, &pixels), Some((0,)));
+    assert_eq!(screen.pixel(0, 1), 1);
+    assert_eq!(screen.pixel(1, 1), 2);
+    assert_eq!(screen.pixel(2, 1), 0);
+    assert_eq!(run(WRITE, 8, 0, &pixels), Some((ErrorCode::Size as u32,)));
+
+    // Fills cover the write frame.
+    assert_eq!(run(SET_WRITE_FRAME, 1 << 16, 2 << 16 | 2, &[]), Some((0,)));
+    assert_eq!(run(FILL, 2, 0, &[0xF8, 0x00]), Some((0,)));
+    assert_eq!(screen.pixel(0, 0), 0);
+    assert_eq!(screen.pixel(1, 0), 0xF800);
+    assert_eq!(screen.pixel(2, 1), 0xF800);
+    assert_eq!(screen.write_count(), 2);
+}
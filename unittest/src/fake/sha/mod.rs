@@ -0,0 +1,264 @@
+//! Fake implementation of the SHA driver, following the system call interface
+//! of Tock's SHA capsule. Digests are computed in software, as soon as the
+//! process requests them.
+//!
+//! The system call interface (shared with `fake::Hmac`) is:
+//!
+//! * Read-Only Allow 1: data to hash. Read-Only Allow 2: the digest to compare
+//!   against when verifying.
+//! * Read-Write Allow 2: the buffer the digest is written to.
+//! * Command 1: set the algorithm to `argument0` (0: SHA-256, 1: SHA-384,
+//!   2: SHA-512). Clears any data added so far.
+//! * Command 2 (run): adds the data and writes the digest of all data added
+//!   since the last digest to the destination buffer.
+//! * Command 3 (update): adds the data.
+//! * Command 4 (finish): writes the digest of all data added so far.
+//! * Command 5 (verify): adds the data, then compares the digest of all data
+//!   added so far against the compare buffer.
+//! * Command 6 (verify finish): compares the digest of the data added so far.
+//! * Subscribe 0: run when a command completes, with arguments
+//!   `(0, value, 0)`. The value is the data length for update, the digest
+//!   length for run and finish, and 1 if the digests matched (else 0) for
+//!   verify.
+//!
+//! Commands 2 to 6 fail with `Reserve` if no algorithm is set, and the run and
+//! finish commands fail with `Size` if the destination buffer is too short for
+//! the digest.
+
+use crate::{command_return, DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+use core::cell::{Cell, RefCell};
+use hmac::{Hmac, Mac};
+use libtock_platform::{CommandReturn, ErrorCode};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// A hash algorithm supported by `fake::Sha` and `fake::Hmac`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShaAlgorithm {
+    Sha256 = 0,
+    Sha384 = 1,
+    Sha512 = 2,
+}
+
+impl ShaAlgorithm {
+    /// The length of this algorithm's digests, in bytes.
+    pub fn digest_len(self) -> usize {
+        match self {
+            ShaAlgorithm::Sha256 => 32,
+            ShaAlgorithm::Sha384 => 48,
+            ShaAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+pub struct Sha {
+    engine: DigestEngine,
+}
+
+impl Sha {
+    pub fn new() -> std::rc::Rc<Sha> {
+        std::rc::Rc::new(Sha {
+            engine: DigestEngine::new(false),
+        })
+    }
+
+    /// Returns the algorithm selected by the process, if any.
+    pub fn algorithm(&self) -> Option<ShaAlgorithm> {
+        self.engine.algorithm()
+    }
+}
+
+impl crate::fake::SyscallDriver for Sha {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(1)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.engine.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        self.engine.allow_readonly(buffer_num, buffer)
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        self.engine.allow_readwrite(buffer_num, buffer)
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        self.engine.command(command_id, argument0)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+// The state and system call handling shared by fake::Sha and fake::Hmac. If
+// `keyed` is true, digests are HMACs keyed with the contents of Read-Only Allow
+// buffer 0.
+pub(super) struct DigestEngine {
+    keyed: bool,
+    algorithm: Cell<Option<ShaAlgorithm>>,
+    // The data added since the last digest was computed.
+    added: RefCell<Vec<u8>>,
+    key: Cell<RoAllowBuffer>,
+    data: Cell<RoAllowBuffer>,
+    compare: Cell<RoAllowBuffer>,
+    dest: RefCell<RwAllowBuffer>,
+    pub share_ref: DriverShareRef,
+}
+
+impl DigestEngine {
+    pub fn new(keyed: bool) -> DigestEngine {
+        DigestEngine {
+            keyed,
+            algorithm: Default::default(),
+            added: Default::default(),
+            key: Default::default(),
+            data: Default::default(),
+            compare: Default::default(),
+            dest: Default::default(),
+            share_ref: Default::default(),
+        }
+    }
+
+    pub fn algorithm(&self) -> Option<ShaAlgorithm> {
+        self.algorithm.get()
+    }
+
+    pub fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_KEY if self.keyed => Ok(self.key.replace(buffer)),
+            ALLOW_DATA => Ok(self.data.replace(buffer)),
+            ALLOW_COMPARE => Ok(self.compare.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    pub fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_DEST => Ok(self.dest.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    pub fn command(&self, command_id: u32, argument0: u32) -> CommandReturn {
+        if command_id == EXISTS {
+            return command_return::success();
+        }
+        if command_id == SET_ALGORITHM {
+            let algorithm = match argument0 {
+                0 => ShaAlgorithm::Sha256,
+                1 => ShaAlgorithm::Sha384,
+                2 => ShaAlgorithm::Sha512,
+                _ => return command_return::failure(ErrorCode::Invalid),
+            };
+            self.algorithm.set(Some(algorithm));
+            self.added.borrow_mut().clear();
+            return command_return::success();
+        }
+        let algorithm = match (command_id, self.algorithm.get()) {
+            (RUN..=VERIFY_FINISH, None) => return command_return::failure(ErrorCode::Reserve),
+            (RUN..=VERIFY_FINISH, Some(algorithm)) => algorithm,
+            _ => return command_return::failure(ErrorCode::NoSupport),
+        };
+        let mut data_len = 0;
+        if matches!(command_id, RUN | UPDATE | VERIFY) {
+            let data = self.data.take();
+            data_len = data.len() as u32;
+            self.added.borrow_mut().extend_from_slice(&data);
+            self.data.set(data);
+        }
+        let value = match command_id {
+            UPDATE => data_len,
+            RUN | FINISH => {
+                let mut dest = self.dest.borrow_mut();
+                if dest.len() < algorithm.digest_len() {
+                    return command_return::failure(ErrorCode::Size);
+                }
+                dest[..algorithm.digest_len()].copy_from_slice(&self.digest(algorithm));
+                algorithm.digest_len() as u32
+            }
+            _ => {
+                let compare = self.compare.take();
+                let matched = *compare == *self.digest(algorithm);
+                self.compare.set(compare);
+                matched as u32
+            }
+        };
+        self.share_ref
+            .schedule_upcall(SUBSCRIBE_DONE, (0, value, 0))
+            .expect("Unable to schedule upcall");
+        command_return::success()
+    }
+
+    // Computes the digest of the data added so far, and clears it.
+    fn digest(&self, algorithm: ShaAlgorithm) -> Vec<u8> {
+        let data = self.added.take();
+        if !self.keyed {
+            return match algorithm {
+                ShaAlgorithm::Sha256 => Sha256::digest(&data).to_vec(),
+                ShaAlgorithm::Sha384 => Sha384::digest(&data).to_vec(),
+                ShaAlgorithm::Sha512 => Sha512::digest(&data).to_vec(),
+            };
+        }
+        let key = self.key.take();
+        let digest = match algorithm {
+            ShaAlgorithm::Sha256 => hmac::<Hmac<Sha256>>(&key, &data),
+            ShaAlgorithm::Sha384 => hmac::<Hmac<Sha384>>(&key, &data),
+            ShaAlgorithm::Sha512 => hmac::<Hmac<Sha512>>(&key, &data),
+        };
+        self.key.set(key);
+        digest
+    }
+}
+
+fn hmac<M: Mac + hmac::digest::KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length.
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).unwrap();
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x40005;
+
+// Command IDs, shared with fake::Hmac.
+pub(super) const EXISTS: u32 = 0;
+pub(super) const SET_ALGORITHM: u32 = 1;
+pub(super) const RUN: u32 = 2;
+pub(super) const UPDATE: u32 = 3;
+pub(super) const FINISH: u32 = 4;
+pub(super) const VERIFY: u32 = 5;
+pub(super) const VERIFY_FINISH: u32 = 6;
+
+// Allow IDs, shared with fake::Hmac. Read-only Allow 0 (the key) is only
+// supported by fake::Hmac.
+pub(super) const ALLOW_KEY: u32 = 0;
+pub(super) const ALLOW_DATA: u32 = 1;
+pub(super) const ALLOW_COMPARE: u32 = 2;
+pub(super) const ALLOW_DEST: u32 = 2;
+
+pub(super) const SUBSCRIBE_DONE: u32 = 0;
@@ -0,0 +1,102 @@
+use crate::fake::{self, sha::*};
+use libtock_platform::{
+    share, AllowRo, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn,
+};
+
+// SHA-256("abc"), from FIPS 180-2.
+const ABC_SHA256: [u8; 32] = [
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let sha = Sha::new();
+    assert!(sha.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        sha.command(RUN, 0, 0).get_failure(),
+        Some(ErrorCode::Reserve)
+    );
+    assert_eq!(
+        sha.command(SET_ALGORITHM, 3, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(sha.command(SET_ALGORITHM, 1, 0).is_success());
+    assert_eq!(sha.algorithm(), Some(ShaAlgorithm::Sha384));
+    // No destination buffer.
+    assert_eq!(sha.command(RUN, 0, 0).get_failure(), Some(ErrorCode::Size));
+    assert!(sha.command(UPDATE, 0, 0).is_success());
+    assert!(sha.command(VERIFY_FINISH, 0, 0).is_success());
+    assert_eq!(
+        sha.command(7, 0, 0).get_failure(),
+        Some(ErrorCode::NoSupport)
+    );
+
+    assert!(sha
+        .allow_readonly(ALLOW_KEY, RoAllowBuffer::default())
+        .is_err());
+    assert!(sha
+        .allow_readonly(ALLOW_DATA, RoAllowBuffer::default())
+        .is_ok());
+    assert!(sha
+        .allow_readwrite(ALLOW_DEST, RwAllowBuffer::default())
+        .is_ok());
+}
+
+// Integration test that verifies Sha works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let sha = Sha::new();
+    kernel.add_driver(&sha);
+    assert!(fake::Syscalls::command(DRIVER_NUM, SET_ALGORITHM, 0, 0).is_success());
+
+    let mut digest = [0; 32];
+    let upcall: core::cell::Cell<Option<(u32, u32)>> = Default::default();
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, ALLOW_DATA>,
+            AllowRo<_, DRIVER_NUM, ALLOW_COMPARE>,
+            AllowRw<_, DRIVER_NUM, ALLOW_DEST>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_DONE>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_data, allow_compare, allow_dest, subscribe) = handle.split();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_DONE>(
+            subscribe, &upcall,
+        )
+        .unwrap();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_DEST>(allow_dest, &mut digest)
+            .unwrap();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_COMPARE>(
+            allow_compare,
+            &ABC_SHA256,
+        )
+        .unwrap();
+
+        // Hash "abc" in two parts.
+        share::scope(|allow_a| {
+            fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_DATA>(allow_a, b"a")
+                .unwrap();
+            assert!(fake::Syscalls::command(DRIVER_NUM, UPDATE, 0, 0).is_success());
+            assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+            assert_eq!(upcall.get(), Some((0, 1)));
+        });
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_DATA>(allow_data, b"bc")
+            .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, RUN, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((0, 32)));
+
+        // Verify: the digest of "bc" alone does not match.
+        assert!(fake::Syscalls::command(DRIVER_NUM, VERIFY, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(upcall.get(), Some((0, 0)));
+    });
+    assert_eq!(digest, ABC_SHA256);
+}
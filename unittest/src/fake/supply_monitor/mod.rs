@@ -0,0 +1,147 @@
+//! Fake implementation of the supply monitor driver, which measures the chip's
+//! supply voltage (channel 0) and die temperature (channel 1).
+//!
+//! `SupplyMonitor` reports raw samples set by the test. A measurement started
+//! while the channel has a queued sample (see `set_raw_sync`) completes
+//! immediately; otherwise it stays pending until `set_raw` is called. Each
+//! channel's calibration defaults to that of an nRF52: VCC via the 12-bit SAADC
+//! (`3600, 12, 0`), and the TEMP peripheral's quarter degrees (`25, 0, 0`).
+//!
+//! The power-fail warning accepts thresholds from 1700 to 2800 mV in 100 mV
+//! steps, like the nRF52's POFWARN. `trigger_power_fail` simulates the supply
+//! dropping below the threshold.
+
+use crate::{command_return, DriverInfo, DriverShareRef};
+use libtock_platform::{CommandReturn, ErrorCode};
+use std::cell::Cell;
+
+pub struct SupplyMonitor {
+    // The channel being measured, if any.
+    pending: Cell<Option<u32>>,
+    // Per channel: the sample to report when a measurement starts.
+    sync_raw: [Cell<Option<u32>>; CHANNELS],
+    // Per channel: (gain, shift, offset).
+    calibration: [Cell<(u32, u32, i32)>; CHANNELS],
+    power_fail_threshold: Cell<Option<u32>>,
+    share_ref: DriverShareRef,
+}
+
+impl SupplyMonitor {
+    pub fn new() -> std::rc::Rc<SupplyMonitor> {
+        std::rc::Rc::new(SupplyMonitor {
+            pending: Cell::new(None),
+            sync_raw: Default::default(),
+            calibration: [Cell::new((3600, 12, 0)), Cell::new((25, 0, 0))],
+            power_fail_threshold: Cell::new(None),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Returns the channel being measured, if any.
+    pub fn pending_channel(&self) -> Option<u32> {
+        self.pending.get()
+    }
+
+    /// Completes the pending measurement of `channel` with the raw sample
+    /// `raw`. Does nothing if `channel` is not being measured.
+    pub fn set_raw(&self, channel: u32, raw: u32) {
+        if self.pending.get() == Some(channel) {
+            self.pending.set(None);
+            self.share_ref
+                .schedule_upcall(SUBSCRIBE_SAMPLE, (channel, raw, 0))
+                .expect("Unable to schedule upcall");
+        }
+    }
+
+    /// Makes the next measurement of `channel` complete immediately with the
+    /// raw sample `raw`.
+    pub fn set_raw_sync(&self, channel: u32, raw: u32) {
+        self.sync_raw[channel as usize].set(Some(raw));
+    }
+
+    /// Sets the calibration reported for `channel`.
+    pub fn set_calibration(&self, channel: u32, gain: u32, shift: u32, offset: i32) {
+        self.calibration[channel as usize].set((gain, shift, offset));
+    }
+
+    /// Returns the power-fail warning threshold, if the warning is enabled.
+    pub fn power_fail_threshold(&self) -> Option<u32> {
+        self.power_fail_threshold.get()
+    }
+
+    /// Simulates VCC dropping below the power-fail warning threshold. Schedules
+    /// the power-fail upcall if the warning is enabled.
+    pub fn trigger_power_fail(&self) {
+        if let Some(threshold) = self.power_fail_threshold.get() {
+            self.share_ref
+                .schedule_upcall(SUBSCRIBE_POWER_FAIL, (threshold, 0, 0))
+                .expect("Unable to schedule upcall");
+        }
+    }
+}
+
+impl crate::fake::SyscallDriver for SupplyMonitor {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM).upcall_count(2)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn command(&self, command_id: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_id {
+            EXISTS => command_return::success(),
+            SAMPLE | CALIBRATION if argument0 as usize >= CHANNELS => {
+                command_return::failure(ErrorCode::Invalid)
+            }
+            SAMPLE => {
+                if self.pending.get().is_some() {
+                    return command_return::failure(ErrorCode::Busy);
+                }
+                self.pending.set(Some(argument0));
+                if let Some(raw) = self.sync_raw[argument0 as usize].take() {
+                    self.set_raw(argument0, raw);
+                }
+                command_return::success()
+            }
+            CALIBRATION => {
+                let (gain, shift, offset) = self.calibration[argument0 as usize].get();
+                command_return::success_3_u32(gain, shift, offset as u32)
+            }
+            ENABLE_POWER_FAIL => {
+                if !(1700..=2800).contains(&argument0) || argument0 % 100 != 0 {
+                    return command_return::failure(ErrorCode::Invalid);
+                }
+                self.power_fail_threshold.set(Some(argument0));
+                command_return::success()
+            }
+            DISABLE_POWER_FAIL => {
+                self.power_fail_threshold.set(None);
+                command_return::success()
+            }
+            _ => command_return::failure(ErrorCode::NoSupport),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Driver number and command IDs
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x9006B;
+
+const CHANNELS: usize = 2;
+
+// Command IDs
+const EXISTS: u32 = 0;
+const SAMPLE: u32 = 1;
+const CALIBRATION: u32 = 2;
+const ENABLE_POWER_FAIL: u32 = 3;
+const DISABLE_POWER_FAIL: u32 = 4;
+
+const SUBSCRIBE_SAMPLE: u32 = 0;
+const SUBSCRIBE_POWER_FAIL: u32 = 1;
@@ -0,0 +1,101 @@
+use crate::fake::{self, supply_monitor::*};
+use libtock_platform::{share, DefaultConfig, Syscalls, YieldNoWaitReturn};
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let monitor = SupplyMonitor::new();
+    assert!(monitor.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        monitor.command(CALIBRATION, 0, 0).get_success_3_u32(),
+        Some((3600, 12, 0))
+    );
+    monitor.set_calibration(1, 1, 2, -3);
+    assert_eq!(
+        monitor.command(CALIBRATION, 1, 0).get_success_3_u32(),
+        Some((1, 2, -3i32 as u32))
+    );
+    assert_eq!(
+        monitor.command(SAMPLE, 2, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+
+    assert!(monitor.command(SAMPLE, 1, 0).is_success());
+    assert_eq!(monitor.pending_channel(), Some(1));
+    assert_eq!(
+        monitor.command(SAMPLE, 0, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    // Samples for other channels are ignored.
+    monitor.set_raw(0, 10);
+    assert_eq!(monitor.pending_channel(), Some(1));
+    monitor.set_raw(1, 10);
+    assert_eq!(monitor.pending_channel(), None);
+
+    monitor.set_raw_sync(0, 20);
+    assert!(monitor.command(SAMPLE, 0, 0).is_success());
+    assert_eq!(monitor.pending_channel(), None);
+
+    for threshold in [1600, 2750, 2900] {
+        assert_eq!(
+            monitor
+                .command(ENABLE_POWER_FAIL, threshold, 0)
+                .get_failure(),
+            Some(ErrorCode::Invalid)
+        );
+    }
+    assert!(monitor.command(ENABLE_POWER_FAIL, 2100, 0).is_success());
+    assert_eq!(monitor.power_fail_threshold(), Some(2100));
+    assert!(monitor.command(DISABLE_POWER_FAIL, 0, 0).is_success());
+    assert_eq!(monitor.power_fail_threshold(), None);
+}
+
+// Integration test that verifies SupplyMonitor works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let monitor = SupplyMonitor::new();
+    kernel.add_driver(&monitor);
+
+    let listener = Cell::<Option<(u32, u32)>>::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_SAMPLE>(
+            subscribe, &listener,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, SAMPLE, 0, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        monitor.set_raw(0, 3413);
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((0, 3413)));
+
+        monitor.set_raw_sync(1, 100);
+        assert!(fake::Syscalls::command(DRIVER_NUM, SAMPLE, 1, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((1, 100)));
+    });
+}
+
+#[test]
+fn power_fail() {
+    let kernel = fake::Kernel::new();
+    let monitor = SupplyMonitor::new();
+    kernel.add_driver(&monitor);
+
+    let listener = Cell::<Option<(u32,)>>::new(None);
+    share::scope(|subscribe| {
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_POWER_FAIL>(
+            subscribe, &listener,
+        )
+        .unwrap();
+        monitor.trigger_power_fail();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+
+        assert!(fake::Syscalls::command(DRIVER_NUM, ENABLE_POWER_FAIL, 2800, 0).is_success());
+        monitor.trigger_power_fail();
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+        assert_eq!(listener.get(), Some((2800,)));
+    });
+}
@@ -1,4 +1,4 @@
-use crate::kernel_data::with_kernel_data;
+use crate::kernel_data::{with_kernel_data, AllowType};
 use crate::{ExpectedSyscall, SyscallLogEntry};
 use libtock_platform::{return_variant, ErrorCode, Register};
 use std::convert::TryInto;
@@ -78,6 +78,9 @@ pub(super) unsafe fn allow_ro(
     let (address_out, len_out) = with_kernel_data(|option_kernel_data| {
         let kernel_data = option_kernel_data
             .expect("fake::Kernel dropped during fake::SyscallDriver::allow_readonly");
+        if error_code.is_none() {
+            kernel_data.record_allow(AllowType::ReadOnly, driver_num, buffer_num, len.into());
+        }
         kernel_data.allow_db.remove_ro_buffer(buffer_out)
     });
 
@@ -1,4 +1,4 @@
-use crate::kernel_data::with_kernel_data;
+use crate::kernel_data::{with_kernel_data, AllowType};
 use crate::{ExpectedSyscall, SyscallLogEntry};
 use libtock_platform::{return_variant, ErrorCode, Register};
 use std::convert::TryInto;
@@ -79,6 +79,9 @@ pub(super) unsafe fn allow_rw(
     let (address_out, len_out) = with_kernel_data(|option_kernel_data| {
         let kernel_data = option_kernel_data
             .expect("fake::Kernel dropped during fake::SyscallDriver::allow_readwrite");
+        if error_code.is_none() {
+            kernel_data.record_allow(AllowType::ReadWrite, driver_num, buffer_num, len.into());
+        }
         kernel_data.allow_db.remove_rw_buffer(buffer_out)
     });
 
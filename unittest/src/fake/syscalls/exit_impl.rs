@@ -3,6 +3,24 @@ use core::convert::TryInto;
 pub(super) fn exit(r0: libtock_platform::Register, r1: libtock_platform::Register) -> ! {
     let exit_num: u32 = r0.try_into().expect("Too large exit number");
     let completion_code: u32 = r1.try_into().expect("Too large completion code");
+
+    #[cfg(not(miri))]
+    {
+        let exit_call = match exit_num {
+            libtock_platform::exit_id::TERMINATE => {
+                Some(crate::ExitCall::Terminate(completion_code))
+            }
+            libtock_platform::exit_id::RESTART => Some(crate::ExitCall::Restart(completion_code)),
+            _ => None,
+        };
+        let handler = crate::kernel_data::with_kernel_data(|kernel_data| {
+            kernel_data.and_then(|kernel_data| kernel_data.exit_handler.take())
+        });
+        if let (Some(exit_call), Some(handler)) = (exit_call, handler) {
+            handler(exit_call);
+        }
+    }
+
     match exit_num {
         libtock_platform::exit_id::TERMINATE => {
             println!("exit-terminate called with code {}", completion_code);
@@ -16,3 +16,18 @@ fn exit_terminate() {
     });
     assert_eq!(exit_call, ExitCall::Terminate(9265));
 }
+
+#[test]
+fn exit_handler() {
+    let exit_call = exit_test("fake::syscalls::exit_impl_tests::exit_handler", || {
+        let kernel = crate::fake::Kernel::new();
+        kernel.set_exit_handler(|exit_call| {
+            assert_eq!(exit_call, ExitCall::Terminate(5));
+            // Report a different exit than the process made, to show the
+            // handler ran before the default handling.
+            exit(libtock_platform::exit_id::RESTART.into(), 6u32.into())
+        });
+        exit(libtock_platform::exit_id::TERMINATE.into(), 5u32.into())
+    });
+    assert_eq!(exit_call, ExitCall::Restart(6));
+}
@@ -67,6 +67,31 @@ pub(super) fn memop(memop_num: Register, argument0: Register) -> [Register; 2] {
                 // just pick a random number to always return, for now
                 (return_variant::SUCCESS, 0x123400.into())
             }
+            3 => {
+                /* app_ram_end */
+                (return_variant::SUCCESS_U32, APP_RAM_END.into())
+            }
+            4 => {
+                /* flash_start */
+                (return_variant::SUCCESS_U32, FLASH_START.into())
+            }
+            5 => {
+                /* flash_end */
+                (return_variant::SUCCESS_U32, FLASH_END.into())
+            }
+            6 => {
+                /* grant_start */
+                (return_variant::SUCCESS_U32, GRANT_START.into())
+            }
+            7 => {
+                /* flash_region_count */
+                // The fake process has no writeable flash regions.
+                (return_variant::SUCCESS_U32, 0.into())
+            }
+            8 | 9 => {
+                /* flash_region_start, flash_region_end */
+                (return_variant::FAILURE, ErrorCode::Invalid.into())
+            }
             10 => {
                 /* debug_stack_start */
                 (return_variant::SUCCESS, 0.into())
@@ -90,3 +115,10 @@ pub(super) fn memop(memop_num: Register, argument0: Register) -> [Register; 2] {
     let r0: u32 = return_variant.into();
     [r0.into(), r1]
 }
+
+// The memory layout the fake kernel reports for the process, which is made up
+// like its RAM start.
+const APP_RAM_END: u32 = 0x133400;
+const GRANT_START: u32 = 0x132400;
+const FLASH_START: u32 = 0x40000;
+const FLASH_END: u32 = 0x48000;
@@ -1,4 +1,6 @@
-use libtock_platform::{syscall_class, yield_id, RawSyscalls, Register};
+use crate::trace::{record, syscall_result};
+use crate::TraceEvent;
+use libtock_platform::{syscall_class, yield_id, RawSyscalls, Register, YieldNoWaitReturn};
 use std::convert::TryInto;
 
 unsafe impl RawSyscalls for crate::fake::Syscalls {
@@ -6,7 +8,7 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
         crate::fake::syscalls::assert_valid(r0);
         match r0.try_into().expect("too-large Yield ID passed") {
             yield_id::NO_WAIT => panic!("yield-no-wait called without an argument"),
-            yield_id::WAIT => super::yield_impl::yield_wait(),
+            yield_id::WAIT => record(super::yield_impl::yield_wait, |()| TraceEvent::YieldWait),
             id => panic!("unknown yield ID {}", id),
         }
     }
@@ -14,7 +16,16 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
     unsafe fn yield2([r0, r1]: [Register; 2]) {
         crate::fake::syscalls::assert_valid((r0, r1));
         match r0.try_into().expect("too-large Yield ID passed") {
-            yield_id::NO_WAIT => unsafe { super::yield_impl::yield_no_wait(r1.into()) },
+            yield_id::NO_WAIT => {
+                let return_ptr: *mut YieldNoWaitReturn = r1.into();
+                record(
+                    || unsafe { super::yield_impl::yield_no_wait(return_ptr) },
+                    // Safety: yield_no_wait set the value return_ptr points to.
+                    |()| TraceEvent::YieldNoWait {
+                        upcall_ran: unsafe { return_ptr.read() } == YieldNoWaitReturn::Upcall,
+                    },
+                )
+            }
             yield_id::WAIT => {
                 // Technically it is acceptable to call yield_wait with an
                 // argument, but it shouldn't be done because it's wasteful so
@@ -27,7 +38,7 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
 
     unsafe fn syscall1<const CLASS: usize>([r0]: [Register; 1]) -> [Register; 2] {
         match CLASS {
-            syscall_class::MEMOP => super::memop_impl::memop(r0, 0u32.into()),
+            syscall_class::MEMOP => record_memop(r0, 0u32.into()),
             _ => panic!("Unknown syscall1 call. Class: {}", CLASS),
         }
     }
@@ -35,7 +46,7 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
     unsafe fn syscall2<const CLASS: usize>([r0, r1]: [Register; 2]) -> [Register; 2] {
         crate::fake::syscalls::assert_valid((r0, r1));
         match CLASS {
-            syscall_class::MEMOP => super::memop_impl::memop(r0, r1),
+            syscall_class::MEMOP => record_memop(r0, r1),
             syscall_class::EXIT => super::exit_impl::exit(r0, r1),
             _ => panic!("Unknown syscall2 call. Class: {}", CLASS),
         }
@@ -43,12 +54,59 @@ unsafe impl RawSyscalls for crate::fake::Syscalls {
 
     unsafe fn syscall4<const CLASS: usize>([r0, r1, r2, r3]: [Register; 4]) -> [Register; 4] {
         crate::fake::syscalls::assert_valid((r0, r1, r2, r3));
+        // Only computed if the call is traced, as the conversions panic on
+        // invalid arguments, which the implementations report more clearly.
+        let driver_num = || r0.try_into().expect("Too large driver number");
+        let num = || r1.try_into().expect("Too large number");
         match CLASS {
-            syscall_class::SUBSCRIBE => unsafe { super::subscribe_impl::subscribe(r0, r1, r2, r3) },
-            syscall_class::COMMAND => super::command_impl::command(r0, r1, r2, r3),
-            syscall_class::ALLOW_RW => unsafe { super::allow_rw_impl::allow_rw(r0, r1, r2, r3) },
-            syscall_class::ALLOW_RO => unsafe { super::allow_ro_impl::allow_ro(r0, r1, r2, r3) },
+            syscall_class::SUBSCRIBE => record(
+                || unsafe { super::subscribe_impl::subscribe(r0, r1, r2, r3) },
+                |&[r0, r1, ..]| TraceEvent::Subscribe {
+                    driver_num: driver_num(),
+                    subscribe_num: num(),
+                    null_upcall: usize::from(r2) == 0,
+                    result: syscall_result(r0, r1),
+                },
+            ),
+            syscall_class::COMMAND => record(
+                || super::command_impl::command(r0, r1, r2, r3),
+                |registers| TraceEvent::Command {
+                    driver_num: driver_num(),
+                    command_id: num(),
+                    argument0: r2.try_into().expect("Too large argument 0"),
+                    argument1: r3.try_into().expect("Too large argument 1"),
+                    return_registers: registers.map(Register::as_u32),
+                },
+            ),
+            syscall_class::ALLOW_RW => record(
+                || unsafe { super::allow_rw_impl::allow_rw(r0, r1, r2, r3) },
+                |&[r0, r1, ..]| TraceEvent::AllowRw {
+                    driver_num: driver_num(),
+                    buffer_num: num(),
+                    len: r3.into(),
+                    result: syscall_result(r0, r1),
+                },
+            ),
+            syscall_class::ALLOW_RO => record(
+                || unsafe { super::allow_ro_impl::allow_ro(r0, r1, r2, r3) },
+                |&[r0, r1, ..]| TraceEvent::AllowRo {
+                    driver_num: driver_num(),
+                    buffer_num: num(),
+                    len: r3.into(),
+                    result: syscall_result(r0, r1),
+                },
+            ),
             _ => panic!("Unknown syscall4 call. Class: {}", CLASS),
         }
     }
 }
+
+fn record_memop(memop_num: Register, argument0: Register) -> [Register; 2] {
+    record(
+        || super::memop_impl::memop(memop_num, argument0),
+        |&[r0, r1]| TraceEvent::Memop {
+            memop_num: memop_num.try_into().expect("Too large memop num"),
+            result: syscall_result(r0, r1),
+        },
+    )
+}
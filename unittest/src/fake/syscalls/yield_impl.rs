@@ -53,6 +53,10 @@ pub(super) fn yield_wait() {
         return;
     }
 
+    if !crate::fake::Kernel::is_upcall_pending() {
+        run_idle_handler();
+    }
+
     // In a real Tock system, a process that calls yield-wait with no queued
     // upcalls would be put to sleep until an upcall was queued (e.g. by an
     // interrupt). However, in this single-threaded test environment, there is
@@ -64,6 +68,19 @@ pub(super) fn yield_wait() {
     );
 }
 
+// Runs the kernel's idle handler, if it has one. The handler is removed from
+// the kernel data while it runs, as it will likely call into fake drivers that
+// access the kernel data.
+fn run_idle_handler() {
+    let handler = with_kernel_data(|kernel_data| kernel_data.unwrap().idle_handler.take());
+    if let Some(mut handler) = handler {
+        handler();
+        with_kernel_data(|kernel_data| {
+            kernel_data.unwrap().idle_handler.get_or_insert(handler);
+        });
+    }
+}
+
 // Pops the next upcall off the kernel data's upcall queue and invokes it, or
 // does nothing if the upcall queue was entry. The return value indicates
 // whether an upcall was run. Panics if no kernel data is present.
@@ -73,6 +90,14 @@ fn invoke_next_upcall() -> bool {
     match option_queue_entry {
         None => false,
         Some(queue_entry) => {
+            crate::trace::record(
+                || (),
+                |()| crate::TraceEvent::Upcall {
+                    driver_num: queue_entry.id.driver_num,
+                    subscribe_num: queue_entry.id.subscribe_num,
+                    args: queue_entry.args,
+                },
+            );
             unsafe {
                 queue_entry.upcall.invoke(queue_entry.args);
             }
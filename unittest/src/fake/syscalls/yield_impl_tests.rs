@@ -190,6 +190,55 @@ fn yield_wait_test() {
     assert_eq!(kernel.take_syscall_log(), [SyscallLogEntry::YieldWait]);
 }
 
+#[test]
+fn yield_wait_idle_handler() {
+    // Queues a copy_args upcall that writes `args` into `output`.
+    fn queue_upcall(args: (u32, u32, u32), output: *mut u32) {
+        with_kernel_data(|option_kernel_data| {
+            option_kernel_data
+                .unwrap()
+                .upcall_queue
+                .push_back(UpcallQueueEntry {
+                    args,
+                    id: UpcallId {
+                        driver_num: 1,
+                        subscribe_num: 2,
+                    },
+                    upcall: Upcall {
+                        fn_pointer: Some(copy_args),
+                        data: output.into(),
+                    },
+                });
+        });
+    }
+
+    let kernel = fake::Kernel::new();
+    let mut output_array = [0u32; 3];
+    let output_ptr = &mut output_array as *mut u32;
+    let idle_calls = std::rc::Rc::new(core::cell::Cell::new(0));
+
+    // The idle handler queues an upcall, as a fake driver responding to the
+    // outside world would.
+    let handler_calls = idle_calls.clone();
+    kernel.set_idle_handler(move || {
+        handler_calls.set(handler_calls.get() + 1);
+        queue_upcall((4, 5, 6), output_ptr);
+    });
+    yield_wait();
+    assert_eq!(output_array, [4, 5, 6]);
+    assert_eq!(idle_calls.get(), 1);
+
+    // The idle handler is not called while an upcall is queued.
+    queue_upcall((7, 8, 9), output_ptr);
+    yield_wait();
+    assert_eq!(output_array, [7, 8, 9]);
+    assert_eq!(idle_calls.get(), 1);
+    assert_eq!(
+        kernel.take_syscall_log(),
+        [SyscallLogEntry::YieldWait, SyscallLogEntry::YieldWait]
+    );
+}
+
 // TODO: Move the yield1 and yield2 tests into a raw_syscalls_impl test module,
 // once all system calls have been implemented.
 
@@ -0,0 +1,155 @@
+//! Fake implementation of a USB bulk endpoint driver, as expected by
+//! `libtock_usb_bulk`.
+//!
+//! The fake plays the host too. Transfers from the host are queued with
+//! `host_send`, and each completes the queued OUT buffer, immediately if one is
+//! queued or as soon as one is. Transfers to the host are read by the host as
+//! soon as they are submitted, and retrieved with `take_host_received`.
+
+use core::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use libtock_platform::{CommandReturn, ErrorCode};
+
+use crate::{DriverInfo, DriverShareRef, RoAllowBuffer, RwAllowBuffer};
+
+pub struct UsbBulk {
+    driver_num: u32,
+    in_buffer: Cell<RoAllowBuffer>,
+    out_buffer: RefCell<RwAllowBuffer>,
+    // The maximum length of the queued OUT transfer, if one is queued.
+    out_queued: Cell<Option<usize>>,
+    host_sent: RefCell<VecDeque<Vec<u8>>>,
+    host_received: RefCell<Vec<Vec<u8>>>,
+    share_ref: DriverShareRef,
+}
+
+impl UsbBulk {
+    pub fn new(driver_num: u32) -> std::rc::Rc<UsbBulk> {
+        std::rc::Rc::new(UsbBulk {
+            driver_num,
+            in_buffer: Default::default(),
+            out_buffer: Default::default(),
+            out_queued: Cell::new(None),
+            host_sent: Default::default(),
+            host_received: Default::default(),
+            share_ref: Default::default(),
+        })
+    }
+
+    /// Queues a transfer from the host. A transfer longer than the OUT buffer
+    /// it completes is truncated.
+    pub fn host_send(&self, transfer: &[u8]) {
+        self.host_sent.borrow_mut().push_back(transfer.to_vec());
+        self.deliver();
+    }
+
+    /// Returns the transfers the host has received so far, and clears them.
+    pub fn take_host_received(&self) -> Vec<Vec<u8>> {
+        self.host_received.take()
+    }
+
+    /// Returns `true` if an OUT buffer is queued.
+    pub fn out_queued(&self) -> bool {
+        self.out_queued.get().is_some()
+    }
+
+    // Completes the queued OUT buffer with the next transfer from the host, if
+    // both exist.
+    fn deliver(&self) {
+        let Some(max_len) = self.out_queued.get() else {
+            return;
+        };
+        let Some(transfer) = self.host_sent.borrow_mut().pop_front() else {
+            return;
+        };
+        let mut buffer = self.out_buffer.borrow_mut();
+        let len = transfer.len().min(max_len).min(buffer.len());
+        buffer[..len].copy_from_slice(&transfer[..len]);
+        self.out_queued.set(None);
+        self.share_ref
+            .schedule_upcall(SUBSCRIBE_OUT, (0, len as u32, 0))
+            .expect("Unable to schedule upcall");
+    }
+}
+
+impl crate::fake::SyscallDriver for UsbBulk {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(self.driver_num).upcall_count(2)
+    }
+
+    fn register(&self, share_ref: DriverShareRef) {
+        self.share_ref.replace(share_ref);
+    }
+
+    fn allow_readonly(
+        &self,
+        buffer_num: u32,
+        buffer: RoAllowBuffer,
+    ) -> Result<RoAllowBuffer, (RoAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_IN => Ok(self.in_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            ALLOW_OUT => Ok(self.out_buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::Invalid)),
+        }
+    }
+
+    fn command(&self, command_num: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_num {
+            EXISTS => {}
+            RECEIVE => {
+                if self.out_queued.get().is_some() {
+                    return crate::command_return::failure(ErrorCode::Busy);
+                }
+                self.out_queued.set(Some(argument0 as usize));
+                self.deliver();
+            }
+            TRANSMIT => {
+                let buffer = self.in_buffer.take();
+                let len = buffer.len().min(argument0 as usize);
+                self.host_received.borrow_mut().push(buffer[..len].to_vec());
+                self.in_buffer.set(buffer);
+                self.share_ref
+                    .schedule_upcall(SUBSCRIBE_IN, (0, len as u32, 0))
+                    .expect("Unable to schedule upcall");
+            }
+            ABORT => {
+                if self.out_queued.take().is_some() {
+                    self.share_ref
+                        .schedule_upcall(SUBSCRIBE_OUT, (ErrorCode::Cancel as u32, 0, 0))
+                        .expect("Unable to schedule upcall");
+                }
+            }
+            _ => return crate::command_return::failure(ErrorCode::NoSupport),
+        }
+        crate::command_return::success()
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Command IDs
+// -----------------------------------------------------------------------------
+
+const EXISTS: u32 = 0;
+const RECEIVE: u32 = 1;
+const TRANSMIT: u32 = 2;
+const ABORT: u32 = 3;
+
+const SUBSCRIBE_OUT: u32 = 0;
+const SUBSCRIBE_IN: u32 = 1;
+
+const ALLOW_IN: u32 = 0;
+const ALLOW_OUT: u32 = 0;
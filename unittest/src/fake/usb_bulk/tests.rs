@@ -0,0 +1,76 @@
+use crate::fake::{self, SyscallDriver};
+use fake::usb_bulk::*;
+use libtock_platform::{
+    share, AllowRo, AllowRw, DefaultConfig, Subscribe, Syscalls, YieldNoWaitReturn,
+};
+
+const DRIVER_NUM: u32 = 0x90100;
+
+#[test]
+fn command() {
+    let usb = UsbBulk::new(DRIVER_NUM);
+    assert!(usb.command(EXISTS, 0, 0).is_success());
+    assert!(usb.command(RECEIVE, 8, 0).is_success());
+    assert!(usb.out_queued());
+    assert_eq!(
+        usb.command(RECEIVE, 8, 0).get_failure(),
+        Some(ErrorCode::Busy)
+    );
+    assert!(usb.command(ABORT, 0, 0).is_success());
+    assert!(!usb.out_queued());
+}
+
+// Integration test that verifies UsbBulk works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    let usb = UsbBulk::new(DRIVER_NUM);
+    kernel.add_driver(&usb);
+
+    let mut buffer = [0; 4];
+    let done = core::cell::Cell::<Option<(u32, u32)>>::new(None);
+    share::scope::<
+        (
+            AllowRw<_, DRIVER_NUM, ALLOW_OUT>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_OUT>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_rw, subscribe) = handle.split();
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, ALLOW_OUT>(allow_rw, &mut buffer)
+            .unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_OUT>(
+            subscribe, &done,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, RECEIVE, 4, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::NoUpcall);
+        usb.host_send(b"hello");
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    assert_eq!(done.get(), Some((0, 4)));
+    assert_eq!(&buffer, b"hell");
+
+    share::scope::<
+        (
+            AllowRo<_, DRIVER_NUM, ALLOW_IN>,
+            Subscribe<_, DRIVER_NUM, SUBSCRIBE_IN>,
+        ),
+        _,
+        _,
+    >(|handle| {
+        let (allow_ro, subscribe) = handle.split();
+        fake::Syscalls::allow_ro::<DefaultConfig, DRIVER_NUM, ALLOW_IN>(allow_ro, b"world")
+            .unwrap();
+        fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, SUBSCRIBE_IN>(
+            subscribe, &done,
+        )
+        .unwrap();
+        assert!(fake::Syscalls::command(DRIVER_NUM, TRANSMIT, 3, 0).is_success());
+        assert_eq!(fake::Syscalls::yield_no_wait(), YieldNoWaitReturn::Upcall);
+    });
+    assert_eq!(done.get(), Some((0, 3)));
+    assert_eq!(usb.take_host_received(), [b"wor".to_vec()]);
+}
@@ -0,0 +1,76 @@
+//! Fake implementation of the Watchdog API.
+//!
+//! `Watchdog` does not keep time. Instead, it records whether it is running,
+//! its timeout, and how many times it has been fed, so tests can verify that
+//! an app feeds it as expected.
+
+use crate::DriverInfo;
+use core::cell::Cell;
+use libtock_platform::{CommandReturn, ErrorCode};
+
+pub struct Watchdog {
+    timeout_ms: Cell<Option<u32>>,
+    feed_count: Cell<u32>,
+}
+
+impl Watchdog {
+    pub fn new() -> std::rc::Rc<Watchdog> {
+        std::rc::Rc::new(Watchdog {
+            timeout_ms: Cell::new(None),
+            feed_count: Cell::new(0),
+        })
+    }
+
+    /// Returns the timeout of the running watchdog, or `None` if the watchdog
+    /// is stopped.
+    pub fn timeout_ms(&self) -> Option<u32> {
+        self.timeout_ms.get()
+    }
+
+    /// Returns the number of times the running watchdog has been fed.
+    pub fn feed_count(&self) -> u32 {
+        self.feed_count.get()
+    }
+}
+
+impl crate::fake::SyscallDriver for Watchdog {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM)
+    }
+
+    fn command(&self, command_num: u32, argument0: u32, _argument1: u32) -> CommandReturn {
+        match command_num {
+            EXISTS => {}
+            START => {
+                if argument0 == 0 {
+                    return crate::command_return::failure(ErrorCode::Invalid);
+                }
+                self.timeout_ms.set(Some(argument0));
+            }
+            FEED => {
+                if self.timeout_ms.get().is_none() {
+                    return crate::command_return::failure(ErrorCode::Off);
+                }
+                self.feed_count.set(self.feed_count.get() + 1);
+            }
+            STOP => self.timeout_ms.set(None),
+            _ => return crate::command_return::failure(ErrorCode::NoSupport),
+        }
+        crate::command_return::success()
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+// -----------------------------------------------------------------------------
+// Implementation details below
+// -----------------------------------------------------------------------------
+
+const DRIVER_NUM: u32 = 0x90069;
+
+// Command numbers
+const EXISTS: u32 = 0;
+const START: u32 = 1;
+const FEED: u32 = 2;
+const STOP: u32 = 3;
@@ -0,0 +1,40 @@
+use crate::fake;
+use fake::watchdog::*;
+use libtock_platform::ErrorCode;
+
+// Tests the command implementation.
+#[test]
+fn command() {
+    use fake::SyscallDriver;
+    let watchdog = Watchdog::new();
+    assert!(watchdog.command(EXISTS, 0, 0).is_success());
+    assert_eq!(
+        watchdog.command(FEED, 0, 0).get_failure(),
+        Some(ErrorCode::Off)
+    );
+    assert_eq!(
+        watchdog.command(START, 0, 0).get_failure(),
+        Some(ErrorCode::Invalid)
+    );
+    assert!(watchdog.command(START, 1000, 0).is_success());
+    assert_eq!(watchdog.timeout_ms(), Some(1000));
+    assert!(watchdog.command(FEED, 0, 0).is_success());
+    assert_eq!(watchdog.feed_count(), 1);
+    assert!(watchdog.command(STOP, 0, 0).is_success());
+    assert_eq!(watchdog.timeout_ms(), None);
+}
+
+// Integration test that verifies Watchdog works with fake::Kernel and
+// libtock_platform::Syscalls.
+#[test]
+fn kernel_integration() {
+    use libtock_platform::Syscalls;
+    let kernel = fake::Kernel::new();
+    let watchdog = Watchdog::new();
+    kernel.add_driver(&watchdog);
+    assert!(fake::Syscalls::command(DRIVER_NUM, EXISTS, 0, 0).is_success());
+    assert!(fake::Syscalls::command(DRIVER_NUM, START, 500, 0).is_success());
+    assert!(fake::Syscalls::command(DRIVER_NUM, FEED, 0, 0).is_success());
+    assert_eq!(watchdog.timeout_ms(), Some(500));
+    assert_eq!(watchdog.feed_count(), 1);
+}
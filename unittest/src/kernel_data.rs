@@ -14,6 +14,14 @@ use std::cell::RefCell;
 pub(crate) struct KernelData {
     pub allow_db: crate::allow_db::AllowDb,
 
+    // The non-empty buffers currently Allowed to each driver, keyed by
+    // (allow type, driver number, buffer number). The value is the buffer's
+    // length. Used to report leaked buffers; see `fake::Kernel::assert_no_leaks`.
+    pub allowed_buffers: std::collections::BTreeMap<(AllowType, u32, u32), usize>,
+    // If true, dropping the `fake::Kernel` panics if any buffers are Allowed or
+    // upcalls are subscribed.
+    pub check_leaks_on_drop: bool,
+
     // The location of the call to `fake::Kernel::new`. Used in the event a
     // duplicate `fake::Kernel` is created to tell the user which kernel they
     // did not clean up in a unit test.
@@ -21,7 +29,15 @@ pub(crate) struct KernelData {
 
     pub drivers: std::collections::HashMap<u32, DriverData>,
     pub expected_syscalls: std::collections::VecDeque<crate::ExpectedSyscall>,
+    // Called when the process calls yield-wait with no upcall queued. See
+    // `fake::Kernel::set_idle_handler`.
+    pub idle_handler: Option<Box<dyn FnMut()>>,
+    // Called when the process calls Exit. See `fake::Kernel::set_exit_handler`.
+    #[cfg(not(miri))]
+    pub exit_handler: Option<Box<dyn FnOnce(crate::ExitCall)>>,
     pub syscall_log: Vec<crate::SyscallLogEntry>,
+    // The syscall trace being recorded, if any. See `fake::Kernel::start_trace`.
+    pub trace: Option<crate::trace::Recorder>,
     pub upcall_queue: crate::upcall::UpcallQueue,
     pub memory_break: *const u8,
 }
@@ -35,6 +51,30 @@ pub(crate) fn with_kernel_data<F: FnOnce(Option<&mut KernelData>) -> R, R>(f: F)
     KERNEL_DATA.with(|refcell| f(refcell.borrow_mut().as_mut()))
 }
 
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum AllowType {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl KernelData {
+    // Records that the Allow call for (allow_type, driver_num, buffer_num)
+    // succeeded, replacing the previous buffer with one of length `len`.
+    pub fn record_allow(
+        &mut self,
+        allow_type: AllowType,
+        driver_num: u32,
+        buffer_num: u32,
+        len: usize,
+    ) {
+        let key = (allow_type, driver_num, buffer_num);
+        match len {
+            0 => self.allowed_buffers.remove(&key),
+            _ => self.allowed_buffers.insert(key, len),
+        };
+    }
+}
+
 // Per-driver data stored in KernelData.
 pub struct DriverData {
     pub driver: std::rc::Rc<dyn crate::fake::SyscallDriver>,
@@ -11,8 +11,12 @@ mod exit_test;
 mod expected_syscall;
 pub mod fake;
 mod kernel_data;
+mod scheduler;
 mod share_data;
+pub mod streaming_slice;
 mod syscall_log;
+mod syscall_sequence;
+mod trace;
 pub mod upcall;
 
 pub use allow_db::{RoAllowBuffer, RwAllowBuffer};
@@ -20,8 +24,14 @@ pub use driver_info::DriverInfo;
 #[cfg(not(miri))]
 pub use exit_test::{exit_test, ExitCall};
 pub use expected_syscall::ExpectedSyscall;
+pub use scheduler::{PollLog, Probe, TestScheduler};
 pub use share_data::DriverShareRef;
 pub use syscall_log::SyscallLogEntry;
+pub use syscall_sequence::{expect, SyscallOverride, SyscallSequence};
+pub use trace::{ParseTraceError, Trace, TraceEvent, UPDATE_TRACES_VAR};
+pub use upcall::PendingUpcall;
 
 #[cfg(test)]
 mod allow_db_test;
+#[cfg(test)]
+mod streaming_slice_test;
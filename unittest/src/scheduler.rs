@@ -0,0 +1,285 @@
+//! A step-by-step driver for `libtock_future` futures.
+//!
+//! `libtock_future::block_on` runs a future to completion, polling it again
+//! after every upcall. That makes it hard to test code built from several
+//! futures (such as combinators), as a test can only observe the final
+//! result. `TestScheduler` performs the same steps as `block_on`, but one at a
+//! time under the test's control: the test queues an upcall (e.g. with
+//! `fake::Kernel::schedule_upcall`), calls `step` to run it and poll the
+//! future, and then checks which futures were polled.
+//!
+//! Polls are observed by wrapping futures in `Probe`s created from a shared
+//! `PollLog`:
+//!
+//! ```
+//! use core::cell::Cell;
+//! use core::task::Poll;
+//! use libtock_future::wait_for_upcall;
+//! use libtock_unittest::{fake, PollLog, TestScheduler};
+//!
+//! let _kernel = fake::Kernel::new();
+//! let log = PollLog::default();
+//! let called: Cell<Option<(u32,)>> = Cell::new(None);
+//! let mut scheduler = TestScheduler::new(log.probe("upcall", wait_for_upcall(&called)));
+//! assert_eq!(scheduler.poll(), Poll::Pending);
+//! log.assert_polled(&["upcall"]);
+//! called.set(Some((3,)));
+//! assert_eq!(scheduler.poll(), Poll::Ready((3,)));
+//! ```
+
+use crate::fake;
+use core::task::Poll;
+use libtock_future::TockFuture;
+use libtock_platform::{Syscalls, YieldNoWaitReturn};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Drives a future against the fake kernel one step at a time.
+pub struct TestScheduler<F: TockFuture<fake::Syscalls>> {
+    future: F,
+    complete: bool,
+}
+
+impl<F: TockFuture<fake::Syscalls>> TestScheduler<F> {
+    pub fn new(future: F) -> Self {
+        TestScheduler {
+            future,
+            complete: false,
+        }
+    }
+
+    /// Polls the future once, without running any upcalls. Panics if the
+    /// future has already completed.
+    #[track_caller]
+    pub fn poll(&mut self) -> Poll<F::Output> {
+        assert!(!self.complete, "Future polled after it completed");
+        let poll = self.future.poll();
+        self.complete = poll.is_ready();
+        poll
+    }
+
+    /// Runs the next queued upcall, if there is one, without polling the
+    /// future. Returns whether an upcall ran.
+    pub fn run_upcall(&mut self) -> bool {
+        fake::Syscalls::yield_no_wait() == YieldNoWaitReturn::Upcall
+    }
+
+    /// Runs the next queued upcall and then polls the future, as `block_on`
+    /// does after each upcall. Panics if no upcall is queued, as `block_on`
+    /// would wait forever.
+    #[track_caller]
+    pub fn step(&mut self) -> Poll<F::Output> {
+        assert!(self.run_upcall(), "step() called with no queued upcall");
+        self.poll()
+    }
+
+    /// Returns true if the future has completed.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+/// A record of the polls of `Probe`s, in order.
+#[derive(Clone, Default)]
+pub struct PollLog {
+    polls: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl PollLog {
+    /// Wraps `future` in a `Probe` that records `label` in this log each time
+    /// it is polled.
+    pub fn probe<F>(&self, label: &'static str, future: F) -> Probe<F> {
+        Probe {
+            label,
+            log: self.clone(),
+            future,
+        }
+    }
+
+    /// Returns the labels of the probes polled since the last call, in poll
+    /// order, and clears the log.
+    pub fn take(&self) -> Vec<&'static str> {
+        self.polls.take()
+    }
+
+    /// Panics unless exactly the probes labelled `expected` were polled, in
+    /// that order, since the log was last cleared. Clears the log.
+    #[track_caller]
+    pub fn assert_polled(&self, expected: &[&'static str]) {
+        assert_eq!(self.take(), expected, "Unexpected polls");
+    }
+}
+
+/// A future that records its polls in a `PollLog`. Created by
+/// `PollLog::probe`.
+pub struct Probe<F> {
+    label: &'static str,
+    log: PollLog,
+    future: F,
+}
+
+impl<S: Syscalls, F: TockFuture<S>> TockFuture<S> for Probe<F> {
+    type Output = F::Output;
+
+    fn poll(&mut self) -> Poll<F::Output> {
+        self.log.polls.borrow_mut().push(self.label);
+        self.future.poll()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DriverInfo, DriverShareRef};
+    use core::cell::Cell;
+    use libtock_future::wait_for_upcall;
+    use libtock_platform::{share, DefaultConfig, Subscribe};
+
+    const DRIVER_NUM: u32 = 0x5000;
+
+    struct TwoUpcalls;
+
+    impl fake::SyscallDriver for TwoUpcalls {
+        fn info(&self) -> DriverInfo {
+            DriverInfo::new(DRIVER_NUM).upcall_count(2)
+        }
+
+        fn register(&self, _: DriverShareRef) {}
+
+        fn command(&self, _: u32, _: u32, _: u32) -> libtock_platform::CommandReturn {
+            crate::command_return::success()
+        }
+    }
+
+    // Minimal combinators of the kind TestScheduler is meant to test.
+
+    // Completes once both futures have, polling only the unfinished ones.
+    struct Join<A: TockFuture<fake::Syscalls>, B: TockFuture<fake::Syscalls>> {
+        a: A,
+        a_output: Option<A::Output>,
+        b: B,
+        b_output: Option<B::Output>,
+    }
+
+    impl<A: TockFuture<fake::Syscalls>, B: TockFuture<fake::Syscalls>> TockFuture<fake::Syscalls>
+        for Join<A, B>
+    {
+        type Output = (A::Output, B::Output);
+
+        fn poll(&mut self) -> Poll<Self::Output> {
+            if self.a_output.is_none() {
+                if let Poll::Ready(output) = self.a.poll() {
+                    self.a_output = Some(output);
+                }
+            }
+            if self.b_output.is_none() {
+                if let Poll::Ready(output) = self.b.poll() {
+                    self.b_output = Some(output);
+                }
+            }
+            match (self.a_output.take(), self.b_output.take()) {
+                (Some(a), Some(b)) => Poll::Ready((a, b)),
+                (a, b) => {
+                    self.a_output = a;
+                    self.b_output = b;
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    // Completes with the first future to complete, always polling `a` first.
+    struct BiasedSelect<A, B>(A, B);
+
+    impl<
+            T,
+            A: TockFuture<fake::Syscalls, Output = T>,
+            B: TockFuture<fake::Syscalls, Output = T>,
+        > TockFuture<fake::Syscalls> for BiasedSelect<A, B>
+    {
+        type Output = T;
+
+        fn poll(&mut self) -> Poll<T> {
+            match self.0.poll() {
+                Poll::Ready(output) => Poll::Ready(output),
+                Poll::Pending => self.1.poll(),
+            }
+        }
+    }
+
+    #[test]
+    fn join_completion_order() {
+        let kernel = fake::Kernel::new();
+        kernel.add_driver(&std::rc::Rc::new(TwoUpcalls));
+        let log = PollLog::default();
+        let a: Cell<Option<(u32,)>> = Cell::new(None);
+        let b: Cell<Option<(u32,)>> = Cell::new(None);
+        share::scope::<(Subscribe<_, DRIVER_NUM, 0>, Subscribe<_, DRIVER_NUM, 1>), _, _>(
+            |handle| {
+                let (subscribe_a, subscribe_b) = handle.split();
+                fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 0>(subscribe_a, &a)
+                    .unwrap();
+                fake::Syscalls::subscribe::<_, _, DefaultConfig, DRIVER_NUM, 1>(subscribe_b, &b)
+                    .unwrap();
+
+                let mut scheduler = TestScheduler::new(Join {
+                    a: log.probe("a", wait_for_upcall(&a)),
+                    a_output: None,
+                    b: log.probe("b", wait_for_upcall(&b)),
+                    b_output: None,
+                });
+                assert_eq!(scheduler.poll(), Poll::Pending);
+                log.assert_polled(&["a", "b"]);
+
+                // Once b completes, only a is polled.
+                kernel.schedule_upcall(DRIVER_NUM, 1, (2, 0, 0));
+                assert_eq!(scheduler.step(), Poll::Pending);
+                log.assert_polled(&["a", "b"]);
+                assert!(!scheduler.run_upcall());
+
+                kernel.schedule_upcall(DRIVER_NUM, 0, (1, 0, 0));
+                assert_eq!(scheduler.step(), Poll::Ready(((1,), (2,))));
+                log.assert_polled(&["a"]);
+                assert!(scheduler.is_complete());
+            },
+        );
+    }
+
+    #[test]
+    fn select_fairness() {
+        let _kernel = fake::Kernel::new();
+        let log = PollLog::default();
+        let a: Cell<Option<(u32,)>> = Cell::new(None);
+        let b: Cell<Option<(u32,)>> = Cell::new(None);
+        let mut scheduler = TestScheduler::new(BiasedSelect(
+            log.probe("a", wait_for_upcall(&a)),
+            log.probe("b", wait_for_upcall(&b)),
+        ));
+        assert_eq!(scheduler.poll(), Poll::Pending);
+        log.assert_polled(&["a", "b"]);
+
+        // When both are ready, the biased select always picks a, and never
+        // polls b: the unfairness is visible in the log.
+        a.set(Some((1,)));
+        b.set(Some((2,)));
+        assert_eq!(scheduler.poll(), Poll::Ready((1,)));
+        log.assert_polled(&["a"]);
+    }
+
+    #[test]
+    #[should_panic = "step() called with no queued upcall"]
+    fn step_without_upcall() {
+        let _kernel = fake::Kernel::new();
+        let called: Cell<Option<(u32,)>> = Cell::new(None);
+        let _ = TestScheduler::new(wait_for_upcall(&called)).step();
+    }
+
+    #[test]
+    #[should_panic = "Future polled after it completed"]
+    fn poll_after_completion() {
+        let called: Cell<Option<(u32,)>> = Cell::new(Some((1,)));
+        let mut scheduler = TestScheduler::new(wait_for_upcall(&called));
+        assert_eq!(scheduler.poll(), Poll::Ready((1,)));
+        let _ = scheduler.poll();
+    }
+}
@@ -0,0 +1,108 @@
+//! Kernel-side implementation of the streaming process slice convention, for
+//! use by fake drivers.
+//!
+//! A streaming process slice is a Read-Write Allow buffer that the kernel
+//! appends chunks of data to, without an upcall per chunk. It starts with an
+//! 8-byte header, in native byte order:
+//!
+//! * bytes 0-1: the version, which must be 0.
+//! * bytes 2-3: flags. Bit 0 (exceeded) is set by the kernel when a chunk did
+//!   not fit. Bit 1 (halt) is set by the process to stop the kernel from
+//!   appending.
+//! * bytes 4-7: the write offset: the number of data bytes appended so far.
+//!
+//! The data follows the header. The process consumes the data by swapping in
+//! a fresh buffer (or zeroing the header of the old one) and reading the
+//! first `write_offset` data bytes.
+
+use libtock_platform::ErrorCode;
+
+/// The length of the header, in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// The flag the kernel sets when a chunk did not fit in the buffer.
+pub const FLAG_EXCEEDED: u16 = 1 << 0;
+
+/// The flag the process sets to stop the kernel from appending.
+pub const FLAG_HALT: u16 = 1 << 1;
+
+/// A decoded streaming process slice header.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Header {
+    pub version: u16,
+    pub flags: u16,
+    pub write_offset: u32,
+}
+
+impl Header {
+    /// Decodes the header at the start of `buffer`. Returns `None` if `buffer`
+    /// is shorter than the header.
+    pub fn read(buffer: &[u8]) -> Option<Header> {
+        let header = buffer.get(..HEADER_LEN)?;
+        Some(Header {
+            version: u16::from_ne_bytes([header[0], header[1]]),
+            flags: u16::from_ne_bytes([header[2], header[3]]),
+            write_offset: u32::from_ne_bytes([header[4], header[5], header[6], header[7]]),
+        })
+    }
+
+    /// Encodes this header at the start of `buffer`, which must be at least
+    /// `HEADER_LEN` bytes long.
+    pub fn write(&self, buffer: &mut [u8]) {
+        buffer[0..2].copy_from_slice(&self.version.to_ne_bytes());
+        buffer[2..4].copy_from_slice(&self.flags.to_ne_bytes());
+        buffer[4..8].copy_from_slice(&self.write_offset.to_ne_bytes());
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.flags & FLAG_EXCEEDED != 0
+    }
+
+    pub fn halted(&self) -> bool {
+        self.flags & FLAG_HALT != 0
+    }
+}
+
+/// Appends `chunk` to the streaming process slice in `buffer`, as the kernel
+/// does. Returns `Ok(true)` if the slice was empty before the chunk was
+/// appended (drivers typically schedule an upcall only then).
+///
+/// Fails without modifying the data if:
+/// * `buffer` is shorter than the header (`Size`),
+/// * the header's version is not 0 (`Invalid`),
+/// * the process set the halt flag (`Busy`),
+/// * the chunk does not fit (`Size`). The exceeded flag is set.
+pub fn append(buffer: &mut [u8], chunk: &[u8]) -> Result<bool, ErrorCode> {
+    let mut header = Header::read(buffer).ok_or(ErrorCode::Size)?;
+    if header.version != 0 {
+        return Err(ErrorCode::Invalid);
+    }
+    if header.halted() {
+        return Err(ErrorCode::Busy);
+    }
+    let start = HEADER_LEN + header.write_offset as usize;
+    let Some(dest) = buffer.get_mut(start..start + chunk.len()) else {
+        header.flags |= FLAG_EXCEEDED;
+        header.write(buffer);
+        return Err(ErrorCode::Size);
+    };
+    dest.copy_from_slice(chunk);
+    let was_empty = header.write_offset == 0;
+    header.write_offset += chunk.len() as u32;
+    header.write(buffer);
+    Ok(was_empty)
+}
+
+/// Returns the data appended to the streaming process slice in `buffer`.
+/// Returns `None` if `buffer` is shorter than the header, or the write offset
+/// lies past the end of `buffer`.
+pub fn contents(buffer: &[u8]) -> Option<&[u8]> {
+    let header = Header::read(buffer)?;
+    buffer.get(HEADER_LEN..HEADER_LEN + header.write_offset as usize)
+}
+
+/// Zeroes the header of `buffer`, as a process does before handing it to the
+/// kernel.
+pub fn reset(buffer: &mut [u8]) {
+    Header::default().write(buffer);
+}
@@ -0,0 +1,133 @@
+//! Unit test cases for functionality in streaming_slice.
+
+use crate::streaming_slice::*;
+use crate::{fake, DriverInfo, RwAllowBuffer};
+use core::cell::RefCell;
+use libtock_platform::{share, CommandReturn, DefaultConfig, ErrorCode, Syscalls};
+
+#[test]
+fn header() {
+    let header = Header {
+        version: 0,
+        flags: FLAG_EXCEEDED | FLAG_HALT,
+        write_offset: 0x01020304,
+    };
+    let mut buffer = [0xff; 10];
+    header.write(&mut buffer);
+    let mut expected = [0; 10];
+    expected[2..4].copy_from_slice(&3u16.to_ne_bytes());
+    expected[4..8].copy_from_slice(&0x01020304u32.to_ne_bytes());
+    expected[8..].copy_from_slice(&[0xff; 2]);
+    assert_eq!(buffer, expected);
+    assert_eq!(Header::read(&buffer), Some(header));
+    assert!(header.exceeded() && header.halted());
+    assert_eq!(Header::read(&buffer[..7]), None);
+
+    reset(&mut buffer);
+    assert_eq!(Header::read(&buffer), Some(Header::default()));
+}
+
+#[test]
+fn append_and_contents() {
+    let mut buffer = [0; HEADER_LEN + 5];
+    assert_eq!(contents(&buffer), Some(&[][..]));
+    assert_eq!(append(&mut buffer, b"ab"), Ok(true));
+    assert_eq!(append(&mut buffer, b"cd"), Ok(false));
+    assert_eq!(contents(&buffer), Some(&b"abcd"[..]));
+
+    // A chunk that does not fit is dropped whole, and sets the exceeded flag.
+    assert_eq!(append(&mut buffer, b"ef"), Err(ErrorCode::Size));
+    let header = Header::read(&buffer).unwrap();
+    assert!(header.exceeded());
+    assert_eq!(header.write_offset, 4);
+    assert_eq!(append(&mut buffer, b"e"), Ok(false));
+    assert_eq!(contents(&buffer), Some(&b"abcde"[..]));
+
+    reset(&mut buffer);
+    assert_eq!(append(&mut buffer, b"xyz"), Ok(true));
+    assert_eq!(contents(&buffer), Some(&b"xyz"[..]));
+}
+
+#[test]
+fn append_errors() {
+    assert_eq!(append(&mut [0; 7], b""), Err(ErrorCode::Size));
+
+    let mut buffer = [0; 12];
+    Header {
+        version: 1,
+        ..Default::default()
+    }
+    .write(&mut buffer);
+    assert_eq!(append(&mut buffer, b"a"), Err(ErrorCode::Invalid));
+
+    Header {
+        flags: FLAG_HALT,
+        ..Default::default()
+    }
+    .write(&mut buffer);
+    assert_eq!(append(&mut buffer, b"a"), Err(ErrorCode::Busy));
+    assert_eq!(contents(&buffer), Some(&[][..]));
+
+    // A write offset past the end of the buffer (e.g. set by a buggy process).
+    Header {
+        write_offset: 5,
+        ..Default::default()
+    }
+    .write(&mut buffer);
+    assert_eq!(contents(&buffer), None);
+    assert_eq!(append(&mut buffer, b""), Err(ErrorCode::Size));
+}
+
+// A driver that appends the command's argument0 (as a byte) to a streaming
+// process slice shared via Read-Write Allow 0.
+#[derive(Default)]
+struct StreamDriver {
+    buffer: RefCell<RwAllowBuffer>,
+}
+
+impl fake::SyscallDriver for StreamDriver {
+    fn info(&self) -> DriverInfo {
+        DriverInfo::new(DRIVER_NUM)
+    }
+
+    fn allow_readwrite(
+        &self,
+        buffer_num: u32,
+        buffer: RwAllowBuffer,
+    ) -> Result<RwAllowBuffer, (RwAllowBuffer, ErrorCode)> {
+        match buffer_num {
+            0 => Ok(self.buffer.replace(buffer)),
+            _ => Err((buffer, ErrorCode::NoSupport)),
+        }
+    }
+
+    fn command(&self, _: u32, argument0: u32, _: u32) -> CommandReturn {
+        match append(&mut self.buffer.borrow_mut(), &[argument0 as u8]) {
+            Ok(_) => crate::command_return::success(),
+            Err(error) => crate::command_return::failure(error),
+        }
+    }
+}
+
+const DRIVER_NUM: u32 = 0x9000;
+
+// Verifies that the streaming slice layout seen by the process matches the
+// kernel's, when the buffer is shared through fake::Kernel.
+#[test]
+fn kernel_integration() {
+    let kernel = fake::Kernel::new();
+    kernel.add_driver(&std::rc::Rc::new(StreamDriver::default()));
+
+    let mut buffer = [0; HEADER_LEN + 2];
+    share::scope(|allow_rw| {
+        fake::Syscalls::allow_rw::<DefaultConfig, DRIVER_NUM, 0>(allow_rw, &mut buffer).unwrap();
+        for byte in [7, 8, 9] {
+            let _ = fake::Syscalls::command(DRIVER_NUM, 0, byte, 0);
+        }
+    });
+    let mut expected = [0; HEADER_LEN + 2];
+    expected[2..4].copy_from_slice(&FLAG_EXCEEDED.to_ne_bytes());
+    expected[4..8].copy_from_slice(&2u32.to_ne_bytes());
+    expected[8..].copy_from_slice(&[7, 8]);
+    assert_eq!(buffer, expected);
+}
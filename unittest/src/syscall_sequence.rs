@@ -0,0 +1,295 @@
+//! A builder for sequences of `ExpectedSyscall`s.
+//!
+//! Writing out each `ExpectedSyscall` as a struct literal is verbose, and it is
+//! easy to get a field wrong (for example, passing an override to the wrong
+//! system call) without the test noticing. `expect()` starts a
+//! `SyscallSequence`, which is built up one system call at a time:
+//!
+//! ```
+//! use libtock_platform::ErrorCode;
+//! use libtock_unittest::{expect, fake};
+//!
+//! let kernel = fake::Kernel::new();
+//! kernel.add_expected_syscalls(
+//!     expect()
+//!         .allow_ro(1, 0)
+//!         .subscribe(1, 0)
+//!         .command(1, 2, 0, 0)
+//!         .returning(ErrorCode::Busy),
+//! );
+//! ```
+//!
+//! `returning` applies to the most recently added system call, and panics if
+//! the override does not make sense for it, so mistakes in the sequence are
+//! reported when the test builds it.
+
+use crate::ExpectedSyscall;
+use libtock_platform::{CommandReturn, ErrorCode, Register, YieldNoWaitReturn};
+
+/// Starts an empty `SyscallSequence`.
+pub fn expect() -> SyscallSequence {
+    SyscallSequence {
+        syscalls: Vec::new(),
+    }
+}
+
+/// An ordered list of `ExpectedSyscall`s, created with `expect()` and added to
+/// a kernel with `fake::Kernel::add_expected_syscalls`.
+#[derive(Clone, Debug)]
+#[must_use = "a SyscallSequence does nothing until it is added to a fake::Kernel"]
+pub struct SyscallSequence {
+    syscalls: Vec<ExpectedSyscall>,
+}
+
+impl SyscallSequence {
+    /// Expects a `yield-no-wait` call.
+    pub fn yield_no_wait(self) -> Self {
+        self.then(ExpectedSyscall::YieldNoWait {
+            override_return: None,
+        })
+    }
+
+    /// Expects a `yield-wait` call.
+    pub fn yield_wait(self) -> Self {
+        self.then(ExpectedSyscall::YieldWait { skip_upcall: false })
+    }
+
+    /// Expects a Subscribe call.
+    pub fn subscribe(self, driver_num: u32, subscribe_num: u32) -> Self {
+        self.then(ExpectedSyscall::Subscribe {
+            driver_num,
+            subscribe_num,
+            skip_with_error: None,
+        })
+    }
+
+    /// Expects a Command call with the given arguments.
+    pub fn command(self, driver_id: u32, command_id: u32, argument0: u32, argument1: u32) -> Self {
+        self.then(ExpectedSyscall::Command {
+            driver_id,
+            command_id,
+            argument0,
+            argument1,
+            override_return: None,
+        })
+    }
+
+    /// Expects a Read-Only Allow call.
+    pub fn allow_ro(self, driver_num: u32, buffer_num: u32) -> Self {
+        self.then(ExpectedSyscall::AllowRo {
+            driver_num,
+            buffer_num,
+            return_error: None,
+        })
+    }
+
+    /// Expects a Read-Write Allow call.
+    pub fn allow_rw(self, driver_num: u32, buffer_num: u32) -> Self {
+        self.then(ExpectedSyscall::AllowRw {
+            driver_num,
+            buffer_num,
+            return_error: None,
+        })
+    }
+
+    /// Expects a Memop call.
+    pub fn memop<A: Into<Register>>(self, memop_num: u32, argument0: A) -> Self {
+        self.then(ExpectedSyscall::Memop {
+            memop_num,
+            argument0: argument0.into(),
+            return_error: None,
+        })
+    }
+
+    /// Appends an arbitrary `ExpectedSyscall`.
+    pub fn then(mut self, syscall: ExpectedSyscall) -> Self {
+        self.syscalls.push(syscall);
+        self
+    }
+
+    /// Overrides the result of the most recently added system call. Panics if
+    /// the sequence is empty or the override does not apply to that system
+    /// call (see `SyscallOverride`).
+    #[track_caller]
+    pub fn returning<O: SyscallOverride>(mut self, value: O) -> Self {
+        let last = self
+            .syscalls
+            .last_mut()
+            .expect("returning() called on an empty SyscallSequence");
+        value.apply(last);
+        self
+    }
+
+    /// Makes the most recently added `yield-wait` return without running an
+    /// upcall. Panics if the most recently added system call is not
+    /// `yield-wait`.
+    #[track_caller]
+    pub fn skip_upcall(mut self) -> Self {
+        match self.syscalls.last_mut() {
+            Some(ExpectedSyscall::YieldWait { skip_upcall }) => *skip_upcall = true,
+            last => panic!("skip_upcall() applies to yield_wait(), not {:?}", last),
+        }
+        self
+    }
+}
+
+impl IntoIterator for SyscallSequence {
+    type Item = ExpectedSyscall;
+    type IntoIter = std::vec::IntoIter<ExpectedSyscall>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.syscalls.into_iter()
+    }
+}
+
+/// A value that can be passed to `SyscallSequence::returning`.
+///
+/// * `ErrorCode` fails any system call other than Yield.
+/// * `CommandReturn` replaces the return value of a Command.
+/// * `YieldNoWaitReturn` replaces the return value of a `yield-no-wait`.
+pub trait SyscallOverride {
+    /// Sets the override on `syscall`, panicking if it does not apply.
+    #[track_caller]
+    fn apply(self, syscall: &mut ExpectedSyscall);
+}
+
+impl SyscallOverride for ErrorCode {
+    #[track_caller]
+    fn apply(self, syscall: &mut ExpectedSyscall) {
+        match syscall {
+            ExpectedSyscall::Subscribe {
+                skip_with_error, ..
+            } => *skip_with_error = Some(self),
+            ExpectedSyscall::Command {
+                override_return, ..
+            } => *override_return = Some(crate::command_return::failure(self)),
+            ExpectedSyscall::AllowRo { return_error, .. }
+            | ExpectedSyscall::AllowRw { return_error, .. }
+            | ExpectedSyscall::Memop { return_error, .. } => *return_error = Some(self),
+            _ => panic!("Cannot return {:?} from {:?}", self, syscall),
+        }
+    }
+}
+
+impl SyscallOverride for CommandReturn {
+    #[track_caller]
+    fn apply(self, syscall: &mut ExpectedSyscall) {
+        match syscall {
+            ExpectedSyscall::Command {
+                override_return, ..
+            } => *override_return = Some(self),
+            _ => panic!("Cannot return {:?} from {:?}", self, syscall),
+        }
+    }
+}
+
+impl SyscallOverride for YieldNoWaitReturn {
+    #[track_caller]
+    fn apply(self, syscall: &mut ExpectedSyscall) {
+        match syscall {
+            ExpectedSyscall::YieldNoWait { override_return } => *override_return = Some(self),
+            _ => panic!("Cannot return {:?} from {:?}", self, syscall),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::matches;
+
+    #[test]
+    fn build() {
+        let syscalls: Vec<_> = expect()
+            .yield_no_wait()
+            .returning(YieldNoWaitReturn::Upcall)
+            .yield_wait()
+            .skip_upcall()
+            .subscribe(1, 2)
+            .returning(ErrorCode::Busy)
+            .command(1, 3, 4, 5)
+            .returning(crate::command_return::success_u32(6))
+            .command(1, 7, 0, 0)
+            .returning(ErrorCode::Fail)
+            .allow_ro(1, 0)
+            .allow_rw(1, 1)
+            .returning(ErrorCode::NoMem)
+            .memop(1, 0u32)
+            .into_iter()
+            .collect();
+        assert_eq!(syscalls.len(), 8);
+        assert!(matches!(
+            syscalls[0],
+            ExpectedSyscall::YieldNoWait {
+                override_return: Some(YieldNoWaitReturn::Upcall)
+            }
+        ));
+        assert!(matches!(
+            syscalls[1],
+            ExpectedSyscall::YieldWait { skip_upcall: true }
+        ));
+        assert!(matches!(
+            syscalls[2],
+            ExpectedSyscall::Subscribe {
+                driver_num: 1,
+                subscribe_num: 2,
+                skip_with_error: Some(ErrorCode::Busy)
+            }
+        ));
+        match syscalls[3] {
+            ExpectedSyscall::Command {
+                driver_id: 1,
+                command_id: 3,
+                argument0: 4,
+                argument1: 5,
+                override_return: Some(command_return),
+            } => assert_eq!(command_return.get_success_u32(), Some(6)),
+            _ => panic!("Unexpected {:?}", syscalls[3]),
+        }
+        match syscalls[4] {
+            ExpectedSyscall::Command {
+                override_return: Some(command_return),
+                ..
+            } => assert_eq!(command_return.get_failure(), Some(ErrorCode::Fail)),
+            _ => panic!("Unexpected {:?}", syscalls[4]),
+        }
+        assert!(matches!(
+            syscalls[5],
+            ExpectedSyscall::AllowRo {
+                driver_num: 1,
+                buffer_num: 0,
+                return_error: None
+            }
+        ));
+        assert!(matches!(
+            syscalls[6],
+            ExpectedSyscall::AllowRw {
+                driver_num: 1,
+                buffer_num: 1,
+                return_error: Some(ErrorCode::NoMem)
+            }
+        ));
+        assert!(matches!(
+            syscalls[7],
+            ExpectedSyscall::Memop {
+                memop_num: 1,
+                return_error: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[should_panic = "Cannot return"]
+    fn mismatched_override() {
+        let _ = expect()
+            .allow_ro(1, 0)
+            .returning(YieldNoWaitReturn::NoUpcall);
+    }
+
+    #[test]
+    #[should_panic = "skip_upcall() applies to yield_wait()"]
+    fn mismatched_skip_upcall() {
+        let _ = expect().yield_no_wait().skip_upcall();
+    }
+}
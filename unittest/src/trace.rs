@@ -0,0 +1,597 @@
+//! Syscall traces: a record of the system calls a test makes, their results,
+//! and the upcalls they run.
+//!
+//! A trace is more detailed than the syscall log (it includes return values
+//! and upcalls) and has a stable text form, one event per line, so it can be
+//! stored in a file and compared against later runs. This enables golden-trace
+//! regression tests of flows whose exact sequence of system calls matters:
+//!
+//! ```no_run
+//! use libtock_unittest::fake;
+//!
+//! let kernel = fake::Kernel::new();
+//! kernel.start_trace();
+//! // ... run the code under test ...
+//! kernel.take_trace().assert_matches_file("traces/receive_frame.trace");
+//! ```
+//!
+//! Setting the `LIBTOCK_UPDATE_TRACES` environment variable makes
+//! `assert_matches_file` (re)write the file instead of comparing against it.
+//! As it accesses the filesystem, it does not work under Miri's default
+//! isolation.
+//! A trace can also be replayed with `fake::Kernel::replay_trace`, which
+//! compares each system call against the trace as it is made, so that a
+//! divergence panics with the offending call on the stack.
+//!
+//! Exit calls are not traced. For Subscribe, Allow, and Memop, only whether the
+//! call succeeded is recorded, as the other return values are addresses, which
+//! differ between runs.
+
+use crate::kernel_data::with_kernel_data;
+use libtock_platform::{return_variant, ErrorCode, Register};
+use std::fmt;
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+
+/// The environment variable that makes `Trace::assert_matches_file` write the
+/// trace to the file rather than comparing against it.
+pub const UPDATE_TRACES_VAR: &str = "LIBTOCK_UPDATE_TRACES";
+
+/// A single event in a `Trace`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TraceEvent {
+    YieldNoWait {
+        upcall_ran: bool,
+    },
+
+    YieldWait,
+
+    /// An upcall invoked by a Yield call.
+    Upcall {
+        driver_num: u32,
+        subscribe_num: u32,
+        args: (u32, u32, u32),
+    },
+
+    Subscribe {
+        driver_num: u32,
+        subscribe_num: u32,
+        /// True if the null upcall was passed (i.e. this unsubscribed).
+        null_upcall: bool,
+        result: Result<(), ErrorCode>,
+    },
+
+    Command {
+        driver_num: u32,
+        command_id: u32,
+        argument0: u32,
+        argument1: u32,
+        /// The registers returned by the call.
+        return_registers: [u32; 4],
+    },
+
+    AllowRo {
+        driver_num: u32,
+        buffer_num: u32,
+        len: usize,
+        result: Result<(), ErrorCode>,
+    },
+
+    AllowRw {
+        driver_num: u32,
+        buffer_num: u32,
+        len: usize,
+        result: Result<(), ErrorCode>,
+    },
+
+    Memop {
+        memop_num: u32,
+        result: Result<(), ErrorCode>,
+    },
+}
+
+/// A sequence of `TraceEvent`s, in the order the system calls were made (and
+/// the upcalls invoked). Recorded with `fake::Kernel::start_trace` and
+/// `fake::Kernel::take_trace`.
+///
+/// `Display` and `FromStr` convert a trace to and from its text form. Parsing
+/// ignores blank lines and lines starting with `#`, so trace files may be
+/// annotated.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trace {
+    pub events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// Panics if this trace differs from `expected`, reporting the first event
+    /// that differs.
+    #[track_caller]
+    pub fn assert_matches(&self, expected: &Trace) {
+        if let Some(message) = first_difference(&expected.events, &self.events) {
+            panic!("Trace does not match the expected trace: {}", message);
+        }
+    }
+
+    /// Panics if this trace differs from the trace stored in the file at
+    /// `path`. If `LIBTOCK_UPDATE_TRACES` is set, writes this trace to the file
+    /// instead.
+    #[track_caller]
+    pub fn assert_matches_file<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        if std::env::var_os(UPDATE_TRACES_VAR).is_some() {
+            if let Err(error) = std::fs::write(path, self.to_string()) {
+                panic!("Unable to write trace {}: {}", path.display(), error);
+            }
+            return;
+        }
+        let expected: Trace = match std::fs::read_to_string(path) {
+            Err(error) => panic!(
+                "Unable to read trace {}: {}. Set {} to create it.",
+                path.display(),
+                error,
+                UPDATE_TRACES_VAR
+            ),
+            Ok(text) => text
+                .parse()
+                .unwrap_or_else(|error| panic!("Invalid trace {}: {}", path.display(), error)),
+        };
+        if let Some(message) = first_difference(&expected.events, &self.events) {
+            panic!(
+                "Trace does not match {}: {}. Set {} to update it.",
+                path.display(),
+                message,
+                UPDATE_TRACES_VAR
+            );
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Trace {
+    type Err = ParseTraceError;
+
+    fn from_str(text: &str) -> Result<Trace, ParseTraceError> {
+        let mut events = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            events.push(line.parse().map_err(|message| ParseTraceError {
+                line: index + 1,
+                message,
+            })?);
+        }
+        Ok(Trace { events })
+    }
+}
+
+/// Returned when parsing a `Trace` from text fails.
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct ParseTraceError {
+    /// The (1-based) line number of the invalid line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use TraceEvent::*;
+        match *self {
+            YieldNoWait { upcall_ran } => write!(
+                f,
+                "yield-no-wait -> {}",
+                if upcall_ran { "upcall" } else { "no-upcall" }
+            ),
+            YieldWait => write!(f, "yield-wait"),
+            Upcall {
+                driver_num,
+                subscribe_num,
+                args,
+            } => write!(
+                f,
+                "upcall {:#x} {} {} {} {}",
+                driver_num, subscribe_num, args.0, args.1, args.2
+            ),
+            Subscribe {
+                driver_num,
+                subscribe_num,
+                null_upcall,
+                result,
+            } => write!(
+                f,
+                "{} {:#x} {} -> {}",
+                if null_upcall {
+                    "unsubscribe"
+                } else {
+                    "subscribe"
+                },
+                driver_num,
+                subscribe_num,
+                DisplayResult(result)
+            ),
+            Command {
+                driver_num,
+                command_id,
+                argument0,
+                argument1,
+                return_registers: [r0, r1, r2, r3],
+            } => write!(
+                f,
+                "command {:#x} {} {} {} -> {} {} {} {}",
+                driver_num, command_id, argument0, argument1, r0, r1, r2, r3
+            ),
+            AllowRo {
+                driver_num,
+                buffer_num,
+                len,
+                result,
+            } => write!(
+                f,
+                "allow-ro {:#x} {} {} -> {}",
+                driver_num,
+                buffer_num,
+                len,
+                DisplayResult(result)
+            ),
+            AllowRw {
+                driver_num,
+                buffer_num,
+                len,
+                result,
+            } => write!(
+                f,
+                "allow-rw {:#x} {} {} -> {}",
+                driver_num,
+                buffer_num,
+                len,
+                DisplayResult(result)
+            ),
+            Memop { memop_num, result } => {
+                write!(f, "memop {} -> {}", memop_num, DisplayResult(result))
+            }
+        }
+    }
+}
+
+impl FromStr for TraceEvent {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<TraceEvent, String> {
+        let (call, ret) = match line.split_once("->") {
+            None => (line, None),
+            Some((call, ret)) => (call, Some(ret.trim())),
+        };
+        let ret = || ret.ok_or_else(|| "missing return value".to_string());
+        let mut words = call.split_whitespace();
+        let name = words.next().ok_or("empty event")?;
+        let event = match name {
+            "yield-no-wait" => TraceEvent::YieldNoWait {
+                upcall_ran: match ret()? {
+                    "upcall" => true,
+                    "no-upcall" => false,
+                    other => return Err(format!("invalid yield-no-wait return `{}`", other)),
+                },
+            },
+            "yield-wait" => TraceEvent::YieldWait,
+            "upcall" => TraceEvent::Upcall {
+                driver_num: number(&mut words)?,
+                subscribe_num: number(&mut words)?,
+                args: (
+                    number(&mut words)?,
+                    number(&mut words)?,
+                    number(&mut words)?,
+                ),
+            },
+            "subscribe" | "unsubscribe" => TraceEvent::Subscribe {
+                driver_num: number(&mut words)?,
+                subscribe_num: number(&mut words)?,
+                null_upcall: name == "unsubscribe",
+                result: parse_result(ret()?)?,
+            },
+            "command" => TraceEvent::Command {
+                driver_num: number(&mut words)?,
+                command_id: number(&mut words)?,
+                argument0: number(&mut words)?,
+                argument1: number(&mut words)?,
+                return_registers: {
+                    let mut registers = ret()?.split_whitespace();
+                    let parsed = [
+                        number(&mut registers)?,
+                        number(&mut registers)?,
+                        number(&mut registers)?,
+                        number(&mut registers)?,
+                    ];
+                    if registers.next().is_some() {
+                        return Err("too many return values".into());
+                    }
+                    parsed
+                },
+            },
+            "allow-ro" => TraceEvent::AllowRo {
+                driver_num: number(&mut words)?,
+                buffer_num: number(&mut words)?,
+                len: number(&mut words)? as usize,
+                result: parse_result(ret()?)?,
+            },
+            "allow-rw" => TraceEvent::AllowRw {
+                driver_num: number(&mut words)?,
+                buffer_num: number(&mut words)?,
+                len: number(&mut words)? as usize,
+                result: parse_result(ret()?)?,
+            },
+            "memop" => TraceEvent::Memop {
+                memop_num: number(&mut words)?,
+                result: parse_result(ret()?)?,
+            },
+            _ => return Err(format!("unknown event `{}`", name)),
+        };
+        if words.next().is_some() {
+            return Err("too many arguments".into());
+        }
+        Ok(event)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Recording
+// -----------------------------------------------------------------------------
+
+// The trace being recorded by a fake::Kernel, stored in KernelData.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    // Events that have not been taken yet, in the order they started. A system
+    // call's slot is None until it returns, as its event includes its result.
+    events: Vec<Option<TraceEvent>>,
+    // The number of events taken by take_trace, which precede `events`.
+    taken: usize,
+    // The trace being replayed, if any.
+    expected: Option<Trace>,
+}
+
+impl Recorder {
+    pub fn replaying(expected: Trace) -> Recorder {
+        Recorder {
+            expected: Some(expected),
+            ..Default::default()
+        }
+    }
+
+    // Removes and returns the events recorded so far. Stops at the first
+    // system call that has not returned yet, as later events are nested in it.
+    pub fn take(&mut self) -> Trace {
+        let complete = self
+            .events
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(self.events.len());
+        self.taken += complete;
+        Trace {
+            events: self.events.drain(..complete).map(Option::unwrap).collect(),
+        }
+    }
+
+    // Returns a description of the missing events if replay did not reach the
+    // end of the expected trace.
+    pub fn unreplayed(&self) -> Option<String> {
+        let expected = &self.expected.as_ref()?.events;
+        let recorded = self.taken + self.events.len();
+        expected.get(recorded).map(|next| {
+            format!(
+                "{} of {} events replayed; next expected event: {}",
+                recorded,
+                expected.len(),
+                next
+            )
+        })
+    }
+
+    fn begin(&mut self) -> usize {
+        self.events.push(None);
+        self.taken + self.events.len() - 1
+    }
+
+    // Fills in the event at `index`. If replaying, returns a description of the
+    // mismatch if the event differs from the expected one.
+    fn complete(&mut self, index: usize, event: TraceEvent) -> Option<String> {
+        if let Some(slot) = index
+            .checked_sub(self.taken)
+            .and_then(|i| self.events.get_mut(i))
+        {
+            *slot = Some(event);
+        }
+        let expected = &self.expected.as_ref()?.events;
+        match expected.get(index) {
+            Some(expected) if *expected == event => None,
+            expected => Some(format!(
+                "event {}: expected `{}`, got `{}`",
+                index,
+                describe(expected),
+                event
+            )),
+        }
+    }
+}
+
+// Runs the system call `syscall`, recording the event `event` computes from its
+// result if the kernel is recording a trace.
+pub(crate) fn record<R>(syscall: impl FnOnce() -> R, event: impl FnOnce(&R) -> TraceEvent) -> R {
+    let index = with_kernel_data(|kernel_data| {
+        kernel_data
+            .and_then(|kernel_data| kernel_data.trace.as_mut())
+            .map(Recorder::begin)
+    });
+    let result = syscall();
+    if let Some(index) = index {
+        let event = event(&result);
+        let mismatch = with_kernel_data(|kernel_data| {
+            kernel_data
+                .and_then(|kernel_data| kernel_data.trace.as_mut())
+                .and_then(|recorder| recorder.complete(index, event))
+        });
+        // A mismatch is not reported while unwinding (e.g. from a share::scope
+        // cleaning up after an earlier mismatch), as that would abort.
+        if let Some(mismatch) = mismatch.filter(|_| !std::thread::panicking()) {
+            panic!(
+                "System calls diverged from the replayed trace at {}",
+                mismatch
+            );
+        }
+    }
+    result
+}
+
+// Decodes the result of a system call that returns a success or failure
+// variant with an error code in r1.
+pub(crate) fn syscall_result(r0: Register, r1: Register) -> Result<(), ErrorCode> {
+    let variant: u32 = r0.try_into().expect("Too large return variant");
+    match variant < return_variant::SUCCESS.into() {
+        false => Ok(()),
+        true => Err(r1.as_u32().try_into().unwrap_or(ErrorCode::Fail)),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementation details below.
+// -----------------------------------------------------------------------------
+
+struct DisplayResult(Result<(), ErrorCode>);
+
+impl fmt::Display for DisplayResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Ok(()) => write!(f, "ok"),
+            Err(error) => write!(f, "{:?}", error),
+        }
+    }
+}
+
+fn parse_result(text: &str) -> Result<Result<(), ErrorCode>, String> {
+    if text == "ok" {
+        return Ok(Ok(()));
+    }
+    if let Some(code) = text.strip_prefix("code ") {
+        let code = code
+            .parse::<u32>()
+            .map_err(|_| format!("invalid error `{}`", text))?;
+        return ErrorCode::try_from(code)
+            .map(Err)
+            .map_err(|_| format!("invalid error `{}`", text));
+    }
+    (1..=1024u32)
+        .filter_map(|code| ErrorCode::try_from(code).ok())
+        .find(|error| format!("{:?}", error) == text)
+        .map(Err)
+        .ok_or_else(|| format!("invalid result `{}`", text))
+}
+
+// Parses the next word as a decimal or 0x-prefixed hexadecimal number.
+fn number(words: &mut SplitWhitespace) -> Result<u32, String> {
+    let word = words.next().ok_or("missing argument")?;
+    match word.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => word.parse(),
+    }
+    .map_err(|_| format!("invalid number `{}`", word))
+}
+
+fn describe(event: Option<&TraceEvent>) -> String {
+    match event {
+        None => "<end of trace>".into(),
+        Some(event) => event.to_string(),
+    }
+}
+
+fn first_difference(expected: &[TraceEvent], actual: &[TraceEvent]) -> Option<String> {
+    let index =
+        (0..expected.len().max(actual.len())).find(|&i| expected.get(i) != actual.get(i))?;
+    Some(format!(
+        "event {}: expected `{}`, got `{}`",
+        index,
+        describe(expected.get(index)),
+        describe(actual.get(index))
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\
+yield-no-wait -> no-upcall
+subscribe 0x1 2 -> ok
+command 0x1 2 3 4 -> 129 5 0 0
+yield-wait
+upcall 0x1 2 5 0 0
+allow-ro 0x90001 0 3 -> NOSUPPORT
+allow-rw 0x1 1 0 -> ok
+unsubscribe 0x1 2 -> ok
+memop 1 -> code 1000
+";
+
+    #[test]
+    fn round_trip() {
+        let trace: Trace = TEXT.parse().unwrap();
+        assert_eq!(trace.events.len(), 9);
+        assert_eq!(
+            trace.events[2],
+            TraceEvent::Command {
+                driver_num: 1,
+                command_id: 2,
+                argument0: 3,
+                argument1: 4,
+                return_registers: [129, 5, 0, 0],
+            }
+        );
+        assert_eq!(
+            trace.events[5],
+            TraceEvent::AllowRo {
+                driver_num: 0x90001,
+                buffer_num: 0,
+                len: 3,
+                result: Err(ErrorCode::NoSupport),
+            }
+        );
+        assert_eq!(trace.to_string(), TEXT);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |text: &str| text.parse::<Trace>().unwrap_err();
+        assert_eq!(
+            "# A comment\n\nyield-wait\nyield-wait 1\n".parse::<Trace>(),
+            Err(ParseTraceError {
+                line: 4,
+                message: "too many arguments".into()
+            })
+        );
+        assert_eq!(error("subscribe 0x1 2").message, "missing return value");
+        assert_eq!(
+            error("command 0x1 2 3 -> 128 0 0 0").message,
+            "missing argument"
+        );
+        assert_eq!(error("memop 0 -> BUSYY").message, "invalid result `BUSYY`");
+        assert_eq!(error("allow-ro x 0 0 -> ok").message, "invalid number `x`");
+        assert_eq!(error("exit 0").message, "unknown event `exit`");
+    }
+
+    #[test]
+    #[should_panic = "event 1: expected `yield-wait`, got `<end of trace>`"]
+    fn assert_matches() {
+        let trace: Trace = "yield-wait\nyield-wait".parse().unwrap();
+        trace.assert_matches(&trace.clone());
+        Trace {
+            events: vec![TraceEvent::YieldWait],
+        }
+        .assert_matches(&trace);
+    }
+}
@@ -55,3 +55,22 @@ pub(crate) struct UpcallId {
     pub driver_num: u32,
     pub subscribe_num: u32,
 }
+
+/// A snapshot of an upcall waiting in the fake kernel's upcall queue, as
+/// returned by `fake::Kernel::pending_upcalls`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PendingUpcall {
+    pub driver_num: u32,
+    pub subscribe_num: u32,
+    pub args: (u32, u32, u32),
+}
+
+impl From<&UpcallQueueEntry> for PendingUpcall {
+    fn from(entry: &UpcallQueueEntry) -> PendingUpcall {
+        PendingUpcall {
+            driver_num: entry.id.driver_num,
+            subscribe_num: entry.id.subscribe_num,
+            args: entry.args,
+        }
+    }
+}